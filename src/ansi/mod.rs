@@ -0,0 +1,266 @@
+//! ANSI escape sequence handling for command output
+//!
+//! Output lines are stored as the program wrote them. When rendering, SGR
+//! sequences (colors and text attributes) become styled spans; every other
+//! escape sequence is dropped.
+//!
+//! Anything else that a terminal wouldn't print as-is is made visible rather
+//! than passed through, since it could shift the layout or hide text: stray
+//! control characters show in caret notation (`^[`, `^G`), and zero-width or
+//! bidi control characters as `<U+200B>`, both in reverse video the way
+//! `less` shows them. Tabs are expanded to spaces.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+use unicode_width::UnicodeWidthChar;
+
+use crate::shell::paste::invisible_name;
+
+/// Columns between tab stops
+const TAB_WIDTH: usize = 8;
+
+/// Convert a line containing ANSI escape sequences into styled spans
+///
+/// A carriage return in the middle of the line (progress bars) discards what
+/// came before it, approximating how a terminal would overwrite it.
+pub fn parse_line(line: &str) -> Vec<Span<'static>> {
+    let line = line.trim_end_matches('\r');
+    let line = line.rsplit('\r').next().unwrap_or(line);
+
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut text = String::new();
+    let mut column = 0;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\t' {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            text.extend(std::iter::repeat(' ').take(spaces));
+            column += spaces;
+            continue;
+        }
+        if c != '\x1b' {
+            if let Some(marker) = visible_marker(c) {
+                if !text.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut text), style));
+                }
+                column += marker.len();
+                spans.push(Span::styled(marker, style.add_modifier(Modifier::REVERSED)));
+            } else {
+                text.push(c);
+                column += c.width().unwrap_or(0);
+            }
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: parameters, then a final byte in @..~
+            Some('[') => {
+                let mut params = String::new();
+                let mut last = None;
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if last == Some('m') {
+                    if !text.is_empty() {
+                        spans.push(Span::styled(std::mem::take(&mut text), style));
+                    }
+                    style = apply_sgr(style, &params);
+                }
+            }
+            // OSC: terminated by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Other two-character escapes
+            _ => {}
+        }
+    }
+
+    if !text.is_empty() {
+        spans.push(Span::styled(text, style));
+    }
+    spans
+}
+
+/// Printable stand-in for a character that wouldn't display by itself
+///
+/// Joiners and soft hyphens are left alone: emoji sequences and several
+/// scripts rely on them, and they can't hide or reorder text.
+fn visible_marker(c: char) -> Option<String> {
+    match c {
+        '\x00'..='\x1f' => Some(format!("^{}", (c as u8 + b'@') as char)),
+        '\x7f' => Some("^?".to_string()),
+        '\u{200C}' | '\u{200D}' | '\u{00AD}' => None,
+        _ if c.is_control() || invisible_name(c).is_some() => Some(format!("<U+{:04X}>", c as u32)),
+        _ => None,
+    }
+}
+
+/// Apply the parameters of an SGR (`ESC [ ... m`) sequence to a style
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            code @ 30..=37 => style = style.fg(Color::Indexed((code - 30) as u8)),
+            code @ 90..=97 => style = style.fg(Color::Indexed((code - 90 + 8) as u8)),
+            code @ 40..=47 => style = style.bg(Color::Indexed((code - 40) as u8)),
+            code @ 100..=107 => style = style.bg(Color::Indexed((code - 100 + 8) as u8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            code @ (38 | 48) => {
+                let (color, used) = extended_color(&codes[i + 1..]);
+                if let Some(color) = color {
+                    style = if code == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    };
+                }
+                i += used;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    style
+}
+
+/// The visible text of a line, with escape sequences removed
+pub fn strip(line: &str) -> String {
+    parse_line(line)
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+/// Parse the arguments of a 256-color (`5;n`) or truecolor (`2;r;g;b`)
+/// SGR code; returns the color and how many arguments were consumed
+fn extended_color(args: &[u16]) -> (Option<Color>, usize) {
+    let byte = |i: usize| args.get(i).map(|v| (*v).min(255) as u8);
+    match args.first() {
+        Some(5) => (byte(1).map(Color::Indexed), 2),
+        Some(2) => match (byte(1), byte(2), byte(3)) {
+            (Some(r), Some(g), Some(b)) => (Some(Color::Rgb(r, g, b)), 4),
+            _ => (None, args.len()),
+        },
+        _ => (None, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_strip() {
+        assert_eq!(strip("\x1b[1;31merror\x1b[0m: failed"), "error: failed");
+    }
+
+    #[test]
+    fn test_plain_line() {
+        let spans = parse_line("hello world");
+        assert_eq!(spans, vec![Span::raw("hello world")]);
+    }
+
+    #[test]
+    fn test_basic_colors() {
+        let spans = parse_line("\x1b[1;31merror\x1b[0m: failed");
+        assert_eq!(
+            spans,
+            vec![
+                Span::styled(
+                    "error",
+                    Style::default()
+                        .fg(Color::Indexed(1))
+                        .add_modifier(Modifier::BOLD)
+                ),
+                Span::raw(": failed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extended_colors() {
+        let spans = parse_line("\x1b[38;5;208mA\x1b[48;2;10;20;30mB");
+        assert_eq!(spans[0].style.fg, Some(Color::Indexed(208)));
+        assert_eq!(spans[1].style.bg, Some(Color::Rgb(10, 20, 30)));
+        assert_eq!(spans[1].style.fg, Some(Color::Indexed(208)));
+    }
+
+    #[test]
+    fn test_strips_other_sequences() {
+        let line = "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07 \x1b[2K\x1b[Gdone";
+        assert_eq!(text(&parse_line(line)), "link done");
+    }
+
+    #[test]
+    fn test_carriage_return_overwrites() {
+        assert_eq!(text(&parse_line("10%\r50%\r100%")), "100%");
+        assert_eq!(text(&parse_line("done\r")), "done");
+    }
+
+    #[test]
+    fn test_control_characters_visible() {
+        let spans = parse_line("\x1b[31mbell\x07 \x1b]0;title");
+        assert_eq!(text(&spans), "bell^G ");
+        assert_eq!(
+            spans[1].style,
+            Style::default()
+                .fg(Color::Indexed(1))
+                .add_modifier(Modifier::REVERSED)
+        );
+        assert_eq!(strip("a\x7fb\u{0085}c"), "a^?b<U+0085>c");
+    }
+
+    #[test]
+    fn test_invisible_characters_visible() {
+        // A right-to-left override could make `txt.exe` read as `exe.txt`
+        assert_eq!(strip("file\u{202E}txt.exe"), "file<U+202E>txt.exe");
+        assert_eq!(strip("a\u{200B}b"), "a<U+200B>b");
+        // Joiners hold emoji sequences together
+        assert_eq!(
+            strip("\u{1F469}\u{200D}\u{1F4BB}"),
+            "\u{1F469}\u{200D}\u{1F4BB}"
+        );
+    }
+
+    #[test]
+    fn test_tabs_expand_to_stops() {
+        assert_eq!(strip("a\tb"), "a       b");
+        assert_eq!(strip("\x1b[1mabc\x1b[0m\tx\t"), "abc     x       ");
+    }
+}
@@ -1,14 +1,56 @@
+//! Session state: input line, output, blocks, modes, and the workers
+//! feeding them
+
+use std::collections::BTreeSet;
 use std::path::PathBuf;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::Sender;
 use std::time::{Duration, Instant};
 
+use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::Span;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::git::{spawn_git_worker, GitMessage, GitStatus};
-use crate::theme::Palette;
-use crate::navigation::directory::NavigationState;
-use crate::shortcuts::manager::ShortcutManager;
+use crate::blocks::{
+    format_duration, CommandBlock, CopyMode, Diagnostics, HexView, OutputDiff, OutputSearch,
+    Timeline, Tool,
+};
+use crate::completion::{self, Completers, CompletionMenu};
+use crate::config::{
+    self, Config, ContrastMode, FetchPolicy, HistoryScope, JobAlert, OutputWrap, PromptToken,
+    QuitKeys, StatusConfig, ThemeConfig,
+};
+use crate::events::{EventBus, Supervised, Supervision};
+use crate::fuzzy::Matcher;
+use crate::git::{
+    spawn_git_worker, BranchSwitcher, ConflictList, GitMessage, GitOverview, GitPanel, GitStatus,
+    GIT_WORKER,
+};
+use crate::history::{Heatmap, History};
+use crate::jobs::{CommandQueue, JobEvent, JobStatus, JobTable, Load};
+use crate::keymap::{Action, EscapeState, Keymap, QuitGuard, QuitPress};
+use crate::macros::{KeyMacros, Macro, Playback, PlaybackPrompt};
+use crate::messages::Messages;
+use crate::navigation::{DirDiff, NavigationState};
+use crate::notes::{NoteStore, Scratchpad};
+use crate::runner::{self, Project, TestRunner};
+use crate::segment::{self, StatusSegment};
+use crate::session::{self, SessionRecord};
+use crate::shell::alias::AliasStore;
+use crate::shell::arglen::{self, InputStats};
+use crate::shell::edit::{self, Direction, EditKind, InputState, KillRing, UndoHistory};
+use crate::shell::env::{EnvPanel, Environment};
+use crate::shell::executor::{auto_cd_target, OutputStream, RunningCommand};
+use crate::shell::hooks::HookRunner;
+use crate::shell::policy::{self, Policy, PolicyAction};
+use crate::shell::pty::PtySession;
+use crate::shell::retry::{Retry, RetrySpec};
+use crate::shortcuts::storage::Shortcut;
+use crate::shortcuts::{DirTracker, ShortcutManager};
+use crate::snippets::Template;
+use crate::synopsis::SynopsisCache;
+use crate::theme::{contrast, loader, Icons, Theme, ThemeGallery};
 
 /// Application modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,223 +61,1955 @@ pub enum AppMode {
     NavigationList,
     /// Shortcut selection mode - navigating shortcuts with arrow keys
     ShortcutSelection,
+    /// An interactive program is running in a PTY and receives all keys
+    Pty,
+    /// Block timeline overlay - jumping to earlier commands
+    Timeline,
+    /// Confirming a pasted multi-command script before running it
+    ConfirmScript,
+    /// Comparing two directories side by side
+    DirDiff,
+    /// Editing the current directory's note
+    Note,
+    /// Activity heatmap of the command history
+    Heatmap,
+    /// Gallery of the installed themes
+    ThemeGallery,
+    /// Files left conflicted by a merge
+    Conflicts,
+    /// Local branches and their upstreams
+    GitOverview,
+    /// Full `git status` of the current repository
+    GitStatus,
+    /// Picking a branch to check out
+    BranchSwitch,
+    /// Hex dump of a block's stored output
+    HexView,
+    /// Variables commands run with
+    Env,
+    /// Selecting output lines to copy
+    Copy,
+    /// Results of the project's tests
+    Tests,
+    /// Searching the output
+    Search,
+}
+
+/// Shown in place of newlines in the input line
+pub const NEWLINE_MARKER: &str = "\u{21b5}";
+
+/// Where the last frame drew the things a mouse click can land on
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScreenAreas {
+    /// The sidebar's shortcut rows, one per goto candidate
+    pub shortcuts: Rect,
+    /// The terminal pane, borders included
+    pub terminal: Rect,
+    /// The prompt and input line, when it's on screen
+    pub input: Option<InputArea>,
+    /// The border between the sidebar and the terminal pane, both of its
+    /// columns
+    pub divider: Rect,
+}
+
+/// Screen geometry of the wrapped prompt and input line
+#[derive(Debug, Clone, Copy)]
+pub struct InputArea {
+    /// Left edge of the pane's content
+    pub x: u16,
+    /// Row the prompt starts on; above the pane when scrolled partly out
+    pub y: i32,
+    /// Columns before the input wraps
+    pub width: u16,
+    /// Display width of the prompt in front of the input
+    pub prompt_width: usize,
+}
+
+impl InputArea {
+    /// Cursor position (a byte offset) for a click at `column`, `row`; None
+    /// when the click is outside the input's rows
+    pub fn cursor_at(&self, input: &str, column: u16, row: u16) -> Option<usize> {
+        let cells = self.cells(input);
+        let line = i32::from(row) - self.y;
+        let rows = cells.last().map_or(0, |&(_, row, _)| row + 1);
+        if column < self.x || line < 0 || line as usize >= rows {
+            return None;
+        }
+        let target = (line as usize, (column - self.x) as usize);
+        let cursor = cells
+            .iter()
+            .take_while(|&&(_, row, col)| (row, col) <= target)
+            .last()
+            .map_or(0, |&(index, _, _)| index);
+        Some(cursor)
+    }
+
+    /// Row and column of the cursor at byte offset `cursor`, counting the
+    /// prompt's first row as row 0
+    pub fn cursor_cell(&self, input: &str, cursor: usize) -> (usize, usize) {
+        self.cells(input)
+            .into_iter()
+            .find(|&(index, _, _)| index >= cursor)
+            .map_or((0, 0), |(_, row, col)| (row, col))
+    }
+
+    /// Where each grapheme of `input` starts as `(byte offset, row, column)`,
+    /// then where the cursor goes after the last one
+    ///
+    /// Follows the wrapping of the input line: a character wider than the
+    /// room left on a row starts the next one.
+    fn cells(&self, input: &str) -> Vec<(usize, usize, usize)> {
+        let width = self.width.max(1) as usize;
+        let (mut row, mut col) = (self.prompt_width / width, self.prompt_width % width);
+        let mut cells = Vec::new();
+        for (index, grapheme) in input.grapheme_indices(true) {
+            let w = match grapheme {
+                "\n" => NEWLINE_MARKER.width(),
+                g => g.chars().map(|c| c.width().unwrap_or(0)).sum(),
+            };
+            if col + w > width {
+                row += 1;
+                col = 0;
+            }
+            cells.push((index, row, col));
+            col += w;
+        }
+        if col >= width {
+            row += 1;
+            col = 0;
+        }
+        cells.push((input.len(), row, col));
+        cells
+    }
 }
 
 /// Main application state
 pub struct App {
     /// Current working directory
     pub current_dir: PathBuf,
-    /// Command history
-    pub history: Vec<String>,
+    /// Command history (persisted across sessions)
+    pub history: History,
     /// Current position in history (for up/down navigation)
     pub history_index: Option<usize>,
+    /// Commands being cycled with Up/Down, captured when navigation starts
+    history_view: Vec<String>,
     /// Current input buffer
     pub input: String,
-    /// Cursor position in input
+    /// Cursor position in input, as a byte offset between graphemes
     pub cursor_pos: usize,
+    /// Text killed from the input line, for yanking back
+    pub kill_ring: KillRing,
+    /// Earlier states of the input line, for undo
+    edits: UndoHistory,
     /// Output buffer (terminal output lines)
     pub output: Vec<String>,
+    /// Indices of the output lines a command wrote to stderr
+    pub stderr_lines: BTreeSet<usize>,
     /// Current application mode
     pub mode: AppMode,
     /// Navigation state for cd -list mode
     pub navigation_state: NavigationState,
+    /// Comparison shown by `jerm diffdir`
+    pub dir_diff: Option<DirDiff>,
     /// Shortcut manager
     pub shortcuts: ShortcutManager,
-    /// Selected shortcut index for goto mode
+    /// Every directory visited, for `z` jumps
+    pub dir_tracker: DirTracker,
+    /// Directories visited this session, in first-visit order
+    pub visited_dirs: Vec<PathBuf>,
+    /// Per-directory notes
+    pub notes: NoteStore,
+    /// Command aliases, expanded before a line runs
+    pub aliases: AliasStore,
+    /// Note open in the scratchpad pane
+    pub note: Option<Scratchpad>,
+    /// Activity view shown by `jerm heatmap`
+    pub heatmap: Option<Heatmap>,
+    /// Conflicted files, while the conflicts overlay is open
+    pub conflicts: Option<ConflictList>,
+    /// Branch dashboard shown by `jerm git overview`
+    pub git_overview: Option<GitOverview>,
+    /// Git status panel, while open
+    pub git_panel: Option<GitPanel>,
+    /// Hex dump of a block's output, while open
+    pub hex_view: Option<HexView>,
+    /// Changes `export` and `unset` made to the environment of commands
+    pub env: Environment,
+    /// Variables shown by `env`
+    pub env_panel: Option<EnvPanel>,
+    /// Output selection, while copy mode is open
+    pub copy_mode: Option<CopyMode>,
+    /// Output search, while open
+    pub search: Option<OutputSearch>,
+    /// Last text copied, pasted back when no clipboard program can read
+    /// the system clipboard
+    pub copied: Option<String>,
+    /// Test view opened by `jerm test`
+    pub tests: Option<TestRunner>,
+    /// Branch picker opened by `jerm branch`
+    pub branch_switcher: Option<BranchSwitcher>,
+    /// Output of `status.command`, refreshed in the background
+    pub status_segment: StatusSegment,
+    /// Theme cards shown by `jerm theme preview`
+    pub theme_gallery: Option<ThemeGallery>,
+    /// Selected shortcut index for goto mode (within the filtered list)
     pub selected_shortcut_index: usize,
+    /// Text typed in goto mode to fuzzy-filter the shortcuts
+    pub goto_filter: String,
     /// Whether the app should quit
     pub should_quit: bool,
-    /// Scroll offset for output (reserved for future use)
-    #[allow(dead_code)]
-    pub output_scroll: usize,
+    /// Whether to suspend the UI and open a plain shell on the next loop
+    pub shell_requested: bool,
+    /// Readonly mode: no command execution or file mutations
+    pub readonly: bool,
+    /// The config directory can't be written: shortcuts, history, and notes
+    /// live only as long as the session
+    pub in_memory: bool,
+    /// Output line pinned to the top of the view while browsing scrollback
+    /// (`None` follows the bottom of the output)
+    pub output_scroll: Option<usize>,
+    /// Output lines before this one were cleared off the screen by `clear`;
+    /// scrolling back still reaches them
+    pub clear_mark: usize,
+    /// Commands executed this session, in order
+    pub blocks: Vec<CommandBlock>,
+    /// State of the block timeline overlay
+    pub timeline: Timeline,
+    /// Block the next command re-runs, set when re-running from the timeline
+    pub rerun: Option<usize>,
     /// Git status for current directory
     pub git_status: Option<GitStatus>,
-    /// Channel to send messages to git worker
-    git_tx: Sender<GitMessage>,
-    /// Channel to receive messages from git worker
-    git_rx: Receiver<GitMessage>,
+    /// Macro currently being recorded
+    pub recording: Option<Macro>,
+    /// Macro currently being played back
+    pub playback: Option<Playback>,
+    /// Keystroke registers (`Alt+Q` / `Alt+P`)
+    pub key_macros: KeyMacros,
+    /// Snippet placeholders being filled in
+    pub template: Option<Template>,
+    /// Rest of the most recent matching history entry, shown as ghost text
+    pub suggestion: Option<String>,
+    /// Synopses of commands typed so far
+    pub synopsis: SynopsisCache,
+    /// Whether the synopsis of the command being typed is shown
+    pub show_synopsis: bool,
+    /// Whether long output lines wrap or are truncated this session
+    pub output_wrap: OutputWrap,
+    /// Columns of output scrolled past on the left, in truncate mode
+    pub output_hscroll: usize,
+    /// Input as of its last change, when that was, and whether the
+    /// synopsis delay has since passed
+    synopsis_input: (String, Instant, bool),
+    /// Size of the input and the directory its globs were expanded in
+    input_stats: (String, PathBuf, InputStats),
+    /// Input and directory last checked for `shell.auto_cd`, and whether
+    /// the input was a directory to change into
+    auto_cd_input: (String, PathBuf, bool),
+    /// Bytes the environment leaves for a command's arguments
+    pub arg_space: usize,
+    /// Whether the input contains pasted text
+    pub pasted: bool,
+    /// Completion popup shown when Tab finds several candidates
+    pub completion: Option<CompletionMenu>,
+    /// Project-aware completers and their per-directory cache
+    pub completers: Completers,
+    /// Shell command running in the foreground (output is streamed)
+    pub running: Option<RunningCommand>,
+    /// `jerm retry` loop rerunning the current command until it succeeds
+    pub retry: Option<Retry>,
+    /// Interactive program running in the foreground
+    pub pty: Option<PtySession>,
+    /// Background and stopped jobs
+    pub jobs: JobTable,
+    /// Commands entered while others were running, waiting their turn
+    pub queue: CommandQueue,
+    /// When the last background job finished and whether it succeeded,
+    /// for `jobs.on_finish`
+    job_finished: Option<(Instant, bool)>,
+    /// Size (rows, cols) of the terminal pane's content area
+    pub pane_size: (u16, u16),
+    /// Where clickable parts of the screen were last drawn
+    pub screen: ScreenAreas,
+    /// Sidebar ratio when the divider drag in progress began
+    divider_drag: Option<f64>,
+    /// When the divider was last clicked, to catch a double-click
+    divider_click: Option<Instant>,
+    /// Whether the idle lock screen is shown
+    pub locked: bool,
+    /// Settings from the active profile's `config.toml`
+    pub config: Config,
+    /// Active color theme
+    pub theme: Theme,
+    /// Translatable strings from the message catalog
+    pub messages: Messages,
+    /// Key chords for rebindable normal-mode actions
+    pub keymap: Keymap,
+    /// Parsed `prompt.format`
+    prompt_format: Vec<PromptToken>,
+    /// Tracks Esc presses for the double-Esc reset
+    pub escape: EscapeState,
+    /// Presses of the quit chords, for `ui.quit_keys`
+    pub quit_guard: QuitGuard,
+    /// Time of the last key press
+    last_activity: Instant,
+    /// Channel the event loop waits on; workers reply through it
+    pub events: EventBus,
+    /// The git worker, taking requests on its channel
+    git: Supervised<Sender<GitMessage>>,
     /// Last time git was polled
     last_git_poll: Instant,
+    /// Last time `git fetch` was requested
+    last_git_fetch: Instant,
+    /// Runs the chpwd hooks
+    hooks: HookRunner,
 }
 
 impl App {
     /// Create a new application instance
     pub fn new() -> Self {
         let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
-        let shortcuts = ShortcutManager::new();
-        let (git_tx, git_rx) = spawn_git_worker();
+        let events = EventBus::new();
+        let git = Supervised::spawn(GIT_WORKER, spawn_git_worker, events.sender());
+
+        let (config, warnings) = Config::load();
+        let shortcuts = ShortcutManager::open(config.storage.shortcuts);
 
         let mut app = Self {
             current_dir: current_dir.clone(),
-            history: Vec::new(),
+            history: History::new(
+                config.history.size,
+                config.history.scope == HistoryScope::Directory,
+            ),
             history_index: None,
+            history_view: Vec::new(),
             input: String::new(),
             cursor_pos: 0,
+            kill_ring: KillRing::default(),
+            edits: UndoHistory::default(),
             output: Vec::new(),
+            stderr_lines: BTreeSet::new(),
             mode: AppMode::Normal,
             navigation_state: NavigationState::new(),
+            dir_diff: None,
             shortcuts,
+            dir_tracker: DirTracker::new(),
+            visited_dirs: vec![current_dir.clone()],
+            notes: NoteStore::new(),
+            aliases: AliasStore::new(),
+            note: None,
+            heatmap: None,
+            conflicts: None,
+            git_overview: None,
+            git_panel: None,
+            hex_view: None,
+            env: Environment::default(),
+            env_panel: None,
+            copy_mode: None,
+            search: None,
+            copied: None,
+            tests: None,
+            branch_switcher: None,
+            status_segment: StatusSegment::new(&StatusConfig::default()),
+            theme_gallery: None,
             selected_shortcut_index: 0,
+            goto_filter: String::new(),
             should_quit: false,
-            output_scroll: 0,
+            shell_requested: false,
+            readonly: false,
+            in_memory: false,
+            output_scroll: None,
+            clear_mark: 0,
+            blocks: Vec::new(),
+            timeline: Timeline::new(),
+            rerun: None,
             git_status: None,
-            git_tx,
-            git_rx,
+            recording: None,
+            key_macros: KeyMacros::new(),
+            playback: None,
+            template: None,
+            completion: None,
+            completers: Completers::new(),
+            pasted: false,
+            suggestion: None,
+            synopsis: SynopsisCache::new(),
+            show_synopsis: true,
+            output_wrap: OutputWrap::Wrap,
+            output_hscroll: 0,
+            synopsis_input: (String::new(), Instant::now(), false),
+            input_stats: (String::new(), PathBuf::new(), InputStats::default()),
+            auto_cd_input: (String::new(), PathBuf::new(), false),
+            arg_space: arglen::arg_space(),
+            running: None,
+            retry: None,
+            pty: None,
+            jobs: JobTable::new(),
+            queue: CommandQueue::new(),
+            job_finished: None,
+            pane_size: (24, 80),
+            screen: ScreenAreas::default(),
+            divider_drag: None,
+            divider_click: None,
+            locked: false,
+            config: Config::default(),
+            theme: Theme::default(),
+            messages: Messages::default(),
+            keymap: Keymap::default(),
+            prompt_format: Vec::new(),
+            escape: EscapeState::new(),
+            quit_guard: QuitGuard::new(),
+            last_activity: Instant::now(),
+            events,
+            git,
             last_git_poll: Instant::now(),
+            last_git_fetch: Instant::now(),
+            hooks: HookRunner::new(),
+        };
+
+        app.apply_config(config, warnings);
+        app.check_storage();
+
+        // Trigger initial git status
+        app.refresh_git_status(false);
+        app
+    }
+
+    /// Apply a loaded config, printing its warnings to the output
+    pub fn apply_config(&mut self, config: Config, mut warnings: Vec<String>) {
+        let (keymap, keymap_warnings) = Keymap::new(&config.keybindings);
+        warnings.extend(keymap_warnings);
+
+        self.keymap = keymap;
+        self.prompt_format = config::parse_prompt_format(&config.prompt.format);
+        match loader::load_theme(&config.theme.name) {
+            Ok(theme) => self.theme = theme,
+            Err(e) => {
+                warnings.push(format!("theme '{}': {e}", config.theme.name));
+                self.theme = Theme::default();
+            }
+        }
+        warnings.extend(self.fit_contrast(&config.theme));
+        crate::theme::icons::set_nerd_fonts(config.theme.nerd_fonts);
+        let (messages, messages_warning) = Messages::load();
+        self.messages = messages;
+        warnings.extend(messages_warning);
+        self.show_synopsis = config.ui.synopsis;
+        self.navigation_state.layout = config.ui.navigator_layout;
+        self.output_wrap = config.ui.output_wrap;
+        self.queue.limit = config.jobs.max_running;
+        self.output_hscroll = 0;
+        self.status_segment = StatusSegment::new(&config.status);
+        self.shortcuts.use_backend(config.storage.shortcuts);
+        self.config = config;
+
+        for warning in warnings {
+            self.add_output(&format!("jerm: config: {warning}"));
+        }
+    }
+
+    /// Enable or disable readonly mode
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+        self.sync_store_readonly();
+    }
+
+    /// Stores save to disk unless in readonly or in-memory mode
+    fn sync_store_readonly(&mut self) {
+        let readonly = self.readonly || self.in_memory;
+        self.shortcuts.set_readonly(readonly);
+        self.dir_tracker.set_readonly(readonly);
+        self.notes.set_readonly(readonly);
+        self.aliases.set_readonly(readonly);
+        self.history.set_readonly(readonly);
+    }
+
+    /// Switch to in-memory mode when the config directory can't be written,
+    /// saying why
+    pub fn check_storage(&mut self) {
+        self.in_memory = !crate::paths::is_writable();
+        self.sync_store_readonly();
+        if self.in_memory {
+            let dir = crate::paths::config_dir().map_or("no config directory".to_string(), |dir| {
+                dir.display().to_string()
+            });
+            self.add_output(&format!(
+                "jerm: can't write to {dir}; shortcuts, history, and notes won't be saved \
+                 (set {} to a writable directory)",
+                crate::paths::DATA_DIR_VAR
+            ));
+        }
+        let load_errors = [
+            self.shortcuts.load_error(),
+            self.dir_tracker.load_error(),
+            self.notes.load_error(),
+            self.aliases.load_error(),
+        ];
+        for error in load_errors
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+        {
+            self.add_output(&format!("jerm: {error}"));
+        }
+    }
+
+    /// Open the session with the greeting header, unless it's turned off
+    pub fn greet(&mut self) {
+        if !self.config.ui.greeting {
+            return;
+        }
+        let last = SessionRecord::load_last();
+        let lines = session::greeting(
+            chrono::Local::now(),
+            last.as_ref(),
+            self.history.entries(),
+            &self.shortcuts.get_shortcuts(),
+        );
+        for line in lines {
+            self.add_output(&line);
+        }
+        self.add_output("");
+    }
+
+    /// Kill the foreground command and every job before exiting, returning
+    /// the command lines that were still running
+    pub fn stop_processes(&mut self) -> Vec<String> {
+        let mut killed = Vec::new();
+        if let Some(running) = self.running.take() {
+            running.kill();
+            killed.push(running.command.clone());
+        }
+        killed.extend(
+            self.jobs
+                .jobs()
+                .iter()
+                .map(|job| job.command.command.clone()),
+        );
+        self.jobs.kill_all();
+        killed
+    }
+
+    /// Record user activity, resetting the idle timer
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Lock the screen once the idle timeout has elapsed, returning true
+    /// if it just locked
+    pub fn check_idle(&mut self) -> bool {
+        let Some(timeout) = self.config.idle_timeout() else {
+            return false;
+        };
+        if !self.locked && self.last_activity.elapsed() >= timeout {
+            self.locked = true;
+            return true;
+        }
+        false
+    }
+
+    /// Name of the active profile, for display
+    pub fn profile_name(&self) -> String {
+        crate::paths::active_profile().unwrap_or_else(|| crate::paths::DEFAULT_PROFILE.to_string())
+    }
+
+    /// Switch to another theme for the rest of the session
+    pub fn set_theme(&mut self, name: &str) -> Result<(), loader::ThemeError> {
+        self.theme = loader::load_theme(name)?;
+        let settings = self.config.theme.clone();
+        for warning in self.fit_contrast(&settings) {
+            self.add_output(&format!("jerm: {warning}"));
+        }
+        Ok(())
+    }
+
+    /// Hold the theme's selected rows to `theme.min_contrast`, returning a
+    /// warning per pair left below it
+    fn fit_contrast(&mut self, settings: &ThemeConfig) -> Vec<String> {
+        if settings.min_contrast == 0.0 {
+            return Vec::new();
+        }
+        let low = match settings.contrast {
+            ContrastMode::Adjust => {
+                contrast::enforce(&mut self.theme, settings.min_contrast);
+                return Vec::new();
+            }
+            ContrastMode::Warn => contrast::check(&self.theme, settings.min_contrast),
+        };
+        low.iter()
+            .map(|pair| {
+                format!(
+                    "theme '{}': {pair}, below theme.min_contrast ({}:1)",
+                    self.theme.name, settings.min_contrast
+                )
+            })
+            .collect()
+    }
+
+    /// Sidebar width for a screen `total` columns wide: `ui.sidebar_ratio`
+    /// of it, leaving both panes their minimum widths
+    pub fn sidebar_width(&self, total: u16) -> u16 {
+        let width = (f64::from(total) * self.config.ui.sidebar_ratio).round() as u16;
+        let max = total
+            .saturating_sub(MIN_TERMINAL_WIDTH)
+            .max(MIN_SIDEBAR_WIDTH);
+        width.clamp(MIN_SIDEBAR_WIDTH, max)
+    }
+
+    /// Start dragging the divider beside the sidebar, or on a double-click
+    /// put the sidebar back to its default share of the width
+    pub fn press_divider(&mut self) {
+        let now = Instant::now();
+        let double = self
+            .divider_click
+            .is_some_and(|at| now.duration_since(at) < DOUBLE_CLICK);
+        if double {
+            self.divider_click = None;
+            self.divider_drag = None;
+            let ratio = config::UiConfig::default().sidebar_ratio;
+            if self.config.ui.sidebar_ratio != ratio {
+                self.config.ui.sidebar_ratio = ratio;
+                self.save_sidebar_ratio();
+            }
+        } else {
+            self.divider_click = Some(now);
+            self.divider_drag = Some(self.config.ui.sidebar_ratio);
+        }
+    }
+
+    /// Move the divider being dragged to `column`, leaving the terminal
+    /// pane its minimum width
+    pub fn drag_divider(&mut self, column: u16) {
+        let total = self.screen.terminal.right();
+        if self.divider_drag.is_none() || total == 0 {
+            return;
+        }
+        let max = total
+            .saturating_sub(MIN_TERMINAL_WIDTH)
+            .max(MIN_SIDEBAR_WIDTH);
+        let width = (column + 1).clamp(MIN_SIDEBAR_WIDTH, max);
+        // Three places are plenty to get the same width back
+        let ratio = f64::from(width) / f64::from(total);
+        self.config.ui.sidebar_ratio = (ratio * 1000.0).round() / 1000.0;
+    }
+
+    /// Finish a divider drag, saving the new ratio to the config file
+    pub fn release_divider(&mut self) {
+        if let Some(start) = self.divider_drag.take() {
+            if self.config.ui.sidebar_ratio != start {
+                self.save_sidebar_ratio();
+            }
+        }
+    }
+
+    /// Write the sidebar ratio to the active profile's config file
+    fn save_sidebar_ratio(&mut self) {
+        if self.readonly || self.in_memory {
+            return;
+        }
+        let Some(path) = config::get_config_path() else {
+            return;
+        };
+        let ratio = self.config.ui.sidebar_ratio.to_string();
+        if let Err(e) = config::set_value(&path, "ui", "sidebar_ratio", &ratio) {
+            self.add_output(&format!("jerm: config: {e}"));
+        }
+    }
+
+    /// Switch to another profile and reload its data
+    pub fn switch_profile(&mut self, name: &str) {
+        crate::paths::set_active_profile(Some(name));
+        self.shortcuts.reload();
+        self.dir_tracker.reload();
+        self.notes.reload();
+        self.aliases.reload();
+        let (config, warnings) = Config::load();
+        self.history = History::new(
+            config.history.size,
+            config.history.scope == HistoryScope::Directory,
+        );
+        self.history_index = None;
+        self.apply_config(config, warnings);
+        self.check_storage();
+        self.selected_shortcut_index = 0;
+    }
+
+    /// Refresh git status for current directory
+    pub fn refresh_git_status(&mut self, with_fetch: bool) {
+        if with_fetch {
+            self.last_git_fetch = Instant::now();
+        }
+        let _ = self.git.handle().send(GitMessage::UpdateStatus {
+            dir: self.current_dir.display().to_string(),
+            with_fetch,
+        });
+    }
+
+    /// Report a crashed worker, and catch up once it's restarted; returns
+    /// true when anything changed on screen
+    pub fn poll_workers(&mut self) -> bool {
+        match self.git.check(Instant::now()) {
+            Some(Supervision::Crashed { message, retry_in }) => {
+                self.add_output(&format!(
+                    "jerm: the git worker crashed, restarting in {}: {}",
+                    format_duration(retry_in),
+                    message.lines().collect::<Vec<_>>().join(" ")
+                ));
+                true
+            }
+            // Requests sent while it was down were lost
+            Some(Supervision::Restarted) => {
+                self.refresh_git_status(false);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Apply a reply from the git worker
+    pub fn handle_git_message(&mut self, msg: GitMessage) {
+        match msg {
+            GitMessage::StatusUpdate(status) => self.git_status = status,
+            // Fetching may have moved the upstream; ignore fetches of a
+            // directory we've since left
+            GitMessage::Fetched { dir } if dir == self.current_dir.display().to_string() => {
+                self.refresh_git_status(false);
+            }
+            GitMessage::OverviewReady(branches) => {
+                if let Some(ref mut switcher) = self.branch_switcher {
+                    switcher.branches = Some(branches.clone());
+                }
+                if let Some(ref mut overview) = self.git_overview {
+                    overview.branches = Some(branches);
+                }
+            }
+            GitMessage::DetailsReady(details) => {
+                if let Some(ref mut panel) = self.git_panel {
+                    panel.details = Some(details);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Start a background git refresh once the poll interval has passed
+    pub fn poll_git_timer(&mut self) {
+        if self.last_git_poll.elapsed() >= self.config.git_poll_interval() {
+            self.refresh_git_status(self.config.git.fetch == FetchPolicy::Timer);
+            self.last_git_poll = Instant::now();
+        }
+    }
+
+    /// Refresh git status and fetch right away (the refresh_git key)
+    pub fn force_git_refresh(&mut self) {
+        self.refresh_git_status(true);
+        self.last_git_poll = Instant::now();
+    }
+
+    /// The terminal window regained focus: fetch if that's the policy,
+    /// skipping quick back-and-forth switches
+    pub fn focus_gained(&mut self) {
+        if self.config.git.fetch == FetchPolicy::Focus
+            && self.last_git_fetch.elapsed() >= FOCUS_FETCH_COOLDOWN
+        {
+            self.force_git_refresh();
+        }
+    }
+
+    /// Start a shell command in the background of the event loop
+    pub fn start_command(&mut self, command: &str) {
+        let events = self.events.sender();
+        match RunningCommand::spawn_to(command, &self.current_dir, &self.env, events) {
+            Ok(running) => {
+                self.running = Some(running);
+                if let Some(block) = self.blocks.last_mut() {
+                    block.mark_spawned();
+                }
+            }
+            Err(e) => {
+                self.add_output(&format!("Error: {e}"));
+                self.set_block_exit(127);
+            }
+        }
+    }
+
+    /// Show a line from [`AppEvent::Output`](crate::events::AppEvent::Output), written by the running
+    /// command or a background job; returns false if neither wrote it
+    pub fn command_output(&mut self, pid: u32, stream: OutputStream, line: &str) -> bool {
+        if self
+            .running
+            .as_ref()
+            .is_some_and(|running| running.pid() == pid)
+        {
+            if let Some(block) = self.blocks.last_mut() {
+                block.mark_output();
+            }
+        } else if self.jobs.by_pid(pid).is_none() {
+            // Written after its command was given up on
+            return false;
+        }
+        self.add_command_output(stream, line);
+        true
+    }
+
+    /// Pass [`AppEvent::OutputClosed`](crate::events::AppEvent::OutputClosed) on to the command it's about
+    pub fn command_output_closed(&mut self, pid: u32) {
+        match self.running {
+            Some(ref mut running) if running.pid() == pid => running.stream_closed(),
+            _ => {
+                if let Some(job) = self.jobs.by_pid(pid) {
+                    job.command.stream_closed();
+                }
+            }
+        }
+    }
+
+    /// Finish the running command's block once it has exited and its
+    /// output is in
+    ///
+    /// Returns true if anything changed on screen.
+    pub fn poll_running(&mut self) -> bool {
+        let Some(ref mut running) = self.running else {
+            return false;
+        };
+        let Some(exit_code) = running.try_finish() else {
+            return false;
+        };
+
+        self.running = None;
+        self.set_block_exit(exit_code);
+        self.refresh_git_status(false); // Local only
+        self.retry_finished(exit_code);
+        true
+    }
+
+    /// Start a `jerm retry` loop with its first attempt
+    pub fn start_retry(&mut self, spec: RetrySpec) {
+        let mut retry = Retry::new(spec);
+        self.add_output(&retry.begin_attempt());
+        let command = retry.spec.command.clone();
+        self.retry = Some(retry);
+        self.start_command(&command);
+        if self.running.is_none() {
+            // The command couldn't even be spawned
+            self.retry = None;
+        }
+    }
+
+    /// Decide what follows a finished attempt: stop, or schedule another
+    fn retry_finished(&mut self, exit_code: i32) {
+        let Some(ref mut retry) = self.retry else {
+            return;
+        };
+        let attempts = retry.spec.attempts;
+        if exit_code == 0 {
+            let attempt = retry.attempt;
+            self.retry = None;
+            if attempt > 1 {
+                self.add_output(&format!(
+                    "jerm retry: succeeded on attempt {attempt}/{attempts}"
+                ));
+            }
+        } else if retry.has_attempts_left() {
+            retry.schedule(Instant::now());
+            let delay = retry.delay();
+            if !delay.is_zero() {
+                self.add_output(&format!(
+                    "jerm retry: exit {exit_code}, retrying in {}",
+                    format_duration(delay)
+                ));
+            }
+        } else {
+            self.retry = None;
+            self.add_output(&format!("jerm retry: failed after {attempts} attempts"));
+        }
+    }
+
+    /// Start the next `jerm retry` attempt once its delay has passed
+    ///
+    /// Each attempt after the first gets its own block. Returns true if an
+    /// attempt started.
+    pub fn poll_retry(&mut self) -> bool {
+        let Some(ref mut retry) = self.retry else {
+            return false;
+        };
+        if self.running.is_some() || !retry.is_due(Instant::now()) {
+            return false;
+        }
+        let separator = retry.begin_attempt();
+        let command = retry.spec.command.clone();
+        let label = format!(
+            "{command} (attempt {}/{})",
+            retry.attempt, retry.spec.attempts
+        );
+
+        self.output_scroll = None;
+        self.begin_block(&label);
+        self.add_output(&separator);
+        self.start_command(&command);
+        if self.running.is_none() {
+            self.retry = None;
+        }
+        true
+    }
+
+    /// Stop a `jerm retry` loop, keeping any attempt that is running
+    pub fn cancel_retry(&mut self) {
+        if self.retry.take().is_some() {
+            self.add_output("jerm retry: cancelled");
+        }
+    }
+
+    /// Interrupt the running command (Ctrl+C)
+    pub fn interrupt_running(&mut self) {
+        if let Some(ref running) = self.running {
+            running.interrupt();
+            self.add_output("^C");
+            self.cancel_retry();
+            // Like a shell list, the commands queued after it don't run either
+            match self.queue.clear() {
+                0 => {}
+                1 => self.add_output("jerm: dropped 1 queued command"),
+                count => self.add_output(&format!("jerm: dropped {count} queued commands")),
+            }
+        }
+    }
+
+    /// What's running now, as the command queue sees it
+    pub fn load(&self) -> Load {
+        let foreground = self.running.is_some() || self.pty.is_some();
+        Load {
+            foreground,
+            running: usize::from(foreground) + self.jobs.running(),
+        }
+    }
+
+    /// Whether the input has to wait in the queue instead of running now
+    pub fn input_must_wait(&self) -> bool {
+        self.queue.must_wait(&self.input, self.load())
+    }
+
+    /// Put the input at the back of the command queue
+    pub fn queue_input(&mut self) {
+        let command = self.input.trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        let waiting = self.queue.push(command.clone());
+        self.add_output(&format!("jerm: queued ({waiting} waiting): {command}"));
+        self.clear_input();
+    }
+
+    /// The oldest queued command, once it's allowed to start
+    pub fn next_queued(&mut self) -> Option<String> {
+        let load = self.load();
+        self.queue.pop_ready(load)
+    }
+
+    /// Suspend the running command into the job table (Ctrl+Z)
+    pub fn suspend_running(&mut self) {
+        let Some(running) = self.running.take() else {
+            return;
+        };
+        running.stop();
+        self.cancel_retry();
+        let command = running.command.clone();
+        let id = self.jobs.add(running, JobStatus::Stopped);
+        self.add_output("^Z");
+        self.add_output(&format!("[{id}]  Stopped  {command}"));
+        self.set_block_exit(128 + libc::SIGTSTP);
+    }
+
+    /// Start a shell command as a background job
+    pub fn start_background(&mut self, command: &str) {
+        let events = self.events.sender();
+        match RunningCommand::spawn_to(command, &self.current_dir, &self.env, events) {
+            Ok(running) => {
+                let pid = running.pid();
+                let id = self.jobs.add(running, JobStatus::Running);
+                self.add_output(&format!("[{id}] {pid}"));
+            }
+            Err(e) => {
+                self.add_output(&format!("Error: {e}"));
+                self.set_block_exit(127);
+            }
+        }
+    }
+
+    /// Stream output from background jobs and announce the ones that finish
+    ///
+    /// Returns true if anything changed on screen.
+    pub fn poll_jobs(&mut self) -> bool {
+        let events = self.jobs.poll();
+        let changed = !events.is_empty();
+        // Output alone doesn't touch the repository; a finished job may have
+        let finished = events
+            .iter()
+            .any(|event| matches!(event, JobEvent::Finished { .. }));
+
+        for event in events {
+            match event {
+                JobEvent::Output(stream, line) => self.add_command_output(stream, &line),
+                JobEvent::Finished {
+                    id,
+                    command,
+                    exit_code: 0,
+                } => {
+                    self.add_output(&format!("[{id}]  Done  {command}"));
+                    self.alert_job_finished(true);
+                }
+                JobEvent::Finished {
+                    id,
+                    command,
+                    exit_code,
+                } => {
+                    self.add_output(&format!("[{id}]  Exit {exit_code}  {command}"));
+                    self.alert_job_finished(false);
+                }
+            }
+        }
+        if finished {
+            self.refresh_git_status(false);
+        }
+        changed
+    }
+
+    /// Draw attention to a background job that just finished, as
+    /// `jobs.on_finish` says
+    fn alert_job_finished(&mut self, success: bool) {
+        match self.config.jobs.on_finish {
+            JobAlert::Off => return,
+            JobAlert::Focus if self.mode == AppMode::Normal => self.output_scroll = None,
+            JobAlert::Highlight | JobAlert::Focus => {}
+        }
+        self.job_finished = Some((Instant::now(), success));
+    }
+
+    /// Whether a background job finished moments ago, and if it succeeded,
+    /// while the terminal pane is highlighted for it
+    pub fn job_highlight(&self) -> Option<bool> {
+        self.job_finished
+            .filter(|(at, _)| at.elapsed() < JOB_HIGHLIGHT)
+            .map(|(_, success)| success)
+    }
+
+    /// Print the job table (`jobs`)
+    pub fn list_jobs(&mut self) {
+        let lines: Vec<String> = self
+            .jobs
+            .jobs()
+            .iter()
+            .map(|job| {
+                format!(
+                    "[{}]  {:<8} {}",
+                    job.id,
+                    job.status.label(),
+                    job.command.command
+                )
+            })
+            .collect();
+        for line in lines {
+            self.add_output(&line);
+        }
+    }
+
+    /// Bring a job to the foreground (`fg`)
+    pub fn foreground_job(&mut self, id: Option<usize>) {
+        let Some(job) = self.jobs.resolve(id).and_then(|id| self.jobs.take(id)) else {
+            self.add_output("fg: no such job");
+            self.set_block_exit(1);
+            return;
+        };
+        if job.status == JobStatus::Stopped {
+            job.command.resume();
+        }
+        self.add_output(&job.command.command);
+        self.running = Some(job.command);
+    }
+
+    /// Resume a stopped job in the background (`bg`)
+    pub fn background_job(&mut self, id: Option<usize>) {
+        let Some(id) = self.jobs.resolve(id) else {
+            self.add_output("bg: no such job");
+            self.set_block_exit(1);
+            return;
+        };
+        if let Some(job) = self.jobs.resume(id) {
+            let line = format!("[{}]  {} &", job.id, job.command.command);
+            self.add_output(&line);
+        }
+    }
+
+    /// Run an interactive command in a PTY, taking over the terminal pane
+    pub fn start_pty(&mut self, command: &str) {
+        let (rows, cols) = self.pane_size;
+        let events = self.events.sender();
+        match PtySession::spawn(command, &self.current_dir, &self.env, rows, cols, events) {
+            Ok(session) => {
+                self.pty = Some(session);
+                self.mode = AppMode::Pty;
+                if let Some(block) = self.blocks.last_mut() {
+                    block.mark_spawned();
+                }
+            }
+            Err(e) => {
+                self.add_output(&format!("Error: {e}"));
+                self.set_block_exit(127);
+            }
+        }
+    }
+
+    /// Feed [`AppEvent::PtyOutput`](crate::events::AppEvent::PtyOutput) to the PTY program's screen; returns
+    /// false if the program has already gone
+    pub fn pty_output(&mut self, bytes: &[u8]) -> bool {
+        let Some(ref mut session) = self.pty else {
+            return false;
+        };
+        session.process_output(bytes);
+        if let Some(block) = self.blocks.last_mut() {
+            block.mark_output();
+        }
+        true
+    }
+
+    /// Note that the PTY program's output has ended
+    pub fn pty_output_closed(&mut self) {
+        if let Some(ref mut session) = self.pty {
+            session.output_closed();
+        }
+    }
+
+    /// Return to the prompt once the PTY program exits
+    ///
+    /// Returns true if anything changed on screen.
+    pub fn poll_pty(&mut self) -> bool {
+        let Some(ref mut session) = self.pty else {
+            return false;
+        };
+        let Some(exit_code) = session.try_finish() else {
+            return false;
+        };
+
+        let transcript = session.transcript();
+        let command = session.command.clone();
+        self.pty = None;
+        self.mode = AppMode::Normal;
+
+        for line in transcript {
+            self.add_output(&line);
+        }
+        self.set_block_exit(exit_code);
+        if exit_code != 0 {
+            self.add_output(&format!("[{command}: exit {exit_code}]"));
+        }
+        self.refresh_git_status(false);
+        true
+    }
+
+    /// Get prompt as styled spans for colored rendering, following `prompt.format`
+    pub fn prompt_spans(&self) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        for token in &self.prompt_format {
+            match token {
+                PromptToken::Text(text) => spans.push(Span::raw(text.clone())),
+                PromptToken::Cwd => spans.push(self.cwd_span()),
+                PromptToken::Git => self.push_git_spans(&mut spans),
+                PromptToken::Profile => spans.push(Span::raw(self.profile_name())),
+                PromptToken::Note => {
+                    if self.notes.has(&self.current_dir) {
+                        spans.push(Span::styled(
+                            format!("{} ", Icons::new().note()),
+                            Style::default().fg(self.theme.prompt_note),
+                        ));
+                    }
+                }
+                PromptToken::Status => {
+                    let pieces = self.status_segment.pieces();
+                    if !pieces.is_empty() {
+                        spans.extend(segment::spans(pieces, &self.theme));
+                        spans.push(Span::raw(" "));
+                    }
+                }
+                PromptToken::Duration => {
+                    // Duration of the previous command, when it was slow
+                    if let Some(duration) = self.reported_duration() {
+                        spans.push(Span::styled(
+                            format!("took {} ", format_duration(duration)),
+                            Style::default().fg(self.theme.prompt_duration),
+                        ));
+                    }
+                }
+            }
+        }
+        spans
+    }
+
+    /// Working directory (with ~ replacement) for the prompt
+    fn cwd_span(&self) -> Span<'static> {
+        let dir = self.current_dir.display().to_string();
+        let home = dirs::home_dir().map(|h| h.display().to_string());
+
+        let display_dir = if let Some(home_path) = home {
+            if dir.starts_with(&home_path) {
+                dir.replacen(&home_path, "~", 1)
+            } else {
+                dir
+            }
+        } else {
+            dir
+        };
+
+        Span::styled(
+            display_dir,
+            Style::default()
+                .fg(self.theme.prompt_path)
+                .add_modifier(Modifier::BOLD),
+        )
+    }
+
+    /// Git branch and status for the prompt, followed by a space
+    fn push_git_spans(&self, spans: &mut Vec<Span<'static>>) {
+        let Some(ref git) = self.git_status else {
+            return;
+        };
+        if git.branch.is_empty() {
+            return;
+        }
+
+        // Branch in muted gray
+        spans.push(Span::styled(
+            git.branch.clone(),
+            Style::default().fg(self.theme.git_branch),
+        ));
+
+        // Dirty indicator
+        if git.is_dirty {
+            spans.push(Span::styled(
+                "*".to_string(),
+                Style::default().fg(self.theme.git_branch),
+            ));
+        }
+
+        // An unfinished rebase or merge stands out from the counts
+        if let Some(operation) = git.operation {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                operation.label(),
+                Style::default()
+                    .fg(self.theme.git_operation)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        // Counts by kind: unmerged (the conflicts key lists them), staged,
+        // modified, untracked, and stashed
+        let counts = [
+            ("!", git.changes.conflicts, self.theme.block_failure),
+            ("+", git.changes.staged, self.theme.git_staged),
+            ("~", git.changes.modified, self.theme.git_modified),
+            ("?", git.changes.untracked, self.theme.git_untracked),
+            ("\u{2691}", git.stashes, self.theme.git_stash),
+        ];
+        for (symbol, count, color) in counts {
+            if count > 0 {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    format!("{symbol}{count}"),
+                    Style::default().fg(color),
+                ));
+            }
+        }
+
+        // Ahead indicator in cyan
+        if git.ahead > 0 {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!("↑{}", git.ahead),
+                Style::default().fg(self.theme.git_ahead_behind),
+            ));
+        }
+
+        // Behind indicator in cyan
+        if git.behind > 0 {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!("↓{}", git.behind),
+                Style::default().fg(self.theme.git_ahead_behind),
+            ));
+        }
+
+        spans.push(Span::raw(" "));
+    }
+
+    /// Get prompt as plain string (for output history and cursor calculations)
+    pub fn prompt_string(&self) -> String {
+        self.prompt_spans()
+            .iter()
+            .map(|s| s.content.as_ref())
+            .collect::<Vec<_>>()
+            .join("")
+    }
+
+    /// Get the current prompt string (backward compatibility)
+    pub fn prompt(&self) -> String {
+        self.prompt_string()
+    }
+
+    /// Add a line to the output buffer
+    pub fn add_output(&mut self, line: &str) {
+        self.output.push(line.to_string());
+    }
+
+    /// Add a line a command printed, remembering whether it was on stderr
+    pub fn add_command_output(&mut self, stream: OutputStream, line: &str) {
+        if stream == OutputStream::Stderr {
+            self.stderr_lines.insert(self.output.len());
+        }
+        self.add_output(line);
+    }
+
+    /// Apply the current project's policy to `command`, printing what its
+    /// rules say; false when one blocks it
+    ///
+    /// A policy file that can't be read is reported and otherwise ignored.
+    pub fn check_policy(&mut self, command: &str) -> bool {
+        let Some(path) = policy::find(&self.current_dir) else {
+            return true;
+        };
+        let policy = match Policy::load(&path) {
+            Ok(policy) => policy,
+            Err(e) => {
+                self.add_output(&format!("jerm: {}: {e}", path.display()));
+                return true;
+            }
+        };
+        let rules: Vec<_> = policy
+            .check(command, &self.env)
+            .into_iter()
+            .cloned()
+            .collect();
+        let mut allowed = true;
+        for rule in rules {
+            // Blocks read as errors
+            let (stream, line) = match rule.action {
+                PolicyAction::Warn => (
+                    OutputStream::Stdout,
+                    format!("\u{26a0} project policy: {}", rule.command),
+                ),
+                PolicyAction::Block => {
+                    allowed = false;
+                    (
+                        OutputStream::Stderr,
+                        format!("\u{2717} blocked by project policy: {}", rule.command),
+                    )
+                }
+            };
+            self.add_command_output(stream, &line);
+            if let Some(message) = rule.message {
+                self.add_command_output(stream, &format!("  {message}"));
+            }
+        }
+        if !allowed {
+            let path = path.display().to_string();
+            self.add_command_output(OutputStream::Stderr, &format!("  (see {path})"));
+        }
+        allowed
+    }
+
+    /// Add the current command to output (with prompt)
+    ///
+    /// Continuation lines of a multi-line command get a `> ` prefix.
+    pub fn add_command_to_output(&mut self, command: &str) {
+        let prompt = self.prompt();
+        let mut lines = command.split('\n');
+        self.add_output(&format!("{prompt}{}", lines.next().unwrap_or("")));
+        for line in lines {
+            self.add_output(&format!("> {line}"));
+        }
+    }
+
+    /// Start a new command block at the current end of the output
+    pub fn begin_block(&mut self, command: &str) {
+        let cwd = self.current_dir.display().to_string();
+        let mut block = CommandBlock::new(command, &cwd, self.output.len());
+        block.rerun_of = self.rerun.take();
+        self.blocks.push(block);
+    }
+
+    /// Record the exit code of the most recent block if it isn't set yet,
+    /// and summarize the errors in its output
+    pub fn set_block_exit(&mut self, exit_code: i32) {
+        let diff = self.rerun_diff();
+        if let Some(block) = self.blocks.last_mut() {
+            if block.exit_code.is_none() {
+                block.finish(exit_code);
+                let output = self.output.get(block.output_start..).unwrap_or_default();
+                block.diagnostics =
+                    Tool::detect(&block.command).and_then(|tool| Diagnostics::scan(tool, output));
+                block.diff = diff;
+            }
+        }
+    }
+
+    /// Compare the latest block's output with the block it re-ran
+    fn rerun_diff(&self) -> Option<OutputDiff> {
+        if !self.config.ui.diff_reruns {
+            return None;
+        }
+        let block = self.blocks.last()?;
+        let previous = self.blocks.get(block.rerun_of?)?;
+        let end = self.blocks.get(block.rerun_of? + 1)?.output_start;
+        // Output starts after each block's prompt line
+        let old = self.output.get(previous.output_start + 1..end)?;
+        let new = self
+            .output
+            .get(block.output_start + 1..)
+            .unwrap_or_default();
+        Some(OutputDiff::compute(&previous.time_label(), old, new))
+    }
+
+    /// Show the bytes of the block at the top of the view (or the
+    /// latest one when following output)
+    pub fn enter_hex_view(&mut self) {
+        let top = self.output_scroll.unwrap_or(usize::MAX);
+        let Some(index) = self.blocks.iter().rposition(|b| b.output_start <= top) else {
+            return;
+        };
+        let block = &self.blocks[index];
+        let end = self
+            .blocks
+            .get(index + 1)
+            .map_or(self.output.len(), |next| next.output_start);
+        // Output starts after the block's prompt line
+        let lines = self
+            .output
+            .get(block.output_start + 1..end)
+            .unwrap_or_default();
+        self.hex_view = Some(HexView::new(&block.command, lines));
+        self.mode = AppMode::HexView;
+    }
+
+    /// Close the hex dump
+    pub fn exit_hex_view(&mut self) {
+        self.hex_view = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// List the variables commands run with
+    pub fn enter_env_panel(&mut self) {
+        self.env_panel = Some(EnvPanel::new(&self.env));
+        self.mode = AppMode::Env;
+    }
+
+    /// Close the variable list
+    pub fn exit_env_panel(&mut self) {
+        self.env_panel = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Start selecting output, on the line at the top of the view (or the
+    /// last line when following output)
+    pub fn enter_copy_mode(&mut self) {
+        if self.output.is_empty() {
+            return;
+        }
+        let start = self.output_scroll.unwrap_or(self.output.len() - 1);
+        self.copy_mode = Some(CopyMode::new(self.output.len(), start));
+        self.mode = AppMode::Copy;
+    }
+
+    /// Start typing a search of the output
+    pub fn start_search(&mut self) {
+        self.search.get_or_insert_with(OutputSearch::new).editing = true;
+        self.mode = AppMode::Search;
+    }
+
+    /// Close the search, leaving the view where it is
+    pub fn close_search(&mut self) {
+        self.search = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Change the search query, moving the view to the newest match
+    pub fn set_search_query(&mut self, query: String) {
+        let Some(ref mut search) = self.search else {
+            return;
+        };
+        search.set_query(query, &self.output);
+        self.show_search_match();
+    }
+
+    /// Move `delta` matches down the output (negative for up)
+    pub fn move_search(&mut self, delta: isize) {
+        if let Some(ref mut search) = self.search {
+            search.move_by(delta);
+            self.show_search_match();
+        }
+    }
+
+    /// Scroll the current match into view, a few lines from the top
+    fn show_search_match(&mut self) {
+        let Some(line) = self.search.as_ref().and_then(OutputSearch::current) else {
+            return;
+        };
+        let context = self.pane_size.0 as usize / 3;
+        self.output_scroll = Some(line.line.saturating_sub(context));
+    }
+
+    /// Open the test view and run the tests of the project around the
+    /// current directory, or `command` in its place
+    pub fn enter_test_mode(&mut self, command: Option<String>) {
+        let project = match (runner::detect(&self.current_dir), command) {
+            (Some(project), None) => project,
+            (Some(project), Some(command)) => Project { command, ..project },
+            (None, Some(command)) => Project {
+                root: self.current_dir.clone(),
+                command,
+            },
+            (None, None) => {
+                self.add_output(
+                    "jerm test: no project found here; pass a command: jerm test <command>",
+                );
+                self.set_block_exit(1);
+                return;
+            }
+        };
+        let mut tests = TestRunner::new(project, self.env.clone(), self.events.sender());
+        tests.run();
+        self.tests = Some(tests);
+        self.mode = AppMode::Tests;
+    }
+
+    /// Close the test view, stopping its run and watch
+    pub fn exit_test_mode(&mut self) {
+        self.tests = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Stream the test view's output; true when anything changed
+    pub fn poll_tests(&mut self) -> bool {
+        self.tests.as_mut().is_some_and(TestRunner::poll)
+    }
+
+    /// Quit on a press of the quit chord bound to `action`, if
+    /// `ui.quit_keys` allows it
+    pub fn request_quit(&mut self, action: Action) {
+        let press = self
+            .quit_guard
+            .press(self.config.ui.quit_keys, action, Instant::now());
+        if press == QuitPress::Quit {
+            self.should_quit = true;
+        }
+    }
+
+    /// Hint for a quit chord that was pressed but didn't quit
+    pub fn quit_hint(&self) -> Option<String> {
+        let action = self.quit_guard.armed(Instant::now())?;
+        match self.config.ui.quit_keys {
+            QuitKeys::Double => {
+                let chord = self.keymap.chord(action)?;
+                Some(format!("{chord} again to quit"))
+            }
+            QuitKeys::Off => Some("type exit to quit".to_string()),
+            QuitKeys::Single => None,
+        }
+    }
+
+    /// Close copy mode
+    pub fn exit_copy_mode(&mut self) {
+        self.copy_mode = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Copy the selected lines to the system clipboard and close copy mode
+    pub fn copy_selection(&mut self) {
+        let Some(copy) = self.copy_mode.take() else {
+            return;
+        };
+        self.mode = AppMode::Normal;
+        let text = copy.text(&self.output);
+        let count = copy.selection().count();
+        let lines = if count == 1 { "line" } else { "lines" };
+        match crate::clipboard::copy(&text) {
+            Ok(method) => self.add_output(&format!(
+                "jerm: copied {count} {lines} ({})",
+                method.label()
+            )),
+            Err(e) => self.add_output(&format!("jerm: copy failed: {e}")),
+        }
+        self.copied = Some(text);
+    }
+
+    /// Track input changes and look up the typed command's synopsis once
+    /// typing pauses
+    ///
+    /// Returns true if a synopsis arrived or the pause just became long
+    /// enough to show one.
+    pub fn poll_synopsis(&mut self) -> bool {
+        let mut changed = self.synopsis.poll();
+        if self.synopsis_input.0 != self.input {
+            self.synopsis_input = (self.input.clone(), Instant::now(), false);
+        }
+        if !self.show_synopsis || self.synopsis_input.1.elapsed() < SYNOPSIS_DELAY {
+            return changed;
+        }
+        if !self.synopsis_input.2 {
+            self.synopsis_input.2 = true;
+            changed = true;
+        }
+        if let Some(command) = synopsis_command(&self.input) {
+            let command = command.to_string();
+            self.synopsis.request(&command);
+        }
+        changed
+    }
+
+    /// Measure the input again when it or the current directory changed
+    pub fn poll_input_stats(&mut self) {
+        if self.input_stats.0 != self.input || self.input_stats.1 != self.current_dir {
+            let stats = InputStats::measure(&self.input, &self.current_dir);
+            self.input_stats = (self.input.clone(), self.current_dir.clone(), stats);
+        }
+    }
+
+    /// Whether the input is a directory `shell.auto_cd` would change into,
+    /// checked again only when the input or the current directory changed
+    pub fn input_is_auto_cd(&mut self) -> bool {
+        if !self.config.shell.auto_cd {
+            return false;
+        }
+        if self.auto_cd_input.0 != self.input || self.auto_cd_input.1 != self.current_dir {
+            let is_dir = auto_cd_target(&self.input, &self.current_dir).is_some();
+            self.auto_cd_input = (self.input.clone(), self.current_dir.clone(), is_dir);
+        }
+        self.auto_cd_input.2
+    }
+
+    /// Size of the input, as of the last [`Self::poll_input_stats`]
+    pub fn input_stats(&self) -> InputStats {
+        self.input_stats.2
+    }
+
+    /// Synopsis line for the command being typed, once typing has paused
+    pub fn synopsis_line(&self) -> Option<String> {
+        if !self.show_synopsis
+            || self.mode != AppMode::Normal
+            || self.running.is_some()
+            || self.synopsis_input.1.elapsed() < SYNOPSIS_DELAY
+        {
+            return None;
+        }
+        let command = synopsis_command(&self.input)?;
+        let synopsis = self.synopsis.get(command)?;
+        Some(format!("{command}: {synopsis}"))
+    }
+
+    /// Show or hide the synopsis under the input
+    pub fn toggle_synopsis(&mut self) {
+        self.show_synopsis = !self.show_synopsis;
+    }
+
+    /// Switch between wrapping and truncating long output lines
+    pub fn toggle_output_wrap(&mut self) {
+        self.output_wrap = match self.output_wrap {
+            OutputWrap::Wrap => OutputWrap::Truncate,
+            OutputWrap::Truncate => OutputWrap::Wrap,
+        };
+        self.output_hscroll = 0;
+    }
+
+    /// Scroll truncated output sideways by `delta` columns, stopping once
+    /// the longest line is scrolled out of view
+    pub fn scroll_horizontal(&mut self, delta: isize) {
+        let longest = self
+            .output
+            .iter()
+            .map(|line| crate::ansi::strip(line).width())
+            .max()
+            .unwrap_or(0);
+        self.output_hscroll = self
+            .output_hscroll
+            .saturating_add_signed(delta)
+            .min(longest.saturating_sub(1));
+    }
+
+    /// Expand or collapse the error list of the block at the top of the
+    /// scrollback view, or of the latest block that has one
+    pub fn toggle_diagnostics(&mut self) {
+        let top = self.output_scroll.unwrap_or(usize::MAX);
+        let block = self
+            .blocks
+            .iter_mut()
+            .rev()
+            .filter(|block| block.output_start <= top)
+            .find_map(|block| block.diagnostics.as_mut());
+        if let Some(diagnostics) = block {
+            diagnostics.expanded = !diagnostics.expanded;
+        }
+    }
+
+    /// Expand or collapse the changed lines of the re-run block at the top
+    /// of the view (or the latest one when following output)
+    pub fn toggle_diff(&mut self) {
+        let top = self.output_scroll.unwrap_or(usize::MAX);
+        let block = self
+            .blocks
+            .iter_mut()
+            .rev()
+            .filter(|block| block.output_start <= top)
+            .find_map(|block| block.diff.as_mut());
+        if let Some(diff) = block {
+            diff.expanded = !diff.expanded;
+        }
+    }
+
+    /// Duration of the last command, if it took at least `prompt.report_time`
+    pub fn reported_duration(&self) -> Option<Duration> {
+        let total = self.blocks.last()?.timing.total()?;
+        (total >= self.config.report_time()).then_some(total)
+    }
+
+    /// Clear the screen, keeping the output above it in the scrollback
+    pub fn clear_screen(&mut self) {
+        self.clear_mark = self.output.len();
+        self.output_scroll = None;
+    }
+
+    /// Clear all output (and the blocks pointing into it), for `clear --hard`
+    pub fn clear_output(&mut self) {
+        self.output.clear();
+        self.stderr_lines.clear();
+        self.blocks.clear();
+        self.output_scroll = None;
+        self.clear_mark = 0;
+    }
+
+    /// Scroll the output view up by `lines` output lines
+    pub fn scroll_up(&mut self, lines: usize) {
+        let rows = self.pane_size.0 as usize;
+        let top = self
+            .output_scroll
+            .unwrap_or_else(|| self.output.len().saturating_sub(rows).max(self.clear_mark));
+        self.output_scroll = Some(top.saturating_sub(lines));
+    }
+
+    /// Scroll the output view down, returning to follow mode at the bottom
+    pub fn scroll_down(&mut self, lines: usize) {
+        let Some(top) = self.output_scroll else {
+            return;
+        };
+        let rows = self.pane_size.0 as usize;
+        let new_top = top + lines;
+        self.output_scroll = if new_top + rows >= self.output.len() {
+            None
+        } else {
+            Some(new_top)
         };
+    }
 
-        // Trigger initial git status
-        app.refresh_git_status(false);
-        app
+    /// Open the block timeline overlay
+    pub fn enter_timeline_mode(&mut self) {
+        if self.blocks.is_empty() {
+            return;
+        }
+        self.timeline = Timeline::new();
+        self.timeline.matcher = self.config.fuzzy;
+        self.timeline.select_last(&self.blocks);
+        self.mode = AppMode::Timeline;
     }
 
-    /// Refresh git status for current directory
-    pub fn refresh_git_status(&mut self, with_fetch: bool) {
-        let _ = self.git_tx.send(GitMessage::UpdateStatus {
-            dir: self.current_dir.display().to_string(),
-            with_fetch,
-        });
+    /// Close the block timeline overlay
+    pub fn exit_timeline_mode(&mut self) {
+        self.mode = AppMode::Normal;
     }
 
-    /// Poll for git updates from worker thread
-    pub fn poll_git_updates(&mut self) {
-        // Drain all messages from git_rx
-        while let Ok(msg) = self.git_rx.try_recv() {
-            if let GitMessage::StatusUpdate(status) = msg {
-                self.git_status = status;
-            }
+    /// Scroll the output to the selected block and close the timeline
+    pub fn confirm_timeline(&mut self) {
+        if let Some(index) = self.timeline.selected_block(&self.blocks) {
+            self.output_scroll = Some(self.blocks[index].output_start);
         }
+        self.exit_timeline_mode();
+    }
 
-        // Check 30s interval for background fetch
-        if self.last_git_poll.elapsed() >= Duration::from_secs(30) {
-            self.refresh_git_status(true); // with fetch
-            self.last_git_poll = Instant::now();
+    /// Put the selected block's command in the input to be re-run, and close
+    /// the timeline. Returns false when nothing can be re-run right now.
+    pub fn rerun_from_timeline(&mut self) -> bool {
+        if self.running.is_some() {
+            return false;
         }
+        let Some(index) = self.timeline.selected_block(&self.blocks) else {
+            return false;
+        };
+        self.clear_input();
+        self.input = self.blocks[index].command.clone();
+        self.cursor_pos = self.input.len();
+        self.rerun = Some(index);
+        self.exit_timeline_mode();
+        true
     }
 
-    /// Get prompt as styled spans for colored rendering
-    pub fn prompt_spans(&self) -> Vec<Span<'static>> {
-        let mut spans = Vec::new();
+    /// Clear the input buffer
+    pub fn clear_input(&mut self) {
+        self.input.clear();
+        self.cursor_pos = 0;
+        self.history_index = None;
+        self.template = None;
+        self.pasted = false;
+    }
 
-        // Directory (with ~ replacement)
-        let dir = self.current_dir.display().to_string();
-        let home = dirs::home_dir().map(|h| h.display().to_string());
+    /// Return to a clean Normal-mode screen: close popups and overlays and
+    /// follow the bottom of the output again (double Esc)
+    pub fn reset_view(&mut self) {
+        if self.mode != AppMode::Pty {
+            self.mode = AppMode::Normal;
+        }
+        self.completion = None;
+        self.template = None;
+        self.suggestion = None;
+        self.output_scroll = None;
+    }
 
-        let display_dir = if let Some(home_path) = home {
-            if dir.starts_with(&home_path) {
-                dir.replacen(&home_path, "~", 1)
-            } else {
-                dir
-            }
+    /// Whether the input must be confirmed before running: it spans several
+    /// lines, or a paste put more than one command on it
+    pub fn needs_confirmation(&self) -> bool {
+        self.input.trim().contains('\n')
+            || (self.pasted && crate::shell::parser::count_commands(&self.input) > 1)
+    }
+
+    /// Recompute the history autosuggestion for the current input
+    ///
+    /// Suggestions only appear while the cursor is at the end of the line.
+    pub fn update_suggestion(&mut self) {
+        self.suggestion = if self.input.trim().is_empty()
+            || self.cursor_pos != self.input.len()
+            || self.template.is_some()
+        {
+            None
         } else {
-            dir
+            self.history
+                .suggest(&self.input)
+                .map(|command| command[self.input.len()..].to_string())
         };
+    }
 
-        spans.push(Span::styled(
-            display_dir,
-            Style::default().fg(Palette::PROMPT_PATH).add_modifier(Modifier::BOLD),
-        ));
-        spans.push(Span::raw(" "));
-
-        // Git info
-        if let Some(ref git) = self.git_status {
-            if !git.branch.is_empty() {
-                // Branch in muted gray
-                spans.push(Span::styled(
-                    git.branch.clone(),
-                    Style::default().fg(Palette::GIT_BRANCH),
-                ));
-
-                // Dirty indicator
-                if git.is_dirty {
-                    spans.push(Span::styled(
-                        "*".to_string(),
-                        Style::default().fg(Palette::GIT_BRANCH),
-                    ));
-                }
+    /// Accept the autosuggestion into the input
+    pub fn accept_suggestion(&mut self) {
+        if let Some(suggestion) = self.suggestion.take() {
+            self.input.push_str(&suggestion);
+            self.cursor_pos = self.input.len();
+        }
+    }
 
-                // Ahead indicator in cyan
-                if git.ahead > 0 {
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        format!("↑{}", git.ahead),
-                        Style::default().fg(Palette::GIT_AHEAD_BEHIND),
-                    ));
-                }
+    /// Complete the word at the cursor (Tab)
+    ///
+    /// A single match is inserted; several matches are first narrowed to
+    /// their common prefix, then shown in a popup that further Tabs cycle
+    /// through.
+    pub fn complete_input(&mut self) {
+        if let Some(ref mut menu) = self.completion {
+            let start = menu.start;
+            let replacement = menu.next().replacement.clone();
+            self.replace_word(start, &replacement);
+            return;
+        }
 
-                // Behind indicator in cyan
-                if git.behind > 0 {
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        format!("↓{}", git.behind),
-                        Style::default().fg(Palette::GIT_AHEAD_BEHIND),
-                    ));
+        let found = completion::complete(
+            &self.input,
+            self.cursor_pos,
+            &self.current_dir,
+            &mut self.completers,
+            &self.config.fuzzy,
+        );
+        match found.candidates.as_slice() {
+            [] => {}
+            [only] => {
+                let suffix = if only.is_dir { "" } else { " " };
+                let replacement = format!("{}{suffix}", only.replacement);
+                self.replace_word(found.start, &replacement);
+            }
+            candidates => {
+                let prefix = completion::common_prefix(candidates);
+                if prefix.len() > self.cursor_pos - found.start {
+                    self.replace_word(found.start, &prefix);
+                } else {
+                    self.completion = Some(CompletionMenu {
+                        start: found.start,
+                        candidates: found.candidates,
+                        selected: None,
+                    });
                 }
-
-                spans.push(Span::raw(" "));
             }
         }
-
-        spans.push(Span::raw("$ "));
-        spans
     }
 
-    /// Get prompt as plain string (for output history and cursor calculations)
-    pub fn prompt_string(&self) -> String {
-        self.prompt_spans()
-            .iter()
-            .map(|s| s.content.as_ref())
-            .collect::<Vec<_>>()
-            .join("")
+    /// Cycle backwards through the completion popup (Shift+Tab)
+    pub fn complete_prev(&mut self) {
+        if let Some(ref mut menu) = self.completion {
+            let start = menu.start;
+            let replacement = menu.prev().replacement.clone();
+            self.replace_word(start, &replacement);
+        }
     }
 
-    /// Get the current prompt string (backward compatibility)
-    pub fn prompt(&self) -> String {
-        self.prompt_string()
+    /// Replace the input between `start` and the cursor
+    fn replace_word(&mut self, start: usize, text: &str) {
+        self.input.replace_range(start..self.cursor_pos, text);
+        self.cursor_pos = start + text.len();
     }
 
-    /// Add a line to the output buffer
-    pub fn add_output(&mut self, line: &str) {
-        self.output.push(line.to_string());
+    /// Put a snippet on the input line, entering fill-in mode if it has
+    /// placeholders
+    pub fn insert_snippet(&mut self, command: &str) {
+        self.clear_input();
+        match Template::parse(command) {
+            Some(template) => {
+                self.template = Some(template);
+                self.sync_template();
+            }
+            None => {
+                self.input = command.to_string();
+                self.cursor_pos = self.input.len();
+            }
+        }
     }
 
-    /// Add the current command to output (with prompt)
-    pub fn add_command_to_output(&mut self, command: &str) {
-        let prompt = self.prompt();
-        self.add_output(&format!("{prompt}{command}"));
+    /// Re-render the input line from the template being filled in
+    pub fn sync_template(&mut self) {
+        if let Some(ref template) = self.template {
+            (self.input, self.cursor_pos) = template.render();
+        }
     }
 
-    /// Clear the input buffer
-    pub fn clear_input(&mut self) {
-        self.input.clear();
-        self.cursor_pos = 0;
-        self.history_index = None;
+    /// Status line shown while filling in a template
+    pub fn template_status(&self) -> Option<String> {
+        let template = self.template.as_ref()?;
+        Some(format!(
+            "\u{270e} {}  Tab next field · Enter run · Esc edit freely",
+            template.progress()
+        ))
     }
 
-    /// Add command to history
+    /// Add command to history, run in the current directory
     pub fn add_to_history(&mut self, command: &str) {
-        if !command.trim().is_empty() {
-            // Don't add duplicates of the last command
-            if self.history.last().map(String::as_str) != Some(command) {
-                self.history.push(command.to_string());
-            }
-        }
+        self.history.push(command, self.current_dir.clone());
     }
 
     /// Navigate to previous command in history
     pub fn history_prev(&mut self) {
-        if self.history.is_empty() {
+        if self.history_index.is_none() {
+            self.history_view = self.history.navigation_list(&self.current_dir);
+        }
+        if self.history_view.is_empty() {
             return;
         }
 
         let new_index = match self.history_index {
-            None => self.history.len().saturating_sub(1),
+            None => self.history_view.len() - 1,
             Some(0) => 0,
             Some(i) => i.saturating_sub(1),
         };
 
         self.history_index = Some(new_index);
-        self.input = self.history[new_index].clone();
+        self.input = self.history_view[new_index].clone();
         self.cursor_pos = self.input.len();
     }
 
@@ -243,7 +2017,7 @@ impl App {
     pub fn history_next(&mut self) {
         match self.history_index {
             None => {}
-            Some(i) if i >= self.history.len().saturating_sub(1) => {
+            Some(i) if i >= self.history_view.len().saturating_sub(1) => {
                 self.history_index = None;
                 self.input.clear();
                 self.cursor_pos = 0;
@@ -251,38 +2025,121 @@ impl App {
             Some(i) => {
                 let new_index = i + 1;
                 self.history_index = Some(new_index);
-                self.input = self.history[new_index].clone();
+                self.input = self.history_view[new_index].clone();
                 self.cursor_pos = self.input.len();
             }
         }
     }
 
+    /// Insert text (e.g. a paste) at the cursor position
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.input.insert_str(self.cursor_pos, &text);
+        self.cursor_pos += text.len();
+    }
+
     /// Insert a character at the cursor position
     pub fn insert_char(&mut self, c: char) {
         self.input.insert(self.cursor_pos, c);
-        self.cursor_pos += 1;
+        self.cursor_pos += c.len_utf8();
     }
 
-    /// Delete the character before the cursor
+    /// Delete the character (grapheme) before the cursor
     pub fn delete_char(&mut self) {
-        if self.cursor_pos > 0 {
-            self.cursor_pos -= 1;
-            self.input.remove(self.cursor_pos);
-        }
+        let start = edit::prev_grapheme(&self.input, self.cursor_pos);
+        self.input.drain(start..self.cursor_pos);
+        self.cursor_pos = start;
     }
 
     /// Move cursor left
     pub fn cursor_left(&mut self) {
-        self.cursor_pos = self.cursor_pos.saturating_sub(1);
+        self.cursor_pos = edit::prev_grapheme(&self.input, self.cursor_pos);
     }
 
     /// Move cursor right
     pub fn cursor_right(&mut self) {
-        if self.cursor_pos < self.input.len() {
-            self.cursor_pos += 1;
+        self.cursor_pos = edit::next_grapheme(&self.input, self.cursor_pos);
+    }
+
+    /// Move the cursor to the start of the word before it
+    pub fn word_left(&mut self) {
+        self.cursor_pos = edit::word_start(&self.input, self.cursor_pos);
+    }
+
+    /// Move the cursor to the end of the word after it
+    pub fn word_right(&mut self) {
+        self.cursor_pos = edit::word_end(&self.input, self.cursor_pos);
+    }
+
+    /// Kill back to the previous whitespace (Ctrl+W)
+    pub fn kill_word_back(&mut self) {
+        let start = edit::rubout_start(&self.input, self.cursor_pos);
+        self.kill(start..self.cursor_pos, Direction::Backward);
+    }
+
+    /// Kill to the end of the word after the cursor (Alt+D)
+    pub fn kill_word_forward(&mut self) {
+        let end = edit::word_end(&self.input, self.cursor_pos);
+        self.kill(self.cursor_pos..end, Direction::Forward);
+    }
+
+    /// Kill from the cursor to the end of the line (Ctrl+K)
+    pub fn kill_to_end(&mut self) {
+        self.kill(self.cursor_pos..self.input.len(), Direction::Forward);
+    }
+
+    fn kill(&mut self, range: std::ops::Range<usize>, direction: Direction) {
+        let text: String = self.input.drain(range.clone()).collect();
+        self.kill_ring.kill(text, direction);
+        self.cursor_pos = range.start;
+    }
+
+    /// Insert the most recent kill at the cursor (Ctrl+Y)
+    pub fn yank(&mut self) {
+        if let Some(text) = self.kill_ring.yank(self.cursor_pos) {
+            self.input.insert_str(self.cursor_pos, text);
+            self.cursor_pos += text.len();
+        }
+    }
+
+    /// Replace the text just yanked with the kill before it (Alt+Y)
+    pub fn yank_pop(&mut self) {
+        if let Some((range, text)) = self.kill_ring.yank_pop() {
+            self.input.replace_range(range.clone(), text);
+            self.cursor_pos = range.start + text.len();
+        }
+    }
+
+    /// The input and cursor position now, for [`App::record_edit`]
+    pub fn input_state(&self) -> InputState {
+        (self.input.clone(), self.cursor_pos)
+    }
+
+    /// Note what a key did to the input, given the state it found
+    pub fn record_edit(&mut self, before: InputState, kind: EditKind) {
+        self.edits.record(before, &self.input, kind);
+    }
+
+    /// Put back the input as it was before the last edit
+    pub fn undo(&mut self) {
+        if let Some(state) = self.edits.undo(self.input_state()) {
+            self.restore_input(state);
         }
     }
 
+    /// Reapply the edit the last undo took back
+    pub fn redo(&mut self) {
+        if let Some(state) = self.edits.redo(self.input_state()) {
+            self.restore_input(state);
+        }
+    }
+
+    fn restore_input(&mut self, (input, cursor): InputState) {
+        self.input = input;
+        self.cursor_pos = cursor;
+        self.completion = None;
+    }
+
     /// Move cursor to start of input
     pub fn cursor_home(&mut self) {
         self.cursor_pos = 0;
@@ -305,10 +2162,127 @@ impl App {
         self.mode = AppMode::Normal;
     }
 
+    /// Open the comparison view for two directories
+    pub fn enter_diffdir_mode(&mut self, a: PathBuf, b: PathBuf) {
+        self.dir_diff = Some(DirDiff::new(a, b));
+        self.mode = AppMode::DirDiff;
+    }
+
+    /// Close the comparison view
+    pub fn exit_diffdir_mode(&mut self) {
+        self.dir_diff = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Open the activity heatmap of the command history
+    pub fn enter_heatmap_mode(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        self.heatmap = Some(Heatmap::new(self.history.entries(), &chrono::Local, today));
+        self.mode = AppMode::Heatmap;
+    }
+
+    /// Close the activity heatmap
+    pub fn exit_heatmap_mode(&mut self) {
+        self.heatmap = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// List the conflicted files of the current repository
+    pub fn enter_conflicts_mode(&mut self) {
+        match ConflictList::load(&self.current_dir) {
+            Ok(list) if list.files.is_empty() => self.add_output("jerm: no merge conflicts here"),
+            Ok(list) => {
+                self.conflicts = Some(list);
+                self.mode = AppMode::Conflicts;
+            }
+            Err(e) => self.add_output(&format!("jerm: conflicts: {e}")),
+        }
+    }
+
+    /// Close the conflicts overlay
+    pub fn exit_conflicts_mode(&mut self) {
+        self.conflicts = None;
+        self.mode = AppMode::Normal;
+        self.refresh_git_status(false);
+    }
+
+    /// Open the branch dashboard; the worker fills it in
+    pub fn enter_git_overview_mode(&mut self) {
+        let _ = self.git.handle().send(GitMessage::Overview {
+            dir: self.current_dir.display().to_string(),
+        });
+        self.git_overview = Some(GitOverview::default());
+        self.mode = AppMode::GitOverview;
+    }
+
+    /// Close the branch dashboard
+    pub fn exit_git_overview_mode(&mut self) {
+        self.git_overview = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Open the branch switcher; the worker lists the branches
+    pub fn enter_branch_switch_mode(&mut self) {
+        let _ = self.git.handle().send(GitMessage::Overview {
+            dir: self.current_dir.display().to_string(),
+        });
+        self.branch_switcher = Some(BranchSwitcher::default());
+        self.mode = AppMode::BranchSwitch;
+    }
+
+    /// Close the branch switcher
+    pub fn exit_branch_switch_mode(&mut self) {
+        self.branch_switcher = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Open or close the git status panel; the worker fills it in
+    pub fn toggle_git_panel(&mut self) {
+        if self.mode == AppMode::GitStatus {
+            self.git_panel = None;
+            self.mode = AppMode::Normal;
+            return;
+        }
+        let _ = self.git.handle().send(GitMessage::Details {
+            dir: self.current_dir.display().to_string(),
+        });
+        self.git_panel = Some(GitPanel::default());
+        self.mode = AppMode::GitStatus;
+    }
+
+    /// Open the gallery of installed themes
+    pub fn enter_theme_gallery_mode(&mut self) {
+        self.theme_gallery = Some(ThemeGallery::load(&self.theme.name));
+        self.mode = AppMode::ThemeGallery;
+    }
+
+    /// Close the theme gallery
+    pub fn exit_theme_gallery_mode(&mut self) {
+        self.theme_gallery = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Open the current directory's note in the scratchpad pane
+    pub fn open_note(&mut self) {
+        let text = self.notes.get(&self.current_dir).unwrap_or_default();
+        self.note = Some(Scratchpad::new(self.current_dir.clone(), text));
+        self.mode = AppMode::Note;
+    }
+
+    /// Save the note being edited and close the pane
+    pub fn close_note(&mut self) {
+        self.mode = AppMode::Normal;
+        if let Some(pad) = self.note.take() {
+            if let Err(e) = self.notes.set(&pad.dir, &pad.text()) {
+                self.add_output(&format!("jerm note: {e}"));
+            }
+        }
+    }
+
     /// Confirm navigation and change to selected directory
     pub fn confirm_navigation(&mut self) {
         if let Some(selected_path) = self.navigation_state.get_selected_path() {
-            self.current_dir = selected_path;
+            self.change_dir(selected_path);
         }
         self.exit_navigation_mode();
     }
@@ -318,12 +2292,64 @@ impl App {
         if !self.shortcuts.is_empty() {
             self.mode = AppMode::ShortcutSelection;
             self.selected_shortcut_index = 0;
+            self.goto_filter.clear();
         }
     }
 
     /// Exit shortcut selection mode
     pub fn exit_goto_mode(&mut self) {
         self.mode = AppMode::Normal;
+        self.goto_filter.clear();
+    }
+
+    /// Shortcuts listed in the sidebar: the first nine, or in goto mode with
+    /// a filter, the nine best fuzzy matches on name or path
+    pub fn goto_candidates(&self) -> Vec<&Shortcut> {
+        let shortcuts = self.shortcuts.get_shortcuts();
+        if self.mode != AppMode::ShortcutSelection || self.goto_filter.is_empty() {
+            return shortcuts.into_iter().take(9).collect();
+        }
+
+        let mut scored: Vec<(i64, &Shortcut)> = shortcuts
+            .into_iter()
+            .filter_map(|shortcut| {
+                let name = self
+                    .config
+                    .fuzzy
+                    .score(&self.goto_filter, &shortcut.label());
+                let path = self
+                    .config
+                    .fuzzy
+                    .score(&self.goto_filter, &shortcut.display_name());
+                Some((name.max(path)?, shortcut))
+            })
+            .collect();
+        // Stable, so equal scores keep the sidebar order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored
+            .into_iter()
+            .take(9)
+            .map(|(_, shortcut)| shortcut)
+            .collect()
+    }
+
+    /// Path of the shortcut highlighted in goto mode
+    fn goto_selected_path(&self) -> Option<PathBuf> {
+        self.goto_candidates()
+            .get(self.selected_shortcut_index)
+            .map(|shortcut| shortcut.path.clone())
+    }
+
+    /// Add a character to the goto filter and select the top match
+    pub fn goto_push_filter(&mut self, c: char) {
+        self.goto_filter.push(c);
+        self.selected_shortcut_index = 0;
+    }
+
+    /// Remove the last character from the goto filter
+    pub fn goto_pop_filter(&mut self) {
+        self.goto_filter.pop();
+        self.selected_shortcut_index = 0;
     }
 
     /// Move selection up in shortcut list
@@ -335,7 +2361,7 @@ impl App {
 
     /// Move selection down in shortcut list
     pub fn goto_move_down(&mut self) {
-        let max_index = self.shortcuts.get_shortcuts().len().saturating_sub(1).min(8);
+        let max_index = self.goto_candidates().len().saturating_sub(1);
         if self.selected_shortcut_index < max_index {
             self.selected_shortcut_index += 1;
         }
@@ -343,18 +2369,187 @@ impl App {
 
     /// Confirm shortcut selection and navigate
     pub fn confirm_goto(&mut self) {
-        if let Some(shortcut) = self.shortcuts.get_shortcut(self.selected_shortcut_index + 1) {
-            let path = shortcut.path.clone();
-            if path.is_dir() {
-                self.add_output(&format!("cd {}", path.display()));
-                self.current_dir = path.clone();
-                self.shortcuts.touch_shortcut(&path);
-            } else {
-                self.add_output(&format!("Error: {} no longer exists", path.display()));
-            }
+        if let Some(path) = self.goto_selected_path() {
+            self.jump_to_shortcut(path);
         }
         self.exit_goto_mode();
     }
+
+    /// Pin or unpin the shortcut highlighted in goto mode, keeping it selected
+    pub fn goto_toggle_pin(&mut self) {
+        let Some(path) = self.goto_selected_path() else {
+            return;
+        };
+        self.shortcuts.toggle_pin(&path);
+        self.goto_select_path(&path);
+    }
+
+    /// Move the pinned shortcut highlighted in goto mode up or down
+    pub fn goto_move_pinned(&mut self, up: bool) {
+        let Some(path) = self.goto_selected_path() else {
+            return;
+        };
+        if self.shortcuts.move_pinned(&path, up) {
+            self.goto_select_path(&path);
+        }
+    }
+
+    /// Highlight a shortcut in goto mode if it's listed
+    fn goto_select_path(&mut self, path: &PathBuf) {
+        let candidates = self.goto_candidates();
+        if let Some(index) = candidates.iter().position(|s| &s.path == path) {
+            self.selected_shortcut_index = index;
+        }
+    }
+
+    /// Ask the main loop to hand the terminal to a plain shell
+    pub fn request_shell(&mut self) {
+        if self.readonly {
+            self.add_output("jerm shell: disabled in readonly mode");
+        } else if self.running.is_some() {
+            self.add_output("jerm shell: wait for the running command to finish");
+        } else {
+            self.shell_requested = true;
+        }
+    }
+
+    /// Delete the shortcut highlighted in goto mode, leaving the mode when
+    /// none are left
+    pub fn goto_remove_selected(&mut self) {
+        let Some(path) = self.goto_selected_path() else {
+            return;
+        };
+        self.shortcuts.remove_shortcut(&path);
+        self.add_output(&format!("Shortcut removed: {}", path.display()));
+
+        if self.shortcuts.is_empty() {
+            self.exit_goto_mode();
+        } else {
+            let visible = self.goto_candidates().len();
+            self.selected_shortcut_index =
+                self.selected_shortcut_index.min(visible.saturating_sub(1));
+        }
+    }
+
+    /// Change the working directory and count the visit for `z`
+    pub fn change_dir(&mut self, path: PathBuf) {
+        self.dir_tracker.record(&path);
+        if !self.visited_dirs.contains(&path) {
+            self.visited_dirs.push(path.clone());
+        }
+        // Hooks run commands, which readonly mode doesn't allow
+        if path != self.current_dir && !self.readonly {
+            self.hooks
+                .run(&self.config.hooks.chpwd, &path, &self.current_dir);
+        }
+        self.current_dir = path;
+    }
+
+    /// Refresh the status segment when it's due; readonly mode runs no
+    /// commands
+    pub fn poll_status_segment(&mut self) -> bool {
+        !self.readonly && self.status_segment.poll(&self.current_dir)
+    }
+
+    /// Report chpwd hooks that failed, returning true if any did
+    pub fn poll_hooks(&mut self) -> bool {
+        let failures = self.hooks.poll();
+        for failure in &failures {
+            self.add_output(&format!("jerm: {failure}"));
+            for line in &failure.output {
+                self.add_output(&format!("  {line}"));
+            }
+        }
+        !failures.is_empty()
+    }
+
+    /// Change to a shortcut's directory and mark it as used; returns false
+    /// if the directory no longer exists
+    pub fn jump_to_shortcut(&mut self, path: PathBuf) -> bool {
+        if path.is_dir() {
+            self.add_output(&format!("cd {}", path.display()));
+            self.change_dir(path.clone());
+            self.shortcuts.touch_shortcut(&path);
+            true
+        } else {
+            self.add_output(&format!("Error: {} no longer exists", path.display()));
+            false
+        }
+    }
+
+    /// Start recording commands into a new macro
+    pub fn start_recording(&mut self, name: &str) {
+        self.recording = Some(Macro::new(name));
+    }
+
+    /// Stop recording and return the recorded macro
+    pub fn stop_recording(&mut self) -> Option<Macro> {
+        self.recording.take()
+    }
+
+    /// Append a command to the macro being recorded
+    pub fn record_command(&mut self, command: &str) {
+        if let Some(ref mut recording) = self.recording {
+            recording.steps.push(command.to_string());
+        }
+    }
+
+    /// Start playing back a macro
+    pub fn start_playback(&mut self, recorded: Macro) {
+        self.playback = Some(Playback::new(recorded));
+        self.prepare_playback_step();
+    }
+
+    /// Stop macro playback early
+    pub fn stop_playback(&mut self) {
+        if let Some(playback) = self.playback.take() {
+            self.add_output(&format!("Macro {} stopped", playback.recorded.name));
+            self.clear_input();
+        }
+    }
+
+    /// Set up the input line for the current playback step
+    ///
+    /// Steps are pre-filled into the input so they can be reviewed (and
+    /// edited) before running; parameters are typed into the empty input.
+    pub fn prepare_playback_step(&mut self) {
+        let Some(prompt) = self.playback.as_ref().map(Playback::prompt) else {
+            return;
+        };
+
+        match prompt {
+            Some(PlaybackPrompt::Parameter(_)) => self.clear_input(),
+            Some(PlaybackPrompt::Step(command)) => {
+                self.clear_input();
+                self.input = command;
+                self.cursor_end();
+            }
+            None => {
+                if let Some(playback) = self.playback.take() {
+                    self.add_output(&format!("Macro {} finished", playback.recorded.name));
+                }
+                self.clear_input();
+            }
+        }
+    }
+
+    /// Skip the current playback step without running it
+    pub fn skip_playback_step(&mut self) {
+        if let Some(ref mut playback) = self.playback {
+            playback.advance();
+        }
+        self.prepare_playback_step();
+    }
+
+    /// Status line describing the current playback step
+    pub fn playback_status(&self) -> Option<String> {
+        let playback = self.playback.as_ref()?;
+        let hint = match playback.prompt()? {
+            PlaybackPrompt::Parameter(name) => format!("value for {{{name}}}, Enter to accept"),
+            PlaybackPrompt::Step(_) => "Enter run · Ctrl+S skip · Esc stop".to_string(),
+        };
+        Some(format!("\u{25b6} {}  {hint}", playback.progress()))
+    }
 }
 
 impl Default for App {
@@ -366,6 +2561,39 @@ impl Default for App {
 impl Drop for App {
     fn drop(&mut self) {
         // Send shutdown message to git worker thread
-        let _ = self.git_tx.send(GitMessage::Shutdown);
+        let _ = self.git.handle().send(GitMessage::Shutdown);
+
+        // Don't leave a foreground command running after exit
+        if let Some(ref running) = self.running {
+            running.kill();
+        }
+        self.jobs.kill_all();
     }
 }
+
+/// Minimum time between fetches triggered by the window regaining focus
+const FOCUS_FETCH_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// How long typing must pause before the synopsis is shown
+const SYNOPSIS_DELAY: Duration = Duration::from_millis(400);
+
+/// How long the terminal pane stays highlighted after a job finishes
+const JOB_HIGHLIGHT: Duration = Duration::from_secs(2);
+
+/// Longest time between the clicks of a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Narrowest the sidebar gets, whatever `ui.sidebar_ratio` says
+const MIN_SIDEBAR_WIDTH: u16 = 10;
+
+/// Columns the terminal pane keeps however wide the sidebar is set
+const MIN_TERMINAL_WIDTH: u16 = 40;
+
+/// Command name at the start of the input, if it looks like one
+fn synopsis_command(input: &str) -> Option<&str> {
+    let command = input.split_whitespace().next()?;
+    let is_name = command
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'));
+    (is_name && !command.starts_with('.')).then_some(command)
+}
@@ -0,0 +1,100 @@
+//! Keyboard selection over output lines, for copying them out
+//!
+//! Copy mode starts with a cursor on one output line; `v` anchors a
+//! selection there, and moving the cursor extends it. Yanking takes the
+//! selected lines, or just the cursor's line, as plain text.
+
+use std::ops::RangeInclusive;
+
+use crate::ansi;
+
+/// Cursor and selection over the output buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyMode {
+    /// Output line the cursor is on
+    pub cursor: usize,
+    /// Where the selection started, once one has
+    pub anchor: Option<usize>,
+    /// First line shown
+    pub scroll: usize,
+    /// Number of output lines
+    len: usize,
+}
+
+impl CopyMode {
+    /// Start on line `start` of `len` output lines
+    pub fn new(len: usize, start: usize) -> Self {
+        let cursor = start.min(len.saturating_sub(1));
+        Self {
+            cursor,
+            anchor: None,
+            scroll: cursor,
+            len,
+        }
+    }
+
+    /// Move the cursor by `delta` lines, stopping at either end
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.len.saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Start a selection at the cursor, or drop the one there is
+    pub fn toggle_selection(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// Lines a yank takes
+    pub fn selection(&self) -> RangeInclusive<usize> {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        anchor.min(self.cursor)..=anchor.max(self.cursor)
+    }
+
+    /// The selected lines as plain text, escape sequences removed
+    pub fn text(&self, output: &[String]) -> String {
+        output
+            .get(self.selection())
+            .unwrap_or_default()
+            .iter()
+            .map(|line| ansi::strip(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Keep the cursor among the `visible` lines shown
+    pub fn adjust_scroll(&mut self, visible: usize) {
+        let visible = visible.max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + visible {
+            self.scroll = self.cursor + 1 - visible;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_text() {
+        let output: Vec<String> = ["$ ls", "\x1b[34msrc\x1b[0m", "Cargo.toml", "README.md"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut copy = CopyMode::new(output.len(), 2);
+        assert_eq!(copy.text(&output), "Cargo.toml");
+
+        copy.toggle_selection();
+        copy.move_by(-5);
+        assert_eq!(copy.cursor, 0);
+        assert_eq!(copy.text(&output), "$ ls\nsrc\nCargo.toml");
+
+        copy.toggle_selection();
+        copy.move_by(10);
+        assert_eq!(copy.selection(), 3..=3);
+    }
+}
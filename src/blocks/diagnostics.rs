@@ -0,0 +1,209 @@
+//! Error and warning summaries for compiler and test runner output
+//!
+//! Blocks whose command is a recognized tool (rustc/cargo, tsc, pytest) get
+//! their output scanned when they finish. The header shows a count badge, and
+//! the list of matching lines can be expanded under it.
+
+use crate::ansi;
+
+/// A tool whose output format is understood
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Rustc,
+    Tsc,
+    Pytest,
+}
+
+impl Tool {
+    /// Recognize the tool a command line runs, ignoring leading `VAR=value`
+    /// assignments and runners such as `npx` or `python -m`
+    pub fn detect(command: &str) -> Option<Self> {
+        let words: Vec<&str> = command
+            .split_whitespace()
+            .skip_while(|w| w.contains('=') && !w.starts_with('-'))
+            .collect();
+        match words.as_slice() {
+            ["rustc", ..] => Some(Tool::Rustc),
+            ["cargo", sub, ..] if CARGO_SUBCOMMANDS.contains(sub) => Some(Tool::Rustc),
+            ["tsc", ..] | ["npx" | "pnpm" | "yarn", "tsc", ..] => Some(Tool::Tsc),
+            ["pytest" | "py.test", ..] => Some(Tool::Pytest),
+            [python, "-m", "pytest", ..] if python.starts_with("python") => Some(Tool::Pytest),
+            _ => None,
+        }
+    }
+
+    /// Classify one line of the tool's output (ANSI sequences removed)
+    fn classify(self, line: &str) -> Option<Severity> {
+        match self {
+            Tool::Rustc => {
+                if line.starts_with("error: aborting due to")
+                    || line.starts_with("error: could not compile")
+                {
+                    None
+                } else if line.starts_with("error[") || line.starts_with("error:") {
+                    Some(Severity::Error)
+                } else if line.starts_with("warning:")
+                    && !line.contains(" generated ")
+                    && !line.starts_with("warning: build failed")
+                {
+                    Some(Severity::Warning)
+                } else {
+                    None
+                }
+            }
+            Tool::Tsc => {
+                if line.contains(": error TS") || line.contains(" - error TS") {
+                    Some(Severity::Error)
+                } else if line.contains(": warning TS") || line.contains(" - warning TS") {
+                    Some(Severity::Warning)
+                } else {
+                    None
+                }
+            }
+            Tool::Pytest => {
+                let trimmed = line.trim_start();
+                if line.starts_with("FAILED ") || line.starts_with("ERROR ") {
+                    Some(Severity::Error)
+                } else if trimmed.contains("Warning: ") && trimmed.contains(".py:") {
+                    Some(Severity::Warning)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Cargo subcommands that run the compiler
+const CARGO_SUBCOMMANDS: &[&str] = &[
+    "build", "b", "check", "c", "test", "t", "clippy", "run", "r",
+];
+
+/// How serious a reported line is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A reported error or warning line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The line as printed, without escape sequences
+    pub text: String,
+}
+
+/// Errors and warnings found in a block's output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub tool: Tool,
+    pub items: Vec<Diagnostic>,
+    /// Whether the list is shown under the block header
+    pub expanded: bool,
+}
+
+impl Diagnostics {
+    /// Scan a tool's output; `None` when nothing was reported
+    pub fn scan(tool: Tool, lines: &[String]) -> Option<Self> {
+        let items: Vec<Diagnostic> = lines
+            .iter()
+            .filter_map(|line| {
+                let text = ansi::strip(line);
+                let severity = tool.classify(&text)?;
+                Some(Diagnostic { severity, text })
+            })
+            .collect();
+
+        (!items.is_empty()).then_some(Self {
+            tool,
+            items,
+            expanded: false,
+        })
+    }
+
+    /// Number of error lines
+    pub fn errors(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    /// Number of warning lines
+    pub fn warnings(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.items.iter().filter(|d| d.severity == severity).count()
+    }
+
+    /// Badge text such as `2 errors, 1 warning`
+    pub fn badge(&self) -> String {
+        let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+        let mut parts = Vec::new();
+        if self.errors() > 0 {
+            parts.push(plural(self.errors(), "error"));
+        }
+        if self.warnings() > 0 {
+            parts.push(plural(self.warnings(), "warning"));
+        }
+        parts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Tool::detect("cargo build --release"), Some(Tool::Rustc));
+        assert_eq!(Tool::detect("RUST_LOG=1 cargo test"), Some(Tool::Rustc));
+        assert_eq!(Tool::detect("rustc main.rs"), Some(Tool::Rustc));
+        assert_eq!(Tool::detect("npx tsc --noEmit"), Some(Tool::Tsc));
+        assert_eq!(Tool::detect("python3 -m pytest -x"), Some(Tool::Pytest));
+        assert_eq!(Tool::detect("pytest"), Some(Tool::Pytest));
+        assert_eq!(Tool::detect("cargo fmt"), None);
+        assert_eq!(Tool::detect("ls"), None);
+    }
+
+    #[test]
+    fn test_scan_rustc() {
+        let output = lines(
+            "\x1b[1;33mwarning\x1b[0m: unused variable: `x`\n\
+             \x20--> src/main.rs:2:9\n\
+             error[E0308]: mismatched types\n\
+             warning: `demo` (bin \"demo\") generated 1 warning\n\
+             error: aborting due to 1 previous error",
+        );
+        let diagnostics = Diagnostics::scan(Tool::Rustc, &output).unwrap();
+        assert_eq!(diagnostics.errors(), 1);
+        assert_eq!(diagnostics.warnings(), 1);
+        assert_eq!(diagnostics.items[0].text, "warning: unused variable: `x`");
+        assert_eq!(diagnostics.badge(), "1 error, 1 warning");
+    }
+
+    #[test]
+    fn test_scan_tsc_and_pytest() {
+        let output = lines(
+            "src/app.ts(3,7): error TS2322: Type 'string' is not assignable\n\
+             src/b.ts:1:1 - error TS1005: ';' expected",
+        );
+        let diagnostics = Diagnostics::scan(Tool::Tsc, &output).unwrap();
+        assert_eq!(diagnostics.badge(), "2 errors");
+
+        let output = lines(
+            "tests/test_a.py::test_ok PASSED\n\
+             \x20 tests/test_a.py:4: DeprecationWarning: old api\n\
+             FAILED tests/test_a.py::test_bad - assert 1 == 2",
+        );
+        let diagnostics = Diagnostics::scan(Tool::Pytest, &output).unwrap();
+        assert_eq!(diagnostics.errors(), 1);
+        assert_eq!(diagnostics.warnings(), 1);
+
+        assert!(Diagnostics::scan(Tool::Pytest, &lines("1 passed in 0.01s")).is_none());
+    }
+}
@@ -0,0 +1,175 @@
+//! Line diff between two runs of the same command
+//!
+//! Re-running a block from the timeline compares the new output with the
+//! output of the block it re-ran. The header shows how many lines were added
+//! and removed, and the changed lines can be expanded under it.
+
+use crate::ansi;
+
+/// Largest number of line pairs compared exactly; past this the changed
+/// middle of the outputs is reported as entirely replaced
+const MAX_CELLS: usize = 1_000_000;
+
+/// Whether a line was added or removed by the new run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+}
+
+/// A changed output line, without escape sequences
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub change: Change,
+    pub text: String,
+}
+
+/// Difference between a block's output and the run it repeated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputDiff {
+    /// Start time label of the earlier run
+    pub against: String,
+    /// Changed lines, in output order
+    pub lines: Vec<DiffLine>,
+    /// Whether the changed lines are shown under the block header
+    pub expanded: bool,
+}
+
+impl OutputDiff {
+    /// Compare the earlier run's output with the new one
+    pub fn compute(against: &str, old: &[String], new: &[String]) -> Self {
+        let old: Vec<String> = old.iter().map(|l| ansi::strip(l)).collect();
+        let new: Vec<String> = new.iter().map(|l| ansi::strip(l)).collect();
+        Self {
+            against: against.to_string(),
+            lines: diff_lines(&old, &new),
+            expanded: false,
+        }
+    }
+
+    /// Number of added lines
+    pub fn added(&self) -> usize {
+        self.count(Change::Added)
+    }
+
+    /// Number of removed lines
+    pub fn removed(&self) -> usize {
+        self.count(Change::Removed)
+    }
+
+    fn count(&self, change: Change) -> usize {
+        self.lines.iter().filter(|l| l.change == change).count()
+    }
+
+    /// Badge text such as `+3 -1 vs 14:02:11` or `same as 14:02:11`
+    pub fn badge(&self) -> String {
+        if self.lines.is_empty() {
+            format!("same as {}", self.against)
+        } else {
+            format!(
+                "+{} \u{2212}{} vs {}",
+                self.added(),
+                self.removed(),
+                self.against
+            )
+        }
+    }
+}
+
+/// Added and removed lines turning `old` into `new`
+fn diff_lines(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    // Unchanged lines at either end need no comparison
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+    let line = |change, text: &String| DiffLine {
+        change,
+        text: text.clone(),
+    };
+    if old.len().saturating_mul(new.len()) > MAX_CELLS {
+        let removed = old.iter().map(|t| line(Change::Removed, t));
+        return removed
+            .chain(new.iter().map(|t| line(Change::Added, t)))
+            .collect();
+    }
+
+    // Longest common subsequence lengths of every pair of suffixes
+    let width = new.len() + 1;
+    let mut lcs = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i * width + j] = if old[i] == new[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len()
+            && (j == new.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            // Removals come before additions, as in a unified diff
+            lines.push(line(Change::Removed, &old[i]));
+            i += 1;
+        } else {
+            lines.push(line(Change::Added, &new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old =
+            lines("running 3 tests\ntest a ... ok\ntest b ... FAILED\ntest c ... ok\nfailures: 1");
+        let new = lines("running 3 tests\ntest a ... ok\ntest b ... \x1b[32mok\x1b[0m\ntest c ... ok\nall passed");
+        let diff = OutputDiff::compute("14:02:11", &old, &new);
+
+        let changes: Vec<(Change, &str)> = diff
+            .lines
+            .iter()
+            .map(|l| (l.change, l.text.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (Change::Removed, "test b ... FAILED"),
+                (Change::Added, "test b ... ok"),
+                (Change::Removed, "failures: 1"),
+                (Change::Added, "all passed"),
+            ]
+        );
+        assert_eq!(diff.badge(), "+2 \u{2212}2 vs 14:02:11");
+    }
+
+    #[test]
+    fn test_same_output() {
+        let output = lines("a\nb");
+        let diff = OutputDiff::compute("09:00:00", &output, &output);
+        assert!(diff.lines.is_empty());
+        assert_eq!(diff.badge(), "same as 09:00:00");
+    }
+}
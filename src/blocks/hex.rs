@@ -0,0 +1,91 @@
+//! Hex dump of a block's output as stored
+//!
+//! The output view makes control and invisible characters visible but
+//! can't show which bytes they are; the dump does, escape sequences
+//! included. It shows the output as jerm keeps it, not byte for byte as
+//! the program wrote it: lines are read as UTF-8 (invalid bytes become
+//! U+FFFD, `ef bf bd`), a `\r` ending a line is dropped, and lines are
+//! joined with `\n`.
+
+/// Bytes shown per dump row
+const ROW_BYTES: usize = 16;
+
+/// The stored output of one block, laid out as a hex dump
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexView {
+    /// Command the output belongs to
+    pub command: String,
+    /// Number of bytes dumped
+    pub len: usize,
+    /// Dump rows: offset, hex bytes, and printable ASCII
+    pub rows: Vec<String>,
+    /// First row shown
+    pub scroll: usize,
+}
+
+impl HexView {
+    /// Dump the output lines of `command`, joined by newlines
+    pub fn new(command: &str, lines: &[String]) -> Self {
+        let bytes = lines.join("\n").into_bytes();
+        Self {
+            command: command.to_string(),
+            len: bytes.len(),
+            rows: dump(&bytes),
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+}
+
+/// `xxd`-style rows: `00000010  1b 5b 33 31 6d ...  |.[31m...|`
+fn dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(ROW_BYTES)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut hex = String::new();
+            for j in 0..ROW_BYTES {
+                match chunk.get(j) {
+                    Some(byte) => hex.push_str(&format!("{byte:02x} ")),
+                    None => hex.push_str("   "),
+                }
+                if j == ROW_BYTES / 2 - 1 {
+                    hex.push(' ');
+                }
+            }
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}  {hex} |{ascii}|", i * ROW_BYTES)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump() {
+        let lines = vec!["\x1b[31mred\x1b[0m".to_string(), "a\u{200B}b".to_string()];
+        let view = HexView::new("ls", &lines);
+        assert_eq!(view.len, 18);
+        assert_eq!(
+            view.rows,
+            vec![
+                "00000000  1b 5b 33 31 6d 72 65 64  1b 5b 30 6d 0a 61 e2 80  |.[31mred.[0m.a..|",
+                "00000010  8b 62                                             |.b|",
+            ]
+        );
+    }
+}
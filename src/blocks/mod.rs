@@ -0,0 +1,189 @@
+//! Command blocks: one record per executed command
+
+pub mod copy;
+pub mod diagnostics;
+pub mod diff;
+pub mod hex;
+pub mod search;
+pub mod timeline;
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+pub use copy::CopyMode;
+pub use diagnostics::{Diagnostics, Severity, Tool};
+pub use diff::{Change, OutputDiff};
+pub use hex::HexView;
+pub use search::OutputSearch;
+pub use timeline::Timeline;
+
+/// A command that was run, and where its output lives in the output buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// The command line as entered
+    pub command: String,
+    /// Directory the command ran in
+    pub cwd: String,
+    /// When the command was started
+    pub started_at: DateTime<Local>,
+    /// Index of the block's first line (the prompt line) in the output buffer
+    pub output_start: usize,
+    /// Exit code, or `None` while the command is still running
+    pub exit_code: Option<i32>,
+    /// Precise timing of the command's phases
+    pub timing: BlockTiming,
+    /// Errors and warnings found in the output of a recognized tool
+    pub diagnostics: Option<Diagnostics>,
+    /// Index of the block this one re-ran from the timeline
+    pub rerun_of: Option<usize>,
+    /// Output changes since the re-run block
+    pub diff: Option<OutputDiff>,
+}
+
+/// Monotonic timestamps of a block's phases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTiming {
+    /// When Enter was pressed
+    pub submitted: Instant,
+    /// When the process was spawned (not set for builtins)
+    pub spawned: Option<Instant>,
+    /// When the first output arrived
+    pub first_output: Option<Instant>,
+    /// When the latest output arrived
+    pub last_output: Option<Instant>,
+    /// When the command finished
+    pub finished: Option<Instant>,
+}
+
+impl BlockTiming {
+    fn new() -> Self {
+        Self {
+            submitted: Instant::now(),
+            spawned: None,
+            first_output: None,
+            last_output: None,
+            finished: None,
+        }
+    }
+
+    /// Time from Enter until the command finished
+    pub fn total(&self) -> Option<Duration> {
+        Some(self.finished? - self.submitted)
+    }
+
+    /// One-line breakdown such as `start 2ms · first output 120ms · stream 3.0s · total 3.2s`
+    pub fn details(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(spawned) = self.spawned {
+            parts.push(format!(
+                "start {}",
+                format_duration(spawned - self.submitted)
+            ));
+        }
+        if let Some(first) = self.first_output {
+            parts.push(format!(
+                "first output {}",
+                format_duration(first - self.submitted)
+            ));
+            if let Some(last) = self.last_output {
+                parts.push(format!("stream {}", format_duration(last - first)));
+            }
+        }
+        match self.total() {
+            Some(total) => parts.push(format!("total {}", format_duration(total))),
+            None => parts.push(format!(
+                "running {}",
+                format_duration(self.submitted.elapsed())
+            )),
+        }
+        parts.join(" \u{b7} ")
+    }
+}
+
+impl CommandBlock {
+    /// Create a block for a command that is starting now
+    pub fn new(command: &str, cwd: &str, output_start: usize) -> Self {
+        Self {
+            command: command.to_string(),
+            cwd: cwd.to_string(),
+            started_at: Local::now(),
+            output_start,
+            exit_code: None,
+            timing: BlockTiming::new(),
+            diagnostics: None,
+            rerun_of: None,
+            diff: None,
+        }
+    }
+
+    /// Record that the command's process has started
+    pub fn mark_spawned(&mut self) {
+        self.timing.spawned = Some(Instant::now());
+    }
+
+    /// Record that output arrived
+    pub fn mark_output(&mut self) {
+        let now = Instant::now();
+        self.timing.first_output.get_or_insert(now);
+        self.timing.last_output = Some(now);
+    }
+
+    /// Record the exit code, unless the block already finished
+    pub fn finish(&mut self, exit_code: i32) {
+        if self.exit_code.is_none() {
+            self.exit_code = Some(exit_code);
+            self.timing.finished = Some(Instant::now());
+        }
+    }
+
+    /// Start time formatted as `HH:MM:SS`
+    pub fn time_label(&self) -> String {
+        self.started_at.format("%H:%M:%S").to_string()
+    }
+}
+
+/// Format a duration compactly: `850ms`, `3.2s`, `1m05s`, `2h03m`
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else if duration.as_millis() >= 1000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_block() {
+        let block = CommandBlock::new("ls", "/tmp", 3);
+        assert_eq!(block.exit_code, None);
+        assert_eq!(block.output_start, 3);
+        assert_eq!(block.time_label().len(), 8);
+    }
+
+    #[test]
+    fn test_finish_only_once() {
+        let mut block = CommandBlock::new("ls", "/tmp", 0);
+        block.finish(2);
+        block.finish(0);
+        assert_eq!(block.exit_code, Some(2));
+        assert!(block.timing.total().is_some());
+        assert!(block.timing.details().starts_with("total "));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(3240)), "3.2s");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1m05s");
+        assert_eq!(format_duration(Duration::from_secs(7380)), "2h03m");
+    }
+}
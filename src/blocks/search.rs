@@ -0,0 +1,142 @@
+//! Searching the output buffer
+//!
+//! Matches are found in each line as it's displayed (escape sequences
+//! removed, tabs expanded), so they line up with the rendered text. The
+//! search is case-insensitive unless the query has an uppercase letter.
+//! It starts at the newest match, since what's being looked for is usually
+//! recent; `n` walks up the scrollback and `N` back down.
+
+use crate::ansi;
+
+/// A match: an output line and the characters of it that matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Query and matches of an output search
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputSearch {
+    pub query: String,
+    /// Whether keys still go to the query
+    pub editing: bool,
+    /// Every match, top to bottom, as of the last query change
+    matches: Vec<Match>,
+    /// Index of the match the view is on
+    current: Option<usize>,
+}
+
+impl OutputSearch {
+    pub fn new() -> Self {
+        Self {
+            editing: true,
+            ..Self::default()
+        }
+    }
+
+    /// Change the query and find its matches in `output`
+    pub fn set_query(&mut self, query: String, output: &[String]) {
+        self.query = query;
+        self.matches = output
+            .iter()
+            .enumerate()
+            .flat_map(|(line, text)| {
+                self.find_in(&display_text(text))
+                    .into_iter()
+                    .map(move |(start, end)| Match { line, start, end })
+            })
+            .collect();
+        self.current = self.matches.len().checked_sub(1);
+    }
+
+    /// Character ranges of the query in a displayed line
+    pub fn find_in(&self, text: &str) -> Vec<(usize, usize)> {
+        let query: Vec<char> = self.query.chars().collect();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let ignore_case = !query.iter().any(|c| c.is_uppercase());
+        let same =
+            |a: char, b: char| a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase()));
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut found = Vec::new();
+        let mut start = 0;
+        while start + query.len() <= chars.len() {
+            let end = start + query.len();
+            if chars[start..end]
+                .iter()
+                .zip(&query)
+                .all(|(&a, &b)| same(a, b))
+            {
+                found.push((start, end));
+                start = end;
+            } else {
+                start += 1;
+            }
+        }
+        found
+    }
+
+    /// The match the view is on
+    pub fn current(&self) -> Option<Match> {
+        self.matches.get(self.current?).copied()
+    }
+
+    /// Move `delta` matches down (negative for up), wrapping around
+    pub fn move_by(&mut self, delta: isize) {
+        let (Some(current), Ok(len)) = (self.current, isize::try_from(self.matches.len())) else {
+            return;
+        };
+        self.current = Some((current as isize + delta).rem_euclid(len) as usize);
+    }
+
+    /// `3/12`, or `no matches`
+    pub fn status(&self) -> String {
+        match self.current {
+            Some(current) => format!("{}/{}", current + 1, self.matches.len()),
+            None => "no matches".to_string(),
+        }
+    }
+}
+
+/// A line's text as it's rendered
+pub fn display_text(line: &str) -> String {
+    ansi::parse_line(line)
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let output: Vec<String> = ["$ cargo build", "\x1b[31merror\x1b[0m: Error one", "ok"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut search = OutputSearch::new();
+        search.set_query("error".to_string(), &output);
+        assert_eq!(search.status(), "2/2");
+        assert_eq!(
+            search.current(),
+            Some(Match {
+                line: 1,
+                start: 7,
+                end: 12
+            })
+        );
+        search.move_by(1);
+        assert_eq!(search.current().map(|m| m.start), Some(0));
+
+        search.set_query("Error".to_string(), &output);
+        assert_eq!(search.status(), "1/1");
+        search.set_query("missing".to_string(), &output);
+        assert_eq!(search.status(), "no matches");
+    }
+}
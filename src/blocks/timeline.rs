@@ -0,0 +1,117 @@
+use super::CommandBlock;
+use crate::fuzzy::{FuzzyConfig, Matcher};
+
+/// State for the block timeline overlay (a table of contents for the session)
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    /// Text typed to filter blocks by command or start time
+    pub filter: String,
+    /// Selected row within the filtered list
+    pub selected_index: usize,
+    /// Matcher used to compare the filter with commands
+    pub matcher: FuzzyConfig,
+}
+
+impl Timeline {
+    /// Create an empty timeline state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indices of blocks whose command fuzzily matches the filter (or whose
+    /// start time begins with it), most recent last
+    pub fn matches(&self, blocks: &[CommandBlock]) -> Vec<usize> {
+        blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| {
+                self.matcher.score(&self.filter, &block.command).is_some()
+                    || block.time_label().starts_with(&self.filter)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Select the most recent matching block
+    pub fn select_last(&mut self, blocks: &[CommandBlock]) {
+        self.selected_index = self.matches(blocks).len().saturating_sub(1);
+    }
+
+    /// Move selection up
+    pub fn move_up(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    /// Move selection down
+    pub fn move_down(&mut self, blocks: &[CommandBlock]) {
+        let max_index = self.matches(blocks).len().saturating_sub(1);
+        if self.selected_index < max_index {
+            self.selected_index += 1;
+        }
+    }
+
+    /// Add a character to the filter
+    pub fn push_filter(&mut self, c: char, blocks: &[CommandBlock]) {
+        self.filter.push(c);
+        self.select_last(blocks);
+    }
+
+    /// Remove the last character from the filter
+    pub fn pop_filter(&mut self, blocks: &[CommandBlock]) {
+        self.filter.pop();
+        self.select_last(blocks);
+    }
+
+    /// Index (into `blocks`) of the selected block
+    pub fn selected_block(&self, blocks: &[CommandBlock]) -> Option<usize> {
+        self.matches(blocks).get(self.selected_index).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks() -> Vec<CommandBlock> {
+        vec![
+            CommandBlock::new("cargo build", "/tmp", 0),
+            CommandBlock::new("ls", "/tmp", 5),
+            CommandBlock::new("cargo test", "/tmp", 8),
+        ]
+    }
+
+    #[test]
+    fn test_filter_by_command() {
+        let blocks = blocks();
+        let mut timeline = Timeline::new();
+        for c in "cargo".chars() {
+            timeline.push_filter(c, &blocks);
+        }
+        assert_eq!(timeline.matches(&blocks), vec![0, 2]);
+        // Filtering selects the most recent match
+        assert_eq!(timeline.selected_block(&blocks), Some(2));
+    }
+
+    #[test]
+    fn test_filter_is_fuzzy() {
+        let blocks = blocks();
+        let mut timeline = Timeline::new();
+        for c in "cgt".chars() {
+            timeline.push_filter(c, &blocks);
+        }
+        assert_eq!(timeline.matches(&blocks), vec![2]);
+    }
+
+    #[test]
+    fn test_navigation_bounds() {
+        let blocks = blocks();
+        let mut timeline = Timeline::new();
+        timeline.select_last(&blocks);
+        timeline.move_down(&blocks);
+        assert_eq!(timeline.selected_index, 2);
+        timeline.move_up();
+        timeline.move_up();
+        timeline.move_up();
+        assert_eq!(timeline.selected_index, 0);
+    }
+}
@@ -0,0 +1,198 @@
+//! Completion scripts for the `jerm` binary's own command line
+//!
+//! Scripts are generated from [`OPTIONS`] and [`SUBCOMMANDS`] so new flags
+//! and subcommands are picked up without editing each shell's script.
+
+use super::{OPTIONS, SUBCOMMANDS};
+
+/// A shell that completion scripts can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Names accepted by `jerm completions`
+    pub const NAMES: &'static [&'static str] = &["bash", "zsh", "fish"];
+
+    /// Look up a shell by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    /// Generate the completion script for this shell
+    pub fn script(self) -> String {
+        match self {
+            Shell::Bash => bash_script(),
+            Shell::Zsh => zsh_script(),
+            Shell::Fish => fish_script(),
+        }
+    }
+}
+
+fn bash_script() -> String {
+    let mut words: Vec<String> = SUBCOMMANDS.iter().map(|s| s.name.to_string()).collect();
+    for option in OPTIONS {
+        words.push(format!("--{}", option.long));
+        if let Some(short) = option.short {
+            words.push(format!("-{short}"));
+        }
+    }
+
+    let mut cases = String::new();
+    for option in OPTIONS.iter().filter(|o| o.value.is_some()) {
+        let action = if option.value == Some("FILE") {
+            "COMPREPLY=($(compgen -f -- \"$cur\"))"
+        } else {
+            "COMPREPLY=()"
+        };
+        cases.push_str(&format!(
+            "        --{})\n            {action}\n            return\n            ;;\n",
+            option.long
+        ));
+    }
+    for subcommand in SUBCOMMANDS.iter().filter(|s| !s.choices.is_empty()) {
+        cases.push_str(&format!(
+            "        {})\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            return\n            ;;\n",
+            subcommand.name,
+            subcommand.choices.join(" ")
+        ));
+    }
+
+    format!(
+        r#"# bash completion for jerm
+_jerm() {{
+    local cur prev
+    cur="${{COMP_WORDS[COMP_CWORD]}}"
+    prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+
+    case "$prev" in
+{cases}    esac
+
+    COMPREPLY=($(compgen -W "{words}" -- "$cur"))
+}}
+complete -F _jerm jerm
+"#,
+        words = words.join(" ")
+    )
+}
+
+fn zsh_script() -> String {
+    let mut arguments = String::new();
+    for option in OPTIONS {
+        let help = option.help.replace('\'', "'\\''");
+        let value = match option.value {
+            Some("FILE") => ":file:_files".to_string(),
+            Some(name) => format!(":{}:", name.to_lowercase()),
+            None => String::new(),
+        };
+        match option.short {
+            Some(short) => arguments.push_str(&format!(
+                "    '(-{short} --{long})'{{-{short},--{long}}}'[{help}]{value}' \\\n",
+                long = option.long
+            )),
+            None => arguments.push_str(&format!("    '--{}[{help}]{value}' \\\n", option.long)),
+        }
+    }
+
+    let subcommands: Vec<String> = SUBCOMMANDS
+        .iter()
+        .map(|s| format!("'{}:{}'", s.name, s.help))
+        .collect();
+    let mut choices = String::new();
+    for subcommand in SUBCOMMANDS.iter().filter(|s| !s.choices.is_empty()) {
+        choices.push_str(&format!(
+            "                {}) _values '{}' {} ;;\n",
+            subcommand.name,
+            subcommand.name,
+            subcommand.choices.join(" ")
+        ));
+    }
+
+    format!(
+        r#"#compdef jerm
+# zsh completion for jerm
+_jerm() {{
+    local -a subcommands
+    subcommands=({subcommands})
+    local state
+
+    _arguments \
+{arguments}    '1: :->command' \
+    '*:: :->args'
+
+    case $state in
+        command)
+            _describe 'command' subcommands
+            ;;
+        args)
+            case $words[1] in
+{choices}            esac
+            ;;
+    esac
+}}
+_jerm "$@"
+"#,
+        subcommands = subcommands.join(" ")
+    )
+}
+
+fn fish_script() -> String {
+    let mut script = String::from("# fish completion for jerm\ncomplete -c jerm -f\n");
+    for option in OPTIONS {
+        let mut line = format!("complete -c jerm -l {}", option.long);
+        if let Some(short) = option.short {
+            line.push_str(&format!(" -s {short}"));
+        }
+        match option.value {
+            Some("FILE") => line.push_str(" -r -F"),
+            Some(_) => line.push_str(" -x"),
+            None => {}
+        }
+        line.push_str(&format!(" -d '{}'\n", option.help.replace('\'', "\\'")));
+        script.push_str(&line);
+    }
+    for subcommand in SUBCOMMANDS {
+        script.push_str(&format!(
+            "complete -c jerm -n __fish_use_subcommand -a {} -d '{}'\n",
+            subcommand.name, subcommand.help
+        ));
+        if !subcommand.choices.is_empty() {
+            script.push_str(&format!(
+                "complete -c jerm -n '__fish_seen_subcommand_from {}' -a '{}'\n",
+                subcommand.name,
+                subcommand.choices.join(" ")
+            ));
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_cover_subcommands_and_options() {
+        for name in Shell::NAMES {
+            let script = Shell::from_name(name).unwrap().script();
+            assert!(script.contains("completions"), "{name}");
+            assert!(script.contains("profile"), "{name}");
+            assert!(script.contains("readonly"), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_bash_script_completes_shell_names() {
+        let script = Shell::Bash.script();
+        assert!(script.contains("compgen -W \"bash zsh fish\""));
+        assert!(script.contains("complete -F _jerm jerm"));
+    }
+}
@@ -0,0 +1,188 @@
+//! Non-interactive `jerm exec` subcommand
+//!
+//! Runs one command the way the prompt would, without the UI: with the
+//! profile's aliases expanded, through `sh -c`, in the current directory or,
+//! with `--in`, in a shortcut's directory (counting the visit and setting
+//! `OLDPWD`), after the profile's chpwd hooks have run there. Output goes
+//! to stdout as it arrives, stderr lines after `ui.stderr_prefix`, between
+//! a `$ command` header and a footer with the exit code, duration, and the
+//! error summary a block would show. jerm exits with the command's code.
+//! The project policy applies as it does at the prompt: warnings are
+//! printed first, and a blocked command isn't run.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::blocks::{format_duration, Diagnostics, Tool};
+use crate::config::Config;
+use crate::shell::alias::AliasStore;
+use crate::shell::env::Environment;
+use crate::shell::executor::{ExecutorError, OutputStream, RunningCommand};
+use crate::shell::hooks;
+use crate::shell::policy::{self, Policy, PolicyAction};
+use crate::shortcuts::manager::ShortcutManager;
+use crate::shortcuts::DirTracker;
+
+/// How often the command's output is collected
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Errors that keep `jerm exec` from running its command
+#[derive(Debug, Error)]
+pub enum ExecError {
+    #[error("no shortcut '{0}' (see `jerm list`)")]
+    UnknownShortcut(String),
+
+    #[error("blocked by project policy (see {})", .0.display())]
+    Blocked(PathBuf),
+
+    #[error("{0}")]
+    Start(#[from] ExecutorError),
+
+    #[error("failed to write output: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Run `command`, in the directory of `shortcut` if given, returning its
+/// exit code
+pub fn run(shortcut: Option<&str>, command: &str) -> Result<i32, ExecError> {
+    let (config, _) = Config::load();
+    let cwd = std::env::current_dir()?;
+    let mut env = Environment::default();
+    let dir = match shortcut {
+        Some(spec) => {
+            let mut manager = ShortcutManager::open(config.storage.shortcuts);
+            let dir = manager
+                .find(spec)
+                .ok_or_else(|| ExecError::UnknownShortcut(spec.to_string()))?
+                .path
+                .clone();
+            manager.touch_shortcut(&dir);
+            DirTracker::new().record(&dir);
+            env.set("OLDPWD", &cwd.to_string_lossy());
+            dir
+        }
+        None => cwd.clone(),
+    };
+    for failure in hooks::run_hooks(&config.hooks.chpwd, &dir, &cwd) {
+        eprintln!("jerm: {failure}");
+        for line in &failure.output {
+            eprintln!("  {line}");
+        }
+    }
+    let expanded = AliasStore::new().expand(command);
+    check_policy(&expanded, &dir, &env)?;
+
+    let mut out = io::stdout().lock();
+    writeln!(out, "{}", header(command, &dir))?;
+    out.flush()?;
+
+    let started = Instant::now();
+    let mut running = RunningCommand::spawn_foreground(&expanded, &dir, &env)?;
+    let mut lines = Vec::new();
+    let code = loop {
+        let finished = running.try_finish();
+        for (stream, line) in running.drain_output() {
+            match stream {
+                OutputStream::Stdout => writeln!(out, "{line}")?,
+                OutputStream::Stderr => writeln!(out, "{}{line}", config.ui.stderr_prefix)?,
+            }
+            lines.push(line);
+        }
+        out.flush()?;
+        if let Some(code) = finished {
+            break code;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let diagnostics = Tool::detect(&expanded).and_then(|tool| Diagnostics::scan(tool, &lines));
+    writeln!(
+        out,
+        "{}",
+        footer(code, started.elapsed(), diagnostics.as_ref())
+    )?;
+    Ok(code)
+}
+
+/// Print the project policy's warnings for `command`, failing if a rule
+/// blocks it
+fn check_policy(command: &str, dir: &Path, env: &Environment) -> Result<(), ExecError> {
+    let Some(path) = policy::find(dir) else {
+        return Ok(());
+    };
+    let policy = match Policy::load(&path) {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("jerm: {}: {e}", path.display());
+            return Ok(());
+        }
+    };
+    let mut blocked = false;
+    for rule in policy.check(command, env) {
+        match rule.action {
+            PolicyAction::Warn => eprintln!("\u{26a0} project policy: {}", rule.command),
+            PolicyAction::Block => {
+                blocked = true;
+                eprintln!("\u{2717} blocked by project policy: {}", rule.command);
+            }
+        }
+        if let Some(message) = &rule.message {
+            eprintln!("  {message}");
+        }
+    }
+    if blocked {
+        return Err(ExecError::Blocked(path));
+    }
+    Ok(())
+}
+
+/// First line: the command and where it runs
+fn header(command: &str, dir: &Path) -> String {
+    let dir = match dirs::home_dir() {
+        Some(home) => match dir.strip_prefix(&home) {
+            Ok(rest) if rest.as_os_str().is_empty() => PathBuf::from("~"),
+            Ok(rest) => Path::new("~").join(rest),
+            Err(_) => dir.to_path_buf(),
+        },
+        None => dir.to_path_buf(),
+    };
+    format!("$ {command}  ({})", dir.display())
+}
+
+/// Last line: how the command ended
+fn footer(code: i32, duration: Duration, diagnostics: Option<&Diagnostics>) -> String {
+    let mut footer = match code {
+        0 => format!("\u{2713} done in {}", format_duration(duration)),
+        _ => format!("\u{2717} exit {code} after {}", format_duration(duration)),
+    };
+    if let Some(diagnostics) = diagnostics {
+        footer.push_str(&format!(" \u{b7} {}", diagnostics.badge()));
+    }
+    footer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_and_footer() {
+        assert_eq!(header("make", Path::new("/srv/app")), "$ make  (/srv/app)");
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(header("ls", &home.join("src")), "$ ls  (~/src)");
+        }
+
+        let second = Duration::from_secs(1);
+        assert_eq!(footer(0, second, None), "\u{2713} done in 1.0s");
+        let lines = vec!["error[E0308]: mismatched types".to_string()];
+        let diagnostics = Diagnostics::scan(Tool::Rustc, &lines);
+        assert_eq!(
+            footer(101, second, diagnostics.as_ref()),
+            "\u{2717} exit 101 after 1.0s \u{b7} 1 error"
+        );
+    }
+}
@@ -0,0 +1,455 @@
+//! Command-line argument parsing for the `jerm` binary
+
+pub mod completions;
+pub mod exec;
+pub mod report;
+pub mod state;
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+pub use completions::Shell;
+
+/// Usage text printed for `--help`
+pub const USAGE: &str = "\
+Usage: jerm [OPTIONS]
+       jerm <COMMAND>
+
+Commands:
+  list [--json]        List saved shortcuts
+  stats [--json]       Show command history statistics
+  completions <SHELL>  Print a completion script for bash, zsh, or fish
+  doctor               Check the environment and suggest fixes
+  export-state <FILE>  Bundle config, shortcuts, snippets, history, and themes
+  import-state <FILE>  Restore an exported bundle, asking about each conflict
+  exec [--in <SHORTCUT>] [--] <COMMAND...>
+                       Run a command without the UI, in a shortcut's directory
+                       with --in, and exit with its code
+
+Options:
+  --readonly         Disable command execution and file changes (navigation only)
+  --record <FILE>    Record the session to FILE in asciinema v2 (.cast) format
+  --profile <NAME>   Use a named profile (also JERM_PROFILE)
+  --json             Print machine-readable JSON (list, stats)
+  --scrub-history    Leave credential-like commands out of export-state
+  --no-summary       Don't print the session summary on exit
+  -h, --help         Print this help message";
+
+/// A command-line option, used for parsing help and completion scripts
+pub struct OptionSpec {
+    /// Long name without the leading dashes
+    pub long: &'static str,
+    /// Optional single-letter alias
+    pub short: Option<char>,
+    /// Name of the option's value, if it takes one
+    pub value: Option<&'static str>,
+    /// One-line description
+    pub help: &'static str,
+}
+
+/// A subcommand, used for completion scripts
+pub struct SubcommandSpec {
+    /// Subcommand name
+    pub name: &'static str,
+    /// Fixed choices for its first argument, if any
+    pub choices: &'static [&'static str],
+    /// One-line description
+    pub help: &'static str,
+}
+
+/// Options accepted by `jerm`
+pub const OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        long: "readonly",
+        short: None,
+        value: None,
+        help: "Disable command execution and file changes",
+    },
+    OptionSpec {
+        long: "record",
+        short: None,
+        value: Some("FILE"),
+        help: "Record the session in asciinema v2 format",
+    },
+    OptionSpec {
+        long: "profile",
+        short: None,
+        value: Some("NAME"),
+        help: "Use a named profile",
+    },
+    OptionSpec {
+        long: "json",
+        short: None,
+        value: None,
+        help: "Print machine-readable JSON",
+    },
+    OptionSpec {
+        long: "scrub-history",
+        short: None,
+        value: None,
+        help: "Leave credential-like commands out of the export",
+    },
+    OptionSpec {
+        long: "no-summary",
+        short: None,
+        value: None,
+        help: "Don't print the session summary on exit",
+    },
+    OptionSpec {
+        long: "help",
+        short: Some('h'),
+        value: None,
+        help: "Print help",
+    },
+];
+
+/// Subcommands accepted by `jerm`
+pub const SUBCOMMANDS: &[SubcommandSpec] = &[
+    SubcommandSpec {
+        name: "list",
+        choices: &[],
+        help: "List saved shortcuts",
+    },
+    SubcommandSpec {
+        name: "stats",
+        choices: &[],
+        help: "Show command history statistics",
+    },
+    SubcommandSpec {
+        name: "completions",
+        choices: Shell::NAMES,
+        help: "Print a shell completion script",
+    },
+    SubcommandSpec {
+        name: "doctor",
+        choices: &[],
+        help: "Check the environment and suggest fixes",
+    },
+    SubcommandSpec {
+        name: "export-state",
+        choices: &[],
+        help: "Bundle the profile's state into a file",
+    },
+    SubcommandSpec {
+        name: "import-state",
+        choices: &[],
+        help: "Restore state from an exported bundle",
+    },
+    SubcommandSpec {
+        name: "exec",
+        choices: &[],
+        help: "Run a command without the UI",
+    },
+];
+
+/// Errors that can occur while parsing command-line arguments
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CliError {
+    #[error("unknown option: {0}")]
+    UnknownOption(String),
+    #[error("unknown command: {0}")]
+    UnknownCommand(String),
+    #[error("missing value for {0}")]
+    MissingValue(String),
+    #[error("invalid profile name: {0} (use letters, digits, - and _)")]
+    InvalidProfile(String),
+    #[error("unsupported shell: {0} (use bash, zsh, or fish)")]
+    UnknownShell(String),
+    #[error("--json only applies to the list and stats commands")]
+    JsonWithoutCommand,
+    #[error("--scrub-history only applies to the export-state command")]
+    ScrubWithoutExport,
+    #[error("exec runs commands, which --readonly disables")]
+    ReadonlyExec,
+}
+
+/// A subcommand that runs instead of the interactive terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    /// Print a completion script for a shell
+    Completions(Shell),
+    /// Print saved shortcuts
+    List,
+    /// Print history statistics
+    Stats,
+    /// Check the environment and print fixes
+    Doctor,
+    /// Write the profile's state to an archive
+    ExportState(PathBuf),
+    /// Restore the profile's state from an archive
+    ImportState(PathBuf),
+    /// Run a command without the UI, optionally in a shortcut's directory
+    Exec {
+        shortcut: Option<String>,
+        command: String,
+    },
+}
+
+/// Options passed to the `jerm` binary
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CliArgs {
+    /// Disable command execution and file mutations
+    pub readonly: bool,
+    /// Record the session to this asciinema cast file
+    pub record: Option<PathBuf>,
+    /// Named profile with its own shortcuts, macros, and settings
+    pub profile: Option<String>,
+    /// Print usage and exit
+    pub help: bool,
+    /// Subcommand to run instead of the terminal
+    pub command: Option<CliCommand>,
+    /// Print subcommand output as JSON
+    pub json: bool,
+    /// Leave credential-like history out of `export-state`
+    pub scrub_history: bool,
+    /// Skip the session summary on exit
+    pub no_summary: bool,
+}
+
+/// Parse command-line arguments (without the program name)
+pub fn parse_args<I>(args: I) -> Result<CliArgs, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = CliArgs::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--readonly" => parsed.readonly = true,
+            "--record" => {
+                let path = args.next().ok_or(CliError::MissingValue(arg))?;
+                parsed.record = Some(PathBuf::from(path));
+            }
+            "--profile" => {
+                let name = args.next().ok_or(CliError::MissingValue(arg))?;
+                if !crate::paths::is_valid_name(&name) {
+                    return Err(CliError::InvalidProfile(name));
+                }
+                parsed.profile = Some(name);
+            }
+            "-h" | "--help" => parsed.help = true,
+            "--json" => parsed.json = true,
+            "--scrub-history" => parsed.scrub_history = true,
+            "--no-summary" => parsed.no_summary = true,
+            "list" if parsed.command.is_none() => parsed.command = Some(CliCommand::List),
+            "stats" if parsed.command.is_none() => parsed.command = Some(CliCommand::Stats),
+            "doctor" if parsed.command.is_none() => parsed.command = Some(CliCommand::Doctor),
+            "completions" if parsed.command.is_none() => {
+                let name = args.next().ok_or(CliError::MissingValue(arg))?;
+                let shell = Shell::from_name(&name).ok_or(CliError::UnknownShell(name))?;
+                parsed.command = Some(CliCommand::Completions(shell));
+            }
+            "export-state" | "import-state" if parsed.command.is_none() => {
+                let path = PathBuf::from(
+                    args.next()
+                        .ok_or_else(|| CliError::MissingValue(arg.clone()))?,
+                );
+                parsed.command = Some(if arg == "export-state" {
+                    CliCommand::ExportState(path)
+                } else {
+                    CliCommand::ImportState(path)
+                });
+            }
+            "exec" if parsed.command.is_none() => {
+                let mut shortcut = None;
+                let mut words: Vec<String> = Vec::new();
+                while let Some(word) = args.next() {
+                    match word.as_str() {
+                        "--in" if words.is_empty() && shortcut.is_none() => {
+                            shortcut = Some(args.next().ok_or(CliError::MissingValue(word))?);
+                        }
+                        "--" if words.is_empty() => words.extend(args.by_ref()),
+                        _ => words.push(word),
+                    }
+                }
+                if words.is_empty() {
+                    return Err(CliError::MissingValue(arg));
+                }
+                // One word is a command line; several are a program and its
+                // arguments, quoted so sh sees each one as it was given
+                let command = match words.as_slice() {
+                    [line] => line.clone(),
+                    _ => words
+                        .iter()
+                        .map(|word| crate::shell::quote(word))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                };
+                parsed.command = Some(CliCommand::Exec { shortcut, command });
+            }
+            _ if arg.starts_with('-') => return Err(CliError::UnknownOption(arg)),
+            _ => return Err(CliError::UnknownCommand(arg)),
+        }
+    }
+
+    if parsed.json && !matches!(parsed.command, Some(CliCommand::List | CliCommand::Stats)) {
+        return Err(CliError::JsonWithoutCommand);
+    }
+    if parsed.scrub_history && !matches!(parsed.command, Some(CliCommand::ExportState(_))) {
+        return Err(CliError::ScrubWithoutExport);
+    }
+    if parsed.readonly && matches!(parsed.command, Some(CliCommand::Exec { .. })) {
+        return Err(CliError::ReadonlyExec);
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_no_args() {
+        assert_eq!(parse_args(args(&[])), Ok(CliArgs::default()));
+    }
+
+    #[test]
+    fn test_parse_readonly() {
+        let parsed = parse_args(args(&["--readonly"])).unwrap();
+        assert!(parsed.readonly);
+    }
+
+    #[test]
+    fn test_parse_no_summary() {
+        let parsed = parse_args(args(&["--no-summary", "--readonly"])).unwrap();
+        assert!(parsed.no_summary);
+        assert!(parsed.readonly);
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let parsed = parse_args(args(&["--record", "session.cast"])).unwrap();
+        assert_eq!(parsed.record, Some(PathBuf::from("session.cast")));
+        assert_eq!(
+            parse_args(args(&["--record"])),
+            Err(CliError::MissingValue("--record".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        let parsed = parse_args(args(&["--profile", "work"])).unwrap();
+        assert_eq!(parsed.profile.as_deref(), Some("work"));
+        assert_eq!(
+            parse_args(args(&["--profile", "../x"])),
+            Err(CliError::InvalidProfile("../x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_completions() {
+        let parsed = parse_args(args(&["completions", "zsh"])).unwrap();
+        assert_eq!(parsed.command, Some(CliCommand::Completions(Shell::Zsh)));
+        assert_eq!(
+            parse_args(args(&["completions", "tcsh"])),
+            Err(CliError::UnknownShell("tcsh".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_doctor() {
+        let parsed = parse_args(args(&["doctor"])).unwrap();
+        assert_eq!(parsed.command, Some(CliCommand::Doctor));
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let parsed = parse_args(args(&["stats", "--json"])).unwrap();
+        assert_eq!(parsed.command, Some(CliCommand::Stats));
+        assert!(parsed.json);
+        let parsed = parse_args(args(&["--json", "list"])).unwrap();
+        assert_eq!(parsed.command, Some(CliCommand::List));
+        assert_eq!(
+            parse_args(args(&["--json"])),
+            Err(CliError::JsonWithoutCommand)
+        );
+    }
+
+    #[test]
+    fn test_parse_state() {
+        let parsed = parse_args(args(&["export-state", "state.json", "--scrub-history"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(CliCommand::ExportState(PathBuf::from("state.json")))
+        );
+        assert!(parsed.scrub_history);
+        let parsed = parse_args(args(&["import-state", "state.json"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(CliCommand::ImportState(PathBuf::from("state.json")))
+        );
+        assert_eq!(
+            parse_args(args(&["import-state", "state.json", "--scrub-history"])),
+            Err(CliError::ScrubWithoutExport)
+        );
+        assert_eq!(
+            parse_args(args(&["export-state"])),
+            Err(CliError::MissingValue("export-state".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_exec() {
+        let parsed = parse_args(args(&["exec", "--in", "api", "make", "test"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(CliCommand::Exec {
+                shortcut: Some("api".to_string()),
+                command: "make test".to_string(),
+            })
+        );
+        // Everything after the command, or after `--`, belongs to it
+        let parsed = parse_args(args(&["exec", "--", "--in", "x", "--json"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(CliCommand::Exec {
+                shortcut: None,
+                command: "--in x --json".to_string(),
+            })
+        );
+        // A single word is run as a command line, several are quoted
+        let parsed = parse_args(args(&["exec", "make lint | tee lint.log"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(CliCommand::Exec {
+                shortcut: None,
+                command: "make lint | tee lint.log".to_string(),
+            })
+        );
+        let parsed = parse_args(args(&["exec", "grep", "-r", "a b", "it's", "$HOME"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Some(CliCommand::Exec {
+                shortcut: None,
+                command: r"grep -r 'a b' 'it'\''s' '$HOME'".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_args(args(&["exec", "--in", "api"])),
+            Err(CliError::MissingValue("exec".to_string()))
+        );
+        assert_eq!(
+            parse_args(args(&["--readonly", "exec", "ls"])),
+            Err(CliError::ReadonlyExec)
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown() {
+        assert_eq!(
+            parse_args(args(&["--bogus"])),
+            Err(CliError::UnknownOption("--bogus".to_string()))
+        );
+        assert_eq!(
+            parse_args(args(&["bogus"])),
+            Err(CliError::UnknownCommand("bogus".to_string()))
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Non-interactive `jerm list` and `jerm stats` subcommands
+//!
+//! Both print a human-readable table by default, or stable JSON with
+//! `--json` for scripts, status bars, and editor plugins. JSON field names
+//! are part of the interface: add fields, don't rename them.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::history::storage::{get_history_path, load_entries, HistoryEntry};
+use crate::shortcuts::manager::ShortcutManager;
+
+/// Number of entries in the "top" lists of `jerm stats`
+const TOP_COUNT: usize = 10;
+
+/// A shortcut as reported by `jerm list`
+#[derive(Debug, Serialize)]
+pub struct ShortcutReport {
+    /// Position in the sidebar (Ctrl+1..9 for the first nine)
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub path: PathBuf,
+    pub last_accessed: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub visits: u32,
+    pub pinned: bool,
+}
+
+/// Output of `jerm list --json`
+#[derive(Debug, Serialize)]
+pub struct ListReport {
+    pub profile: String,
+    pub shortcuts: Vec<ShortcutReport>,
+}
+
+/// A value with the number of times it occurred
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Count<T> {
+    pub value: T,
+    pub count: usize,
+}
+
+/// Output of `jerm stats --json`
+#[derive(Debug, Serialize)]
+pub struct StatsReport {
+    pub profile: String,
+    pub total_commands: usize,
+    pub unique_commands: usize,
+    pub first_command_at: Option<DateTime<Utc>>,
+    pub last_command_at: Option<DateTime<Utc>>,
+    pub top_commands: Vec<Count<String>>,
+    pub top_directories: Vec<Count<PathBuf>>,
+    pub shortcuts: usize,
+}
+
+/// Print the shortcuts of the active profile
+pub fn print_list(json: bool) -> Result<(), serde_json::Error> {
+    let manager = ShortcutManager::open(Config::load().0.storage.shortcuts);
+    let report = ListReport {
+        profile: profile_name(),
+        shortcuts: manager
+            .get_shortcuts()
+            .into_iter()
+            .enumerate()
+            .map(|(i, shortcut)| ShortcutReport {
+                index: i + 1,
+                name: shortcut.name.clone(),
+                path: shortcut.path.clone(),
+                last_accessed: shortcut.last_accessed,
+                created_at: shortcut.created_at,
+                visits: shortcut.visits,
+                pinned: shortcut.pinned,
+            })
+            .collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if report.shortcuts.is_empty() {
+        println!("No shortcuts saved");
+    }
+    for shortcut in &report.shortcuts {
+        let name = shortcut
+            .name
+            .as_ref()
+            .map(|name| format!("{name}: "))
+            .unwrap_or_default();
+        println!(
+            "{:>3}  {name}{}  ({})",
+            shortcut.index,
+            shortcut.path.display(),
+            shortcut
+                .last_accessed
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
+/// Print usage statistics for the active profile
+pub fn print_stats(json: bool) -> Result<(), serde_json::Error> {
+    let entries = get_history_path()
+        .and_then(|path| load_entries(&path, usize::MAX))
+        .map(|(entries, _)| entries)
+        .unwrap_or_default();
+    let report = build_stats(
+        &entries,
+        ShortcutManager::open(Config::load().0.storage.shortcuts).len(),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("Profile:          {}", report.profile);
+    println!("Commands run:     {}", report.total_commands);
+    println!("Unique commands:  {}", report.unique_commands);
+    println!("Shortcuts:        {}", report.shortcuts);
+    if let (Some(first), Some(last)) = (report.first_command_at, report.last_command_at) {
+        println!(
+            "History range:    {} .. {}",
+            first.with_timezone(&chrono::Local).format("%Y-%m-%d"),
+            last.with_timezone(&chrono::Local).format("%Y-%m-%d")
+        );
+    }
+    if !report.top_commands.is_empty() {
+        println!("\nTop commands:");
+        for entry in &report.top_commands {
+            println!("  {:>5}  {}", entry.count, entry.value);
+        }
+    }
+    if !report.top_directories.is_empty() {
+        println!("\nTop directories:");
+        for entry in &report.top_directories {
+            println!("  {:>5}  {}", entry.count, entry.value.display());
+        }
+    }
+    Ok(())
+}
+
+/// Compute statistics from history entries
+fn build_stats(entries: &[HistoryEntry], shortcuts: usize) -> StatsReport {
+    let commands = top_counts(entries.iter().map(|e| e.command.clone()));
+    let unique_commands = commands.len();
+
+    StatsReport {
+        profile: profile_name(),
+        total_commands: entries.len(),
+        unique_commands,
+        first_command_at: entries.iter().map(|e| e.timestamp).min(),
+        last_command_at: entries.iter().map(|e| e.timestamp).max(),
+        top_commands: commands.into_iter().take(TOP_COUNT).collect(),
+        top_directories: top_counts(entries.iter().map(|e| e.cwd.clone()))
+            .into_iter()
+            .take(TOP_COUNT)
+            .collect(),
+        shortcuts,
+    }
+}
+
+/// Count occurrences, most frequent first (ties broken by value)
+fn top_counts<T: Ord + std::hash::Hash + Clone>(values: impl Iterator<Item = T>) -> Vec<Count<T>> {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut counts: Vec<Count<T>> = counts
+        .into_iter()
+        .map(|(value, count)| Count { value, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts
+}
+
+/// Name of the active profile
+pub(super) fn profile_name() -> String {
+    crate::paths::active_profile().unwrap_or_else(|| crate::paths::DEFAULT_PROFILE.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_stats() {
+        let entries: Vec<HistoryEntry> = [
+            ("ls", "/a"),
+            ("make", "/b"),
+            ("ls", "/b"),
+            ("git status", "/b"),
+        ]
+        .iter()
+        .map(|(command, cwd)| HistoryEntry::new(*command, PathBuf::from(cwd)))
+        .collect();
+
+        let stats = build_stats(&entries, 3);
+        assert_eq!(stats.total_commands, 4);
+        assert_eq!(stats.unique_commands, 3);
+        assert_eq!(stats.shortcuts, 3);
+        assert_eq!(
+            stats.top_commands[0],
+            Count {
+                value: "ls".to_string(),
+                count: 2
+            }
+        );
+        assert_eq!(stats.top_commands[1].value, "git status");
+        assert_eq!(stats.top_directories[0].value, PathBuf::from("/b"));
+    }
+
+    #[test]
+    fn test_stats_json_fields() {
+        let json = serde_json::to_value(build_stats(&[], 0)).unwrap();
+        for field in [
+            "profile",
+            "total_commands",
+            "unique_commands",
+            "first_command_at",
+            "last_command_at",
+            "top_commands",
+            "top_directories",
+            "shortcuts",
+        ] {
+            assert!(json.get(field).is_some(), "missing {field}");
+        }
+    }
+}
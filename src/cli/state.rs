@@ -0,0 +1,198 @@
+//! `jerm export-state` and the guided `jerm import-state`
+//!
+//! Import prints what the archive would change, asks about each file that
+//! differs from the local copy (defaulting to keeping it), and writes
+//! nothing until the whole plan is confirmed.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::shortcuts::{store, ShortcutStore};
+use crate::state::{self, FileStatus, PlannedFile, Resolution, StateArchive, StateError};
+
+/// Write the active profile's state to `path`
+pub fn export(path: &Path, scrub_history: bool) -> Result<(), StateError> {
+    let dir = crate::paths::config_dir().ok_or(StateError::ConfigDirNotFound)?;
+    let mut shortcuts = store::open(Config::load().0.storage.shortcuts);
+    let archive = StateArchive::capture(
+        &dir,
+        &super::report::profile_name(),
+        scrub_history,
+        shortcuts.as_mut(),
+    )?;
+    archive.write(path)?;
+    println!(
+        "Exported {} files from profile {} to {}",
+        archive.files.len(),
+        archive.profile,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Restore the archive at `path` into the active profile, asking on stdin
+pub fn import(path: &Path) -> Result<(), StateError> {
+    let dir = crate::paths::config_dir().ok_or(StateError::ConfigDirNotFound)?;
+    let archive = StateArchive::read(path)?;
+    let mut shortcuts = store::open(Config::load().0.storage.shortcuts);
+    let stdin = io::stdin();
+    guide(
+        &archive,
+        &dir,
+        shortcuts.as_mut(),
+        &mut stdin.lock(),
+        &mut io::stdout(),
+    )?;
+    Ok(())
+}
+
+/// Walk through an import, returning the number of files written
+fn guide(
+    archive: &StateArchive,
+    dir: &Path,
+    shortcuts: &mut dyn ShortcutStore,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<usize, StateError> {
+    let mut plan = state::plan(archive, dir, shortcuts)?;
+    writeln!(
+        out,
+        "Archive from profile {} ({})",
+        archive.profile,
+        archive.created_at.format("%Y-%m-%d %H:%M UTC")
+    )?;
+    for file in &plan {
+        let status = match file.status {
+            FileStatus::New => "new",
+            FileStatus::Unchanged => "unchanged",
+            FileStatus::Conflict => "differs",
+        };
+        writeln!(out, "  {status:<10} {}", file.name)?;
+    }
+
+    for file in plan.iter_mut().filter(|f| f.status == FileStatus::Conflict) {
+        file.resolution = ask_resolution(file, input, out)?;
+    }
+
+    let changes = plan
+        .iter()
+        .filter(|f| f.resolution != Resolution::Keep)
+        .count();
+    if changes == 0 {
+        writeln!(out, "Nothing to import")?;
+        return Ok(0);
+    }
+
+    write!(
+        out,
+        "Write {changes} files? Overwritten files are kept as <name>.bak [y/N] "
+    )?;
+    out.flush()?;
+    if !matches!(read_answer(input)?.as_str(), "y" | "yes") {
+        writeln!(out, "Import cancelled")?;
+        return Ok(0);
+    }
+
+    let written = state::apply(archive, dir, &plan, shortcuts)?;
+    writeln!(out, "Imported {written} files")?;
+    Ok(written)
+}
+
+/// Ask what to do with a file that differs, repeating until the answer is valid
+fn ask_resolution(
+    file: &PlannedFile,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<Resolution, StateError> {
+    let mergeable = state::can_merge(&file.name);
+    loop {
+        if mergeable {
+            write!(
+                out,
+                "{} differs: [k]eep, [r]eplace, or [m]erge? [k] ",
+                file.name
+            )?;
+        } else {
+            write!(out, "{} differs: [k]eep or [r]eplace? [k] ", file.name)?;
+        }
+        out.flush()?;
+        match read_answer(input)?.as_str() {
+            "" | "k" | "keep" => return Ok(Resolution::Keep),
+            "r" | "replace" => return Ok(Resolution::Replace),
+            "m" | "merge" if mergeable => return Ok(Resolution::Merge),
+            _ => writeln!(out)?,
+        }
+    }
+}
+
+/// Read one lowercased answer; end of input counts as an empty answer
+fn read_answer(input: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(line.trim().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shortcuts::store::MemoryStore;
+    use crate::testutil::ScratchDir;
+    use std::fs;
+
+    #[test]
+    fn test_guide_asks_before_writing() {
+        let dir = ScratchDir::new("import");
+        fs::write(dir.join("config.toml"), "[ui]\n").unwrap();
+        let mut store = MemoryStore::default();
+        let mut archive = StateArchive::capture(&dir, "work", false, &mut store).unwrap();
+        archive
+            .files
+            .insert("config.toml".to_string(), "[git]\n".to_string());
+
+        // A merge isn't offered for config, so "m" is asked again
+        let mut out = Vec::new();
+        let written = guide(
+            &archive,
+            &dir,
+            &mut store,
+            &mut "m\nr\nn\n".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(written, 0);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches("[k]eep or [r]eplace").count(), 2);
+        assert!(out.contains("Import cancelled"));
+        assert_eq!(
+            fs::read_to_string(dir.join("config.toml")).unwrap(),
+            "[ui]\n"
+        );
+
+        let written = guide(
+            &archive,
+            &dir,
+            &mut store,
+            &mut "r\ny\n".as_bytes(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(written, 1);
+        assert_eq!(
+            fs::read_to_string(dir.join("config.toml")).unwrap(),
+            "[git]\n"
+        );
+
+        // Without input every conflict is kept
+        fs::write(dir.join("config.toml"), "[ui]\n").unwrap();
+        let written = guide(
+            &archive,
+            &dir,
+            &mut store,
+            &mut "".as_bytes(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(written, 0);
+    }
+}
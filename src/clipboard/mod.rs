@@ -0,0 +1,128 @@
+//! The system clipboard
+//!
+//! Copying goes through the platform's clipboard program (`pbcopy`,
+//! `wl-copy`, `xclip`, or `xsel`) when one works, and otherwise asks the
+//! terminal to set the clipboard with an OSC 52 sequence, which also
+//! reaches the local clipboard over SSH. Pasting reads the same programs;
+//! terminals don't answer OSC 52 reads reliably, so without one the caller
+//! falls back to the last text copied in jerm.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Programs that take the clipboard on stdin, with their arguments
+const COPY_PROGRAMS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Programs that print the clipboard, with their arguments
+const PASTE_PROGRAMS: &[(&str, &[&str])] = &[
+    ("pbpaste", &[]),
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+];
+
+/// How text reached the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// A clipboard program, by name
+    Program(&'static str),
+    /// An OSC 52 sequence written to the terminal
+    Osc52,
+}
+
+impl Method {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Method::Program(name) => name,
+            Method::Osc52 => "OSC 52",
+        }
+    }
+}
+
+/// Put `text` on the system clipboard
+pub fn copy(text: &str) -> io::Result<Method> {
+    for &(program, args) in COPY_PROGRAMS {
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        // Dropping stdin closes it, so the program sees the end of input
+        let written = child
+            .stdin
+            .take()
+            .map_or(Ok(()), |mut stdin| stdin.write_all(text.as_bytes()));
+        if child.wait()?.success() && written.is_ok() {
+            return Ok(Method::Program(program));
+        }
+    }
+
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52(text).as_bytes())?;
+    stdout.flush()?;
+    Ok(Method::Osc52)
+}
+
+/// The system clipboard's text, when a clipboard program can read it
+pub fn paste() -> Option<String> {
+    PASTE_PROGRAMS.iter().find_map(|&(program, args)| {
+        let output = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    })
+}
+
+/// Escape sequence asking the terminal to set its clipboard to `text`
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Standard base64 with padding
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(osc52("hi\n"), "\x1b]52;c;aGkK\x07");
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::project::{CargoTargets, MakeTargets, NpmScripts};
+
+/// Offers the next word of a command from a project file, such as the
+/// targets of a Makefile after `make`
+pub trait Completer {
+    /// Kind of word wanted after `previous` (the words before the one being
+    /// completed), or `None` if this completer doesn't apply
+    fn slot(&self, previous: &[&str]) -> Option<&'static str>;
+
+    /// Project file the words come from, found from the working directory
+    fn source(&self, cwd: &Path) -> Option<PathBuf>;
+
+    /// Paths whose modification invalidates the cached words
+    fn watched(&self, source: &Path) -> Vec<PathBuf> {
+        vec![source.to_path_buf()]
+    }
+
+    /// Every word the project offers, with the slot it fills
+    fn read(&self, source: &Path) -> Vec<(&'static str, String)>;
+}
+
+/// Words read by one completer, with the modification time they match
+struct CacheEntry {
+    stamp: Option<SystemTime>,
+    words: Vec<(&'static str, String)>,
+}
+
+/// The registered completers, with their words cached per directory
+pub struct Completers {
+    completers: Vec<Box<dyn Completer>>,
+    cache: HashMap<(usize, PathBuf), CacheEntry>,
+}
+
+impl Completers {
+    /// Create a registry with the built-in make, cargo and npm completers
+    pub fn new() -> Self {
+        let mut completers = Self {
+            completers: Vec::new(),
+            cache: HashMap::new(),
+        };
+        completers.register(Box::new(MakeTargets));
+        completers.register(Box::new(CargoTargets));
+        completers.register(Box::new(NpmScripts));
+        completers
+    }
+
+    /// Add a completer; earlier ones take precedence
+    pub fn register(&mut self, completer: Box<dyn Completer>) {
+        self.completers.push(completer);
+    }
+
+    /// Words offered after `previous` in `cwd`, from the first completer
+    /// that applies and finds its project file
+    pub fn words(&mut self, previous: &[&str], cwd: &Path) -> Option<Vec<String>> {
+        for (index, completer) in self.completers.iter().enumerate() {
+            let Some(slot) = completer.slot(previous) else {
+                continue;
+            };
+            let Some(source) = completer.source(cwd) else {
+                continue;
+            };
+
+            // Re-read the project file only when it (or a watched
+            // directory) has changed since it was cached
+            let stamp = completer
+                .watched(&source)
+                .iter()
+                .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+                .max();
+            let key = (index, cwd.to_path_buf());
+            let entry = match self.cache.get(&key) {
+                Some(entry) if entry.stamp == stamp => entry,
+                _ => {
+                    let words = completer.read(&source);
+                    self.cache.insert(key.clone(), CacheEntry { stamp, words });
+                    &self.cache[&key]
+                }
+            };
+
+            return Some(
+                entry
+                    .words
+                    .iter()
+                    .filter(|(s, _)| *s == slot)
+                    .map(|(_, word)| word.clone())
+                    .collect(),
+            );
+        }
+        None
+    }
+}
+
+impl Default for Completers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Nearest file named `name` in `cwd` or one of its ancestors
+pub fn find_upward(cwd: &Path, name: &str) -> Option<PathBuf> {
+    cwd.ancestors()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,499 @@
+//! Tab completion for commands, `jerm` builtins, project targets, and file
+//! paths
+//!
+//! Candidates must start with the typed word. When nothing does, `jerm`
+//! subcommands, project targets, and paths fall back to fuzzy matches, best
+//! first. Project targets (`make <Tab>`, `npm run <Tab>`, ...) come from the
+//! [`completer::Completer`]s registered in [`Completers`].
+
+pub mod completer;
+mod project;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::fuzzy::Matcher;
+
+pub use completer::Completers;
+
+/// Builtins handled by jerm itself
+const BUILTINS: &[&str] = &[
+    "cd", "z", "clear", "exit", "quit", "jobs", "fg", "bg", "jerm", "alias", "unalias", "export",
+    "unset", "env",
+];
+
+/// Characters escaped in completed paths
+const SHELL_SPECIAL: &str = " \t\n'\"`$&;|<>()*?[]{}!#\\";
+
+/// Subcommands of the `jerm` builtin
+const JERM_SUBCOMMANDS: &[&str] = &[
+    "save", "goto", "rm", "list", "record", "stop", "play", "timeline", "profile", "theme",
+    "snippet", "shell", "retry", "diffdir", "note", "heatmap", "doctor", "git", "branch", "test",
+    "pty",
+];
+
+/// A completion candidate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// Text that replaces the word being completed
+    pub replacement: String,
+    /// Short label shown in the popup (file name without its directory)
+    pub display: String,
+    /// Whether the candidate is a directory (no trailing space is added)
+    pub is_dir: bool,
+}
+
+/// Candidates for the word ending at the cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Byte offset where the word being completed starts
+    pub start: usize,
+    /// Matching candidates, sorted
+    pub candidates: Vec<Candidate>,
+}
+
+/// Completion popup state while cycling through multiple candidates
+#[derive(Debug, Clone)]
+pub struct CompletionMenu {
+    /// Byte offset where the completed word starts
+    pub start: usize,
+    /// Candidates being cycled through
+    pub candidates: Vec<Candidate>,
+    /// Candidate currently inserted, if cycling has started
+    pub selected: Option<usize>,
+}
+
+impl CompletionMenu {
+    /// Move to the next candidate (wrapping) and return it
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &Candidate {
+        let index = self.selected.map_or(0, |i| (i + 1) % self.candidates.len());
+        self.selected = Some(index);
+        &self.candidates[index]
+    }
+
+    /// Move to the previous candidate (wrapping) and return it
+    pub fn prev(&mut self) -> &Candidate {
+        let len = self.candidates.len();
+        let index = self.selected.map_or(len - 1, |i| (i + len - 1) % len);
+        self.selected = Some(index);
+        &self.candidates[index]
+    }
+}
+
+/// Complete the word ending at `cursor` (a byte offset into `input`)
+pub fn complete(
+    input: &str,
+    cursor: usize,
+    cwd: &Path,
+    completers: &mut Completers,
+    matcher: &dyn Matcher,
+) -> Completion {
+    let before = &input[..cursor];
+    let start = word_start(before);
+    let word = &before[start..];
+    let previous: Vec<&str> = before[..start].split_whitespace().collect();
+
+    let candidates = match previous.as_slice() {
+        [] if !word.contains('/') => command_candidates(word),
+        ["jerm"] => prefixed_or_fuzzy(JERM_SUBCOMMANDS.iter().copied(), word, matcher),
+        ["cd"] => path_candidates(word, cwd, true, matcher),
+        _ => match completers.words(&previous, cwd) {
+            Some(words) => prefixed_or_fuzzy(words.iter().map(String::as_str), word, matcher),
+            None => path_candidates(word, cwd, false, matcher),
+        },
+    };
+
+    Completion { start, candidates }
+}
+
+/// Longest prefix shared by every candidate's replacement text
+pub fn common_prefix(candidates: &[Candidate]) -> String {
+    let Some(first) = candidates.first() else {
+        return String::new();
+    };
+    let mut prefix = first.replacement.as_str();
+    for candidate in &candidates[1..] {
+        let shared = prefix
+            .char_indices()
+            .zip(candidate.replacement.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(
+                prefix.len().min(candidate.replacement.len()),
+                |((i, _), _)| i,
+            );
+        prefix = &prefix[..shared];
+    }
+    prefix.to_string()
+}
+
+/// Byte offset where the word before the cursor starts (after an
+/// unescaped space)
+fn word_start(before: &str) -> usize {
+    let bytes = before.as_bytes();
+    let mut index = bytes.len();
+    while index > 0 {
+        let escaped = index >= 2 && bytes[index - 2] == b'\\';
+        if bytes[index - 1] == b' ' && !escaped {
+            break;
+        }
+        index -= 1;
+    }
+    index
+}
+
+/// Candidates from a fixed list of words
+fn word_candidates<'a>(words: impl Iterator<Item = &'a str>, prefix: &str) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = words
+        .filter(|w| w.starts_with(prefix))
+        .map(|w| Candidate {
+            replacement: w.to_string(),
+            display: w.to_string(),
+            is_dir: false,
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.replacement.cmp(&b.replacement));
+    candidates.dedup();
+    candidates
+}
+
+/// Words starting with `word`, or the fuzzy matches when none do
+fn prefixed_or_fuzzy<'a>(
+    words: impl Iterator<Item = &'a str> + Clone,
+    word: &str,
+    matcher: &dyn Matcher,
+) -> Vec<Candidate> {
+    let candidates = word_candidates(words.clone(), word);
+    if candidates.is_empty() {
+        fuzzy_candidates(word_candidates(words, ""), word, matcher)
+    } else {
+        candidates
+    }
+}
+
+/// Candidates whose display text fuzzily matches `query`, best first
+fn fuzzy_candidates(
+    candidates: Vec<Candidate>,
+    query: &str,
+    matcher: &dyn Matcher,
+) -> Vec<Candidate> {
+    let mut scored: Vec<(i64, Candidate)> = candidates
+        .into_iter()
+        .filter_map(|c| Some((matcher.score(query, &c.display)?, c)))
+        .collect();
+    scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Builtins and executables on `$PATH` starting with `prefix`
+fn command_candidates(prefix: &str) -> Vec<Candidate> {
+    let mut names: Vec<String> = BUILTINS.iter().map(|b| (*b).to_string()).collect();
+
+    if let Some(path) = env::var_os("PATH") {
+        for dir in env::split_paths(&path) {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name.starts_with(prefix) && is_executable(&entry.path()) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+
+    word_candidates(names.iter().map(String::as_str), prefix)
+}
+
+/// Whether a path is an executable file
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+/// Files (or only directories) matching a partially typed path
+///
+/// Hidden files are only offered when the prefix starts with a dot.
+fn path_candidates(
+    word: &str,
+    cwd: &Path,
+    dirs_only: bool,
+    matcher: &dyn Matcher,
+) -> Vec<Candidate> {
+    let unescaped = unescape(word);
+    let (dir_part, file_prefix) = match unescaped.rfind('/') {
+        Some(slash) => unescaped.split_at(slash + 1),
+        None => ("", unescaped.as_str()),
+    };
+
+    let search_dir = resolve_dir(dir_part, cwd);
+    let Ok(entries) = fs::read_dir(search_dir) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<Candidate> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && !file_prefix.starts_with('.') {
+                return None;
+            }
+            let is_dir = entry.path().is_dir();
+            if dirs_only && !is_dir {
+                return None;
+            }
+            let suffix = if is_dir { "/" } else { "" };
+            Some(Candidate {
+                replacement: format!("{}{suffix}", escape(&format!("{dir_part}{name}"))),
+                display: format!("{name}{suffix}"),
+                is_dir,
+            })
+        })
+        .collect();
+    candidates.sort_by_key(|c| c.display.to_lowercase());
+
+    let prefixed: Vec<Candidate> = candidates
+        .iter()
+        .filter(|c| c.display.starts_with(file_prefix))
+        .cloned()
+        .collect();
+    if prefixed.is_empty() {
+        fuzzy_candidates(candidates, file_prefix, matcher)
+    } else {
+        prefixed
+    }
+}
+
+/// Backslash every character sh would otherwise treat specially
+///
+/// `~` is left alone so a typed `~/` still means the home directory.
+fn escape(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if SHELL_SPECIAL.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A typed word with its backslash escapes removed
+fn unescape(word: &str) -> String {
+    let mut unescaped = String::with_capacity(word.len());
+    let mut chars = word.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Directory to list for the directory part of a typed path
+fn resolve_dir(dir_part: &str, cwd: &Path) -> PathBuf {
+    if dir_part.is_empty() {
+        return cwd.to_path_buf();
+    }
+    if let Some(rest) = dir_part.strip_prefix('~') {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest.trim_start_matches('/'));
+        }
+    }
+    cwd.join(dir_part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::FuzzyConfig;
+    use crate::testutil::ScratchDir;
+
+    fn fixture() -> ScratchDir {
+        let dir = ScratchDir::new("complete");
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        fs::write(dir.join("src/main.rs"), "").unwrap();
+        fs::write(dir.join("setup file.txt"), "").unwrap();
+        fs::write(dir.join(".hidden"), "").unwrap();
+        dir
+    }
+
+    fn replacements(completion: &Completion) -> Vec<&str> {
+        completion
+            .candidates
+            .iter()
+            .map(|c| c.replacement.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_complete_paths() {
+        let dir = fixture();
+
+        let completion = complete(
+            "cat s",
+            5,
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(completion.start, 4);
+        assert_eq!(
+            replacements(&completion),
+            vec!["scripts/", "setup\\ file.txt", "src/"]
+        );
+
+        let completion = complete(
+            "cat src/m",
+            9,
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(replacements(&completion), vec!["src/main.rs"]);
+
+        let completion = complete(
+            "cd s",
+            4,
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(replacements(&completion), vec!["scripts/", "src/"]);
+
+        let completion = complete(
+            "ls .h",
+            5,
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(replacements(&completion), vec![".hidden"]);
+
+        // Nothing starts with "stp", so fuzzy matches are offered
+        let completion = complete(
+            "cat stp",
+            7,
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(replacements(&completion), vec!["setup\\ file.txt"]);
+    }
+
+    #[test]
+    fn test_complete_escapes_paths() {
+        let dir = ScratchDir::new("complete-escape");
+        fs::write(dir.join("it's (1) $x&y;z|*?[a].txt"), "").unwrap();
+        fs::create_dir_all(dir.join("a\\b")).unwrap();
+        fs::write(dir.join("a\\b/c d"), "").unwrap();
+
+        let completion = complete(
+            "cat i",
+            5,
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        let escaped = r"it\'s\ \(1\)\ \$x\&y\;z\|\*\?\[a\].txt";
+        assert_eq!(replacements(&completion), vec![escaped]);
+
+        // What was completed can be completed further
+        let input = format!("cat {escaped}");
+        let completion = complete(
+            &input,
+            input.len(),
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(replacements(&completion), vec![escaped]);
+
+        let completion = complete(
+            r"cat a\\b/",
+            9,
+            &dir,
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(replacements(&completion), vec![r"a\\b/c\ d"]);
+    }
+
+    #[test]
+    fn test_complete_builtins() {
+        let completion = complete(
+            "jerm ti",
+            7,
+            Path::new("/"),
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert_eq!(replacements(&completion), vec!["timeline"]);
+
+        let completion = complete(
+            "jo",
+            2,
+            Path::new("/"),
+            &mut Completers::new(),
+            &FuzzyConfig::default(),
+        );
+        assert!(replacements(&completion).contains(&"jobs"));
+    }
+
+    #[test]
+    fn test_complete_make_targets() {
+        let dir = ScratchDir::new("complete-make");
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        fs::write(dir.join("Makefile"), "build:\nbench:\ninstall:\n").unwrap();
+        fs::write(dir.join("docs/notes.txt"), "").unwrap();
+
+        let mut completers = Completers::new();
+        let completion = complete("make b", 6, &dir, &mut completers, &FuzzyConfig::default());
+        assert_eq!(replacements(&completion), vec!["bench", "build"]);
+
+        // Without a Makefile, paths are completed as usual
+        let docs = dir.join("docs");
+        let completion = complete("make n", 6, &docs, &mut completers, &FuzzyConfig::default());
+        assert_eq!(replacements(&completion), vec!["notes.txt"]);
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        let candidate = |s: &str| Candidate {
+            replacement: s.to_string(),
+            display: s.to_string(),
+            is_dir: false,
+        };
+        assert_eq!(
+            common_prefix(&[candidate("scripts/"), candidate("src/")]),
+            "s"
+        );
+        assert_eq!(
+            common_prefix(&[candidate("timeline"), candidate("timer")]),
+            "time"
+        );
+        assert_eq!(common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn test_menu_cycles() {
+        let candidate = |s: &str| Candidate {
+            replacement: s.to_string(),
+            display: s.to_string(),
+            is_dir: false,
+        };
+        let mut menu = CompletionMenu {
+            start: 0,
+            candidates: vec![candidate("a"), candidate("b")],
+            selected: None,
+        };
+        assert_eq!(menu.next().replacement, "a");
+        assert_eq!(menu.next().replacement, "b");
+        assert_eq!(menu.next().replacement, "a");
+        assert_eq!(menu.prev().replacement, "b");
+    }
+}
@@ -0,0 +1,222 @@
+//! Completers for project build files: Makefile targets, Cargo targets and
+//! commands, and package.json scripts
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::completer::{find_upward, Completer};
+
+/// Targets of the Makefile in the working directory, after `make`
+pub struct MakeTargets;
+
+impl Completer for MakeTargets {
+    fn slot(&self, previous: &[&str]) -> Option<&'static str> {
+        matches!(previous.first(), Some(&"make")).then_some("target")
+    }
+
+    fn source(&self, cwd: &Path) -> Option<PathBuf> {
+        // Same lookup order as make itself
+        ["GNUmakefile", "makefile", "Makefile"]
+            .iter()
+            .map(|name| cwd.join(name))
+            .find(|path| path.is_file())
+    }
+
+    fn read(&self, source: &Path) -> Vec<(&'static str, String)> {
+        let Ok(contents) = fs::read_to_string(source) else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            // Recipes, comments, and special targets like .PHONY
+            .filter(|line| !line.starts_with(['\t', ' ', '#', '.']))
+            .filter_map(|line| line.split_once(':'))
+            // Skip `VAR := value` and pattern or variable targets
+            .filter(|(targets, rest)| !rest.starts_with('=') && !targets.contains(['=', '$', '%']))
+            .flat_map(|(targets, _)| targets.split_whitespace())
+            .map(|target| ("target", target.to_string()))
+            .collect()
+    }
+}
+
+/// Cargo commands after `cargo`, and binary, test, example and bench names
+/// of the current package after `--bin`, `--test`, `--example` and `--bench`
+pub struct CargoTargets;
+
+/// Commonly used cargo commands
+const CARGO_COMMANDS: &[&str] = &[
+    "add", "bench", "build", "check", "clean", "clippy", "doc", "fetch", "fix", "fmt", "init",
+    "install", "metadata", "new", "publish", "remove", "run", "search", "test", "tree", "update",
+];
+
+/// Target kinds: the flag selecting one, the manifest table listing them,
+/// and the directory cargo discovers them in
+const CARGO_TARGETS: &[(&str, &str, &str)] = &[
+    ("--bin", "bin", "src/bin"),
+    ("--test", "test", "tests"),
+    ("--example", "example", "examples"),
+    ("--bench", "bench", "benches"),
+];
+
+impl Completer for CargoTargets {
+    fn slot(&self, previous: &[&str]) -> Option<&'static str> {
+        match previous {
+            ["cargo"] => Some("command"),
+            ["cargo", .., flag] => CARGO_TARGETS
+                .iter()
+                .find(|(f, _, _)| f == flag)
+                .map(|(_, kind, _)| *kind),
+            _ => None,
+        }
+    }
+
+    fn source(&self, cwd: &Path) -> Option<PathBuf> {
+        find_upward(cwd, "Cargo.toml")
+    }
+
+    fn watched(&self, source: &Path) -> Vec<PathBuf> {
+        let root = source.parent().unwrap_or(Path::new(""));
+        let mut paths = vec![source.to_path_buf()];
+        paths.extend(CARGO_TARGETS.iter().map(|(_, _, dir)| root.join(dir)));
+        paths
+    }
+
+    fn read(&self, source: &Path) -> Vec<(&'static str, String)> {
+        let mut words: Vec<(&'static str, String)> = CARGO_COMMANDS
+            .iter()
+            .map(|command| ("command", (*command).to_string()))
+            .collect();
+
+        let Ok(manifest) = fs::read_to_string(source)
+            .map_err(|_| ())
+            .and_then(|text| text.parse::<toml::Table>().map_err(|_| ()))
+        else {
+            return words;
+        };
+        let root = source.parent().unwrap_or(Path::new(""));
+
+        // The package's default binary is named after the package
+        if let Some(name) = manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+        {
+            if root.join("src/main.rs").is_file() {
+                words.push(("bin", name.to_string()));
+            }
+        }
+
+        for (_, kind, dir) in CARGO_TARGETS {
+            // Targets declared in `[[bin]]`, `[[test]]`, ... tables
+            let declared = manifest
+                .get(*kind)
+                .and_then(|t| t.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|t| t.get("name").and_then(|n| n.as_str()))
+                .map(str::to_string);
+            words.extend(declared.map(|name| (*kind, name)));
+
+            // Targets discovered from `name.rs` or `name/main.rs`
+            let Ok(entries) = fs::read_dir(root.join(dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = if path.is_dir() && path.join("main.rs").is_file() {
+                    path.file_name()
+                } else if path.extension().is_some_and(|e| e == "rs") {
+                    path.file_stem()
+                } else {
+                    None
+                };
+                if let Some(name) = name {
+                    words.push((*kind, name.to_string_lossy().into_owned()));
+                }
+            }
+        }
+        words
+    }
+}
+
+/// Scripts of the nearest package.json, after `npm run` and friends
+pub struct NpmScripts;
+
+impl Completer for NpmScripts {
+    fn slot(&self, previous: &[&str]) -> Option<&'static str> {
+        match previous {
+            ["npm" | "pnpm" | "yarn" | "bun", "run" | "run-script"] | ["yarn" | "pnpm"] => {
+                Some("script")
+            }
+            _ => None,
+        }
+    }
+
+    fn source(&self, cwd: &Path) -> Option<PathBuf> {
+        find_upward(cwd, "package.json")
+    }
+
+    fn read(&self, source: &Path) -> Vec<(&'static str, String)> {
+        let Ok(package) = fs::read_to_string(source)
+            .map_err(|_| ())
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).map_err(|_| ()))
+        else {
+            return Vec::new();
+        };
+        package
+            .get("scripts")
+            .and_then(|s| s.as_object())
+            .into_iter()
+            .flat_map(|scripts| scripts.keys())
+            .map(|name| ("script", name.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::Completers;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_project_words() {
+        let dir = ScratchDir::new("project");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::create_dir_all(dir.join("tests")).unwrap();
+        fs::write(
+            dir.join("Makefile"),
+            ".PHONY: build\nCC := gcc\nbuild test: deps\n\tcc main.c\n%.o: %.c\n# lint:\nlint:\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[[bin]]\nname = \"helper\"\npath = \"src/helper.rs\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src/main.rs"), "").unwrap();
+        fs::write(dir.join("tests/api.rs"), "").unwrap();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"scripts": {"dev": "vite", "lint": "eslint ."}}"#,
+        )
+        .unwrap();
+
+        let mut completers = Completers::new();
+        let mut words = |previous: &[&str]| {
+            let mut words = completers.words(previous, &dir)?;
+            words.sort();
+            Some(words)
+        };
+
+        assert_eq!(words(&["make"]).unwrap(), vec!["build", "lint", "test"]);
+        assert_eq!(
+            words(&["cargo", "run", "--bin"]).unwrap(),
+            vec!["demo", "helper"]
+        );
+        assert_eq!(words(&["cargo", "test", "--test"]).unwrap(), vec!["api"]);
+        assert!(words(&["cargo"]).unwrap().contains(&"clippy".to_string()));
+        assert_eq!(words(&["npm", "run"]).unwrap(), vec!["dev", "lint"]);
+        assert_eq!(words(&["cat"]), None);
+    }
+}
@@ -0,0 +1,760 @@
+//! User configuration loaded from `config.toml`
+//!
+//! The file lives in the active profile's config directory
+//! (`~/.config/jerm/config.toml` for the default profile). Every setting is
+//! optional; missing settings and a missing file fall back to the defaults
+//! below. The older `JERM_*` environment variables still work and take
+//! precedence over the file.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::fuzzy::FuzzyConfig;
+use crate::history::DEFAULT_HISTORY_SIZE;
+use crate::shortcuts::Backend;
+
+/// Errors that can occur while loading the config file
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse config file: {0}")]
+    Parse(#[from] toml::de::Error),
+}
+
+/// All user settings
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub ui: UiConfig,
+    pub theme: ThemeConfig,
+    pub prompt: PromptConfig,
+    pub git: GitConfig,
+    pub history: HistoryConfig,
+    pub fuzzy: FuzzyConfig,
+    pub paste: PasteConfig,
+    pub hooks: HooksConfig,
+    pub status: StatusConfig,
+    pub jobs: JobsConfig,
+    pub shell: ShellConfig,
+    pub storage: StorageConfig,
+    /// Action name to key chord (see [`crate::keymap::Action`])
+    pub keybindings: HashMap<String, String>,
+}
+
+/// Layout and screen settings
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Share of the screen's width taken by the shortcuts sidebar
+    pub sidebar_ratio: f64,
+    /// Seconds of inactivity before the screen locks (0 disables it)
+    pub idle_lock: u64,
+    /// Tint sidebar shortcuts by how often and recently they're used
+    pub shortcut_heat: bool,
+    /// Show a one-line synopsis of the command being typed
+    pub synopsis: bool,
+    /// Diff a command re-run from the timeline against its previous output
+    pub diff_reruns: bool,
+    /// How the sidebar shows when each shortcut was last used
+    pub shortcut_time: TimeDisplay,
+    /// Layout of the cd -list navigator
+    pub navigator_layout: NavLayout,
+    /// Whether long output lines wrap or are cut off at the pane edge
+    pub output_wrap: OutputWrap,
+    /// Open with a greeting and a summary of recent activity
+    pub greeting: bool,
+    /// Print a summary of the session on exit
+    pub exit_summary: bool,
+    /// Whether the quit chords quit at once, need a second press, or
+    /// never quit (leaving only `exit`)
+    pub quit_keys: QuitKeys,
+    /// Shown before each line a command writes to stderr
+    pub stderr_prefix: String,
+    /// Move an input longer than a couple of rows into a box of its own
+    /// under the output
+    pub input_box: bool,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            sidebar_ratio: 0.25,
+            idle_lock: 0,
+            shortcut_heat: true,
+            synopsis: true,
+            diff_reruns: true,
+            shortcut_time: TimeDisplay::Compact,
+            navigator_layout: NavLayout::List,
+            output_wrap: OutputWrap::Wrap,
+            greeting: true,
+            exit_summary: true,
+            quit_keys: QuitKeys::Single,
+            stderr_prefix: String::new(),
+            input_box: false,
+        }
+    }
+}
+
+/// Layout of the cd -list navigator
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NavLayout {
+    /// The current directory as a single list
+    #[default]
+    List,
+    /// Parent, current, and selected directories side by side (like ranger)
+    Columns,
+}
+
+/// What Ctrl+D, or Ctrl+C on an empty line, does
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuitKeys {
+    /// Quit on the first press
+    #[default]
+    Single,
+    /// Quit on a second press of the same chord in quick succession
+    Double,
+    /// Never quit; only `exit` does
+    Off,
+}
+
+/// How output lines longer than the pane are shown
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputWrap {
+    /// Continue on the next row
+    #[default]
+    Wrap,
+    /// Cut off at the edge, with Left/Right scrolling sideways
+    Truncate,
+}
+
+/// How a shortcut's last access time is shown
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeDisplay {
+    /// Terse relative time: `5m`, `2h`, `1mo`
+    #[default]
+    Compact,
+    /// Spelled out: `5 minutes ago`
+    Relative,
+    /// Local clock time today, the date before that
+    Absolute,
+    /// No time
+    Hidden,
+}
+
+/// Appearance settings
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// Color theme: a preset (`default`, `light`) or a file in `themes/`
+    pub name: String,
+    /// Use Nerd Font icons
+    pub nerd_fonts: bool,
+    /// Least contrast ratio for text on selected rows (0 = no check)
+    pub min_contrast: f64,
+    /// What to do with a theme below `min_contrast`
+    pub contrast: ContrastMode,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            nerd_fonts: false,
+            min_contrast: 3.0,
+            contrast: ContrastMode::Adjust,
+        }
+    }
+}
+
+/// Handling of theme colors below the minimum contrast
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContrastMode {
+    /// Move the text colors until they're readable
+    #[default]
+    Adjust,
+    /// Keep the colors and print a warning for each pair
+    Warn,
+}
+
+/// Prompt settings
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromptConfig {
+    /// Prompt layout; see [`PromptToken`] for the placeholders
+    pub format: String,
+    /// Commands at least this many seconds long show their duration
+    pub report_time: f64,
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            format: DEFAULT_PROMPT_FORMAT.to_string(),
+            report_time: 2.0,
+        }
+    }
+}
+
+/// Git integration settings
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitConfig {
+    /// Seconds between background git refreshes
+    pub poll_interval: u64,
+    /// When background refreshes also run `git fetch`
+    pub fetch: FetchPolicy,
+    /// Underline common misspellings in `git commit -m` messages while
+    /// they're typed
+    pub spell_check: bool,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: 30,
+            fetch: FetchPolicy::Timer,
+            spell_check: false,
+        }
+    }
+}
+
+/// When jerm runs `git fetch` without being asked
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FetchPolicy {
+    /// With every background refresh
+    #[default]
+    Timer,
+    /// When the terminal window regains focus; timed refreshes only read
+    /// the local status
+    Focus,
+}
+
+/// Cleanup applied to text pasted into the input line
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasteConfig {
+    /// Remove a leading `$ ` prompt from each pasted line
+    pub strip_prompts: bool,
+    /// Turn typographic quotes into plain ASCII quotes
+    pub ascii_quotes: bool,
+    /// Warn about invisible characters such as zero-width spaces and
+    /// right-to-left overrides
+    pub warn_invisible: bool,
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self {
+            strip_prompts: true,
+            ascii_quotes: true,
+            warn_invisible: true,
+        }
+    }
+}
+
+/// Commands run on events, through `sh -c`
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Run after every change of directory, in the new directory
+    pub chpwd: Vec<String>,
+}
+
+/// External command feeding the `{status}` prompt segment
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusConfig {
+    /// Command whose first line of output is shown (disabled when empty)
+    pub command: String,
+    /// Seconds between runs
+    pub interval: u64,
+    /// Also show the segment in the status bar
+    pub status_bar: bool,
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            interval: 10,
+            status_bar: false,
+        }
+    }
+}
+
+/// Limits on commands running at once, and what finished jobs do
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobsConfig {
+    /// Most commands running at once, foreground and background together;
+    /// further ones wait in the queue (0 for no limit)
+    pub max_running: usize,
+    /// How a background job that finishes gets noticed
+    pub on_finish: JobAlert,
+}
+
+/// How a finished background job gets noticed beyond its `Done` line
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobAlert {
+    /// Just the `Done` line
+    #[default]
+    Off,
+    /// The terminal pane's frame briefly takes the color of how it ended,
+    /// over whatever view is open
+    Highlight,
+    /// Highlight, and scroll the output back down to the `Done` line when
+    /// browsing scrollback
+    Focus,
+}
+
+/// How typed commands are interpreted
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShellConfig {
+    /// A directory entered on its own is changed into, like zsh's
+    /// `AUTO_CD`, unless a command of that name exists
+    pub auto_cd: bool,
+    /// Programs run in a pseudo-terminal besides the built-in ones, such as
+    /// `k9s` or `mc`
+    pub pty_commands: Vec<String>,
+}
+
+/// Where saved data is kept
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Backend for the shortcuts
+    pub shortcuts: Backend,
+}
+
+/// Which commands Up-arrow cycles through first
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryScope {
+    /// All commands, most recent first
+    #[default]
+    Global,
+    /// Commands run in the current directory first, then the rest
+    Directory,
+}
+
+/// Command history settings
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Number of commands kept on disk
+    pub size: usize,
+    pub scope: HistoryScope,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            size: DEFAULT_HISTORY_SIZE,
+            scope: HistoryScope::Global,
+        }
+    }
+}
+
+/// Prompt layout used when none is configured
+pub const DEFAULT_PROMPT_FORMAT: &str = "{cwd} {note}{git}{duration}$ ";
+
+/// Piece of a prompt format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptToken {
+    /// Literal text
+    Text(String),
+    /// `{cwd}`: working directory with `~` for home
+    Cwd,
+    /// `{git}`: branch, dirty marker and ahead/behind counts plus a trailing
+    /// space, or nothing outside a repository
+    Git,
+    /// `{duration}`: `took 3.2s ` after a slow command, otherwise nothing
+    Duration,
+    /// `{profile}`: name of the active profile
+    Profile,
+    /// `{note}`: a note marker plus a trailing space when the working
+    /// directory has a note, otherwise nothing
+    Note,
+    /// `{status}`: output of `status.command`, plus a trailing space when
+    /// there is any
+    Status,
+}
+
+/// Split a prompt format into tokens; unknown placeholders stay literal
+pub fn parse_prompt_format(format: &str) -> Vec<PromptToken> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = format;
+
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let Some(close) = rest.find('}') else {
+            break;
+        };
+        let token = match &rest[1..close] {
+            "cwd" => PromptToken::Cwd,
+            "git" => PromptToken::Git,
+            "duration" => PromptToken::Duration,
+            "profile" => PromptToken::Profile,
+            "note" => PromptToken::Note,
+            "status" => PromptToken::Status,
+            _ => {
+                text.push_str(&rest[..=close]);
+                rest = &rest[close + 1..];
+                continue;
+            }
+        };
+        if !text.is_empty() {
+            tokens.push(PromptToken::Text(std::mem::take(&mut text)));
+        }
+        tokens.push(token);
+        rest = &rest[close + 1..];
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(PromptToken::Text(text));
+    }
+    tokens
+}
+
+impl Config {
+    /// Load the active profile's config, falling back to defaults
+    ///
+    /// Returns the config and a warning for each problem found, for display
+    /// at startup; a broken file never prevents jerm from starting.
+    pub fn load() -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut config = match get_config_path() {
+            Some(path) => match load_from(&path) {
+                Ok(config) => config,
+                Err(e) => {
+                    warnings.push(format!("{}: {e}", path.display()));
+                    Self::default()
+                }
+            },
+            None => Self::default(),
+        };
+        config.apply_env();
+        warnings.extend(config.validate());
+        (config, warnings)
+    }
+
+    /// Apply the `JERM_*` environment variables on top of the file
+    fn apply_env(&mut self) {
+        if let Ok(value) = env::var("JERM_NERD_FONTS") {
+            self.theme.nerd_fonts = value == "1" || value.eq_ignore_ascii_case("true");
+        }
+        if let Some(size) = env_parse::<usize>("JERM_HISTORY_SIZE").filter(|size| *size > 0) {
+            self.history.size = size;
+        }
+        if let Ok(scope) = env::var("JERM_HISTORY_SCOPE") {
+            self.history.scope = if scope.eq_ignore_ascii_case("directory") {
+                HistoryScope::Directory
+            } else {
+                HistoryScope::Global
+            };
+        }
+        if let Some(secs) = env_parse::<f64>("JERM_REPORT_TIME") {
+            self.prompt.report_time = secs;
+        }
+        if let Some(secs) = env_parse::<u64>("JERM_IDLE_LOCK") {
+            self.ui.idle_lock = secs;
+        }
+    }
+
+    /// Replace out-of-range values with defaults, describing each fix
+    fn validate(&mut self) -> Vec<String> {
+        let defaults = Self::default();
+        let mut warnings = Vec::new();
+
+        if !(0.05..=0.9).contains(&self.ui.sidebar_ratio) {
+            warnings.push(format!(
+                "ui.sidebar_ratio must be between 0.05 and 0.9, using {}",
+                defaults.ui.sidebar_ratio
+            ));
+            self.ui.sidebar_ratio = defaults.ui.sidebar_ratio;
+        }
+        if !self.prompt.report_time.is_finite() || self.prompt.report_time < 0.0 {
+            warnings.push(format!(
+                "prompt.report_time must be a positive number, using {}",
+                defaults.prompt.report_time
+            ));
+            self.prompt.report_time = defaults.prompt.report_time;
+        }
+        let contrast = self.theme.min_contrast;
+        if !(contrast == 0.0 || (1.0..=21.0).contains(&contrast)) {
+            warnings.push(format!(
+                "theme.min_contrast must be 0 or from 1 to 21, using {}",
+                defaults.theme.min_contrast
+            ));
+            self.theme.min_contrast = defaults.theme.min_contrast;
+        }
+        if self.status.interval == 0 {
+            warnings.push(format!(
+                "status.interval must be at least 1, using {}",
+                defaults.status.interval
+            ));
+            self.status.interval = defaults.status.interval;
+        }
+        if self.git.poll_interval == 0 {
+            warnings.push(format!(
+                "git.poll_interval must be at least 1, using {}",
+                defaults.git.poll_interval
+            ));
+            self.git.poll_interval = defaults.git.poll_interval;
+        }
+        if !self.storage.shortcuts.is_available() {
+            warnings.push(
+                "storage.shortcuts = \"sqlite\" needs jerm built with the sqlite feature, \
+                 using json"
+                    .to_string(),
+            );
+            self.storage.shortcuts = defaults.storage.shortcuts;
+        }
+        if self.history.size == 0 {
+            warnings.push(format!(
+                "history.size must be at least 1, using {}",
+                defaults.history.size
+            ));
+            self.history.size = defaults.history.size;
+        }
+        warnings
+    }
+
+    /// Inactivity period before the screen locks
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.ui.idle_lock > 0).then(|| Duration::from_secs(self.ui.idle_lock))
+    }
+
+    /// Threshold for showing a command's duration in the prompt
+    pub fn report_time(&self) -> Duration {
+        Duration::from_secs_f64(self.prompt.report_time)
+    }
+
+    /// Interval between background git refreshes
+    pub fn git_poll_interval(&self) -> Duration {
+        Duration::from_secs(self.git.poll_interval)
+    }
+}
+
+/// Get the path to the active profile's config file
+pub fn get_config_path() -> Option<PathBuf> {
+    crate::paths::config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Load a config file; a missing file yields the defaults
+pub fn load_from(path: &Path) -> Result<Config, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(toml::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Set `key` in the `[table]` section of a config file to `value` (a TOML
+/// literal such as `32` or `"wrap"`), leaving the rest of the file, comments
+/// included, as it was
+///
+/// The file isn't written if the result wouldn't load.
+pub fn set_value(path: &Path, table: &str, key: &str, value: &str) -> Result<(), ConfigError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let updated = with_value(&content, table, key, value);
+    toml::from_str::<Config>(&updated)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, updated)?;
+    Ok(())
+}
+
+/// `content` with `key = value` in its `[table]` section, replacing the
+/// key's line (but not its comment) or adding one
+fn with_value(content: &str, table: &str, key: &str, value: &str) -> String {
+    let uncommented = |line: &str| line.split('#').next().unwrap_or("").trim().to_string();
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let entry = format!("{key} = {value}");
+
+    let Some(header) = lines
+        .iter()
+        .position(|line| uncommented(line) == format!("[{table}]"))
+    else {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.extend([format!("[{table}]"), entry]);
+        return lines.join("\n") + "\n";
+    };
+    let end = lines[header + 1..]
+        .iter()
+        .position(|line| uncommented(line).starts_with('['))
+        .map_or(lines.len(), |offset| header + 1 + offset);
+
+    let existing = (header + 1..end).find(|&index| {
+        let line = uncommented(&lines[index]);
+        line.split('=')
+            .next()
+            .is_some_and(|name| name.trim() == key)
+    });
+    match existing {
+        Some(index) => {
+            let line = &lines[index];
+            let comment = line.find('#').map(|at| {
+                let padding = at.saturating_sub(entry.len()).max(1);
+                format!("{}{}", " ".repeat(padding), &line[at..])
+            });
+            lines[index] = entry + comment.as_deref().unwrap_or("");
+        }
+        None => {
+            let last = (header..end)
+                .rev()
+                .find(|&index| !lines[index].trim().is_empty())
+                .unwrap_or(header);
+            lines.insert(last + 1, entry);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Parse an environment variable, ignoring it when unset or malformed
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let dir = ScratchDir::new("config");
+        let path = dir.join("config.toml");
+        assert_eq!(load_from(&path).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_partial_file() {
+        let config: Config = toml::from_str(
+            r#"
+            [ui]
+            sidebar_ratio = 0.3
+
+            [history]
+            scope = "directory"
+
+            [git]
+            fetch = "focus"
+
+            [hooks]
+            chpwd = ["tmux rename-window \"$(basename \"$PWD\")\""]
+
+            [fuzzy]
+            algorithm = "subsequence"
+
+            [fuzzy.weights]
+            boundary = 2
+
+            [keybindings]
+            clear_screen = "ctrl+k"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.ui.sidebar_ratio, 0.3);
+        assert_eq!(config.ui.idle_lock, 0);
+        assert_eq!(config.history.scope, HistoryScope::Directory);
+        assert_eq!(config.history.size, DEFAULT_HISTORY_SIZE);
+        assert_eq!(config.git.fetch, FetchPolicy::Focus);
+        assert_eq!(config.git.poll_interval, 30);
+        assert_eq!(config.hooks.chpwd.len(), 1);
+        assert_eq!(config.prompt.format, DEFAULT_PROMPT_FORMAT);
+        assert_eq!(config.keybindings["clear_screen"], "ctrl+k");
+        assert_eq!(config.fuzzy.algorithm, crate::fuzzy::Algorithm::Subsequence);
+        assert_eq!(config.fuzzy.weights.boundary, 2);
+        assert_eq!(config.fuzzy.weights.consecutive, 4);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_errors() {
+        assert!(toml::from_str::<Config>("[ui]\nsidebar = 3\n").is_err());
+    }
+
+    #[test]
+    fn test_validate_replaces_bad_values() {
+        let mut config = Config::default();
+        config.ui.sidebar_ratio = 2.0;
+        config.git.poll_interval = 0;
+
+        assert_eq!(config.validate().len(), 2);
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_with_value_keeps_the_rest() {
+        let content = "# jerm\n[ui]\nsidebar_ratio = 0.25   # of the width\ngreeting = false\n\n\
+                       [git]\nfetch = \"focus\"\n";
+        assert_eq!(
+            with_value(content, "ui", "sidebar_ratio", "0.35"),
+            content.replace("0.25   #", "0.35   #")
+        );
+        assert_eq!(
+            with_value(content, "git", "poll_interval", "60"),
+            format!("{content}poll_interval = 60\n")
+        );
+        assert_eq!(
+            with_value("[git]\nfetch = \"focus\"\n", "ui", "sidebar_ratio", "0.3"),
+            "[git]\nfetch = \"focus\"\n\n[ui]\nsidebar_ratio = 0.3\n"
+        );
+        assert_eq!(
+            with_value("", "ui", "greeting", "true"),
+            "[ui]\ngreeting = true\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_prompt_format() {
+        assert_eq!(
+            parse_prompt_format(DEFAULT_PROMPT_FORMAT),
+            vec![
+                PromptToken::Cwd,
+                PromptToken::Text(" ".to_string()),
+                PromptToken::Note,
+                PromptToken::Git,
+                PromptToken::Duration,
+                PromptToken::Text("$ ".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_prompt_format("[{profile}] {nope} {cwd"),
+            vec![
+                PromptToken::Text("[".to_string()),
+                PromptToken::Profile,
+                PromptToken::Text("] {nope} {cwd".to_string()),
+            ]
+        );
+    }
+}
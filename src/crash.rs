@@ -0,0 +1,177 @@
+//! Getting the terminal back when jerm panics
+//!
+//! While the UI runs the terminal is in raw mode on the alternate screen.
+//! A panic on the main thread would leave it that way, so the panic hook
+//! restores it before the message is printed, and [`TerminalGuard`] does
+//! the same when `main` returns early. After a panic in the event loop,
+//! `main` writes the message and the end of the output buffer to
+//! `crash.log` in jerm's config directory.
+//!
+//! A panic on a named worker thread is kept for its supervisor to report
+//! (see [`crate::events::supervisor`]) instead of being printed over the UI.
+
+use std::any::Any;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+use crossterm::{
+    cursor,
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+/// Output lines kept in a crash report
+const REPORT_LINES: usize = 200;
+
+/// Whether the terminal is currently set up for the UI
+static UI_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The last panic on the main thread, with its location
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Panics on named worker threads, by thread name, until reported
+static WORKER_PANICS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Puts the terminal in UI mode and takes it out again when dropped
+pub struct TerminalGuard(());
+
+impl TerminalGuard {
+    /// Enter raw mode and the alternate screen, with mouse, paste, and
+    /// focus reporting
+    pub fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        UI_ACTIVE.store(true, Ordering::SeqCst);
+        let guard = Self(());
+        execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste,
+            EnableFocusChange
+        )?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+/// Undo what [`TerminalGuard::enter`] did; does nothing if it's already undone
+pub fn restore_terminal() -> io::Result<()> {
+    if !UI_ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        DisableFocusChange,
+        cursor::Show
+    )
+}
+
+/// Restore the terminal before a panic on the calling thread is reported
+///
+/// While the UI is up, a panic on a named worker thread is kept for
+/// [`take_worker_panic`] rather than printed; other threads only print
+/// their message, and the UI keeps running without them.
+pub fn install_panic_hook() {
+    let main_thread: ThreadId = thread::current().id();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let current = thread::current();
+        if current.id() == main_thread {
+            let _ = restore_terminal();
+            if let Ok(mut last) = LAST_PANIC.lock() {
+                *last = Some(info.to_string());
+            }
+        } else if let Some(name) = current.name() {
+            if let Ok(mut panics) = WORKER_PANICS.lock() {
+                panics.push((name.to_string(), info.to_string()));
+                if UI_ACTIVE.load(Ordering::SeqCst) {
+                    return;
+                }
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+/// The message of the last panic on the worker thread named `thread`
+pub fn take_worker_panic(thread: &str) -> Option<String> {
+    let mut panics = WORKER_PANICS.lock().ok()?;
+    let index = panics.iter().rposition(|(name, _)| name == thread)?;
+    Some(panics.remove(index).1)
+}
+
+/// Write a crash report for a panic caught on the main thread, returning
+/// where it went
+pub fn write_report(payload: &(dyn Any + Send), output: &[String]) -> io::Result<PathBuf> {
+    let message = LAST_PANIC
+        .lock()
+        .ok()
+        .and_then(|mut last| last.take())
+        .unwrap_or_else(|| panic_message(payload));
+    let dir = crate::paths::root_config_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("crash.log");
+    fs::write(&path, report(&message, output))?;
+    Ok(path)
+}
+
+/// The text of a panic payload (a `&str` or `String` for `panic!` with a message)
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic with a non-string payload".to_string())
+}
+
+fn report(message: &str, output: &[String]) -> String {
+    let tail = &output[output.len().saturating_sub(REPORT_LINES)..];
+    let mut text = format!(
+        "jerm {} crashed at {}\n{message}\n\nLast {} lines of output:\n",
+        env!("CARGO_PKG_VERSION"),
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z"),
+        tail.len()
+    );
+    for line in tail {
+        text.push_str(&crate::ansi::strip(line));
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let output: Vec<String> = (0..250)
+            .map(|i| format!("\x1b[1mline {i}\x1b[0m"))
+            .collect();
+        let text = report("panicked at src/app.rs:1:1:\nboom", &output);
+        assert!(text.contains("boom\n\nLast 200 lines of output:\nline 50\n"));
+        assert!(text.ends_with("line 249\n"));
+
+        let payload: Box<dyn Any + Send> = Box::new(format!("index {}", 3));
+        assert_eq!(panic_message(payload.as_ref()), "index 3");
+    }
+}
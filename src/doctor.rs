@@ -0,0 +1,243 @@
+//! `jerm doctor`: check the environment jerm runs in and suggest fixes
+//!
+//! Each check looks at one thing that commonly makes jerm look or behave
+//! broken (a missing git, no truecolor, a non-UTF-8 locale, ...). The same
+//! checks run quietly at startup, which only mentions that problems exist.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::Config;
+use crate::theme::Icons;
+
+/// How a check turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// Works, but something will look or behave worse than it could
+    Warning,
+    /// Something jerm relies on is broken
+    Problem,
+}
+
+/// Result of one check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    /// What was found
+    pub detail: String,
+    /// What to do about it, when it isn't fine
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn failed(name: &'static str, status: Status, detail: impl Into<String>, fix: &str) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+            fix: Some(fix.to_string()),
+        }
+    }
+
+    /// Report lines, e.g. `✗ locale    LANG=C is not UTF-8` and its fix
+    pub fn lines(&self) -> Vec<String> {
+        let marker = match self.status {
+            Status::Ok => "\u{2713}",
+            Status::Warning => "!",
+            Status::Problem => "\u{2717}",
+        };
+        let mut lines = vec![format!("{marker} {:<10}{}", self.name, self.detail)];
+        if let Some(ref fix) = self.fix {
+            lines.push(format!("  {:<10}\u{2192} {fix}", ""));
+        }
+        lines
+    }
+}
+
+/// Run every check
+pub fn run(config: &Config) -> Vec<Check> {
+    let var = |name| {
+        env::var(name)
+            .ok()
+            .filter(|value: &String| !value.is_empty())
+    };
+    vec![
+        check_git(),
+        check_truecolor(var("COLORTERM").as_deref()),
+        check_nerd_fonts(config.theme.nerd_fonts),
+        check_config_dir(crate::paths::config_dir().as_deref()),
+        check_locale(&[
+            ("LC_ALL", var("LC_ALL")),
+            ("LC_CTYPE", var("LC_CTYPE")),
+            ("LANG", var("LANG")),
+        ]),
+        check_shell(var("SHELL").as_deref()),
+    ]
+}
+
+/// Number of checks with a problem
+pub fn problems(checks: &[Check]) -> usize {
+    checks
+        .iter()
+        .filter(|c| c.status == Status::Problem)
+        .count()
+}
+
+fn check_git() -> Check {
+    match Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => Check::ok(
+            "git",
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ),
+        _ => Check::failed(
+            "git",
+            Status::Problem,
+            "git was not found on PATH; the prompt shows no branch or status",
+            "install git (e.g. `xcode-select --install` or your package manager)",
+        ),
+    }
+}
+
+fn check_truecolor(colorterm: Option<&str>) -> Check {
+    match colorterm {
+        Some(value @ ("truecolor" | "24bit")) => Check::ok("truecolor", format!("COLORTERM={value}")),
+        _ => Check::failed(
+            "truecolor",
+            Status::Warning,
+            "COLORTERM doesn't announce 24-bit color; theme colors may be approximated",
+            "use a truecolor terminal (iTerm2, kitty, WezTerm, ...) or export COLORTERM=truecolor if yours supports it",
+        ),
+    }
+}
+
+fn check_nerd_fonts(enabled: bool) -> Check {
+    let glyph = Icons::FOLDER.nerd;
+    if enabled {
+        Check::ok(
+            "nerd font",
+            format!("icons enabled; this should be a folder: {glyph}"),
+        )
+    } else {
+        Check::ok(
+            "nerd font",
+            format!("icons disabled; if this is a folder, set theme.nerd_fonts = true: {glyph}"),
+        )
+    }
+}
+
+fn check_config_dir(dir: Option<&Path>) -> Check {
+    let Some(dir) = dir else {
+        return Check::failed(
+            "config",
+            Status::Problem,
+            "no config directory found; shortcuts and history won't be saved",
+            "set JERM_DATA_DIR to a writable directory",
+        );
+    };
+    match crate::paths::probe_writable(dir) {
+        Ok(()) => Check::ok("config", format!("{} is writable", dir.display())),
+        Err(e) => Check::failed(
+            "config",
+            Status::Problem,
+            format!("can't write to {}: {e}", dir.display()),
+            "fix the directory's permissions, or set JERM_DATA_DIR to a writable directory",
+        ),
+    }
+}
+
+/// The first set of `LC_ALL`, `LC_CTYPE`, `LANG` decides the encoding
+fn check_locale(vars: &[(&str, Option<String>)]) -> Check {
+    let fix = "export LANG=en_US.UTF-8 (or another UTF-8 locale) in your shell profile";
+    let Some((name, value)) = vars
+        .iter()
+        .find_map(|(name, value)| Some((name, value.as_deref()?)))
+    else {
+        return Check::failed(
+            "locale",
+            Status::Warning,
+            "no locale set; icons and box drawing may show as ?",
+            fix,
+        );
+    };
+    let lower = value.to_lowercase();
+    if lower.contains("utf-8") || lower.contains("utf8") {
+        Check::ok("locale", format!("{name}={value}"))
+    } else {
+        Check::failed(
+            "locale",
+            Status::Warning,
+            format!("{name}={value} is not UTF-8; icons and box drawing may show as ?"),
+            fix,
+        )
+    }
+}
+
+fn check_shell(shell: Option<&str>) -> Check {
+    match shell {
+        Some(shell) if Path::new(shell).is_file() => Check::ok("shell", shell.to_string()),
+        Some(shell) => Check::failed(
+            "shell",
+            Status::Warning,
+            format!("SHELL={shell} doesn't exist; Alt+S falls back to /bin/sh"),
+            "set SHELL to your shell's full path, e.g. /bin/zsh",
+        ),
+        None => Check::failed(
+            "shell",
+            Status::Warning,
+            "SHELL is not set; Alt+S falls back to /bin/sh",
+            "export SHELL=/bin/zsh (or your shell's full path)",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_check_locale() {
+        let utf8 = Some("en_US.UTF-8".to_string());
+        let check = check_locale(&[("LC_ALL", None), ("LANG", utf8.clone())]);
+        assert_eq!(check.status, Status::Ok);
+        assert_eq!(check.detail, "LANG=en_US.UTF-8");
+
+        // LC_ALL overrides LANG
+        let check = check_locale(&[("LC_ALL", Some("C".to_string())), ("LANG", utf8)]);
+        assert_eq!(check.status, Status::Warning);
+        assert!(check.detail.starts_with("LC_ALL=C "));
+
+        let check = check_locale(&[("LANG", None)]);
+        assert_eq!(check.status, Status::Warning);
+    }
+
+    #[test]
+    fn test_check_truecolor_and_lines() {
+        assert_eq!(check_truecolor(Some("24bit")).status, Status::Ok);
+        let check = check_truecolor(None);
+        assert_eq!(check.status, Status::Warning);
+        let lines = check.lines();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("! truecolor "));
+        assert!(lines[1].trim_start().starts_with('\u{2192}'));
+    }
+
+    #[test]
+    fn test_check_config_dir() {
+        let dir = ScratchDir::new("doctor");
+        assert_eq!(check_config_dir(Some(&dir)).status, Status::Ok);
+        assert_eq!(check_config_dir(None).status, Status::Problem);
+    }
+}
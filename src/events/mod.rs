@@ -0,0 +1,204 @@
+//! The event loop's single inbox
+//!
+//! Terminal input, the output of commands, jobs and PTY programs, the git
+//! worker, and the [`Ticker`] that drives timers all send [`AppEvent`]s into
+//! one channel, and the loop blocks on it until one arrives. Sources that
+//! haven't moved over yet (process exits, test runs, hooks, ...) are still
+//! checked by the `poll_*` methods after each event; a new worker should take
+//! a [`EventBus::sender`] and add a variant here instead. Long-lived workers
+//! run under a [`Supervised`], which restarts them if they panic.
+//!
+//! This is plain threads and `std::sync::mpsc`, not an async runtime; moving
+//! to tokio was left out of this change and needs its own go-ahead.
+
+pub mod supervisor;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crossterm::event::{self, Event};
+
+use crate::git::GitMessage;
+use crate::shell::executor::OutputStream;
+
+pub use supervisor::{Supervised, Supervision};
+
+/// How long the input thread waits for a key before checking for a pause
+const INPUT_POLL: Duration = Duration::from_millis(50);
+
+/// Something the event loop should react to
+#[derive(Debug)]
+pub enum AppEvent {
+    /// A key, paste, resize, or focus change from the terminal
+    Input(Event),
+    /// A line a command or background job wrote
+    Output {
+        pid: u32,
+        stream: OutputStream,
+        line: String,
+    },
+    /// One of a command's output streams reached EOF
+    OutputClosed { pid: u32 },
+    /// Bytes the PTY program wrote
+    PtyOutput(Vec<u8>),
+    /// The PTY program's output reached EOF
+    PtyClosed,
+    /// Time to check the timers (git poll, retries, idle lock, ...)
+    Tick,
+    /// A reply from the git worker
+    Git(GitMessage),
+    /// A file changed in a project the test view watches
+    FilesChanged,
+    /// The terminal can't be read any more, so no keys will arrive
+    InputFailed(String),
+}
+
+/// The channel every event source sends into
+pub struct EventBus {
+    tx: Sender<AppEvent>,
+    rx: Receiver<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self { tx, rx }
+    }
+
+    /// A handle for a new event source
+    pub fn sender(&self) -> Sender<AppEvent> {
+        self.tx.clone()
+    }
+
+    /// Wait up to `timeout` for the next event
+    pub fn next(&self, timeout: Duration) -> Option<AppEvent> {
+        self.rx.recv_timeout(timeout).ok()
+    }
+
+    /// Wait for the next event
+    pub fn wait(&self) -> AppEvent {
+        // The bus holds a sender itself, so the channel never disconnects
+        self.rx.recv().expect("event bus disconnected")
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends [`AppEvent::Tick`] at a steady interval
+///
+/// A tick isn't sent again until the loop has [`acknowledged`](Self::ack)
+/// the last one, so ticks don't pile up while the loop is busy (or while
+/// `jerm shell` has the terminal).
+pub struct Ticker {
+    pending: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Ticker {
+    /// Start ticking into `tx` every `interval`
+    pub fn spawn(tx: Sender<AppEvent>, interval: Duration) -> Self {
+        let pending = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (thread_pending, thread_stopped) = (Arc::clone(&pending), Arc::clone(&stopped));
+        thread::spawn(move || {
+            while !thread_stopped.load(Ordering::SeqCst) {
+                thread::sleep(interval);
+                if !thread_pending.swap(true, Ordering::SeqCst) && tx.send(AppEvent::Tick).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        Self { pending, stopped }
+    }
+
+    /// The last tick was handled; the next one may be sent
+    pub fn ack(&self) {
+        self.pending.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Reads terminal events on a thread of its own
+///
+/// While another program owns the terminal (`jerm shell`) the reader must
+/// be paused, or it would take that program's keys.
+pub struct InputReader {
+    paused: Arc<AtomicBool>,
+    /// Set by the thread once it has seen the pause and stopped reading
+    parked: Arc<AtomicBool>,
+    /// Set when the thread has stopped for good
+    finished: Arc<AtomicBool>,
+}
+
+impl InputReader {
+    /// Start forwarding terminal events to `tx`
+    pub fn spawn(tx: Sender<AppEvent>) -> Self {
+        let paused = Arc::new(AtomicBool::new(false));
+        let parked = Arc::new(AtomicBool::new(false));
+        let finished = Arc::new(AtomicBool::new(false));
+        let (thread_paused, thread_parked, thread_finished) = (
+            Arc::clone(&paused),
+            Arc::clone(&parked),
+            Arc::clone(&finished),
+        );
+        thread::spawn(move || {
+            let error = loop {
+                if thread_paused.load(Ordering::SeqCst) {
+                    thread_parked.store(true, Ordering::SeqCst);
+                    thread::sleep(INPUT_POLL);
+                    continue;
+                }
+                let event = match event::poll(INPUT_POLL) {
+                    Ok(true) => event::read(),
+                    Ok(false) => continue,
+                    Err(e) => Err(e),
+                };
+                match event {
+                    Ok(event) => {
+                        // The loop has gone, so there's no one to tell
+                        if tx.send(AppEvent::Input(event)).is_err() {
+                            break None;
+                        }
+                    }
+                    Err(e) => break Some(e),
+                }
+            };
+            thread_finished.store(true, Ordering::SeqCst);
+            if let Some(e) = error {
+                let _ = tx.send(AppEvent::InputFailed(e.to_string()));
+            }
+        });
+        Self {
+            paused,
+            parked,
+            finished,
+        }
+    }
+
+    /// Stop reading, returning once the thread is no longer polling
+    pub fn pause(&self) {
+        self.parked.store(false, Ordering::SeqCst);
+        self.paused.store(true, Ordering::SeqCst);
+        while !self.parked.load(Ordering::SeqCst) && !self.finished.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Read terminal events again
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+}
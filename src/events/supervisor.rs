@@ -0,0 +1,162 @@
+//! Restarting background workers that panic
+//!
+//! A worker that panics takes its end of its channels with it: requests
+//! sent to it vanish and no replies come back, so whatever it kept up to
+//! date (the git status, say) silently freezes. [`Supervised`] owns a
+//! worker's thread and the handle used to talk to it; the event loop
+//! checks on it each tick, and once the thread has died of a panic it's
+//! reported and started again after a backoff that doubles with each crash
+//! in quick succession.
+
+use std::sync::mpsc::Sender;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::AppEvent;
+use crate::crash;
+
+/// Wait before the first restart
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest wait between restarts
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A worker that stays up this long starts its backoff over
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// Start a worker, returning the handle to talk to it and its thread
+///
+/// The thread should be named after the worker so its panic message can
+/// be found (see [`crash::take_worker_panic`]).
+pub type Spawn<T> = fn(Sender<AppEvent>) -> (T, JoinHandle<()>);
+
+/// What a check found happened to a worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Supervision {
+    /// The worker panicked and will be restarted after `retry_in`
+    Crashed { message: String, retry_in: Duration },
+    /// The worker was started again; requests sent while it was down are lost
+    Restarted,
+}
+
+/// A worker thread that is restarted when it panics
+pub struct Supervised<T> {
+    /// Thread name, for messages
+    name: &'static str,
+    spawn: Spawn<T>,
+    events: Sender<AppEvent>,
+    handle: T,
+    /// `None` while waiting to restart
+    thread: Option<JoinHandle<()>>,
+    started: Instant,
+    /// Crashes in a row without a stable run between them
+    crashes: u32,
+    restart_at: Option<Instant>,
+}
+
+impl<T> Supervised<T> {
+    pub fn spawn(name: &'static str, spawn: Spawn<T>, events: Sender<AppEvent>) -> Self {
+        let (handle, thread) = spawn(events.clone());
+        Self {
+            name,
+            spawn,
+            events,
+            handle,
+            thread: Some(thread),
+            started: Instant::now(),
+            crashes: 0,
+            restart_at: None,
+        }
+    }
+
+    /// The handle the worker was started with
+    pub fn handle(&self) -> &T {
+        &self.handle
+    }
+
+    /// Restart the worker once its backoff is over, or notice that it
+    /// died; a worker that returned on its own isn't restarted
+    pub fn check(&mut self, now: Instant) -> Option<Supervision> {
+        if let Some(restart_at) = self.restart_at {
+            if now < restart_at {
+                return None;
+            }
+            let (handle, thread) = (self.spawn)(self.events.clone());
+            self.handle = handle;
+            self.thread = Some(thread);
+            self.started = now;
+            self.restart_at = None;
+            return Some(Supervision::Restarted);
+        }
+
+        if !self.thread.as_ref()?.is_finished() {
+            return None;
+        }
+        let payload = self.thread.take()?.join().err()?;
+        let message = crash::take_worker_panic(self.name)
+            .unwrap_or_else(|| crash::panic_message(payload.as_ref()));
+        if now.duration_since(self.started) >= STABLE_AFTER {
+            self.crashes = 0;
+        }
+        self.crashes += 1;
+        let retry_in = backoff(self.crashes);
+        self.restart_at = Some(now + retry_in);
+        Some(Supervision::Crashed { message, retry_in })
+    }
+}
+
+/// Wait before restarting after `crashes` crashes in a row
+fn backoff(crashes: u32) -> Duration {
+    FIRST_BACKOFF
+        .saturating_mul(1 << crashes.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+
+    static STARTS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Panics the first time, then waits for its channel to close
+    fn flaky(_: Sender<AppEvent>) -> (Sender<()>, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel::<()>();
+        let first = STARTS.fetch_add(1, Ordering::SeqCst) == 0;
+        let thread = thread::spawn(move || {
+            assert!(!first, "worker crashed");
+            while rx.recv().is_ok() {}
+        });
+        (tx, thread)
+    }
+
+    #[test]
+    fn test_restart_with_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(40), MAX_BACKOFF);
+
+        let (events, _rx) = mpsc::channel();
+        let mut worker = Supervised::spawn("flaky", flaky, events);
+        let start = Instant::now();
+        while !worker.thread.as_ref().unwrap().is_finished() {
+            thread::sleep(Duration::from_millis(5));
+        }
+        let Some(Supervision::Crashed { message, retry_in }) = worker.check(start) else {
+            panic!("crash not noticed");
+        };
+        assert!(message.contains("worker crashed"));
+        assert_eq!(retry_in, FIRST_BACKOFF);
+
+        assert_eq!(worker.check(start), None);
+        assert_eq!(
+            worker.check(start + FIRST_BACKOFF),
+            Some(Supervision::Restarted)
+        );
+        assert!(worker.handle().send(()).is_ok());
+        assert_eq!(worker.check(start + FIRST_BACKOFF), None);
+        assert_eq!(STARTS.load(Ordering::SeqCst), 2);
+    }
+}
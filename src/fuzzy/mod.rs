@@ -0,0 +1,157 @@
+//! Fuzzy matching for filters and completion
+//!
+//! Matching is behind the [`Matcher`] trait so the algorithm can be chosen in
+//! `config.toml`:
+//!
+//! ```toml
+//! [fuzzy]
+//! algorithm = "skim"          # or "subsequence"
+//!
+//! [fuzzy.weights]             # skim only
+//! boundary = 8
+//! consecutive = 4
+//! ```
+//!
+//! Both matchers are case-insensitive unless the query contains an uppercase
+//! letter ("smart case").
+
+mod skim;
+mod subsequence;
+
+use serde::Deserialize;
+
+pub use skim::SkimMatcher;
+pub use subsequence::SubsequenceMatcher;
+
+/// Scores how well a candidate matches a query
+pub trait Matcher {
+    /// Score of `candidate` for `query`, higher is better; `None` if the
+    /// query's characters don't all appear in order
+    fn score(&self, query: &str, candidate: &str) -> Option<i64>;
+}
+
+/// Available matching algorithms
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    /// fzf/skim-style scoring with bonuses for word starts and runs
+    #[default]
+    Skim,
+    /// Plain in-order subsequence, preferring the tightest match
+    Subsequence,
+}
+
+/// Scoring weights for [`SkimMatcher`]
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Weights {
+    /// Score for each matched character
+    #[serde(rename = "match")]
+    pub match_char: i64,
+    /// Bonus for a match right after the previous one
+    pub consecutive: i64,
+    /// Bonus for a match at the start of a word (after `/`, `-`, space, ...)
+    pub boundary: i64,
+    /// Bonus for a match on a lower-to-upper case change (`camelCase`)
+    pub camel_case: i64,
+    /// Multiplier for the bonus of the query's first character
+    pub first_char_multiplier: i64,
+    /// Penalty for starting a gap between matches
+    pub gap_start: i64,
+    /// Penalty for each further skipped character
+    pub gap_extension: i64,
+}
+
+impl Default for Weights {
+    /// fzf's defaults
+    fn default() -> Self {
+        Self {
+            match_char: 16,
+            consecutive: 4,
+            boundary: 8,
+            camel_case: 7,
+            first_char_multiplier: 2,
+            gap_start: -3,
+            gap_extension: -1,
+        }
+    }
+}
+
+/// The `[fuzzy]` config section: the chosen algorithm and its weights
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FuzzyConfig {
+    pub algorithm: Algorithm,
+    pub weights: Weights,
+}
+
+impl Matcher for FuzzyConfig {
+    fn score(&self, query: &str, candidate: &str) -> Option<i64> {
+        match self.algorithm {
+            Algorithm::Skim => SkimMatcher::new(self.weights).score(query, candidate),
+            Algorithm::Subsequence => SubsequenceMatcher.score(query, candidate),
+        }
+    }
+}
+
+/// Character positions in `candidate` to highlight for `query`
+///
+/// Takes the earliest occurrence of each query character, which may differ
+/// from the alignment a matcher scored but is close enough for display.
+pub fn match_positions(query: &str, candidate: &str) -> Option<Vec<usize>> {
+    let ignore_case = ignore_case(query);
+    let mut query_chars = query.chars().peekable();
+    let mut positions = Vec::new();
+
+    for (index, c) in candidate.chars().enumerate() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        if chars_match(wanted, c, ignore_case) {
+            positions.push(index);
+            query_chars.next();
+        }
+    }
+
+    query_chars.peek().is_none().then_some(positions)
+}
+
+/// Whether matching should ignore case for this query (smart case)
+fn ignore_case(query: &str) -> bool {
+    !query.chars().any(char::is_uppercase)
+}
+
+/// Compare two characters, folding case when asked to
+fn chars_match(query: char, candidate: char, ignore_case: bool) -> bool {
+    if ignore_case {
+        query.to_lowercase().eq(candidate.to_lowercase())
+    } else {
+        query == candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smart_case() {
+        for algorithm in [Algorithm::Skim, Algorithm::Subsequence] {
+            let matcher = FuzzyConfig {
+                algorithm,
+                ..FuzzyConfig::default()
+            };
+            assert!(matcher.score("readme", "README.md").is_some());
+            assert!(matcher.score("ReadMe", "readme.md").is_none());
+            assert!(matcher.score("", "anything").is_some());
+        }
+    }
+
+    #[test]
+    fn test_match_positions() {
+        assert_eq!(match_positions("api", "~/src/api"), Some(vec![6, 7, 8]));
+        assert_eq!(match_positions("sa", "~/src/api"), Some(vec![2, 6]));
+        assert_eq!(match_positions("", "x"), Some(vec![]));
+        assert_eq!(match_positions("zz", "z"), None);
+    }
+}
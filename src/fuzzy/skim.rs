@@ -0,0 +1,138 @@
+use super::{chars_match, ignore_case, Matcher, Weights};
+
+/// Lowest score, used for impossible alignments
+const NONE: i64 = i64::MIN / 2;
+
+/// fzf/skim-style matcher
+///
+/// Finds the best-scoring alignment of the query in the candidate: every
+/// matched character scores [`Weights::match_char`] plus a bonus for where
+/// it sits (word start, camelCase hump) or for extending a run, and gaps
+/// between matches are penalized. As in fzf, a run keeps the bonus of its
+/// first character, so `car` scores higher in `cargo` than in `c_a_r`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkimMatcher {
+    weights: Weights,
+}
+
+impl SkimMatcher {
+    /// Create a matcher with the given weights
+    pub fn new(weights: Weights) -> Self {
+        Self { weights }
+    }
+
+    /// Positional bonus for a match at `index`
+    fn bonus(&self, chars: &[char], index: usize) -> i64 {
+        let Some(&prev) = index.checked_sub(1).and_then(|i| chars.get(i)) else {
+            return self.weights.boundary;
+        };
+        let current = chars[index];
+        if !prev.is_alphanumeric() && current.is_alphanumeric() {
+            self.weights.boundary
+        } else if prev.is_lowercase() && current.is_uppercase() {
+            self.weights.camel_case
+        } else {
+            0
+        }
+    }
+}
+
+impl Matcher for SkimMatcher {
+    fn score(&self, query: &str, candidate: &str) -> Option<i64> {
+        let ignore_case = ignore_case(query);
+        let query: Vec<char> = query.chars().collect();
+        let chars: Vec<char> = candidate.chars().collect();
+        if query.is_empty() {
+            return Some(0);
+        }
+        if query.len() > chars.len() {
+            return None;
+        }
+
+        let weights = &self.weights;
+        let bonuses: Vec<i64> = (0..chars.len()).map(|j| self.bonus(&chars, j)).collect();
+
+        // row[j]: best score with the current query character matched at j;
+        // run[j]: bonus of the first character of the run ending there
+        let mut row: Vec<i64> = chars
+            .iter()
+            .enumerate()
+            .map(|(j, &c)| {
+                if chars_match(query[0], c, ignore_case) {
+                    weights.match_char + bonuses[j] * weights.first_char_multiplier
+                } else {
+                    NONE
+                }
+            })
+            .collect();
+        let mut run = bonuses.clone();
+
+        for &wanted in &query[1..] {
+            let mut next = vec![NONE; chars.len()];
+            let mut next_run = vec![0; chars.len()];
+            // Best previous match at least two characters back, with the gap
+            // penalty for reaching j already applied
+            let mut gapped = NONE;
+            for j in 1..chars.len() {
+                if gapped > NONE {
+                    gapped += weights.gap_extension;
+                }
+                if j >= 2 && row[j - 2] > NONE {
+                    gapped = gapped.max(row[j - 2] + weights.gap_start);
+                }
+                if !chars_match(wanted, chars[j], ignore_case) {
+                    continue;
+                }
+
+                // Extending a run keeps the bonus of the run's first character
+                if row[j - 1] > NONE {
+                    let inherited = run[j - 1].max(bonuses[j]);
+                    next[j] = row[j - 1] + weights.match_char + inherited.max(weights.consecutive);
+                    next_run[j] = inherited;
+                }
+                if gapped > NONE && gapped + weights.match_char + bonuses[j] > next[j] {
+                    next[j] = gapped + weights.match_char + bonuses[j];
+                    next_run[j] = bonuses[j];
+                }
+            }
+            row = next;
+            run = next_run;
+        }
+
+        row.into_iter().max().filter(|score| *score > NONE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, candidate: &str) -> Option<i64> {
+        SkimMatcher::default().score(query, candidate)
+    }
+
+    #[test]
+    fn test_word_starts_beat_scattered_matches() {
+        assert!(score("gc", "git commit") > score("gc", "magic"));
+        assert!(score("fb", "FooBar") > score("fb", "buffbox"));
+    }
+
+    #[test]
+    fn test_runs_beat_gaps() {
+        assert!(score("car", "cargo") > score("car", "c_a_r"));
+        assert_eq!(score("xyz", "cargo"), None);
+        assert_eq!(score("ab", "ba"), None);
+    }
+
+    #[test]
+    fn test_weights_change_ranking() {
+        let runs = SkimMatcher::new(Weights {
+            consecutive: 40,
+            boundary: 0,
+            camel_case: 0,
+            ..Weights::default()
+        });
+        // With boundaries worth nothing, the contiguous "ab" wins
+        assert!(runs.score("ab", "xaby") > runs.score("ab", "a-b"));
+    }
+}
@@ -0,0 +1,47 @@
+use super::{chars_match, ignore_case, Matcher};
+
+/// Matches the query's characters in order anywhere in the candidate
+///
+/// The score only prefers tighter matches: each character skipped between
+/// the first and last match costs one point.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubsequenceMatcher;
+
+impl Matcher for SubsequenceMatcher {
+    fn score(&self, query: &str, candidate: &str) -> Option<i64> {
+        let ignore_case = ignore_case(query);
+        let mut query_chars = query.chars().peekable();
+        let mut first = None;
+        let mut last = 0;
+
+        for (index, c) in candidate.chars().enumerate() {
+            let Some(&wanted) = query_chars.peek() else {
+                break;
+            };
+            if chars_match(wanted, c, ignore_case) {
+                first.get_or_insert(index);
+                last = index;
+                query_chars.next();
+            }
+        }
+
+        if query_chars.peek().is_some() {
+            return None;
+        }
+        let span = first.map_or(0, |first| last - first + 1);
+        Some(-((span - query.chars().count()) as i64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefers_tight_matches() {
+        let matcher = SubsequenceMatcher;
+        assert_eq!(matcher.score("abc", "xabcx"), Some(0));
+        assert_eq!(matcher.score("abc", "a-b-c"), Some(-2));
+        assert_eq!(matcher.score("abc", "acb"), None);
+    }
+}
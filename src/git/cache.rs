@@ -0,0 +1,250 @@
+//! Git status per repository, kept until the repository changes
+//!
+//! Reading the status runs several git commands, which adds up when moving
+//! between directories of the same repository. The worker keeps the last
+//! status of each repository and a filesystem watcher on its root marks it
+//! stale when the worktree, the index, `HEAD`, or the refs change. Linked
+//! worktrees and submodules (where `.git` is a file) keep their git
+//! directory elsewhere, so they aren't cached.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::status::{get_git_status, GitError, GitStatus};
+
+/// Repositories remembered at once; the least recently used is dropped
+const MAX_REPOS: usize = 16;
+
+/// Root to whether its cached status is out of date
+type StaleMap = Arc<Mutex<HashMap<PathBuf, bool>>>;
+
+/// Cached status of watched repositories
+pub struct StatusCache {
+    /// `None` when no watcher could be started; nothing is cached then
+    watcher: Option<RecommendedWatcher>,
+    stale: StaleMap,
+    /// Root to its status and when it was last asked for
+    entries: HashMap<PathBuf, (GitStatus, Instant)>,
+}
+
+impl StatusCache {
+    pub fn new() -> Self {
+        let stale: StaleMap = Arc::default();
+        let marks = Arc::clone(&stale);
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            let Ok(mut marks) = marks.lock() else {
+                return;
+            };
+            for (root, stale) in marks.iter_mut() {
+                if event.paths.iter().any(|path| invalidates(root, path)) {
+                    *stale = true;
+                }
+            }
+        })
+        .ok();
+        Self {
+            watcher,
+            stale,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Status of the repository containing `dir`, from the cache when
+    /// nothing changed since it was read
+    pub fn status(&mut self, dir: &Path) -> Result<GitStatus, GitError> {
+        let Some(root) = repo_root(dir).filter(|_| self.watcher.is_some()) else {
+            return get_git_status(dir);
+        };
+
+        if self.is_fresh(&root) {
+            if let Some((status, used)) = self.entries.get_mut(&root) {
+                *used = Instant::now();
+                return Ok(status.clone());
+            }
+        }
+
+        // Mark it fresh before reading, so changes made while git runs
+        // aren't lost
+        let watched = self.set_stale(&root, false);
+        let status = get_git_status(&root)?;
+        if watched || self.watch(&root) {
+            self.entries.insert(root, (status.clone(), Instant::now()));
+        }
+        Ok(status)
+    }
+
+    /// Whether `root` has a cached status that is still current
+    pub fn is_fresh(&self, root: &Path) -> bool {
+        self.entries.contains_key(root)
+            && self
+                .stale
+                .lock()
+                .map(|marks| marks.get(root) == Some(&false))
+                .unwrap_or(false)
+    }
+
+    /// Set the stale mark of a watched root; returns whether it is watched
+    fn set_stale(&self, root: &Path, value: bool) -> bool {
+        let Ok(mut marks) = self.stale.lock() else {
+            return false;
+        };
+        match marks.get_mut(root) {
+            Some(stale) => {
+                *stale = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start watching `root`, dropping the least recently used repository
+    /// when full; returns false if it can't be watched (e.g. too many
+    /// directories for the inotify limit)
+    fn watch(&mut self, root: &Path) -> bool {
+        let Some(ref mut watcher) = self.watcher else {
+            return false;
+        };
+        if self.entries.len() >= MAX_REPOS {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(root, _)| root.clone());
+            if let Some(oldest) = oldest {
+                let _ = watcher.unwatch(&oldest);
+                self.entries.remove(&oldest);
+                if let Ok(mut marks) = self.stale.lock() {
+                    marks.remove(&oldest);
+                }
+            }
+        }
+        if watcher.watch(root, RecursiveMode::Recursive).is_err() {
+            // A partial watch would miss changes
+            let _ = watcher.unwatch(root);
+            return false;
+        }
+        if let Ok(mut marks) = self.stale.lock() {
+            marks.insert(root.to_path_buf(), false);
+        }
+        true
+    }
+}
+
+impl Default for StatusCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Closest directory at or above `dir` with a `.git` directory
+fn repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .filter(|root| root.join(".git").is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// Whether a change at `path` can change the status of the repository at
+/// `root`: anything in the worktree, and the parts of `.git` that hold the
+/// branch, the index, refs, and config (not objects, logs, or lock files)
+fn invalidates(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let mut components = relative.components();
+    if components.next() != Some(Component::Normal(".git".as_ref())) {
+        return true;
+    }
+    let inside: PathBuf = components.collect();
+    [
+        "HEAD",
+        "index",
+        "packed-refs",
+        "config",
+        "MERGE_HEAD",
+        "CHERRY_PICK_HEAD",
+        "REVERT_HEAD",
+        "BISECT_LOG",
+    ]
+    .iter()
+    .any(|name| inside == Path::new(name))
+        || ["refs", "rebase-merge", "rebase-apply"]
+            .iter()
+            .any(|dir| inside.starts_with(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+    use std::fs;
+    use std::process::Command;
+    use std::time::Duration;
+
+    #[test]
+    fn test_invalidates() {
+        let root = Path::new("/repo");
+        assert!(invalidates(root, Path::new("/repo/src/main.rs")));
+        assert!(invalidates(root, Path::new("/repo/.git/HEAD")));
+        assert!(invalidates(root, Path::new("/repo/.git/index")));
+        assert!(invalidates(root, Path::new("/repo/.git/refs/heads/main")));
+        assert!(invalidates(
+            root,
+            Path::new("/repo/.git/rebase-merge/msgnum")
+        ));
+        assert!(!invalidates(root, Path::new("/repo/.git/index.lock")));
+        assert!(!invalidates(root, Path::new("/repo/.git/objects/ab/cdef")));
+        assert!(!invalidates(root, Path::new("/elsewhere/file")));
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_changes() {
+        let root = ScratchDir::new("status-cache");
+        fs::create_dir_all(root.join("src")).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(["-c", "user.name=jerm", "-c", "user.email=jerm@example.com"])
+                .args(args)
+                .current_dir(&root)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "base"]);
+        let root = root.canonicalize().unwrap();
+
+        let mut cache = StatusCache::new();
+        if cache.watcher.is_none() {
+            return; // No filesystem notifications here
+        }
+        let status = cache.status(&root.join("src")).unwrap();
+        assert!(!status.is_dirty);
+        assert_eq!(
+            repo_root(&root.join("src")).as_deref(),
+            Some(root.as_path())
+        );
+
+        // Reading the status must not invalidate it
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(cache.is_fresh(&root));
+
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cache.is_fresh(&root) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!cache.is_fresh(&root));
+        assert_eq!(cache.status(&root).unwrap().changes.untracked, 1);
+    }
+}
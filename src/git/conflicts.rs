@@ -0,0 +1,120 @@
+//! Files left unmerged by a merge, rebase, cherry-pick, or stash pop
+
+use std::path::{Path, PathBuf};
+
+use super::status::{run_git, GitError};
+
+/// Check a two-letter `git status --porcelain` code for an unmerged path
+pub fn is_conflict(code: &str) -> bool {
+    matches!(code, "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU")
+}
+
+/// The conflicted files of a repository, shown by the conflicts overlay
+#[derive(Debug)]
+pub struct ConflictList {
+    /// Top-level directory of the repository
+    pub root: PathBuf,
+    /// Conflicted paths, relative to `root`
+    pub files: Vec<String>,
+    /// Index of the highlighted file
+    pub selected: usize,
+}
+
+impl ConflictList {
+    /// List the conflicted files of the repository containing `dir`
+    pub fn load(dir: &Path) -> Result<Self, GitError> {
+        let root = PathBuf::from(run_git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+        let mut files: Vec<String> = run_git(&root, &["diff", "--name-only", "--diff-filter=U"])?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        files.dedup();
+        Ok(Self {
+            root,
+            files,
+            selected: 0,
+        })
+    }
+
+    /// Move the highlight, stopping at either end
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.files.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Absolute path of the highlighted file
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.files
+            .get(self.selected)
+            .map(|file| self.root.join(file))
+    }
+
+    /// Stage the highlighted file (`git add`) and drop it from the list
+    ///
+    /// Returns the file's name.
+    pub fn mark_resolved(&mut self) -> Result<String, GitError> {
+        let Some(file) = self.files.get(self.selected).cloned() else {
+            return Err(GitError::CommandFailed("no file selected".to_string()));
+        };
+        run_git(&self.root, &["add", "--", &file])?;
+        self.files.remove(self.selected);
+        self.move_by(0);
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+    use std::fs;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=jerm", "-c", "user.email=jerm@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(
+            status.success() || args[0] == "merge",
+            "git {args:?} failed"
+        );
+    }
+
+    #[test]
+    fn test_is_conflict() {
+        assert!(is_conflict("UU"));
+        assert!(is_conflict("AA"));
+        assert!(!is_conflict(" M"));
+        assert!(!is_conflict("??"));
+    }
+
+    #[test]
+    fn test_load_and_resolve() {
+        let root = ScratchDir::new("conflicts");
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        git(&root, &["init", "-q", "-b", "main"]);
+        fs::write(root.join("src/lib.rs"), "one\n").unwrap();
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-qm", "base"]);
+        git(&root, &["checkout", "-qb", "other"]);
+        fs::write(root.join("src/lib.rs"), "two\n").unwrap();
+        git(&root, &["commit", "-qam", "other"]);
+        git(&root, &["checkout", "-q", "main"]);
+        fs::write(root.join("src/lib.rs"), "three\n").unwrap();
+        git(&root, &["commit", "-qam", "main"]);
+        git(&root, &["merge", "-q", "other"]);
+
+        let mut list = ConflictList::load(&root.join("src")).unwrap();
+        assert_eq!(list.files, vec!["src/lib.rs"]);
+        assert!(list.selected_path().unwrap().ends_with("src/lib.rs"));
+
+        assert_eq!(list.mark_resolved().unwrap(), "src/lib.rs");
+        assert!(list.files.is_empty());
+        assert!(ConflictList::load(&root).unwrap().files.is_empty());
+    }
+}
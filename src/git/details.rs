@@ -0,0 +1,172 @@
+//! Full `git status` breakdown for the status panel
+
+use std::path::Path;
+
+use super::conflicts::is_conflict;
+use super::status::{run_git, GitError};
+
+/// A changed file and how it changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Porcelain status letter (`M`, `A`, `D`, `R`, `C`, `T`)
+    pub code: char,
+    /// Path relative to the repository root (`old -> new` for renames)
+    pub path: String,
+}
+
+impl FileChange {
+    /// Word for the change, as `git status` prints it
+    pub fn label(&self) -> &'static str {
+        match self.code {
+            'A' => "new file",
+            'D' => "deleted",
+            'R' => "renamed",
+            'C' => "copied",
+            'T' => "typechange",
+            _ => "modified",
+        }
+    }
+}
+
+/// Branch, upstream, and changed files of a working tree
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusDetails {
+    /// Branch name (`HEAD (no branch)` when detached)
+    pub branch: String,
+    /// Upstream branch, if one is configured
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    /// Changes in the index
+    pub staged: Vec<FileChange>,
+    /// Changes not staged yet
+    pub unstaged: Vec<FileChange>,
+    /// Files git doesn't track
+    pub untracked: Vec<String>,
+    /// Files left unmerged
+    pub conflicts: Vec<String>,
+}
+
+impl StatusDetails {
+    /// Run `git status` in `dir`
+    pub fn read(dir: &Path) -> Result<Self, GitError> {
+        run_git(
+            dir,
+            &["--no-optional-locks", "status", "--porcelain", "--branch"],
+        )
+        .map(|out| Self::parse(&out))
+    }
+
+    /// Parse `git status --porcelain --branch` output
+    pub fn parse(porcelain: &str) -> Self {
+        let mut details = Self::default();
+        for line in porcelain.lines() {
+            if let Some(header) = line.strip_prefix("## ") {
+                details.parse_header(header);
+                continue;
+            }
+            let (Some(code), Some(path)) = (line.get(..2), line.get(3..)) else {
+                continue;
+            };
+            let path = path.to_string();
+            if code == "??" {
+                details.untracked.push(path);
+            } else if is_conflict(code) {
+                details.conflicts.push(path);
+            } else {
+                let mut states = code.chars();
+                if let Some(x) = states.next().filter(|&c| c != ' ') {
+                    details.staged.push(FileChange {
+                        code: x,
+                        path: path.clone(),
+                    });
+                }
+                if let Some(y) = states.next().filter(|&c| c != ' ') {
+                    details.unstaged.push(FileChange { code: y, path });
+                }
+            }
+        }
+        details
+    }
+
+    /// Parse the branch line: `main...origin/main [ahead 1, behind 2]`
+    fn parse_header(&mut self, header: &str) {
+        let (refs, tracking) = match header.split_once(" [") {
+            Some((refs, tracking)) => (refs, tracking.trim_end_matches(']')),
+            None => (header, ""),
+        };
+        match refs.split_once("...") {
+            Some((branch, upstream)) => {
+                self.branch = branch.to_string();
+                self.upstream = Some(upstream.to_string());
+            }
+            None => self.branch = refs.trim_start_matches("No commits yet on ").to_string(),
+        }
+        for part in tracking.split(", ") {
+            if let Some(n) = part.strip_prefix("ahead ") {
+                self.ahead = n.parse().unwrap_or(0);
+            } else if let Some(n) = part.strip_prefix("behind ") {
+                self.behind = n.parse().unwrap_or(0);
+            }
+        }
+    }
+
+    /// Whether the working tree has no changes at all
+    pub fn is_clean(&self) -> bool {
+        self.staged.is_empty()
+            && self.unstaged.is_empty()
+            && self.untracked.is_empty()
+            && self.conflicts.is_empty()
+    }
+}
+
+/// State of the git status panel
+#[derive(Debug, Default)]
+pub struct GitPanel {
+    /// Status once the worker has read it, or why it couldn't
+    pub details: Option<Result<StatusDetails, String>>,
+    /// First line shown
+    pub scroll: usize,
+}
+
+impl GitPanel {
+    /// Scroll by `delta` lines, stopping at the top
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let details = StatusDetails::parse(
+            "## main...origin/main [ahead 2, behind 1]\nMM src/app.rs\nA  src/new.rs\n \
+             D old.rs\nR  a.rs -> b.rs\n?? notes.txt\nUU src/lib.rs\n",
+        );
+        assert_eq!(details.branch, "main");
+        assert_eq!(details.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((details.ahead, details.behind), (2, 1));
+        let staged: Vec<&str> = details.staged.iter().map(|c| c.label()).collect();
+        assert_eq!(staged, vec!["modified", "new file", "renamed"]);
+        assert_eq!(details.staged[2].path, "a.rs -> b.rs");
+        let unstaged: Vec<&str> = details.unstaged.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(unstaged, vec!["src/app.rs", "old.rs"]);
+        assert_eq!(details.untracked, vec!["notes.txt"]);
+        assert_eq!(details.conflicts, vec!["src/lib.rs"]);
+        assert!(!details.is_clean());
+    }
+
+    #[test]
+    fn test_parse_header_without_upstream() {
+        let details = StatusDetails::parse("## feature\n");
+        assert_eq!(details.branch, "feature");
+        assert_eq!(details.upstream, None);
+        assert!(details.is_clean());
+
+        let details = StatusDetails::parse("## No commits yet on main\n");
+        assert_eq!(details.branch, "main");
+    }
+}
@@ -1,3 +1,13 @@
+pub mod cache;
+pub mod conflicts;
+pub mod details;
+pub mod operation;
+pub mod overview;
 pub mod status;
+pub mod switcher;
 
-pub use status::{spawn_git_worker, GitMessage, GitStatus};
+pub use conflicts::ConflictList;
+pub use details::GitPanel;
+pub use overview::GitOverview;
+pub use status::{spawn_git_worker, GitMessage, GitStatus, GIT_WORKER};
+pub use switcher::BranchSwitcher;
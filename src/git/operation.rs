@@ -0,0 +1,120 @@
+//! Multi-step git operations left in progress (rebase, merge, ...)
+//!
+//! Git records these as files in the git directory, the same ones its own
+//! `git-prompt.sh` reads: `rebase-merge/` and `rebase-apply/` with step
+//! counters, and `MERGE_HEAD`, `CHERRY_PICK_HEAD`, `REVERT_HEAD`, or
+//! `BISECT_LOG` for the rest.
+
+use std::fs;
+use std::path::Path;
+
+/// Kind of operation in progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Rebase,
+    /// `git am` applying a patch series
+    Am,
+    Merge,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+/// An operation waiting to be continued or aborted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operation {
+    pub kind: OperationKind,
+    /// Current step and total number of steps, when git tracks them
+    pub progress: Option<(u32, u32)>,
+}
+
+impl Operation {
+    /// Look for an operation in progress in `git_dir`
+    pub fn detect(git_dir: &Path) -> Option<Self> {
+        let rebase_merge = git_dir.join("rebase-merge");
+        if rebase_merge.is_dir() {
+            return Some(Self {
+                kind: OperationKind::Rebase,
+                progress: read_progress(&rebase_merge, "msgnum", "end"),
+            });
+        }
+
+        let rebase_apply = git_dir.join("rebase-apply");
+        if rebase_apply.is_dir() {
+            let kind = if rebase_apply.join("applying").exists() {
+                OperationKind::Am
+            } else {
+                OperationKind::Rebase
+            };
+            return Some(Self {
+                kind,
+                progress: read_progress(&rebase_apply, "next", "last"),
+            });
+        }
+
+        let kind = [
+            ("MERGE_HEAD", OperationKind::Merge),
+            ("CHERRY_PICK_HEAD", OperationKind::CherryPick),
+            ("REVERT_HEAD", OperationKind::Revert),
+            ("BISECT_LOG", OperationKind::Bisect),
+        ]
+        .into_iter()
+        .find(|(file, _)| git_dir.join(file).exists())
+        .map(|(_, kind)| kind)?;
+        Some(Self {
+            kind,
+            progress: None,
+        })
+    }
+
+    /// Prompt label such as `REBASE 2/5`
+    pub fn label(&self) -> String {
+        let name = match self.kind {
+            OperationKind::Rebase => "REBASE",
+            OperationKind::Am => "AM",
+            OperationKind::Merge => "MERGE",
+            OperationKind::CherryPick => "CHERRY-PICK",
+            OperationKind::Revert => "REVERT",
+            OperationKind::Bisect => "BISECT",
+        };
+        match self.progress {
+            Some((step, total)) => format!("{name} {step}/{total}"),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// Read a step counter and total stored as two files in `dir`
+fn read_progress(dir: &Path, step: &str, total: &str) -> Option<(u32, u32)> {
+    let read = |name: &str| fs::read_to_string(dir.join(name)).ok()?.trim().parse().ok();
+    Some((read(step)?, read(total)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_detect() {
+        let git_dir = ScratchDir::new("operation");
+        assert_eq!(Operation::detect(&git_dir), None);
+
+        fs::write(git_dir.join("MERGE_HEAD"), "abc\n").unwrap();
+        assert_eq!(Operation::detect(&git_dir).unwrap().label(), "MERGE");
+
+        // A rebase that stopped on a conflict also leaves MERGE_HEAD-like
+        // files behind; the rebase is what needs continuing
+        let rebase = git_dir.join("rebase-merge");
+        fs::create_dir(&rebase).unwrap();
+        fs::write(rebase.join("msgnum"), "2\n").unwrap();
+        fs::write(rebase.join("end"), "5\n").unwrap();
+        assert_eq!(Operation::detect(&git_dir).unwrap().label(), "REBASE 2/5");
+
+        fs::remove_dir_all(&rebase).unwrap();
+        let apply = git_dir.join("rebase-apply");
+        fs::create_dir(&apply).unwrap();
+        fs::write(apply.join("applying"), "").unwrap();
+        assert_eq!(Operation::detect(&git_dir).unwrap().label(), "AM");
+    }
+}
@@ -0,0 +1,223 @@
+//! Local branches with their upstreams, for `jerm git overview`
+//!
+//! Counting commits ahead and behind walks history, so the counts are cached
+//! by the pair of commits compared: a branch that hasn't moved since the
+//! last overview costs nothing.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::status::{run_git, GitError};
+
+/// Ahead/behind counts by (branch commit, upstream commit)
+pub type AheadBehindCache = HashMap<(String, String), (u32, u32)>;
+
+/// A local branch as listed by the overview
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchInfo {
+    /// Short branch name
+    pub name: String,
+    /// Whether the branch is checked out
+    pub is_head: bool,
+    /// Upstream state and counts
+    pub upstream: Upstream,
+    /// Committer time of the branch's last commit (Unix seconds)
+    pub last_commit: i64,
+}
+
+/// How a branch relates to its upstream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Upstream {
+    /// No upstream configured
+    None,
+    /// The configured upstream no longer exists (deleted on the remote)
+    Gone(String),
+    /// Commits the branch is ahead of and behind its upstream
+    Tracking {
+        name: String,
+        ahead: u32,
+        behind: u32,
+    },
+}
+
+/// A `refs/heads` entry before its upstream is compared
+#[derive(Debug, PartialEq, Eq)]
+struct HeadRef<'a> {
+    name: &'a str,
+    commit: &'a str,
+    upstream: &'a str,
+    upstream_short: &'a str,
+    last_commit: i64,
+    is_head: bool,
+}
+
+/// Field separator of the `for-each-ref` formats
+const SEP: char = '\t';
+
+/// List the local branches of the repository containing `dir`, most
+/// recently committed first
+pub fn list_branches(
+    dir: &Path,
+    cache: &mut AheadBehindCache,
+) -> Result<Vec<BranchInfo>, GitError> {
+    // Commit of every branch and remote-tracking ref, to find upstreams
+    let refs = run_git(
+        dir,
+        &[
+            "for-each-ref",
+            "--format=%(refname)\t%(objectname)",
+            "refs/heads",
+            "refs/remotes",
+        ],
+    )?;
+    let commits: HashMap<&str, &str> = refs.lines().filter_map(|l| l.split_once(SEP)).collect();
+
+    let heads = run_git(
+        dir,
+        &[
+            "for-each-ref",
+            "--format=%(refname:short)\t%(objectname)\t%(upstream)\t%(upstream:short)\t%(committerdate:unix)\t%(HEAD)",
+            "refs/heads",
+        ],
+    )?;
+
+    let mut branches: Vec<BranchInfo> = Vec::new();
+    for head in heads.lines().filter_map(parse_head) {
+        let upstream = if head.upstream.is_empty() {
+            Upstream::None
+        } else if let Some(upstream_commit) = commits.get(head.upstream) {
+            let key = (head.commit.to_string(), (*upstream_commit).to_string());
+            let (ahead, behind) = match cache.get(&key) {
+                Some(counts) => *counts,
+                None => {
+                    let counts = count_ahead_behind(dir, &key.0, &key.1)?;
+                    cache.insert(key, counts);
+                    counts
+                }
+            };
+            Upstream::Tracking {
+                name: head.upstream_short.to_string(),
+                ahead,
+                behind,
+            }
+        } else {
+            Upstream::Gone(head.upstream_short.to_string())
+        };
+        branches.push(BranchInfo {
+            name: head.name.to_string(),
+            is_head: head.is_head,
+            upstream,
+            last_commit: head.last_commit,
+        });
+    }
+
+    branches.sort_by_key(|branch| std::cmp::Reverse(branch.last_commit));
+    Ok(branches)
+}
+
+/// State of the `jerm git overview` dashboard
+#[derive(Debug, Default)]
+pub struct GitOverview {
+    /// Branches once the worker has listed them, or why it couldn't
+    pub branches: Option<Result<Vec<BranchInfo>, String>>,
+    /// Index of the highlighted branch
+    pub selected: usize,
+}
+
+impl GitOverview {
+    /// Move the highlight, stopping at either end
+    pub fn move_by(&mut self, delta: isize) {
+        let count = match self.branches {
+            Some(Ok(ref branches)) => branches.len(),
+            _ => 0,
+        };
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(count.saturating_sub(1));
+    }
+}
+
+/// Parse one line of the `refs/heads` listing
+fn parse_head(line: &str) -> Option<HeadRef<'_>> {
+    let mut fields = line.split(SEP);
+    Some(HeadRef {
+        name: fields.next()?,
+        commit: fields.next()?,
+        upstream: fields.next()?,
+        upstream_short: fields.next()?,
+        last_commit: fields.next()?.parse().ok()?,
+        is_head: fields.next()? == "*",
+    })
+}
+
+/// Commits in `branch` but not `upstream`, and the other way around
+fn count_ahead_behind(dir: &Path, branch: &str, upstream: &str) -> Result<(u32, u32), GitError> {
+    let range = format!("{branch}...{upstream}");
+    let output = run_git(dir, &["rev-list", "--left-right", "--count", &range])?;
+    let mut counts = output.split_whitespace().map(|n| n.parse().unwrap_or(0));
+    Ok((counts.next().unwrap_or(0), counts.next().unwrap_or(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_parse_head() {
+        let line = "main\tabc123\trefs/remotes/origin/main\torigin/main\t1700000000\t*";
+        assert_eq!(
+            parse_head(line),
+            Some(HeadRef {
+                name: "main",
+                commit: "abc123",
+                upstream: "refs/remotes/origin/main",
+                upstream_short: "origin/main",
+                last_commit: 1_700_000_000,
+                is_head: true,
+            })
+        );
+
+        let local = parse_head("wip\tdef456\t\t\t1690000000\t ").unwrap();
+        assert_eq!(local.upstream, "");
+        assert!(!local.is_head);
+
+        assert_eq!(parse_head("broken\tline"), None);
+    }
+
+    #[test]
+    fn test_list_branches() {
+        let root = ScratchDir::new("overview");
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=jerm", "-c", "user.email=jerm@example.com"])
+                .args(args)
+                .current_dir(&root)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "base"]);
+        git(&["branch", "topic"]);
+        git(&["branch", "--set-upstream-to=main", "topic"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "next"]);
+
+        let mut cache = AheadBehindCache::new();
+        let branches = list_branches(&root, &mut cache).unwrap();
+        let topic = branches.iter().find(|b| b.name == "topic").unwrap();
+        assert_eq!(
+            topic.upstream,
+            Upstream::Tracking {
+                name: "main".to_string(),
+                ahead: 0,
+                behind: 1,
+            }
+        );
+        let main = branches.iter().find(|b| b.name == "main").unwrap();
+        assert!(main.is_head);
+        assert_eq!(main.upstream, Upstream::None);
+        assert_eq!(cache.len(), 1);
+    }
+}
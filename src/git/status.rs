@@ -1,16 +1,72 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::mpsc::{self, Receiver, Sender};
-use std::thread;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
 use thiserror::Error;
 
+use crate::events::AppEvent;
+
+use super::cache::StatusCache;
+use super::conflicts::is_conflict;
+use super::details::StatusDetails;
+use super::operation::Operation;
+use super::overview::{list_branches, AheadBehindCache, BranchInfo};
+
 #[derive(Debug, Clone)]
 pub struct GitStatus {
     pub branch: String,
+    #[allow(dead_code)]
     pub is_detached: bool,
     pub is_dirty: bool,
     pub ahead: u32,
     pub behind: u32,
+    /// Files changed in the working tree, by kind
+    pub changes: ChangeCounts,
+    /// Number of stash entries
+    pub stashes: usize,
+    /// Rebase, merge, or similar waiting to be continued
+    pub operation: Option<Operation>,
+}
+
+/// Changed files of a working tree, counted from `git status --porcelain`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeCounts {
+    /// Files with changes in the index
+    pub staged: usize,
+    /// Tracked files with changes not yet staged
+    pub modified: usize,
+    /// Files git doesn't track
+    pub untracked: usize,
+    /// Files left unmerged
+    pub conflicts: usize,
+}
+
+impl ChangeCounts {
+    /// Count the `XY path` lines of porcelain output
+    ///
+    /// A file both staged and changed again afterwards (`MM`) counts as
+    /// staged and modified.
+    pub fn from_porcelain(porcelain: &str) -> Self {
+        let mut counts = Self::default();
+        for code in porcelain.lines().filter_map(|line| line.get(..2)) {
+            if code == "??" {
+                counts.untracked += 1;
+            } else if is_conflict(code) {
+                counts.conflicts += 1;
+            } else {
+                let mut states = code.chars();
+                if states.next().is_some_and(|x| x != ' ') {
+                    counts.staged += 1;
+                }
+                if states.next().is_some_and(|y| y != ' ') {
+                    counts.modified += 1;
+                }
+            }
+        }
+        counts
+    }
 }
 
 #[derive(Debug, Error)]
@@ -20,23 +76,38 @@ pub enum GitError {
     #[error("Not a git repository")]
     NotARepository,
     #[error("Operation timed out")]
+    #[allow(dead_code)]
     Timeout,
 }
 
 #[derive(Debug, Clone)]
 pub enum GitMessage {
-    UpdateStatus { dir: String, with_fetch: bool },
+    UpdateStatus {
+        dir: String,
+        with_fetch: bool,
+    },
     StatusUpdate(Option<GitStatus>),
+    /// A background `git fetch` in `dir` finished
+    Fetched {
+        dir: String,
+    },
+    /// List the local branches of the repository containing `dir`
+    Overview {
+        dir: String,
+    },
+    OverviewReady(Result<Vec<BranchInfo>, String>),
+    /// Read the full status of the repository containing `dir`
+    Details {
+        dir: String,
+    },
+    DetailsReady(Result<StatusDetails, String>),
     Shutdown,
 }
 
-pub fn is_git_repo(dir: &Path) -> bool {
-    Command::new("git")
-        .args(["rev-parse", "--git-dir"])
-        .current_dir(dir)
-        .output()
-        .map(|output| output.status.success())
-        .unwrap_or(false)
+/// Git directory of the repository containing `dir`
+pub fn git_dir(dir: &Path) -> Option<PathBuf> {
+    let output = run_git(dir, &["rev-parse", "--git-dir"]).ok()?;
+    Some(dir.join(output.trim()))
 }
 
 pub fn get_branch_name(dir: &Path) -> Result<String, GitError> {
@@ -71,9 +142,29 @@ pub fn get_short_hash(dir: &Path) -> Result<String, GitError> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-pub fn check_dirty_state(dir: &Path) -> Result<bool, GitError> {
+/// Run git in `dir` and return its standard output
+pub(super) fn run_git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
     let output = Command::new("git")
-        .args(["status", "--porcelain"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| GitError::CommandFailed(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(GitError::CommandFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Output of `git status --porcelain`: one `XY path` line per changed file
+pub fn read_porcelain(dir: &Path) -> Result<String, GitError> {
+    let output = Command::new("git")
+        // Without optional locks git doesn't rewrite the index, which the
+        // status cache's watcher would take for a change
+        .args(["--no-optional-locks", "status", "--porcelain"])
         .current_dir(dir)
         .output()
         .map_err(|e| GitError::CommandFailed(e.to_string()))?;
@@ -84,7 +175,7 @@ pub fn check_dirty_state(dir: &Path) -> Result<bool, GitError> {
         ));
     }
 
-    Ok(!output.stdout.is_empty())
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
 pub fn get_ahead_behind(dir: &Path) -> Result<(u32, u32), GitError> {
@@ -100,7 +191,7 @@ pub fn get_ahead_behind(dir: &Path) -> Result<(u32, u32), GitError> {
     }
 
     let output_str = String::from_utf8_lossy(&output.stdout);
-    let parts: Vec<&str> = output_str.trim().split_whitespace().collect();
+    let parts: Vec<&str> = output_str.split_whitespace().collect();
 
     if parts.len() != 2 {
         return Ok((0, 0));
@@ -112,10 +203,21 @@ pub fn get_ahead_behind(dir: &Path) -> Result<(u32, u32), GitError> {
     Ok((ahead, behind))
 }
 
+/// Number of stash entries (none when git can't tell)
+pub fn count_stashes(dir: &Path) -> usize {
+    Command::new("git")
+        .args(["stash", "list"])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map_or(0, |output| {
+            String::from_utf8_lossy(&output.stdout).lines().count()
+        })
+}
+
 pub fn get_git_status(dir: &Path) -> Result<GitStatus, GitError> {
-    if !is_git_repo(dir) {
-        return Err(GitError::NotARepository);
-    }
+    let git_dir = git_dir(dir).ok_or(GitError::NotARepository)?;
 
     let branch = get_branch_name(dir)?;
     let (is_detached, branch_display) = if branch == "HEAD" {
@@ -126,51 +228,120 @@ pub fn get_git_status(dir: &Path) -> Result<GitStatus, GitError> {
         (false, branch)
     };
 
-    let is_dirty = check_dirty_state(dir)?;
+    let porcelain = read_porcelain(dir)?;
     let (ahead, behind) = get_ahead_behind(dir)?;
 
     Ok(GitStatus {
         branch: branch_display,
         is_detached,
-        is_dirty,
+        is_dirty: !porcelain.is_empty(),
         ahead,
         behind,
+        changes: ChangeCounts::from_porcelain(&porcelain),
+        stashes: count_stashes(dir),
+        operation: Operation::detect(&git_dir),
     })
 }
 
-pub fn spawn_git_worker() -> (Sender<GitMessage>, Receiver<GitMessage>) {
+/// Name of the git worker's thread
+pub const GIT_WORKER: &str = "git";
+
+/// Start the git worker; its replies arrive as [`AppEvent::Git`] on `events`
+pub fn spawn_git_worker(events: Sender<AppEvent>) -> (Sender<GitMessage>, JoinHandle<()>) {
     let (main_tx, worker_rx) = mpsc::channel::<GitMessage>();
-    let (worker_tx, main_rx) = mpsc::channel::<GitMessage>();
-
-    thread::spawn(move || {
-        loop {
-            match worker_rx.recv() {
-                Ok(GitMessage::UpdateStatus { dir, with_fetch }) => {
-                    // Optionally run git fetch
-                    if with_fetch {
-                        let _ = Command::new("git")
-                            .args(["fetch"])
-                            .current_dir(&dir)
-                            .output();
-                    }
+    let worker_tx = GitReplies(events);
 
-                    // Query git status
-                    let status = get_git_status(Path::new(&dir)).ok();
-                    let _ = worker_tx.send(GitMessage::StatusUpdate(status));
-                }
-                Ok(GitMessage::Shutdown) => {
-                    break;
-                }
-                Ok(GitMessage::StatusUpdate(_)) => {
-                    // Worker shouldn't receive this message, ignore
-                }
-                Err(_) => {
-                    // Channel closed, exit
-                    break;
+    let thread = thread::Builder::new()
+        .name(GIT_WORKER.to_string())
+        .spawn(move || {
+            let mut cache = AheadBehindCache::new();
+            let mut statuses = StatusCache::new();
+            // At most one fetch at a time, off this thread: a slow network must
+            // not hold up the status refresh that follows a command
+            let fetching = Arc::new(AtomicBool::new(false));
+            loop {
+                match worker_rx.recv() {
+                    Ok(GitMessage::UpdateStatus { dir, with_fetch }) => {
+                        // Optionally run git fetch
+                        if with_fetch && !fetching.swap(true, Ordering::SeqCst) {
+                            let fetching = Arc::clone(&fetching);
+                            let worker_tx = worker_tx.clone();
+                            let dir = dir.clone();
+                            thread::spawn(move || {
+                                let _ = Command::new("git")
+                                    .args(["fetch"])
+                                    .current_dir(&dir)
+                                    .output();
+                                fetching.store(false, Ordering::SeqCst);
+                                worker_tx.send(GitMessage::Fetched { dir });
+                            });
+                        }
+
+                        // Query git status
+                        let status = statuses.status(Path::new(&dir)).ok();
+                        worker_tx.send(GitMessage::StatusUpdate(status));
+                    }
+                    Ok(GitMessage::Overview { dir }) => {
+                        let branches =
+                            list_branches(Path::new(&dir), &mut cache).map_err(|e| e.to_string());
+                        worker_tx.send(GitMessage::OverviewReady(branches));
+                    }
+                    Ok(GitMessage::Details { dir }) => {
+                        let details =
+                            StatusDetails::read(Path::new(&dir)).map_err(|e| e.to_string());
+                        worker_tx.send(GitMessage::DetailsReady(details));
+                    }
+                    Ok(GitMessage::Shutdown) => {
+                        break;
+                    }
+                    Ok(
+                        GitMessage::StatusUpdate(_)
+                        | GitMessage::Fetched { .. }
+                        | GitMessage::OverviewReady(_)
+                        | GitMessage::DetailsReady(_),
+                    ) => {
+                        // Worker shouldn't receive this message, ignore
+                    }
+                    Err(_) => {
+                        // Channel closed, exit
+                        break;
+                    }
                 }
             }
-        }
-    });
+        })
+        .expect("failed to spawn the git worker thread");
+
+    (main_tx, thread)
+}
+
+/// The worker's side of the event channel
+#[derive(Clone)]
+struct GitReplies(Sender<AppEvent>);
 
-    (main_tx, main_rx)
+impl GitReplies {
+    /// Send a reply; once the event loop is gone there's no one to tell
+    fn send(&self, message: GitMessage) {
+        let _ = self.0.send(AppEvent::Git(message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_counts() {
+        let porcelain = "M  src/main.rs\nMM src/app.rs\n M README.md\nA  new.rs\n\
+                         ?? notes.txt\nUU src/lib.rs\nR  old -> new\n";
+        assert_eq!(
+            ChangeCounts::from_porcelain(porcelain),
+            ChangeCounts {
+                staged: 4,
+                modified: 2,
+                untracked: 1,
+                conflicts: 1,
+            }
+        );
+        assert_eq!(ChangeCounts::from_porcelain(""), ChangeCounts::default());
+    }
 }
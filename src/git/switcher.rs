@@ -0,0 +1,94 @@
+//! Branch picker opened by `jerm branch`
+
+use super::overview::BranchInfo;
+
+/// State of the branch switcher
+#[derive(Debug, Default)]
+pub struct BranchSwitcher {
+    /// Local branches once the worker has listed them, or why it couldn't
+    pub branches: Option<Result<Vec<BranchInfo>, String>>,
+    /// Typed text narrowing the list
+    pub filter: String,
+    /// Index of the highlighted branch among the matches
+    pub selected: usize,
+}
+
+impl BranchSwitcher {
+    /// Branches whose name contains the filter (ignoring case)
+    pub fn matches(&self) -> Vec<&BranchInfo> {
+        let Some(Ok(ref branches)) = self.branches else {
+            return Vec::new();
+        };
+        let filter = self.filter.to_lowercase();
+        branches
+            .iter()
+            .filter(|branch| branch.name.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    /// Move the highlight, stopping at either end
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.matches().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Add a character to the filter
+    pub fn push_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.selected = 0;
+    }
+
+    /// Remove the last character of the filter
+    pub fn pop_char(&mut self) {
+        self.filter.pop();
+        self.selected = 0;
+    }
+
+    /// Name of the highlighted branch
+    pub fn selected_name(&self) -> Option<String> {
+        self.matches()
+            .get(self.selected)
+            .map(|branch| branch.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::overview::Upstream;
+
+    fn branch(name: &str) -> BranchInfo {
+        BranchInfo {
+            name: name.to_string(),
+            is_head: false,
+            upstream: Upstream::None,
+            last_commit: 0,
+        }
+    }
+
+    #[test]
+    fn test_filter_and_select() {
+        let mut switcher = BranchSwitcher {
+            branches: Some(Ok(vec![
+                branch("main"),
+                branch("feature/Login"),
+                branch("fix-login-typo"),
+            ])),
+            ..BranchSwitcher::default()
+        };
+        switcher.move_by(5);
+        assert_eq!(switcher.selected_name().as_deref(), Some("fix-login-typo"));
+
+        for c in "login".chars() {
+            switcher.push_char(c);
+        }
+        assert_eq!(switcher.matches().len(), 2);
+        assert_eq!(switcher.selected_name().as_deref(), Some("feature/Login"));
+
+        switcher.push_char('x');
+        assert_eq!(switcher.selected_name(), None);
+        switcher.pop_char();
+        switcher.move_by(1);
+        assert_eq!(switcher.selected_name().as_deref(), Some("fix-login-typo"));
+    }
+}
@@ -0,0 +1,450 @@
+//! Headless UI tests: drive [`App`] with synthetic keys through the same
+//! handlers as the event loop, render into a [`TestBackend`], and check
+//! what ended up on screen
+//!
+//! Each [`Harness`] gets a data directory of its own with a small directory
+//! tree to browse. `JERM_DATA_DIR` is process-wide, so harnesses hold a lock
+//! for their lifetime and the tests using them run one at a time.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{backend::TestBackend, Terminal};
+
+use jerm::app::{App, AppMode};
+use jerm::config::JobAlert;
+use jerm::events::AppEvent;
+use jerm::paths::DATA_DIR_VAR;
+
+use crate::{draw_ui, handle_key, handle_mouse};
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 30;
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An app with a fake screen
+pub struct Harness {
+    pub app: App,
+    /// Directory tree the app starts in: `alpha/`, `beta/nested/`, `notes.txt`
+    pub root: PathBuf,
+    data_dir: PathBuf,
+    terminal: Terminal<TestBackend>,
+    _env: MutexGuard<'static, ()>,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        let env = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let data_dir = std::env::temp_dir().join(format!(
+            "jerm-ui-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&data_dir);
+        let root = data_dir.join("work");
+        fs::create_dir_all(root.join("alpha")).unwrap();
+        fs::create_dir_all(root.join("beta/nested")).unwrap();
+        fs::write(root.join("notes.txt"), "hello\n").unwrap();
+        let root = root.canonicalize().unwrap();
+        std::env::set_var(DATA_DIR_VAR, &data_dir);
+
+        let mut app = App::new();
+        app.change_dir(root.clone());
+        let terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+        Self {
+            app,
+            root,
+            data_dir,
+            terminal,
+            _env: env,
+        }
+    }
+
+    /// Press a key without modifiers
+    pub fn press(&mut self, code: KeyCode) {
+        self.press_with(code, KeyModifiers::NONE);
+    }
+
+    pub fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        handle_key(&mut self.app, KeyEvent::new(code, modifiers));
+    }
+
+    /// Click at a screen position, as drawn by the last render
+    pub fn click(&mut self, column: u16, row: u16) {
+        self.mouse(MouseEventKind::Down(MouseButton::Left), column, row);
+    }
+
+    pub fn mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) {
+        let event = MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        handle_mouse(&mut self.app, event);
+    }
+
+    /// Screen position where `text` was drawn
+    #[track_caller]
+    pub fn locate(&mut self, text: &str) -> (u16, u16) {
+        let screen = self.render();
+        screen
+            .iter()
+            .enumerate()
+            .find_map(|(row, line)| {
+                let start = line.find(text)?;
+                Some((line[..start].chars().count() as u16, row as u16))
+            })
+            .unwrap_or_else(|| panic!("screen doesn't show {text:?}"))
+    }
+
+    /// Type each character of `text`
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.press(KeyCode::Char(c));
+        }
+    }
+
+    /// Deliver command output events and poll for exits, as the event loop
+    /// does, until `done` holds (or five seconds pass)
+    pub fn settle(&mut self, done: impl Fn(&App) -> bool) {
+        let started = std::time::Instant::now();
+        while !done(&self.app) && started.elapsed().as_secs() < 5 {
+            match self.app.events.next(std::time::Duration::from_millis(10)) {
+                Some(AppEvent::Output { pid, stream, line }) => {
+                    self.app.command_output(pid, stream, &line);
+                }
+                Some(AppEvent::OutputClosed { pid }) => self.app.command_output_closed(pid),
+                _ => {}
+            }
+            self.app.poll_running();
+            self.app.poll_jobs();
+        }
+    }
+
+    /// Type a command and press Enter
+    pub fn run(&mut self, command: &str) {
+        self.type_text(command);
+        self.press(KeyCode::Enter);
+    }
+
+    /// Draw the UI and return the screen, one string per row
+    pub fn render(&mut self) -> Vec<String> {
+        let app = &mut self.app;
+        self.terminal.draw(|f| draw_ui(f, app)).unwrap();
+        let buffer = self.terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    /// Draw the UI and panic, showing the screen, unless it contains `text`
+    #[track_caller]
+    pub fn assert_shows(&mut self, text: &str) {
+        let screen = self.render();
+        assert!(
+            screen.iter().any(|row| row.contains(text)),
+            "screen doesn't show {text:?}:\n{}",
+            screen.join("\n")
+        );
+    }
+
+    /// The opposite of [`Harness::assert_shows`]
+    #[track_caller]
+    pub fn assert_hides(&mut self, text: &str) {
+        let screen = self.render();
+        assert!(
+            !screen.iter().any(|row| row.contains(text)),
+            "screen shows {text:?}:\n{}",
+            screen.join("\n")
+        );
+    }
+
+    /// Save `dir` (relative to the root) as a shortcut
+    pub fn add_shortcut(&mut self, dir: &str, name: Option<&str>) {
+        self.app
+            .shortcuts
+            .add_shortcut(self.root.join(dir), name.map(str::to_string));
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.data_dir);
+    }
+}
+
+#[test]
+fn test_normal_mode() {
+    let mut ui = Harness::new();
+    ui.type_text("echo hi");
+    assert_eq!(ui.app.input, "echo hi");
+    ui.assert_shows("echo hi");
+
+    ui.press(KeyCode::Backspace);
+    ui.press(KeyCode::Backspace);
+    ui.press(KeyCode::Esc);
+    assert_eq!(ui.app.mode, AppMode::Normal);
+
+    // Builtins run without spawning anything
+    ui.press_with(KeyCode::Char('u'), KeyModifiers::CONTROL);
+    ui.run("cd beta");
+    assert_eq!(ui.app.current_dir, ui.root.join("beta"));
+    ui.assert_shows("cd beta");
+    ui.run("cd missing");
+    ui.assert_shows("cd: Directory not found: missing");
+}
+
+#[test]
+fn test_navigation_mode() {
+    let mut ui = Harness::new();
+    ui.run("cd -list");
+    assert_eq!(ui.app.mode, AppMode::NavigationList);
+    ui.assert_shows("alpha");
+    ui.assert_shows("beta");
+    // Only directories are listed
+    ui.assert_hides("notes.txt");
+
+    ui.press(KeyCode::Esc);
+    assert_eq!(ui.app.mode, AppMode::Normal);
+    assert_eq!(ui.app.current_dir, ui.root);
+}
+
+#[test]
+fn test_goto_mode() {
+    let mut ui = Harness::new();
+    ui.add_shortcut("alpha", Some("first"));
+    ui.add_shortcut("beta/nested", None);
+    ui.assert_shows("first");
+    ui.assert_shows("nested");
+
+    ui.press_with(KeyCode::Char('g'), KeyModifiers::CONTROL);
+    assert_eq!(ui.app.mode, AppMode::ShortcutSelection);
+    ui.assert_shows("↵ go");
+
+    // Filtering down to one shortcut and confirming jumps there
+    ui.type_text("nest");
+    ui.assert_hides("first");
+    ui.press(KeyCode::Enter);
+    assert_eq!(ui.app.mode, AppMode::Normal);
+    assert_eq!(ui.app.current_dir, ui.root.join("beta/nested"));
+}
+
+#[test]
+fn test_sidebar_wide_labels() {
+    let mut ui = Harness::new();
+    // Cut to fit by width, not bytes, in the middle of wide characters
+    let wide = "日本語のとても長いディレクトリの名前";
+    fs::create_dir_all(ui.root.join(wide)).unwrap();
+    ui.add_shortcut(wide, None);
+    ui.assert_shows("..");
+    ui.assert_shows("前");
+}
+
+#[test]
+fn test_sidebar_divider_drag() {
+    let mut ui = Harness::new();
+    ui.render();
+    let border = ui.app.sidebar_width(WIDTH) - 1;
+    ui.click(border, 5);
+    ui.mouse(MouseEventKind::Drag(MouseButton::Left), 39, 5);
+    ui.mouse(MouseEventKind::Up(MouseButton::Left), 39, 5);
+    assert_eq!(ui.app.config.ui.sidebar_ratio, 0.4);
+    let saved = fs::read_to_string(jerm::config::get_config_path().unwrap()).unwrap();
+    assert!(saved.contains("sidebar_ratio = 0.4"));
+
+    // The split keeps its share as the screen changes size, leaving the
+    // terminal pane its minimum width
+    assert_eq!(ui.app.sidebar_width(WIDTH), 40);
+    assert_eq!(ui.app.sidebar_width(200), 80);
+    assert_eq!(ui.app.sidebar_width(60), 20);
+}
+
+#[test]
+fn test_key_macros() {
+    let mut ui = Harness::new();
+    ui.press_with(KeyCode::Char('q'), KeyModifiers::ALT);
+    ui.type_text("a");
+    ui.assert_shows("[recording @a]");
+    ui.type_text("ab");
+    ui.press(KeyCode::Left);
+    ui.press_with(KeyCode::Char('q'), KeyModifiers::ALT);
+    ui.assert_shows("recorded 3 keys into @a");
+    assert_eq!(ui.app.input, "ab");
+
+    // Replays land where the cursor is now, like the recording did
+    ui.press_with(KeyCode::Char('p'), KeyModifiers::ALT);
+    ui.type_text("2a");
+    assert_eq!(ui.app.input, "aaabbb");
+    ui.assert_hides("@?");
+}
+
+#[test]
+fn test_mouse() {
+    let mut ui = Harness::new();
+    ui.run("cd -list");
+    ui.render();
+    let selected = ui.app.navigation_state.selected_index;
+    ui.mouse(MouseEventKind::ScrollDown, 50, 10);
+    assert_ne!(ui.app.navigation_state.selected_index, selected);
+    ui.press(KeyCode::Esc);
+
+    ui.add_shortcut("beta/nested", None);
+    let (column, row) = ui.locate("nested");
+    ui.click(column, row);
+    assert_eq!(ui.app.current_dir, ui.root.join("beta/nested"));
+
+    ui.type_text("echo hello");
+    let (column, row) = ui.locate("hello");
+    ui.click(column, row);
+    assert_eq!(ui.app.cursor_pos, 5);
+    ui.type_text("x");
+    assert_eq!(ui.app.input, "echo xhello");
+}
+
+#[test]
+fn test_multibyte_input() {
+    let mut ui = Harness::new();
+    // `é` typed as `e` plus a combining accent is one character
+    ui.type_text("echo 日本e\u{301}");
+    ui.press(KeyCode::Backspace);
+    assert_eq!(ui.app.input, "echo 日本");
+
+    ui.press(KeyCode::Left);
+    ui.type_text("x");
+    assert_eq!(ui.app.input, "echo 日x本");
+    ui.assert_shows("x本");
+
+    // Clicks land between characters despite the double-width ones
+    let (column, row) = ui.locate("x");
+    ui.press(KeyCode::End);
+    ui.click(column, row);
+    assert_eq!(ui.app.cursor_pos, "echo 日".len());
+}
+
+#[test]
+fn test_spell_check() {
+    let mut ui = Harness::new();
+    ui.type_text("git commit -m \"Seperate teh");
+    ui.assert_hides("teh \u{2192} the");
+
+    ui.app.config.git.spell_check = true;
+    ui.assert_shows("\u{270e} Seperate \u{2192} Separate \u{b7} teh \u{2192} the");
+}
+
+#[test]
+fn test_job_highlight() {
+    let mut ui = Harness::new();
+    ui.app.config.jobs.on_finish = JobAlert::Highlight;
+    ui.run("true &");
+    ui.settle(|app| app.job_highlight().is_some());
+    ui.render();
+    let frame = ui.terminal.backend().buffer().get(WIDTH - 1, 0).fg;
+    assert_eq!(frame, ui.app.theme.block_success);
+}
+
+#[test]
+fn test_clear_keeps_scrollback() {
+    let mut ui = Harness::new();
+    ui.app.add_output("before-clear");
+    ui.assert_shows("before-clear");
+    ui.press_with(KeyCode::Char('l'), KeyModifiers::CONTROL);
+    ui.assert_hides("before-clear");
+    ui.press(KeyCode::PageUp);
+    ui.assert_shows("before-clear");
+
+    ui.press(KeyCode::Esc);
+    ui.run("clear --hard");
+    ui.press(KeyCode::PageUp);
+    ui.assert_hides("before-clear");
+}
+
+#[test]
+fn test_undo_input() {
+    let mut ui = Harness::new();
+    ui.type_text("echo hi");
+    ui.press_with(KeyCode::Char('w'), KeyModifiers::CONTROL);
+    assert_eq!(ui.app.input, "echo ");
+
+    // Ctrl+_ arrives as Ctrl+7; at the prompt Ctrl+Z undoes too
+    ui.press_with(KeyCode::Char('7'), KeyModifiers::CONTROL);
+    assert_eq!(ui.app.input, "echo hi");
+    ui.press_with(KeyCode::Char('z'), KeyModifiers::CONTROL);
+    assert_eq!(ui.app.input, "");
+    ui.press_with(KeyCode::Char('_'), KeyModifiers::ALT);
+    assert_eq!(ui.app.input, "echo hi");
+    assert_eq!(ui.app.cursor_pos, 7);
+}
+
+#[test]
+fn test_input_box() {
+    let mut ui = Harness::new();
+    ui.app.config.ui.input_box = true;
+    for n in 0..40 {
+        ui.app.add_output(&format!("line {n}"));
+    }
+    ui.type_text("echo ");
+    let (_, row) = ui.locate("line 39");
+    ui.assert_hides(" Input ");
+
+    // Past a couple of rows the input moves into its box, which keeps its
+    // size as the input grows
+    ui.type_text(&"x".repeat(200));
+    ui.assert_shows(" Input ");
+    let (_, boxed_row) = ui.locate("line 39");
+    assert!(boxed_row < row);
+    ui.type_text(&"y".repeat(400));
+    assert_eq!(ui.locate("line 39").1, boxed_row);
+    ui.assert_shows("yyyy");
+}
+
+#[test]
+fn test_export_reaches_commands() {
+    let mut ui = Harness::new();
+    ui.run("export JERM_GREETING='hello there'");
+    ui.run("echo \"greeting: $JERM_GREETING\"");
+    ui.settle(|app| app.running.is_none());
+    ui.assert_shows("greeting: hello there");
+
+    // The panel marks what `export` set
+    let listed = |ui: &Harness| {
+        let panel = ui.app.env_panel.as_ref().unwrap();
+        panel
+            .vars
+            .iter()
+            .find(|(name, ..)| name == "JERM_GREETING")
+            .cloned()
+    };
+    ui.run("env");
+    ui.assert_shows(" Environment (");
+    let var = ("JERM_GREETING".to_string(), "hello there".to_string(), true);
+    assert_eq!(listed(&ui), Some(var));
+    ui.press(KeyCode::Esc);
+    ui.run("unset JERM_GREETING");
+    ui.run("env");
+    assert_eq!(listed(&ui), None);
+}
+
+#[test]
+fn test_project_policy() {
+    let mut ui = Harness::new();
+    fs::create_dir_all(ui.root.join(".jerm")).unwrap();
+    fs::write(
+        ui.root.join(".jerm/policy.toml"),
+        "[[rule]]\ncommand = \"touch blocked\"\naction = \"block\"\nmessage = \"Not here\"\n",
+    )
+    .unwrap();
+    ui.app.change_dir(ui.root.join("alpha"));
+    ui.run("touch blocked");
+    ui.assert_shows("\u{2717} blocked by project policy: touch blocked");
+    ui.assert_shows("Not here");
+    assert!(ui.app.running.is_none());
+    assert!(!ui.root.join("alpha/blocked").exists());
+}
@@ -1,5 +1,6 @@
 //! Syntax highlighting for command input
 
+pub mod spelling;
 pub mod tokenizer;
 
 pub use tokenizer::Tokenizer;
@@ -0,0 +1,338 @@
+//! Spell-assist for commit messages typed in the input line
+//!
+//! When `git.spell_check` is on, the messages given to `git commit` with
+//! `-m` or `--message` are checked as they're typed. A full dictionary
+//! would flag every identifier and product name a commit mentions, so the
+//! check only knows common misspellings, each with its correction.
+
+use std::ops::Range;
+
+use super::tokenizer::{TokenType, Tokenizer};
+
+/// Common misspellings and their corrections, sorted by misspelling
+const MISSPELLINGS: &[(&str, &str)] = &[
+    ("accidentaly", "accidentally"),
+    ("accomodate", "accommodate"),
+    ("accross", "across"),
+    ("acheive", "achieve"),
+    ("addres", "address"),
+    ("adress", "address"),
+    ("agian", "again"),
+    ("algorith", "algorithm"),
+    ("allready", "already"),
+    ("alogrithm", "algorithm"),
+    ("alot", "a"),
+    ("amoung", "among"),
+    ("anonymus", "anonymous"),
+    ("apparantly", "apparently"),
+    ("appearence", "appearance"),
+    ("argment", "argument"),
+    ("arguement", "argument"),
+    ("asynchronus", "asynchronous"),
+    ("attribte", "attribute"),
+    ("authentification", "authentication"),
+    ("avaiable", "available"),
+    ("availabe", "available"),
+    ("availible", "available"),
+    ("backwords", "backwards"),
+    ("beacuse", "because"),
+    ("becasue", "because"),
+    ("becuase", "because"),
+    ("befor", "before"),
+    ("begining", "beginning"),
+    ("beleive", "believe"),
+    ("benifit", "benefit"),
+    ("boundry", "boundary"),
+    ("buidl", "build"),
+    ("calback", "callback"),
+    ("cancelation", "cancellation"),
+    ("catagory", "category"),
+    ("chage", "change"),
+    ("changeing", "changing"),
+    ("charater", "character"),
+    ("cheking", "checking"),
+    ("choosen", "chosen"),
+    ("comand", "command"),
+    ("comming", "coming"),
+    ("commited", "committed"),
+    ("commmit", "commit"),
+    ("comparision", "comparison"),
+    ("compatability", "compatibility"),
+    ("compatable", "compatible"),
+    ("compiliation", "compilation"),
+    ("completly", "completely"),
+    ("concurent", "concurrent"),
+    ("conditon", "condition"),
+    ("configuation", "configuration"),
+    ("configuraiton", "configuration"),
+    ("conneciton", "connection"),
+    ("consistant", "consistent"),
+    ("containg", "containing"),
+    ("continous", "continuous"),
+    ("convertion", "conversion"),
+    ("correclty", "correctly"),
+    ("cuase", "cause"),
+    ("curent", "current"),
+    ("currenly", "currently"),
+    ("dafault", "default"),
+    ("databse", "database"),
+    ("deafult", "default"),
+    ("decleration", "declaration"),
+    ("definately", "definitely"),
+    ("definitly", "definitely"),
+    ("defualt", "default"),
+    ("depedency", "dependency"),
+    ("dependancy", "dependency"),
+    ("dependecy", "dependency"),
+    ("deprected", "deprecated"),
+    ("descripton", "description"),
+    ("desription", "description"),
+    ("destory", "destroy"),
+    ("determin", "determine"),
+    ("developement", "development"),
+    ("diference", "difference"),
+    ("differnt", "different"),
+    ("dirctory", "directory"),
+    ("direcotry", "directory"),
+    ("directroy", "directory"),
+    ("documantation", "documentation"),
+    ("documenation", "documentation"),
+    ("doesnt", "doesn't"),
+    ("dont", "don't"),
+    ("dupliate", "duplicate"),
+    ("eficient", "efficient"),
+    ("elemnt", "element"),
+    ("enviornment", "environment"),
+    ("enviroment", "environment"),
+    ("envrionment", "environment"),
+    ("equivalant", "equivalent"),
+    ("exeption", "exception"),
+    ("existance", "existence"),
+    ("existant", "existent"),
+    ("explicitely", "explicitly"),
+    ("extention", "extension"),
+    ("fucntion", "function"),
+    ("funciton", "function"),
+    ("functino", "function"),
+    ("futher", "further"),
+    ("garantee", "guarantee"),
+    ("gaurd", "guard"),
+    ("handeling", "handling"),
+    ("handleing", "handling"),
+    ("heigth", "height"),
+    ("hierachy", "hierarchy"),
+    ("identifer", "identifier"),
+    ("immediatly", "immediately"),
+    ("implemenation", "implementation"),
+    ("implementaion", "implementation"),
+    ("implmentation", "implementation"),
+    ("incase", "in"),
+    ("inconsistant", "inconsistent"),
+    ("independant", "independent"),
+    ("infomation", "information"),
+    ("informaton", "information"),
+    ("initalize", "initialize"),
+    ("initilize", "initialize"),
+    ("instace", "instance"),
+    ("intead", "instead"),
+    ("interupt", "interrupt"),
+    ("intial", "initial"),
+    ("isnt", "isn't"),
+    ("lenght", "length"),
+    ("libary", "library"),
+    ("maintainance", "maintenance"),
+    ("managment", "management"),
+    ("mesage", "message"),
+    ("messsage", "message"),
+    ("mispelled", "misspelled"),
+    ("mispelling", "misspelling"),
+    ("neccessary", "necessary"),
+    ("necesary", "necessary"),
+    ("occured", "occurred"),
+    ("occurence", "occurrence"),
+    ("occurrance", "occurrence"),
+    ("optionaly", "optionally"),
+    ("overriden", "overridden"),
+    ("paramater", "parameter"),
+    ("paramter", "parameter"),
+    ("parmeter", "parameter"),
+    ("perfomance", "performance"),
+    ("performace", "performance"),
+    ("permision", "permission"),
+    ("persistant", "persistent"),
+    ("posible", "possible"),
+    ("prefered", "preferred"),
+    ("previosly", "previously"),
+    ("proccess", "process"),
+    ("programatically", "programmatically"),
+    ("propery", "property"),
+    ("propogate", "propagate"),
+    ("recieve", "receive"),
+    ("recieved", "received"),
+    ("recursivly", "recursively"),
+    ("refered", "referred"),
+    ("refrence", "reference"),
+    ("regresion", "regression"),
+    ("relevent", "relevant"),
+    ("remvoe", "remove"),
+    ("reponse", "response"),
+    ("repositry", "repository"),
+    ("requried", "required"),
+    ("resouce", "resource"),
+    ("retreive", "retrieve"),
+    ("retun", "return"),
+    ("seperate", "separate"),
+    ("seperately", "separately"),
+    ("seperator", "separator"),
+    ("succesful", "successful"),
+    ("successfull", "successful"),
+    ("sucess", "success"),
+    ("sucessful", "successful"),
+    ("suport", "support"),
+    ("supress", "suppress"),
+    ("teh", "the"),
+    ("temoprary", "temporary"),
+    ("temproary", "temporary"),
+    ("thier", "their"),
+    ("threshhold", "threshold"),
+    ("tranform", "transform"),
+    ("udpate", "update"),
+    ("unecessary", "unnecessary"),
+    ("unneccessary", "unnecessary"),
+    ("untill", "until"),
+    ("upate", "update"),
+    ("usefull", "useful"),
+    ("varaible", "variable"),
+    ("variabel", "variable"),
+    ("verison", "version"),
+    ("wether", "whether"),
+    ("whcih", "which"),
+    ("whitch", "which"),
+    ("wich", "which"),
+    ("wierd", "weird"),
+    ("witdh", "width"),
+    ("writting", "writing"),
+];
+
+/// A misspelled word in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    /// Byte range of the word
+    pub range: Range<usize>,
+    /// Suggested spelling, capitalized like the word
+    pub correction: String,
+}
+
+/// Misspelled words in the commit messages of `input`
+pub fn check(input: &str) -> Vec<Misspelling> {
+    commit_messages(input)
+        .into_iter()
+        .flat_map(|message| words(input, message))
+        .filter_map(|range| {
+            let correction = correct(&input[range.clone()])?;
+            Some(Misspelling { range, correction })
+        })
+        .collect()
+}
+
+/// Byte ranges of the quoted messages passed to `git commit`, inside
+/// their quotes
+fn commit_messages(input: &str) -> Vec<Range<usize>> {
+    let mut messages = Vec::new();
+    let mut start = 0;
+    // Whether the current command is `git ... commit`, and whether the
+    // last flag takes the message
+    let (mut git, mut commit, mut message_flag) = (false, false, false);
+    for token in Tokenizer::tokenize(input) {
+        let range = start..start + token.text.len();
+        start = range.end;
+        match token.token_type {
+            TokenType::Whitespace => continue,
+            TokenType::Operator => (git, commit) = (false, false),
+            TokenType::Command => git = token.text == "git",
+            TokenType::Text if git && !commit => commit = token.text == "commit",
+            TokenType::String if commit && message_flag => {
+                // The closing quote is missing while the message is typed
+                let quote = &token.text[..1];
+                let closed = token.text.len() > 1 && token.text.ends_with(quote);
+                messages.push(range.start + 1..range.end - usize::from(closed));
+            }
+            _ => {}
+        }
+        message_flag = token.token_type == TokenType::Flag
+            && (token.text == "--message"
+                || (!token.text.starts_with("--") && token.text.ends_with('m')));
+    }
+    messages
+}
+
+/// Byte ranges of the words in `range` of `input`
+fn words(input: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let text = &input[range.clone()];
+    let mut words = Vec::new();
+    let mut word: Option<usize> = None;
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (word, c.is_alphabetic() || c == '\'') {
+            (None, true) => word = Some(index),
+            (Some(begin), false) => {
+                // Quotes around a word aren't part of it
+                let quoted = &text[begin..index];
+                let found = quoted.trim_start_matches('\'');
+                let begin = range.start + begin + quoted.len() - found.len();
+                let found = found.trim_end_matches('\'');
+                if !found.is_empty() {
+                    words.push(begin..begin + found.len());
+                }
+                word = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Correction for `word`, if it's a known misspelling
+fn correct(word: &str) -> Option<String> {
+    let lower = word.to_lowercase();
+    let index = MISSPELLINGS
+        .binary_search_by(|(wrong, _)| (*wrong).cmp(lower.as_str()))
+        .ok()?;
+    let correction = MISSPELLINGS[index].1;
+    let mut chars = word.chars();
+    Some(match chars.next() {
+        Some(first) if first.is_uppercase() => {
+            let mut rest = correction.chars();
+            rest.next()
+                .map(|c| c.to_uppercase().chain(rest).collect())
+                .unwrap_or_default()
+        }
+        _ => correction.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_misspellings_sorted() {
+        assert!(MISSPELLINGS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_check_commit_messages() {
+        let input = "git commit -am \"Seperate teh parser's tests\" -m 'fix typo'";
+        let found = check(input);
+        assert_eq!(found.len(), 2);
+        assert_eq!(&input[found[0].range.clone()], "Seperate");
+        assert_eq!(found[0].correction, "Separate");
+        assert_eq!(&input[found[1].range.clone()], "teh");
+        assert_eq!(found[1].correction, "the");
+
+        // Unfinished messages are checked as they're typed
+        assert_eq!(check("git commit --message \"recieve").len(), 1);
+        // Only commit messages
+        assert!(check("echo \"teh\" && git commit --amend").is_empty());
+        assert!(check("git log -m \"teh\"").is_empty());
+    }
+}
@@ -3,7 +3,7 @@
 use ratatui::style::Style;
 use ratatui::text::Span;
 
-use crate::theme::Palette;
+use crate::theme::Theme;
 
 /// Type of token for syntax highlighting
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -205,19 +205,19 @@ impl Tokenizer {
     }
 
     /// Convert tokens to styled spans for rendering
-    pub fn to_spans(tokens: &[Token]) -> Vec<Span<'static>> {
+    pub fn to_spans(tokens: &[Token], theme: &Theme) -> Vec<Span<'static>> {
         tokens
             .iter()
             .map(|token| {
                 let style = match token.token_type {
-                    TokenType::Command => Style::default().fg(Palette::SYNTAX_COMMAND),
-                    TokenType::Flag => Style::default().fg(Palette::SYNTAX_FLAG),
-                    TokenType::Path => Style::default().fg(Palette::SYNTAX_PATH),
-                    TokenType::String => Style::default().fg(Palette::SYNTAX_STRING),
-                    TokenType::Number => Style::default().fg(Palette::SYNTAX_NUMBER),
-                    TokenType::Operator => Style::default().fg(Palette::SYNTAX_OPERATOR),
+                    TokenType::Command => Style::default().fg(theme.syntax_command),
+                    TokenType::Flag => Style::default().fg(theme.syntax_flag),
+                    TokenType::Path => Style::default().fg(theme.syntax_path),
+                    TokenType::String => Style::default().fg(theme.syntax_string),
+                    TokenType::Number => Style::default().fg(theme.syntax_number),
+                    TokenType::Operator => Style::default().fg(theme.syntax_operator),
                     TokenType::Whitespace | TokenType::Text => {
-                        Style::default().fg(Palette::SYNTAX_TEXT)
+                        Style::default().fg(theme.syntax_text)
                     }
                 };
                 Span::styled(token.text.clone(), style)
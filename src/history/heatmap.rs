@@ -0,0 +1,165 @@
+//! Activity heatmap for `jerm heatmap`
+//!
+//! Commands from the history are counted per local day and laid out like a
+//! contribution graph: one column per week, one row per weekday.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+
+use super::storage::HistoryEntry;
+
+/// Weeks shown, ending with the current one
+pub const WEEKS: i64 = 52;
+
+/// Commands run per day, with the selected day
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    /// Commands of each day in the window, in the order they ran
+    days: BTreeMap<NaiveDate, Vec<(NaiveDateTime, String)>>,
+    /// Most commands run on a single day
+    max: usize,
+    /// Last day shown (today)
+    pub today: NaiveDate,
+    /// Day whose commands are listed
+    pub selected: NaiveDate,
+    /// Scroll offset of the command list
+    pub scroll: usize,
+}
+
+impl Heatmap {
+    /// Count the history's commands per day in `tz`, up to `today`
+    pub fn new<Tz: TimeZone>(entries: &[HistoryEntry], tz: &Tz, today: NaiveDate) -> Self {
+        let start = first_day(today);
+        let mut days: BTreeMap<NaiveDate, Vec<(NaiveDateTime, String)>> = BTreeMap::new();
+        for entry in entries {
+            let time = entry.timestamp.with_timezone(tz).naive_local();
+            if (start..=today).contains(&time.date()) {
+                days.entry(time.date())
+                    .or_default()
+                    .push((time, entry.command.clone()));
+            }
+        }
+        let max = days.values().map(Vec::len).max().unwrap_or(0);
+
+        Self {
+            days,
+            max,
+            today,
+            selected: today,
+            scroll: 0,
+        }
+    }
+
+    /// Monday of the first week shown
+    pub fn start(&self) -> NaiveDate {
+        first_day(self.today)
+    }
+
+    /// Number of commands run on a day
+    pub fn count(&self, day: NaiveDate) -> usize {
+        self.days.get(&day).map_or(0, Vec::len)
+    }
+
+    /// Total commands in the window
+    pub fn total(&self) -> usize {
+        self.days.values().map(Vec::len).sum()
+    }
+
+    /// Intensity of a day from 0 (no commands) to 4 (the busiest days)
+    pub fn level(&self, day: NaiveDate) -> u8 {
+        let count = self.count(day);
+        if count == 0 || self.max == 0 {
+            return 0;
+        }
+        (count * 4).div_ceil(self.max).min(4) as u8
+    }
+
+    /// Commands run on the selected day, with the time they ran
+    pub fn selected_commands(&self) -> &[(NaiveDateTime, String)] {
+        self.days.get(&self.selected).map_or(&[], Vec::as_slice)
+    }
+
+    /// Commands run in each hour of the selected day
+    pub fn selected_hours(&self) -> [usize; 24] {
+        let mut hours = [0; 24];
+        for (time, _) in self.selected_commands() {
+            hours[time.hour() as usize] += 1;
+        }
+        hours
+    }
+
+    /// Move the selection by a number of days, staying within the window
+    pub fn move_days(&mut self, days: i64) {
+        let target = self.selected + Duration::days(days);
+        if (self.start()..=self.today).contains(&target) {
+            self.selected = target;
+            self.scroll = 0;
+        }
+    }
+
+    /// Scroll the command list
+    pub fn scroll_by(&mut self, lines: isize) {
+        let max = self.selected_commands().len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+}
+
+/// Monday of the week `WEEKS - 1` weeks before `today`'s
+fn first_day(today: NaiveDate) -> NaiveDate {
+    let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    monday - Duration::weeks(WEEKS - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    fn entry(command: &str, time: &str) -> HistoryEntry {
+        HistoryEntry {
+            command: command.to_string(),
+            cwd: PathBuf::from("/"),
+            timestamp: time.parse().unwrap(),
+        }
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_counts_and_levels() {
+        let entries = vec![
+            entry("ls", "2026-03-02T09:15:00Z"),
+            entry("make", "2026-03-02T09:40:00Z"),
+            entry("git push", "2026-03-02T17:00:00Z"),
+            entry("ls", "2026-03-04T11:00:00Z"),
+            entry("old", "2024-01-01T11:00:00Z"),
+        ];
+        let mut heatmap = Heatmap::new(&entries, &Utc, date("2026-03-04"));
+
+        assert_eq!(heatmap.total(), 4);
+        assert_eq!(heatmap.level(date("2026-03-02")), 4);
+        assert_eq!(heatmap.level(date("2026-03-04")), 2);
+        assert_eq!(heatmap.level(date("2026-03-03")), 0);
+
+        heatmap.move_days(-2);
+        assert_eq!(heatmap.selected, date("2026-03-02"));
+        assert_eq!(heatmap.selected_commands().len(), 3);
+        let hours = heatmap.selected_hours();
+        assert_eq!((hours[9], hours[17]), (2, 1));
+
+        // Can't go past today
+        heatmap.move_days(7);
+        assert_eq!(heatmap.selected, date("2026-03-02"));
+    }
+
+    #[test]
+    fn test_window_starts_on_monday() {
+        let heatmap = Heatmap::new(&[], &Utc, date("2026-03-04"));
+        assert_eq!(heatmap.start().weekday(), chrono::Weekday::Mon);
+        assert_eq!((heatmap.today - heatmap.start()).num_days(), 51 * 7 + 2);
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+
+use super::storage::{self, HistoryEntry};
+
+/// Number of entries kept when no size is configured
+pub const DEFAULT_HISTORY_SIZE: usize = 10_000;
+
+/// Command history, loaded on startup and appended to as commands run
+pub struct History {
+    entries: Vec<HistoryEntry>,
+    max_size: usize,
+    /// Lines currently in the history file, used to decide when to compact it
+    file_lines: usize,
+    readonly: bool,
+    /// Whether Up-arrow prefers commands run in the current directory
+    scoped: bool,
+}
+
+impl History {
+    /// Create a history keeping `max_size` entries and load it from disk
+    ///
+    /// With `scoped`, Up-arrow prefers commands run in the current directory.
+    pub fn new(max_size: usize, scoped: bool) -> Self {
+        let mut history = Self {
+            entries: Vec::new(),
+            max_size: max_size.max(1),
+            file_lines: 0,
+            readonly: false,
+            scoped,
+        };
+        history.reload();
+        history
+    }
+
+    /// Enable or disable writing to disk
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    /// Reload entries from disk (e.g. after switching profiles)
+    pub fn reload(&mut self) {
+        let loaded = storage::get_history_path()
+            .and_then(|path| storage::load_entries(&path, self.max_size));
+        (self.entries, self.file_lines) = loaded.unwrap_or_default();
+    }
+
+    /// Record a command, skipping blanks and repeats of the last command
+    pub fn push(&mut self, command: &str, cwd: PathBuf) {
+        let command = command.trim();
+        if command.is_empty() || self.last() == Some(command) {
+            return;
+        }
+
+        let entry = HistoryEntry::new(command, cwd);
+        self.entries.push(entry.clone());
+        let excess = self.entries.len().saturating_sub(self.max_size);
+        self.entries.drain(..excess);
+        self.persist(&entry);
+    }
+
+    /// Commands to cycle through with Up/Down, oldest first
+    ///
+    /// In directory-scoped mode, commands run in `cwd` come last so Up-arrow
+    /// reaches them first; the rest of the history follows without repeating
+    /// them.
+    pub fn navigation_list(&self, cwd: &Path) -> Vec<String> {
+        let all = self.entries.iter().map(|e| e.command.clone());
+        if !self.scoped {
+            return all.collect();
+        }
+
+        let local: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| e.cwd == cwd)
+            .map(|e| e.command.clone())
+            .collect();
+        let mut list: Vec<String> = all.filter(|command| !local.contains(command)).collect();
+        list.extend(local);
+        list
+    }
+
+    /// Most recent command that extends `prefix`
+    pub fn suggest(&self, prefix: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|e| e.command.as_str())
+            .find(|command| command.len() > prefix.len() && command.starts_with(prefix))
+    }
+
+    /// Every entry loaded, oldest first
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Most recent command
+    pub fn last(&self) -> Option<&str> {
+        self.entries.last().map(|e| e.command.as_str())
+    }
+
+    /// Append an entry to disk, compacting the file once it has grown
+    /// well past the size limit
+    fn persist(&mut self, entry: &HistoryEntry) {
+        if self.readonly {
+            return;
+        }
+        let Ok(path) = storage::get_history_path() else {
+            return;
+        };
+
+        if self.file_lines >= self.max_size.saturating_mul(2) {
+            if storage::write_entries(&path, &self.entries).is_ok() {
+                self.file_lines = self.entries.len();
+            }
+        } else if storage::append_entry(&path, entry).is_ok() {
+            self.file_lines += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(scoped: bool, entries: &[(&str, &str)]) -> History {
+        History {
+            entries: entries
+                .iter()
+                .map(|(command, cwd)| HistoryEntry::new(*command, PathBuf::from(cwd)))
+                .collect(),
+            max_size: DEFAULT_HISTORY_SIZE,
+            file_lines: 0,
+            readonly: true,
+            scoped,
+        }
+    }
+
+    const ENTRIES: &[(&str, &str)] = &[
+        ("cargo build", "/work/app"),
+        ("ls", "/tmp"),
+        ("cargo test", "/work/app"),
+        ("ls", "/work/app"),
+        ("rm -rf target", "/tmp"),
+    ];
+
+    #[test]
+    fn test_global_navigation() {
+        let list = history(false, ENTRIES).navigation_list(Path::new("/work/app"));
+        assert_eq!(
+            list,
+            vec!["cargo build", "ls", "cargo test", "ls", "rm -rf target"]
+        );
+    }
+
+    #[test]
+    fn test_directory_scoped_navigation() {
+        let list = history(true, ENTRIES).navigation_list(Path::new("/work/app"));
+        // Up-arrow walks from the end: local commands first, then the rest
+        assert_eq!(
+            list,
+            vec!["rm -rf target", "cargo build", "cargo test", "ls"]
+        );
+    }
+
+    #[test]
+    fn test_suggest() {
+        let history = history(false, ENTRIES);
+        assert_eq!(history.suggest("cargo"), Some("cargo test"));
+        assert_eq!(history.suggest("rm -rf target"), None);
+        assert_eq!(history.suggest("vim"), None);
+    }
+
+    #[test]
+    fn test_push_skips_repeats() {
+        let mut history = history(false, &[]);
+        history.push("ls", PathBuf::from("/"));
+        history.push("ls ", PathBuf::from("/"));
+        history.push("  ", PathBuf::from("/"));
+        assert_eq!(history.navigation_list(Path::new("/")), vec!["ls"]);
+    }
+}
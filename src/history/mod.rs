@@ -0,0 +1,11 @@
+//! Persistent command history
+//!
+//! Every command run is appended to `history` in the config directory as a
+//! JSON line carrying the command, its working directory, and when it ran.
+
+pub mod heatmap;
+pub mod manager;
+pub mod storage;
+
+pub use heatmap::Heatmap;
+pub use manager::{History, DEFAULT_HISTORY_SIZE};
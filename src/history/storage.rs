@@ -0,0 +1,142 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors that can occur while reading or writing the history file
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("Failed to access history file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to encode history entry: {0}")]
+    Encode(#[from] serde_json::Error),
+
+    #[error("Config directory not found")]
+    ConfigDirNotFound,
+}
+
+/// A command that was run, with the directory it ran in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The command line as typed
+    pub command: String,
+    /// Working directory the command ran in
+    pub cwd: PathBuf,
+    /// When the command was run
+    pub timestamp: DateTime<Utc>,
+}
+
+impl HistoryEntry {
+    /// Create an entry for a command run now
+    pub fn new(command: impl Into<String>, cwd: PathBuf) -> Self {
+        Self {
+            command: command.into(),
+            cwd,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Get the path to the history file
+pub fn get_history_path() -> Result<PathBuf, HistoryError> {
+    let config_dir = crate::paths::config_dir().ok_or(HistoryError::ConfigDirNotFound)?;
+    Ok(config_dir.join("history"))
+}
+
+/// Load the most recent `max_size` entries from a history file
+///
+/// Lines that fail to parse are skipped so one bad write never loses the
+/// rest of the history. Returns the entries and the number of lines read.
+pub fn load_entries(
+    path: &Path,
+    max_size: usize,
+) -> Result<(Vec<HistoryEntry>, usize), HistoryError> {
+    if !path.exists() {
+        return Ok((Vec::new(), 0));
+    }
+
+    let contents = fs::read_to_string(path)?;
+    let lines = contents.lines().filter(|l| !l.trim().is_empty()).count();
+    let mut entries: Vec<HistoryEntry> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let excess = entries.len().saturating_sub(max_size);
+    entries.drain(..excess);
+    Ok((entries, lines))
+}
+
+/// Append a single entry to a history file
+pub fn append_entry(path: &Path, entry: &HistoryEntry) -> Result<(), HistoryError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Replace a history file with the given entries
+pub fn write_entries(path: &Path, entries: &[HistoryEntry]) -> Result<(), HistoryError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(&serde_json::to_string(entry)?);
+        contents.push('\n');
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = ScratchDir::new("history");
+        let path = dir.join("history.jsonl");
+
+        for command in ["ls", "git status", "make"] {
+            append_entry(&path, &HistoryEntry::new(command, PathBuf::from("/tmp"))).unwrap();
+        }
+
+        let (entries, lines) = load_entries(&path, 2).unwrap();
+        let commands: Vec<&str> = entries.iter().map(|e| e.command.as_str()).collect();
+        assert_eq!(commands, vec!["git status", "make"]);
+        assert_eq!(lines, 3);
+        assert_eq!(entries[0].cwd, PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn test_load_skips_bad_lines() {
+        let dir = ScratchDir::new("history");
+        let path = dir.join("history.jsonl");
+        let entry = HistoryEntry::new("ls", PathBuf::from("/"));
+        fs::write(
+            &path,
+            format!("not json\n{}\n", serde_json::to_string(&entry).unwrap()),
+        )
+        .unwrap();
+
+        let (entries, _) = load_entries(&path, 100).unwrap();
+        assert_eq!(entries, vec![entry]);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = ScratchDir::new("history");
+        let (entries, lines) = load_entries(&dir.join("missing.jsonl"), 100).unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(lines, 0);
+    }
+}
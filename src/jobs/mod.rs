@@ -0,0 +1,209 @@
+//! Background job control (`&`, `jobs`, `fg`, `bg`)
+
+pub mod queue;
+
+pub use queue::{CommandQueue, Load};
+
+use crate::shell::executor::{OutputStream, RunningCommand};
+
+/// State of a background job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Stopped,
+}
+
+impl JobStatus {
+    /// Label shown by `jobs`
+    pub fn label(self) -> &'static str {
+        match self {
+            JobStatus::Running => "Running",
+            JobStatus::Stopped => "Stopped",
+        }
+    }
+}
+
+/// A command running (or suspended) in the background
+pub struct Job {
+    /// Job number shown as `[n]`
+    pub id: usize,
+    /// Current state
+    pub status: JobStatus,
+    /// The underlying process
+    pub command: RunningCommand,
+}
+
+/// Event produced while polling background jobs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobEvent {
+    /// A line of output from a job
+    Output(OutputStream, String),
+    /// A job finished with the given exit code
+    Finished {
+        id: usize,
+        command: String,
+        exit_code: i32,
+    },
+}
+
+/// Table of background jobs, numbered like a shell's job table
+#[derive(Default)]
+pub struct JobTable {
+    jobs: Vec<Job>,
+}
+
+impl JobTable {
+    /// Create an empty job table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a command to the table and return its job number
+    ///
+    /// Numbers are reused once jobs finish, starting from the lowest free one.
+    pub fn add(&mut self, command: RunningCommand, status: JobStatus) -> usize {
+        let id = (1..)
+            .find(|n| !self.jobs.iter().any(|job| job.id == *n))
+            .unwrap_or(1);
+        self.jobs.push(Job {
+            id,
+            status,
+            command,
+        });
+        self.jobs.sort_by_key(|job| job.id);
+        id
+    }
+
+    /// All jobs, ordered by job number
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    /// Whether there are no jobs
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Number of jobs that aren't stopped
+    pub fn running(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Running)
+            .count()
+    }
+
+    /// Resolve a job reference: the given number, or the most recent job
+    pub fn resolve(&self, id: Option<usize>) -> Option<usize> {
+        match id {
+            Some(id) => self.jobs.iter().find(|job| job.id == id).map(|job| job.id),
+            None => self.jobs.iter().map(|job| job.id).max(),
+        }
+    }
+
+    /// The job whose shell has the given pid
+    pub fn by_pid(&mut self, pid: u32) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.command.pid() == pid)
+    }
+
+    /// Remove a job from the table (e.g. to bring it to the foreground)
+    pub fn take(&mut self, id: usize) -> Option<Job> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        Some(self.jobs.remove(index))
+    }
+
+    /// Resume a stopped job in the background
+    pub fn resume(&mut self, id: usize) -> Option<&Job> {
+        let job = self.jobs.iter_mut().find(|job| job.id == id)?;
+        job.command.resume();
+        job.status = JobStatus::Running;
+        Some(job)
+    }
+
+    /// Collect output and completions from all jobs, removing finished ones
+    pub fn poll(&mut self) -> Vec<JobEvent> {
+        let mut events = Vec::new();
+
+        for job in &mut self.jobs {
+            for (stream, line) in job.command.drain_output() {
+                events.push(JobEvent::Output(stream, line));
+            }
+        }
+
+        let mut index = 0;
+        while index < self.jobs.len() {
+            if let Some(exit_code) = self.jobs[index].command.try_finish() {
+                let job = self.jobs.remove(index);
+                events.push(JobEvent::Finished {
+                    id: job.id,
+                    command: job.command.command.clone(),
+                    exit_code,
+                });
+            } else {
+                index += 1;
+            }
+        }
+
+        events
+    }
+
+    /// Kill every job (used on exit); returns how many were killed
+    pub fn kill_all(&mut self) -> usize {
+        for job in &self.jobs {
+            job.command.resume();
+            job.command.kill();
+        }
+        let count = self.jobs.len();
+        self.jobs.clear();
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::env::Environment;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    fn spawn(command: &str) -> RunningCommand {
+        RunningCommand::spawn(command, Path::new("/tmp"), &Environment::default()).unwrap()
+    }
+
+    #[test]
+    fn test_job_numbers_are_reused() {
+        let mut table = JobTable::new();
+        assert_eq!(table.add(spawn("sleep 5"), JobStatus::Running), 1);
+        assert_eq!(table.add(spawn("sleep 5"), JobStatus::Running), 2);
+        let job = table.take(1).unwrap();
+        job.command.kill();
+        assert_eq!(table.add(spawn("sleep 5"), JobStatus::Running), 1);
+        assert_eq!(table.resolve(None), Some(2));
+        assert_eq!(table.resolve(Some(7)), None);
+        assert_eq!(table.kill_all(), 2);
+    }
+
+    #[test]
+    fn test_poll_reports_completion() {
+        let mut table = JobTable::new();
+        table.add(spawn("echo hi"), JobStatus::Running);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut events = Vec::new();
+        while !events
+            .iter()
+            .any(|e| matches!(e, JobEvent::Finished { .. }))
+        {
+            assert!(Instant::now() < deadline, "job did not finish");
+            events.extend(table.poll());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(events.contains(&JobEvent::Output(OutputStream::Stdout, "hi".to_string())));
+        assert!(events.contains(&JobEvent::Finished {
+            id: 1,
+            command: "echo hi".to_string(),
+            exit_code: 0,
+        }));
+        assert!(table.is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! Commands entered while earlier ones are still running
+//!
+//! Commands start strictly in the order they were entered: a foreground
+//! command waits for the one before it, and with `jobs.max_running` set,
+//! commands that start a process wait for a free slot. Builtins such as
+//! `cd` queue behind a foreground command like anything else, so `make`
+//! followed by `cd ..` still builds in the original directory, but never
+//! need a slot: `fg` or `jobs` at the limit would otherwise wait on the very
+//! jobs they're meant to control.
+
+use std::collections::VecDeque;
+
+use crate::shell::parser::{parse_command, ParsedCommand};
+
+/// What's running right now, for deciding whether a command can start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Load {
+    /// A foreground command or interactive program is running
+    pub foreground: bool,
+    /// Commands running in total, foreground and background
+    pub running: usize,
+}
+
+/// Commands waiting to start, oldest first
+#[derive(Debug, Default)]
+pub struct CommandQueue {
+    pending: VecDeque<String>,
+    /// Most commands running at once (0 for no limit)
+    pub limit: usize,
+}
+
+impl CommandQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `command` has to wait behind the queue or the current load
+    pub fn must_wait(&self, command: &str, load: Load) -> bool {
+        !self.pending.is_empty() || !self.can_start(command, load)
+    }
+
+    /// Add a command to the back; returns how many are now waiting
+    pub fn push(&mut self, command: String) -> usize {
+        self.pending.push_back(command);
+        self.pending.len()
+    }
+
+    /// Take the oldest command if it can start now
+    pub fn pop_ready(&mut self, load: Load) -> Option<String> {
+        let next = self.pending.front()?;
+        if self.can_start(next, load) {
+            self.pending.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Drop every waiting command, returning how many there were
+    pub fn clear(&mut self) -> usize {
+        let count = self.pending.len();
+        self.pending.clear();
+        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    fn can_start(&self, command: &str, load: Load) -> bool {
+        let has_slot = self.limit == 0 || load.running < self.limit;
+        match parse_command(command) {
+            ParsedCommand::Background(_) => has_slot,
+            ParsedCommand::Shell(_)
+            | ParsedCommand::JermRetry(Some(_))
+            | ParsedCommand::JermTest(_)
+            | ParsedCommand::JermPty(Some(_)) => !load.foreground && has_slot,
+            _ => !load.foreground,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE: Load = Load {
+        foreground: false,
+        running: 0,
+    };
+    const BUSY: Load = Load {
+        foreground: true,
+        running: 1,
+    };
+
+    #[test]
+    fn test_foreground_waits_for_foreground() {
+        let mut queue = CommandQueue::new();
+        assert!(!queue.must_wait("make", IDLE));
+        assert!(queue.must_wait("make", BUSY));
+        // Background commands aren't held up without a limit
+        assert!(!queue.must_wait("sleep 5 &", BUSY));
+
+        queue.push("make test".to_string());
+        queue.push("cd ..".to_string());
+        assert!(queue.must_wait("sleep 5 &", BUSY));
+        assert_eq!(queue.pop_ready(BUSY), None);
+        assert_eq!(queue.pop_ready(IDLE).as_deref(), Some("make test"));
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn test_limit_holds_background_commands() {
+        let mut queue = CommandQueue::new();
+        queue.limit = 2;
+        let two_jobs = Load {
+            foreground: false,
+            running: 2,
+        };
+        assert!(queue.must_wait("sleep 5 &", two_jobs));
+        assert!(queue.must_wait("ls", two_jobs));
+        assert!(!queue.must_wait("sleep 5 &", BUSY));
+
+        queue.push("sleep 5 &".to_string());
+        assert_eq!(queue.pop_ready(two_jobs), None);
+        assert_eq!(queue.pop_ready(BUSY).as_deref(), Some("sleep 5 &"));
+        assert_eq!(queue.clear(), 0);
+    }
+
+    #[test]
+    fn test_builtins_need_no_slot() {
+        let mut queue = CommandQueue::new();
+        queue.limit = 1;
+        let one_job = Load {
+            foreground: false,
+            running: 1,
+        };
+        assert!(!queue.must_wait("fg", one_job));
+        assert!(!queue.must_wait("jobs", one_job));
+        assert!(!queue.must_wait("exit", one_job));
+        assert!(queue.must_wait("sleep 5 &", one_job));
+        assert!(queue.must_wait("fg", BUSY));
+    }
+}
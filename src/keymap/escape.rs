@@ -0,0 +1,95 @@
+//! Double-Esc detection
+//!
+//! The first Esc does whatever the current mode uses it for (clearing the
+//! input, closing a popup, leaving an overlay). A second Esc within
+//! [`DOUBLE_ESC_WINDOW`] resets the screen to a clean Normal-mode state.
+
+use std::time::{Duration, Instant};
+
+/// Longest gap between two Esc presses that still counts as a double Esc
+pub const DOUBLE_ESC_WINDOW: Duration = Duration::from_millis(500);
+
+/// What an Esc press means given the presses before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapePress {
+    /// Handled by the current mode
+    Single,
+    /// Second press in a row: clear transient UI
+    Double,
+}
+
+/// State machine fed with every key press
+#[derive(Debug, Clone, Default)]
+pub struct EscapeState {
+    /// Time of the last Esc, if it was the most recent key
+    armed_at: Option<Instant>,
+}
+
+impl EscapeState {
+    /// Create a state with no pending Esc
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an Esc press at `now`
+    ///
+    /// A double press disarms the state, so a third Esc counts as single.
+    pub fn press(&mut self, now: Instant) -> EscapePress {
+        match self.armed_at.take() {
+            Some(at) if now.duration_since(at) <= DOUBLE_ESC_WINDOW => EscapePress::Double,
+            _ => {
+                self.armed_at = Some(now);
+                EscapePress::Single
+            }
+        }
+    }
+
+    /// Register any other key, which breaks an Esc sequence
+    pub fn reset(&mut self) {
+        self.armed_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_press_within_window() {
+        let mut state = EscapeState::new();
+        let start = Instant::now();
+        assert_eq!(state.press(start), EscapePress::Single);
+        assert_eq!(
+            state.press(start + Duration::from_millis(200)),
+            EscapePress::Double
+        );
+        // The sequence starts over after a double press
+        assert_eq!(
+            state.press(start + Duration::from_millis(300)),
+            EscapePress::Single
+        );
+    }
+
+    #[test]
+    fn test_slow_press_is_single() {
+        let mut state = EscapeState::new();
+        let start = Instant::now();
+        state.press(start);
+        assert_eq!(
+            state.press(start + DOUBLE_ESC_WINDOW + Duration::from_millis(1)),
+            EscapePress::Single
+        );
+    }
+
+    #[test]
+    fn test_other_key_breaks_sequence() {
+        let mut state = EscapeState::new();
+        let start = Instant::now();
+        state.press(start);
+        state.reset();
+        assert_eq!(
+            state.press(start + Duration::from_millis(100)),
+            EscapePress::Single
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Rebindable key chords for normal-mode actions
+//!
+//! Text editing keys (arrows, Backspace, Enter, typing) are fixed; the
+//! chords listed in [`Action`] can be rebound in the `[keybindings]` section
+//! of `config.toml`, e.g. `clear_screen = "ctrl+k"` or `timeline = "none"`.
+
+mod escape;
+mod quit;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+pub use escape::{EscapePress, EscapeState};
+pub use quit::{QuitGuard, QuitPress};
+
+/// A normal-mode action that can be bound to a key chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Interrupt the running command, clear the input, or quit when empty
+    Interrupt,
+    /// Quit when the input is empty
+    Quit,
+    /// Suspend the running command into the job table; at the prompt,
+    /// undo
+    Suspend,
+    /// Clear the output
+    ClearScreen,
+    /// Open the block timeline
+    Timeline,
+    /// Pick a shortcut from the sidebar
+    Goto,
+    /// Scroll the output up one page
+    ScrollUp,
+    /// Scroll the output down one page
+    ScrollDown,
+    /// Move the cursor to the start of the line
+    LineStart,
+    /// Move the cursor to the end of the line
+    LineEnd,
+    /// Clear the input line
+    ClearLine,
+    /// Move the cursor to the start of the word before it
+    WordLeft,
+    /// Move the cursor to the end of the word after it
+    WordRight,
+    /// Kill back to the previous whitespace
+    KillWordBack,
+    /// Kill to the end of the word after the cursor
+    KillWordForward,
+    /// Kill to the end of the line
+    KillToEnd,
+    /// Insert the most recent kill
+    Yank,
+    /// Right after a yank, replace it with the kill before
+    YankPop,
+    /// Undo the last change to the input line
+    Undo,
+    /// Reapply the last change undone
+    Redo,
+    /// Skip the current macro step during playback
+    SkipMacroStep,
+    /// Suspend jerm and open a plain shell in the current directory
+    Shell,
+    /// Expand or collapse a block's list of errors and warnings
+    ToggleErrors,
+    /// Expand or collapse the changed lines of a re-run block
+    ToggleDiff,
+    /// Show or hide the command synopsis under the input
+    ToggleSynopsis,
+    /// Refresh git status and run `git fetch` now
+    RefreshGit,
+    /// List the files left conflicted by a merge
+    Conflicts,
+    /// Show or hide the git status panel
+    GitStatus,
+    /// Switch between wrapping and truncating long output lines
+    ToggleWrap,
+    /// Show a block's output as a hex dump
+    HexView,
+    /// Start recording keys into a register, or stop recording
+    RecordKeys,
+    /// Replay the keys in a register
+    PlayKeys,
+    /// Select output lines with the keyboard to copy them
+    CopyMode,
+    /// Paste the system clipboard into the input line
+    Paste,
+    /// Search the output
+    Search,
+}
+
+impl Action {
+    /// Every action with its config name and default chord
+    pub const DEFAULTS: &'static [(Action, &'static str, &'static str)] = &[
+        (Action::Interrupt, "interrupt", "ctrl+c"),
+        (Action::Quit, "quit", "ctrl+d"),
+        (Action::Suspend, "suspend", "ctrl+z"),
+        (Action::ClearScreen, "clear_screen", "ctrl+l"),
+        (Action::Timeline, "timeline", "ctrl+t"),
+        (Action::Goto, "goto", "ctrl+g"),
+        (Action::ScrollUp, "scroll_up", "pageup"),
+        (Action::ScrollDown, "scroll_down", "pagedown"),
+        (Action::LineStart, "line_start", "ctrl+a"),
+        (Action::LineEnd, "line_end", "ctrl+e"),
+        (Action::ClearLine, "clear_line", "ctrl+u"),
+        (Action::WordLeft, "word_left", "alt+b"),
+        (Action::WordRight, "word_right", "alt+f"),
+        (Action::KillWordBack, "kill_word_back", "ctrl+w"),
+        (Action::KillWordForward, "kill_word_forward", "alt+d"),
+        (Action::KillToEnd, "kill_to_end", "ctrl+k"),
+        (Action::Yank, "yank", "ctrl+y"),
+        (Action::YankPop, "yank_pop", "alt+y"),
+        (Action::Undo, "undo", "ctrl+_"),
+        (Action::Redo, "redo", "alt+_"),
+        (Action::SkipMacroStep, "skip_macro_step", "ctrl+s"),
+        (Action::Shell, "shell", "alt+s"),
+        (Action::ToggleErrors, "toggle_errors", "ctrl+o"),
+        (Action::ToggleDiff, "toggle_diff", "f2"),
+        (Action::ToggleSynopsis, "toggle_synopsis", "f1"),
+        (Action::RefreshGit, "refresh_git", "f5"),
+        (Action::Conflicts, "conflicts", "f6"),
+        (Action::GitStatus, "git_status", "alt+g"),
+        (Action::ToggleWrap, "toggle_wrap", "alt+z"),
+        (Action::HexView, "hex_view", "f3"),
+        (Action::RecordKeys, "record_keys", "alt+q"),
+        (Action::PlayKeys, "play_keys", "alt+p"),
+        (Action::CopyMode, "copy_mode", "alt+c"),
+        (Action::Paste, "paste", "alt+v"),
+        (Action::Search, "search", "ctrl+f"),
+    ];
+
+    /// Whether the action adds to the kill ring
+    pub fn kills(self) -> bool {
+        matches!(
+            self,
+            Action::KillWordBack | Action::KillWordForward | Action::KillToEnd
+        )
+    }
+
+    /// Whether the action inserts from the kill ring
+    pub fn yanks(self) -> bool {
+        matches!(self, Action::Yank | Action::YankPop)
+    }
+
+    /// Look up an action by its config name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::DEFAULTS
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(action, _, _)| *action)
+    }
+}
+
+/// A key with modifiers, e.g. `ctrl+l`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Build a chord from a key event, normalizing letter case
+    ///
+    /// Terminals send Ctrl+_ as the control code that also stands for
+    /// Ctrl+7, so that's what it arrives as.
+    pub fn from_event(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char('7') if modifiers == KeyModifiers::CONTROL => KeyCode::Char('_'),
+            KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+            other => other,
+        };
+        Self { code, modifiers }
+    }
+
+    /// Parse a chord such as `ctrl+l`, `alt+shift+x`, `pageup`, or `f5`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = None;
+
+        for part in text.split('+').map(|p| p.trim().to_ascii_lowercase()) {
+            match part.as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                _ if key.is_none() => key = Some(parse_key(&part)?),
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            code: key?,
+            modifiers,
+        })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    /// Human-readable form such as `Ctrl+G`, for hints
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// Parse the key part of a chord
+fn parse_key(key: &str) -> Option<KeyCode> {
+    let code = match key {
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "insert" => KeyCode::Insert,
+        "delete" | "del" => KeyCode::Delete,
+        "space" => KeyCode::Char(' '),
+        _ => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                (Some('f'), Some(_)) => KeyCode::F(key[1..].parse().ok()?),
+                _ => return None,
+            }
+        }
+    };
+    Some(code)
+}
+
+/// Mapping from key chords to actions
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyChord, Action>,
+}
+
+impl Keymap {
+    /// Build the keymap from the defaults plus overrides (action name to
+    /// chord, or `none` to unbind)
+    ///
+    /// Returns the keymap and a warning for every override that was ignored.
+    pub fn new(overrides: &HashMap<String, String>) -> (Self, Vec<String>) {
+        let mut chords: HashMap<Action, Option<KeyChord>> = Action::DEFAULTS
+            .iter()
+            .map(|(action, _, chord)| (*action, KeyChord::parse(chord)))
+            .collect();
+        let mut warnings = Vec::new();
+        let mut overridden = Vec::new();
+
+        let mut names: Vec<&String> = overrides.keys().collect();
+        names.sort();
+        for name in names {
+            let chord = &overrides[name];
+            let Some(action) = Action::from_name(name) else {
+                warnings.push(format!("unknown keybinding action '{name}'"));
+                continue;
+            };
+            if chord.eq_ignore_ascii_case("none") {
+                chords.insert(action, None);
+            } else if let Some(parsed) = KeyChord::parse(chord) {
+                chords.insert(action, Some(parsed));
+                overridden.push(action);
+            } else {
+                warnings.push(format!("invalid key '{chord}' for {name}"));
+            }
+        }
+
+        // A chord taken by an override is no longer the default's
+        let (mut custom, defaults): (Vec<_>, Vec<_>) = chords
+            .into_iter()
+            .filter_map(|(action, chord)| Some((chord?, action)))
+            .partition(|(_, action)| overridden.contains(action));
+        custom.sort_by_key(|(_, action)| overridden.iter().position(|a| a == action));
+        let bindings = defaults.into_iter().chain(custom).collect();
+        (Self { bindings }, warnings)
+    }
+
+    /// Action bound to a key event, if any
+    pub fn action(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        self.bindings
+            .get(&KeyChord::from_event(code, modifiers))
+            .copied()
+    }
+
+    /// Chord bound to an action, if any
+    pub fn chord(&self, action: Action) -> Option<KeyChord> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| **bound == action)
+            .map(|(chord, _)| *chord)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&HashMap::new()).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chords() {
+        assert_eq!(
+            KeyChord::parse("ctrl+l"),
+            Some(KeyChord {
+                code: KeyCode::Char('l'),
+                modifiers: KeyModifiers::CONTROL
+            })
+        );
+        assert_eq!(
+            KeyChord::parse("Alt+Shift+X").map(|c| c.modifiers),
+            Some(KeyModifiers::ALT | KeyModifiers::SHIFT)
+        );
+        assert_eq!(KeyChord::parse("f5").map(|c| c.code), Some(KeyCode::F(5)));
+        assert_eq!(
+            KeyChord::parse("pageup").map(|c| c.code),
+            Some(KeyCode::PageUp)
+        );
+        assert_eq!(KeyChord::parse("ctrl+"), None);
+        assert_eq!(KeyChord::parse("ctrl+ab"), None);
+    }
+
+    #[test]
+    fn test_default_bindings() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.action(KeyCode::Char('l'), KeyModifiers::CONTROL),
+            Some(Action::ClearScreen)
+        );
+        assert_eq!(
+            keymap.action(KeyCode::PageUp, KeyModifiers::NONE),
+            Some(Action::ScrollUp)
+        );
+        assert_eq!(keymap.action(KeyCode::Char('l'), KeyModifiers::NONE), None);
+
+        // Without keyboard enhancements Ctrl+Shift arrives as plain Ctrl,
+        // so such a default could never be pressed
+        let chords: Vec<KeyChord> = Action::DEFAULTS
+            .iter()
+            .map(|(_, _, chord)| KeyChord::parse(chord).unwrap())
+            .collect();
+        let ctrl_shift = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+        assert!(chords.iter().all(|c| !c.modifiers.contains(ctrl_shift)));
+        for (i, chord) in chords.iter().enumerate() {
+            assert!(!chords[i + 1..].contains(chord), "{chord:?} is bound twice");
+        }
+    }
+
+    #[test]
+    fn test_overrides() {
+        let overrides: HashMap<String, String> = [
+            ("clear_screen", "ctrl+k"),
+            ("timeline", "none"),
+            ("bogus", "ctrl+b"),
+            ("quit", "ctrl+"),
+        ]
+        .iter()
+        .map(|(a, k)| ((*a).to_string(), (*k).to_string()))
+        .collect();
+
+        let (keymap, warnings) = Keymap::new(&overrides);
+        assert_eq!(
+            keymap.action(KeyCode::Char('k'), KeyModifiers::CONTROL),
+            Some(Action::ClearScreen)
+        );
+        // ...taking the chord from its default action
+        assert_eq!(keymap.chord(Action::KillToEnd), None);
+        assert_eq!(
+            keymap.action(KeyCode::Char('l'), KeyModifiers::CONTROL),
+            None
+        );
+        assert_eq!(
+            keymap.action(KeyCode::Char('t'), KeyModifiers::CONTROL),
+            None
+        );
+        // Invalid overrides keep the default binding
+        assert_eq!(
+            keymap.action(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            Some(Action::Quit)
+        );
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            keymap.chord(Action::ClearScreen).unwrap().to_string(),
+            "Ctrl+K"
+        );
+        assert_eq!(keymap.chord(Action::Timeline), None);
+    }
+}
@@ -0,0 +1,122 @@
+//! Protection against quitting by accident
+//!
+//! With `ui.quit_keys = "double"` a quit chord (Ctrl+D, or Ctrl+C on an
+//! empty line) only arms on its first press, and quits if the same chord
+//! comes again within [`DOUBLE_QUIT_WINDOW`]. With `"off"` the chords never
+//! quit and only `exit` does. Either way the armed chord is shown as a hint
+//! until the window passes.
+
+use std::time::{Duration, Instant};
+
+use super::Action;
+use crate::config::QuitKeys;
+
+/// Longest gap between two presses of a quit chord that still quits
+pub const DOUBLE_QUIT_WINDOW: Duration = Duration::from_millis(1000);
+
+/// What a quit chord press means given the setting and earlier presses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuitPress {
+    /// Quit now
+    Quit,
+    /// First press: waiting for the second
+    Armed,
+    /// The chords don't quit
+    Refused,
+}
+
+/// State machine fed with quit chord presses
+#[derive(Debug, Clone, Default)]
+pub struct QuitGuard {
+    /// The last quit chord's action and when it was pressed
+    armed: Option<(Action, Instant)>,
+}
+
+impl QuitGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a press of the chord bound to `action` at `now`
+    pub fn press(&mut self, keys: QuitKeys, action: Action, now: Instant) -> QuitPress {
+        let previous = self.armed.take();
+        match keys {
+            QuitKeys::Single => QuitPress::Quit,
+            QuitKeys::Off => {
+                self.armed = Some((action, now));
+                QuitPress::Refused
+            }
+            QuitKeys::Double => match previous {
+                Some((last, at))
+                    if last == action && now.duration_since(at) <= DOUBLE_QUIT_WINDOW =>
+                {
+                    QuitPress::Quit
+                }
+                _ => {
+                    self.armed = Some((action, now));
+                    QuitPress::Armed
+                }
+            },
+        }
+    }
+
+    /// The chord pressed last, while its hint should still show
+    pub fn armed(&self, now: Instant) -> Option<Action> {
+        self.armed
+            .filter(|(_, at)| now.duration_since(*at) <= DOUBLE_QUIT_WINDOW)
+            .map(|(action, _)| action)
+    }
+
+    /// Register any other key, which breaks a quit sequence
+    pub fn reset(&mut self) {
+        self.armed = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_press() {
+        let mut guard = QuitGuard::new();
+        let start = Instant::now();
+        let later = start + Duration::from_millis(300);
+        assert_eq!(
+            guard.press(QuitKeys::Double, Action::Quit, start),
+            QuitPress::Armed
+        );
+        assert_eq!(guard.armed(later), Some(Action::Quit));
+        // Ctrl+C after Ctrl+D is a new sequence, not the second press
+        assert_eq!(
+            guard.press(QuitKeys::Double, Action::Interrupt, later),
+            QuitPress::Armed
+        );
+        assert_eq!(
+            guard.press(QuitKeys::Double, Action::Interrupt, later),
+            QuitPress::Quit
+        );
+
+        let slow = later + DOUBLE_QUIT_WINDOW + Duration::from_millis(1);
+        guard.press(QuitKeys::Double, Action::Quit, later);
+        assert_eq!(guard.armed(slow), None);
+        assert_eq!(
+            guard.press(QuitKeys::Double, Action::Quit, slow),
+            QuitPress::Armed
+        );
+
+        guard.reset();
+        assert_eq!(
+            guard.press(QuitKeys::Off, Action::Quit, start),
+            QuitPress::Refused
+        );
+        assert_eq!(
+            guard.press(QuitKeys::Off, Action::Quit, start),
+            QuitPress::Refused
+        );
+        assert_eq!(
+            guard.press(QuitKeys::Single, Action::Quit, start),
+            QuitPress::Quit
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! jerm as a library
+//!
+//! The `jerm` binary is a thin terminal front end over this crate. Tools
+//! that want jerm's directory jumping or shortcut handling without its UI
+//! can embed the same pieces:
+//!
+//! - [`app::App`]: the whole session state, drivable without a terminal
+//!   (edit the input line, [`change_dir`](app::App::change_dir), jump to
+//!   shortcuts, read [`output`](app::App::output)); running the typed
+//!   command is left to the front end
+//! - [`shell::parser`]: splitting an input line into a [`ParsedCommand`](shell::parser::ParsedCommand)
+//! - [`shell::executor`]: running commands and resolving `cd` targets
+//! - [`shortcuts`]: the saved directory shortcuts and visit tracking
+//! - [`navigation`]: the directory browser behind the `jerm` prompt
+//!
+//! Settings and state are read from the active profile's directories (see
+//! [`paths`]); set `JERM_DATA_DIR` to keep an embedding or a test away from
+//! the user's own.
+//!
+//! ```
+//! use jerm::shell::parser::{parse_command, ParsedCommand};
+//!
+//! assert_eq!(
+//!     parse_command("cd ~/src"),
+//!     ParsedCommand::Cd(Some("~/src".to_string()))
+//! );
+//! ```
+//!
+//! The remaining modules are public so the binary can reach them, but they
+//! are shaped by its needs and may change without notice.
+
+pub mod ansi;
+pub mod app;
+pub mod blocks;
+#[doc(hidden)]
+pub mod cli;
+pub mod clipboard;
+pub mod completion;
+pub mod config;
+pub mod crash;
+#[doc(hidden)]
+pub mod doctor;
+pub mod events;
+pub mod fuzzy;
+pub mod git;
+pub mod highlight;
+pub mod history;
+pub mod jobs;
+pub mod keymap;
+pub mod macros;
+pub mod messages;
+pub mod navigation;
+pub mod notes;
+pub mod paths;
+pub mod recording;
+pub mod runner;
+pub mod schema;
+pub mod segment;
+pub mod session;
+pub mod shell;
+pub mod shortcuts;
+pub mod snippets;
+pub mod state;
+pub mod synopsis;
+#[cfg(test)]
+mod testutil;
+pub mod theme;
+#[doc(hidden)]
+pub mod ui;
@@ -0,0 +1,171 @@
+//! Keystroke macros, like vim's `q` and `@`
+//!
+//! `Alt+Q` and a letter start recording every key into that register, in
+//! whatever mode they land, until `Alt+Q` again. `Alt+P`, an optional count,
+//! and a letter replay the register that many times. Registers last for
+//! the session; `jerm record` is the way to keep commands for later.
+//!
+//! Keys sent to an interactive program are not recorded: the program owns
+//! the keyboard in PTY mode.
+
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Most times a register can be replayed in one go
+const MAX_COUNT: usize = 999;
+
+/// Waiting for the register letter (and, for replay, the count)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Record,
+    Play(usize),
+}
+
+/// What the caller should do with a key after the macros have seen it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Feed {
+    /// Handle the key as usual
+    Pass,
+    /// The key picked a register or count; nothing else to do
+    Consumed,
+    /// Handle these keys in order, in place of the one pressed
+    Replay(Vec<KeyEvent>),
+}
+
+/// Registers of recorded keys, and the recording in progress
+#[derive(Debug, Default)]
+pub struct KeyMacros {
+    registers: HashMap<char, Vec<KeyEvent>>,
+    recording: Option<(char, Vec<KeyEvent>)>,
+    pending: Option<Pending>,
+}
+
+impl KeyMacros {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask for a register to record into, or stop the recording in progress
+    ///
+    /// Returns the register and number of keys when a recording stops.
+    pub fn toggle_record(&mut self) -> Option<(char, usize)> {
+        match self.recording.take() {
+            Some((register, keys)) => {
+                let count = keys.len();
+                self.registers.insert(register, keys);
+                Some((register, count))
+            }
+            None => {
+                self.pending = Some(Pending::Record);
+                None
+            }
+        }
+    }
+
+    /// Ask for a count and a register to replay
+    pub fn start_play(&mut self) {
+        self.pending = Some(Pending::Play(0));
+    }
+
+    /// Register being recorded into, if any
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    /// Indicator for the title: what's being recorded or asked for
+    pub fn status(&self) -> Option<String> {
+        match (self.pending, self.recording()) {
+            (Some(Pending::Record), _) => Some("record @?".to_string()),
+            (Some(Pending::Play(0)), _) => Some("play @?".to_string()),
+            (Some(Pending::Play(count)), _) => Some(format!("play {count}@?")),
+            (None, Some(register)) => Some(format!("recording @{register}")),
+            (None, None) => None,
+        }
+    }
+
+    /// Look at a key before it's handled
+    pub fn feed(&mut self, key: KeyEvent) -> Feed {
+        let Some(pending) = self.pending.take() else {
+            if let Some((_, ref mut keys)) = self.recording {
+                keys.push(key);
+            }
+            return Feed::Pass;
+        };
+
+        let letter = match key.code {
+            KeyCode::Char(c) if c.is_ascii_lowercase() && !has_modifiers(key) => Some(c),
+            _ => None,
+        };
+        match (pending, key.code, letter) {
+            (Pending::Record, _, Some(register)) => {
+                self.recording = Some((register, Vec::new()));
+            }
+            (Pending::Play(count), KeyCode::Char(digit @ '0'..='9'), _) if !has_modifiers(key) => {
+                let digit = digit.to_digit(10).unwrap_or(0) as usize;
+                self.pending = Some(Pending::Play((count * 10 + digit).min(MAX_COUNT)));
+            }
+            (Pending::Play(count), _, Some(register)) => {
+                let keys = self.registers.get(&register).cloned().unwrap_or_default();
+                let keys: Vec<KeyEvent> = std::iter::repeat(keys)
+                    .take(count.max(1))
+                    .flatten()
+                    .collect();
+                // A replay inside a recording is recorded as the keys it played
+                if let Some((_, ref mut recorded)) = self.recording {
+                    recorded.extend(keys.iter().copied());
+                }
+                return Feed::Replay(keys);
+            }
+            // Anything else (Esc included) cancels
+            _ => {}
+        }
+        Feed::Consumed
+    }
+}
+
+fn has_modifiers(key: KeyEvent) -> bool {
+    !(key.modifiers - KeyModifiers::SHIFT).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut macros = KeyMacros::new();
+        assert_eq!(macros.toggle_record(), None);
+        assert_eq!(macros.feed(key('a')), Feed::Consumed);
+        assert_eq!(macros.status().as_deref(), Some("recording @a"));
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(macros.feed(down), Feed::Pass);
+        assert_eq!(macros.feed(key('x')), Feed::Pass);
+        assert_eq!(macros.toggle_record(), Some(('a', 2)));
+        assert_eq!(macros.feed(key('y')), Feed::Pass);
+
+        macros.start_play();
+        assert_eq!(macros.feed(key('1')), Feed::Consumed);
+        assert_eq!(macros.feed(key('2')), Feed::Consumed);
+        assert_eq!(macros.status().as_deref(), Some("play 12@?"));
+        match macros.feed(key('a')) {
+            Feed::Replay(keys) => {
+                assert_eq!(keys.len(), 24);
+                assert_eq!(keys[..2], [down, key('x')]);
+            }
+            other => panic!("expected a replay, got {other:?}"),
+        }
+
+        // An empty register replays nothing, and Esc cancels
+        macros.start_play();
+        assert_eq!(macros.feed(key('z')), Feed::Replay(Vec::new()));
+        macros.toggle_record();
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(macros.feed(esc), Feed::Consumed);
+        assert_eq!(macros.recording(), None);
+    }
+}
@@ -0,0 +1,10 @@
+//! Recorded command macros (`jerm record` / `jerm play`), and keystroke
+//! macros kept in registers for the session
+
+pub mod keys;
+pub mod playback;
+pub mod storage;
+
+pub use keys::{Feed, KeyMacros};
+pub use playback::{Playback, PlaybackPrompt};
+pub use storage::Macro;
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use super::storage::Macro;
+
+/// What playback needs from the user next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaybackPrompt {
+    /// A value for the named `{parameter}`
+    Parameter(String),
+    /// Confirmation to run the given command
+    Step(String),
+}
+
+/// State for replaying a macro one confirmed step at a time
+#[derive(Debug, Clone)]
+pub struct Playback {
+    /// The macro being played
+    pub recorded: Macro,
+    /// Index of the current step
+    pub index: usize,
+    /// Parameter values entered so far (reused by later steps)
+    params: HashMap<String, String>,
+}
+
+impl Playback {
+    /// Start playing a macro from its first step
+    pub fn new(recorded: Macro) -> Self {
+        Self {
+            recorded,
+            index: 0,
+            params: HashMap::new(),
+        }
+    }
+
+    /// Get what the current step needs, or `None` when playback is finished
+    pub fn prompt(&self) -> Option<PlaybackPrompt> {
+        let step = self.recorded.steps.get(self.index)?;
+
+        if let Some(missing) = placeholders(step)
+            .into_iter()
+            .find(|name| !self.params.contains_key(name))
+        {
+            return Some(PlaybackPrompt::Parameter(missing));
+        }
+
+        Some(PlaybackPrompt::Step(substitute(step, &self.params)))
+    }
+
+    /// Record the value of a parameter
+    pub fn set_param(&mut self, name: &str, value: &str) {
+        self.params.insert(name.to_string(), value.to_string());
+    }
+
+    /// Move on to the next step
+    pub fn advance(&mut self) {
+        self.index += 1;
+    }
+
+    /// Short progress label such as `deploy 2/5`
+    pub fn progress(&self) -> String {
+        format!(
+            "{} {}/{}",
+            self.recorded.name,
+            (self.index + 1).min(self.recorded.steps.len()),
+            self.recorded.steps.len()
+        )
+    }
+}
+
+/// Find `{name}` placeholders in a command, in order of first appearance
+///
+/// Shell expansions like `${HOME}` and `{}` are left alone.
+pub fn placeholders(command: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = command;
+
+    while let Some(start) = rest.find('{') {
+        let escaped = rest[..start].ends_with('$');
+        let after = &rest[start + 1..];
+
+        if let Some(end) = after.find('}') {
+            let name = &after[..end];
+            if !escaped && is_placeholder_name(name) && !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+
+        rest = after;
+    }
+
+    names
+}
+
+/// Replace `{name}` placeholders with their values
+fn substitute(command: &str, params: &HashMap<String, String>) -> String {
+    let mut result = command.to_string();
+    for name in placeholders(command) {
+        if let Some(value) = params.get(&name) {
+            result = result.replace(&format!("{{{name}}}"), value);
+        }
+    }
+    result
+}
+
+/// Placeholder names are identifiers: letters, digits, `_` and `-`
+pub fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(steps: &[&str]) -> Macro {
+        let mut m = Macro::new("test");
+        m.steps = steps.iter().map(|s| (*s).to_string()).collect();
+        m
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(
+            placeholders("docker logs -f {container}"),
+            vec!["container"]
+        );
+        assert!(placeholders("echo ${HOME}").is_empty());
+        assert!(placeholders(r"find . -exec rm {} \;").is_empty());
+        assert!(placeholders("awk '{print $1}'").is_empty());
+    }
+
+    #[test]
+    fn test_playback_prompts_for_params() {
+        let mut playback = Playback::new(recorded(&["ssh {host}", "echo {host}"]));
+        assert_eq!(
+            playback.prompt(),
+            Some(PlaybackPrompt::Parameter("host".to_string()))
+        );
+
+        playback.set_param("host", "prod");
+        assert_eq!(
+            playback.prompt(),
+            Some(PlaybackPrompt::Step("ssh prod".to_string()))
+        );
+
+        // Parameter values carry over to later steps
+        playback.advance();
+        assert_eq!(
+            playback.prompt(),
+            Some(PlaybackPrompt::Step("echo prod".to_string()))
+        );
+
+        playback.advance();
+        assert_eq!(playback.prompt(), None);
+    }
+
+    #[test]
+    fn test_progress() {
+        let playback = Playback::new(recorded(&["ls", "pwd"]));
+        assert_eq!(playback.progress(), "test 1/2");
+    }
+}
@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors that can occur while reading or writing macros
+#[derive(Error, Debug)]
+pub enum MacroError {
+    #[error("Failed to access macro file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse macro file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Config directory not found")]
+    ConfigDirNotFound,
+
+    #[error("Invalid macro name: {0}")]
+    InvalidName(String),
+
+    #[error("No macro named {0}")]
+    NotFound(String),
+}
+
+/// A named sequence of recorded commands
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Macro {
+    /// Name the macro is stored and played under
+    pub name: String,
+    /// Commands in the order they were run
+    pub steps: Vec<String>,
+    /// When the macro was recorded
+    pub created_at: DateTime<Utc>,
+}
+
+impl Macro {
+    /// Create an empty macro with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            steps: Vec::new(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Get the directory macros are stored in
+pub fn get_macros_dir() -> Result<PathBuf, MacroError> {
+    let config_dir = crate::paths::config_dir().ok_or(MacroError::ConfigDirNotFound)?;
+    Ok(config_dir.join("macros"))
+}
+
+/// Get the file path for a macro name
+fn macro_path(name: &str) -> Result<PathBuf, MacroError> {
+    if !crate::paths::is_valid_name(name) {
+        return Err(MacroError::InvalidName(name.to_string()));
+    }
+    Ok(get_macros_dir()?.join(format!("{name}.json")))
+}
+
+/// Load a macro by name
+pub fn load_macro(name: &str) -> Result<Macro, MacroError> {
+    let path = macro_path(name)?;
+    if !path.exists() {
+        return Err(MacroError::NotFound(name.to_string()));
+    }
+    let contents = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Save a macro, replacing any existing macro with the same name
+pub fn save_macro(recorded: &Macro) -> Result<(), MacroError> {
+    let path = macro_path(&recorded.name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(recorded)?)?;
+    Ok(())
+}
+
+/// List the names of all saved macros, sorted alphabetically
+pub fn list_macros() -> Result<Vec<String>, MacroError> {
+    let dir = get_macros_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                path.file_stem().map(|s| s.to_string_lossy().to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macro_serialization() {
+        let mut recorded = Macro::new("deploy");
+        recorded.steps.push("git pull".to_string());
+
+        let json = serde_json::to_string(&recorded).unwrap();
+        let parsed: Macro = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, recorded);
+    }
+}
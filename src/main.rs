@@ -1,188 +1,688 @@
-mod app;
-mod git;
-mod highlight;
-mod navigation;
-mod shell;
-mod shortcuts;
-mod theme;
-mod ui;
+#[cfg(test)]
+mod harness;
 
 use std::io;
-use std::time::Duration;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        self as term, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     Terminal,
 };
 
-use app::{App, AppMode};
-use shell::{
-    executor::{execute_command, resolve_cd_path},
-    parser::{parse_command, ParsedCommand},
+use jerm::app::{App, AppMode};
+use jerm::config::OutputWrap;
+use jerm::events::{AppEvent, InputReader, Ticker};
+use jerm::keymap::{Action, EscapePress};
+use jerm::macros::{Feed, Playback, PlaybackPrompt};
+use jerm::navigation::PromptAction;
+use jerm::recording::{CastRecorder, RecordingWriter};
+use jerm::session::{ExitSummary, SessionRecord};
+use jerm::shell::{
+    edit::EditKind,
+    executor::{auto_cd_target, resolve_cd_path},
+    parser::{count_commands, parse_command, AliasAction, ParsedCommand, SnippetAction},
+    pty::wants_pty,
+    retry::RetrySpec,
 };
-use ui::{render_navigator, render_sidebar, render_terminal};
+use jerm::ui::{
+    highlight_frame, render_branch_switcher, render_confirm_script, render_conflicts,
+    render_copy_mode, render_diffdir, render_env, render_git_overview, render_git_status,
+    render_heatmap, render_hex_view, render_lock, render_navigator, render_note, render_pty,
+    render_sidebar, render_terminal, render_tests, render_theme_gallery, render_timeline,
+};
+use jerm::{cli, clipboard, config, crash, doctor, macros, paths, shell, snippets, theme};
 
 fn main() -> io::Result<()> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("jerm: {err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
+    // Select the profile before anything is loaded from disk
+    let profile = args
+        .profile
+        .clone()
+        .or_else(|| std::env::var("JERM_PROFILE").ok())
+        .filter(|name| !name.is_empty());
+    if let Some(ref name) = profile {
+        if !paths::is_valid_name(name) {
+            eprintln!("jerm: invalid profile name: {name}");
+            std::process::exit(2);
+        }
+    }
+    paths::set_active_profile(profile.as_deref());
+
+    // Subcommands print their output and exit without starting the UI
+    if let Some(command) = args.command {
+        let result: Result<(), Box<dyn std::error::Error>> = match command {
+            cli::CliCommand::Completions(shell) => {
+                print!("{}", shell.script());
+                Ok(())
+            }
+            cli::CliCommand::List => cli::report::print_list(args.json).map_err(Into::into),
+            cli::CliCommand::Stats => cli::report::print_stats(args.json).map_err(Into::into),
+            cli::CliCommand::ExportState(path) => {
+                cli::state::export(&path, args.scrub_history).map_err(Into::into)
+            }
+            cli::CliCommand::ImportState(path) => cli::state::import(&path).map_err(Into::into),
+            cli::CliCommand::Exec { shortcut, command } => {
+                match cli::exec::run(shortcut.as_deref(), &command) {
+                    Ok(code) => std::process::exit(code),
+                    Err(err) => Err(err.into()),
+                }
+            }
+            cli::CliCommand::Doctor => {
+                let (config, _) = config::Config::load();
+                let checks = doctor::run(&config);
+                for line in checks.iter().flat_map(doctor::Check::lines) {
+                    println!("{line}");
+                }
+                if doctor::problems(&checks) > 0 {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+        };
+        if let Err(err) = result {
+            eprintln!("jerm: {err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Open the session recording before touching the terminal
+    let recorder = match args.record {
+        Some(ref path) => {
+            let (width, height) = term::size()?;
+            match CastRecorder::create(path, width, height) {
+                Ok(recorder) => Some(recorder),
+                Err(err) => {
+                    eprintln!("jerm: cannot record to {}: {err}", path.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Setup terminal; the guard and the panic hook put it back however
+    // jerm exits
+    crash::install_panic_hook();
+    let guard = crash::TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(RecordingWriter::new(io::stdout(), recorder));
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
     let mut app = App::new();
+    app.set_readonly(args.readonly);
+    app.greet();
+
+    // Only mention problems at startup; `jerm doctor` explains them
+    let problems = doctor::problems(&doctor::run(&app.config));
+    if problems > 0 {
+        let noun = if problems == 1 { "problem" } else { "problems" };
+        app.add_output(&format!(
+            "jerm: {problems} setup {noun} found; run `jerm doctor` for fixes"
+        ));
+    }
 
     // Run main loop
-    let result = run_app(&mut terminal, &mut app);
+    let input = InputReader::spawn(app.events.sender());
+    let started_at = chrono::Utc::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        run_app(&mut terminal, &mut app, &input)
+    }));
+    input.pause();
 
     // Restore terminal
+    drop(guard);
+
+    // The next greeting reports how long this session lasted
+    let ended_at = chrono::Utc::now();
+    if !app.readonly && !app.in_memory {
+        let _ = SessionRecord {
+            started_at,
+            ended_at,
+        }
+        .save();
+    }
+
+    // Now on the normal screen, say what the session did
+    let killed = app.stop_processes();
+    if result.is_ok() && app.config.ui.exit_summary && !args.no_summary {
+        let summary = ExitSummary {
+            duration: (ended_at - started_at).to_std().unwrap_or_default(),
+            commands: app.blocks.len(),
+            dirs: app.visited_dirs.len(),
+            killed,
+        };
+        for line in summary.lines() {
+            println!("{line}");
+        }
+    }
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => eprintln!("Error: {}", err),
+        Err(payload) => {
+            // The hook already printed the panic; keep the session's output too
+            match crash::write_report(payload.as_ref(), &app.output) {
+                Ok(path) => eprintln!("jerm: crash report written to {}", path.display()),
+                Err(err) => eprintln!("jerm: could not write a crash report: {err}"),
+            }
+            std::process::exit(101);
+        }
+    }
+
+    Ok(())
+}
+
+/// Terminal type used by jerm: crossterm on stdout, optionally recorded
+type JermTerminal = Terminal<CrosstermBackend<RecordingWriter<io::Stdout>>>;
+
+/// Longest time between redraws when nothing changes, so running
+/// times and relative timestamps stay current
+const REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the timers (git poll, retries, idle lock, redraw) are checked
+const TICK: Duration = Duration::from_millis(100);
+
+/// Shortest time between redraws for streaming output, so a flood of
+/// lines doesn't redraw once per line
+const OUTPUT_FRAME: Duration = Duration::from_millis(16);
+
+/// Columns truncated output moves per Left/Right press
+const HSCROLL_STEP: isize = 8;
+
+fn run_app(terminal: &mut JermTerminal, app: &mut App, input: &InputReader) -> io::Result<()> {
+    let ticker = Ticker::spawn(app.events.sender(), TICK);
+    let mut needs_redraw = true;
+    // Output shown since the last draw, drawn at most once per frame
+    let mut output_pending = false;
+    let mut last_draw = Instant::now();
+    loop {
+        // Check sources that don't send events yet; `|` rather than `||` so
+        // every source is drained after each event
+        let changed = app.poll_running()
+            | app.poll_jobs()
+            | app.poll_tests()
+            | app.poll_workers()
+            | app.poll_pty()
+            | app.poll_synopsis()
+            | app.navigation_state.poll_search()
+            | app.poll_hooks()
+            | app.poll_status_segment();
+        app.poll_input_stats();
+        let queued = app.queue.len();
+        run_queued(app);
+        let changed = changed || app.queue.len() != queued;
+
+        if changed || needs_redraw || (output_pending && last_draw.elapsed() >= OUTPUT_FRAME) {
+            terminal.draw(|f| draw_ui(f, app))?;
+            needs_redraw = false;
+            output_pending = false;
+            last_draw = Instant::now();
+        }
+
+        match app.events.wait() {
+            AppEvent::Output { pid, stream, line } => {
+                output_pending |= app.command_output(pid, stream, &line);
+            }
+            AppEvent::OutputClosed { pid } => app.command_output_closed(pid),
+            AppEvent::PtyOutput(bytes) => output_pending |= app.pty_output(&bytes),
+            AppEvent::PtyClosed => app.pty_output_closed(),
+            AppEvent::Tick => {
+                ticker.ack();
+                app.poll_git_timer();
+                needs_redraw |= app.poll_retry() | app.check_idle();
+                // Output that came in under a frame ago, and a steady
+                // heartbeat for elapsed times
+                needs_redraw |= output_pending || last_draw.elapsed() >= REDRAW_INTERVAL;
+            }
+            AppEvent::Git(message) => {
+                app.handle_git_message(message);
+                needs_redraw = true;
+            }
+            AppEvent::FilesChanged => {
+                if let Some(ref mut tests) = app.tests {
+                    tests.files_changed();
+                }
+            }
+            AppEvent::InputFailed(error) => {
+                // Without keys the session could never be quit
+                return Err(io::Error::other(format!(
+                    "can't read the terminal: {error}"
+                )));
+            }
+            AppEvent::Input(event) => {
+                // Keys, pastes, and resizes all change what's on screen
+                needs_redraw = true;
+                if matches!(event, Event::Key(_) | Event::Paste(_)) {
+                    app.touch();
+                    if app.locked {
+                        // The input that unlocks the screen is not passed on
+                        app.locked = false;
+                        continue;
+                    }
+                }
+                match event {
+                    Event::Key(key) => handle_key(app, key),
+                    Event::Paste(text) => {
+                        app.escape.reset();
+                        handle_paste(app, &text);
+                    }
+                    Event::FocusGained => app.focus_gained(),
+                    Event::Mouse(mouse) if !app.locked => handle_mouse(app, mouse),
+                    _ => {}
+                }
+            }
+        }
+
+        if app.shell_requested {
+            app.shell_requested = false;
+            input.pause();
+            let result = run_external_shell(terminal, app);
+            input.resume();
+            result?;
+            needs_redraw = true;
+        }
+
+        if app.should_quit {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Leave the UI, run `$SHELL` in the current directory until it exits, then
+/// restore the UI with the app state untouched
+fn run_external_shell(terminal: &mut JermTerminal, app: &mut App) -> io::Result<()> {
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        DisableFocusChange
     )?;
     terminal.show_cursor()?;
 
-    if let Err(err) = result {
-        eprintln!("Error: {}", err);
-    }
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    println!("jerm: starting {shell}; exit it to return");
+    let status = std::process::Command::new(&shell)
+        .current_dir(&app.current_dir)
+        .status();
 
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste,
+        EnableFocusChange
+    )?;
+    terminal.clear()?;
+    // Time spent in the shell doesn't count as idle
+    app.touch();
+
+    match status {
+        Ok(status) => app.add_output(&format!(
+            "jerm shell: {shell} exited ({})",
+            status
+                .code()
+                .map_or("signal".to_string(), |c| c.to_string())
+        )),
+        Err(err) => app.add_output(&format!("jerm shell: cannot start {shell}: {err}")),
+    }
     Ok(())
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> io::Result<()> {
-    loop {
-        // Poll git updates
-        app.poll_git_updates();
-
-        terminal.draw(|f| draw_ui(f, app))?;
-
-        // Non-blocking poll with 100ms timeout
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match app.mode {
-                    AppMode::Normal => handle_normal_mode(app, key.code, key.modifiers),
-                    AppMode::NavigationList => handle_navigation_mode(app, key.code),
-                    AppMode::ShortcutSelection => handle_goto_mode(app, key.code),
+/// Handle a key press: keystroke macros see it first, then the mode
+fn handle_key(app: &mut App, key: KeyEvent) {
+    // Interactive programs get every key, macro chords included
+    if app.mode != AppMode::Pty {
+        match app.keymap.action(key.code, key.modifiers) {
+            Some(Action::RecordKeys) => {
+                if let Some((register, count)) = app.key_macros.toggle_record() {
+                    app.add_output(&format!("jerm: recorded {count} keys into @{register}"));
+                }
+                return;
+            }
+            Some(Action::PlayKeys) => {
+                app.key_macros.start_play();
+                return;
+            }
+            _ => {}
+        }
+        match app.key_macros.feed(key) {
+            Feed::Pass => {}
+            Feed::Consumed => return,
+            // Replayed keys are each a single press, even two Escs in a row
+            Feed::Replay(keys) => {
+                for key in keys {
+                    app.escape.reset();
+                    dispatch_key(app, key);
                 }
+                return;
             }
         }
+    }
 
-        if app.should_quit {
-            break;
+    // Quit chords have to come back to back to count as a double press
+    let action = app.keymap.action(key.code, key.modifiers);
+    if !matches!(action, Some(Action::Quit | Action::Interrupt)) {
+        app.quit_guard.reset();
+    }
+
+    // Every mode but PTY (where Esc belongs to the program) treats a
+    // quick second Esc as "back to a clean screen"
+    if key.code == KeyCode::Esc && app.mode != AppMode::Pty {
+        match app.escape.press(Instant::now()) {
+            EscapePress::Double => app.reset_view(),
+            EscapePress::Single => dispatch_key(app, key),
         }
+    } else {
+        app.escape.reset();
+        dispatch_key(app, key);
     }
+}
 
-    Ok(())
+/// How a Normal-mode key counts for undo; `None` for undo and redo
+/// themselves
+fn edit_kind(app: &App, key: KeyEvent) -> Option<EditKind> {
+    match app.keymap.action(key.code, key.modifiers) {
+        Some(Action::Undo | Action::Redo) => return None,
+        Some(Action::Suspend) if app.running.is_none() => return None,
+        _ => {}
+    }
+    let typing = !key
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    Some(match key.code {
+        KeyCode::Char(_) if typing => EditKind::Insert,
+        KeyCode::Backspace | KeyCode::Delete => EditKind::Delete,
+        KeyCode::Enter => EditKind::Submit,
+        _ => EditKind::Other,
+    })
+}
+
+/// Send a key press to the current mode's handler
+fn dispatch_key(app: &mut App, key: KeyEvent) {
+    match app.mode {
+        AppMode::Normal => {
+            let before = app.input_state();
+            handle_normal_mode(app, key.code, key.modifiers);
+            if let Some(kind) = edit_kind(app, key) {
+                app.record_edit(before, kind);
+            }
+            app.update_suggestion();
+        }
+        AppMode::NavigationList => handle_navigation_mode(app, key.code),
+        AppMode::DirDiff => handle_diffdir_mode(app, key.code),
+        AppMode::Note => handle_note_mode(app, key),
+        AppMode::Heatmap => handle_heatmap_mode(app, key.code),
+        AppMode::ThemeGallery => handle_theme_gallery_mode(app, key.code),
+        AppMode::Conflicts => handle_conflicts_mode(app, key.code),
+        AppMode::GitOverview => handle_git_overview_mode(app, key.code),
+        AppMode::GitStatus => handle_git_status_mode(app, key),
+        AppMode::BranchSwitch => handle_branch_switch_mode(app, key.code),
+        AppMode::HexView => handle_hex_view_mode(app, key.code),
+        AppMode::Env => handle_env_mode(app, key.code),
+        AppMode::Copy => handle_copy_mode(app, key.code),
+        AppMode::Tests => handle_tests_mode(app, key.code),
+        AppMode::Search => handle_search_mode(app, key),
+        AppMode::ShortcutSelection => handle_goto_mode(app, key),
+        AppMode::Pty => handle_pty_mode(app, key),
+        AppMode::Timeline => handle_timeline_mode(app, key),
+        AppMode::ConfirmScript => handle_confirm_mode(app, key.code),
+    }
 }
 
 fn draw_ui(f: &mut ratatui::Frame, app: &mut App) {
     let size = f.size();
 
+    if app.locked {
+        app.screen.divider = Rect::default();
+        render_lock(f, size, &app.theme);
+        return;
+    }
+
     // Main layout: sidebar on left, terminal on right
     let main_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(25), // Sidebar (fixed width)
-            Constraint::Min(40),    // Terminal (flexible)
+            Constraint::Length(app.sidebar_width(size.width)), // Sidebar (share of the width)
+            Constraint::Min(40),                               // Terminal (flexible)
         ])
         .split(size);
-
-    // Always render sidebar first (left side), passing selection info if in goto mode
-    let selected_index = if app.mode == AppMode::ShortcutSelection {
-        Some(app.selected_shortcut_index)
-    } else {
-        None
+    app.screen.divider = Rect {
+        x: main_chunks[0].right().saturating_sub(1),
+        width: 2,
+        ..main_chunks[0]
     };
-    render_sidebar(f, main_chunks[0], &app.shortcuts, selected_index);
+
+    // Always render sidebar first (left side)
+    render_sidebar(f, main_chunks[0], app);
+
+    // Keep the PTY sized to the terminal pane's content area (inside borders)
+    let terminal_area = main_chunks[1];
+    app.pane_size = (
+        terminal_area.height.saturating_sub(2),
+        terminal_area.width.saturating_sub(2),
+    );
+    if let Some(ref mut session) = app.pty {
+        session.resize(app.pane_size.0, app.pane_size.1);
+    }
 
     // Render terminal/navigator based on mode (right side)
     match app.mode {
-        AppMode::Normal => {
+        AppMode::Normal | AppMode::Search => {
             render_terminal(f, main_chunks[1], app);
         }
         AppMode::NavigationList => {
             // In navigation mode, show navigator in the terminal area
-            render_navigator(f, main_chunks[1], &mut app.navigation_state);
+            render_navigator(f, main_chunks[1], &mut app.navigation_state, &app.theme);
+        }
+        AppMode::DirDiff => match app.dir_diff {
+            Some(ref mut diff) => render_diffdir(f, main_chunks[1], diff, &app.theme),
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::Heatmap => match app.heatmap {
+            Some(ref mut heatmap) => render_heatmap(f, main_chunks[1], heatmap, &app.theme),
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::ThemeGallery => match app.theme_gallery {
+            Some(ref mut gallery) => {
+                render_theme_gallery(f, main_chunks[1], gallery, &app.theme.name, &app.theme)
+            }
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::BranchSwitch => {
+            render_terminal(f, main_chunks[1], app);
+            if let Some(ref switcher) = app.branch_switcher {
+                render_branch_switcher(f, main_chunks[1], switcher, &app.theme);
+            }
+        }
+        AppMode::GitStatus => match app.git_panel {
+            Some(ref mut panel) => render_git_status(f, main_chunks[1], panel, &app.theme),
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::HexView => match app.hex_view {
+            Some(ref mut view) => render_hex_view(f, main_chunks[1], view, &app.theme),
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::Env => match app.env_panel {
+            Some(ref mut panel) => render_env(f, main_chunks[1], panel, &app.theme),
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::Copy => match app.copy_mode {
+            Some(ref mut copy) => {
+                render_copy_mode(f, main_chunks[1], copy, &app.output, &app.theme)
+            }
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::Tests => match app.tests {
+            Some(ref mut tests) => render_tests(f, main_chunks[1], tests, &app.theme),
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::GitOverview => match app.git_overview {
+            Some(ref overview) => {
+                render_git_overview(f, main_chunks[1], overview, &app.messages.time, &app.theme)
+            }
+            None => render_terminal(f, main_chunks[1], app),
+        },
+        AppMode::Conflicts => {
+            render_terminal(f, main_chunks[1], app);
+            if let Some(ref conflicts) = app.conflicts {
+                render_conflicts(f, main_chunks[1], conflicts, app.readonly, &app.theme);
+            }
+        }
+        AppMode::Note => {
+            render_terminal(f, main_chunks[1], app);
+            if let Some(ref mut pad) = app.note {
+                render_note(f, main_chunks[1], pad, &app.theme);
+            }
         }
         AppMode::ShortcutSelection => {
             // In goto mode, still show terminal but highlight sidebar
             render_terminal(f, main_chunks[1], app);
         }
+        AppMode::Timeline => {
+            render_terminal(f, main_chunks[1], app);
+            render_timeline(f, main_chunks[1], &app.blocks, &app.timeline, &app.theme);
+        }
+        AppMode::ConfirmScript => {
+            render_terminal(f, main_chunks[1], app);
+            render_confirm_script(
+                f,
+                main_chunks[1],
+                &app.input,
+                count_commands(&app.input),
+                &app.theme,
+            );
+        }
+        AppMode::Pty => match app.pty {
+            Some(ref session) => render_pty(f, main_chunks[1], session, &app.theme),
+            None => render_terminal(f, main_chunks[1], app),
+        },
+    }
+
+    // A background job just finished (`jobs.on_finish`)
+    if let Some(success) = app.job_highlight() {
+        let color = if success {
+            app.theme.block_success
+        } else {
+            app.theme.block_failure
+        };
+        highlight_frame(f.buffer_mut(), main_chunks[1], color);
     }
 }
 
 fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    if app.template.is_some() && handle_template_key(app, code, modifiers) {
+        return;
+    }
+
+    // Any key other than Tab closes the completion popup; Esc and Enter
+    // (with a candidate chosen) only close it
+    if !matches!(code, KeyCode::Tab | KeyCode::BackTab) {
+        if let Some(menu) = app.completion.take() {
+            if code == KeyCode::Esc || (code == KeyCode::Enter && menu.selected.is_some()) {
+                return;
+            }
+        }
+    }
+
+    let action = app.keymap.action(code, modifiers);
+    app.kill_ring.next_key(
+        action.is_some_and(Action::kills),
+        action.is_some_and(Action::yanks),
+    );
+    if let Some(action) = action {
+        if handle_action(app, action) {
+            return;
+        }
+    }
+
     match (code, modifiers) {
         // Ctrl+1 through Ctrl+9 - navigate to shortcut
         (KeyCode::Char(c), KeyModifiers::CONTROL) if ('1'..='9').contains(&c) => {
             let index = c.to_digit(10).unwrap() as usize;
             if let Some(shortcut) = app.shortcuts.get_shortcut(index) {
                 let path = shortcut.path.clone();
-                if path.is_dir() {
-                    app.add_output(&format!("cd {}", path.display()));
-                    app.current_dir = path.clone();
-                    app.shortcuts.touch_shortcut(&path);
+                if app.jump_to_shortcut(path) {
                     app.refresh_git_status(false);
-                } else {
-                    app.add_output(&format!("Error: {} no longer exists", path.display()));
                 }
             }
         }
 
-        // Ctrl+C - cancel/clear
-        (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
-            if app.input.is_empty() {
-                app.should_quit = true;
+        // Enter - execute command (or confirm the current macro step)
+        (KeyCode::Enter, _) => {
+            if app.playback.is_some() {
+                // Macro steps wait for the foreground command to finish
+                if app.running.is_none() {
+                    confirm_playback_step(app);
+                }
+            } else if app.needs_confirmation() {
+                // Show the whole script before running pasted commands
+                app.mode = AppMode::ConfirmScript;
             } else {
-                app.add_output(&format!("{}{}^C", app.prompt(), app.input));
-                app.clear_input();
-            }
-        }
-
-        // Ctrl+D - exit
-        (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
-            if app.input.is_empty() {
-                app.should_quit = true;
+                submit_input(app);
             }
         }
 
-        // Ctrl+L - clear screen
-        (KeyCode::Char('l'), KeyModifiers::CONTROL) => {
-            app.output.clear();
-        }
-
-        // Ctrl+A - move to start
-        (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
-            app.cursor_home();
-        }
-
-        // Ctrl+E - move to end
-        (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
-            app.cursor_end();
+        // Backspace - delete character
+        (KeyCode::Backspace, _) => {
+            app.delete_char();
         }
 
-        // Ctrl+U - clear line
-        (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
-            app.clear_input();
+        // `v` in the scrollback starts copy mode there
+        (KeyCode::Char('v'), KeyModifiers::NONE) if app.output_scroll.is_some() => {
+            app.enter_copy_mode();
         }
 
-        // Enter - execute command
-        (KeyCode::Enter, _) => {
-            execute_input(app);
+        // `/` in the scrollback searches the output
+        (KeyCode::Char('/'), KeyModifiers::NONE) if app.output_scroll.is_some() => {
+            app.start_search();
         }
 
-        // Backspace - delete character
-        (KeyCode::Backspace, _) => {
-            app.delete_char();
+        // Left/Right with nothing typed (or in the scrollback) scroll
+        // truncated output sideways
+        (KeyCode::Left | KeyCode::Right, KeyModifiers::NONE)
+            if app.output_wrap == OutputWrap::Truncate
+                && (app.input.is_empty() || app.output_scroll.is_some()) =>
+        {
+            let step = if code == KeyCode::Left {
+                -HSCROLL_STEP
+            } else {
+                HSCROLL_STEP
+            };
+            app.scroll_horizontal(step);
         }
 
         // Left arrow - move cursor left
@@ -190,6 +690,11 @@ fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             app.cursor_left();
         }
 
+        // Right arrow / End - accept the autosuggestion
+        (KeyCode::Right | KeyCode::End, _) if app.suggestion.is_some() => {
+            app.accept_suggestion();
+        }
+
         // Right arrow - move cursor right
         (KeyCode::Right, _) => {
             app.cursor_right();
@@ -215,14 +720,21 @@ fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             app.cursor_end();
         }
 
-        // Tab - could be used for autocomplete later
+        // Tab / Shift+Tab - complete, or cycle through the completion popup
         (KeyCode::Tab, _) => {
-            // TODO: Implement tab completion
+            app.complete_input();
+        }
+        (KeyCode::BackTab, _) => {
+            app.complete_prev();
         }
 
-        // Escape - clear input
+        // Escape - stop macro playback, or clear input
         (KeyCode::Esc, _) => {
-            app.clear_input();
+            if app.playback.is_some() {
+                app.stop_playback();
+            } else {
+                app.clear_input();
+            }
         }
 
         // Regular character input
@@ -234,93 +746,918 @@ fn handle_normal_mode(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
     }
 }
 
-fn handle_navigation_mode(app: &mut App, code: KeyCode) {
-    match code {
-        // Up - move selection up
-        KeyCode::Up => {
-            app.navigation_state.move_up();
-        }
-
-        // Down - move selection down
-        KeyCode::Down => {
-            app.navigation_state.move_down();
+/// Run a rebindable action; returns false when it doesn't apply right now so
+/// the key is handled as ordinary input
+fn handle_action(app: &mut App, action: Action) -> bool {
+    match action {
+        // Interrupt the running command, or cancel/clear
+        Action::Interrupt => {
+            if app.running.is_some() {
+                app.interrupt_running();
+            } else if app.retry.is_some() {
+                app.cancel_retry();
+            } else if app.input.is_empty() {
+                app.request_quit(action);
+            } else {
+                app.add_output(&format!("{}{}^C", app.prompt(), app.input));
+                app.clear_input();
+            }
         }
-
-        // Right - enter selected directory
-        KeyCode::Right => {
-            app.navigation_state.enter_selected();
+        Action::Quit if app.input.is_empty() => app.request_quit(action),
+        Action::Suspend if app.running.is_none() => app.undo(),
+        Action::Suspend => app.suspend_running(),
+        Action::ClearScreen => app.clear_screen(),
+        Action::Timeline => app.enter_timeline_mode(),
+        Action::Goto => app.enter_goto_mode(),
+        Action::ScrollUp => app.scroll_up(app.pane_size.0.max(1) as usize),
+        Action::ScrollDown => app.scroll_down(app.pane_size.0.max(1) as usize),
+        Action::LineStart => app.cursor_home(),
+        Action::LineEnd => app.cursor_end(),
+        Action::ClearLine => app.clear_input(),
+        Action::WordLeft => app.word_left(),
+        Action::WordRight => app.word_right(),
+        Action::KillWordBack => app.kill_word_back(),
+        Action::KillWordForward => app.kill_word_forward(),
+        Action::KillToEnd => app.kill_to_end(),
+        Action::Yank => app.yank(),
+        Action::YankPop => app.yank_pop(),
+        Action::Undo => app.undo(),
+        Action::Redo => app.redo(),
+        Action::SkipMacroStep if app.playback.is_some() => app.skip_playback_step(),
+        Action::Shell => app.request_shell(),
+        Action::ToggleErrors => app.toggle_diagnostics(),
+        Action::ToggleDiff => app.toggle_diff(),
+        Action::ToggleSynopsis => app.toggle_synopsis(),
+        Action::RefreshGit => app.force_git_refresh(),
+        Action::Conflicts => app.enter_conflicts_mode(),
+        Action::GitStatus => app.toggle_git_panel(),
+        Action::ToggleWrap => app.toggle_output_wrap(),
+        Action::HexView => app.enter_hex_view(),
+        Action::CopyMode => app.enter_copy_mode(),
+        Action::Search => app.start_search(),
+        Action::Paste => {
+            if let Some(text) = clipboard::paste().or_else(|| app.copied.clone()) {
+                handle_paste(app, &text);
+            }
         }
+        // Handled before the mode sees the key
+        Action::RecordKeys | Action::PlayKeys => return false,
+        Action::Quit | Action::SkipMacroStep => return false,
+    }
+    true
+}
 
-        // Left - go up one level
-        KeyCode::Left => {
-            app.navigation_state.go_up();
-        }
+/// Keys for filling in snippet placeholders; returns false to fall through
+/// to normal editing (which ends fill-in mode)
+fn handle_template_key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) -> bool {
+    let Some(ref mut template) = app.template else {
+        return false;
+    };
 
-        // Enter - confirm selection
-        KeyCode::Enter => {
-            if let Some(path) = app.navigation_state.get_selected_path() {
-                app.add_output(&format!("cd {}", path.display()));
-            }
-            app.confirm_navigation();
-            app.refresh_git_status(false);
+    match (code, modifiers) {
+        (KeyCode::Tab, _) => template.next_field(),
+        (KeyCode::BackTab, _) => template.prev_field(),
+        (KeyCode::Backspace, _) => template.pop_char(),
+        (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => template.push_char(c),
+        (KeyCode::Esc, _) => {
+            app.template = None;
+            return true;
         }
-
-        // Escape - cancel navigation
-        KeyCode::Esc => {
-            app.exit_navigation_mode();
+        _ => {
+            app.template = None;
+            return false;
         }
-
-        _ => {}
     }
+
+    app.sync_template();
+    true
 }
 
-fn handle_goto_mode(app: &mut App, code: KeyCode) {
+fn handle_confirm_mode(app: &mut App, code: KeyCode) {
     match code {
-        // Up - move selection up
-        KeyCode::Up => {
-            app.goto_move_up();
-        }
-
-        // Down - move selection down
-        KeyCode::Down => {
-            app.goto_move_down();
-        }
-
-        // Enter - confirm selection and navigate
         KeyCode::Enter => {
-            app.confirm_goto();
-            app.refresh_git_status(false);
+            app.mode = AppMode::Normal;
+            submit_input(app);
         }
-
-        // Escape - cancel goto mode
         KeyCode::Esc => {
-            app.exit_goto_mode();
+            app.mode = AppMode::Normal;
         }
-
         _ => {}
     }
 }
 
-fn execute_input(app: &mut App) {
-    let input = app.input.clone();
-    app.add_command_to_output(&input);
-    app.add_to_history(&input);
-    app.clear_input();
+/// Lines the output buffer moves per scroll wheel notch
+const MOUSE_SCROLL_LINES: usize = 3;
 
-    match parse_command(&input) {
-        ParsedCommand::Empty => {
-            // Do nothing for empty commands
+/// Handle a mouse event against where the last frame drew things
+fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    let (column, row) = (mouse.column, mouse.row);
+    let position = ratatui::layout::Position { x: column, y: row };
+    match mouse.kind {
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            let up = mouse.kind == MouseEventKind::ScrollUp;
+            match app.mode {
+                AppMode::NavigationList if up => app.navigation_state.move_up(),
+                AppMode::NavigationList => app.navigation_state.move_down(),
+                AppMode::Normal | AppMode::ShortcutSelection | AppMode::Search
+                    if app.screen.terminal.contains(position) =>
+                {
+                    if up {
+                        app.scroll_up(MOUSE_SCROLL_LINES);
+                    } else {
+                        app.scroll_down(MOUSE_SCROLL_LINES);
+                    }
+                }
+                _ => {}
+            }
         }
-
-        ParsedCommand::Cd(path) => {
-            let target = path.as_deref().unwrap_or("~");
-            match resolve_cd_path(target, &app.current_dir) {
-                Ok(new_path) => {
-                    app.current_dir = new_path;
-                    app.refresh_git_status(false); // Local only
+        MouseEventKind::Down(MouseButton::Left) if app.screen.divider.contains(position) => {
+            app.press_divider();
+        }
+        MouseEventKind::Drag(MouseButton::Left) => app.drag_divider(column),
+        MouseEventKind::Up(MouseButton::Left) => app.release_divider(),
+        MouseEventKind::Down(MouseButton::Left) => {
+            if !matches!(app.mode, AppMode::Normal | AppMode::ShortcutSelection) {
+                return;
+            }
+            if app.screen.shortcuts.contains(position) {
+                let index = (row - app.screen.shortcuts.y) as usize;
+                let path = app.goto_candidates().get(index).map(|s| s.path.clone());
+                if let Some(path) = path {
+                    app.exit_goto_mode();
+                    if app.jump_to_shortcut(path) {
+                        app.refresh_git_status(false);
+                    }
                 }
-                Err(e) => {
-                    app.add_output(&format!("cd: {}", e));
+            } else if app.screen.terminal.contains(position) {
+                // Clicking the terminal pane leaves goto mode
+                if app.mode == AppMode::ShortcutSelection {
+                    app.exit_goto_mode();
+                }
+                let cursor = app
+                    .screen
+                    .input
+                    .and_then(|area| area.cursor_at(&app.input, column, row));
+                if let Some(cursor) = cursor {
+                    app.cursor_pos = cursor;
+                    app.completion = None;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Handle bracketed paste: insert into the input line, or forward to a PTY
+fn handle_paste(app: &mut App, text: &str) {
+    match app.mode {
+        AppMode::Normal if app.running.is_none() => {
+            app.completion = None;
+            app.template = None;
+            let pasted = shell::paste::transform(text, &app.config.paste);
+            if !pasted.invisible.is_empty() {
+                let found: Vec<String> = pasted
+                    .invisible
+                    .iter()
+                    .map(|(c, name)| format!("U+{:04X} ({name})", u32::from(*c)))
+                    .collect();
+                app.add_output(&format!(
+                    "jerm: pasted text contains invisible characters: {}",
+                    found.join(", ")
+                ));
+            }
+            let before = app.input_state();
+            app.insert_str(&pasted.text);
+            app.record_edit(before, EditKind::Other);
+            app.pasted = true;
+            app.update_suggestion();
+        }
+        AppMode::Pty => {
+            if let Some(ref mut session) = app.pty {
+                session.paste(text);
+            }
+        }
+        // Output lines are searched one at a time, so only the first
+        // pasted line makes sense as a query
+        AppMode::Search => {
+            let Some(ref search) = app.search else {
+                return;
+            };
+            if search.editing {
+                let query = search.query.clone() + text.lines().next().unwrap_or_default();
+                app.set_search_query(query);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_diffdir_mode(app: &mut App, code: KeyCode) {
+    if code == KeyCode::Esc {
+        app.exit_diffdir_mode();
+        return;
+    }
+    let Some(ref mut diff) = app.dir_diff else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up => diff.move_up(),
+        KeyCode::Down => diff.move_down(),
+        KeyCode::Left => diff.move_column(false),
+        KeyCode::Right => diff.move_column(true),
+        KeyCode::Enter => diff.enter_selected(),
+        KeyCode::Backspace => diff.go_up(),
+        _ => {}
+    }
+}
+
+fn handle_heatmap_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_heatmap_mode();
+        return;
+    }
+    let Some(ref mut heatmap) = app.heatmap else {
+        return;
+    };
+
+    match code {
+        KeyCode::Left => heatmap.move_days(-7),
+        KeyCode::Right => heatmap.move_days(7),
+        KeyCode::Up => heatmap.move_days(-1),
+        KeyCode::Down => heatmap.move_days(1),
+        KeyCode::PageUp => heatmap.scroll_by(-10),
+        KeyCode::PageDown => heatmap.scroll_by(10),
+        _ => {}
+    }
+}
+
+fn handle_theme_gallery_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_theme_gallery_mode();
+        return;
+    }
+    let Some(ref mut gallery) = app.theme_gallery else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up => gallery.move_by(-1),
+        KeyCode::Down => gallery.move_by(1),
+        KeyCode::PageUp => gallery.move_by(-5),
+        KeyCode::PageDown => gallery.move_by(5),
+        KeyCode::Enter => {
+            let Some(name) = gallery.selected_name().map(str::to_string) else {
+                return;
+            };
+            app.exit_theme_gallery_mode();
+            match app.set_theme(&name) {
+                Ok(()) => app.add_output(&format!(
+                    "Switched to theme '{name}' (set theme.name in config.toml to keep it)"
+                )),
+                Err(e) => app.add_output(&format!("jerm theme: {e}")),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_branch_switch_mode(app: &mut App, code: KeyCode) {
+    if code == KeyCode::Esc {
+        app.exit_branch_switch_mode();
+        return;
+    }
+    let Some(ref mut switcher) = app.branch_switcher else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up => switcher.move_by(-1),
+        KeyCode::Down => switcher.move_by(1),
+        KeyCode::PageUp => switcher.move_by(-10),
+        KeyCode::PageDown => switcher.move_by(10),
+        KeyCode::Backspace => switcher.pop_char(),
+        KeyCode::Char(c) => switcher.push_char(c),
+        // Check out as a regular command, so git's output and errors (a
+        // dirty tree, say) land in the output and the prompt refreshes
+        KeyCode::Enter => {
+            let Some(name) = switcher.selected_name() else {
+                return;
+            };
+            app.exit_branch_switch_mode();
+            app.input = format!("git checkout {}", shell::quote(&name));
+            execute_input(app);
+        }
+        _ => {}
+    }
+}
+
+fn handle_git_status_mode(app: &mut App, key: KeyEvent) {
+    // The panel's own key closes it too
+    let toggle = app.keymap.action(key.code, key.modifiers) == Some(Action::GitStatus);
+    if toggle || matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.toggle_git_panel();
+        return;
+    }
+    let Some(ref mut panel) = app.git_panel else {
+        return;
+    };
+
+    match key.code {
+        KeyCode::Up => panel.scroll_by(-1),
+        KeyCode::Down => panel.scroll_by(1),
+        KeyCode::PageUp => panel.scroll_by(-10),
+        KeyCode::PageDown => panel.scroll_by(10),
+        _ => {}
+    }
+}
+
+fn handle_hex_view_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_hex_view();
+        return;
+    }
+    let Some(ref mut view) = app.hex_view else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up => view.scroll_by(-1),
+        KeyCode::Down => view.scroll_by(1),
+        KeyCode::PageUp => view.scroll_by(-10),
+        KeyCode::PageDown => view.scroll_by(10),
+        KeyCode::Home => view.scroll = 0,
+        KeyCode::End => view.scroll = usize::MAX,
+        _ => {}
+    }
+}
+
+fn handle_env_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_env_panel();
+        return;
+    }
+    let Some(ref mut panel) = app.env_panel else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up => panel.scroll_by(-1),
+        KeyCode::Down => panel.scroll_by(1),
+        KeyCode::PageUp => panel.scroll_by(-10),
+        KeyCode::PageDown => panel.scroll_by(10),
+        KeyCode::Home => panel.scroll = 0,
+        KeyCode::End => panel.scroll = usize::MAX,
+        _ => {}
+    }
+}
+
+fn handle_copy_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_copy_mode();
+        return;
+    }
+    if matches!(code, KeyCode::Char('y') | KeyCode::Enter) {
+        app.copy_selection();
+        return;
+    }
+    let Some(ref mut copy) = app.copy_mode else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => copy.move_by(-1),
+        KeyCode::Down | KeyCode::Char('j') => copy.move_by(1),
+        KeyCode::PageUp => copy.move_by(-10),
+        KeyCode::PageDown => copy.move_by(10),
+        KeyCode::Home | KeyCode::Char('g') => copy.cursor = 0,
+        KeyCode::End | KeyCode::Char('G') => copy.move_by(isize::MAX),
+        KeyCode::Char('v') => copy.toggle_selection(),
+        _ => {}
+    }
+}
+
+fn handle_search_mode(app: &mut App, key: KeyEvent) {
+    let Some(ref mut search) = app.search else {
+        app.mode = AppMode::Normal;
+        return;
+    };
+
+    if search.editing {
+        let mut query = search.query.clone();
+        match key.code {
+            KeyCode::Esc => app.close_search(),
+            KeyCode::Enter if query.is_empty() => app.close_search(),
+            KeyCode::Enter => search.editing = false,
+            KeyCode::Backspace => {
+                query.pop();
+                app.set_search_query(query);
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                query.push(c);
+                app.set_search_query(query);
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    let page = app.pane_size.0.max(1) as usize;
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => app.close_search(),
+        KeyCode::Char('n') => app.move_search(-1),
+        KeyCode::Char('N') => app.move_search(1),
+        KeyCode::Char('/') => app.start_search(),
+        KeyCode::Up | KeyCode::Char('k') => app.scroll_up(1),
+        KeyCode::Down | KeyCode::Char('j') => app.scroll_down(1),
+        KeyCode::PageUp => app.scroll_up(page),
+        KeyCode::PageDown => app.scroll_down(page),
+        _ => {}
+    }
+}
+
+fn handle_tests_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_test_mode();
+        return;
+    }
+    let Some(ref mut tests) = app.tests else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => tests.move_by(-1),
+        KeyCode::Down | KeyCode::Char('j') => tests.move_by(1),
+        KeyCode::PageUp => tests.move_by(-10),
+        KeyCode::PageDown => tests.move_by(10),
+        KeyCode::Enter | KeyCode::Char(' ') => tests.toggle_expanded(),
+        KeyCode::Char('r') => tests.run(),
+        KeyCode::Char('w') => tests.toggle_watch(),
+        _ => {}
+    }
+}
+
+fn handle_git_overview_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_git_overview_mode();
+        return;
+    }
+    let Some(ref mut overview) = app.git_overview else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up => overview.move_by(-1),
+        KeyCode::Down => overview.move_by(1),
+        KeyCode::PageUp => overview.move_by(-10),
+        KeyCode::PageDown => overview.move_by(10),
+        _ => {}
+    }
+}
+
+fn handle_conflicts_mode(app: &mut App, code: KeyCode) {
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        app.exit_conflicts_mode();
+        return;
+    }
+    let Some(ref mut conflicts) = app.conflicts else {
+        return;
+    };
+
+    match code {
+        KeyCode::Up => conflicts.move_by(-1),
+        KeyCode::Down => conflicts.move_by(1),
+        // Edit the file; the overlay closes so the editor gets the pane
+        KeyCode::Enter | KeyCode::Char('e') if !app.readonly => {
+            let Some(path) = conflicts.selected_path() else {
+                return;
+            };
+            app.exit_conflicts_mode();
+            app.input = format!("{} {}", editor(), shell::quote(&path.display().to_string()));
+            execute_input(app);
+        }
+        // Mark resolved: stage the file
+        KeyCode::Char('a') if !app.readonly => match conflicts.mark_resolved() {
+            Ok(file) => {
+                let done = conflicts.files.is_empty();
+                app.add_output(&format!("git add {file}"));
+                if done {
+                    app.add_output("All conflicts resolved");
+                    app.exit_conflicts_mode();
+                }
+            }
+            Err(e) => app.add_output(&format!("git add: {e}")),
+        },
+        _ => {}
+    }
+}
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then `vi`
+fn editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+fn handle_note_mode(app: &mut App, key: KeyEvent) {
+    if key.code == KeyCode::Esc {
+        app.close_note();
+        return;
+    }
+    let Some(ref mut pad) = app.note else {
+        return;
+    };
+
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => pad.insert_char(c),
+        KeyCode::Enter => pad.insert_newline(),
+        KeyCode::Backspace => pad.backspace(),
+        KeyCode::Delete => pad.delete(),
+        KeyCode::Left => pad.move_left(),
+        KeyCode::Right => pad.move_right(),
+        KeyCode::Up => pad.move_up(),
+        KeyCode::Down => pad.move_down(),
+        KeyCode::Home => pad.move_home(),
+        KeyCode::End => pad.move_end(),
+        _ => {}
+    }
+}
+
+fn handle_navigation_mode(app: &mut App, code: KeyCode) {
+    if app.navigation_state.prompt.is_some() {
+        handle_navigation_prompt(app, code);
+        return;
+    }
+    if app.navigation_state.search.is_some() {
+        handle_navigation_search(app, code);
+        return;
+    }
+
+    match code {
+        // Up - move selection up
+        KeyCode::Up => {
+            app.navigation_state.move_up();
+        }
+
+        // Down - move selection down
+        KeyCode::Down => {
+            app.navigation_state.move_down();
+        }
+
+        // Right - enter selected directory
+        KeyCode::Right => {
+            app.navigation_state.enter_selected();
+        }
+
+        // Left - go up one level
+        KeyCode::Left => {
+            app.navigation_state.go_up();
+        }
+
+        // Enter - confirm selection
+        KeyCode::Enter => {
+            // Files and directories that can't be read can't be confirmed
+            if app
+                .navigation_state
+                .selected_entry()
+                .is_some_and(|entry| !entry.accessible || !entry.is_dir)
+            {
+                return;
+            }
+            if let Some(path) = app.navigation_state.get_selected_path() {
+                app.add_output(&format!("cd {}", path.display()));
+            }
+            app.confirm_navigation();
+            app.refresh_git_status(false);
+        }
+
+        // f - show or hide files
+        KeyCode::Char('f') => {
+            app.navigation_state.toggle_files();
+        }
+
+        // v - switch between the list and columns layouts
+        KeyCode::Char('v') => {
+            app.navigation_state.toggle_layout();
+        }
+
+        // s - cycle the sort order
+        KeyCode::Char('s') => {
+            app.navigation_state.cycle_sort();
+        }
+
+        // / - search the whole tree below
+        KeyCode::Char('/') => {
+            app.navigation_state.begin_search();
+        }
+
+        // a - create a directory
+        KeyCode::Char('a') if !app.readonly => {
+            app.navigation_state.begin_mkdir();
+        }
+
+        // r - rename the selection
+        KeyCode::Char('r') if !app.readonly => {
+            app.navigation_state.begin_rename();
+        }
+
+        // d - delete the selection, once confirmed
+        KeyCode::Char('d') if !app.readonly => {
+            app.navigation_state.begin_delete();
+        }
+
+        // . or h - show or hide hidden entries
+        KeyCode::Char('.' | 'h') => {
+            app.navigation_state.toggle_hidden();
+        }
+
+        // Escape - cancel navigation
+        KeyCode::Esc => {
+            app.exit_navigation_mode();
+        }
+
+        _ => {}
+    }
+}
+
+/// Keys while the navigator's deep search is open
+fn handle_navigation_search(app: &mut App, code: KeyCode) {
+    let Some(ref mut search) = app.navigation_state.search else {
+        return;
+    };
+
+    match code {
+        KeyCode::Esc => app.navigation_state.end_search(),
+        KeyCode::Up => search.move_by(-1),
+        KeyCode::Down => search.move_by(1),
+        KeyCode::PageUp => search.move_by(-10),
+        KeyCode::PageDown => search.move_by(10),
+        KeyCode::Right => app.navigation_state.browse_search_result(),
+        KeyCode::Enter => {
+            let Some(path) = search.selected_path().cloned() else {
+                return;
+            };
+            app.navigation_state.end_search();
+            app.add_output(&format!("cd {}", path.display()));
+            app.change_dir(path);
+            app.exit_navigation_mode();
+            app.refresh_git_status(false);
+        }
+        KeyCode::Backspace => search.pop_char(),
+        KeyCode::Char(c) => search.push_char(c),
+        _ => {}
+    }
+}
+
+/// Keys while a navigator file operation prompt is open
+fn handle_navigation_prompt(app: &mut App, code: KeyCode) {
+    let Some(ref mut prompt) = app.navigation_state.prompt else {
+        return;
+    };
+    let confirming = matches!(prompt.action, PromptAction::Delete(_));
+
+    match code {
+        KeyCode::Esc => app.navigation_state.cancel_prompt(),
+        KeyCode::Char('n' | 'N') if confirming => app.navigation_state.cancel_prompt(),
+        KeyCode::Enter if !confirming => submit_navigation_prompt(app),
+        KeyCode::Char('y' | 'Y') if confirming => submit_navigation_prompt(app),
+        KeyCode::Backspace if !confirming => {
+            prompt.input.pop();
+        }
+        KeyCode::Char(c) if !confirming => prompt.input.push(c),
+        _ => {}
+    }
+}
+
+/// Run the navigator's pending file operation, reporting failures in the output
+fn submit_navigation_prompt(app: &mut App) {
+    let Some(verb) = app
+        .navigation_state
+        .prompt
+        .as_ref()
+        .map(|p| p.action.verb())
+    else {
+        return;
+    };
+    let cwd = app.current_dir.clone();
+    if let Err(e) = app.navigation_state.submit_prompt(&cwd) {
+        app.add_output(&format!("{verb}: {e}"));
+    }
+}
+
+fn handle_goto_mode(app: &mut App, key: KeyEvent) {
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key.code {
+        // Shift+Up / Shift+Down - move a pinned shortcut
+        KeyCode::Up if shift => {
+            app.goto_move_pinned(true);
+        }
+        KeyCode::Down if shift => {
+            app.goto_move_pinned(false);
+        }
+
+        // Up - move selection up
+        KeyCode::Up => {
+            app.goto_move_up();
+        }
+
+        // Down - move selection down
+        KeyCode::Down => {
+            app.goto_move_down();
+        }
+
+        // Enter - confirm selection and navigate
+        KeyCode::Enter => {
+            app.confirm_goto();
+            app.refresh_git_status(false);
+        }
+
+        // Escape - clear the filter, or cancel goto mode
+        KeyCode::Esc if !app.goto_filter.is_empty() => {
+            app.goto_filter.clear();
+            app.selected_shortcut_index = 0;
+        }
+        KeyCode::Esc => {
+            app.exit_goto_mode();
+        }
+
+        // Delete - delete the highlighted shortcut
+        KeyCode::Delete if !app.readonly => {
+            app.goto_remove_selected();
+        }
+
+        // Ctrl+P - pin or unpin the highlighted shortcut
+        KeyCode::Char('p') if ctrl => {
+            app.goto_toggle_pin();
+        }
+
+        // Typing filters the list
+        KeyCode::Backspace => {
+            app.goto_pop_filter();
+        }
+        KeyCode::Char(c) if !ctrl && !key.modifiers.contains(KeyModifiers::ALT) => {
+            app.goto_push_filter(c);
+        }
+
+        _ => {}
+    }
+}
+
+fn handle_timeline_mode(app: &mut App, key: KeyEvent) {
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('r') {
+        if app.rerun_from_timeline() {
+            execute_input(app);
+        }
+        return;
+    }
+    match key.code {
+        KeyCode::Up => app.timeline.move_up(),
+        KeyCode::Down => app.timeline.move_down(&app.blocks),
+        KeyCode::Enter => app.confirm_timeline(),
+        KeyCode::Esc => app.exit_timeline_mode(),
+        KeyCode::Backspace => app.timeline.pop_filter(&app.blocks),
+        KeyCode::Char(c) => app.timeline.push_filter(c, &app.blocks),
+        _ => {}
+    }
+}
+
+fn handle_pty_mode(app: &mut App, key: KeyEvent) {
+    // Every key goes to the program, including Ctrl+C
+    if let Some(ref mut session) = app.pty {
+        session.send_key(key);
+    }
+}
+
+fn confirm_playback_step(app: &mut App) {
+    let Some(prompt) = app.playback.as_ref().and_then(Playback::prompt) else {
+        return;
+    };
+
+    match prompt {
+        PlaybackPrompt::Parameter(name) => {
+            let value = app.input.clone();
+            if let Some(ref mut playback) = app.playback {
+                playback.set_param(&name, &value);
+            }
+        }
+        PlaybackPrompt::Step(_) => {
+            execute_input(app);
+            if let Some(ref mut playback) = app.playback {
+                playback.advance();
+            }
+        }
+    }
+
+    app.prepare_playback_step();
+}
+
+/// Run the input, or queue it behind the commands still running
+fn submit_input(app: &mut App) {
+    if app.input_must_wait() {
+        app.queue_input();
+    } else {
+        execute_input(app);
+    }
+}
+
+/// Start queued commands whose turn has come, leaving the input being
+/// typed untouched
+fn run_queued(app: &mut App) {
+    while let Some(command) = app.next_queued() {
+        let typed = std::mem::replace(&mut app.input, command);
+        let cursor = app.cursor_pos;
+        execute_input(app);
+        app.input = typed;
+        app.cursor_pos = cursor;
+    }
+}
+
+fn execute_input(app: &mut App) {
+    let input = app.input.clone();
+    app.output_scroll = None;
+    if !input.trim().is_empty() {
+        // A new command takes over from a retry loop waiting for its next attempt
+        app.cancel_retry();
+        app.begin_block(input.trim());
+    }
+    app.add_command_to_output(&input);
+    app.add_to_history(&input);
+    app.clear_input();
+
+    let parsed = parse_command(&app.aliases.expand(&input));
+    if parsed.is_recordable() {
+        app.record_command(input.trim());
+    }
+
+    // A directory on its own is a cd with `shell.auto_cd`
+    let parsed = match parsed {
+        ParsedCommand::Shell(command)
+            if app.config.shell.auto_cd && auto_cd_target(&command, &app.current_dir).is_some() =>
+        {
+            ParsedCommand::Cd(Some(command))
+        }
+        parsed => parsed,
+    };
+
+    match parsed {
+        ParsedCommand::Empty => {
+            // Do nothing for empty commands
+        }
+
+        ParsedCommand::Cd(path) => {
+            let target = path.as_deref().unwrap_or("~");
+            match resolve_cd_path(target, &app.current_dir) {
+                Ok(new_path) => {
+                    app.change_dir(new_path);
+                    app.refresh_git_status(false); // Local only
+                }
+                Err(e) => {
+                    app.add_output(&format!("cd: {}", e));
+                    app.set_block_exit(1);
+                }
+            }
+        }
+
+        ParsedCommand::Z(keywords) if keywords.is_empty() => {
+            let now = chrono::Utc::now();
+            let lines: Vec<String> = app
+                .dir_tracker
+                .matches(&[], std::path::Path::new(""))
+                .iter()
+                .take(10)
+                .map(|dir| format!("{:>7.1}  {}", dir.frecency(now), dir.path.display()))
+                .collect();
+            if lines.is_empty() {
+                app.add_output("z: no directories visited yet");
+            }
+            for line in lines {
+                app.add_output(&line);
+            }
+        }
+
+        ParsedCommand::Z(keywords) => {
+            let best = app
+                .dir_tracker
+                .matches(&keywords, &app.current_dir)
+                .first()
+                .map(|dir| dir.path.clone());
+            match best {
+                Some(path) => {
+                    app.add_output(&format!("cd {}", path.display()));
+                    app.change_dir(path);
+                    app.refresh_git_status(false); // Local only
+                }
+                None => {
+                    app.add_output(&format!("z: no match for '{}'", keywords.join(" ")));
+                    app.set_block_exit(1);
                 }
             }
         }
@@ -329,33 +1666,474 @@ fn execute_input(app: &mut App) {
             app.enter_navigation_mode();
         }
 
-        ParsedCommand::Clear => {
-            app.output.clear();
+        ParsedCommand::Clear { hard: false } => {
+            app.clear_screen();
+        }
+
+        ParsedCommand::Clear { hard: true } => {
+            app.clear_output();
         }
 
         ParsedCommand::Exit => {
             app.should_quit = true;
         }
 
-        ParsedCommand::JermSave => {
-            app.shortcuts.add_shortcut(app.current_dir.clone());
-            app.add_output(&format!("Shortcut saved: {}", app.current_dir.display()));
+        ParsedCommand::Alias(AliasAction::List) => {
+            let aliases: Vec<String> = app
+                .aliases
+                .list()
+                .map(|(name, command)| shell::alias::format_alias(name, command))
+                .collect();
+            if aliases.is_empty() {
+                app.add_output("No aliases");
+            }
+            for alias in aliases {
+                app.add_output(&alias);
+            }
+        }
+
+        ParsedCommand::Alias(AliasAction::Show(name)) => match app.aliases.get(&name) {
+            Some(command) => {
+                let alias = shell::alias::format_alias(&name, command);
+                app.add_output(&alias);
+            }
+            None => {
+                app.add_output(&format!("alias: {name}: not found"));
+                app.set_block_exit(1);
+            }
+        },
+
+        ParsedCommand::Alias(AliasAction::Set { name, command }) => {
+            if !paths::is_valid_name(&name) {
+                app.add_output(&format!(
+                    "alias: invalid name '{name}' (use letters, digits, - and _)"
+                ));
+                app.set_block_exit(1);
+            } else if let Err(e) = app.aliases.set(&name, &command) {
+                app.add_output(&format!("alias: {e}"));
+                app.set_block_exit(1);
+            }
+        }
+
+        ParsedCommand::Export(None) => {
+            app.add_output("usage: export NAME=value ...");
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::Export(Some(args)) => {
+            if let Err(e) = app.env.export(&args) {
+                app.add_output(&format!("export: {e}"));
+                app.set_block_exit(1);
+            }
+        }
+
+        ParsedCommand::Unset(None) => {
+            app.add_output("usage: unset NAME ...");
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::Unset(Some(args)) => {
+            if let Err(e) = app.env.unset(&args) {
+                app.add_output(&format!("unset: {e}"));
+                app.set_block_exit(1);
+            }
+        }
+
+        ParsedCommand::Env => {
+            app.enter_env_panel();
+        }
+
+        ParsedCommand::Unalias(None) => {
+            app.add_output("usage: unalias <name>");
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::Unalias(Some(name)) => match app.aliases.remove(&name) {
+            Ok(true) => {}
+            Ok(false) => {
+                app.add_output(&format!("unalias: {name}: not found"));
+                app.set_block_exit(1);
+            }
+            Err(e) => {
+                app.add_output(&format!("unalias: {e}"));
+                app.set_block_exit(1);
+            }
+        },
+
+        ParsedCommand::JermSave(_) if app.readonly => {
+            app.add_output("jerm save: disabled in readonly mode");
+        }
+
+        ParsedCommand::JermSave(Some(ref name)) if !paths::is_valid_name(name) => {
+            app.add_output(&format!(
+                "jerm save: invalid name '{name}' (use letters, digits, - and _)"
+            ));
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::JermSave(name) => {
+            let label = match name {
+                Some(ref name) => format!("{name} -> {}", app.current_dir.display()),
+                None => app.current_dir.display().to_string(),
+            };
+            app.shortcuts.add_shortcut(app.current_dir.clone(), name);
+            app.add_output(&format!("Shortcut saved: {label}"));
+        }
+
+        ParsedCommand::JermList => {
+            if app.shortcuts.is_empty() {
+                app.add_output("No shortcuts saved");
+            }
+            let lines: Vec<String> = app
+                .shortcuts
+                .get_shortcuts()
+                .iter()
+                .enumerate()
+                .map(|(i, shortcut)| {
+                    let name = shortcut
+                        .name
+                        .as_ref()
+                        .map(|name| format!("{name}: "))
+                        .unwrap_or_default();
+                    format!(
+                        "{:>3}  {name}{}  ({})",
+                        i + 1,
+                        shortcut.display_name(),
+                        shortcut.time_ago()
+                    )
+                })
+                .collect();
+            for line in lines {
+                app.add_output(&line);
+            }
         }
 
-        ParsedCommand::JermGoto => {
+        ParsedCommand::JermRm(None) => {
+            app.add_output("usage: jerm rm <index|name>");
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::JermRm(Some(_)) if app.readonly => {
+            app.add_output("jerm rm: disabled in readonly mode");
+        }
+
+        ParsedCommand::JermRm(Some(spec)) => match app.shortcuts.find(&spec) {
+            Some(shortcut) => {
+                let path = shortcut.path.clone();
+                app.shortcuts.remove_shortcut(&path);
+                app.add_output(&format!("Shortcut removed: {}", path.display()));
+            }
+            None => {
+                app.add_output(&format!("jerm rm: no shortcut '{spec}'"));
+                app.set_block_exit(1);
+            }
+        },
+
+        ParsedCommand::JermGoto(None) => {
             app.enter_goto_mode();
         }
 
-        ParsedCommand::Shell(cmd) => match execute_command(&cmd, &app.current_dir) {
-            Ok(result) => {
-                for line in result.all_lines() {
-                    app.add_output(&line);
+        ParsedCommand::JermGoto(Some(name)) => match app.shortcuts.find_by_name(&name) {
+            Some(shortcut) => {
+                let path = shortcut.path.clone();
+                if app.jump_to_shortcut(path) {
+                    app.refresh_git_status(false);
+                } else {
+                    app.set_block_exit(1);
+                }
+            }
+            None => {
+                app.add_output(&format!("jerm goto: no shortcut named '{name}'"));
+                app.set_block_exit(1);
+            }
+        },
+
+        ParsedCommand::JermRecord(None) => {
+            app.add_output("usage: jerm record <name>");
+        }
+
+        ParsedCommand::JermRecord(Some(_)) if app.readonly => {
+            app.add_output("jerm record: disabled in readonly mode");
+        }
+
+        ParsedCommand::JermRecord(Some(name)) => {
+            if !paths::is_valid_name(&name) {
+                app.add_output(&format!(
+                    "jerm record: invalid name '{name}' (use letters, digits, - and _)"
+                ));
+            } else if let Some(ref recording) = app.recording {
+                app.add_output(&format!(
+                    "jerm record: already recording '{}', run jerm stop first",
+                    recording.name
+                ));
+            } else {
+                app.start_recording(&name);
+                app.add_output(&format!(
+                    "Recording macro '{name}'. Use {{param}} for values asked on playback; jerm stop to save."
+                ));
+            }
+        }
+
+        ParsedCommand::JermStop => match app.stop_recording() {
+            Some(recorded) if recorded.steps.is_empty() => {
+                app.add_output(&format!(
+                    "Macro '{}' discarded (no commands)",
+                    recorded.name
+                ));
+            }
+            Some(recorded) => match macros::storage::save_macro(&recorded) {
+                Ok(()) => app.add_output(&format!(
+                    "Macro '{}' saved ({} steps)",
+                    recorded.name,
+                    recorded.steps.len()
+                )),
+                Err(e) => app.add_output(&format!("jerm stop: {e}")),
+            },
+            None => app.add_output("jerm stop: not recording"),
+        },
+
+        ParsedCommand::JermPlay(None) => match macros::storage::list_macros() {
+            Ok(names) if names.is_empty() => app.add_output("No macros recorded"),
+            Ok(names) => {
+                for name in names {
+                    app.add_output(&format!("  {name}"));
                 }
-                app.refresh_git_status(false); // Local only
             }
+            Err(e) => app.add_output(&format!("jerm play: {e}")),
+        },
+
+        ParsedCommand::JermPlay(Some(_)) if app.playback.is_some() => {
+            app.add_output("jerm play: a macro is already playing");
+        }
+
+        ParsedCommand::JermProfile(None) => {
+            let active = app.profile_name();
+            let mut names = vec![paths::DEFAULT_PROFILE.to_string()];
+            names.extend(paths::list_profiles());
+            if !names.contains(&active) {
+                names.push(active.clone());
+            }
+            for name in names {
+                let marker = if name == active { "*" } else { " " };
+                app.add_output(&format!("{marker} {name}"));
+            }
+        }
+
+        ParsedCommand::JermProfile(Some(name)) => {
+            if paths::is_valid_name(&name) {
+                app.switch_profile(&name);
+                app.add_output(&format!("Switched to profile '{}'", app.profile_name()));
+            } else {
+                app.add_output(&format!(
+                    "jerm profile: invalid name '{name}' (use letters, digits, - and _)"
+                ));
+                app.set_block_exit(1);
+            }
+        }
+
+        ParsedCommand::JermTheme(None) => {
+            for name in theme::loader::list_themes() {
+                let marker = if name == app.theme.name { "*" } else { " " };
+                app.add_output(&format!("{marker} {name}"));
+            }
+        }
+
+        ParsedCommand::JermThemePreview => {
+            app.enter_theme_gallery_mode();
+        }
+
+        ParsedCommand::JermTheme(Some(name)) => match app.set_theme(&name) {
+            Ok(()) => app.add_output(&format!(
+                "Switched to theme '{name}' (set theme.name in config.toml to keep it)"
+            )),
             Err(e) => {
-                app.add_output(&format!("Error: {}", e));
+                app.add_output(&format!("jerm theme: {e}"));
+                app.set_block_exit(1);
+            }
+        },
+
+        ParsedCommand::JermSnippet(SnippetAction::List) => match snippets::storage::load_snippets()
+        {
+            Ok(data) if data.snippets.is_empty() => app.add_output("No snippets saved"),
+            Ok(data) => {
+                for snippet in data.snippets {
+                    app.add_output(&format!("  {:<16} {}", snippet.name, snippet.command));
+                }
+            }
+            Err(e) => app.add_output(&format!("jerm snippet: {e}")),
+        },
+
+        ParsedCommand::JermSnippet(SnippetAction::Save { .. } | SnippetAction::Remove(_))
+            if app.readonly =>
+        {
+            app.add_output("jerm snippet: disabled in readonly mode");
+        }
+
+        ParsedCommand::JermSnippet(SnippetAction::Save { name, command }) => {
+            if !paths::is_valid_name(&name) {
+                app.add_output(&format!(
+                    "jerm snippet: invalid name '{name}' (use letters, digits, - and _)"
+                ));
+                app.set_block_exit(1);
+            } else {
+                match snippets::storage::add_snippet(snippets::Snippet::new(&name, command)) {
+                    Ok(()) => app.add_output(&format!("Snippet '{name}' saved")),
+                    Err(e) => {
+                        app.add_output(&format!("jerm snippet: {e}"));
+                        app.set_block_exit(1);
+                    }
+                }
+            }
+        }
+
+        ParsedCommand::JermSnippet(SnippetAction::Remove(name)) => {
+            match snippets::storage::remove_snippet(&name) {
+                Ok(()) => app.add_output(&format!("Snippet '{name}' removed")),
+                Err(e) => {
+                    app.add_output(&format!("jerm snippet: {e}"));
+                    app.set_block_exit(1);
+                }
+            }
+        }
+
+        ParsedCommand::JermSnippet(SnippetAction::Insert(name)) => {
+            match snippets::storage::find_snippet(&name) {
+                Ok(snippet) => app.insert_snippet(&snippet.command),
+                Err(e) => {
+                    app.add_output(&format!("jerm snippet: {e}"));
+                    app.set_block_exit(1);
+                }
+            }
+        }
+
+        ParsedCommand::JermTimeline => {
+            app.enter_timeline_mode();
+        }
+
+        ParsedCommand::JermDiffDir(None) => {
+            app.add_output("usage: jerm diffdir <dir-a> <dir-b>");
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::JermDiffDir(Some((a, b))) => {
+            match (
+                resolve_cd_path(&a, &app.current_dir),
+                resolve_cd_path(&b, &app.current_dir),
+            ) {
+                (Ok(a), Ok(b)) => app.enter_diffdir_mode(a, b),
+                (Err(e), _) | (_, Err(e)) => {
+                    app.add_output(&format!("jerm diffdir: {e}"));
+                    app.set_block_exit(1);
+                }
             }
+        }
+
+        ParsedCommand::JermShell => {
+            app.request_shell();
+        }
+
+        ParsedCommand::JermNote => {
+            app.open_note();
+        }
+
+        ParsedCommand::JermHeatmap => {
+            app.enter_heatmap_mode();
+        }
+
+        ParsedCommand::JermGitOverview => {
+            app.enter_git_overview_mode();
+        }
+
+        ParsedCommand::JermBranch => {
+            app.enter_branch_switch_mode();
+        }
+
+        ParsedCommand::JermDoctor => {
+            let checks = doctor::run(&app.config);
+            for line in checks.iter().flat_map(doctor::Check::lines) {
+                app.add_output(&line);
+            }
+            if doctor::problems(&checks) > 0 {
+                app.set_block_exit(1);
+            }
+        }
+
+        ParsedCommand::JermPlay(Some(name)) => match macros::storage::load_macro(&name) {
+            Ok(recorded) => app.start_playback(recorded),
+            Err(e) => app.add_output(&format!("jerm play: {e}")),
         },
+
+        ParsedCommand::Jobs => {
+            app.list_jobs();
+        }
+
+        ParsedCommand::Fg(id) => {
+            app.foreground_job(id);
+        }
+
+        ParsedCommand::Bg(id) => {
+            app.background_job(id);
+        }
+
+        ParsedCommand::JermRetry(None) => {
+            app.add_output("usage: jerm retry [N] [--backoff] <command>");
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::JermPty(None) => {
+            app.add_output("usage: jerm pty <command>");
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::Background(_)
+        | ParsedCommand::Shell(_)
+        | ParsedCommand::JermRetry(_)
+        | ParsedCommand::JermTest(_)
+        | ParsedCommand::JermPty(_)
+            if app.readonly =>
+        {
+            app.add_output("Command execution is disabled in readonly mode");
+        }
+
+        ParsedCommand::Background(ref cmd)
+        | ParsedCommand::Shell(ref cmd)
+        | ParsedCommand::JermRetry(Some(RetrySpec {
+            command: ref cmd, ..
+        }))
+        | ParsedCommand::JermTest(Some(ref cmd))
+        | ParsedCommand::JermPty(Some(ref cmd))
+            if !app.check_policy(cmd) =>
+        {
+            app.set_block_exit(1);
+        }
+
+        ParsedCommand::JermTest(command) => {
+            app.enter_test_mode(command);
+        }
+
+        ParsedCommand::Background(cmd) => {
+            app.start_background(&cmd);
+        }
+
+        ParsedCommand::JermRetry(Some(spec)) => {
+            app.start_retry(spec);
+        }
+
+        ParsedCommand::JermPty(Some(cmd)) => {
+            app.start_pty(&cmd);
+        }
+
+        ParsedCommand::Shell(cmd) if wants_pty(&cmd, &app.config.shell.pty_commands) => {
+            app.start_pty(&cmd);
+        }
+
+        ParsedCommand::Shell(cmd) => {
+            app.start_command(&cmd);
+        }
+    }
+
+    // Builtins that didn't report a failure succeeded; commands report on exit
+    if app.pty.is_none() && app.running.is_none() {
+        app.set_block_exit(0);
     }
 }
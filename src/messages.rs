@@ -0,0 +1,209 @@
+//! Message catalog: user-facing strings that can be translated
+//!
+//! English strings are built in. A `messages.toml` in the config directory
+//! overrides any of them:
+//!
+//! ```toml
+//! [time]
+//! ago = "il y a {}"
+//! hour = { compact = "h", one = "heure", many = "heures" }
+//! ```
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Local};
+use serde::Deserialize;
+
+/// Every translatable string, grouped by where it's used
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Messages {
+    /// Shortcut access times in the sidebar
+    pub time: TimeMessages,
+}
+
+/// Names of one time unit
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct UnitNames {
+    /// Suffix in compact times, e.g. `h` in `2h`
+    pub compact: String,
+    /// Singular, e.g. `1 hour ago`
+    pub one: String,
+    /// Plural, e.g. `2 hours ago`
+    pub many: String,
+}
+
+impl UnitNames {
+    fn new(compact: &str, one: &str, many: &str) -> Self {
+        Self {
+            compact: compact.to_string(),
+            one: one.to_string(),
+            many: many.to_string(),
+        }
+    }
+}
+
+/// Strings for relative and absolute times
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeMessages {
+    /// Compact time for less than a minute ago
+    pub now: String,
+    /// Verbose time for less than a minute ago
+    pub just_now: String,
+    /// Verbose relative time; `{}` is the amount and unit, e.g. `5 minutes`
+    pub ago: String,
+    /// strftime format for absolute times earlier than today
+    pub absolute: String,
+    /// strftime format for absolute times today
+    pub absolute_today: String,
+    pub minute: UnitNames,
+    pub hour: UnitNames,
+    pub day: UnitNames,
+    pub week: UnitNames,
+    pub month: UnitNames,
+    pub year: UnitNames,
+}
+
+impl Default for TimeMessages {
+    fn default() -> Self {
+        Self {
+            now: "now".to_string(),
+            just_now: "just now".to_string(),
+            ago: "{} ago".to_string(),
+            absolute: "%b %-d".to_string(),
+            absolute_today: "%H:%M".to_string(),
+            minute: UnitNames::new("m", "minute", "minutes"),
+            hour: UnitNames::new("h", "hour", "hours"),
+            day: UnitNames::new("d", "day", "days"),
+            week: UnitNames::new("w", "week", "weeks"),
+            month: UnitNames::new("mo", "month", "months"),
+            year: UnitNames::new("y", "year", "years"),
+        }
+    }
+}
+
+impl TimeMessages {
+    /// Amount and unit describing `age`, or `None` under a minute
+    ///
+    /// Both compact and verbose times use this, so they always agree: weeks
+    /// up to 30 days, then 30-day months (45 days is one month), then years.
+    fn amount(&self, age: Duration) -> Option<(i64, &UnitNames)> {
+        let days = age.num_days();
+        let amount = if age.num_minutes() < 1 {
+            return None;
+        } else if age.num_hours() < 1 {
+            (age.num_minutes(), &self.minute)
+        } else if days < 1 {
+            (age.num_hours(), &self.hour)
+        } else if days < 7 {
+            (days, &self.day)
+        } else if days < 30 {
+            (days / 7, &self.week)
+        } else if days < 365 {
+            (days / 30, &self.month)
+        } else {
+            (days / 365, &self.year)
+        };
+        Some(amount)
+    }
+
+    /// Terse relative time such as `now`, `5m`, `2h`, `1mo`
+    pub fn compact(&self, age: Duration) -> String {
+        match self.amount(age) {
+            Some((amount, unit)) => format!("{amount}{}", unit.compact),
+            None => self.now.clone(),
+        }
+    }
+
+    /// Spelled-out relative time such as `just now` or `5 minutes ago`
+    pub fn verbose(&self, age: Duration) -> String {
+        match self.amount(age) {
+            Some((amount, unit)) => {
+                let name = if amount == 1 { &unit.one } else { &unit.many };
+                self.ago.replace("{}", &format!("{amount} {name}"))
+            }
+            None => self.just_now.clone(),
+        }
+    }
+
+    /// Local clock time for today, otherwise the date
+    pub fn absolute(&self, time: DateTime<Local>, now: DateTime<Local>) -> String {
+        let format = if time.date_naive() == now.date_naive() {
+            &self.absolute_today
+        } else {
+            &self.absolute
+        };
+        time.format(format).to_string()
+    }
+}
+
+/// Get the path of the message catalog
+pub fn get_messages_path() -> Option<PathBuf> {
+    crate::paths::config_dir().map(|dir| dir.join("messages.toml"))
+}
+
+impl Messages {
+    /// Load the catalog, falling back to English with a warning if the file
+    /// can't be read
+    pub fn load() -> (Self, Option<String>) {
+        let Some(path) = get_messages_path().filter(|path| path.exists()) else {
+            return (Self::default(), None);
+        };
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| toml::from_str(&content).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(messages) => (messages, None),
+            Err(e) => (Self::default(), Some(format!("{}: {e}", path.display()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_rounding_agrees() {
+        let time = TimeMessages::default();
+        for (days, compact, verbose) in [
+            (28, "4w", "4 weeks ago"),
+            (45, "1mo", "1 month ago"),
+            (60, "2mo", "2 months ago"),
+            (400, "1y", "1 year ago"),
+        ] {
+            assert_eq!(time.compact(Duration::days(days)), compact);
+            assert_eq!(time.verbose(Duration::days(days)), verbose);
+        }
+        assert_eq!(time.verbose(Duration::seconds(20)), "just now");
+    }
+
+    #[test]
+    fn test_catalog_overrides() {
+        let messages: Messages = toml::from_str(
+            "[time]\nago = \"il y a {}\"\nminute = { compact = \"min\", one = \"minute\", many = \"minutes\" }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            messages.time.verbose(Duration::minutes(5)),
+            "il y a 5 minutes"
+        );
+        assert_eq!(messages.time.compact(Duration::minutes(5)), "5min");
+        assert_eq!(messages.time.hour.many, "hours");
+    }
+
+    #[test]
+    fn test_absolute() {
+        let time = TimeMessages::default();
+        let now = Local::now();
+        assert_eq!(time.absolute(now, now), now.format("%H:%M").to_string());
+        let earlier = now - Duration::days(3);
+        assert_eq!(
+            time.absolute(earlier, now),
+            earlier.format("%b %-d").to_string()
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Most entries visited when checking whether two subdirectories differ;
+/// past this they are assumed to differ
+const SCAN_BUDGET: usize = 10_000;
+
+/// Size and modification time of one side of a compared entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryMeta {
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl EntryMeta {
+    fn read(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Ok(Self {
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// An entry present in at least one of the compared directories
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry {
+    pub name: String,
+    pub a: Option<EntryMeta>,
+    pub b: Option<EntryMeta>,
+}
+
+impl DiffEntry {
+    /// Whether drill-down is possible: a directory on both sides
+    pub fn is_shared_dir(&self) -> bool {
+        matches!((self.a, self.b), (Some(a), Some(b)) if a.is_dir && b.is_dir)
+    }
+
+    /// Why a differing entry differs, e.g. `4.0K → 5.2K` or `newer in B`
+    pub fn describe(&self) -> String {
+        let (Some(a), Some(b)) = (self.a, self.b) else {
+            return String::new();
+        };
+        if a.is_dir != b.is_dir {
+            "file vs directory".to_string()
+        } else if a.is_dir {
+            "contents differ".to_string()
+        } else if a.size != b.size {
+            format!("{} \u{2192} {}", format_size(a.size), format_size(b.size))
+        } else if a.modified > b.modified {
+            "newer in A".to_string()
+        } else {
+            "newer in B".to_string()
+        }
+    }
+}
+
+/// Which column of the comparison an entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffColumn {
+    OnlyA = 0,
+    Differs = 1,
+    OnlyB = 2,
+}
+
+/// State for the `jerm diffdir` comparison view
+#[derive(Debug, Clone)]
+pub struct DirDiff {
+    /// Compared directories as given
+    pub root_a: PathBuf,
+    pub root_b: PathBuf,
+    /// Subdirectory currently shown, relative to both roots
+    pub relative: PathBuf,
+    /// Entries only in A, differing, and only in B
+    pub columns: [Vec<DiffEntry>; 3],
+    /// Column with the selection
+    pub column: DiffColumn,
+    /// Selected index within each column
+    pub selected: [usize; 3],
+    /// Scroll offset of each column
+    pub scroll: [usize; 3],
+    /// Error encountered while reading the current subdirectory, if any
+    pub error: Option<String>,
+}
+
+impl DirDiff {
+    /// Compare two directories
+    pub fn new(root_a: PathBuf, root_b: PathBuf) -> Self {
+        let mut diff = Self {
+            root_a,
+            root_b,
+            relative: PathBuf::new(),
+            columns: Default::default(),
+            column: DiffColumn::OnlyA,
+            selected: [0; 3],
+            scroll: [0; 3],
+            error: None,
+        };
+        diff.refresh();
+        diff
+    }
+
+    /// Re-read the current subdirectory on both sides
+    pub fn refresh(&mut self) {
+        self.columns = Default::default();
+        self.selected = [0; 3];
+        self.scroll = [0; 3];
+        self.error = None;
+
+        let dir_a = self.root_a.join(&self.relative);
+        let dir_b = self.root_b.join(&self.relative);
+        let entries = match compare(&dir_a, &dir_b) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+
+        let mut budget = SCAN_BUDGET;
+        for entry in entries {
+            let column = match (entry.a, entry.b) {
+                (Some(_), None) => DiffColumn::OnlyA,
+                (None, Some(_)) => DiffColumn::OnlyB,
+                (Some(a), Some(b)) if a.is_dir && b.is_dir => {
+                    let name = &entry.name;
+                    if !dirs_differ(&dir_a.join(name), &dir_b.join(name), &mut budget) {
+                        continue;
+                    }
+                    DiffColumn::Differs
+                }
+                (Some(a), Some(b)) if a != b => DiffColumn::Differs,
+                _ => continue,
+            };
+            self.columns[column as usize].push(entry);
+        }
+
+        // Start in the first column that has something to show
+        self.column = [DiffColumn::OnlyA, DiffColumn::Differs, DiffColumn::OnlyB]
+            .into_iter()
+            .find(|c| !self.columns[*c as usize].is_empty())
+            .unwrap_or(DiffColumn::OnlyA);
+    }
+
+    /// Whether the two directories have no differences at this level
+    pub fn is_identical(&self) -> bool {
+        self.error.is_none() && self.columns.iter().all(Vec::is_empty)
+    }
+
+    /// Move selection up in the focused column
+    pub fn move_up(&mut self) {
+        let column = self.column as usize;
+        self.selected[column] = self.selected[column].saturating_sub(1);
+    }
+
+    /// Move selection down in the focused column
+    pub fn move_down(&mut self) {
+        let column = self.column as usize;
+        if self.selected[column] + 1 < self.columns[column].len() {
+            self.selected[column] += 1;
+        }
+    }
+
+    /// Focus the next non-empty column to the left or right
+    pub fn move_column(&mut self, right: bool) {
+        let order = [DiffColumn::OnlyA, DiffColumn::Differs, DiffColumn::OnlyB];
+        let current = self.column as usize;
+        let next = if right {
+            order[current + 1..]
+                .iter()
+                .find(|c| !self.columns[**c as usize].is_empty())
+        } else {
+            order[..current]
+                .iter()
+                .rev()
+                .find(|c| !self.columns[**c as usize].is_empty())
+        };
+        if let Some(next) = next {
+            self.column = *next;
+        }
+    }
+
+    /// The selected entry in the focused column
+    pub fn selected_entry(&self) -> Option<&DiffEntry> {
+        let column = self.column as usize;
+        self.columns[column].get(self.selected[column])
+    }
+
+    /// Drill down into the selected directory if it exists on both sides
+    pub fn enter_selected(&mut self) {
+        let name = self
+            .selected_entry()
+            .filter(|e| e.is_shared_dir())
+            .map(|e| e.name.clone());
+        if let Some(name) = name {
+            self.relative.push(name);
+            self.refresh();
+        }
+    }
+
+    /// Go back up one level, staying within the compared roots
+    pub fn go_up(&mut self) {
+        if self.relative.pop() {
+            self.refresh();
+        }
+    }
+
+    /// Adjust a column's scroll offset for the visible height
+    pub fn adjust_scroll(&mut self, column: usize, visible_height: usize) {
+        let selected = self.selected[column];
+        if selected >= self.scroll[column] + visible_height {
+            self.scroll[column] = selected.saturating_sub(visible_height.saturating_sub(1));
+        } else if selected < self.scroll[column] {
+            self.scroll[column] = selected;
+        }
+    }
+}
+
+/// Entries of both directories, merged by name and sorted
+fn compare(dir_a: &Path, dir_b: &Path) -> io::Result<Vec<DiffEntry>> {
+    let mut merged: BTreeMap<String, DiffEntry> = BTreeMap::new();
+    for (dir, is_a) in [(dir_a, true), (dir_b, false)] {
+        for entry in fs::read_dir(dir)?.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Ok(meta) = EntryMeta::read(&entry.path()) else {
+                continue;
+            };
+            let diff = merged.entry(name.clone()).or_insert(DiffEntry {
+                name,
+                a: None,
+                b: None,
+            });
+            if is_a {
+                diff.a = Some(meta);
+            } else {
+                diff.b = Some(meta);
+            }
+        }
+    }
+    Ok(merged.into_values().collect())
+}
+
+/// Whether two directories differ anywhere below, by name, size, or mtime
+fn dirs_differ(dir_a: &Path, dir_b: &Path, budget: &mut usize) -> bool {
+    let Ok(entries) = compare(dir_a, dir_b) else {
+        return true;
+    };
+    for entry in entries {
+        if *budget == 0 {
+            return true;
+        }
+        *budget -= 1;
+        match (entry.a, entry.b) {
+            (Some(a), Some(b)) if a.is_dir && b.is_dir => {
+                let name = &entry.name;
+                if dirs_differ(&dir_a.join(name), &dir_b.join(name), budget) {
+                    return true;
+                }
+            }
+            (Some(a), Some(b)) if a == b => {}
+            _ => return true,
+        }
+    }
+    false
+}
+
+/// Format a byte count compactly: `512B`, `4.0K`, `1.2M`, `3.0G`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1}{}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    /// Two trees to compare, in a scratch directory that lives as long as
+    /// the first element
+    fn fixture() -> (ScratchDir, PathBuf, PathBuf) {
+        let root = ScratchDir::new("diffdir");
+        let (a, b) = (root.join("a"), root.join("b"));
+        for dir in [&a, &b] {
+            fs::create_dir_all(dir.join("same")).unwrap();
+            fs::create_dir_all(dir.join("changed")).unwrap();
+        }
+        fs::write(a.join("only_a.txt"), "a").unwrap();
+        fs::write(b.join("only_b.txt"), "b").unwrap();
+        fs::write(a.join("size.txt"), "short").unwrap();
+        fs::write(b.join("size.txt"), "much longer").unwrap();
+        fs::write(a.join("changed/inner.txt"), "1").unwrap();
+        fs::write(b.join("changed/inner.txt"), "12").unwrap();
+        (root, a, b)
+    }
+
+    fn names(entries: &[DiffEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_columns_and_drill_down() {
+        let (_root, a, b) = fixture();
+        let mut diff = DirDiff::new(a.clone(), b);
+
+        assert_eq!(names(&diff.columns[0]), vec!["only_a.txt"]);
+        assert_eq!(names(&diff.columns[1]), vec!["changed", "size.txt"]);
+        assert_eq!(names(&diff.columns[2]), vec!["only_b.txt"]);
+        assert_eq!(diff.columns[1][1].describe(), "5B \u{2192} 11B");
+
+        diff.move_column(true);
+        assert_eq!(diff.column, DiffColumn::Differs);
+        diff.enter_selected();
+        assert_eq!(diff.relative, PathBuf::from("changed"));
+        assert_eq!(names(&diff.columns[1]), vec!["inner.txt"]);
+
+        diff.go_up();
+        assert!(diff.relative.as_os_str().is_empty());
+        diff.go_up();
+        assert!(diff.relative.as_os_str().is_empty());
+    }
+
+    #[test]
+    fn test_missing_directory() {
+        let diff = DirDiff::new(PathBuf::from("/nonexistent/a"), PathBuf::from("/tmp"));
+        assert!(diff.error.is_some());
+        assert!(!diff.is_identical());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512B");
+        assert_eq!(format_size(4096), "4.0K");
+        assert_eq!(format_size(1_572_864), "1.5M");
+    }
+}
@@ -1,5 +1,14 @@
+use std::cmp::Reverse;
+use std::ffi::CString;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::fileops::{self, FileOpError};
+use super::search::DeepSearch;
+use crate::config::NavLayout;
 
 /// Entry in a directory listing
 #[derive(Debug, Clone)]
@@ -10,10 +19,79 @@ pub struct DirEntry {
     pub path: PathBuf,
     /// Whether this is a directory
     pub is_dir: bool,
+    /// Whether the directory can be listed by the current user
+    pub accessible: bool,
+    /// Last modification time, if it could be read
+    pub modified: Option<SystemTime>,
+    /// Number of entries for a directory, bytes for a file (only collected
+    /// when sorting by size)
+    pub size: Option<u64>,
+}
+
+/// Order of the entries in the navigator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// Alphabetically
+    #[default]
+    Name,
+    /// Most recently modified first
+    Modified,
+    /// Largest first: most entries for directories, most bytes for files
+    Size,
+}
+
+impl SortMode {
+    /// The mode after this one when cycling
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Name => SortMode::Modified,
+            SortMode::Modified => SortMode::Size,
+            SortMode::Size => SortMode::Name,
+        }
+    }
+
+    /// Short name shown in the navigator header
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Name => "name",
+            SortMode::Modified => "mtime",
+            SortMode::Size => "size",
+        }
+    }
+}
+
+/// File operation waiting for input in the navigator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptAction {
+    /// Create a directory with the typed name
+    Mkdir,
+    /// Rename this entry to the typed name
+    Rename(PathBuf),
+    /// Delete this entry once confirmed
+    Delete(PathBuf),
+}
+
+impl PromptAction {
+    /// Command name used when reporting errors
+    pub fn verb(&self) -> &'static str {
+        match self {
+            PromptAction::Mkdir => "mkdir",
+            PromptAction::Rename(_) => "rename",
+            PromptAction::Delete(_) => "rm",
+        }
+    }
+}
+
+/// Inline prompt shown in the navigator footer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavPrompt {
+    pub action: PromptAction,
+    /// Text typed so far (unused when confirming a delete)
+    pub input: String,
 }
 
 /// State for the cd -list navigation mode
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct NavigationState {
     /// Current virtual directory being browsed
     pub current_path: PathBuf,
@@ -23,6 +101,26 @@ pub struct NavigationState {
     pub selected_index: usize,
     /// Scroll offset for long lists
     pub scroll_offset: usize,
+    /// Error encountered while reading the current directory, if any
+    pub error: Option<String>,
+    /// Whether files are listed (dimmed, after the directories)
+    pub show_files: bool,
+    /// Whether entries starting with a dot are listed
+    pub show_hidden: bool,
+    /// Order of the listed entries
+    pub sort: SortMode,
+    /// File operation prompt, while one is open
+    pub prompt: Option<NavPrompt>,
+    /// Search of the whole tree below `current_path`, while open
+    pub search: Option<DeepSearch>,
+    /// Single list, or parent, current, and preview columns
+    pub layout: NavLayout,
+    /// Directories next to the current one (columns layout only)
+    pub parent: Vec<DirEntry>,
+    /// Index of the current directory in `parent`
+    pub parent_selected: Option<usize>,
+    /// Listing of the selected directory, and which one it is
+    preview: Option<(PathBuf, Result<Vec<DirEntry>, String>)>,
 }
 
 impl NavigationState {
@@ -33,6 +131,16 @@ impl NavigationState {
             entries: Vec::new(),
             selected_index: 0,
             scroll_offset: 0,
+            error: None,
+            show_files: false,
+            show_hidden: false,
+            sort: SortMode::Name,
+            prompt: None,
+            search: None,
+            layout: NavLayout::List,
+            parent: Vec::new(),
+            parent_selected: None,
+            preview: None,
         }
     }
 
@@ -41,62 +149,272 @@ impl NavigationState {
         self.current_path = path;
         self.selected_index = 0;
         self.scroll_offset = 0;
+        self.prompt = None;
+        self.search = None;
         self.refresh_entries();
     }
 
     /// Refresh the entries list from the current path
     pub fn refresh_entries(&mut self) {
         self.entries.clear();
+        self.error = None;
+        self.preview = None;
 
         // Add parent directory entry if not at root
-        if self.current_path.parent().is_some() {
+        if let Some(parent) = self.current_path.parent() {
             self.entries.push(DirEntry {
                 name: "..".to_string(),
-                path: self.current_path.parent().unwrap().to_path_buf(),
+                path: parent.to_path_buf(),
                 is_dir: true,
+                accessible: is_accessible(parent),
+                modified: None,
+                size: None,
             });
         }
 
         // Read directory entries
-        if let Ok(read_dir) = fs::read_dir(&self.current_path) {
-            let mut dirs: Vec<DirEntry> = read_dir
-                .filter_map(std::result::Result::ok)
-                .filter_map(|entry| {
-                    let path = entry.path();
-                    let is_dir = path.is_dir();
-
-                    // Only show directories in cd -list mode
-                    if !is_dir {
-                        return None;
-                    }
-
-                    let name = entry.file_name().to_string_lossy().to_string();
-
-                    // Skip hidden directories by default
-                    if name.starts_with('.') {
-                        return None;
-                    }
-
-                    Some(DirEntry { name, path, is_dir })
-                })
-                .collect();
-
-            // Sort alphabetically
-            dirs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        match self.read_entries(&self.current_path) {
+            Ok(entries) => self.entries.extend(entries),
+            Err(e) => self.error = Some(describe_read_error(&e)),
+        }
 
-            self.entries.extend(dirs);
+        if self.layout == NavLayout::Columns {
+            self.refresh_parent();
         }
 
-        // Reset selection if out of bounds
+        // Reset selection if out of bounds or on a file
         if self.selected_index >= self.entries.len() {
             self.selected_index = self.entries.len().saturating_sub(1);
         }
+        if !self
+            .entries
+            .get(self.selected_index)
+            .map_or(true, |e| e.is_dir)
+        {
+            self.selected_index = self.entries.iter().rposition(|e| e.is_dir).unwrap_or(0);
+        }
+    }
+
+    /// Entries of `dir` as listed with the current options: filtered,
+    /// directories first, and sorted
+    fn read_entries(&self, dir: &Path) -> io::Result<Vec<DirEntry>> {
+        let mut entries: Vec<DirEntry> = fs::read_dir(dir)?
+            .filter_map(std::result::Result::ok)
+            .filter_map(|entry| {
+                let path = entry.path();
+                let is_dir = path.is_dir();
+
+                // Files are only listed when toggled on
+                if !is_dir && !self.show_files {
+                    return None;
+                }
+
+                let name = entry.file_name().to_string_lossy().to_string();
+
+                // Skip hidden entries unless toggled on
+                if name.starts_with('.') && !self.show_hidden {
+                    return None;
+                }
+
+                let accessible = !is_dir || is_accessible(&path);
+                let metadata = entry.metadata().ok();
+                let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+                // Counting a directory's entries reads it, so only
+                // do it when the count is needed
+                let size = match self.sort {
+                    SortMode::Size if is_dir && accessible => count_entries(&path),
+                    SortMode::Size if is_dir => None,
+                    SortMode::Size => metadata.map(|m| m.len()),
+                    _ => None,
+                };
+                Some(DirEntry {
+                    name,
+                    path,
+                    is_dir,
+                    accessible,
+                    modified,
+                    size,
+                })
+            })
+            .collect();
+
+        // Directories before files, then in the chosen order
+        // (alphabetical among equals)
+        entries.sort_by_key(|entry| entry.name.to_lowercase());
+        match self.sort {
+            SortMode::Name => entries.sort_by_key(|entry| !entry.is_dir),
+            SortMode::Modified => {
+                entries.sort_by_key(|entry| (!entry.is_dir, Reverse(entry.modified)))
+            }
+            SortMode::Size => entries.sort_by_key(|entry| (!entry.is_dir, Reverse(entry.size))),
+        }
+        Ok(entries)
+    }
+
+    /// List the parent directory for the left column, marking the current one
+    fn refresh_parent(&mut self) {
+        self.parent = self
+            .current_path
+            .parent()
+            .and_then(|parent| self.read_entries(parent).ok())
+            .unwrap_or_default();
+        self.parent.retain(|entry| entry.is_dir);
+        self.parent_selected = self
+            .parent
+            .iter()
+            .position(|entry| entry.path == self.current_path);
+    }
+
+    /// Entries of the selected directory for the right column (cached until
+    /// the selection changes), or why they can't be listed
+    pub fn preview(&mut self) -> Option<&Result<Vec<DirEntry>, String>> {
+        let path = self.get_selected_path()?;
+        if self.preview.as_ref().map(|(p, _)| p) != Some(&path) {
+            let entries = self
+                .read_entries(&path)
+                .map_err(|e| describe_read_error(&e));
+            self.preview = Some((path, entries));
+        }
+        self.preview.as_ref().map(|(_, entries)| entries)
+    }
+
+    /// Switch between the single list and the three-column layout
+    pub fn toggle_layout(&mut self) {
+        self.layout = match self.layout {
+            NavLayout::List => NavLayout::Columns,
+            NavLayout::Columns => NavLayout::List,
+        };
+        self.refresh_keeping_selection();
+    }
+
+    /// Show or hide files
+    pub fn toggle_files(&mut self) {
+        self.show_files = !self.show_files;
+        self.refresh_keeping_selection();
+    }
+
+    /// Show or hide entries starting with a dot
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.refresh_keeping_selection();
+    }
+
+    /// Switch to the next sort mode
+    pub fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        self.refresh_keeping_selection();
+    }
+
+    /// Selected entry that can be renamed or deleted (anything but `..`)
+    fn selected_target(&self) -> Option<&DirEntry> {
+        self.selected_entry().filter(|entry| entry.name != "..")
+    }
+
+    /// Ask for the name of a new directory
+    pub fn begin_mkdir(&mut self) {
+        self.prompt = Some(NavPrompt {
+            action: PromptAction::Mkdir,
+            input: String::new(),
+        });
+    }
+
+    /// Ask for a new name for the selected entry
+    pub fn begin_rename(&mut self) {
+        if let Some(entry) = self.selected_target() {
+            self.prompt = Some(NavPrompt {
+                action: PromptAction::Rename(entry.path.clone()),
+                input: entry.name.clone(),
+            });
+        }
+    }
+
+    /// Ask to confirm deleting the selected entry
+    pub fn begin_delete(&mut self) {
+        if let Some(entry) = self.selected_target() {
+            self.prompt = Some(NavPrompt {
+                action: PromptAction::Delete(entry.path.clone()),
+                input: String::new(),
+            });
+        }
+    }
+
+    /// Close the prompt without doing anything
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    /// Run the prompt's file operation and close it. The listing is
+    /// refreshed, with a created or renamed entry selected. `cwd` can't be
+    /// deleted.
+    pub fn submit_prompt(&mut self, cwd: &Path) -> Result<(), FileOpError> {
+        let Some(prompt) = self.prompt.take() else {
+            return Ok(());
+        };
+        let selected = match prompt.action {
+            PromptAction::Mkdir => Some(fileops::make_dir(&self.current_path, &prompt.input)?),
+            PromptAction::Rename(path) => Some(fileops::rename(&path, &prompt.input)?),
+            PromptAction::Delete(path) => {
+                fileops::delete(&path, cwd)?;
+                None
+            }
+        };
+        self.refresh_entries();
+        let index = selected.and_then(|path| self.entries.iter().position(|e| e.path == path));
+        if let Some(index) = index {
+            self.selected_index = index;
+        }
+        Ok(())
+    }
+
+    /// Open a search of every directory below the current one
+    pub fn begin_search(&mut self) {
+        self.search = Some(DeepSearch::new(self.current_path.clone(), self.show_hidden));
+    }
+
+    /// Close the search, stopping its walk
+    pub fn end_search(&mut self) {
+        self.search = None;
+    }
+
+    /// Collect the search's latest matches, returning true if any arrived
+    pub fn poll_search(&mut self) -> bool {
+        self.search.as_mut().is_some_and(|search| search.poll())
+    }
+
+    /// Close the search and browse the selected match
+    pub fn browse_search_result(&mut self) {
+        let Some(path) = self
+            .search
+            .as_ref()
+            .and_then(|s| s.selected_path().cloned())
+        else {
+            return;
+        };
+        self.search = None;
+        self.current_path = path;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.refresh_entries();
     }
 
-    /// Move selection up
+    /// Refresh the entries, keeping the selected directory selected if it
+    /// is still listed
+    fn refresh_keeping_selection(&mut self) {
+        let selected = self.get_selected_path();
+        self.refresh_entries();
+        let index = selected.and_then(|path| self.entries.iter().position(|e| e.path == path));
+        if let Some(index) = index {
+            self.selected_index = index;
+        }
+    }
+
+    /// Move selection up, skipping files
     pub fn move_up(&mut self) {
-        if self.selected_index > 0 {
-            self.selected_index -= 1;
+        if let Some(index) = self.entries[..self.selected_index.min(self.entries.len())]
+            .iter()
+            .rposition(|e| e.is_dir)
+        {
+            self.selected_index = index;
 
             // Adjust scroll if needed
             if self.selected_index < self.scroll_offset {
@@ -105,10 +423,15 @@ impl NavigationState {
         }
     }
 
-    /// Move selection down
+    /// Move selection down, skipping files
     pub fn move_down(&mut self) {
-        if self.selected_index < self.entries.len().saturating_sub(1) {
-            self.selected_index += 1;
+        if let Some(offset) = self
+            .entries
+            .iter()
+            .skip(self.selected_index + 1)
+            .position(|e| e.is_dir)
+        {
+            self.selected_index += offset + 1;
         }
     }
 
@@ -124,7 +447,7 @@ impl NavigationState {
     /// Enter the selected directory (right arrow)
     pub fn enter_selected(&mut self) {
         if let Some(entry) = self.entries.get(self.selected_index) {
-            if entry.is_dir && entry.name != ".." {
+            if entry.is_dir && entry.accessible && entry.name != ".." {
                 self.current_path = entry.path.clone();
                 self.selected_index = 0;
                 self.scroll_offset = 0;
@@ -150,6 +473,11 @@ impl NavigationState {
             .map(|e| e.path.clone())
     }
 
+    /// Get the currently selected entry
+    pub fn selected_entry(&self) -> Option<&DirEntry> {
+        self.entries.get(self.selected_index)
+    }
+
     /// Get visible entries based on scroll offset
     pub fn get_visible_entries(&self, visible_height: usize) -> Vec<(usize, &DirEntry)> {
         self.entries
@@ -172,9 +500,38 @@ impl Default for NavigationState {
     }
 }
 
+/// Check whether a directory can be listed by the current user
+///
+/// Asks `access(2)` rather than opening the directory, so a listing
+/// doesn't open each of its subdirectories.
+fn is_accessible(path: &Path) -> bool {
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is NUL-terminated and outlives the call
+    unsafe { libc::access(path.as_ptr(), libc::R_OK | libc::X_OK) == 0 }
+}
+
+/// Number of entries in a directory, if it can be read
+fn count_entries(path: &Path) -> Option<u64> {
+    fs::read_dir(path)
+        .ok()
+        .map(|entries| entries.count() as u64)
+}
+
+/// Turn a directory read error into a short message for the navigator
+fn describe_read_error(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        io::ErrorKind::NotFound => "directory not found".to_string(),
+        _ => err.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::ScratchDir;
 
     #[test]
     fn test_navigation_state_new() {
@@ -197,6 +554,187 @@ mod tests {
         assert_eq!(state.selected_index, 0);
     }
 
+    #[test]
+    fn test_describe_permission_denied() {
+        let err = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(describe_read_error(&err), "permission denied");
+    }
+
+    #[test]
+    fn test_missing_directory_reports_error() {
+        let mut state = NavigationState::new();
+        state.start_navigation(PathBuf::from("/nonexistent/path/12345"));
+        assert_eq!(state.error.as_deref(), Some("directory not found"));
+    }
+
+    #[test]
+    fn test_is_accessible() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = ScratchDir::new("nav-locked");
+        assert!(is_accessible(&dir));
+        assert!(!is_accessible(Path::new("/nonexistent/path/12345")));
+
+        // Root can list anything, so only check the lock for other users
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o000)).unwrap();
+        // SAFETY: geteuid has no preconditions
+        if unsafe { libc::geteuid() } != 0 {
+            assert!(!is_accessible(&dir));
+        }
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_enter_inaccessible_is_noop() {
+        let mut state = NavigationState::new();
+        state.current_path = PathBuf::from("/tmp");
+        state.entries = vec![DirEntry {
+            name: "locked".to_string(),
+            path: PathBuf::from("/tmp/locked"),
+            is_dir: true,
+            accessible: false,
+            modified: None,
+            size: None,
+        }];
+        state.enter_selected();
+        assert_eq!(state.current_path, PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn test_files_are_listed_but_skipped() {
+        let root = ScratchDir::new("nav-files");
+        fs::create_dir_all(root.join("b_dir")).unwrap();
+        fs::write(root.join("a_file.txt"), "").unwrap();
+
+        let mut state = NavigationState::new();
+        state.start_navigation(root.to_path_buf());
+        let names = |state: &NavigationState| -> Vec<String> {
+            state.entries.iter().map(|e| e.name.clone()).collect()
+        };
+        assert_eq!(names(&state), vec!["..", "b_dir"]);
+
+        state.move_down();
+        state.toggle_files();
+        assert_eq!(names(&state), vec!["..", "b_dir", "a_file.txt"]);
+        assert_eq!(state.selected_index, 1);
+
+        // The file can't be selected
+        state.move_down();
+        assert_eq!(state.selected_index, 1);
+    }
+
+    #[test]
+    fn test_toggle_hidden() {
+        let root = ScratchDir::new("nav-hidden");
+        fs::create_dir_all(root.join(".config")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        let mut state = NavigationState::new();
+        state.start_navigation(root.to_path_buf());
+        state.move_down();
+        assert_eq!(state.entries.len(), 2);
+
+        state.toggle_hidden();
+        let names: Vec<&str> = state.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["..", ".config", "src"]);
+        assert_eq!(state.selected_entry().unwrap().name, "src");
+    }
+
+    #[test]
+    fn test_cycle_sort() {
+        let root = ScratchDir::new("nav-sort");
+        for (name, entries) in [("alpha", 1), ("beta", 3), ("gamma", 2)] {
+            for i in 0..entries {
+                fs::create_dir_all(root.join(name).join(i.to_string())).unwrap();
+            }
+        }
+        let hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        for (name, age) in [("alpha", 1), ("beta", 3), ("gamma", 2)] {
+            let dir = fs::File::open(root.join(name)).unwrap();
+            dir.set_modified(hour_ago - std::time::Duration::from_secs(age * 60))
+                .unwrap();
+        }
+
+        let mut state = NavigationState::new();
+        state.start_navigation(root.to_path_buf());
+        let names = |state: &NavigationState| -> Vec<String> {
+            state.entries.iter().map(|e| e.name.clone()).collect()
+        };
+        assert_eq!(names(&state), vec!["..", "alpha", "beta", "gamma"]);
+
+        state.move_down();
+        state.cycle_sort();
+        assert_eq!(state.sort, SortMode::Modified);
+        assert_eq!(names(&state), vec!["..", "alpha", "gamma", "beta"]);
+        assert_eq!(state.selected_entry().unwrap().name, "alpha");
+
+        state.cycle_sort();
+        assert_eq!(state.sort.label(), "size");
+        assert_eq!(names(&state), vec!["..", "beta", "gamma", "alpha"]);
+
+        state.cycle_sort();
+        assert_eq!(state.sort, SortMode::Name);
+    }
+
+    #[test]
+    fn test_prompt_file_ops() {
+        let root = ScratchDir::new("nav-ops");
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        let mut state = NavigationState::new();
+        state.start_navigation(root.to_path_buf());
+
+        state.begin_mkdir();
+        state.prompt.as_mut().unwrap().input.push_str("build");
+        state.submit_prompt(&root).unwrap();
+        assert!(state.prompt.is_none());
+        assert_eq!(state.selected_entry().unwrap().name, "build");
+
+        state.begin_rename();
+        assert_eq!(state.prompt.as_ref().unwrap().input, "build");
+        state.prompt.as_mut().unwrap().input = "src".to_string();
+        assert!(matches!(
+            state.submit_prompt(&root),
+            Err(FileOpError::Exists(_))
+        ));
+        assert!(state.prompt.is_none());
+
+        state.begin_delete();
+        state.submit_prompt(&root).unwrap();
+        let names: Vec<&str> = state.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["..", "src"]);
+
+        // The parent entry can't be renamed or deleted
+        state.selected_index = 0;
+        state.begin_delete();
+        assert!(state.prompt.is_none());
+    }
+
+    #[test]
+    fn test_columns_layout() {
+        let root = ScratchDir::new("nav-columns");
+        fs::create_dir_all(root.join("app/src")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("app/Cargo.toml"), "").unwrap();
+
+        let mut state = NavigationState::new();
+        state.start_navigation(root.join("app"));
+        assert!(state.parent.is_empty());
+
+        state.toggle_layout();
+        let names: Vec<&str> = state.parent.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "docs"]);
+        assert_eq!(state.parent_selected, Some(0));
+
+        // The preview follows the selection
+        state.move_down();
+        assert_eq!(state.selected_entry().unwrap().name, "src");
+        assert_eq!(state.preview().unwrap().as_ref().unwrap().len(), 0);
+        state.move_up();
+        let preview = state.preview().unwrap().as_ref().unwrap();
+        assert!(preview.iter().any(|e| e.name == "docs"));
+    }
+
     #[test]
     fn test_move_down_empty() {
         let mut state = NavigationState::new();
@@ -0,0 +1,111 @@
+//! File operations run from the navigator: create, rename, and delete
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FileOpError {
+    #[error("invalid name '{0}'")]
+    InvalidName(String),
+
+    #[error("'{0}' already exists")]
+    Exists(String),
+
+    #[error("'{0}' contains the current directory")]
+    InUse(String),
+
+    #[error("{0}")]
+    Io(#[from] io::Error),
+}
+
+/// Check that `name` is a single path component, and return it trimmed
+fn validate_name(name: &str) -> Result<&str, FileOpError> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        return Err(FileOpError::InvalidName(name.to_string()));
+    }
+    Ok(name)
+}
+
+/// Create the directory `name` inside `parent`
+pub fn make_dir(parent: &Path, name: &str) -> Result<PathBuf, FileOpError> {
+    let name = validate_name(name)?;
+    let path = parent.join(name);
+    if path.symlink_metadata().is_ok() {
+        return Err(FileOpError::Exists(name.to_string()));
+    }
+    fs::create_dir(&path)?;
+    Ok(path)
+}
+
+/// Rename `path` to `name` in the same directory
+pub fn rename(path: &Path, name: &str) -> Result<PathBuf, FileOpError> {
+    let name = validate_name(name)?;
+    let target = path.with_file_name(name);
+    if target == path {
+        return Ok(target);
+    }
+    if target.symlink_metadata().is_ok() {
+        return Err(FileOpError::Exists(name.to_string()));
+    }
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Delete `path`, with everything in it if it's a directory. Refuses to
+/// delete `cwd` or one of its parents.
+pub fn delete(path: &Path, cwd: &Path) -> Result<(), FileOpError> {
+    let metadata = path.symlink_metadata()?;
+    if metadata.is_symlink() || !metadata.is_dir() {
+        // A link is removed itself, never what it points to
+        fs::remove_file(path)?;
+        return Ok(());
+    }
+    if cwd.starts_with(path) {
+        let name = path.file_name().unwrap_or(path.as_os_str());
+        return Err(FileOpError::InUse(name.to_string_lossy().to_string()));
+    }
+    fs::remove_dir_all(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_file_ops() {
+        let root = ScratchDir::new("fileops");
+
+        let created = make_dir(&root, " build ").unwrap();
+        assert_eq!(created, root.join("build"));
+        assert!(matches!(
+            make_dir(&root, "build"),
+            Err(FileOpError::Exists(_))
+        ));
+        assert!(matches!(
+            make_dir(&root, "a/b"),
+            Err(FileOpError::InvalidName(_))
+        ));
+        assert!(matches!(
+            make_dir(&root, ".."),
+            Err(FileOpError::InvalidName(_))
+        ));
+
+        fs::write(created.join("out.txt"), "").unwrap();
+        let renamed = rename(&created, "dist").unwrap();
+        assert!(renamed.join("out.txt").exists());
+        assert!(!created.exists());
+
+        assert!(matches!(
+            delete(&renamed, &renamed.join("sub")),
+            Err(FileOpError::InUse(_))
+        ));
+        delete(&renamed, &root).unwrap();
+        assert!(!renamed.exists());
+    }
+}
@@ -1,3 +1,9 @@
+//! Browsing directories from the prompt
+
+pub mod diffdir;
 pub mod directory;
+pub mod fileops;
+pub mod search;
 
-pub use directory::NavigationState;
+pub use diffdir::DirDiff;
+pub use directory::{NavigationState, PromptAction};
@@ -0,0 +1,284 @@
+//! Deep search in the navigator: find directories anywhere below a path
+//!
+//! The walk runs on a background thread, breadth first so shallow matches
+//! come first, and sends matches back in batches as it finds them. Changing
+//! the query or closing the search cancels the walk.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+/// Most matches collected for one query
+pub const MAX_RESULTS: usize = 500;
+
+/// Directories read between two batches sent to the UI
+const BATCH_DIRS: usize = 64;
+
+/// A running or finished search under one directory
+#[derive(Debug)]
+pub struct DeepSearch {
+    /// Directory being searched
+    pub root: PathBuf,
+    /// Text matched against directory names, or against paths relative to
+    /// `root` when it contains a `/`
+    pub query: String,
+    /// Whether hidden directories are searched too
+    pub hidden: bool,
+    /// Matches found so far, shallowest first
+    pub results: Vec<PathBuf>,
+    /// Index of the selected match
+    pub selected: usize,
+    /// First visible match
+    pub scroll: usize,
+    /// Whether the walk has finished (or hit [`MAX_RESULTS`])
+    pub done: bool,
+    rx: Option<Receiver<Vec<PathBuf>>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl DeepSearch {
+    /// Open a search under `root`; nothing is walked until there's a query
+    pub fn new(root: PathBuf, hidden: bool) -> Self {
+        Self {
+            root,
+            query: String::new(),
+            hidden,
+            results: Vec::new(),
+            selected: 0,
+            scroll: 0,
+            done: true,
+            rx: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Add a character to the query and search again
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.restart();
+    }
+
+    /// Remove the last character of the query and search again
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.restart();
+    }
+
+    /// Stop the walk, keeping the matches found so far
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.rx = None;
+        self.done = true;
+    }
+
+    /// Cancel the current walk and start one for the current query
+    fn restart(&mut self) {
+        self.cancel();
+        self.results.clear();
+        self.selected = 0;
+        self.scroll = 0;
+        if self.query.is_empty() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (root, query, hidden) = (self.root.clone(), self.query.to_lowercase(), self.hidden);
+        let stop = Arc::clone(&cancel);
+        thread::spawn(move || {
+            walk(&root, &query, hidden, &stop, |batch| tx.send(batch).is_ok());
+        });
+        self.rx = Some(rx);
+        self.cancel = cancel;
+        self.done = false;
+    }
+
+    /// Collect matches the walk has sent since the last call
+    ///
+    /// Returns true if there were new matches or the walk finished.
+    pub fn poll(&mut self) -> bool {
+        let Some(ref rx) = self.rx else {
+            return false;
+        };
+        let mut changed = false;
+        loop {
+            match rx.try_recv() {
+                Ok(batch) => {
+                    self.results.extend(batch);
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.rx = None;
+                    self.done = true;
+                    changed = true;
+                    break;
+                }
+            }
+        }
+        self.results.truncate(MAX_RESULTS);
+        changed
+    }
+
+    /// Move the selection by `delta` matches
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.results.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Keep the selected match among the `visible` rows shown
+    pub fn adjust_scroll(&mut self, visible: usize) {
+        let visible = visible.max(1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + visible {
+            self.scroll = self.selected + 1 - visible;
+        }
+    }
+
+    /// The selected match
+    pub fn selected_path(&self) -> Option<&PathBuf> {
+        self.results.get(self.selected)
+    }
+
+    /// A match shown relative to the search root
+    pub fn display(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+impl Drop for DeepSearch {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Walk the directories below `root` breadth first, passing batches of those
+/// whose name contains `query` (lowercase) to `send`. A query with a `/` is
+/// matched against the path relative to `root` instead. Stops when
+/// `cancel` is set, `send` returns false, or [`MAX_RESULTS`] were found.
+/// Symlinks are not followed.
+fn walk(
+    root: &Path,
+    query: &str,
+    hidden: bool,
+    cancel: &AtomicBool,
+    mut send: impl FnMut(Vec<PathBuf>) -> bool,
+) {
+    let mut queue = VecDeque::from([root.to_path_buf()]);
+    let mut batch = Vec::new();
+    let mut found = 0;
+    let mut read = 0;
+
+    while let Some(dir) = queue.pop_front() {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut children: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .filter(|entry| hidden || !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| entry.path())
+            .collect();
+        children.sort();
+
+        for child in children {
+            // Names match unless the query spans directories
+            let haystack = if query.contains('/') {
+                child.strip_prefix(root).unwrap_or(&child).as_os_str()
+            } else {
+                child.file_name().unwrap_or_default()
+            };
+            if haystack.to_string_lossy().to_lowercase().contains(query) {
+                batch.push(child.clone());
+                found += 1;
+                if found >= MAX_RESULTS {
+                    send(batch);
+                    return;
+                }
+            }
+            queue.push_back(child);
+        }
+
+        read += 1;
+        if read % BATCH_DIRS == 0 && !batch.is_empty() && !send(std::mem::take(&mut batch)) {
+            return;
+        }
+    }
+    if !batch.is_empty() {
+        send(batch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_walk_finds_nested_matches() {
+        let root = ScratchDir::new("deep-search");
+        for dir in ["src/ui", "docs/ui-guide", "target/debug", ".git/ui"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+
+        let search = |hidden| {
+            let mut found = Vec::new();
+            walk(&root, "ui", hidden, &AtomicBool::new(false), |batch| {
+                found.extend(batch);
+                true
+            });
+            found
+        };
+        assert_eq!(
+            search(false),
+            vec![root.join("docs/ui-guide"), root.join("src/ui")]
+        );
+        assert_eq!(search(true).len(), 3);
+
+        let mut nested = Vec::new();
+        walk(&root, "src/", false, &AtomicBool::new(false), |batch| {
+            nested.extend(batch);
+            true
+        });
+        assert_eq!(nested, vec![root.join("src/ui")]);
+
+        let mut none = Vec::new();
+        walk(&root, "ui", false, &AtomicBool::new(true), |batch| {
+            none.extend(batch);
+            true
+        });
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_search_streams_results() {
+        let root = ScratchDir::new("deep-poll");
+        fs::create_dir_all(root.join("a/build")).unwrap();
+
+        let mut search = DeepSearch::new(root.to_path_buf(), false);
+        search.push_char('b');
+        search.push_char('u');
+        while !search.done {
+            search.poll();
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(search.results, vec![root.join("a/build")]);
+        assert_eq!(search.display(&search.results[0]), "a/build");
+
+        search.pop_char();
+        search.pop_char();
+        assert!(search.done);
+        assert!(search.results.is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::PathBuf;
+
+/// A note being edited: its text as lines, with a cursor
+#[derive(Debug, Clone)]
+pub struct Scratchpad {
+    /// Directory the note belongs to
+    pub dir: PathBuf,
+    pub lines: Vec<String>,
+    /// Cursor line
+    pub row: usize,
+    /// Cursor position within the line, in characters
+    pub col: usize,
+    /// First line shown
+    pub scroll: usize,
+}
+
+impl Scratchpad {
+    /// Start editing a note, with the cursor at the end of its text
+    pub fn new(dir: PathBuf, text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self {
+            dir,
+            lines,
+            row,
+            col,
+            scroll: 0,
+        }
+    }
+
+    /// The note's full text
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Byte offset of the cursor in the current line
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map_or(line.len(), |(index, _)| index)
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    /// Insert a character at the cursor
+    pub fn insert_char(&mut self, c: char) {
+        let index = self.byte_index();
+        self.lines[self.row].insert(index, c);
+        self.col += 1;
+    }
+
+    /// Split the line at the cursor
+    pub fn insert_newline(&mut self) {
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    /// Delete the character before the cursor, joining lines at the start
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    /// Delete the character under the cursor, joining lines at the end
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.col = self.line_len(self.row);
+    }
+
+    /// Adjust scroll so the cursor line is visible
+    pub fn adjust_scroll(&mut self, visible_height: usize) {
+        if self.row >= self.scroll + visible_height {
+            self.scroll = self.row.saturating_sub(visible_height.saturating_sub(1));
+        } else if self.row < self.scroll {
+            self.scroll = self.row;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editing() {
+        let mut pad = Scratchpad::new(PathBuf::from("/p"), "todo\nfix é");
+        assert_eq!((pad.row, pad.col), (1, 5));
+
+        pad.backspace();
+        pad.insert_char('x');
+        pad.move_home();
+        pad.insert_newline();
+        assert_eq!(pad.text(), "todo\n\nfix x");
+
+        pad.backspace();
+        pad.backspace();
+        assert_eq!(pad.text(), "todofix x");
+        assert_eq!((pad.row, pad.col), (0, 4));
+
+        pad.move_end();
+        pad.delete();
+        pad.move_left();
+        pad.delete();
+        assert_eq!(pad.text(), "todofix ");
+    }
+}
@@ -0,0 +1,10 @@
+//! Per-directory scratchpad notes (`jerm note`)
+//!
+//! Each directory can have one free-form note, edited in a pane over the
+//! terminal. Directories with a note show a badge in the prompt.
+
+pub mod editor;
+pub mod storage;
+
+pub use editor::Scratchpad;
+pub use storage::NoteStore;
@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::schema::{self, Schema, SchemaError};
+
+/// Errors that can occur while reading or writing notes
+#[derive(Error, Debug)]
+pub enum NoteError {
+    #[error("Failed to access notes file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse notes file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Format(#[from] SchemaError),
+
+    #[error("Config directory not found")]
+    ConfigDirNotFound,
+}
+
+/// Format history of `notes.json`
+pub const SCHEMA: Schema = Schema {
+    file: "notes.json",
+    migrations: &[schema::unversioned],
+};
+
+/// Container for all notes, keyed by directory (for JSON serialization)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NotesData {
+    pub notes: BTreeMap<PathBuf, String>,
+}
+
+/// Notes of every directory, kept in memory and written on each change
+pub struct NoteStore {
+    data: NotesData,
+    /// When set, changes are kept in memory and never written to disk
+    readonly: bool,
+    /// Why the file couldn't be read; it's left untouched until fixed
+    load_error: Option<String>,
+}
+
+impl NoteStore {
+    /// Create a store, loading notes from disk
+    pub fn new() -> Self {
+        let mut store = Self {
+            data: NotesData::default(),
+            readonly: false,
+            load_error: None,
+        };
+        store.reload();
+        store
+    }
+
+    /// Why the notes file couldn't be read, if it couldn't
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Enable or disable readonly mode (no writes to disk)
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    /// Reload notes from disk (after switching profiles)
+    pub fn reload(&mut self) {
+        (self.data, self.load_error) = match load_notes() {
+            Ok(data) => (data, None),
+            Err(e) => (NotesData::default(), Some(e.to_string())),
+        };
+    }
+
+    /// The note for a directory, if it has one
+    pub fn get(&self, dir: &Path) -> Option<&str> {
+        self.data.notes.get(dir).map(String::as_str)
+    }
+
+    /// Whether a directory has a note
+    pub fn has(&self, dir: &Path) -> bool {
+        self.data.notes.contains_key(dir)
+    }
+
+    /// Replace a directory's note; a blank note is removed
+    pub fn set(&mut self, dir: &Path, text: &str) -> Result<(), NoteError> {
+        let text = text.trim_end();
+        let changed = if text.trim().is_empty() {
+            self.data.notes.remove(dir).is_some()
+        } else {
+            self.data.notes.insert(dir.to_path_buf(), text.to_string()) != Some(text.to_string())
+        };
+
+        if changed && !self.readonly && self.load_error.is_none() {
+            save_notes(&self.data)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for NoteStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the path to the notes file
+pub fn get_notes_path() -> Result<PathBuf, NoteError> {
+    let config_dir = crate::paths::config_dir().ok_or(NoteError::ConfigDirNotFound)?;
+    Ok(config_dir.join("notes.json"))
+}
+
+/// Load all notes from disk
+pub fn load_notes() -> Result<NotesData, NoteError> {
+    Ok(SCHEMA.load(&get_notes_path()?)?)
+}
+
+/// Save all notes to disk
+pub fn save_notes(data: &NotesData) -> Result<(), NoteError> {
+    Ok(SCHEMA.save(&get_notes_path()?, data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_clear() {
+        let mut store = NoteStore {
+            data: NotesData::default(),
+            readonly: true,
+            load_error: None,
+        };
+        let dir = Path::new("/tmp/project");
+
+        store.set(dir, "- fix the build\n\n").unwrap();
+        assert_eq!(store.get(dir), Some("- fix the build"));
+        assert!(store.has(dir));
+
+        store.set(dir, "  \n").unwrap();
+        assert!(!store.has(dir));
+    }
+}
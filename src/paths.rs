@@ -0,0 +1,124 @@
+//! Locations of jerm's files on disk
+//!
+//! Everything jerm stores lives in the config directory of the active
+//! profile. The default profile uses `~/.config/jerm` directly (or
+//! `$JERM_DATA_DIR` when set); a named profile uses `profiles/<name>` inside
+//! it.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Name of the active profile (`None` for the default profile)
+static ACTIVE_PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Name used to refer to the default profile
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Environment variable that moves all of jerm's files to another directory
+pub const DATA_DIR_VAR: &str = "JERM_DATA_DIR";
+
+/// Get jerm's top-level config directory, shared by all profiles
+pub fn root_config_dir() -> Option<PathBuf> {
+    match env::var_os(DATA_DIR_VAR).filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => dirs::config_dir().map(|dir| dir.join("jerm")),
+    }
+}
+
+/// Create `dir` if needed and check that a file can be written in it
+pub fn probe_writable(dir: &Path) -> io::Result<()> {
+    let probe = dir.join(".jerm-probe");
+    fs::create_dir_all(dir)?;
+    fs::write(&probe, "")?;
+    fs::remove_file(&probe)
+}
+
+/// Whether the active profile's files can be saved
+pub fn is_writable() -> bool {
+    config_dir().is_some_and(|dir| probe_writable(&dir).is_ok())
+}
+
+/// Get the config directory of the active profile
+pub fn config_dir() -> Option<PathBuf> {
+    let root = root_config_dir()?;
+    match active_profile() {
+        Some(name) => Some(root.join("profiles").join(name)),
+        None => Some(root),
+    }
+}
+
+/// Get the name of the active profile (`None` for the default profile)
+pub fn active_profile() -> Option<String> {
+    ACTIVE_PROFILE.read().ok().and_then(|p| p.clone())
+}
+
+/// Switch the active profile; `default` (or `None`) selects the default profile
+pub fn set_active_profile(name: Option<&str>) {
+    let name = name.filter(|n| *n != DEFAULT_PROFILE).map(String::from);
+    if let Ok(mut active) = ACTIVE_PROFILE.write() {
+        *active = name;
+    }
+}
+
+/// List named profiles that exist on disk, sorted alphabetically
+pub fn list_profiles() -> Vec<String> {
+    let Some(dir) = root_config_dir().map(|root| root.join("profiles")) else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = read_dir
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| is_valid_name(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Check that a name (profile, macro, ...) is safe to use as a file name
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_valid_names() {
+        assert!(is_valid_name("work"));
+        assert!(is_valid_name("client_a-2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../evil"));
+        assert!(!is_valid_name("has space"));
+    }
+
+    #[test]
+    fn test_probe_writable() {
+        let dir = ScratchDir::new("probe");
+        assert!(probe_writable(&dir.join("nested")).is_ok());
+        assert!(!dir.join("nested").join(".jerm-probe").exists());
+        assert!(probe_writable(Path::new("/proc/jerm")).is_err());
+    }
+
+    #[test]
+    fn test_profile_dirs() {
+        let root = root_config_dir().unwrap();
+        set_active_profile(Some("work"));
+        assert_eq!(config_dir(), Some(root.join("profiles").join("work")));
+        set_active_profile(Some(DEFAULT_PROFILE));
+        assert_eq!(config_dir(), Some(root));
+        assert_eq!(active_profile(), None);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use serde_json::json;
+
+/// Writes terminal output as an asciinema v2 `.cast` stream
+///
+/// The first line is a JSON header; every following line is an output
+/// event `[seconds, "o", data]`.
+pub struct CastRecorder<F: Write> {
+    file: F,
+    start: Instant,
+    /// Bytes written since the last event (flushed once per frame)
+    pending: Vec<u8>,
+}
+
+impl CastRecorder<BufWriter<File>> {
+    /// Create a recorder writing to the file at `path`
+    pub fn create(path: &Path, width: u16, height: u16) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        Self::new(file, width, height)
+    }
+}
+
+impl<F: Write> CastRecorder<F> {
+    /// Create a recorder, writing the cast header immediately
+    pub fn new(mut file: F, width: u16, height: u16) -> io::Result<Self> {
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": chrono::Utc::now().timestamp(),
+            "env": {
+                "SHELL": std::env::var("SHELL").unwrap_or_default(),
+                "TERM": std::env::var("TERM").unwrap_or_default(),
+            },
+        });
+        writeln!(file, "{header}")?;
+
+        Ok(Self {
+            file,
+            start: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Buffer output bytes until the next flush
+    pub fn record(&mut self, data: &[u8]) {
+        self.pending.extend_from_slice(data);
+    }
+
+    /// Emit buffered output as a single timed event
+    pub fn flush_event(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        // Keep an incomplete UTF-8 sequence at the end for the next event
+        let valid_up_to = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        if valid_up_to == 0 {
+            return Ok(());
+        }
+        let rest = self.pending.split_off(valid_up_to);
+        let data = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending = rest;
+
+        let elapsed = self.start.elapsed().as_secs_f64();
+        writeln!(self.file, "{}", json!([elapsed, "o", data]))?;
+        self.file.flush()
+    }
+
+    /// Consume the recorder and return the underlying writer
+    #[cfg(test)]
+    pub fn into_inner(mut self) -> io::Result<F> {
+        self.flush_event()?;
+        Ok(self.file)
+    }
+}
+
+/// Writer that forwards everything to `inner` and optionally tees it to a recorder
+///
+/// Used as the crossterm backend's output so recordings capture exactly what
+/// was rendered.
+pub struct RecordingWriter<W: Write> {
+    inner: W,
+    recorder: Option<CastRecorder<BufWriter<File>>>,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// Wrap a writer, recording its output when a recorder is given
+    pub fn new(inner: W, recorder: Option<CastRecorder<BufWriter<File>>>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<W: Write> Write for RecordingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.record(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.flush_event()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(recorder: CastRecorder<Vec<u8>>) -> Vec<serde_json::Value> {
+        let bytes = recorder.into_inner().unwrap();
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_header() {
+        let recorder = CastRecorder::new(Vec::new(), 80, 24).unwrap();
+        let lines = lines(recorder);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["height"], 24);
+    }
+
+    #[test]
+    fn test_output_event() {
+        let mut recorder = CastRecorder::new(Vec::new(), 80, 24).unwrap();
+        recorder.record(b"\x1b[1mhello");
+        recorder.flush_event().unwrap();
+        let lines = lines(recorder);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "\u{1b}[1mhello");
+    }
+
+    #[test]
+    fn test_split_utf8_is_kept_for_next_event() {
+        let mut recorder = CastRecorder::new(Vec::new(), 80, 24).unwrap();
+        let bytes = "é".as_bytes();
+        recorder.record(&bytes[..1]);
+        recorder.flush_event().unwrap();
+        recorder.record(&bytes[1..]);
+        let lines = lines(recorder);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1][2], "é");
+    }
+}
@@ -0,0 +1,5 @@
+//! Session recording in asciinema v2 format
+
+pub mod cast;
+
+pub use cast::{CastRecorder, RecordingWriter};
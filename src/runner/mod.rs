@@ -0,0 +1,234 @@
+//! Running a project's tests from inside jerm
+//!
+//! `jerm test` finds the project around the current directory (see
+//! [`detect`]), runs its test command through the executor, and reads the
+//! counts and failing tests out of the output as it finishes. In watch mode
+//! a filesystem watcher on the project root reruns the tests shortly after
+//! a file changes, skipping build and dependency directories so a test run
+//! doesn't trigger itself.
+
+mod project;
+mod report;
+
+use std::collections::HashSet;
+use std::path::{Component, Path};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::events::AppEvent;
+use crate::shell::env::Environment;
+use crate::shell::executor::RunningCommand;
+
+pub use project::{detect, Project};
+pub use report::{Failure, TestReport};
+
+/// How long changes must settle before a watched project reruns
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Directories whose changes never trigger a rerun
+const IGNORED_DIRS: &[&str] = &[
+    ".git",
+    "target",
+    "node_modules",
+    "__pycache__",
+    ".pytest_cache",
+    "coverage",
+];
+
+/// A finished test run
+#[derive(Debug, Clone)]
+pub struct Finished {
+    pub report: TestReport,
+    pub code: i32,
+    pub duration: Duration,
+}
+
+/// The test view's runs of one project
+pub struct TestRunner {
+    pub project: Project,
+    /// Environment the runs start with
+    env: Environment,
+    /// Output of the current run, or of the last one once it finished
+    pub output: Vec<String>,
+    /// Result of the last finished run
+    pub last: Option<Finished>,
+    /// Selected failure of the last run
+    pub selected: usize,
+    /// Failures whose output is shown
+    pub expanded: HashSet<String>,
+    /// First row shown
+    pub scroll: usize,
+    running: Option<RunningCommand>,
+    started: Instant,
+    /// Events the watcher sends, for starting it later
+    tx: Sender<AppEvent>,
+    /// Set while watching; dropping it stops the watch
+    watcher: Option<RecommendedWatcher>,
+    /// When a watched file last changed, until the rerun it causes
+    changed_at: Option<Instant>,
+}
+
+impl TestRunner {
+    pub fn new(project: Project, env: Environment, tx: Sender<AppEvent>) -> Self {
+        Self {
+            project,
+            env,
+            output: Vec::new(),
+            last: None,
+            selected: 0,
+            expanded: HashSet::new(),
+            scroll: 0,
+            running: None,
+            started: Instant::now(),
+            tx,
+            watcher: None,
+            changed_at: None,
+        }
+    }
+
+    /// Start a run, stopping the one in progress
+    pub fn run(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.kill();
+        }
+        self.output.clear();
+        self.started = Instant::now();
+        match RunningCommand::spawn(&self.project.command, &self.project.root, &self.env) {
+            Ok(running) => self.running = Some(running),
+            Err(e) => self.output.push(format!("jerm test: {e}")),
+        }
+    }
+
+    /// How long the current run has taken, while there is one
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.running.as_ref().map(|_| self.started.elapsed())
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Start or stop rerunning on file changes
+    pub fn toggle_watch(&mut self) {
+        if self.watcher.take().is_some() {
+            self.changed_at = None;
+            return;
+        }
+        let tx = self.tx.clone();
+        let root = self.project.root.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            if event.paths.iter().any(|path| !is_ignored(&root, path)) {
+                let _ = tx.send(AppEvent::FilesChanged);
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&self.project.root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        match watcher {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => self
+                .output
+                .push(format!("jerm test: can't watch for changes: {e}")),
+        }
+    }
+
+    /// Note a change under the project root, rerunning once changes settle
+    pub fn files_changed(&mut self) {
+        if self.is_watching() {
+            self.changed_at = Some(Instant::now());
+        }
+    }
+
+    /// Collect output and finish the run once the command exits; true when
+    /// anything changed
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        if self.changed_at.is_some_and(|at| at.elapsed() >= DEBOUNCE) {
+            self.changed_at = None;
+            self.run();
+            changed = true;
+        }
+
+        let Some(running) = self.running.as_mut() else {
+            return changed;
+        };
+        let lines = running.drain_output();
+        changed |= !lines.is_empty();
+        self.output.extend(lines.into_iter().map(|(_, line)| line));
+
+        if let Some(code) = running.try_finish() {
+            self.running = None;
+            let report = TestReport::parse(&self.output);
+            self.selected = self.selected.min(report.failures.len().saturating_sub(1));
+            self.last = Some(Finished {
+                report,
+                code,
+                duration: self.started.elapsed(),
+            });
+            changed = true;
+        }
+        changed
+    }
+
+    /// Failures of the last run
+    pub fn failures(&self) -> &[Failure] {
+        self.last.as_ref().map_or(&[], |last| &last.report.failures)
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.failures().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Show or hide the selected failure's output
+    pub fn toggle_expanded(&mut self) {
+        let Some(name) = self.failures().get(self.selected).map(|f| f.name.clone()) else {
+            return;
+        };
+        if !self.expanded.remove(&name) {
+            self.expanded.insert(name);
+        }
+    }
+}
+
+impl Drop for TestRunner {
+    fn drop(&mut self) {
+        if let Some(running) = &self.running {
+            running.kill();
+        }
+    }
+}
+
+/// Whether a change at `path` is in a directory that never reruns tests
+fn is_ignored(root: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.components().any(|component| match component {
+        Component::Normal(name) => IGNORED_DIRS.iter().any(|dir| name == *dir),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let root = Path::new("/src/app");
+        assert!(is_ignored(root, Path::new("/src/app/target/debug/app")));
+        assert!(is_ignored(
+            root,
+            Path::new("/src/app/web/node_modules/x.js")
+        ));
+        assert!(!is_ignored(root, Path::new("/src/app/src/main.rs")));
+    }
+}
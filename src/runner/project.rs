@@ -0,0 +1,115 @@
+//! Which test command a directory's project uses
+//!
+//! The nearest directory (walking up from the current one) with a project
+//! manifest decides: `Cargo.toml`, `package.json` with a `test` script,
+//! `go.mod`, a pytest configuration, or a Makefile with a `test` target.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// npm's placeholder script for packages without tests
+const NPM_NO_TEST: &str = "echo \"Error: no test specified\" && exit 1";
+
+/// Files marking a Python project that pytest runs
+const PYTEST_MARKERS: &[&str] = &[
+    "pytest.ini",
+    "pyproject.toml",
+    "setup.cfg",
+    "tox.ini",
+    "conftest.py",
+];
+
+/// A project and how its tests run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// Directory the test command runs in
+    pub root: PathBuf,
+    pub command: String,
+}
+
+/// The project containing `dir`, if it has tests jerm knows how to run
+pub fn detect(dir: &Path) -> Option<Project> {
+    dir.ancestors().find_map(|root| {
+        let command = test_command(root)?;
+        Some(Project {
+            root: root.to_path_buf(),
+            command,
+        })
+    })
+}
+
+/// Test command of the project rooted at `root`
+fn test_command(root: &Path) -> Option<String> {
+    if root.join("Cargo.toml").is_file() {
+        return Some("cargo test".to_string());
+    }
+    if let Some(command) = npm_test(root) {
+        return Some(command);
+    }
+    if root.join("go.mod").is_file() {
+        return Some("go test ./...".to_string());
+    }
+    if PYTEST_MARKERS.iter().any(|file| root.join(file).is_file()) {
+        return Some("pytest".to_string());
+    }
+    let makefile = fs::read_to_string(root.join("Makefile")).ok()?;
+    makefile
+        .lines()
+        .any(|line| line.starts_with("test:"))
+        .then(|| "make test".to_string())
+}
+
+/// `<manager> test` for a package with a real `test` script, the manager
+/// picked by its lockfile
+fn npm_test(root: &Path) -> Option<String> {
+    let package = fs::read_to_string(root.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&package).ok()?;
+    let script = package.get("scripts")?.get("test")?.as_str()?;
+    if script == NPM_NO_TEST {
+        return None;
+    }
+    let manager = if root.join("pnpm-lock.yaml").is_file() {
+        "pnpm"
+    } else if root.join("yarn.lock").is_file() {
+        "yarn"
+    } else {
+        "npm"
+    };
+    Some(format!("{manager} test"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_detect() {
+        let dir = ScratchDir::new("runner-project");
+        let nested = dir.join("web/src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.join("go.mod"), "module example.com/app\n").unwrap();
+
+        fs::write(
+            dir.join("web/package.json"),
+            format!(
+                r#"{{"scripts": {{"test": "{}"}}}}"#,
+                NPM_NO_TEST.replace('"', "\\\"")
+            ),
+        )
+        .unwrap();
+        let project = detect(&nested).unwrap();
+        assert_eq!(project.root, dir.path());
+        assert_eq!(project.command, "go test ./...");
+
+        fs::write(
+            dir.join("web/package.json"),
+            r#"{"scripts": {"test": "jest"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("web/yarn.lock"), "").unwrap();
+        let project = detect(&nested).unwrap();
+        assert_eq!(project.root, dir.join("web"));
+        assert_eq!(project.command, "yarn test");
+    }
+}
@@ -0,0 +1,248 @@
+//! Pass/fail counts and failing tests read from a test run's output
+//!
+//! Understands the summaries and failure sections of `cargo test`,
+//! pytest, `go test`, and Jest. Counts come from the summary lines
+//! (`test result: ...`, `== 2 failed, 10 passed ==`, `Tests: ...`);
+//! failures from the lines naming them, each with the output printed in
+//! its section.
+
+use crate::ansi;
+
+/// A failing test and what it printed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub name: String,
+    pub output: Vec<String>,
+}
+
+/// What a test run reported
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    pub passed: usize,
+    pub failed: usize,
+    /// Ignored or skipped tests
+    pub ignored: usize,
+    pub failures: Vec<Failure>,
+    /// Whether a summary line gave the counts
+    pub counted: bool,
+}
+
+impl TestReport {
+    /// Read the output of a test run
+    pub fn parse(lines: &[String]) -> Self {
+        let mut report = Self::default();
+        // Failure whose section the following lines belong to
+        let mut section: Option<usize> = None;
+        // `go test` indents a failure's output, and its section ends at
+        // the first line that isn't
+        let mut indented = false;
+        // `go test -v` lists passes but has no summary counts
+        let mut go_passed = 0;
+
+        for line in lines {
+            let text = ansi::strip(line);
+            let trimmed = text.trim();
+            if indented && !text.starts_with(char::is_whitespace) {
+                section = None;
+                indented = false;
+            }
+
+            // Summaries
+            let summary = text
+                .strip_prefix("test result: ")
+                .or_else(|| trimmed.strip_prefix("Tests:"))
+                .or_else(|| {
+                    let inner = trimmed.strip_prefix('=')?.strip_suffix('=')?;
+                    (inner.contains(" passed") || inner.contains(" failed"))
+                        .then(|| inner.trim_matches('='))
+                });
+            if let Some(summary) = summary {
+                report.add_counts(summary);
+                section = None;
+                continue;
+            }
+
+            // Lines naming a failure
+            if let Some(name) = text
+                .strip_prefix("test ")
+                .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+            {
+                report.failure(name);
+                continue;
+            }
+            if let Some(rest) = text.strip_prefix("FAILED ") {
+                report.failure(rest.split(" - ").next().unwrap_or(rest).trim());
+                continue;
+            }
+            if let Some(rest) = trimmed.strip_prefix("--- FAIL: ") {
+                let name = rest.split_whitespace().next().unwrap_or(rest);
+                section = Some(report.failure(name));
+                indented = true;
+                continue;
+            }
+            if trimmed.starts_with("--- PASS: ") {
+                go_passed += 1;
+                continue;
+            }
+
+            // Headers of the sections with a failure's output
+            let header = text
+                .strip_prefix("---- ")
+                .and_then(|rest| rest.strip_suffix(" stdout ----"))
+                .map(str::to_string)
+                .or_else(|| {
+                    let name = trimmed.strip_prefix("___")?.strip_suffix("___")?;
+                    Some(name.trim_matches('_').trim().replace('.', "::"))
+                })
+                .or_else(|| trimmed.strip_prefix("\u{25cf} ").map(str::to_string));
+            if let Some(name) = header {
+                section = Some(report.failure(&name));
+                continue;
+            }
+            if text == "failures:" || trimmed.starts_with("====") {
+                section = None;
+                continue;
+            }
+
+            if let Some(index) = section {
+                report.failures[index].output.push(text);
+            }
+        }
+
+        for failure in &mut report.failures {
+            while failure.output.last().is_some_and(|l| l.trim().is_empty()) {
+                failure.output.pop();
+            }
+            let blank = failure
+                .output
+                .iter()
+                .take_while(|l| l.trim().is_empty())
+                .count();
+            failure.output.drain(..blank);
+        }
+        if !report.counted {
+            report.failed = report.failures.len();
+            report.passed = go_passed;
+        }
+        report
+    }
+
+    /// Add the counts of a summary such as `ok. 3 passed; 1 failed`
+    fn add_counts(&mut self, summary: &str) {
+        let words: Vec<&str> = summary.split_whitespace().collect();
+        for pair in words.windows(2) {
+            let Ok(count) = pair[0].parse::<usize>() else {
+                continue;
+            };
+            match pair[1].trim_matches(|c: char| !c.is_alphabetic()) {
+                "passed" => self.passed += count,
+                "failed" | "error" | "errors" => self.failed += count,
+                "ignored" | "skipped" => self.ignored += count,
+                _ => continue,
+            }
+            self.counted = true;
+        }
+    }
+
+    /// Index of the failure named `name`, added if it's new
+    ///
+    /// Names match when one is the other's last `::` parts, since pytest
+    /// heads sections with just the test function.
+    fn failure(&mut self, name: &str) -> usize {
+        let same = |other: &str| {
+            other == name
+                || other.ends_with(&format!("::{name}"))
+                || name.ends_with(&format!("::{other}"))
+        };
+        if let Some(index) = self.failures.iter().position(|f| same(&f.name)) {
+            let failure = &mut self.failures[index];
+            if name.len() > failure.name.len() {
+                failure.name = name.to_string();
+            }
+            return index;
+        }
+        self.failures.push(Failure {
+            name: name.to_string(),
+            output: Vec::new(),
+        });
+        self.failures.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_cargo() {
+        let output = lines(
+            "running 3 tests
+test tests::ok ... ok
+test tests::bad ... FAILED
+test tests::skip ... ignored
+
+failures:
+
+---- tests::bad stdout ----
+thread 'tests::bad' panicked at src/lib.rs:9:5:
+assertion failed
+
+failures:
+    tests::bad
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+test result: ok. 4 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out",
+        );
+        let report = TestReport::parse(&output);
+        assert_eq!((report.passed, report.failed, report.ignored), (5, 1, 1));
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].name, "tests::bad");
+        assert_eq!(report.failures[0].output.len(), 2);
+    }
+
+    #[test]
+    fn test_pytest_go_and_jest() {
+        let pytest = lines(
+            "=================================== FAILURES ===================================
+__________________________________ test_add ___________________________________
+    def test_add():
+>       assert 1 == 2
+=========================== short test summary info ============================
+FAILED tests/test_math.py::test_add - assert 1 == 2
+========================= 1 failed, 3 passed in 0.05s ==========================",
+        );
+        let report = TestReport::parse(&pytest);
+        assert_eq!((report.passed, report.failed), (3, 1));
+        assert_eq!(report.failures[0].name, "tests/test_math.py::test_add");
+        assert_eq!(report.failures[0].output[1], ">       assert 1 == 2");
+
+        let go = lines(
+            "--- PASS: TestOk (0.00s)
+--- FAIL: TestBad (0.00s)
+    math_test.go:9: got 1, want 2
+FAIL",
+        );
+        let report = TestReport::parse(&go);
+        assert_eq!((report.passed, report.failed), (1, 1));
+        assert_eq!(
+            report.failures[0].output,
+            ["    math_test.go:9: got 1, want 2"]
+        );
+
+        let jest = lines(
+            "  \u{25cf} math \u{203a} adds
+
+    expect(received).toBe(expected)
+
+Tests:       1 failed, 5 passed, 6 total",
+        );
+        let report = TestReport::parse(&jest);
+        assert_eq!((report.passed, report.failed), (5, 1));
+        assert_eq!(report.failures[0].name, "math \u{203a} adds");
+        assert_eq!(report.failures[0].output.len(), 1);
+    }
+}
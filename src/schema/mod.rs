@@ -0,0 +1,216 @@
+//! Format versions of the JSON files in the config directory
+//!
+//! Each file keeps a top-level `"version"`; a file without one predates
+//! versioning and counts as version 0. Reading runs the file's
+//! [`Migration`]s in order to bring an older file up to date in memory, and
+//! the first save afterwards keeps the original as `<file>.v<N>.bak`. A
+//! file from a newer jerm, or one that isn't valid JSON, is an error rather
+//! than empty data, and is never overwritten.
+//!
+//! History is left out: it's one JSON entry per line, read line by line so
+//! a bad write only loses itself.
+
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Upgrade a file's top-level object by one version
+pub type Migration = fn(&mut Map<String, Value>);
+
+/// Errors that can occur while reading or writing a versioned file
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("Failed to access {file}: {source}")]
+    Io {
+        file: &'static str,
+        source: std::io::Error,
+    },
+
+    #[error("{file} is not valid: {reason}; fix or remove it, jerm won't overwrite it")]
+    Invalid { file: &'static str, reason: String },
+
+    #[error(
+        "{file} is format version {found}, newer than this jerm reads ({supported}); \
+         upgrade jerm to use it"
+    )]
+    Newer {
+        file: &'static str,
+        found: u64,
+        supported: u64,
+    },
+}
+
+/// Layout history of one file
+pub struct Schema {
+    /// File name, for messages and backups
+    pub file: &'static str,
+    /// `migrations[n]` upgrades a version `n` file to version `n + 1`
+    pub migrations: &'static [Migration],
+}
+
+/// The step to version 1 for files that only gained the version field
+pub fn unversioned(_: &mut Map<String, Value>) {}
+
+impl Schema {
+    /// Version written by this build
+    pub fn version(&self) -> u64 {
+        self.migrations.len() as u64
+    }
+
+    /// Parse a file's contents, migrating them when they're older
+    pub fn parse<T: DeserializeOwned>(&self, contents: &str) -> Result<T, SchemaError> {
+        let (version, mut object) = self.read_object(contents)?;
+        for migrate in &self.migrations[version as usize..] {
+            migrate(&mut object);
+        }
+        serde_json::from_value(Value::Object(object)).map_err(|e| self.invalid(e))
+    }
+
+    /// Contents for `data`, stamped with the current version
+    pub fn to_string<T: Serialize>(&self, data: &T) -> Result<String, SchemaError> {
+        let Value::Object(mut object) = serde_json::to_value(data).map_err(|e| self.invalid(e))?
+        else {
+            return Err(self.invalid("not an object"));
+        };
+        object.insert("version".to_string(), Value::from(self.version()));
+        serde_json::to_string_pretty(&object).map_err(|e| self.invalid(e))
+    }
+
+    /// Read `path`, or the default when there's no file yet
+    pub fn load<T: DeserializeOwned + Default>(&self, path: &Path) -> Result<T, SchemaError> {
+        if !path.exists() {
+            return Ok(T::default());
+        }
+        let contents = fs::read_to_string(path).map_err(|e| self.io(e))?;
+        self.parse(&contents)
+    }
+
+    /// Write `data` to `path`, keeping a backup of an older file and
+    /// refusing to replace one this build can't read
+    pub fn save<T: Serialize + DeserializeOwned>(
+        &self,
+        path: &Path,
+        data: &T,
+    ) -> Result<(), SchemaError> {
+        let contents = self.to_string(data)?;
+        if let Ok(existing) = fs::read_to_string(path) {
+            // Valid JSON of the right version can still fail to load
+            self.parse::<T>(&existing)?;
+            let (version, _) = self.read_object(&existing)?;
+            let backup = path.with_file_name(format!("{}.v{version}.bak", self.file));
+            if version < self.version() && !backup.exists() {
+                fs::copy(path, backup).map_err(|e| self.io(e))?;
+            }
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| self.io(e))?;
+        }
+        fs::write(path, contents).map_err(|e| self.io(e))
+    }
+
+    /// The version and remaining fields of a file this build can read
+    fn read_object(&self, contents: &str) -> Result<(u64, Map<String, Value>), SchemaError> {
+        let Value::Object(mut object) =
+            serde_json::from_str(contents).map_err(|e| self.invalid(e))?
+        else {
+            return Err(self.invalid("not a JSON object"));
+        };
+        let version = match object.remove("version") {
+            None => 0,
+            Some(value) => value
+                .as_u64()
+                .ok_or_else(|| self.invalid(format!("bad version {value}")))?,
+        };
+        if version > self.version() {
+            return Err(SchemaError::Newer {
+                file: self.file,
+                found: version,
+                supported: self.version(),
+            });
+        }
+        Ok((version, object))
+    }
+
+    fn io(&self, source: std::io::Error) -> SchemaError {
+        SchemaError::Io {
+            file: self.file,
+            source,
+        }
+    }
+
+    fn invalid(&self, reason: impl ToString) -> SchemaError {
+        SchemaError::Invalid {
+            file: self.file,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Data {
+        items: Vec<String>,
+    }
+
+    /// Version 1 renamed `entries` to `items`
+    fn rename_entries(object: &mut Map<String, Value>) {
+        if let Some(entries) = object.remove("entries") {
+            object.insert("items".to_string(), entries);
+        }
+    }
+
+    const SCHEMA: Schema = Schema {
+        file: "data.json",
+        migrations: &[rename_entries],
+    };
+
+    #[test]
+    fn test_parse_migrates_and_rejects_newer() {
+        let data: Data = SCHEMA.parse(r#"{"entries":["a"]}"#).unwrap();
+        assert_eq!(data.items, vec!["a"]);
+        let data: Data = SCHEMA.parse(r#"{"version":1,"items":["b"]}"#).unwrap();
+        assert_eq!(data.items, vec!["b"]);
+
+        let newer = SCHEMA.parse::<Data>(r#"{"version":2,"things":[]}"#);
+        assert!(matches!(newer, Err(SchemaError::Newer { found: 2, .. })));
+        assert!(matches!(
+            SCHEMA.parse::<Data>("{\"items\": [\"a\""),
+            Err(SchemaError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_save_backs_up_and_protects() {
+        let dir = ScratchDir::new("schema");
+        let path = dir.join("data.json");
+
+        fs::write(&path, r#"{"entries":["a"]}"#).unwrap();
+        let mut data: Data = SCHEMA.load(&path).unwrap();
+        data.items.push("b".to_string());
+        SCHEMA.save(&path, &data).unwrap();
+        let backup = fs::read_to_string(dir.join("data.json.v0.bak")).unwrap();
+        assert_eq!(backup, r#"{"entries":["a"]}"#);
+        assert_eq!(SCHEMA.load::<Data>(&path).unwrap(), data);
+
+        // A file from a newer build is left alone
+        fs::write(&path, r#"{"version":5}"#).unwrap();
+        assert!(SCHEMA.save(&path, &data).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"version":5}"#);
+
+        // So is a current one whose fields don't fit
+        let mismatched = r#"{"version":1,"items":[1,2]}"#;
+        fs::write(&path, mismatched).unwrap();
+        assert!(SCHEMA.load::<Data>(&path).is_err());
+        assert!(SCHEMA.save(&path, &data).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), mismatched);
+    }
+}
@@ -0,0 +1,289 @@
+//! User-defined status segment for the prompt and status bar
+//!
+//! `[status] command` runs through `sh -c` in the working directory every
+//! `interval` seconds on a background thread. The first line of its output
+//! is shown through the `{status}` prompt placeholder (and the status bar
+//! with `status_bar = true`). The line may use a little markup:
+//! `[name]text[/]` colors text with a theme color (`git_staged`,
+//! `block_warning`, ...) or a basic color (`red`, `cyan`, ...), `[b]` makes
+//! it bold, and `[[` is a literal `[`.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+
+use crate::config::StatusConfig;
+use crate::theme::Theme;
+
+/// Longest segment shown, in characters
+const MAX_CHARS: usize = 60;
+
+/// A run taking longer than this is killed and shows nothing
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A run of segment text with the markup that applies to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piece {
+    pub text: String,
+    /// Theme slot or basic color name
+    pub color: Option<String>,
+    pub bold: bool,
+}
+
+/// Split a line into pieces by its markup
+///
+/// Tags that aren't `[/]`, `[b]`, or a bare color name stay as text.
+pub fn parse_markup(line: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut current = Piece {
+        text: String::new(),
+        color: None,
+        bold: false,
+    };
+    let mut rest = line;
+
+    while let Some(open) = rest.find('[') {
+        current.text.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if let Some(after) = rest.strip_prefix("[[") {
+            current.text.push('[');
+            rest = after;
+            continue;
+        }
+        let tag = rest[1..].find(']').map(|close| &rest[1..=close]);
+        let is_name = |tag: &str| {
+            !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        let (color, bold) = match tag {
+            Some("/") => (None, false),
+            Some("b") => (current.color.clone(), true),
+            Some(name) if is_name(name) => (Some(name.to_string()), current.bold),
+            _ => {
+                current.text.push('[');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let tag_len = tag.map_or(0, str::len) + 2;
+        rest = &rest[tag_len..];
+        let next = Piece {
+            text: String::new(),
+            color,
+            bold,
+        };
+        let done = std::mem::replace(&mut current, next);
+        if !done.text.is_empty() {
+            pieces.push(done);
+        }
+    }
+
+    current.text.push_str(rest);
+    if !current.text.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Style pieces with the theme's colors
+pub fn spans(pieces: &[Piece], theme: &Theme) -> Vec<Span<'static>> {
+    pieces
+        .iter()
+        .map(|piece| {
+            let mut style = Style::default();
+            if let Some(color) = piece
+                .color
+                .as_deref()
+                .and_then(|name| resolve_color(name, theme))
+            {
+                style = style.fg(color);
+            }
+            if piece.bold {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            Span::styled(piece.text.clone(), style)
+        })
+        .collect()
+}
+
+/// A theme slot by name, then one of the basic terminal colors
+fn resolve_color(name: &str, theme: &Theme) -> Option<Color> {
+    theme.color(name).or(match name {
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "magenta" => Some(Color::Magenta),
+        "cyan" => Some(Color::Cyan),
+        "gray" => Some(Color::Gray),
+        "white" => Some(Color::White),
+        _ => None,
+    })
+}
+
+/// The configured status command and its latest output
+pub struct StatusSegment {
+    command: String,
+    interval: Duration,
+    /// Latest output, parsed
+    pieces: Vec<Piece>,
+    /// Directory of the last run; changing directory runs it again
+    dir: PathBuf,
+    last_run: Option<Instant>,
+    /// The run in progress
+    rx: Option<Receiver<Option<String>>>,
+}
+
+impl StatusSegment {
+    pub fn new(config: &StatusConfig) -> Self {
+        Self {
+            command: config.command.trim().to_string(),
+            interval: Duration::from_secs(config.interval),
+            pieces: Vec::new(),
+            dir: PathBuf::new(),
+            last_run: None,
+            rx: None,
+        }
+    }
+
+    /// Pick up a finished run and start the next one when it's due
+    ///
+    /// Returns true if the segment changed.
+    pub fn poll(&mut self, dir: &Path) -> bool {
+        if self.command.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+        if let Some(ref rx) = self.rx {
+            match rx.try_recv() {
+                Ok(line) => {
+                    let pieces = line.map(|line| parse_markup(&line)).unwrap_or_default();
+                    changed = pieces != self.pieces;
+                    self.pieces = pieces;
+                    self.rx = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => return false,
+                Err(mpsc::TryRecvError::Disconnected) => self.rx = None,
+            }
+        }
+
+        let due = self
+            .last_run
+            .map_or(true, |last| last.elapsed() >= self.interval);
+        if due || self.dir != dir {
+            self.start(dir);
+        }
+        changed
+    }
+
+    /// The latest output, parsed (empty before the first run finishes)
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    fn start(&mut self, dir: &Path) {
+        let (tx, rx) = mpsc::channel();
+        let command = self.command.clone();
+        let cwd = dir.to_path_buf();
+        thread::spawn(move || {
+            let _ = tx.send(run(&command, &cwd));
+        });
+        self.rx = Some(rx);
+        self.dir = dir.to_path_buf();
+        self.last_run = Some(Instant::now());
+    }
+}
+
+/// Run the command and return its first line, cleaned up for display
+fn run(command: &str, dir: &Path) -> Option<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().ok()? {
+            break status;
+        }
+        if started.elapsed() >= TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    if !status.success() {
+        return None;
+    }
+
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    let line = output.lines().find(|line| !line.trim().is_empty())?;
+    let line: String = crate::ansi::strip(line)
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CHARS)
+        .collect();
+    Some(line.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(text: &str, color: Option<&str>, bold: bool) -> Piece {
+        Piece {
+            text: text.to_string(),
+            color: color.map(str::to_string),
+            bold,
+        }
+    }
+
+    #[test]
+    fn test_parse_markup() {
+        assert_eq!(
+            parse_markup("[red]3 failing[/] \u{b7} [b][git_staged]ok[/]"),
+            vec![
+                piece("3 failing", Some("red"), false),
+                piece(" \u{b7} ", None, false),
+                piece("ok", Some("git_staged"), true),
+            ]
+        );
+        assert_eq!(
+            parse_markup("[[x] [not a tag] [ok"),
+            vec![piece("[x] [not a tag] [ok", None, false)]
+        );
+        assert_eq!(parse_markup(""), vec![]);
+    }
+
+    #[test]
+    fn test_spans_use_theme_slots() {
+        let theme = Theme::default();
+        let spans = spans(&parse_markup("[block_failure]down[/][nope]x"), &theme);
+        assert_eq!(spans[0].style.fg, Some(theme.block_failure));
+        assert_eq!(spans[1].style.fg, None);
+    }
+
+    #[test]
+    fn test_run() {
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            run("printf '\\n\\033[31mred\\033[0m\\nsecond\\n'", &dir).as_deref(),
+            Some("red")
+        );
+        assert_eq!(run("echo hi; exit 1", &dir), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! The startup greeting and the session record it reads
+//!
+//! When jerm exits it notes when the session started and ended in
+//! `session.json` in the config directory. The next launch opens with a
+//! short header: a greeting for the time of day and the date, how long
+//! the last session lasted, how many commands ran yesterday, and the
+//! first three shortcuts with their `Ctrl` jump keys. On the way out,
+//! [`ExitSummary`] is printed to the normal screen.
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::blocks::format_duration;
+use crate::history::storage::HistoryEntry;
+use crate::schema::{self, Schema, SchemaError};
+use crate::shortcuts::storage::Shortcut;
+
+/// Format history of `session.json`
+pub const SCHEMA: Schema = Schema {
+    file: "session.json",
+    migrations: &[schema::unversioned],
+};
+
+/// When a session ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+}
+
+impl SessionRecord {
+    /// The previous session, if one was recorded
+    pub fn load_last() -> Option<Self> {
+        let contents = fs::read_to_string(record_path()?).ok()?;
+        SCHEMA.parse(&contents).ok()
+    }
+
+    /// Remember this session for the next launch
+    pub fn save(&self) -> Result<(), SchemaError> {
+        match record_path() {
+            Some(path) => SCHEMA.save(&path, self),
+            None => Ok(()),
+        }
+    }
+}
+
+fn record_path() -> Option<PathBuf> {
+    crate::paths::config_dir().map(|dir| dir.join("session.json"))
+}
+
+/// Header lines for the start of a session
+pub fn greeting(
+    now: DateTime<Local>,
+    last: Option<&SessionRecord>,
+    history: &[HistoryEntry],
+    shortcuts: &[&Shortcut],
+) -> Vec<String> {
+    let salutation = match now.hour() {
+        5..=11 => "Good morning",
+        12..=17 => "Good afternoon",
+        18..=22 => "Good evening",
+        _ => "Working late",
+    };
+    let mut lines = vec![format!(
+        "\x1b[1m{salutation}\x1b[0m \x1b[2m·\x1b[0m {}",
+        now.format("%A, %B %-d")
+    )];
+
+    let mut facts = Vec::new();
+    if let Some(last) = last {
+        let length = (last.ended_at - last.started_at)
+            .to_std()
+            .unwrap_or_default();
+        facts.push(format!("last session {}", format_duration(length)));
+    }
+    if let Some(yesterday) = now.date_naive().pred_opt() {
+        let count = history
+            .iter()
+            .filter(|entry| entry.timestamp.with_timezone(&Local).date_naive() == yesterday)
+            .count();
+        let noun = if count == 1 { "command" } else { "commands" };
+        facts.push(format!("{count} {noun} yesterday"));
+    }
+    lines.push(format!("\x1b[2m{}\x1b[0m", facts.join(" · ")));
+
+    if !shortcuts.is_empty() {
+        let top: Vec<String> = shortcuts
+            .iter()
+            .take(3)
+            .enumerate()
+            .map(|(i, shortcut)| format!("\x1b[1m^{}\x1b[0m {}", i + 1, short_label(shortcut)))
+            .collect();
+        lines.push(top.join("  "));
+    }
+    lines
+}
+
+/// What happened in a session, printed when jerm exits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitSummary {
+    pub duration: std::time::Duration,
+    /// Commands run, builtins included
+    pub commands: usize,
+    /// Distinct directories visited
+    pub dirs: usize,
+    /// Commands still running at exit, which were killed
+    pub killed: Vec<String>,
+}
+
+impl ExitSummary {
+    pub fn lines(&self) -> Vec<String> {
+        let count = |count: usize, one: &str, many: &str| {
+            format!("{count} {}", if count == 1 { one } else { many })
+        };
+        let mut lines = vec![format!(
+            "jerm: session {} · {} · {}",
+            format_duration(self.duration),
+            count(self.commands, "command", "commands"),
+            count(self.dirs, "directory", "directories")
+        )];
+        if !self.killed.is_empty() {
+            lines.push(format!(
+                "jerm: killed {} still running: {}",
+                count(self.killed.len(), "command", "commands"),
+                self.killed.join(", ")
+            ));
+        }
+        lines
+    }
+}
+
+/// A shortcut's name, or else the last part of its path
+fn short_label(shortcut: &Shortcut) -> String {
+    match (&shortcut.name, shortcut.path.file_name()) {
+        (Some(name), _) => name.clone(),
+        (None, Some(dir)) => dir.to_string_lossy().into_owned(),
+        (None, None) => shortcut.label(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_greeting() {
+        let now = Local.with_ymd_and_hms(2024, 3, 15, 9, 30, 0).unwrap();
+        let at = |days: i64| HistoryEntry {
+            command: "ls".to_string(),
+            cwd: PathBuf::from("/"),
+            timestamp: (now - Duration::days(days)).with_timezone(&Utc),
+        };
+        let history = vec![at(2), at(1), at(1), at(0)];
+        let last = SessionRecord {
+            started_at: Utc.with_ymd_and_hms(2024, 3, 14, 10, 0, 0).unwrap(),
+            ended_at: Utc.with_ymd_and_hms(2024, 3, 14, 11, 12, 0).unwrap(),
+        };
+        let mut api = Shortcut::new(PathBuf::from("/src/api"));
+        api.name = Some("api".to_string());
+        let web = Shortcut::new(PathBuf::from("/src/web"));
+
+        let lines: Vec<String> = greeting(now, Some(&last), &history, &[&api, &web])
+            .iter()
+            .map(|line| crate::ansi::strip(line))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Good morning · Friday, March 15",
+                "last session 1h12m · 2 commands yesterday",
+                "^1 api  ^2 web",
+            ]
+        );
+
+        // A first launch has nothing to summarize but yesterday's count
+        let lines = greeting(now, None, &[], &[]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(crate::ansi::strip(&lines[1]), "0 commands yesterday");
+    }
+
+    #[test]
+    fn test_exit_summary() {
+        let mut summary = ExitSummary {
+            duration: std::time::Duration::from_secs(4330),
+            commands: 1,
+            dirs: 3,
+            killed: Vec::new(),
+        };
+        assert_eq!(
+            summary.lines(),
+            vec!["jerm: session 1h12m · 1 command · 3 directories"]
+        );
+
+        summary.killed = vec!["make watch".to_string(), "sleep 100".to_string()];
+        assert_eq!(
+            summary.lines()[1],
+            "jerm: killed 2 commands still running: make watch, sleep 100"
+        );
+    }
+}
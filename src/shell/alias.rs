@@ -0,0 +1,190 @@
+//! Command aliases (`alias name='command'`)
+//!
+//! Aliases live in `aliases.json` in the profile's config directory. Before
+//! a line runs, each word in command position that names an alias is
+//! replaced by its command, once: an alias's command isn't expanded again,
+//! so `alias ls='ls -G'` works.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::highlight::tokenizer::{TokenType, Tokenizer};
+use crate::schema::{self, Schema, SchemaError};
+
+/// Errors that can occur while reading or writing aliases
+#[derive(Error, Debug)]
+pub enum AliasError {
+    #[error("Failed to access aliases file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse aliases file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Format(#[from] SchemaError),
+
+    #[error("Config directory not found")]
+    ConfigDirNotFound,
+}
+
+/// Format history of `aliases.json`
+pub const SCHEMA: Schema = Schema {
+    file: "aliases.json",
+    migrations: &[schema::unversioned],
+};
+
+/// Container for all aliases, keyed by name (for JSON serialization)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AliasesData {
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// Every alias, kept in memory and written on each change
+pub struct AliasStore {
+    data: AliasesData,
+    /// When set, changes are kept in memory and never written to disk
+    readonly: bool,
+    /// Why the file couldn't be read; it's left untouched until fixed
+    load_error: Option<String>,
+}
+
+impl AliasStore {
+    /// Create a store, loading aliases from disk
+    pub fn new() -> Self {
+        let mut store = Self {
+            data: AliasesData::default(),
+            readonly: false,
+            load_error: None,
+        };
+        store.reload();
+        store
+    }
+
+    /// Why the aliases file couldn't be read, if it couldn't
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Enable or disable readonly mode (no writes to disk)
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    /// Reload aliases from disk (after switching profiles)
+    pub fn reload(&mut self) {
+        (self.data, self.load_error) = match load_aliases() {
+            Ok(data) => (data, None),
+            Err(e) => (AliasesData::default(), Some(e.to_string())),
+        };
+    }
+
+    /// The command an alias stands for
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.data.aliases.get(name).map(String::as_str)
+    }
+
+    /// Every alias and its command, by name
+    pub fn list(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.data
+            .aliases
+            .iter()
+            .map(|(name, command)| (name.as_str(), command.as_str()))
+    }
+
+    /// Define or redefine an alias
+    pub fn set(&mut self, name: &str, command: &str) -> Result<(), AliasError> {
+        self.data
+            .aliases
+            .insert(name.to_string(), command.to_string());
+        self.save()
+    }
+
+    /// Remove an alias, returning whether there was one
+    pub fn remove(&mut self, name: &str) -> Result<bool, AliasError> {
+        if self.data.aliases.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    fn save(&self) -> Result<(), AliasError> {
+        if !self.readonly && self.load_error.is_none() {
+            save_aliases(&self.data)?;
+        }
+        Ok(())
+    }
+
+    /// `input` with the aliases among its command names expanded
+    pub fn expand(&self, input: &str) -> String {
+        if self.data.aliases.is_empty() {
+            return input.to_string();
+        }
+        Tokenizer::tokenize(input)
+            .into_iter()
+            .map(|token| match token.token_type {
+                TokenType::Command => self.get(&token.text).map_or(token.text, str::to_string),
+                _ => token.text,
+            })
+            .collect()
+    }
+}
+
+impl Default for AliasStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An alias as `alias` prints it, ready to paste back in
+pub fn format_alias(name: &str, command: &str) -> String {
+    format!("alias {name}='{}'", command.replace('\'', "'\\''"))
+}
+
+/// Get the path to the aliases file
+pub fn get_aliases_path() -> Result<PathBuf, AliasError> {
+    let config_dir = crate::paths::config_dir().ok_or(AliasError::ConfigDirNotFound)?;
+    Ok(config_dir.join("aliases.json"))
+}
+
+/// Load all aliases from disk
+pub fn load_aliases() -> Result<AliasesData, AliasError> {
+    Ok(SCHEMA.load(&get_aliases_path()?)?)
+}
+
+/// Save all aliases to disk
+pub fn save_aliases(data: &AliasesData) -> Result<(), AliasError> {
+    Ok(SCHEMA.save(&get_aliases_path()?, data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let mut store = AliasStore {
+            data: AliasesData::default(),
+            readonly: true,
+            load_error: None,
+        };
+        store.set("ll", "ls -la").unwrap();
+        store.set("ls", "ls -G").unwrap();
+        store.set("g", "jerm goto").unwrap();
+
+        assert_eq!(store.expand("ll /tmp | grep ll"), "ls -la /tmp | grep ll");
+        assert_eq!(store.expand("ls && g api"), "ls -G && jerm goto api");
+        assert_eq!(store.expand("echo 'll'; ./ll"), "echo 'll'; ./ll");
+
+        assert!(store.remove("ll").unwrap());
+        assert!(!store.remove("ll").unwrap());
+        assert_eq!(store.expand("ll"), "ll");
+        assert_eq!(
+            format_alias("hi", "echo 'hi'"),
+            "alias hi='echo '\\''hi'\\'''"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Size of the command being typed, and how close it comes to `ARG_MAX`
+//!
+//! The shell expands globs after jerm hands the command over, so a short
+//! line like `grep foo **/*.log` can still fail with "argument list too
+//! long". Globs in the current directory are expanded here too, to estimate
+//! the size the command will really have.
+
+use std::fs;
+use std::path::Path;
+
+use crate::highlight::tokenizer::TokenType;
+use crate::highlight::Tokenizer;
+
+/// Share of the argument space past which the input is flagged
+const WARN_RATIO: f64 = 0.75;
+
+/// Argument space assumed when the system doesn't report one
+const FALLBACK_ARG_MAX: usize = 256 * 1024;
+
+/// Measurements of an input line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InputStats {
+    /// Characters typed
+    pub chars: usize,
+    /// Words, not counting operators
+    pub args: usize,
+    /// Bytes passed to the largest command of the line once its globs are
+    /// expanded (arguments plus their terminators)
+    pub expanded_bytes: usize,
+}
+
+impl InputStats {
+    /// Measure `input`, expanding its globs in `cwd`
+    pub fn measure(input: &str, cwd: &Path) -> Self {
+        let mut stats = Self {
+            chars: input.chars().count(),
+            ..Self::default()
+        };
+        let mut command_bytes = 0;
+        for token in Tokenizer::tokenize(input) {
+            match token.token_type {
+                TokenType::Whitespace => {}
+                TokenType::Operator if !matches!(token.text.as_str(), ">" | ">>" | "<") => {
+                    stats.expanded_bytes = stats.expanded_bytes.max(command_bytes);
+                    command_bytes = 0;
+                }
+                TokenType::Operator => {}
+                TokenType::String => {
+                    stats.args += 1;
+                    command_bytes += token.text.len() + 1;
+                }
+                _ => {
+                    stats.args += 1;
+                    command_bytes += match expand(&token.text, cwd) {
+                        Some(bytes) => bytes,
+                        None => token.text.len() + 1,
+                    };
+                }
+            }
+        }
+        stats.expanded_bytes = stats.expanded_bytes.max(command_bytes);
+        stats
+    }
+
+    /// Whether the expanded command comes close to `limit` bytes
+    pub fn near_limit(&self, limit: usize) -> bool {
+        self.expanded_bytes > 0 && self.expanded_bytes as f64 >= limit as f64 * WARN_RATIO
+    }
+}
+
+/// Bytes available for a command's arguments: `ARG_MAX` less what the
+/// environment takes
+pub fn arg_space() -> usize {
+    // SAFETY: sysconf has no preconditions
+    let arg_max = unsafe { libc::sysconf(libc::_SC_ARG_MAX) };
+    let arg_max = usize::try_from(arg_max)
+        .ok()
+        .filter(|max| *max > 0)
+        .unwrap_or(FALLBACK_ARG_MAX);
+    let environment: usize = std::env::vars_os()
+        .map(|(key, value)| key.len() + value.len() + 2)
+        .sum();
+    arg_max.saturating_sub(environment)
+}
+
+/// Bytes of the names a glob expands to in `cwd`, or `None` when `word` isn't
+/// a glob this can expand (no wildcard, a path, or nothing matching, which the
+/// shell passes through unchanged)
+fn expand(word: &str, cwd: &Path) -> Option<usize> {
+    if !word.contains(['*', '?', '[']) || word.contains('/') {
+        return None;
+    }
+    let bytes: usize = fs::read_dir(cwd)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        // A leading dot has to be matched explicitly
+        .filter(|name| !name.starts_with('.') || word.starts_with('.'))
+        .filter(|name| wildcard_match(word, name))
+        .map(|name| name.len() + 1)
+        .sum();
+    (bytes > 0).then_some(bytes)
+}
+
+/// Match `name` against a shell wildcard pattern with `*`, `?`, and `[...]`
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches_from(&pattern, &name)
+}
+
+fn matches_from(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| matches_from(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && matches_from(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some(close) = pattern
+                .iter()
+                .skip(2)
+                .position(|c| *c == ']')
+                .map(|i| i + 2)
+            else {
+                return name.first() == Some(&'[') && matches_from(&pattern[1..], &name[1..]);
+            };
+            let Some(&c) = name.first() else {
+                return false;
+            };
+            let class = &pattern[1..close];
+            let (negate, class) = match class.first() {
+                Some('!' | '^') => (true, &class[1..]),
+                _ => (false, class),
+            };
+            let mut matched = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == '-' {
+                    matched |= class[i] <= c && c <= class[i + 2];
+                    i += 3;
+                } else {
+                    matched |= class[i] == c;
+                    i += 1;
+                }
+            }
+            matched != negate && matches_from(&pattern[close + 1..], &name[1..])
+        }
+        Some(&literal) => name.first() == Some(&literal) && matches_from(&pattern[1..], &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.log", "build.log"));
+        assert!(!wildcard_match("*.log", "build.txt"));
+        assert!(wildcard_match("file?.txt", "file1.txt"));
+        assert!(wildcard_match("[a-c]*", "beta"));
+        assert!(!wildcard_match("[!a-c]*", "beta"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn test_measure_expands_globs() {
+        let dir = ScratchDir::new("arglen");
+        for name in ["a.log", "bb.log", "c.txt", ".hidden.log"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        let stats = InputStats::measure("rm -f *.log | wc -l", &dir);
+        assert_eq!(stats.chars, 19);
+        assert_eq!(stats.args, 5);
+        // rm, -f, a.log, bb.log
+        assert_eq!(stats.expanded_bytes, 3 + 3 + 6 + 7);
+
+        // Quoted and unmatched globs are passed as they are
+        let stats = InputStats::measure("echo '*.log' *.md", &dir);
+        assert_eq!(stats.expanded_bytes, 5 + 8 + 5);
+
+        assert!(stats.near_limit(20));
+        assert!(!stats.near_limit(100));
+    }
+}
@@ -0,0 +1,297 @@
+//! Readline-style word motion, the kill ring, and undo for the input line
+//!
+//! Positions are byte offsets into the input that fall between grapheme
+//! clusters, so `é` written as `e` plus a combining accent, or a flag
+//! emoji, moves and deletes as one character.
+//!
+//! Like readline, Alt+B/Alt+F and Alt+D treat runs of letters, digits, and
+//! `_` as words, while Ctrl+W deletes back to the previous whitespace, so
+//! it takes a whole path or flag at once. Killed text goes to a ring that
+//! Ctrl+Y yanks back from; Alt+Y right after a yank swaps in the kill
+//! before it. Kills in a row join into one entry.
+//!
+//! Undo steps back through the input's earlier states, a run of typed
+//! characters or of deletions being one step; running the command starts
+//! the history afresh.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Most kills the ring keeps
+const RING_SIZE: usize = 16;
+
+/// Most undo steps kept for the input
+const UNDO_LIMIT: usize = 100;
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn is_space(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_whitespace)
+}
+
+/// Start of the grapheme before `pos` (Left, Backspace)
+pub fn prev_grapheme(text: &str, pos: usize) -> usize {
+    text[..pos]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index)
+}
+
+/// End of the grapheme after `pos` (Right)
+pub fn next_grapheme(text: &str, pos: usize) -> usize {
+    text[pos..]
+        .graphemes(true)
+        .next()
+        .map_or(pos, |grapheme| pos + grapheme.len())
+}
+
+/// Back from `pos` over the graphemes matching `matches`
+fn back_while(text: &str, pos: usize, matches: impl Fn(&str) -> bool) -> usize {
+    text[..pos]
+        .grapheme_indices(true)
+        .rev()
+        .take_while(|(_, grapheme)| matches(grapheme))
+        .last()
+        .map_or(pos, |(index, _)| index)
+}
+
+/// On from `pos` over the graphemes matching `matches`
+fn forward_while(text: &str, pos: usize, matches: impl Fn(&str) -> bool) -> usize {
+    text[pos..]
+        .grapheme_indices(true)
+        .find(|(_, grapheme)| !matches(grapheme))
+        .map_or(text.len(), |(index, _)| pos + index)
+}
+
+/// Start of the word before `pos` (Alt+B)
+pub fn word_start(text: &str, pos: usize) -> usize {
+    let end = back_while(text, pos, |g| !is_word(g));
+    back_while(text, end, is_word)
+}
+
+/// End of the word after `pos` (Alt+F, Alt+D)
+pub fn word_end(text: &str, pos: usize) -> usize {
+    let start = forward_while(text, pos, |g| !is_word(g));
+    forward_while(text, start, is_word)
+}
+
+/// Start of the whitespace-delimited word before `pos` (Ctrl+W)
+pub fn rubout_start(text: &str, pos: usize) -> usize {
+    let end = back_while(text, pos, is_space);
+    back_while(text, end, |g| !is_space(g))
+}
+
+/// Which way a kill went from the cursor, deciding how it joins the last
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Backward,
+    Forward,
+}
+
+/// Recently killed text, newest first
+#[derive(Debug, Clone, Default)]
+pub struct KillRing {
+    entries: VecDeque<String>,
+    /// Whether the last key killed text, so the next kill joins it
+    joining: bool,
+    /// Input range of the text just yanked, and which entry it was
+    yanked: Option<(Range<usize>, usize)>,
+}
+
+impl KillRing {
+    /// Note the next key before it runs; only a kill continues a run of
+    /// kills, and only a yank keeps the last yank open for Alt+Y
+    pub fn next_key(&mut self, kills: bool, yanks: bool) {
+        if !kills {
+            self.joining = false;
+        }
+        if !yanks {
+            self.yanked = None;
+        }
+    }
+
+    /// Add killed text, joining it to the previous kill when they're in a row
+    pub fn kill(&mut self, text: String, direction: Direction) {
+        if text.is_empty() {
+            return;
+        }
+        match self.entries.front_mut() {
+            Some(last) if self.joining => match direction {
+                Direction::Backward => last.insert_str(0, &text),
+                Direction::Forward => last.push_str(&text),
+            },
+            _ => {
+                self.entries.push_front(text);
+                self.entries.truncate(RING_SIZE);
+            }
+        }
+        self.joining = true;
+    }
+
+    /// Newest kill, to insert at `at` (Ctrl+Y)
+    pub fn yank(&mut self, at: usize) -> Option<&str> {
+        let text = self.entries.front()?;
+        self.yanked = Some((at..at + text.len(), 0));
+        Some(text)
+    }
+
+    /// Right after a yank, the input range it inserted and the kill before
+    /// it, which replaces it (Alt+Y)
+    pub fn yank_pop(&mut self) -> Option<(Range<usize>, &str)> {
+        let (range, index) = self.yanked.take()?;
+        let index = (index + 1) % self.entries.len();
+        let text = &self.entries[index];
+        self.yanked = Some((range.start..range.start + text.len(), index));
+        Some((range, text))
+    }
+}
+
+/// What a key did to the input, deciding whether its undo step joins the
+/// one before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// Typed a character
+    Insert,
+    /// Deleted a character
+    Delete,
+    /// Ran the command
+    Submit,
+    /// Anything else, always a step of its own
+    Other,
+}
+
+/// The input and cursor position at one point
+pub type InputState = (String, usize);
+
+/// Earlier (and undone) states of the input line
+#[derive(Debug, Clone, Default)]
+pub struct UndoHistory {
+    undo: Vec<InputState>,
+    redo: Vec<InputState>,
+    /// Kind of the last edit while a run of them can still grow
+    run: Option<EditKind>,
+}
+
+impl UndoHistory {
+    /// Note what a key did: `before` is the state it found, `input` the
+    /// text it left
+    pub fn record(&mut self, before: InputState, input: &str, kind: EditKind) {
+        if kind == EditKind::Submit && input.is_empty() {
+            *self = Self::default();
+            return;
+        }
+        if before.0 == input {
+            // Moving the cursor ends a run of typing
+            self.run = None;
+            return;
+        }
+        if self.run != Some(kind) || kind == EditKind::Other {
+            self.undo.push(before);
+            if self.undo.len() > UNDO_LIMIT {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.run = Some(kind);
+    }
+
+    /// The state before the last edit, to replace `current`
+    pub fn undo(&mut self, current: InputState) -> Option<InputState> {
+        let state = self.undo.pop()?;
+        self.redo.push(current);
+        self.run = None;
+        Some(state)
+    }
+
+    /// The state the last undo left, to replace `current`
+    pub fn redo(&mut self, current: InputState) -> Option<InputState> {
+        let state = self.redo.pop()?;
+        self.undo.push(current);
+        self.run = None;
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_boundaries() {
+        let text = "git commit -m 'fix: café_bar'";
+        assert_eq!(word_start(text, text.len()), 20);
+        assert_eq!(word_start(text, 11), 4);
+        assert_eq!(word_start(text, 0), 0);
+        assert_eq!(word_end(text, 3), 10);
+        assert_eq!(word_end(text, 10), 13);
+        assert_eq!(word_end(text, text.len()), text.len());
+        assert_eq!(rubout_start(text, text.len()), 20);
+        assert_eq!(rubout_start(text, 14), 11);
+        assert_eq!(rubout_start("  ls", 2), 0);
+
+        // A letter with a combining accent and a flag are one step each
+        let text = "cafe\u{301} \u{1f1eb}\u{1f1f7}";
+        assert_eq!(prev_grapheme(text, 6), 3);
+        assert_eq!(next_grapheme(text, 3), 6);
+        assert_eq!(prev_grapheme(text, text.len()), 7);
+        assert_eq!(word_end(text, 0), 6);
+        assert_eq!(word_start(text, 6), 0);
+    }
+
+    #[test]
+    fn test_kill_ring() {
+        let mut ring = KillRing::default();
+        assert_eq!(ring.yank(0), None);
+
+        ring.next_key(true, false);
+        ring.kill("world".to_string(), Direction::Backward);
+        ring.next_key(true, false);
+        ring.kill("hello ".to_string(), Direction::Backward);
+        ring.next_key(false, false);
+        ring.next_key(true, false);
+        ring.kill("!".to_string(), Direction::Forward);
+
+        ring.next_key(false, true);
+        assert_eq!(ring.yank(3), Some("!"));
+        ring.next_key(false, true);
+        assert_eq!(ring.yank_pop(), Some((3..4, "hello world")));
+        ring.next_key(false, true);
+        assert_eq!(ring.yank_pop(), Some((3..14, "!")));
+
+        // Alt+Y after anything but a yank does nothing
+        ring.next_key(false, false);
+        assert_eq!(ring.yank_pop(), None);
+    }
+
+    #[test]
+    fn test_undo_history() {
+        let mut history = UndoHistory::default();
+        let state = |text: &str| (text.to_string(), text.len());
+        history.record(state(""), "l", EditKind::Insert);
+        history.record(state("l"), "ls", EditKind::Insert);
+        history.record(state("ls"), "ls", EditKind::Other);
+        history.record(state("ls"), "ls ", EditKind::Insert);
+        history.record(state("ls "), "ls -la", EditKind::Other);
+
+        assert_eq!(history.undo(state("ls -la")), Some(state("ls ")));
+        assert_eq!(history.undo(state("ls ")), Some(state("ls")));
+        assert_eq!(history.redo(state("ls")), Some(state("ls ")));
+        assert_eq!(history.undo(state("ls ")), Some(state("ls")));
+        assert_eq!(history.undo(state("ls")), Some(state("")));
+        assert_eq!(history.undo(state("")), None);
+
+        // A new edit drops what was undone, and running the command
+        // forgets everything
+        history.record(state(""), "c", EditKind::Insert);
+        assert_eq!(history.redo(state("c")), None);
+        history.record(state("c"), "", EditKind::Submit);
+        assert_eq!(history.undo(state("")), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Environment variables set with `export` and removed with `unset`
+//!
+//! Every command runs in a fresh `sh -c`, so an `export` there is gone
+//! when it exits. The builtins record their changes here instead, and each
+//! command jerm starts gets them on top of jerm's own environment.
+//!
+//! Values expand `$NAME` and `${NAME}` as sh would, except inside single
+//! quotes, so `export PATH="$HOME/bin:$PATH"` works.
+
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use portable_pty::CommandBuilder;
+use thiserror::Error;
+
+/// Errors in the arguments of `export` and `unset`
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EnvError {
+    #[error("'{0}': not a valid variable name")]
+    InvalidName(String),
+
+    #[error("missing closing quote")]
+    UnclosedQuote,
+}
+
+/// Changes made to the environment commands run with
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    /// New values by name, `None` for removed variables
+    changes: BTreeMap<String, Option<String>>,
+}
+
+impl Environment {
+    /// A variable's value as commands see it
+    pub fn get(&self, name: &str) -> Option<String> {
+        match self.changes.get(name) {
+            Some(value) => value.clone(),
+            None => std::env::var(name).ok(),
+        }
+    }
+
+    /// Set each `NAME=value` in `args`; a bare `NAME` is left as it is
+    pub fn export(&mut self, args: &str) -> Result<(), EnvError> {
+        let words = self.words(args)?;
+        let mut assignments = Vec::new();
+        for word in &words {
+            let (name, value) = match word.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (word.as_str(), None),
+            };
+            if !is_valid_name(name) {
+                return Err(EnvError::InvalidName(name.to_string()));
+            }
+            assignments.extend(value.map(|value| (name, value)));
+        }
+        for (name, value) in assignments {
+            self.changes
+                .insert(name.to_string(), Some(value.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Set one variable to `value` as it is, without expanding it
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.changes
+            .insert(name.to_string(), Some(value.to_string()));
+    }
+
+    /// Remove each variable named in `args`
+    pub fn unset(&mut self, args: &str) -> Result<(), EnvError> {
+        let names = self.words(args)?;
+        if let Some(name) = names.iter().find(|name| !is_valid_name(name)) {
+            return Err(EnvError::InvalidName(name.clone()));
+        }
+        for name in names {
+            self.changes.insert(name, None);
+        }
+        Ok(())
+    }
+
+    /// Every variable commands see, by name, with whether `export` or
+    /// `unset` changed it
+    pub fn vars(&self) -> Vec<(String, String, bool)> {
+        let mut vars: BTreeMap<String, (String, bool)> = std::env::vars_os()
+            .map(|(name, value)| {
+                let name = name.to_string_lossy().into_owned();
+                (name, (value.to_string_lossy().into_owned(), false))
+            })
+            .collect();
+        for (name, value) in &self.changes {
+            match value {
+                Some(value) => vars.insert(name.clone(), (value.clone(), true)),
+                None => vars.remove(name),
+            };
+        }
+        vars.into_iter()
+            .map(|(name, (value, changed))| (name, value, changed))
+            .collect()
+    }
+
+    /// Apply the changes to a command about to start
+    pub fn apply(&self, command: &mut Command) {
+        for (name, value) in &self.changes {
+            match value {
+                Some(value) => command.env(name, value),
+                None => command.env_remove(name),
+            };
+        }
+    }
+
+    /// Apply the changes to a command about to start in a pseudo-terminal
+    pub fn apply_pty(&self, builder: &mut CommandBuilder) {
+        for (name, value) in &self.changes {
+            match value {
+                Some(value) => builder.env(name, value),
+                None => builder.env_remove(name),
+            }
+        }
+    }
+
+    /// Split `input` into words as sh would, expanding variables
+    fn words(&self, input: &str) -> Result<Vec<String>, EnvError> {
+        let mut words = Vec::new();
+        let mut word: Option<String> = None;
+        let mut quote = None;
+        let mut chars = input.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (None, c) if c.is_whitespace() => words.extend(word.take()),
+                (None, '\'' | '"') => {
+                    quote = Some(c);
+                    word.get_or_insert_with(String::new);
+                }
+                (Some(open), c) if c == open => quote = None,
+                (None | Some('"'), '\\') => {
+                    let word = word.get_or_insert_with(String::new);
+                    word.extend(chars.next());
+                }
+                (None | Some('"'), '$') => {
+                    let braced = chars.next_if_eq(&'{').is_some();
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_') {
+                        name.push(c);
+                    }
+                    if braced && chars.next_if_eq(&'}').is_none() {
+                        return Err(EnvError::UnclosedQuote);
+                    }
+                    let word = word.get_or_insert_with(String::new);
+                    if name.is_empty() && !braced {
+                        word.push('$');
+                    } else {
+                        word.push_str(&self.get(&name).unwrap_or_default());
+                    }
+                }
+                (_, c) => word.get_or_insert_with(String::new).push(c),
+            }
+        }
+        if quote.is_some() {
+            return Err(EnvError::UnclosedQuote);
+        }
+        words.extend(word);
+        Ok(words)
+    }
+}
+
+/// Whether `name` can name a variable: letters, digits, and `_`, not
+/// starting with a digit
+fn is_valid_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The variables shown by `env`
+#[derive(Debug, Clone)]
+pub struct EnvPanel {
+    /// Name, value, and whether `export` or `unset` changed it
+    pub vars: Vec<(String, String, bool)>,
+    /// First row shown
+    pub scroll: usize,
+}
+
+impl EnvPanel {
+    pub fn new(env: &Environment) -> Self {
+        Self {
+            vars: env.vars(),
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_and_unset() {
+        let mut env = Environment::default();
+        env.export("JERM_A=one").unwrap();
+        env.export("JERM_B='two $JERM_A' JERM_C=\"${JERM_A}s\"")
+            .unwrap();
+        assert_eq!(env.get("JERM_B").as_deref(), Some("two $JERM_A"));
+        assert_eq!(env.get("JERM_C").as_deref(), Some("ones"));
+
+        env.export("JERM_A=\"a b\"\\ c").unwrap();
+        assert_eq!(env.get("JERM_A").as_deref(), Some("a b c"));
+        assert_eq!(
+            env.export("JERM_D=1 2X=3"),
+            Err(EnvError::InvalidName("2X".to_string()))
+        );
+        assert_eq!(env.get("JERM_D"), None);
+        assert_eq!(env.export("JERM_D='1"), Err(EnvError::UnclosedQuote));
+
+        env.unset("JERM_B HOME").unwrap();
+        assert_eq!(env.get("HOME"), None);
+        let vars = env.vars();
+        assert!(vars
+            .iter()
+            .all(|(name, ..)| name != "HOME" && name != "JERM_B"));
+        assert!(vars.contains(&("JERM_C".to_string(), "ones".to_string(), true)));
+    }
+}
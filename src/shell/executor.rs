@@ -1,8 +1,18 @@
+//! Running commands through the system shell and resolving `cd` targets
+
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use thiserror::Error;
 
+use super::env::Environment;
+use crate::events::AppEvent;
+
 /// Errors that can occur during command execution
 #[derive(Error, Debug)]
 pub enum ExecutorError {
@@ -17,17 +27,20 @@ pub enum ExecutorError {
 
     #[error("Invalid path: {0}")]
     InvalidPath(String),
+
+    #[error("Failed to start pseudo-terminal: {0}")]
+    Pty(String),
 }
 
 /// Result of command execution
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct CommandResult {
     /// Standard output lines
     pub stdout: Vec<String>,
     /// Standard error lines
     pub stderr: Vec<String>,
     /// Exit code (0 = success)
-    #[allow(dead_code)]
     pub exit_code: i32,
 }
 
@@ -38,15 +51,24 @@ impl CommandResult {
         self.exit_code == 0
     }
 
-    /// Get all output lines (stdout followed by stderr)
-    pub fn all_lines(&self) -> Vec<String> {
-        let mut lines = self.stdout.clone();
-        lines.extend(self.stderr.clone());
-        lines
+    /// Get all output lines (stdout followed by stderr), each with the
+    /// stream it came from
+    #[allow(dead_code)]
+    pub fn all_lines(&self) -> Vec<(OutputStream, String)> {
+        let stdout = self
+            .stdout
+            .iter()
+            .map(|l| (OutputStream::Stdout, l.clone()));
+        let stderr = self
+            .stderr
+            .iter()
+            .map(|l| (OutputStream::Stderr, l.clone()));
+        stdout.chain(stderr).collect()
     }
 }
 
-/// Execute a shell command in the given directory
+/// Execute a shell command in the given directory, waiting for it to finish
+#[allow(dead_code)]
 pub fn execute_command(command: &str, current_dir: &Path) -> Result<CommandResult, ExecutorError> {
     let output = Command::new("sh")
         .arg("-c")
@@ -73,22 +95,270 @@ pub fn execute_command(command: &str, current_dir: &Path) -> Result<CommandResul
     })
 }
 
+/// Which stream an output line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// How long to wait for trailing output after the process exits
+///
+/// Background grandchildren can keep the pipes open indefinitely.
+pub(super) const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+/// A shell command running in the background with streamed output
+///
+/// Output is either kept for [`Self::drain_output`] or, for commands
+/// started with [`Self::spawn_to`], sent to the event loop as
+/// [`AppEvent::Output`].
+pub struct RunningCommand {
+    /// The command line that was started
+    pub command: String,
+    child: Child,
+    /// Output waiting to be drained; `None` when it goes to the event loop
+    output_rx: Option<Receiver<(OutputStream, String)>>,
+    /// Streams the event loop hasn't seen [`AppEvent::OutputClosed`] for
+    open_streams: usize,
+    /// Exit code and time of exit, once the process has finished
+    exited: Option<(i32, Instant)>,
+    /// Whether both output streams have been fully read
+    output_done: bool,
+}
+
+/// Where a command's output lines go
+#[derive(Clone)]
+enum LineSink {
+    Channel(Sender<(OutputStream, String)>),
+    Events(Sender<AppEvent>, u32),
+}
+
+impl LineSink {
+    /// Pass a line on; false once no one is listening
+    fn send(&self, stream: OutputStream, line: String) -> bool {
+        match self {
+            LineSink::Channel(tx) => tx.send((stream, line)).is_ok(),
+            LineSink::Events(tx, pid) => tx
+                .send(AppEvent::Output {
+                    pid: *pid,
+                    stream,
+                    line,
+                })
+                .is_ok(),
+        }
+    }
+
+    /// Say the stream has ended; a channel says so by disconnecting
+    fn close(self) {
+        if let LineSink::Events(tx, pid) = self {
+            let _ = tx.send(AppEvent::OutputClosed { pid });
+        }
+    }
+}
+
+impl RunningCommand {
+    /// Start a shell command in its own process group, with the changes
+    /// `export` and `unset` made to the environment
+    pub fn spawn(
+        command: &str,
+        current_dir: &Path,
+        env: &Environment,
+    ) -> Result<Self, ExecutorError> {
+        let mut shell = Command::new("sh");
+        shell.stdin(Stdio::null()).process_group(0);
+        env.apply(&mut shell);
+        Self::start(shell, command, current_dir, None)
+    }
+
+    /// Like [`Self::spawn`], but each output line is sent to `events` as
+    /// [`AppEvent::Output`], followed by [`AppEvent::OutputClosed`] per
+    /// stream; pass the latter back through [`Self::stream_closed`]
+    pub fn spawn_to(
+        command: &str,
+        current_dir: &Path,
+        env: &Environment,
+        events: Sender<AppEvent>,
+    ) -> Result<Self, ExecutorError> {
+        let mut shell = Command::new("sh");
+        shell.stdin(Stdio::null()).process_group(0);
+        env.apply(&mut shell);
+        Self::start(shell, command, current_dir, Some(events))
+    }
+
+    /// Start a shell command in jerm's own process group, reading jerm's
+    /// stdin, so Ctrl+C and piped input reach it as they would without jerm
+    ///
+    /// The signal methods only reach commands started with [`Self::spawn`].
+    pub fn spawn_foreground(
+        command: &str,
+        current_dir: &Path,
+        env: &Environment,
+    ) -> Result<Self, ExecutorError> {
+        let mut shell = Command::new("sh");
+        env.apply(&mut shell);
+        Self::start(shell, command, current_dir, None)
+    }
+
+    fn start(
+        mut shell: Command,
+        command: &str,
+        current_dir: &Path,
+        events: Option<Sender<AppEvent>>,
+    ) -> Result<Self, ExecutorError> {
+        let mut child = shell
+            .arg("-c")
+            .arg(command)
+            .current_dir(current_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let pid = child.id();
+        let (sink, output_rx) = match events {
+            Some(tx) => (LineSink::Events(tx, pid), None),
+            None => {
+                let (tx, rx) = mpsc::channel();
+                (LineSink::Channel(tx), Some(rx))
+            }
+        };
+        let mut open_streams = 0;
+        if let Some(stdout) = child.stdout.take() {
+            spawn_line_reader(stdout, OutputStream::Stdout, sink.clone());
+            open_streams += 1;
+        }
+        if let Some(stderr) = child.stderr.take() {
+            spawn_line_reader(stderr, OutputStream::Stderr, sink);
+            open_streams += 1;
+        }
+
+        Ok(Self {
+            command: command.to_string(),
+            child,
+            output_rx,
+            open_streams,
+            exited: None,
+            output_done: false,
+        })
+    }
+
+    /// Note that one of the output streams sent [`AppEvent::OutputClosed`]
+    pub fn stream_closed(&mut self) {
+        self.open_streams = self.open_streams.saturating_sub(1);
+        if self.open_streams == 0 {
+            self.output_done = true;
+        }
+    }
+
+    /// Collect output lines produced since the last call; always empty for
+    /// commands started with [`Self::spawn_to`]
+    pub fn drain_output(&mut self) -> Vec<(OutputStream, String)> {
+        let mut lines = Vec::new();
+        let Some(ref output_rx) = self.output_rx else {
+            return lines;
+        };
+        loop {
+            match output_rx.try_recv() {
+                Ok(line) => lines.push(line),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.output_done = true;
+                    break;
+                }
+            }
+        }
+        lines
+    }
+
+    /// Exit code once the command has finished and its output is drained
+    pub fn try_finish(&mut self) -> Option<i32> {
+        if self.exited.is_none() {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                let code = status
+                    .code()
+                    .or_else(|| status.signal().map(|signal| 128 + signal))
+                    .unwrap_or(-1);
+                self.exited = Some((code, Instant::now()));
+            }
+        }
+
+        let (code, exited_at) = self.exited?;
+        if self.output_done || exited_at.elapsed() >= OUTPUT_GRACE {
+            Some(code)
+        } else {
+            None
+        }
+    }
+
+    /// Send SIGINT to the command's whole process group
+    pub fn interrupt(&self) {
+        self.signal(libc::SIGINT);
+    }
+
+    /// Kill the command's whole process group
+    pub fn kill(&self) {
+        self.signal(libc::SIGKILL);
+    }
+
+    /// Suspend the command's process group (Ctrl+Z)
+    pub fn stop(&self) {
+        self.signal(libc::SIGTSTP);
+    }
+
+    /// Resume a suspended process group
+    pub fn resume(&self) {
+        self.signal(libc::SIGCONT);
+    }
+
+    /// Process id of the shell running the command
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    fn signal(&self, signal: libc::c_int) {
+        let Ok(pid) = libc::pid_t::try_from(self.child.id()) else {
+            return;
+        };
+        // SAFETY: kill(2) has no memory-safety requirements; a negative pid
+        // targets the process group created for this child by process_group(0).
+        unsafe {
+            libc::kill(-pid, signal);
+        }
+    }
+}
+
+/// Forward lines from a pipe to `sink` until EOF
+fn spawn_line_reader<R: Read + Send + 'static>(reader: R, stream: OutputStream, sink: LineSink) {
+    thread::spawn(move || {
+        let reader = BufReader::new(reader);
+        for line in reader.split(b'\n') {
+            let Ok(bytes) = line else {
+                break;
+            };
+            let text = String::from_utf8_lossy(&bytes)
+                .trim_end_matches('\r')
+                .to_string();
+            if !sink.send(stream, text) {
+                break;
+            }
+        }
+        sink.close();
+    });
+}
+
 /// Resolve a path for cd command
 /// Handles ~, relative paths, and absolute paths
 pub fn resolve_cd_path(
     path: &str,
     current_dir: &Path,
 ) -> Result<std::path::PathBuf, ExecutorError> {
-    let expanded = if path.starts_with('~') {
-        if let Some(home) = dirs::home_dir() {
-            if path == "~" {
-                home
-            } else {
-                home.join(&path[2..]) // Skip "~/"
-            }
-        } else {
+    let expanded = if path == "~" || path.starts_with("~/") {
+        let Some(home) = dirs::home_dir() else {
             return Err(ExecutorError::InvalidPath("Cannot expand ~".to_string()));
-        }
+        };
+        home.join(path.strip_prefix("~/").unwrap_or_default())
+    } else if path.starts_with('~') {
+        // Other users' homes (`~user`) aren't looked up
+        return Err(ExecutorError::InvalidPath(path.to_string()));
     } else if path.starts_with('/') {
         std::path::PathBuf::from(path)
     } else if path == "-" {
@@ -112,10 +382,38 @@ pub fn resolve_cd_path(
     Ok(canonical)
 }
 
+/// The directory to change into when `input` is nothing but a path to one
+/// (`shell.auto_cd`)
+///
+/// A name that is also a command on `$PATH` stays a command.
+pub fn auto_cd_target(input: &str, current_dir: &Path) -> Option<std::path::PathBuf> {
+    let word = input.trim();
+    if word.is_empty() || word.contains(char::is_whitespace) || word == "-" {
+        return None;
+    }
+    if !word.contains('/') && is_on_path(word) {
+        return None;
+    }
+    resolve_cd_path(word, current_dir).ok()
+}
+
+/// Whether an executable named `name` is on `$PATH`
+fn is_on_path(name: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        std::fs::metadata(dir.join(name))
+            .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use crate::events::EventBus;
+    use crate::testutil::ScratchDir;
 
     #[test]
     fn test_execute_echo() {
@@ -139,6 +437,70 @@ mod tests {
         assert_eq!(result.exit_code, 1);
     }
 
+    fn wait_for(running: &mut RunningCommand) -> (Vec<(OutputStream, String)>, i32) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut lines = Vec::new();
+        loop {
+            lines.extend(running.drain_output());
+            if let Some(code) = running.try_finish() {
+                return (lines, code);
+            }
+            assert!(Instant::now() < deadline, "command did not finish");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_running_command_streams() {
+        let env = Environment::default();
+        let command = "echo out; echo err >&2; exit 3";
+        let mut running = RunningCommand::spawn(command, Path::new("/tmp"), &env).unwrap();
+        let (lines, code) = wait_for(&mut running);
+        assert_eq!(code, 3);
+        assert!(lines.contains(&(OutputStream::Stdout, "out".to_string())));
+        assert!(lines.contains(&(OutputStream::Stderr, "err".to_string())));
+    }
+
+    #[test]
+    fn test_running_command_to_events() {
+        let env = Environment::default();
+        let bus = EventBus::new();
+        let command = "echo out; echo err >&2";
+        let mut running =
+            RunningCommand::spawn_to(command, Path::new("/tmp"), &env, bus.sender()).unwrap();
+        assert!(running.drain_output().is_empty());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut lines = Vec::new();
+        let code = loop {
+            match bus.next(Duration::from_millis(10)) {
+                Some(AppEvent::Output { pid, stream, line }) => {
+                    assert_eq!(pid, running.pid());
+                    lines.push((stream, line));
+                }
+                Some(AppEvent::OutputClosed { .. }) => running.stream_closed(),
+                _ => {}
+            }
+            if let Some(code) = running.try_finish() {
+                break code;
+            }
+            assert!(Instant::now() < deadline, "command did not finish");
+        };
+        assert_eq!(code, 0);
+        assert!(lines.contains(&(OutputStream::Stdout, "out".to_string())));
+        assert!(lines.contains(&(OutputStream::Stderr, "err".to_string())));
+    }
+
+    #[test]
+    fn test_running_command_interrupt() {
+        let env = Environment::default();
+        let mut running = RunningCommand::spawn("sleep 10", Path::new("/tmp"), &env).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        running.interrupt();
+        let (_, code) = wait_for(&mut running);
+        assert_eq!(code, 128 + libc::SIGINT);
+    }
+
     #[test]
     fn test_resolve_absolute_path() {
         let result = resolve_cd_path("/tmp", Path::new("/"));
@@ -157,4 +519,29 @@ mod tests {
         let result = resolve_cd_path("/nonexistent/path/12345", Path::new("/"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_auto_cd_target() {
+        let dir = ScratchDir::new("auto-cd");
+        std::fs::create_dir_all(dir.join("project")).unwrap();
+        std::fs::create_dir_all(dir.join("sh")).unwrap();
+
+        assert_eq!(auto_cd_target("project", &dir), Some(dir.join("project")));
+        assert_eq!(
+            auto_cd_target(" ./project/ ", &dir),
+            Some(dir.join("project"))
+        );
+        assert_eq!(
+            auto_cd_target("..", &dir.join("project")),
+            Some(dir.to_path_buf())
+        );
+        // Commands win over directories of the same name
+        assert_eq!(auto_cd_target("sh", &dir), None);
+        assert_eq!(auto_cd_target("./sh", &dir), Some(dir.join("sh")));
+        assert_eq!(auto_cd_target("project extra", &dir), None);
+        assert_eq!(auto_cd_target("missing", &dir), None);
+        // Only `~` and `~/` are expanded; anything else after `~` isn't a path
+        assert_eq!(auto_cd_target("~é", &dir), None);
+        assert_eq!(auto_cd_target("~foo", &dir), None);
+    }
 }
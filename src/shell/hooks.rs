@@ -0,0 +1,170 @@
+//! Commands run on every directory change, like zsh's `chpwd`
+//!
+//! Hooks run in order on a background thread with the new directory as
+//! their working directory and `OLDPWD` set to the previous one. Their
+//! output is thrown away unless one fails.
+
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Lines of a failing hook's output worth showing
+const MAX_ERROR_LINES: usize = 5;
+
+/// A hook that exited unsuccessfully or couldn't start
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookFailure {
+    /// The hook's command line
+    pub command: String,
+    /// Exit code, `None` when it couldn't run or was killed
+    pub exit_code: Option<i32>,
+    /// Last lines of its standard error (or output when stderr was empty)
+    pub output: Vec<String>,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => write!(f, "chpwd hook `{}` failed (exit {code})", self.command),
+            None => write!(f, "chpwd hook `{}` failed", self.command),
+        }
+    }
+}
+
+/// Runs hooks off the UI thread and collects their failures
+pub struct HookRunner {
+    tx: Sender<HookFailure>,
+    rx: Receiver<HookFailure>,
+}
+
+impl HookRunner {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self { tx, rx }
+    }
+
+    /// Start `commands` for a change from `old_dir` to `dir`
+    pub fn run(&self, commands: &[String], dir: &Path, old_dir: &Path) {
+        if commands.is_empty() {
+            return;
+        }
+        let commands = commands.to_vec();
+        let dir = dir.to_path_buf();
+        let old_dir = old_dir.to_path_buf();
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            for command in commands {
+                if let Some(failure) = run_hook(&command, &dir, &old_dir) {
+                    let _ = tx.send(failure);
+                }
+            }
+        });
+    }
+
+    /// Failures reported since the last call
+    pub fn poll(&self) -> Vec<HookFailure> {
+        self.rx.try_iter().collect()
+    }
+}
+
+impl Default for HookRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run `commands` in order and wait for them, returning their failures
+pub fn run_hooks(commands: &[String], dir: &Path, old_dir: &Path) -> Vec<HookFailure> {
+    commands
+        .iter()
+        .filter_map(|command| run_hook(command, dir, old_dir))
+        .collect()
+}
+
+/// Run one hook to completion, returning why it failed if it did
+fn run_hook(command: &str, dir: &Path, old_dir: &Path) -> Option<HookFailure> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .env("PWD", dir)
+        .env("OLDPWD", old_dir)
+        .output();
+
+    let output = match output {
+        Ok(output) if output.status.success() => return None,
+        Ok(output) => output,
+        Err(e) => {
+            return Some(HookFailure {
+                command: command.to_string(),
+                exit_code: None,
+                output: vec![e.to_string()],
+            })
+        }
+    };
+
+    let text = if output.stderr.is_empty() {
+        output.stdout
+    } else {
+        output.stderr
+    };
+    let text = String::from_utf8_lossy(&text);
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let start = lines.len().saturating_sub(MAX_ERROR_LINES);
+    Some(HookFailure {
+        command: command.to_string(),
+        exit_code: output.status.code(),
+        output: lines[start..].iter().map(|l| l.to_string()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_run_hook() {
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            run_hook("test \"$PWD\" = \"$(pwd)\"", &dir, Path::new("/")),
+            None
+        );
+        assert_eq!(run_hook("echo quiet", &dir, Path::new("/")), None);
+
+        let failure = run_hook(
+            "echo out; echo \"$OLDPWD\" >&2; exit 3",
+            &dir,
+            Path::new("/old"),
+        )
+        .unwrap();
+        assert_eq!(failure.exit_code, Some(3));
+        assert_eq!(failure.output, vec!["/old"]);
+        assert_eq!(
+            failure.to_string(),
+            "chpwd hook `echo out; echo \"$OLDPWD\" >&2; exit 3` failed (exit 3)"
+        );
+    }
+
+    #[test]
+    fn test_runner_reports_failures_in_order() {
+        let runner = HookRunner::new();
+        let hooks = vec![
+            "true".to_string(),
+            "exit 1".to_string(),
+            "exit 2".to_string(),
+        ];
+        runner.run(&hooks, &std::env::temp_dir(), Path::new("/"));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut failures = Vec::new();
+        while failures.len() < 2 && Instant::now() < deadline {
+            failures.extend(runner.poll());
+            thread::sleep(Duration::from_millis(10));
+        }
+        let codes: Vec<Option<i32>> = failures.iter().map(|f| f.exit_code).collect();
+        assert_eq!(codes, vec![Some(1), Some(2)]);
+    }
+}
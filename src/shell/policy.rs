@@ -0,0 +1,250 @@
+//! Per-project command policies (`.jerm/policy.toml`)
+//!
+//! A repository can list commands that shouldn't run from inside it, or
+//! that deserve a second look:
+//!
+//! ```toml
+//! [[rule]]
+//! command = "terraform apply"
+//! action = "block"
+//! message = "Apply from the deploy pipeline"
+//! unless_env = "CI"
+//!
+//! [[rule]]
+//! command = "npm publish"
+//! message = "Releases are published by CI"
+//! ```
+//!
+//! A rule matches any command in the line that starts with its words, so
+//! `terraform apply -auto-approve` matches too. Leading `VAR=value`
+//! assignments and the wrappers `sudo`, `env`, `time`, and `nohup` (with
+//! their options) are looked past, so `sudo -E terraform apply` and
+//! `env TF_LOG=1 terraform apply` match as well. It warns unless `action` is
+//! `"block"`, and is skipped while the variable in `unless_env` is set. The
+//! nearest `.jerm/policy.toml` above the current directory applies.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::env::Environment;
+use crate::highlight::tokenizer::{TokenType, Tokenizer};
+
+/// Where a project keeps its policy, relative to its root
+pub const POLICY_FILE: &str = ".jerm/policy.toml";
+
+/// Programs that run the command after them
+const WRAPPERS: &[&str] = &["sudo", "env", "time", "nohup"];
+
+/// Wrapper options whose value is the next word, as in `sudo -u deploy`
+const OPTIONS_WITH_VALUE: &[&str] = &["-u", "-g", "-C", "-D", "-h", "-p", "-U"];
+
+/// Errors that can occur while reading a policy file
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("Failed to read policy file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse policy file: {0}")]
+    Parse(#[from] toml::de::Error),
+}
+
+/// What a matching rule does
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    /// Print the rule's message, then run the command
+    #[default]
+    Warn,
+    /// Print the rule's message instead of running the command
+    Block,
+}
+
+/// One command a project warns about or blocks
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Words a command starts with
+    pub command: String,
+    #[serde(default)]
+    pub action: PolicyAction,
+    /// Why, shown with the warning or block
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Variable that turns the rule off while set, such as `CI`
+    #[serde(default)]
+    pub unless_env: Option<String>,
+}
+
+/// The rules of a project's policy file
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    #[serde(rename = "rule")]
+    pub rules: Vec<Rule>,
+}
+
+impl Policy {
+    /// Read a policy file
+    pub fn load(path: &Path) -> Result<Self, PolicyError> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Rules that apply to `input`, with the variables in `env`
+    pub fn check(&self, input: &str, env: &Environment) -> Vec<&Rule> {
+        let commands = commands(input);
+        self.rules
+            .iter()
+            .filter(|rule| {
+                let disabled = rule
+                    .unless_env
+                    .as_deref()
+                    .and_then(|name| env.get(name))
+                    .is_some_and(|value| !value.is_empty());
+                let words: Vec<String> = rule
+                    .command
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect();
+                !disabled
+                    && !words.is_empty()
+                    && commands.iter().any(|command| runs(command, &words))
+            })
+            .collect()
+    }
+}
+
+/// The policy file nearest `dir`, looking up from it
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(POLICY_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Whether `command` runs the program and arguments in `words`, also when
+/// the program is given by path
+fn runs(command: &[String], words: &[String]) -> bool {
+    let command = program(command);
+    command.len() >= words.len()
+        && command
+            .iter()
+            .zip(words)
+            .enumerate()
+            .all(|(i, (word, expected))| {
+                word == expected || (i == 0 && word.rsplit('/').next() == Some(expected.as_str()))
+            })
+}
+
+/// The words of `command` from the program it runs, past leading
+/// assignments and wrappers
+fn program(mut command: &[String]) -> &[String] {
+    while let Some((word, rest)) = command.split_first() {
+        if is_assignment(word) {
+            command = rest;
+        } else if WRAPPERS.contains(&word.as_str()) {
+            command = rest;
+            while let Some((option, rest)) = command.split_first() {
+                if !option.starts_with('-') {
+                    break;
+                }
+                command = match rest.split_first() {
+                    Some((_, value_rest)) if OPTIONS_WITH_VALUE.contains(&option.as_str()) => {
+                        value_rest
+                    }
+                    _ => rest,
+                };
+            }
+        } else {
+            break;
+        }
+    }
+    command
+}
+
+/// Whether `word` is a `NAME=value` assignment
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Words of each command in an input line, without their quotes
+fn commands(input: &str) -> Vec<Vec<String>> {
+    let mut commands = vec![Vec::new()];
+    for token in Tokenizer::tokenize(input) {
+        match token.token_type {
+            TokenType::Operator => {
+                if matches!(token.text.as_str(), ";" | "&&" | "||" | "|" | "&") {
+                    commands.push(Vec::new());
+                }
+            }
+            TokenType::Whitespace if token.text.contains('\n') => commands.push(Vec::new()),
+            TokenType::Whitespace => {}
+            TokenType::String => {
+                let text = token.text.trim_matches(|c| c == '\'' || c == '"');
+                commands.last_mut().unwrap().push(text.to_string());
+            }
+            _ => commands.last_mut().unwrap().push(token.text),
+        }
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let policy: Policy = toml::from_str(
+            r#"
+            [[rule]]
+            command = "terraform apply"
+            action = "block"
+            unless_env = "JERM_POLICY_TEST_CI"
+
+            [[rule]]
+            command = "npm publish"
+            message = "Releases are published by CI"
+            "#,
+        )
+        .unwrap();
+        let mut env = Environment::default();
+        let actions = |input: &str, env: &Environment| -> Vec<PolicyAction> {
+            policy.check(input, env).iter().map(|r| r.action).collect()
+        };
+
+        assert_eq!(
+            actions("terraform apply -auto-approve", &env),
+            [PolicyAction::Block]
+        );
+        assert_eq!(actions("terraform plan", &env), []);
+        assert_eq!(actions("echo terraform apply", &env), []);
+        assert_eq!(
+            actions("make && 'npm' publish --dry-run", &env),
+            [PolicyAction::Warn]
+        );
+
+        for input in [
+            "TF_LOG=debug terraform apply",
+            "sudo terraform apply",
+            "sudo -E -u deploy terraform apply",
+            "env TF_LOG=1 terraform apply",
+            "env -i PATH=/bin terraform apply",
+            "time terraform apply",
+            "nohup terraform apply &",
+            "time sudo env A=1 /usr/bin/terraform apply",
+        ] {
+            assert_eq!(actions(input, &env), [PolicyAction::Block], "{input}");
+        }
+        assert_eq!(actions("sudo -u terraform apply", &env), []);
+
+        env.export("JERM_POLICY_TEST_CI=1").unwrap();
+        assert_eq!(actions("terraform apply", &env), []);
+
+        assert!(toml::from_str::<Policy>("[[rule]]\ncommand = \"x\"\nmode = \"block\"\n").is_err());
+    }
+}
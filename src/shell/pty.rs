@@ -0,0 +1,388 @@
+//! Pseudo-terminal execution for interactive programs
+//!
+//! Programs like `vim`, `less`, or `htop` need a real TTY. They run inside a
+//! PTY whose output is fed through a `vt100` parser; the resulting screen is
+//! rendered in the terminal pane while keystrokes are forwarded to the child.
+
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+
+use super::env::Environment;
+use super::executor::{ExecutorError, OUTPUT_GRACE};
+use crate::events::AppEvent;
+
+/// Programs that are always run inside a PTY
+const INTERACTIVE_COMMANDS: &[&str] = &[
+    "vim", "vi", "nvim", "nano", "emacs", "less", "more", "man", "htop", "top", "btop", "ssh",
+    "tmux", "screen", "watch", "fzf", "tig", "lazygit", "python", "python3", "node", "irb", "psql",
+    "mysql", "sqlite3", "bash", "zsh", "fish", "sh",
+];
+
+/// Lines kept by the emulator above the visible screen
+const SCROLLBACK_LINES: usize = 1000;
+
+/// Check whether a command should run inside a PTY
+///
+/// Looks at the program name of the command (skipping `sudo` and leading
+/// `VAR=value` assignments): it's one of the built-in editors, pagers,
+/// monitors, and REPLs, or one of `extra` (`shell.pty_commands`). Shells
+/// and REPLs only count when started without arguments. Anything else can
+/// be given a PTY with `jerm pty <command>`.
+pub fn wants_pty(command: &str, extra: &[String]) -> bool {
+    let words: Vec<&str> = command
+        .split_whitespace()
+        .skip_while(|w| *w == "sudo" || (w.contains('=') && !w.starts_with('=')))
+        .collect();
+
+    let Some(program) = words.first() else {
+        return false;
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+
+    let is_repl = matches!(
+        program,
+        "python" | "python3" | "node" | "irb" | "bash" | "zsh" | "fish" | "sh"
+    );
+    if is_repl && words.len() > 1 {
+        return false;
+    }
+
+    INTERACTIVE_COMMANDS.contains(&program) || extra.iter().any(|name| name == program)
+}
+
+/// A program running in the foreground inside a pseudo-terminal
+pub struct PtySession {
+    /// The command line that was started
+    pub command: String,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    parser: vt100::Parser,
+    /// Exit code and time of exit, once the program has finished
+    exited: Option<(i32, Instant)>,
+    /// Whether [`AppEvent::PtyClosed`] has been seen
+    output_done: bool,
+}
+
+impl PtySession {
+    /// Spawn `command` via `sh -c` in a PTY of the given size
+    ///
+    /// Its output is sent to `events` as [`AppEvent::PtyOutput`] and ends
+    /// with [`AppEvent::PtyClosed`]; pass them back through
+    /// [`Self::process_output`] and [`Self::output_closed`].
+    pub fn spawn(
+        command: &str,
+        cwd: &Path,
+        env: &Environment,
+        rows: u16,
+        cols: u16,
+        events: Sender<AppEvent>,
+    ) -> Result<Self, ExecutorError> {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| ExecutorError::Pty(e.to_string()))?;
+
+        let mut builder = CommandBuilder::new("sh");
+        builder.arg("-c");
+        builder.arg(command);
+        builder.cwd(cwd);
+        builder.env("TERM", "xterm-256color");
+        env.apply_pty(&mut builder);
+
+        let child = pair
+            .slave
+            .spawn_command(builder)
+            .map_err(|e| ExecutorError::Pty(e.to_string()))?;
+        drop(pair.slave);
+
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| ExecutorError::Pty(e.to_string()))?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| ExecutorError::Pty(e.to_string()))?;
+
+        // Read output on a background thread so rendering never blocks
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if events.send(AppEvent::PtyOutput(buf[..n].to_vec())).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            let _ = events.send(AppEvent::PtyClosed);
+        });
+
+        Ok(Self {
+            command: command.to_string(),
+            master: pair.master,
+            writer,
+            child,
+            parser: vt100::Parser::new(rows, cols, SCROLLBACK_LINES),
+            exited: None,
+            output_done: false,
+        })
+    }
+
+    /// Feed output from [`AppEvent::PtyOutput`] into the emulator
+    pub fn process_output(&mut self, bytes: &[u8]) {
+        self.parser.process(bytes);
+    }
+
+    /// Note that [`AppEvent::PtyClosed`] arrived
+    pub fn output_closed(&mut self) {
+        self.output_done = true;
+    }
+
+    /// Send raw bytes to the program's input
+    pub fn write_input(&mut self, bytes: &[u8]) {
+        let _ = self.writer.write_all(bytes);
+        let _ = self.writer.flush();
+    }
+
+    /// Send pasted text, bracketed if the program asked for it
+    pub fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\r").replace('\n', "\r");
+        if self.parser.screen().bracketed_paste() {
+            self.write_input(format!("\x1b[200~{text}\x1b[201~").as_bytes());
+        } else {
+            self.write_input(text.as_bytes());
+        }
+    }
+
+    /// Forward a key press to the program
+    pub fn send_key(&mut self, key: KeyEvent) {
+        let bytes = key_to_bytes(key, self.parser.screen().application_cursor());
+        if !bytes.is_empty() {
+            self.write_input(&bytes);
+        }
+    }
+
+    /// Resize the PTY and emulator if the size changed
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        if self.parser.screen().size() == (rows, cols) {
+            return;
+        }
+        self.parser.set_size(rows, cols);
+        let _ = self.master.resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        });
+    }
+
+    /// Exit code once the program has finished and its output has been
+    /// processed (or had a moment to arrive)
+    pub fn try_finish(&mut self) -> Option<i32> {
+        if self.exited.is_none() {
+            let code = match self.child.try_wait() {
+                Ok(Some(status)) => Some(i32::try_from(status.exit_code()).unwrap_or(-1)),
+                Ok(None) => None,
+                Err(_) => Some(-1),
+            };
+            self.exited = code.map(|code| (code, Instant::now()));
+        }
+
+        let (code, exited_at) = self.exited?;
+        if self.output_done || exited_at.elapsed() >= OUTPUT_GRACE {
+            Some(code)
+        } else {
+            None
+        }
+    }
+
+    /// Kill the program
+    #[allow(dead_code)]
+    pub fn kill(&mut self) {
+        let _ = self.child.kill();
+    }
+
+    /// The emulated screen
+    pub fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
+    }
+
+    /// Final screen contents to keep in the output buffer
+    ///
+    /// Full-screen programs (alternate screen) leave nothing behind, like in
+    /// a regular terminal.
+    pub fn transcript(&self) -> Vec<String> {
+        let screen = self.parser.screen();
+        if screen.alternate_screen() {
+            return Vec::new();
+        }
+
+        let mut lines: Vec<String> = screen
+            .contents()
+            .lines()
+            .map(|l| l.trim_end().to_string())
+            .collect();
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        lines
+    }
+}
+
+/// Encode a key press as the bytes a terminal would send
+pub fn key_to_bytes(key: KeyEvent, application_cursor: bool) -> Vec<u8> {
+    let arrow = |c: u8| {
+        if application_cursor {
+            vec![0x1b, b'O', c]
+        } else {
+            vec![0x1b, b'[', c]
+        }
+    };
+
+    match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let lower = c.to_ascii_lowercase();
+            if lower.is_ascii_lowercase() {
+                vec![lower as u8 & 0x1f]
+            } else {
+                match c {
+                    ' ' | '@' => vec![0],
+                    '[' => vec![0x1b],
+                    '\\' => vec![0x1c],
+                    ']' => vec![0x1d],
+                    '_' => vec![0x1f],
+                    _ => Vec::new(),
+                }
+            }
+        }
+        KeyCode::Char(c) => {
+            let mut bytes = Vec::new();
+            if key.modifiers.contains(KeyModifiers::ALT) {
+                bytes.push(0x1b);
+            }
+            let mut buf = [0u8; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            bytes
+        }
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => arrow(b'A'),
+        KeyCode::Down => arrow(b'B'),
+        KeyCode::Right => arrow(b'C'),
+        KeyCode::Left => arrow(b'D'),
+        KeyCode::Home => arrow(b'H'),
+        KeyCode::End => arrow(b'F'),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::F(n) => match n {
+            1 => b"\x1bOP".to_vec(),
+            2 => b"\x1bOQ".to_vec(),
+            3 => b"\x1bOR".to_vec(),
+            4 => b"\x1bOS".to_vec(),
+            5 => b"\x1b[15~".to_vec(),
+            6 => b"\x1b[17~".to_vec(),
+            7 => b"\x1b[18~".to_vec(),
+            8 => b"\x1b[19~".to_vec(),
+            9 => b"\x1b[20~".to_vec(),
+            10 => b"\x1b[21~".to_vec(),
+            11 => b"\x1b[23~".to_vec(),
+            12 => b"\x1b[24~".to_vec(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use std::time::Duration;
+
+    #[test]
+    fn test_wants_pty() {
+        let wants_pty = |command| wants_pty(command, &[]);
+        assert!(wants_pty("vim src/main.rs"));
+        assert!(wants_pty("sudo htop"));
+        assert!(wants_pty("EDITOR=nano less README.md"));
+        assert!(wants_pty("/usr/bin/vim"));
+        assert!(wants_pty("python3"));
+        assert!(!wants_pty("python3 script.py"));
+        assert!(!wants_pty("ls -la"));
+        assert!(!wants_pty(""));
+    }
+
+    #[test]
+    fn test_wants_pty_extra() {
+        let extra = ["k9s".to_string()];
+        assert!(wants_pty("k9s --context prod", &extra));
+        assert!(wants_pty("sudo /usr/local/bin/k9s", &extra));
+        assert!(!wants_pty("k9s", &[]));
+        assert!(!wants_pty("ls", &extra));
+    }
+
+    #[test]
+    fn test_key_to_bytes() {
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Char('c'), KeyModifiers::CONTROL), false),
+            vec![0x03]
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Char('x'), KeyModifiers::NONE), false),
+            b"x".to_vec()
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Up, KeyModifiers::NONE), false),
+            b"\x1b[A".to_vec()
+        );
+        assert_eq!(
+            key_to_bytes(key(KeyCode::Up, KeyModifiers::NONE), true),
+            b"\x1bOA".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_spawn_and_transcript() {
+        let env = Environment::default();
+        let bus = EventBus::new();
+        let mut session =
+            PtySession::spawn("echo hello", Path::new("/tmp"), &env, 24, 80, bus.sender()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut exit = None;
+        while exit.is_none() && Instant::now() < deadline {
+            match bus.next(Duration::from_millis(10)) {
+                Some(AppEvent::PtyOutput(bytes)) => session.process_output(&bytes),
+                Some(AppEvent::PtyClosed) => session.output_closed(),
+                _ => {}
+            }
+            exit = session.try_finish();
+        }
+
+        assert_eq!(exit, Some(0));
+        assert_eq!(session.transcript(), vec!["hello"]);
+    }
+}
@@ -0,0 +1,144 @@
+//! `jerm retry`: rerun a failing command until it succeeds
+//!
+//! Each attempt after the first starts its own command block, headed by a
+//! separator line, so attempts can be told apart in the output and the
+//! block timeline.
+
+use std::time::{Duration, Instant};
+
+/// Attempts made when no count is given
+pub const DEFAULT_ATTEMPTS: u32 = 3;
+
+/// Delay before the second attempt with `--backoff`; it doubles every time
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+
+/// Longest delay between attempts with `--backoff`
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Arguments of `jerm retry [N] [--backoff] <command>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetrySpec {
+    /// Maximum number of attempts, including the first
+    pub attempts: u32,
+    /// Wait exponentially longer between attempts
+    pub backoff: bool,
+    /// Shell command to run
+    pub command: String,
+}
+
+/// State of a retry loop in progress
+#[derive(Debug, Clone)]
+pub struct Retry {
+    pub spec: RetrySpec,
+    /// Attempt currently running or last finished (1-based)
+    pub attempt: u32,
+    /// When the next attempt starts, once one is scheduled
+    pub next_start: Option<Instant>,
+}
+
+impl Retry {
+    /// Start tracking a retry loop; no attempt has run yet
+    pub fn new(spec: RetrySpec) -> Self {
+        Self {
+            spec,
+            attempt: 0,
+            next_start: None,
+        }
+    }
+
+    /// Whether another attempt is allowed after the current one
+    pub fn has_attempts_left(&self) -> bool {
+        self.attempt < self.spec.attempts
+    }
+
+    /// Delay before the attempt after the current one
+    pub fn delay(&self) -> Duration {
+        if !self.spec.backoff {
+            return Duration::ZERO;
+        }
+        let doublings = self.attempt.saturating_sub(1).min(16);
+        (BACKOFF_BASE * 2u32.pow(doublings)).min(BACKOFF_MAX)
+    }
+
+    /// Schedule the next attempt after the delay
+    pub fn schedule(&mut self, now: Instant) {
+        self.next_start = Some(now + self.delay());
+    }
+
+    /// Whether the scheduled attempt should start now
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.next_start.is_some_and(|start| now >= start)
+    }
+
+    /// Move on to the next attempt and return its separator line
+    pub fn begin_attempt(&mut self) -> String {
+        self.attempt += 1;
+        self.next_start = None;
+        format!(
+            "\x1b[2m\u{2500}\u{2500} attempt {}/{} \u{2500}\u{2500}\x1b[0m",
+            self.attempt, self.spec.attempts
+        )
+    }
+
+    /// Short status for the terminal title, e.g. `retry 2/3` or `retry 2/3 in 4s`
+    pub fn status(&self, now: Instant) -> String {
+        match self.next_start {
+            Some(start) => format!(
+                "retry {}/{} in {}s",
+                self.attempt + 1,
+                self.spec.attempts,
+                start.saturating_duration_since(now).as_secs_f64().ceil()
+            ),
+            None => format!("retry {}/{}", self.attempt, self.spec.attempts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(attempts: u32, backoff: bool) -> RetrySpec {
+        RetrySpec {
+            attempts,
+            backoff,
+            command: "make test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_attempts() {
+        let mut retry = Retry::new(spec(2, false));
+        assert!(retry.has_attempts_left());
+        assert!(retry.begin_attempt().contains("attempt 1/2"));
+        assert!(retry.has_attempts_left());
+        retry.begin_attempt();
+        assert!(!retry.has_attempts_left());
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let mut retry = Retry::new(spec(10, true));
+        let mut delays = Vec::new();
+        for _ in 0..7 {
+            retry.begin_attempt();
+            delays.push(retry.delay().as_secs());
+        }
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 30, 30]);
+
+        let mut retry = Retry::new(spec(3, false));
+        retry.begin_attempt();
+        assert_eq!(retry.delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut retry = Retry::new(spec(3, true));
+        retry.begin_attempt();
+        let now = Instant::now();
+        retry.schedule(now);
+        assert!(!retry.is_due(now));
+        assert!(retry.is_due(now + Duration::from_secs(1)));
+        assert_eq!(retry.status(now), "retry 2/3 in 1s");
+    }
+}
@@ -1,24 +1,135 @@
 use std::path::PathBuf;
 
-use super::storage::{load_shortcuts, save_shortcuts, Shortcut, ShortcutsData};
+use super::storage::{self, Shortcut, ShortcutsData};
+use super::store::{self, Backend, ShortcutStore};
 
 /// Manages directory shortcuts
 pub struct ShortcutManager {
     data: ShortcutsData,
+    /// Where the shortcuts are kept between sessions
+    store: Box<dyn ShortcutStore>,
+    backend: Backend,
+    /// When set, changes are kept in memory and never written to disk
+    readonly: bool,
+    /// Why the file couldn't be read; it's left untouched until fixed
+    load_error: Option<String>,
+    /// Paths were normalized or duplicates merged on load, and the store
+    /// is rewritten with the next change
+    normalized: bool,
 }
 
 impl ShortcutManager {
     /// Create a new shortcut manager, loading existing shortcuts from disk
     pub fn new() -> Self {
-        let data = load_shortcuts().unwrap_or_default();
-        Self { data }
+        Self::open(Backend::default())
     }
 
-    /// Get all shortcuts, sorted by last accessed (most recent first)
+    /// Create a shortcut manager over `backend`, loading its shortcuts
+    pub fn open(backend: Backend) -> Self {
+        let mut manager = Self {
+            data: ShortcutsData::default(),
+            store: store::open(backend),
+            backend,
+            readonly: false,
+            load_error: None,
+            normalized: false,
+        };
+        manager.reload();
+        manager
+    }
+
+    /// Switch to `backend` and load its shortcuts, if it isn't the one in use
+    pub fn use_backend(&mut self, backend: Backend) {
+        if backend != self.backend {
+            self.store = store::open(backend);
+            self.backend = backend;
+            self.reload();
+        }
+    }
+
+    /// Why the shortcuts file couldn't be read, if it couldn't
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Enable or disable readonly mode (no writes to disk)
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    /// Store the shortcuts at the `changed` positions unless in readonly
+    /// mode or the store couldn't be read
+    fn persist(&mut self, changed: &[usize]) {
+        if self.readonly || self.load_error.is_some() {
+            return;
+        }
+        if self.normalized {
+            self.normalized = self.store.replace(&self.data.shortcuts).is_err();
+        } else {
+            let _ = self.store.update(&self.data.shortcuts, changed);
+        }
+    }
+
+    /// Get all shortcuts: pinned ones first in their stored order, then the
+    /// rest sorted by last accessed (most recent first)
     pub fn get_shortcuts(&self) -> Vec<&Shortcut> {
-        let mut shortcuts: Vec<_> = self.data.shortcuts.iter().collect();
-        shortcuts.sort_by(|a, b| b.last_accessed.cmp(&a.last_accessed));
-        shortcuts
+        let (mut pinned, mut rest): (Vec<_>, Vec<_>) =
+            self.data.shortcuts.iter().partition(|s| s.pinned);
+        rest.sort_by_key(|s| std::cmp::Reverse(s.last_accessed));
+        pinned.extend(rest);
+        pinned
+    }
+
+    /// Pin or unpin a shortcut; a newly pinned one goes below the other pins
+    ///
+    /// Returns whether the shortcut is now pinned.
+    pub fn toggle_pin(&mut self, path: &PathBuf) -> bool {
+        let Some(index) = self.data.shortcuts.iter().position(|s| &s.path == path) else {
+            return false;
+        };
+        let mut shortcut = self.data.shortcuts.remove(index);
+        shortcut.pinned = !shortcut.pinned;
+        let pinned = shortcut.pinned;
+        let new_index = if pinned {
+            self.data
+                .shortcuts
+                .iter()
+                .rposition(|s| s.pinned)
+                .map_or(0, |i| i + 1)
+        } else {
+            index
+        };
+        self.data.shortcuts.insert(new_index, shortcut);
+        // Everything between the old and new positions moved along
+        let moved: Vec<usize> = (index.min(new_index)..=index.max(new_index)).collect();
+        self.persist(&moved);
+        pinned
+    }
+
+    /// Swap a pinned shortcut with the pinned one above (`up`) or below it
+    ///
+    /// Returns false when the shortcut isn't pinned or is already at the end.
+    pub fn move_pinned(&mut self, path: &PathBuf, up: bool) -> bool {
+        let pins: Vec<usize> = (0..self.data.shortcuts.len())
+            .filter(|&i| self.data.shortcuts[i].pinned)
+            .collect();
+        let Some(slot) = pins
+            .iter()
+            .position(|&i| &self.data.shortcuts[i].path == path)
+        else {
+            return false;
+        };
+        let neighbor = if up {
+            slot.checked_sub(1)
+        } else {
+            Some(slot + 1).filter(|&s| s < pins.len())
+        };
+        let Some(neighbor) = neighbor else {
+            return false;
+        };
+        self.data.shortcuts.swap(pins[slot], pins[neighbor]);
+        self.persist(&[pins[slot], pins[neighbor]]);
+        true
     }
 
     /// Get a shortcut by index (1-based, for Ctrl+1 through Ctrl+9)
@@ -30,35 +141,79 @@ impl ShortcutManager {
     }
 
     /// Add a new shortcut or update existing one's access time
-    pub fn add_shortcut(&mut self, path: PathBuf) {
+    ///
+    /// A name labels the shortcut; it moves over if another shortcut had it.
+    /// The path is normalized first, so another spelling of a saved
+    /// directory updates its shortcut.
+    pub fn add_shortcut(&mut self, path: PathBuf, name: Option<String>) {
+        let path = storage::normalize_path(&path);
+        let mut changed = Vec::new();
+        if let Some(ref name) = name {
+            for (index, shortcut) in self.data.shortcuts.iter_mut().enumerate() {
+                if shortcut.name.as_ref() == Some(name) {
+                    shortcut.name = None;
+                    changed.push(index);
+                }
+            }
+        }
+
         // Check if shortcut already exists
-        if let Some(existing) = self.data.shortcuts.iter_mut().find(|s| s.path == path) {
+        if let Some(index) = self.data.shortcuts.iter().position(|s| s.path == path) {
+            let existing = &mut self.data.shortcuts[index];
             existing.touch();
+            if name.is_some() {
+                existing.name = name;
+            }
+            changed.push(index);
         } else {
-            self.data.shortcuts.push(Shortcut::new(path));
+            let mut shortcut = Shortcut::new(path);
+            shortcut.name = name;
+            self.data.shortcuts.push(shortcut);
+            changed.push(self.data.shortcuts.len() - 1);
         }
 
         // Save to disk
-        let _ = save_shortcuts(&self.data);
+        self.persist(&changed);
+    }
+
+    /// Find a shortcut by its name
+    pub fn find_by_name(&self, name: &str) -> Option<&Shortcut> {
+        self.data
+            .shortcuts
+            .iter()
+            .find(|s| s.name.as_deref() == Some(name))
     }
 
     /// Update the access time for a shortcut
     pub fn touch_shortcut(&mut self, path: &PathBuf) {
-        if let Some(shortcut) = self.data.shortcuts.iter_mut().find(|s| &s.path == path) {
-            shortcut.touch();
-            let _ = save_shortcuts(&self.data);
+        if let Some(index) = self.data.shortcuts.iter().position(|s| &s.path == path) {
+            self.data.shortcuts[index].touch();
+            self.persist(&[index]);
+        }
+    }
+
+    /// Find a shortcut by its position in the list (1-based) or its name
+    pub fn find(&self, spec: &str) -> Option<&Shortcut> {
+        match spec.parse::<usize>() {
+            Ok(index) => self.get_shortcuts().get(index.checked_sub(1)?).copied(),
+            Err(_) => self.find_by_name(spec),
         }
     }
 
     /// Remove a shortcut by path
-    #[allow(dead_code)]
     pub fn remove_shortcut(&mut self, path: &PathBuf) {
-        self.data.shortcuts.retain(|s| &s.path != path);
-        let _ = save_shortcuts(&self.data);
+        let Some(index) = self.data.shortcuts.iter().position(|s| &s.path == path) else {
+            return;
+        };
+        let removed = self.data.shortcuts.remove(index);
+        if self.normalized {
+            self.persist(&[]);
+        } else if !self.readonly && self.load_error.is_none() {
+            let _ = self.store.remove(&self.data.shortcuts, index, &removed);
+        }
     }
 
     /// Get the number of shortcuts
-    #[allow(dead_code)]
     pub fn len(&self) -> usize {
         self.data.shortcuts.len()
     }
@@ -68,12 +223,16 @@ impl ShortcutManager {
         self.data.shortcuts.is_empty()
     }
 
-    /// Reload shortcuts from disk
-    #[allow(dead_code)]
+    /// Reload shortcuts from disk, merging shortcuts saved under different
+    /// spellings of the same directory
     pub fn reload(&mut self) {
-        if let Ok(data) = load_shortcuts() {
-            self.data = data;
-        }
+        let (mut shortcuts, load_error) = match self.store.load() {
+            Ok(shortcuts) => (shortcuts, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        self.normalized = storage::dedup(&mut shortcuts);
+        self.data = ShortcutsData { shortcuts };
+        self.load_error = load_error;
     }
 }
 
@@ -86,6 +245,19 @@ impl Default for ShortcutManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shortcuts::store::JsonStore;
+
+    /// A manager with no shortcuts that never writes
+    fn scratch() -> ShortcutManager {
+        ShortcutManager {
+            data: ShortcutsData::default(),
+            store: Box::new(JsonStore),
+            backend: Backend::Json,
+            readonly: true,
+            load_error: None,
+            normalized: false,
+        }
+    }
 
     #[test]
     fn test_manager_new() {
@@ -94,6 +266,90 @@ mod tests {
         let _ = manager.get_shortcuts();
     }
 
+    #[test]
+    fn test_names_are_unique() {
+        let mut manager = scratch();
+        manager.add_shortcut(PathBuf::from("/tmp"), Some("work".to_string()));
+        manager.add_shortcut(PathBuf::from("/var"), None);
+        assert_eq!(
+            manager.find_by_name("work").unwrap().path,
+            PathBuf::from("/tmp")
+        );
+
+        // Saving again without a name keeps it; a taken name moves over
+        manager.add_shortcut(PathBuf::from("/tmp"), None);
+        assert!(manager.find_by_name("work").is_some());
+        manager.add_shortcut(PathBuf::from("/var"), Some("work".to_string()));
+        assert_eq!(
+            manager.find_by_name("work").unwrap().path,
+            PathBuf::from("/var")
+        );
+        assert_eq!(manager.len(), 2);
+    }
+
+    #[test]
+    fn test_other_spellings_update_the_shortcut() {
+        let mut manager = scratch();
+        let Some(home) = dirs::home_dir() else {
+            return;
+        };
+        manager.add_shortcut(home.clone(), None);
+        manager.add_shortcut(PathBuf::from("~"), None);
+        manager.add_shortcut(PathBuf::from(format!("{}/", home.display())), None);
+        assert_eq!(manager.len(), 1);
+        assert_eq!(manager.get_shortcuts()[0].visits, 3);
+    }
+
+    #[test]
+    fn test_find_and_remove() {
+        let mut manager = scratch();
+        manager.add_shortcut(PathBuf::from("/tmp"), Some("scratch".to_string()));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        manager.add_shortcut(PathBuf::from("/var"), None);
+
+        // Most recently used first
+        assert_eq!(manager.find("1").unwrap().path, PathBuf::from("/var"));
+        assert_eq!(manager.find("scratch").unwrap().path, PathBuf::from("/tmp"));
+        assert!(manager.find("0").is_none());
+        assert!(manager.find("3").is_none());
+
+        manager.remove_shortcut(&PathBuf::from("/var"));
+        assert_eq!(manager.find("1").unwrap().path, PathBuf::from("/tmp"));
+        assert_eq!(manager.len(), 1);
+    }
+
+    #[test]
+    fn test_pinned_order() {
+        let mut manager = scratch();
+        for path in ["/a", "/b", "/c"] {
+            manager.add_shortcut(PathBuf::from(path), None);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let order = |m: &ShortcutManager| -> Vec<PathBuf> {
+            m.get_shortcuts().iter().map(|s| s.path.clone()).collect()
+        };
+        let paths = |list: &[&str]| -> Vec<PathBuf> { list.iter().map(PathBuf::from).collect() };
+        assert_eq!(order(&manager), paths(&["/c", "/b", "/a"]));
+
+        // Pins go to the top in the order they were pinned
+        assert!(manager.toggle_pin(&PathBuf::from("/a")));
+        assert!(manager.toggle_pin(&PathBuf::from("/b")));
+        assert_eq!(order(&manager), paths(&["/a", "/b", "/c"]));
+
+        // Visiting an unpinned shortcut doesn't move it above the pins
+        manager.touch_shortcut(&PathBuf::from("/c"));
+        assert_eq!(order(&manager), paths(&["/a", "/b", "/c"]));
+
+        assert!(manager.move_pinned(&PathBuf::from("/b"), true));
+        assert_eq!(order(&manager), paths(&["/b", "/a", "/c"]));
+        assert!(!manager.move_pinned(&PathBuf::from("/b"), true));
+        assert!(!manager.move_pinned(&PathBuf::from("/c"), true));
+
+        // Unpinned again, it sorts by recency (after the just-visited /c)
+        assert!(!manager.toggle_pin(&PathBuf::from("/b")));
+        assert_eq!(order(&manager), paths(&["/a", "/c", "/b"]));
+    }
+
     #[test]
     fn test_get_shortcut_bounds() {
         let manager = ShortcutManager::new();
@@ -1,4 +1,12 @@
+//! Saved directory shortcuts and frecency of visited directories
+
 pub mod manager;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod storage;
+pub mod store;
+pub mod visits;
 
 pub use manager::ShortcutManager;
+pub use store::{Backend, ShortcutStore};
+pub use visits::DirTracker;
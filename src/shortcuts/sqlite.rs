@@ -0,0 +1,216 @@
+//! Shortcuts in an SQLite database
+//!
+//! `shortcuts.db` sits next to where `shortcuts.json` would, one row per
+//! shortcut. The first time a profile's database is opened it's filled
+//! from `shortcuts.json`, which is left in place.
+
+use std::fs;
+use std::path::PathBuf;
+
+use rusqlite::{params, Connection, Transaction};
+
+use super::storage::{load_shortcuts, Shortcut, StorageError};
+use super::store::ShortcutStore;
+
+/// Database file in the profile's config directory
+const DB_FILE: &str = "shortcuts.db";
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS shortcuts (
+    path TEXT PRIMARY KEY,
+    position INTEGER NOT NULL,
+    name TEXT,
+    last_accessed TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    visits INTEGER NOT NULL,
+    pinned INTEGER NOT NULL
+)";
+
+const UPSERT: &str = "INSERT INTO shortcuts
+    (path, position, name, last_accessed, created_at, visits, pinned)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+    ON CONFLICT (path) DO UPDATE SET
+        position = excluded.position,
+        name = excluded.name,
+        last_accessed = excluded.last_accessed,
+        created_at = excluded.created_at,
+        visits = excluded.visits,
+        pinned = excluded.pinned";
+
+/// `shortcuts.db` of the active profile
+pub struct SqliteStore {
+    /// Database to use instead of the profile's
+    fixed: Option<PathBuf>,
+    /// The open database and its path; reopened when the profile changes
+    db: Option<(PathBuf, Connection)>,
+}
+
+impl SqliteStore {
+    pub fn new() -> Self {
+        Self {
+            fixed: None,
+            db: None,
+        }
+    }
+
+    /// A store in the database at `path`, whatever the profile
+    pub fn at(path: PathBuf) -> Self {
+        Self {
+            fixed: Some(path),
+            db: None,
+        }
+    }
+
+    /// The database, opened (and imported into, when it's new) if it isn't yet
+    fn connection(&mut self) -> Result<&mut Connection, StorageError> {
+        let path = match self.fixed {
+            Some(ref path) => path.clone(),
+            None => crate::paths::config_dir()
+                .ok_or(StorageError::ConfigDirNotFound)?
+                .join(DB_FILE),
+        };
+        if self.db.as_ref().map_or(true, |(open, _)| *open != path) {
+            self.db = None;
+            // Read the JSON file before creating the database, so a broken
+            // file is reported rather than skipped for good
+            let imported = if path.exists() || self.fixed.is_some() {
+                Vec::new()
+            } else {
+                load_shortcuts()?.shortcuts
+            };
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut conn = Connection::open(&path)?;
+            conn.execute_batch(CREATE_TABLE)?;
+            let changed: Vec<usize> = (0..imported.len()).collect();
+            upsert(&mut conn, &imported, &changed)?;
+            self.db = Some((path, conn));
+        }
+        Ok(&mut self.db.as_mut().expect("database was just opened").1)
+    }
+}
+
+impl Default for SqliteStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShortcutStore for SqliteStore {
+    fn load(&mut self) -> Result<Vec<Shortcut>, StorageError> {
+        let conn = self.connection()?;
+        let mut statement = conn.prepare(
+            "SELECT path, name, last_accessed, created_at, visits, pinned
+             FROM shortcuts ORDER BY position",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok(Shortcut {
+                path: PathBuf::from(row.get::<_, String>(0)?),
+                name: row.get(1)?,
+                last_accessed: row.get(2)?,
+                created_at: row.get(3)?,
+                visits: row.get(4)?,
+                pinned: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn update(&mut self, shortcuts: &[Shortcut], changed: &[usize]) -> Result<(), StorageError> {
+        upsert(self.connection()?, shortcuts, changed)
+    }
+
+    fn replace(&mut self, shortcuts: &[Shortcut]) -> Result<(), StorageError> {
+        let tx = self.connection()?.transaction()?;
+        tx.execute("DELETE FROM shortcuts", [])?;
+        write_rows(&tx, shortcuts, &(0..shortcuts.len()).collect::<Vec<_>>())?;
+        Ok(tx.commit()?)
+    }
+
+    fn remove(
+        &mut self,
+        _: &[Shortcut],
+        index: usize,
+        removed: &Shortcut,
+    ) -> Result<(), StorageError> {
+        let tx = self.connection()?.transaction()?;
+        tx.execute(
+            "DELETE FROM shortcuts WHERE path = ?1",
+            [removed.path.to_string_lossy()],
+        )?;
+        tx.execute(
+            "UPDATE shortcuts SET position = position - 1 WHERE position > ?1",
+            [index as i64],
+        )?;
+        Ok(tx.commit()?)
+    }
+}
+
+/// Insert or update the shortcuts at `changed` positions in one transaction
+fn upsert(
+    conn: &mut Connection,
+    shortcuts: &[Shortcut],
+    changed: &[usize],
+) -> Result<(), StorageError> {
+    let tx = conn.transaction()?;
+    write_rows(&tx, shortcuts, changed)?;
+    Ok(tx.commit()?)
+}
+
+/// Insert or update the shortcuts at `changed` positions
+fn write_rows(
+    tx: &Transaction,
+    shortcuts: &[Shortcut],
+    changed: &[usize],
+) -> Result<(), StorageError> {
+    let mut statement = tx.prepare_cached(UPSERT)?;
+    for &index in changed {
+        let Some(shortcut) = shortcuts.get(index) else {
+            continue;
+        };
+        statement.execute(params![
+            shortcut.path.to_string_lossy(),
+            index as i64,
+            shortcut.name,
+            shortcut.last_accessed,
+            shortcut.created_at,
+            shortcut.visits,
+            shortcut.pinned,
+        ])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+
+    #[test]
+    fn test_incremental_updates() {
+        let dir = ScratchDir::new("sqlite");
+        let path = dir.join(DB_FILE);
+
+        let mut shortcuts: Vec<Shortcut> = ["/a", "/b", "/c"]
+            .iter()
+            .map(|p| Shortcut::new(PathBuf::from(p)))
+            .collect();
+        let mut store = SqliteStore::at(path.clone());
+        store.update(&shortcuts, &[0, 1, 2]).unwrap();
+
+        shortcuts[2].name = Some("work".to_string());
+        shortcuts[2].touch();
+        store.update(&shortcuts, &[2]).unwrap();
+        let removed = shortcuts.remove(0);
+        store.remove(&shortcuts, 0, &removed).unwrap();
+        shortcuts.push(Shortcut::new(PathBuf::from("/d")));
+        store.update(&shortcuts, &[2]).unwrap();
+
+        let loaded = SqliteStore::at(path.clone()).load().unwrap();
+        assert_eq!(loaded, shortcuts);
+
+        shortcuts.remove(0);
+        store.replace(&shortcuts).unwrap();
+        assert_eq!(SqliteStore::at(path).load().unwrap(), shortcuts);
+    }
+}
@@ -1,10 +1,14 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::config::TimeDisplay;
+use crate::messages::TimeMessages;
+use crate::schema::{self, Schema, SchemaError};
+
 /// Errors that can occur during storage operations
 #[derive(Error, Debug)]
 pub enum StorageError {
@@ -14,19 +18,46 @@ pub enum StorageError {
     #[error("Failed to parse shortcuts file: {0}")]
     ParseError(#[from] serde_json::Error),
 
+    #[error(transparent)]
+    Format(#[from] SchemaError),
+
+    #[cfg(feature = "sqlite")]
+    #[error("Shortcuts database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
     #[error("Config directory not found")]
     ConfigDirNotFound,
 }
 
+/// Format history of `shortcuts.json`
+pub const SCHEMA: Schema = Schema {
+    file: "shortcuts.json",
+    migrations: &[schema::unversioned],
+};
+
 /// A single directory shortcut
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Shortcut {
     /// The directory path
     pub path: PathBuf,
+    /// Optional label shown instead of the path and usable with `jerm goto`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// When the shortcut was last accessed
     pub last_accessed: DateTime<Utc>,
     /// When the shortcut was created
     pub created_at: DateTime<Utc>,
+    /// Number of times the shortcut was saved or jumped to
+    #[serde(default = "default_visits")]
+    pub visits: u32,
+    /// Pinned shortcuts stay at the top in the order they're stored in
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+/// Visits assumed for shortcuts saved before visits were counted
+fn default_visits() -> u32 {
+    1
 }
 
 impl Shortcut {
@@ -35,14 +66,34 @@ impl Shortcut {
         let now = Utc::now();
         Self {
             path,
+            name: None,
             last_accessed: now,
             created_at: now,
+            visits: 1,
+            pinned: false,
         }
     }
 
-    /// Update the last accessed time to now
+    /// Update the last accessed time to now and count the visit
     pub fn touch(&mut self) {
         self.last_accessed = Utc::now();
+        self.visits = self.visits.saturating_add(1);
+    }
+
+    /// Fold in a duplicate of this shortcut: the earlier creation time, the
+    /// later access time, the visits of both, and its name and pin when
+    /// this one has none
+    pub fn merge(&mut self, other: Shortcut) {
+        self.created_at = self.created_at.min(other.created_at);
+        self.last_accessed = self.last_accessed.max(other.last_accessed);
+        self.visits = self.visits.saturating_add(other.visits);
+        self.name = self.name.take().or(other.name);
+        self.pinned |= other.pinned;
+    }
+
+    /// Frecency score: visits weighted by how recently the shortcut was used
+    pub fn frecency(&self, now: DateTime<Utc>) -> f64 {
+        frecency(self.visits, self.last_accessed, now)
     }
 
     /// Get a display name for the shortcut (abbreviated path)
@@ -59,41 +110,85 @@ impl Shortcut {
         path_str
     }
 
+    /// Label for the sidebar: the name if set, otherwise the abbreviated path
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.display_name())
+    }
+
     /// Get a human-readable relative time since last access
     ///
     /// Returns strings like "now", "5m", "2h", "3d", "2w", "1mo"
     pub fn time_ago(&self) -> String {
-        let now = Utc::now();
-        let duration = now.signed_duration_since(self.last_accessed);
-
-        let seconds = duration.num_seconds();
-        if seconds < 60 {
-            return "now".to_string();
-        }
+        self.time_label(TimeDisplay::Compact, &TimeMessages::default())
+            .unwrap_or_default()
+    }
 
-        let minutes = duration.num_minutes();
-        if minutes < 60 {
-            return format!("{}m", minutes);
+    /// Last access time as configured for the sidebar, or `None` when hidden
+    pub fn time_label(&self, display: TimeDisplay, messages: &TimeMessages) -> Option<String> {
+        let age = Utc::now().signed_duration_since(self.last_accessed);
+        match display {
+            TimeDisplay::Compact => Some(messages.compact(age)),
+            TimeDisplay::Relative => Some(messages.verbose(age)),
+            TimeDisplay::Absolute => {
+                Some(messages.absolute(self.last_accessed.with_timezone(&Local), Local::now()))
+            }
+            TimeDisplay::Hidden => None,
         }
+    }
+}
 
-        let hours = duration.num_hours();
-        if hours < 24 {
-            return format!("{}h", hours);
-        }
+/// Visits weighted by how recently the last one was
+///
+/// Uses zoxide's weights: x4 within the hour, x2 within the day, /2
+/// within the week, /4 after that.
+pub fn frecency(visits: u32, last: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+    let age = now.signed_duration_since(last);
+    let weight = if age.num_hours() < 1 {
+        4.0
+    } else if age.num_days() < 1 {
+        2.0
+    } else if age.num_weeks() < 1 {
+        0.5
+    } else {
+        0.25
+    };
+    f64::from(visits) * weight
+}
 
-        let days = duration.num_days();
-        if days < 7 {
-            return format!("{}d", days);
-        }
+/// The one spelling of a directory's path: `~` expanded and symlinks,
+/// `.`, `..`, and trailing slashes resolved
+///
+/// A directory that no longer exists can't be resolved, so only its `~`,
+/// `.` parts, and trailing slash are dealt with.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let expanded = match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    };
+    expanded
+        .canonicalize()
+        .unwrap_or_else(|_| expanded.components().collect())
+}
 
-        let weeks = days / 7;
-        if weeks < 4 {
-            return format!("{}w", weeks);
+/// Normalize the shortcuts' paths, merging each duplicate into the first
+/// shortcut for its directory, and return whether anything changed
+pub fn dedup(shortcuts: &mut Vec<Shortcut>) -> bool {
+    let mut changed = false;
+    let mut unique: Vec<Shortcut> = Vec::with_capacity(shortcuts.len());
+    for mut shortcut in shortcuts.drain(..) {
+        let path = normalize_path(&shortcut.path);
+        changed |= path != shortcut.path;
+        shortcut.path = path;
+        match unique.iter_mut().find(|s| s.path == shortcut.path) {
+            Some(first) => {
+                first.merge(shortcut);
+                changed = true;
+            }
+            None => unique.push(shortcut),
         }
-
-        let months = days / 30;
-        format!("{}mo", months.max(1))
     }
+    *shortcuts = unique;
+    changed
 }
 
 /// Container for all shortcuts (for JSON serialization)
@@ -104,8 +199,8 @@ pub struct ShortcutsData {
 
 /// Get the path to the shortcuts config file
 pub fn get_config_path() -> Result<PathBuf, StorageError> {
-    let config_dir = dirs::config_dir().ok_or(StorageError::ConfigDirNotFound)?;
-    Ok(config_dir.join("jerm").join("shortcuts.json"))
+    let config_dir = crate::paths::config_dir().ok_or(StorageError::ConfigDirNotFound)?;
+    Ok(config_dir.join("shortcuts.json"))
 }
 
 /// Ensure the config directory exists
@@ -119,28 +214,18 @@ pub fn ensure_config_dir() -> Result<PathBuf, StorageError> {
 
 /// Load shortcuts from disk
 pub fn load_shortcuts() -> Result<ShortcutsData, StorageError> {
-    let config_path = get_config_path()?;
-
-    if !config_path.exists() {
-        return Ok(ShortcutsData::default());
-    }
-
-    let contents = fs::read_to_string(&config_path)?;
-    let data: ShortcutsData = serde_json::from_str(&contents)?;
-    Ok(data)
+    Ok(SCHEMA.load(&get_config_path()?)?)
 }
 
 /// Save shortcuts to disk
 pub fn save_shortcuts(data: &ShortcutsData) -> Result<(), StorageError> {
-    let config_path = ensure_config_dir()?;
-    let contents = serde_json::to_string_pretty(data)?;
-    fs::write(config_path, contents)?;
-    Ok(())
+    Ok(SCHEMA.save(&get_config_path()?, data)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::ScratchDir;
 
     #[test]
     fn test_shortcut_new() {
@@ -157,12 +242,70 @@ mod tests {
         std::thread::sleep(std::time::Duration::from_millis(10));
         shortcut.touch();
         assert!(shortcut.last_accessed >= original_accessed);
+        assert_eq!(shortcut.visits, 2);
+    }
+
+    #[test]
+    fn test_frecency() {
+        let now = Utc::now();
+        let mut recent = Shortcut::new(PathBuf::from("/tmp"));
+        let mut frequent = Shortcut::new(PathBuf::from("/var"));
+        frequent.visits = 10;
+        frequent.last_accessed = now - chrono::Duration::days(3);
+
+        // 1 visit this hour beats nothing, but 10 visits this week beat both
+        assert_eq!(recent.frecency(now), 4.0);
+        assert_eq!(frequent.frecency(now), 5.0);
+        recent.last_accessed = now - chrono::Duration::days(30);
+        assert_eq!(recent.frecency(now), 0.25);
+    }
+
+    #[test]
+    fn test_visits_default_for_old_files() {
+        let json = r#"{"path":"/tmp","last_accessed":"2024-01-28T10:30:00Z","created_at":"2024-01-10T08:00:00Z"}"#;
+        let shortcut: Shortcut = serde_json::from_str(json).unwrap();
+        assert_eq!(shortcut.visits, 1);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let dir = ScratchDir::new("dedup");
+        fs::create_dir_all(dir.join("real")).unwrap();
+        let real = dir.join("real").canonicalize().unwrap();
+        std::os::unix::fs::symlink(&real, dir.join("link")).unwrap();
+
+        let now = Utc::now();
+        let shortcut = |path: PathBuf, days: i64| Shortcut {
+            created_at: now - chrono::Duration::days(days),
+            last_accessed: now - chrono::Duration::days(days),
+            ..Shortcut::new(path)
+        };
+        let mut named = shortcut(dir.join("link"), 1);
+        named.name = Some("work".to_string());
+        let mut shortcuts = vec![
+            shortcut(real.clone(), 3),
+            named,
+            shortcut(PathBuf::from(format!("{}/./", real.display())), 7),
+            shortcut(PathBuf::from("/jerm-gone/./x/"), 2),
+        ];
+        assert!(dedup(&mut shortcuts));
+        assert_eq!(shortcuts.len(), 2);
+        assert_eq!(shortcuts[0].path, real);
+        assert_eq!(shortcuts[0].name.as_deref(), Some("work"));
+        assert_eq!(shortcuts[0].visits, 3);
+        assert_eq!(shortcuts[0].created_at, now - chrono::Duration::days(7));
+        assert_eq!(shortcuts[0].last_accessed, now - chrono::Duration::days(1));
+        assert_eq!(shortcuts[1].path, PathBuf::from("/jerm-gone/x"));
+        assert!(!dedup(&mut shortcuts));
     }
 
     #[test]
     fn test_display_name() {
-        let shortcut = Shortcut::new(PathBuf::from("/tmp"));
+        let mut shortcut = Shortcut::new(PathBuf::from("/tmp"));
         assert_eq!(shortcut.display_name(), "/tmp");
+        assert_eq!(shortcut.label(), "/tmp");
+        shortcut.name = Some("scratch".to_string());
+        assert_eq!(shortcut.label(), "scratch");
     }
 
     #[test]
@@ -208,6 +351,23 @@ mod tests {
         assert_eq!(shortcut.time_ago(), "3d");
     }
 
+    #[test]
+    fn test_time_label_settings() {
+        use chrono::Duration;
+        let mut shortcut = Shortcut::new(PathBuf::from("/tmp"));
+        shortcut.last_accessed = Utc::now() - Duration::days(29);
+        assert_eq!(shortcut.time_ago(), "4w");
+        shortcut.last_accessed = Utc::now() - Duration::days(45);
+        let messages = TimeMessages::default();
+        assert_eq!(
+            shortcut
+                .time_label(TimeDisplay::Relative, &messages)
+                .as_deref(),
+            Some("1 month ago")
+        );
+        assert_eq!(shortcut.time_label(TimeDisplay::Hidden, &messages), None);
+    }
+
     #[test]
     fn test_time_ago_weeks() {
         use chrono::Duration;
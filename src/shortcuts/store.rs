@@ -0,0 +1,130 @@
+//! Where shortcuts are kept between sessions
+//!
+//! [`ShortcutManager`](super::ShortcutManager) keeps every shortcut in
+//! memory and tells its [`ShortcutStore`] which ones changed. The JSON
+//! store rewrites `shortcuts.json` each time, which is simple and easy to
+//! edit by hand; the SQLite store (built with the `sqlite` feature) writes
+//! only the changed rows of `shortcuts.db`, which stays cheap as the list
+//! and what's recorded per shortcut grow.
+
+use serde::Deserialize;
+
+use super::storage::{load_shortcuts, save_shortcuts, Shortcut, ShortcutsData, StorageError};
+
+/// Backend for the shortcuts, set by `storage.shortcuts` in `config.toml`
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// `shortcuts.json`, rewritten on each change
+    #[default]
+    Json,
+    /// `shortcuts.db`, updated a row at a time
+    Sqlite,
+}
+
+impl Backend {
+    /// Whether this build can use the backend
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Json => true,
+            Backend::Sqlite => cfg!(feature = "sqlite"),
+        }
+    }
+}
+
+/// Persistent storage for the shortcut list
+///
+/// A shortcut's position in the list is part of what's stored, since
+/// pinned shortcuts keep the order they're stored in.
+pub trait ShortcutStore {
+    /// Read the stored shortcuts, in their stored order
+    fn load(&mut self) -> Result<Vec<Shortcut>, StorageError>;
+
+    /// Store the shortcuts at `changed` positions of `shortcuts`, which
+    /// were added, edited, or moved
+    fn update(&mut self, shortcuts: &[Shortcut], changed: &[usize]) -> Result<(), StorageError>;
+
+    /// Store `shortcuts` in place of everything stored before
+    fn replace(&mut self, shortcuts: &[Shortcut]) -> Result<(), StorageError>;
+
+    /// Forget `removed`, which was at position `index` before it was taken
+    /// out of `shortcuts`
+    fn remove(
+        &mut self,
+        shortcuts: &[Shortcut],
+        index: usize,
+        removed: &Shortcut,
+    ) -> Result<(), StorageError>;
+}
+
+/// Open the store for `backend`, falling back to JSON when this build
+/// doesn't have it
+pub fn open(backend: Backend) -> Box<dyn ShortcutStore> {
+    match backend {
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => Box::new(super::sqlite::SqliteStore::new()),
+        _ => Box::new(JsonStore),
+    }
+}
+
+/// `shortcuts.json` in the profile's config directory
+pub struct JsonStore;
+
+impl ShortcutStore for JsonStore {
+    fn load(&mut self) -> Result<Vec<Shortcut>, StorageError> {
+        Ok(load_shortcuts()?.shortcuts)
+    }
+
+    fn update(&mut self, shortcuts: &[Shortcut], _: &[usize]) -> Result<(), StorageError> {
+        save_all(shortcuts)
+    }
+
+    fn replace(&mut self, shortcuts: &[Shortcut]) -> Result<(), StorageError> {
+        save_all(shortcuts)
+    }
+
+    fn remove(
+        &mut self,
+        shortcuts: &[Shortcut],
+        _: usize,
+        _: &Shortcut,
+    ) -> Result<(), StorageError> {
+        save_all(shortcuts)
+    }
+}
+
+fn save_all(shortcuts: &[Shortcut]) -> Result<(), StorageError> {
+    save_shortcuts(&ShortcutsData {
+        shortcuts: shortcuts.to_vec(),
+    })
+}
+
+/// Shortcuts kept in memory, standing in for a store in tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore(pub Vec<Shortcut>);
+
+#[cfg(test)]
+impl ShortcutStore for MemoryStore {
+    fn load(&mut self) -> Result<Vec<Shortcut>, StorageError> {
+        Ok(self.0.clone())
+    }
+
+    fn update(&mut self, shortcuts: &[Shortcut], _: &[usize]) -> Result<(), StorageError> {
+        self.replace(shortcuts)
+    }
+
+    fn replace(&mut self, shortcuts: &[Shortcut]) -> Result<(), StorageError> {
+        self.0 = shortcuts.to_vec();
+        Ok(())
+    }
+
+    fn remove(
+        &mut self,
+        shortcuts: &[Shortcut],
+        _: usize,
+        _: &Shortcut,
+    ) -> Result<(), StorageError> {
+        self.replace(shortcuts)
+    }
+}
@@ -0,0 +1,226 @@
+//! Every directory jerm has been in, for `z <query>` jumps
+//!
+//! Unlike shortcuts, directories are tracked without being saved: each `cd`
+//! (or navigator, shortcut, or `z` jump) counts a visit. The list lives in
+//! `dirs.json` next to `shortcuts.json`.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::storage::{frecency, StorageError};
+use crate::schema::{self, Schema};
+
+/// Format history of `dirs.json`
+pub const SCHEMA: Schema = Schema {
+    file: "dirs.json",
+    migrations: &[schema::unversioned],
+};
+
+/// Most directories remembered; the lowest-scoring ones are dropped first
+const MAX_DIRS: usize = 1000;
+
+/// A directory that was visited
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DirVisit {
+    pub path: PathBuf,
+    pub visits: u32,
+    pub last_visited: DateTime<Utc>,
+}
+
+impl DirVisit {
+    /// Frecency score, weighted the same way as shortcuts
+    pub fn frecency(&self, now: DateTime<Utc>) -> f64 {
+        frecency(self.visits, self.last_visited, now)
+    }
+
+    /// Whether every keyword appears in the path, in order and ignoring
+    /// case, with the last one in the final path component (as zoxide does)
+    fn matches(&self, keywords: &[String]) -> bool {
+        let path = self.path.to_string_lossy().to_lowercase();
+        let mut rest = path.as_str();
+        for keyword in keywords {
+            let keyword = keyword.to_lowercase();
+            match rest.find(&keyword) {
+                Some(index) => rest = &rest[index + keyword.len()..],
+                None => return false,
+            }
+        }
+
+        let last_component = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        keywords
+            .last()
+            .map_or(true, |last| last_component.contains(&last.to_lowercase()))
+    }
+}
+
+/// Container for tracked directories (for JSON serialization)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct VisitsData {
+    pub dirs: Vec<DirVisit>,
+}
+
+/// Tracks visited directories and finds the best match for a query
+pub struct DirTracker {
+    data: VisitsData,
+    /// When set, changes are kept in memory and never written to disk
+    readonly: bool,
+    /// Why the file couldn't be read; it's left untouched until fixed
+    load_error: Option<String>,
+}
+
+impl DirTracker {
+    /// Create a tracker, loading visits from disk
+    pub fn new() -> Self {
+        let mut tracker = Self {
+            data: VisitsData::default(),
+            readonly: false,
+            load_error: None,
+        };
+        tracker.reload();
+        tracker
+    }
+
+    /// Why the visits file couldn't be read, if it couldn't
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// Enable or disable readonly mode (no writes to disk)
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    /// Reload visits from disk (after switching profiles)
+    pub fn reload(&mut self) {
+        (self.data, self.load_error) = match load_visits() {
+            Ok(data) => (data, None),
+            Err(e) => (VisitsData::default(), Some(e.to_string())),
+        };
+    }
+
+    /// Count a visit to a directory
+    pub fn record(&mut self, path: &Path) {
+        let now = Utc::now();
+        match self.data.dirs.iter_mut().find(|d| d.path == path) {
+            Some(dir) => {
+                dir.visits = dir.visits.saturating_add(1);
+                dir.last_visited = now;
+            }
+            None => self.data.dirs.push(DirVisit {
+                path: path.to_path_buf(),
+                visits: 1,
+                last_visited: now,
+            }),
+        }
+
+        if self.data.dirs.len() > MAX_DIRS {
+            self.data
+                .dirs
+                .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+            self.data.dirs.truncate(MAX_DIRS);
+        }
+
+        if !self.readonly && self.load_error.is_none() {
+            let _ = save_visits(&self.data);
+        }
+    }
+
+    /// Existing directories matching the keywords, best first, leaving out
+    /// `current` so repeating a jump moves on to the next match
+    pub fn matches(&self, keywords: &[String], current: &Path) -> Vec<&DirVisit> {
+        let now = Utc::now();
+        let mut matches: Vec<&DirVisit> = self
+            .data
+            .dirs
+            .iter()
+            .filter(|d| d.path != current && d.matches(keywords) && d.path.is_dir())
+            .collect();
+        matches.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        matches
+    }
+}
+
+impl Default for DirTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the path to the visited directories file
+pub fn get_visits_path() -> Result<PathBuf, StorageError> {
+    let config_dir = crate::paths::config_dir().ok_or(StorageError::ConfigDirNotFound)?;
+    Ok(config_dir.join("dirs.json"))
+}
+
+/// Load visited directories from disk
+pub fn load_visits() -> Result<VisitsData, StorageError> {
+    Ok(SCHEMA.load(&get_visits_path()?)?)
+}
+
+/// Save visited directories to disk
+pub fn save_visits(data: &VisitsData) -> Result<(), StorageError> {
+    Ok(SCHEMA.save(&get_visits_path()?, data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ScratchDir;
+    use std::fs;
+
+    fn keywords(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    fn visit(path: &str) -> DirVisit {
+        DirVisit {
+            path: PathBuf::from(path),
+            visits: 1,
+            last_visited: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_keyword_matching() {
+        let dir = visit("/home/me/src/Jerm-TUI");
+        assert!(dir.matches(&keywords(&["jerm"])));
+        assert!(dir.matches(&keywords(&["src", "tui"])));
+        assert!(!dir.matches(&keywords(&["tui", "src"])));
+        // The last keyword must be in the last component
+        assert!(!dir.matches(&keywords(&["src"])));
+        assert!(dir.matches(&keywords(&[])));
+    }
+
+    #[test]
+    fn test_best_match_by_frecency() {
+        let mut tracker = DirTracker {
+            data: VisitsData::default(),
+            readonly: true,
+            load_error: None,
+        };
+        let tmp = ScratchDir::new("z");
+        let (rare, often) = (tmp.join("proj-rare"), tmp.join("proj-often"));
+        fs::create_dir_all(&rare).unwrap();
+        fs::create_dir_all(&often).unwrap();
+
+        tracker.record(&rare);
+        for _ in 0..3 {
+            tracker.record(&often);
+        }
+        tracker.record(&tmp.join("proj-gone"));
+
+        let found = tracker.matches(&keywords(&["proj"]), Path::new("/"));
+        let paths: Vec<&PathBuf> = found.iter().map(|d| &d.path).collect();
+        assert_eq!(paths, vec![&often, &rare]);
+
+        // Already there: the next best match is offered
+        let found = tracker.matches(&keywords(&["proj"]), &often);
+        assert_eq!(found[0].path, rare);
+    }
+}
@@ -0,0 +1,10 @@
+//! Saved command snippets (`jerm snippet`)
+//!
+//! Snippets may contain `{name}` placeholders; inserting one enters a
+//! fill-in mode where Tab jumps between the fields.
+
+pub mod storage;
+pub mod template;
+
+pub use storage::Snippet;
+pub use template::Template;
@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::schema::{self, Schema, SchemaError};
+
+/// Errors that can occur while reading or writing snippets
+#[derive(Error, Debug)]
+pub enum SnippetError {
+    #[error("Failed to access snippets file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse snippets file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Format(#[from] SchemaError),
+
+    #[error("Config directory not found")]
+    ConfigDirNotFound,
+
+    #[error("No snippet named {0}")]
+    NotFound(String),
+}
+
+/// Format history of `snippets.json`
+pub const SCHEMA: Schema = Schema {
+    file: "snippets.json",
+    migrations: &[schema::unversioned],
+};
+
+/// A saved command line
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Snippet {
+    /// Name the snippet is inserted by
+    pub name: String,
+    /// Command text, possibly containing `{placeholders}`
+    pub command: String,
+    /// When the snippet was saved
+    pub created_at: DateTime<Utc>,
+}
+
+impl Snippet {
+    /// Create a snippet saved now
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Container for all snippets (for JSON serialization)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SnippetsData {
+    pub snippets: Vec<Snippet>,
+}
+
+/// Get the path to the snippets file
+pub fn get_snippets_path() -> Result<PathBuf, SnippetError> {
+    let config_dir = crate::paths::config_dir().ok_or(SnippetError::ConfigDirNotFound)?;
+    Ok(config_dir.join("snippets.json"))
+}
+
+/// Load all snippets from disk
+pub fn load_snippets() -> Result<SnippetsData, SnippetError> {
+    Ok(SCHEMA.load(&get_snippets_path()?)?)
+}
+
+/// Save all snippets to disk
+pub fn save_snippets(data: &SnippetsData) -> Result<(), SnippetError> {
+    Ok(SCHEMA.save(&get_snippets_path()?, data)?)
+}
+
+/// Find a snippet by name
+pub fn find_snippet(name: &str) -> Result<Snippet, SnippetError> {
+    load_snippets()?
+        .snippets
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| SnippetError::NotFound(name.to_string()))
+}
+
+/// Add a snippet, replacing any existing snippet with the same name
+pub fn add_snippet(snippet: Snippet) -> Result<(), SnippetError> {
+    let mut data = load_snippets()?;
+    data.snippets.retain(|s| s.name != snippet.name);
+    data.snippets.push(snippet);
+    data.snippets.sort_by(|a, b| a.name.cmp(&b.name));
+    save_snippets(&data)
+}
+
+/// Remove a snippet by name
+pub fn remove_snippet(name: &str) -> Result<(), SnippetError> {
+    let mut data = load_snippets()?;
+    let before = data.snippets.len();
+    data.snippets.retain(|s| s.name != name);
+    if data.snippets.len() == before {
+        return Err(SnippetError::NotFound(name.to_string()));
+    }
+    save_snippets(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialization() {
+        let data = SnippetsData {
+            snippets: vec![Snippet::new("logs", "docker logs -f {container}")],
+        };
+
+        let json = serde_json::to_string(&data).unwrap();
+        let parsed: SnippetsData = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.snippets, data.snippets);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::macros::playback::is_placeholder_name;
+
+/// Part of a template: literal text or a `{placeholder}` field
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field(String),
+}
+
+/// Fill-in state for a snippet with placeholders
+///
+/// Fields are edited one at a time; every occurrence of the same name
+/// shares a value. Unfilled fields are shown as `{name}`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    segments: Vec<Segment>,
+    /// Distinct field names in order of first appearance
+    names: Vec<String>,
+    /// Value typed for each name, indexed like `names`
+    values: Vec<String>,
+    /// Index of the field being edited
+    current: usize,
+}
+
+impl Template {
+    /// Parse a snippet; returns `None` when it has no placeholders
+    pub fn parse(command: &str) -> Option<Self> {
+        let segments = parse_segments(command);
+        let mut names: Vec<String> = Vec::new();
+        for segment in &segments {
+            if let Segment::Field(name) = segment {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        if names.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            segments,
+            values: vec![String::new(); names.len()],
+            names,
+            current: 0,
+        })
+    }
+
+    /// Name of the field being edited
+    pub fn current_name(&self) -> &str {
+        &self.names[self.current]
+    }
+
+    /// Progress label such as `container 1/2`
+    pub fn progress(&self) -> String {
+        format!(
+            "{} {}/{}",
+            self.current_name(),
+            self.current + 1,
+            self.names.len()
+        )
+    }
+
+    /// Type a character into the current field
+    pub fn push_char(&mut self, c: char) {
+        self.values[self.current].push(c);
+    }
+
+    /// Delete the last character of the current field
+    pub fn pop_char(&mut self) {
+        self.values[self.current].pop();
+    }
+
+    /// Move to the next field, wrapping around
+    pub fn next_field(&mut self) {
+        self.current = (self.current + 1) % self.names.len();
+    }
+
+    /// Move to the previous field, wrapping around
+    pub fn prev_field(&mut self) {
+        self.current = (self.current + self.names.len() - 1) % self.names.len();
+    }
+
+    /// Render the command line and the cursor position (end of the
+    /// current field's first occurrence)
+    pub fn render(&self) -> (String, usize) {
+        let mut text = String::new();
+        let mut cursor = None;
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(literal) => text.push_str(literal),
+                Segment::Field(name) => {
+                    let index = self.names.iter().position(|n| n == name).unwrap_or(0);
+                    let value = &self.values[index];
+                    if value.is_empty() {
+                        text.push_str(&format!("{{{name}}}"));
+                    } else {
+                        text.push_str(value);
+                    }
+                    if index == self.current && cursor.is_none() {
+                        cursor = Some(text.len());
+                    }
+                }
+            }
+        }
+
+        let cursor = cursor.unwrap_or(text.len());
+        (text, cursor)
+    }
+}
+
+/// Split a command into literal text and `{name}` fields
+///
+/// Uses the same rules as macro parameters: `${VAR}`, `{}` and anything
+/// that isn't an identifier stay literal.
+fn parse_segments(command: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = command;
+
+    while let Some(start) = rest.find('{') {
+        let escaped = rest[..start].ends_with('$');
+        let after = &rest[start + 1..];
+        let name = after.find('}').map(|end| &after[..end]);
+
+        match name {
+            Some(name) if !escaped && is_placeholder_name(name) => {
+                text.push_str(&rest[..start]);
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Field(name.to_string()));
+                rest = &after[name.len() + 1..];
+            }
+            _ => {
+                text.push_str(&rest[..=start]);
+                rest = after;
+            }
+        }
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_str(template: &mut Template, s: &str) {
+        for c in s.chars() {
+            template.push_char(c);
+        }
+    }
+
+    #[test]
+    fn test_no_placeholders() {
+        assert!(Template::parse("ls -la").is_none());
+        assert!(Template::parse("echo ${HOME} {}").is_none());
+    }
+
+    #[test]
+    fn test_fill_fields() {
+        let mut template = Template::parse("docker logs -f {container} --tail {lines}").unwrap();
+        assert_eq!(
+            template.render(),
+            ("docker logs -f {container} --tail {lines}".to_string(), 26)
+        );
+
+        type_str(&mut template, "web");
+        assert_eq!(template.render().0, "docker logs -f web --tail {lines}");
+        assert_eq!(template.render().1, 18);
+
+        template.next_field();
+        assert_eq!(template.progress(), "lines 2/2");
+        type_str(&mut template, "100");
+        template.pop_char();
+        assert_eq!(template.render().0, "docker logs -f web --tail 10");
+
+        template.next_field();
+        assert_eq!(template.current_name(), "container");
+    }
+
+    #[test]
+    fn test_repeated_field_shares_value() {
+        let mut template = Template::parse("cp {file} {file}.bak && echo '${x}'").unwrap();
+        type_str(&mut template, "a.txt");
+        assert_eq!(template.render().0, "cp a.txt a.txt.bak && echo '${x}'");
+    }
+}
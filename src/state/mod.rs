@@ -0,0 +1,556 @@
+//! Portable snapshots of a profile's state
+//!
+//! `jerm export-state` bundles the files that make up a profile (config,
+//! shortcuts, snippets, history, and user themes) into a single JSON archive
+//! for moving to another machine. `jerm import-state` compares an archive
+//! with the local files and restores it file by file: new files are added,
+//! identical ones skipped, and each file that differs is kept, replaced, or
+//! (for shortcuts, snippets, and history) merged.
+//!
+//! Shortcuts go through the [`ShortcutStore`] picked by `storage.shortcuts`,
+//! so they travel as a `shortcuts.json` entry whichever backend holds them.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::history::storage::HistoryEntry;
+use crate::schema::SchemaError;
+use crate::shortcuts::storage::{self as shortcuts, ShortcutsData, StorageError};
+use crate::shortcuts::ShortcutStore;
+use crate::snippets::storage::{self as snippets, SnippetsData};
+
+/// Archive format version written by this build
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// State files stored directly in the profile directory
+const FILES: &[&str] = &["config.toml", SHORTCUTS_FILE, "snippets.json", HISTORY_FILE];
+
+/// Archive entry holding the shortcuts, whichever store they're kept in
+const SHORTCUTS_FILE: &str = "shortcuts.json";
+
+/// Name of the history file, the only one that can be scrubbed
+const HISTORY_FILE: &str = "history";
+
+/// Directory holding user theme files
+const THEMES_DIR: &str = "themes";
+
+/// Words that mark a command as likely to contain a credential
+const SECRET_MARKERS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "bearer ",
+];
+
+/// Errors that can occur while exporting or importing state
+#[derive(Error, Debug)]
+pub enum StateError {
+    #[error("Failed to access state file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Failed to parse state: {0}")]
+    Format(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+
+    #[error(transparent)]
+    Shortcuts(#[from] StorageError),
+
+    #[error("Config directory not found")]
+    ConfigDirNotFound,
+
+    #[error("Archive version {0} is newer than this jerm supports ({ARCHIVE_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error("Archive contains an unexpected file: {0}")]
+    UnexpectedFile(String),
+}
+
+/// A snapshot of a profile's state files
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateArchive {
+    /// Format version, checked on import
+    pub version: u32,
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    /// Profile the snapshot was taken from
+    pub profile: String,
+    /// File contents keyed by path relative to the profile directory
+    pub files: BTreeMap<String, String>,
+}
+
+impl StateArchive {
+    /// Read every state file in `dir` up front, so the snapshot is taken in one go
+    ///
+    /// With `scrub`, history entries that look like they hold a credential
+    /// are left out.
+    pub fn capture(
+        dir: &Path,
+        profile: &str,
+        scrub: bool,
+        store: &mut dyn ShortcutStore,
+    ) -> Result<Self, StateError> {
+        let mut files = BTreeMap::new();
+        for name in FILES {
+            if let Some(contents) = read_local(name, dir, store)? {
+                let contents = if scrub && *name == HISTORY_FILE {
+                    scrub_history(&contents)
+                } else {
+                    contents
+                };
+                files.insert((*name).to_string(), contents);
+            }
+        }
+
+        if let Ok(entries) = fs::read_dir(dir.join(THEMES_DIR)) {
+            for entry in entries.filter_map(Result::ok) {
+                let name = format!("{THEMES_DIR}/{}", entry.file_name().to_string_lossy());
+                if is_known_file(&name) {
+                    files.insert(name, fs::read_to_string(entry.path())?);
+                }
+            }
+        }
+
+        Ok(Self {
+            version: ARCHIVE_VERSION,
+            created_at: Utc::now(),
+            profile: profile.to_string(),
+            files,
+        })
+    }
+
+    /// Write the archive, replacing `path` only once it's fully written
+    pub fn write(&self, path: &Path) -> Result<(), StateError> {
+        write_atomic(path, &serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Read an archive, rejecting newer formats and files jerm doesn't own
+    pub fn read(path: &Path) -> Result<Self, StateError> {
+        let archive: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if archive.version > ARCHIVE_VERSION {
+            return Err(StateError::UnsupportedVersion(archive.version));
+        }
+        if let Some(name) = archive.files.keys().find(|name| !is_known_file(name)) {
+            return Err(StateError::UnexpectedFile(name.clone()));
+        }
+        Ok(archive)
+    }
+}
+
+/// How an archived file compares with the local copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    /// No local copy exists
+    New,
+    /// The local copy has the same contents
+    Unchanged,
+    /// The local copy differs
+    Conflict,
+}
+
+/// What to do with an archived file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Leave the local copy as it is
+    Keep,
+    /// Overwrite the local copy with the archived one
+    Replace,
+    /// Combine both copies, local entries winning on clashes
+    Merge,
+}
+
+/// One archived file and what importing it will do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// Path relative to the profile directory
+    pub name: String,
+    pub status: FileStatus,
+    pub resolution: Resolution,
+}
+
+/// Compare an archive with the files in `dir`
+///
+/// New files default to being restored and conflicts to keeping the local
+/// copy, so nothing is overwritten unless asked for.
+pub fn plan(
+    archive: &StateArchive,
+    dir: &Path,
+    store: &mut dyn ShortcutStore,
+) -> Result<Vec<PlannedFile>, StateError> {
+    archive
+        .files
+        .iter()
+        .map(|(name, contents)| {
+            let (status, resolution) = match read_local(name, dir, store)? {
+                None => (FileStatus::New, Resolution::Replace),
+                Some(local) if same_contents(name, &local, contents) => {
+                    (FileStatus::Unchanged, Resolution::Keep)
+                }
+                Some(_) => (FileStatus::Conflict, Resolution::Keep),
+            };
+            Ok(PlannedFile {
+                name: name.clone(),
+                status,
+                resolution,
+            })
+        })
+        .collect()
+}
+
+/// Whether a file holds entries that can be merged instead of replaced
+pub fn can_merge(name: &str) -> bool {
+    matches!(name, SHORTCUTS_FILE | "snippets.json" | HISTORY_FILE)
+}
+
+/// Restore the planned files into `dir`, returning how many were written
+///
+/// Each local file that gets overwritten is first copied to `<name>.bak`.
+/// Shortcuts are handed to `store` rather than written to `dir`.
+pub fn apply(
+    archive: &StateArchive,
+    dir: &Path,
+    plan: &[PlannedFile],
+    store: &mut dyn ShortcutStore,
+) -> Result<usize, StateError> {
+    let mut written = 0;
+    for file in plan
+        .iter()
+        .filter(|file| file.resolution != Resolution::Keep)
+    {
+        let Some(incoming) = archive.files.get(&file.name) else {
+            continue;
+        };
+        let target = dir.join(&file.name);
+        let local = read_local(&file.name, dir, store)?;
+        let contents = match (file.resolution, &local) {
+            (Resolution::Merge, Some(local)) => merge(&file.name, local, incoming)?,
+            _ => incoming.clone(),
+        };
+        if let Some(local) = &local {
+            write_atomic(&backup_path(&target), local)?;
+        }
+        if file.name == SHORTCUTS_FILE {
+            let data: ShortcutsData = shortcuts::SCHEMA.parse(&contents)?;
+            store.replace(&data.shortcuts)?;
+        } else {
+            write_atomic(&target, &contents)?;
+        }
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Combine a local and an archived copy of a mergeable file
+pub fn merge(name: &str, local: &str, incoming: &str) -> Result<String, StateError> {
+    match name {
+        SHORTCUTS_FILE => {
+            let mut data: ShortcutsData = shortcuts::SCHEMA.parse(local)?;
+            let other: ShortcutsData = shortcuts::SCHEMA.parse(incoming)?;
+            for shortcut in other.shortcuts {
+                if !data.shortcuts.iter().any(|s| s.path == shortcut.path) {
+                    data.shortcuts.push(shortcut);
+                }
+            }
+            Ok(shortcuts::SCHEMA.to_string(&data)?)
+        }
+        "snippets.json" => {
+            let mut data: SnippetsData = snippets::SCHEMA.parse(local)?;
+            let other: SnippetsData = snippets::SCHEMA.parse(incoming)?;
+            for snippet in other.snippets {
+                if !data.snippets.iter().any(|s| s.name == snippet.name) {
+                    data.snippets.push(snippet);
+                }
+            }
+            data.snippets.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(snippets::SCHEMA.to_string(&data)?)
+        }
+        HISTORY_FILE => {
+            let mut seen = HashSet::new();
+            let mut entries: Vec<HistoryEntry> = local
+                .lines()
+                .chain(incoming.lines())
+                .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok())
+                .filter(|entry| {
+                    seen.insert((entry.command.clone(), entry.cwd.clone(), entry.timestamp))
+                })
+                .collect();
+            entries.sort_by_key(|entry| entry.timestamp);
+            let mut contents = String::new();
+            for entry in &entries {
+                contents.push_str(&serde_json::to_string(entry)?);
+                contents.push('\n');
+            }
+            Ok(contents)
+        }
+        _ => Ok(incoming.to_string()),
+    }
+}
+
+/// Drop history lines whose command looks like it contains a credential
+pub fn scrub_history(contents: &str) -> String {
+    contents
+        .lines()
+        .filter(|line| {
+            serde_json::from_str::<HistoryEntry>(line)
+                .is_ok_and(|entry| !looks_secret(&entry.command))
+        })
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Whether a command mentions something credential-like
+fn looks_secret(command: &str) -> bool {
+    let command = command.to_lowercase();
+    SECRET_MARKERS.iter().any(|marker| command.contains(marker))
+}
+
+/// Whether `name` is a state file jerm would export
+fn is_known_file(name: &str) -> bool {
+    FILES.contains(&name)
+        || name
+            .strip_prefix(THEMES_DIR)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .and_then(|file| file.strip_suffix(".toml"))
+            .is_some_and(crate::paths::is_valid_name)
+}
+
+/// Read the local copy of an archive entry, treating a missing one as `None`
+///
+/// Shortcuts come from `store`, with an empty list counting as missing.
+fn read_local(
+    name: &str,
+    dir: &Path,
+    store: &mut dyn ShortcutStore,
+) -> Result<Option<String>, StateError> {
+    if name != SHORTCUTS_FILE {
+        return Ok(read_optional(&dir.join(name))?);
+    }
+    let shortcuts = store.load()?;
+    if shortcuts.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        shortcuts::SCHEMA.to_string(&ShortcutsData { shortcuts })?,
+    ))
+}
+
+/// Whether a local and an archived copy hold the same state
+///
+/// Shortcuts are compared by their entries, since an archive written from
+/// a hand-edited `shortcuts.json` may be laid out differently.
+fn same_contents(name: &str, local: &str, incoming: &str) -> bool {
+    if local == incoming {
+        return true;
+    }
+    name == SHORTCUTS_FILE
+        && matches!(
+            (
+                shortcuts::SCHEMA.parse::<ShortcutsData>(local),
+                shortcuts::SCHEMA.parse::<ShortcutsData>(incoming),
+            ),
+            (Ok(a), Ok(b)) if a.shortcuts == b.shortcuts
+        )
+}
+
+/// Read a file, treating a missing one as `None`
+fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Path a file is backed up to before being overwritten
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Write through a temporary file so readers never see a partial file
+fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shortcuts::storage::Shortcut;
+    use crate::shortcuts::store::MemoryStore;
+    use crate::testutil::ScratchDir;
+
+    fn temp_dir(name: &str) -> ScratchDir {
+        let dir = ScratchDir::new(&format!("state-{name}"));
+        fs::create_dir_all(dir.join(THEMES_DIR)).unwrap();
+        dir
+    }
+
+    fn history_line(command: &str) -> String {
+        serde_json::to_string(&HistoryEntry::new(command, PathBuf::from("/tmp"))).unwrap() + "\n"
+    }
+
+    #[test]
+    fn test_capture_and_read() {
+        let dir = temp_dir("capture");
+        let ls = history_line("ls");
+        fs::write(dir.join("config.toml"), "[ui]\n").unwrap();
+        fs::write(
+            dir.join("history"),
+            ls.clone() + &history_line("export API_TOKEN=abc"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join(THEMES_DIR).join("dusk.toml"),
+            "base = \"default\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join(THEMES_DIR).join("notes.txt"), "").unwrap();
+
+        let archive =
+            StateArchive::capture(&dir, "default", true, &mut MemoryStore::default()).unwrap();
+        let names: Vec<&str> = archive.files.keys().map(String::as_str).collect();
+        assert_eq!(names, ["config.toml", "history", "themes/dusk.toml"]);
+        assert_eq!(archive.files["history"], ls);
+
+        let path = dir.join("state.json");
+        archive.write(&path).unwrap();
+        assert_eq!(StateArchive::read(&path).unwrap(), archive);
+    }
+
+    #[test]
+    fn test_read_rejects_unknown_files() {
+        let dir = temp_dir("reject");
+        let mut archive =
+            StateArchive::capture(&dir, "default", false, &mut MemoryStore::default()).unwrap();
+        archive
+            .files
+            .insert("../.bashrc".to_string(), String::new());
+        let path = dir.join("state.json");
+        archive.write(&path).unwrap();
+        assert!(matches!(
+            StateArchive::read(&path),
+            Err(StateError::UnexpectedFile(_))
+        ));
+
+        archive.files.clear();
+        archive.version = ARCHIVE_VERSION + 1;
+        archive.write(&path).unwrap();
+        assert!(matches!(
+            StateArchive::read(&path),
+            Err(StateError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_plan_and_apply() {
+        let dir = temp_dir("apply");
+        let ls = history_line("ls");
+        fs::write(dir.join("config.toml"), "[ui]\n").unwrap();
+        fs::write(dir.join("history"), &ls).unwrap();
+
+        let mut archive =
+            StateArchive::capture(&dir, "default", false, &mut MemoryStore::default()).unwrap();
+        archive
+            .files
+            .insert("history".to_string(), history_line("make"));
+        archive
+            .files
+            .insert("snippets.json".to_string(), "{\"snippets\":[]}".to_string());
+
+        let mut store = MemoryStore::default();
+        let mut plan = plan(&archive, &dir, &mut store).unwrap();
+        let statuses: Vec<(&str, FileStatus, Resolution)> = plan
+            .iter()
+            .map(|file| (file.name.as_str(), file.status, file.resolution))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("config.toml", FileStatus::Unchanged, Resolution::Keep),
+                ("history", FileStatus::Conflict, Resolution::Keep),
+                ("snippets.json", FileStatus::New, Resolution::Replace),
+            ]
+        );
+
+        plan[1].resolution = Resolution::Merge;
+        assert_eq!(apply(&archive, &dir, &plan, &mut store).unwrap(), 2);
+        let history = fs::read_to_string(dir.join("history")).unwrap();
+        assert_eq!(history.lines().count(), 2);
+        assert_eq!(fs::read_to_string(dir.join("history.bak")).unwrap(), ls);
+        assert!(dir.join("snippets.json").exists());
+    }
+
+    #[test]
+    fn test_shortcuts_go_through_store() {
+        let dir = temp_dir("shortcuts");
+        // A stale file the configured store doesn't read
+        fs::write(dir.join(SHORTCUTS_FILE), "{\"shortcuts\":[]}").unwrap();
+        let mut store = MemoryStore(vec![Shortcut::new(PathBuf::from("/work"))]);
+        let archive = StateArchive::capture(&dir, "default", false, &mut store).unwrap();
+        let data: ShortcutsData = shortcuts::SCHEMA
+            .parse(&archive.files[SHORTCUTS_FILE])
+            .unwrap();
+        assert_eq!(data.shortcuts, store.0);
+
+        let mut other = MemoryStore(vec![Shortcut::new(PathBuf::from("/home"))]);
+        let mut plan = plan(&archive, &dir, &mut other).unwrap();
+        assert_eq!(plan[0].status, FileStatus::Conflict);
+        plan[0].resolution = Resolution::Merge;
+        assert_eq!(apply(&archive, &dir, &plan, &mut other).unwrap(), 1);
+        let paths: Vec<&Path> = other.0.iter().map(|s| s.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/home"), Path::new("/work")]);
+        assert!(dir.join("shortcuts.json.bak").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join(SHORTCUTS_FILE)).unwrap(),
+            "{\"shortcuts\":[]}"
+        );
+        assert_eq!(
+            super::plan(&archive, &dir, &mut store).unwrap()[0].status,
+            FileStatus::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_local_entries() {
+        let local =
+            r#"{"snippets":[{"name":"b","command":"local","created_at":"2024-01-01T00:00:00Z"}]}"#;
+        let incoming = r#"{"snippets":[
+            {"name":"b","command":"remote","created_at":"2024-01-01T00:00:00Z"},
+            {"name":"a","command":"new","created_at":"2024-01-01T00:00:00Z"}]}"#;
+        let merged: SnippetsData =
+            serde_json::from_str(&merge("snippets.json", local, incoming).unwrap()).unwrap();
+        let commands: Vec<&str> = merged.snippets.iter().map(|s| s.command.as_str()).collect();
+        assert_eq!(commands, ["new", "local"]);
+
+        let line = history_line("ls");
+        assert_eq!(merge(HISTORY_FILE, &line, &line).unwrap(), line);
+    }
+
+    #[test]
+    fn test_looks_secret() {
+        assert!(looks_secret(
+            "curl -H 'Authorization: Bearer x' example.com"
+        ));
+        assert!(looks_secret("export GITHUB_TOKEN=abc"));
+        assert!(!looks_secret("cargo test"));
+    }
+}
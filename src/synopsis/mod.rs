@@ -0,0 +1,165 @@
+//! One-line command synopses shown under the input while typing
+//!
+//! Descriptions come from a local tldr page cache when one is installed
+//! (tldr, tealdeer, or the node client), falling back to `whatis`. Lookups
+//! run on a worker thread and each command is looked up once per session.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Synopses looked up so far, and the worker that looks them up
+pub struct SynopsisCache {
+    /// Command name to its synopsis (`None` when none was found)
+    entries: HashMap<String, Option<String>>,
+    tx: Sender<String>,
+    rx: Receiver<(String, Option<String>)>,
+}
+
+impl SynopsisCache {
+    /// Start the lookup worker
+    pub fn new() -> Self {
+        let (tx, worker_rx) = mpsc::channel::<String>();
+        let (worker_tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            while let Ok(command) = worker_rx.recv() {
+                let synopsis = lookup(&command);
+                if worker_tx.send((command, synopsis)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            entries: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    /// Look up a command in the background unless it was already requested
+    pub fn request(&mut self, command: &str) {
+        if !self.entries.contains_key(command) {
+            self.entries.insert(command.to_string(), None);
+            let _ = self.tx.send(command.to_string());
+        }
+    }
+
+    /// Store finished lookups, returning true if any arrived
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((command, synopsis)) = self.rx.try_recv() {
+            self.entries.insert(command, synopsis);
+            changed = true;
+        }
+        changed
+    }
+
+    /// The synopsis of a command, once found
+    pub fn get(&self, command: &str) -> Option<&str> {
+        self.entries.get(command)?.as_deref()
+    }
+}
+
+impl Default for SynopsisCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Find a one-line description of a command
+pub fn lookup(command: &str) -> Option<String> {
+    tldr_description(command).or_else(|| whatis_description(command))
+}
+
+/// Directories where tldr clients keep their page caches
+fn tldr_page_dirs() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(cache) = dirs::cache_dir() {
+        roots.push(cache.join("tldr/pages"));
+        roots.push(cache.join("tealdeer/tldr-pages/pages"));
+        roots.push(cache.join("tealdeer/tldr-pages/pages.en"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(".tldrc/tldr/pages"));
+        roots.push(home.join(".tldr/cache/pages"));
+    }
+
+    let platform = if cfg!(target_os = "macos") {
+        "osx"
+    } else {
+        "linux"
+    };
+    roots
+        .into_iter()
+        .flat_map(|root| [root.join("common"), root.join(platform)])
+        .collect()
+}
+
+/// Description from the command's tldr page, if one is cached locally
+fn tldr_description(command: &str) -> Option<String> {
+    tldr_page_dirs()
+        .into_iter()
+        .find_map(|dir| fs::read_to_string(dir.join(format!("{command}.md"))).ok())
+        .and_then(|page| parse_tldr(&page))
+}
+
+/// First description line of a tldr page (the `> ` lines after the title)
+fn parse_tldr(page: &str) -> Option<String> {
+    page.lines()
+        .filter_map(|line| line.strip_prefix("> "))
+        .find(|line| !line.starts_with("More information"))
+        .map(|line| line.trim().trim_end_matches('.').to_string())
+}
+
+/// Description from `whatis`, if the command has a man page
+fn whatis_description(command: &str) -> Option<String> {
+    let output = Command::new("whatis").arg(command).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_whatis(&String::from_utf8_lossy(&output.stdout), command)
+}
+
+/// Description of `command` in `whatis` output such as
+/// `ls (1)               - list directory contents`
+fn parse_whatis(output: &str, command: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (names, description) = line.split_once(" - ")?;
+        let matches = names
+            .split(',')
+            .any(|name| name.split_whitespace().next() == Some(command));
+        matches.then(|| description.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tldr() {
+        let page = "# tar\n\n> Archiving utility.\n> Often combined with a compression method.\n> More information: <https://www.gnu.org/software/tar>.\n\n- Create an archive:\n";
+        assert_eq!(parse_tldr(page).as_deref(), Some("Archiving utility"));
+        assert_eq!(parse_tldr("# empty\n"), None);
+    }
+
+    #[test]
+    fn test_parse_whatis() {
+        let output = "ls (1)               - list directory contents\n";
+        assert_eq!(
+            parse_whatis(output, "ls").as_deref(),
+            Some("list directory contents")
+        );
+        let output = "git-log (1)          - Show commit logs\ngit (1)              - the stupid content tracker\n";
+        assert_eq!(
+            parse_whatis(output, "git").as_deref(),
+            Some("the stupid content tracker")
+        );
+        assert_eq!(parse_whatis("nothing appropriate.\n", "zz"), None);
+    }
+}
@@ -0,0 +1,54 @@
+//! Fixtures shared by the unit tests
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An empty directory for one test, removed with everything in it on drop
+///
+/// Each one gets a path of its own, so tests running in parallel (or the
+/// same test in two processes) never share files.
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    /// Create the directory; `name` only shows in the path, to tell whose
+    /// leftovers are whose
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "jerm-{name}-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        // Resolved, so it compares equal to paths the code under test resolves
+        Self(path.canonicalize().unwrap())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Deref for ScratchDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for ScratchDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -1,96 +1,386 @@
-//! Cold-tone color palette for Jerm UI
+//! Color themes for Jerm UI
 //!
-//! Inspired by Warp Terminal with blues, cyans, and teals.
+//! The built-in `default` theme is cold-toned, inspired by Warp Terminal with
+//! blues, cyans, and teals; `light` suits terminals with a light background.
+//! Users can add their own themes as TOML files (see [`super::loader`]).
 
 use ratatui::style::Color;
 
-/// Color palette for the entire application
-pub struct Palette;
+/// Colors used across the application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Name of the theme (preset or file name)
+    pub name: String,
 
-impl Palette {
     // ─────────────────────────────────────────────────────────────────────────
     // UI Colors
     // ─────────────────────────────────────────────────────────────────────────
-
     /// Default border color (muted blue-gray)
-    pub const BORDER_DEFAULT: Color = Color::Rgb(88, 110, 130);
+    pub border_default: Color,
 
     /// Active/focused border color (bright cyan)
-    pub const BORDER_ACTIVE: Color = Color::Rgb(80, 200, 220);
+    pub border_active: Color,
 
     /// Muted text for hints and secondary info
-    pub const TEXT_MUTED: Color = Color::Rgb(100, 120, 140);
+    pub text_muted: Color,
 
     /// Normal text color
-    pub const TEXT_NORMAL: Color = Color::Rgb(200, 210, 220);
+    pub text_normal: Color,
 
     /// Highlighted/selected background
-    pub const BG_SELECTED: Color = Color::Rgb(45, 65, 85);
+    pub bg_selected: Color,
 
     /// Prompt directory path (bright cyan-blue)
-    pub const PROMPT_PATH: Color = Color::Rgb(80, 200, 220);
+    pub prompt_path: Color,
+
+    /// Prompt duration of a slow previous command - warm yellow
+    pub prompt_duration: Color,
+
+    /// Prompt badge for a directory with a note - amber
+    pub prompt_note: Color,
+
+    /// Output a command wrote to stderr (soft red)
+    pub text_error: Color,
 
     // ─────────────────────────────────────────────────────────────────────────
     // Syntax Highlighting Colors
     // ─────────────────────────────────────────────────────────────────────────
-
     /// Command names (blue)
-    pub const SYNTAX_COMMAND: Color = Color::Rgb(100, 160, 240);
+    pub syntax_command: Color,
 
     /// Flags like --help, -v (orange/amber)
-    pub const SYNTAX_FLAG: Color = Color::Rgb(230, 160, 80);
+    pub syntax_flag: Color,
 
     /// File paths (teal/cyan)
-    pub const SYNTAX_PATH: Color = Color::Rgb(80, 200, 180);
+    pub syntax_path: Color,
 
     /// Quoted strings (yellow/gold)
-    pub const SYNTAX_STRING: Color = Color::Rgb(230, 200, 100);
+    pub syntax_string: Color,
 
     /// Numbers (purple/lavender)
-    pub const SYNTAX_NUMBER: Color = Color::Rgb(180, 140, 220);
+    pub syntax_number: Color,
 
     /// Operators like |, >, &&, etc. (light gray)
-    pub const SYNTAX_OPERATOR: Color = Color::Rgb(160, 170, 180);
+    pub syntax_operator: Color,
 
     /// Plain text (default)
-    pub const SYNTAX_TEXT: Color = Color::Rgb(200, 210, 220);
+    pub syntax_text: Color,
 
     // ─────────────────────────────────────────────────────────────────────────
     // Git Colors
     // ─────────────────────────────────────────────────────────────────────────
-
     /// Git branch name (gray)
-    pub const GIT_BRANCH: Color = Color::Rgb(140, 150, 160);
+    pub git_branch: Color,
 
     /// Git ahead/behind indicators (cyan)
-    pub const GIT_AHEAD_BEHIND: Color = Color::Rgb(80, 200, 220);
+    pub git_ahead_behind: Color,
+
+    /// Count of staged files (green)
+    pub git_staged: Color,
+
+    /// Count of modified, unstaged files (yellow)
+    pub git_modified: Color,
+
+    /// Count of untracked files (gray)
+    pub git_untracked: Color,
+
+    /// Count of stashes (purple)
+    pub git_stash: Color,
+
+    /// Rebase, merge, or similar in progress (orange)
+    pub git_operation: Color,
 
     // ─────────────────────────────────────────────────────────────────────────
-    // Sidebar Colors
+    // Block Colors
     // ─────────────────────────────────────────────────────────────────────────
+    /// Successful command marker (soft green)
+    pub block_success: Color,
+
+    /// Failed command marker (muted red)
+    pub block_failure: Color,
 
+    /// Warning count badge (amber)
+    pub block_warning: Color,
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Sidebar Colors
+    // ─────────────────────────────────────────────────────────────────────────
     /// Shortcut number (bright cyan)
-    pub const SIDEBAR_NUMBER: Color = Color::Rgb(80, 200, 220);
+    pub sidebar_number: Color,
 
     /// Shortcut path (normal text)
-    pub const SIDEBAR_PATH: Color = Color::Rgb(200, 210, 220);
+    pub sidebar_path: Color,
 
     /// Relative time indicator (muted)
-    pub const SIDEBAR_TIME: Color = Color::Rgb(100, 120, 140);
+    pub sidebar_time: Color,
 
     // ─────────────────────────────────────────────────────────────────────────
     // Navigator Colors
     // ─────────────────────────────────────────────────────────────────────────
-
     /// Header path in navigator (amber/gold)
-    pub const NAV_HEADER: Color = Color::Rgb(230, 180, 100);
+    pub nav_header: Color,
 
     /// Selected item background (dark blue)
-    pub const NAV_SELECTED_BG: Color = Color::Rgb(40, 80, 120);
+    pub nav_selected_bg: Color,
 
     /// Selected item foreground (bright white)
-    pub const NAV_SELECTED_FG: Color = Color::Rgb(240, 245, 250);
+    pub nav_selected_fg: Color,
 
     /// Key hints (cyan)
-    pub const NAV_KEY_HINT: Color = Color::Rgb(80, 200, 220);
+    pub nav_key_hint: Color,
+
+    /// Unreadable directories and read errors (muted red)
+    pub nav_denied: Color,
+}
+
+impl Theme {
+    /// Names of the built-in themes
+    pub const PRESETS: &'static [&'static str] = &["default", "light"];
+
+    /// Built-in theme by name
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
+    /// Cold-tone theme for dark terminals (the default)
+    pub fn dark() -> Self {
+        Self {
+            name: "default".to_string(),
+            border_default: Color::Rgb(88, 110, 130),
+            border_active: Color::Rgb(80, 200, 220),
+            text_muted: Color::Rgb(100, 120, 140),
+            text_normal: Color::Rgb(200, 210, 220),
+            bg_selected: Color::Rgb(45, 65, 85),
+            prompt_path: Color::Rgb(80, 200, 220),
+            prompt_duration: Color::Rgb(230, 200, 100),
+            prompt_note: Color::Rgb(230, 180, 100),
+            text_error: Color::Rgb(235, 130, 125),
+            syntax_command: Color::Rgb(100, 160, 240),
+            syntax_flag: Color::Rgb(230, 160, 80),
+            syntax_path: Color::Rgb(80, 200, 180),
+            syntax_string: Color::Rgb(230, 200, 100),
+            syntax_number: Color::Rgb(180, 140, 220),
+            syntax_operator: Color::Rgb(160, 170, 180),
+            syntax_text: Color::Rgb(200, 210, 220),
+            git_branch: Color::Rgb(140, 150, 160),
+            git_ahead_behind: Color::Rgb(80, 200, 220),
+            git_staged: Color::Rgb(120, 200, 140),
+            git_modified: Color::Rgb(230, 200, 100),
+            git_untracked: Color::Rgb(140, 150, 160),
+            git_stash: Color::Rgb(180, 140, 220),
+            git_operation: Color::Rgb(240, 150, 70),
+            block_success: Color::Rgb(120, 200, 140),
+            block_failure: Color::Rgb(220, 110, 110),
+            block_warning: Color::Rgb(230, 200, 100),
+            sidebar_number: Color::Rgb(80, 200, 220),
+            sidebar_path: Color::Rgb(200, 210, 220),
+            sidebar_time: Color::Rgb(100, 120, 140),
+            nav_header: Color::Rgb(230, 180, 100),
+            nav_selected_bg: Color::Rgb(40, 80, 120),
+            nav_selected_fg: Color::Rgb(240, 245, 250),
+            nav_key_hint: Color::Rgb(80, 200, 220),
+            nav_denied: Color::Rgb(200, 100, 110),
+        }
+    }
+
+    /// Theme for terminals with a light background
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            border_default: Color::Rgb(150, 160, 175),
+            border_active: Color::Rgb(0, 120, 160),
+            text_muted: Color::Rgb(110, 120, 135),
+            text_normal: Color::Rgb(40, 45, 55),
+            bg_selected: Color::Rgb(215, 225, 235),
+            prompt_path: Color::Rgb(0, 110, 150),
+            prompt_duration: Color::Rgb(170, 110, 0),
+            prompt_note: Color::Rgb(170, 100, 0),
+            text_error: Color::Rgb(175, 35, 35),
+            syntax_command: Color::Rgb(30, 90, 200),
+            syntax_flag: Color::Rgb(180, 90, 0),
+            syntax_path: Color::Rgb(0, 130, 120),
+            syntax_string: Color::Rgb(150, 110, 0),
+            syntax_number: Color::Rgb(130, 60, 170),
+            syntax_operator: Color::Rgb(100, 105, 115),
+            syntax_text: Color::Rgb(40, 45, 55),
+            git_branch: Color::Rgb(100, 105, 115),
+            git_ahead_behind: Color::Rgb(0, 120, 160),
+            git_staged: Color::Rgb(30, 140, 70),
+            git_modified: Color::Rgb(170, 110, 0),
+            git_untracked: Color::Rgb(100, 105, 115),
+            git_stash: Color::Rgb(130, 60, 170),
+            git_operation: Color::Rgb(190, 90, 0),
+            block_success: Color::Rgb(30, 140, 70),
+            block_failure: Color::Rgb(190, 40, 40),
+            block_warning: Color::Rgb(170, 110, 0),
+            sidebar_number: Color::Rgb(0, 120, 160),
+            sidebar_path: Color::Rgb(40, 45, 55),
+            sidebar_time: Color::Rgb(110, 120, 135),
+            nav_header: Color::Rgb(170, 100, 0),
+            nav_selected_bg: Color::Rgb(200, 220, 240),
+            nav_selected_fg: Color::Rgb(20, 25, 35),
+            nav_key_hint: Color::Rgb(0, 120, 160),
+            nav_denied: Color::Rgb(190, 50, 60),
+        }
+    }
+
+    /// Names of every color slot, in theme file order
+    pub const SLOTS: &'static [&'static str] = &[
+        "border_default",
+        "border_active",
+        "text_muted",
+        "text_normal",
+        "bg_selected",
+        "prompt_path",
+        "prompt_duration",
+        "prompt_note",
+        "text_error",
+        "syntax_command",
+        "syntax_flag",
+        "syntax_path",
+        "syntax_string",
+        "syntax_number",
+        "syntax_operator",
+        "syntax_text",
+        "git_branch",
+        "git_ahead_behind",
+        "git_staged",
+        "git_modified",
+        "git_untracked",
+        "git_stash",
+        "git_operation",
+        "block_success",
+        "block_failure",
+        "block_warning",
+        "sidebar_number",
+        "sidebar_path",
+        "sidebar_time",
+        "nav_header",
+        "nav_selected_bg",
+        "nav_selected_fg",
+        "nav_key_hint",
+        "nav_denied",
+    ];
+
+    /// Every color of the theme, in [`Self::SLOTS`] order
+    pub fn palette(&self) -> Vec<Color> {
+        let mut theme = self.clone();
+        Self::SLOTS
+            .iter()
+            .filter_map(|slot| theme.color_mut(slot).map(|color| *color))
+            .collect()
+    }
+
+    /// Color of a slot by its name in theme files
+    pub fn color(&self, key: &str) -> Option<Color> {
+        self.clone().color_mut(key).map(|color| *color)
+    }
+
+    /// Color slot by its name in theme files (e.g. `prompt_path`)
+    pub fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        let color = match key {
+            "border_default" => &mut self.border_default,
+            "border_active" => &mut self.border_active,
+            "text_muted" => &mut self.text_muted,
+            "text_normal" => &mut self.text_normal,
+            "bg_selected" => &mut self.bg_selected,
+            "prompt_path" => &mut self.prompt_path,
+            "prompt_duration" => &mut self.prompt_duration,
+            "prompt_note" => &mut self.prompt_note,
+            "text_error" => &mut self.text_error,
+            "syntax_command" => &mut self.syntax_command,
+            "syntax_flag" => &mut self.syntax_flag,
+            "syntax_path" => &mut self.syntax_path,
+            "syntax_string" => &mut self.syntax_string,
+            "syntax_number" => &mut self.syntax_number,
+            "syntax_operator" => &mut self.syntax_operator,
+            "syntax_text" => &mut self.syntax_text,
+            "git_branch" => &mut self.git_branch,
+            "git_ahead_behind" => &mut self.git_ahead_behind,
+            "git_staged" => &mut self.git_staged,
+            "git_modified" => &mut self.git_modified,
+            "git_untracked" => &mut self.git_untracked,
+            "git_stash" => &mut self.git_stash,
+            "git_operation" => &mut self.git_operation,
+            "block_success" => &mut self.block_success,
+            "block_failure" => &mut self.block_failure,
+            "block_warning" => &mut self.block_warning,
+            "sidebar_number" => &mut self.sidebar_number,
+            "sidebar_path" => &mut self.sidebar_path,
+            "sidebar_time" => &mut self.sidebar_time,
+            "nav_header" => &mut self.nav_header,
+            "nav_selected_bg" => &mut self.nav_selected_bg,
+            "nav_selected_fg" => &mut self.nav_selected_fg,
+            "nav_key_hint" => &mut self.nav_key_hint,
+            "nav_denied" => &mut self.nav_denied,
+            _ => return None,
+        };
+        Some(color)
+    }
+}
+
+/// Mix two colors; `amount` 0.0 gives `from` and 1.0 gives `to`
+///
+/// Only RGB colors can be mixed; other colors switch over halfway.
+pub fn blend(from: Color, to: Color, amount: f64) -> Color {
+    let amount = amount.clamp(0.0, 1.0);
+    match (from, to) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+            let mix = |a: u8, b: u8| {
+                (f64::from(a) + (f64::from(b) - f64::from(a)) * amount).round() as u8
+            };
+            Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+        }
+        _ if amount < 0.5 => from,
+        _ => to,
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_resolve() {
+        for name in Theme::PRESETS {
+            assert_eq!(Theme::preset(name).unwrap().name, *name);
+        }
+        assert!(Theme::preset("neon").is_none());
+    }
+
+    #[test]
+    fn test_color_mut() {
+        let mut theme = Theme::default();
+        *theme.color_mut("prompt_path").unwrap() = Color::Black;
+        assert_eq!(theme.prompt_path, Color::Black);
+        assert!(theme.color_mut("name").is_none());
+    }
+
+    #[test]
+    fn test_palette_covers_every_slot() {
+        let theme = Theme::light();
+        let palette = theme.palette();
+        assert_eq!(palette.len(), Theme::SLOTS.len());
+        assert_eq!(palette[5], theme.prompt_path);
+    }
+
+    #[test]
+    fn test_blend() {
+        let black = Color::Rgb(0, 0, 0);
+        let white = Color::Rgb(255, 255, 255);
+        assert_eq!(blend(black, white, 0.0), black);
+        assert_eq!(blend(black, white, 0.5), Color::Rgb(128, 128, 128));
+        assert_eq!(blend(black, white, 2.0), white);
+        assert_eq!(blend(Color::Gray, white, 0.7), white);
+    }
 }
@@ -0,0 +1,194 @@
+//! Contrast of the text on selected rows
+//!
+//! Selected rows draw a theme's text colors on one of its backgrounds, so a
+//! custom theme can leave them unreadable. Ratios follow WCAG 2: from 1:1
+//! (the same color) to 21:1 (black on white); 4.5:1 is the usual minimum
+//! for body text and 3:1 for large text. Muted text is left out, since
+//! it's meant to recede.
+//!
+//! Named and indexed colors are measured with xterm's default palette; the
+//! terminal's own default color can't be measured and skips the check.
+
+use std::fmt;
+
+use ratatui::style::Color;
+
+use super::{blend, Theme};
+
+/// Text and background slots drawn together on selected rows
+pub const PAIRS: &[(&str, &str)] = &[
+    ("nav_selected_fg", "nav_selected_bg"),
+    ("text_normal", "bg_selected"),
+    ("sidebar_path", "bg_selected"),
+    ("sidebar_number", "bg_selected"),
+];
+
+/// A pair of slots below the minimum contrast
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowContrast {
+    pub fg: &'static str,
+    pub bg: &'static str,
+    /// Contrast ratio before any adjustment
+    pub ratio: f64,
+}
+
+impl fmt::Display for LowContrast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {} is {:.1}:1", self.fg, self.bg, self.ratio)
+    }
+}
+
+/// Pairs of `theme` with less than `min` contrast
+pub fn check(theme: &Theme, min: f64) -> Vec<LowContrast> {
+    PAIRS
+        .iter()
+        .filter_map(|&(fg, bg)| {
+            let ratio = ratio(theme.color(fg)?, theme.color(bg)?)?;
+            (ratio < min).then_some(LowContrast { fg, bg, ratio })
+        })
+        .collect()
+}
+
+/// Move each text color below `min` contrast toward black or white until
+/// it reaches `min`, returning the pairs that were changed
+///
+/// A text color goes whichever way its background leaves more room; when
+/// neither reaches `min`, it ends at the extreme.
+pub fn enforce(theme: &mut Theme, min: f64) -> Vec<LowContrast> {
+    let low = check(theme, min);
+    for pair in &low {
+        let (Some(fg), Some(bg)) = (
+            theme.color(pair.fg).and_then(rgb),
+            theme.color(pair.bg).and_then(rgb),
+        ) else {
+            continue;
+        };
+        let fg = Color::Rgb(fg.0, fg.1, fg.2);
+        let bg = Color::Rgb(bg.0, bg.1, bg.2);
+        let black = Color::Rgb(0, 0, 0);
+        let white = Color::Rgb(255, 255, 255);
+        let target = if ratio(black, bg) > ratio(white, bg) {
+            black
+        } else {
+            white
+        };
+        // The smallest step of 5% that's enough
+        let adjusted = (1..=20)
+            .map(|step| blend(fg, target, f64::from(step) / 20.0))
+            .find(|&color| ratio(color, bg).is_some_and(|r| r >= min))
+            .unwrap_or(target);
+        if let Some(slot) = theme.color_mut(pair.fg) {
+            *slot = adjusted;
+        }
+    }
+    low
+}
+
+/// WCAG contrast ratio between two colors
+pub fn ratio(a: Color, b: Color) -> Option<f64> {
+    let (a, b) = (luminance(a)?, luminance(b)?);
+    Some((a.max(b) + 0.05) / (a.min(b) + 0.05))
+}
+
+/// Relative luminance, 0.0 for black to 1.0 for white
+fn luminance(color: Color) -> Option<f64> {
+    let (r, g, b) = rgb(color)?;
+    let linear = |c: u8| {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+}
+
+/// RGB value of a color in xterm's default palette
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let index = match color {
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Reset => return None,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    Some(match index {
+        0..=15 => BASIC[index as usize],
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let n = index - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratio() {
+        let white = Color::Rgb(255, 255, 255);
+        assert!((ratio(Color::Black, white).unwrap() - 21.0).abs() < 0.01);
+        assert!((ratio(white, Color::Indexed(231)).unwrap() - 1.0).abs() < 0.01);
+        assert_eq!(ratio(Color::Reset, white), None);
+        for name in Theme::PRESETS {
+            assert_eq!(check(&Theme::preset(name).unwrap(), 3.0), []);
+        }
+    }
+
+    #[test]
+    fn test_enforce() {
+        let mut theme = Theme::dark();
+        theme.nav_selected_fg = Color::Rgb(60, 90, 130);
+        theme.text_normal = Color::Reset;
+        let changed = enforce(&mut theme, 4.5);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].fg, "nav_selected_fg");
+        assert!(changed[0].ratio < 1.5);
+        assert!(check(&theme, 4.5).is_empty());
+        assert_eq!(theme.text_normal, Color::Reset);
+
+        // Nothing reaches 21:1 on gray, so the text ends at the extreme
+        theme.nav_selected_bg = Color::Rgb(128, 128, 128);
+        enforce(&mut theme, 21.0);
+        assert_eq!(theme.nav_selected_fg, Color::Rgb(0, 0, 0));
+    }
+}
@@ -0,0 +1,97 @@
+//! State of the `jerm theme preview` gallery
+
+use super::loader;
+use super::Theme;
+
+/// An installed theme, or why its file couldn't be loaded
+#[derive(Debug, Clone)]
+pub struct GalleryEntry {
+    pub name: String,
+    pub theme: Result<Theme, String>,
+}
+
+/// Every installed theme, one card each, with one selected
+#[derive(Debug, Clone)]
+pub struct ThemeGallery {
+    pub entries: Vec<GalleryEntry>,
+    /// Index of the selected card
+    pub selected: usize,
+    /// Index of the first visible card
+    pub scroll: usize,
+}
+
+impl ThemeGallery {
+    /// Load all installed themes, selecting `current`
+    pub fn load(current: &str) -> Self {
+        let entries = loader::list_themes()
+            .into_iter()
+            .map(|name| GalleryEntry {
+                theme: loader::load_theme(&name).map_err(|e| e.to_string()),
+                name,
+            })
+            .collect();
+        Self::new(entries, current)
+    }
+
+    fn new(entries: Vec<GalleryEntry>, current: &str) -> Self {
+        let selected = entries.iter().position(|e| e.name == current).unwrap_or(0);
+        Self {
+            entries,
+            selected,
+            scroll: 0,
+        }
+    }
+
+    /// Move the selection by `delta` cards, stopping at either end
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Keep the selected card among the `visible` cards shown
+    pub fn adjust_scroll(&mut self, visible: usize) {
+        let visible = visible.max(1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + visible {
+            self.scroll = self.selected + 1 - visible;
+        }
+    }
+
+    /// Name of the selected theme, if it loaded
+    pub fn selected_name(&self) -> Option<&str> {
+        let entry = self.entries.get(self.selected)?;
+        entry.theme.is_ok().then_some(entry.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gallery_selection() {
+        let entries = ["broken", "default", "light"]
+            .into_iter()
+            .map(|name| GalleryEntry {
+                name: name.to_string(),
+                theme: Theme::preset(name).ok_or_else(|| "invalid color".to_string()),
+            })
+            .collect();
+        let mut gallery = ThemeGallery::new(entries, "light");
+        assert_eq!(gallery.selected, 2);
+
+        gallery.move_by(5);
+        assert_eq!(gallery.selected, 2);
+        gallery.adjust_scroll(2);
+        assert_eq!(gallery.scroll, 1);
+
+        gallery.move_by(-5);
+        assert_eq!(gallery.selected_name(), None);
+        gallery.adjust_scroll(2);
+        assert_eq!(gallery.scroll, 0);
+
+        gallery.move_by(1);
+        assert_eq!(gallery.selected_name(), Some("default"));
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! Provides icons with fallback variants for terminals without Nerd Fonts.
 
-use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether Nerd Font icons are enabled, set from the config at startup
+static NERD_FONTS: AtomicBool = AtomicBool::new(false);
 
 /// A pair of icons: nerd font version and fallback
 #[derive(Debug, Clone, Copy)]
@@ -39,6 +42,15 @@ impl Icons {
     /// Up arrow (for parent directory)
     pub const UP_ARROW: IconPair = IconPair::new("\u{f062}", ".."); //
 
+    /// Lock icon (for directories without read permission)
+    pub const LOCK: IconPair = IconPair::new("\u{f023}", "\u{1F512}"); //
+
+    /// Pin icon (for pinned shortcuts)
+    pub const PIN: IconPair = IconPair::new("\u{f08d}", "*"); //
+
+    /// Note icon (prompt badge for directories with a note)
+    pub const NOTE: IconPair = IconPair::new("\u{f249}", "\u{270E}"); //
+
     /// Create Icons with Nerd Font detection
     pub fn new() -> Self {
         Self {
@@ -84,6 +96,33 @@ impl Icons {
         }
     }
 
+    /// Get the appropriate lock icon
+    pub fn lock(&self) -> &'static str {
+        if self.use_nerd_fonts {
+            Self::LOCK.nerd
+        } else {
+            Self::LOCK.fallback
+        }
+    }
+
+    /// Get the appropriate pin icon
+    pub fn pin(&self) -> &'static str {
+        if self.use_nerd_fonts {
+            Self::PIN.nerd
+        } else {
+            Self::PIN.fallback
+        }
+    }
+
+    /// Get the appropriate note icon
+    pub fn note(&self) -> &'static str {
+        if self.use_nerd_fonts {
+            Self::NOTE.nerd
+        } else {
+            Self::NOTE.fallback
+        }
+    }
+
     /// Check if Nerd Fonts are enabled
     pub fn has_nerd_fonts(&self) -> bool {
         self.use_nerd_fonts
@@ -96,13 +135,16 @@ impl Default for Icons {
     }
 }
 
-/// Detect if Nerd Font support is enabled via environment variable
+/// Enable or disable Nerd Font icons
 ///
-/// Set `JERM_NERD_FONTS=1` to enable Nerd Font icons
+/// Set from `theme.nerd_fonts` in the config (or `JERM_NERD_FONTS=1`).
+pub fn set_nerd_fonts(enabled: bool) {
+    NERD_FONTS.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if Nerd Font support is enabled
 pub fn detect_nerd_font_support() -> bool {
-    env::var("JERM_NERD_FONTS")
-        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-        .unwrap_or(false)
+    NERD_FONTS.load(Ordering::Relaxed)
 }
 
 #[cfg(test)]
@@ -118,15 +160,19 @@ mod tests {
 
     #[test]
     fn test_icons_fallback_default() {
-        // Without env var set, should use fallback
-        let icons = Icons { use_nerd_fonts: false };
+        // Without Nerd Fonts enabled, should use fallback
+        let icons = Icons {
+            use_nerd_fonts: false,
+        };
         assert_eq!(icons.folder(), "");
         assert_eq!(icons.home(), "~");
     }
 
     #[test]
     fn test_icons_nerd_fonts() {
-        let icons = Icons { use_nerd_fonts: true };
+        let icons = Icons {
+            use_nerd_fonts: true,
+        };
         assert_eq!(icons.folder(), "\u{f07b}");
         assert_eq!(icons.home(), "\u{f015}");
     }
@@ -0,0 +1,157 @@
+//! Loading themes by name
+//!
+//! A name is looked up in the profile's `themes/` directory first
+//! (`~/.config/jerm/themes/<name>.toml`), then among the built-in presets.
+//! A theme file starts from a preset and overrides any of its colors:
+//!
+//! ```toml
+//! base = "light"
+//!
+//! [colors]
+//! prompt_path = "#005f87"
+//! text_normal = "black"
+//! ```
+//!
+//! Colors are `#rrggbb`, a color name (`red`, `lightblue`, ...) or a
+//! 256-color index.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ratatui::style::Color;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::Theme;
+
+/// Errors that can occur while loading a theme
+#[derive(Error, Debug)]
+pub enum ThemeError {
+    #[error("Failed to read theme file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse theme file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Theme not found: {0}")]
+    NotFound(String),
+
+    #[error("Unknown base theme: {0}")]
+    UnknownBase(String),
+
+    #[error("Unknown color '{0}'")]
+    UnknownColor(String),
+
+    #[error("Invalid value '{value}' for color '{key}'")]
+    InvalidColor { key: String, value: String },
+}
+
+/// Contents of a theme file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    /// Preset the file starts from
+    base: Option<String>,
+    /// Color overrides by slot name
+    colors: BTreeMap<String, String>,
+}
+
+/// Get the directory holding user themes
+pub fn get_themes_dir() -> Option<PathBuf> {
+    crate::paths::config_dir().map(|dir| dir.join("themes"))
+}
+
+/// Load a theme by name: a user theme file, or else a preset
+pub fn load_theme(name: &str) -> Result<Theme, ThemeError> {
+    if crate::paths::is_valid_name(name) {
+        if let Some(path) = get_themes_dir().map(|dir| dir.join(format!("{name}.toml"))) {
+            if path.exists() {
+                return parse_theme(name, &fs::read_to_string(path)?);
+            }
+        }
+    }
+    Theme::preset(name).ok_or_else(|| ThemeError::NotFound(name.to_string()))
+}
+
+/// Build a theme from the contents of a theme file
+pub fn parse_theme(name: &str, content: &str) -> Result<Theme, ThemeError> {
+    let file: ThemeFile = toml::from_str(content)?;
+    let base = file.base.as_deref().unwrap_or("default");
+    let mut theme = Theme::preset(base).ok_or_else(|| ThemeError::UnknownBase(base.to_string()))?;
+    theme.name = name.to_string();
+
+    for (key, value) in file.colors {
+        let color = Color::from_str(&value).map_err(|_| ThemeError::InvalidColor {
+            key: key.clone(),
+            value: value.clone(),
+        })?;
+        let slot = theme
+            .color_mut(&key)
+            .ok_or_else(|| ThemeError::UnknownColor(key.clone()))?;
+        *slot = color;
+    }
+    Ok(theme)
+}
+
+/// Names of all available themes (presets and user files), sorted
+pub fn list_themes() -> Vec<String> {
+    let mut names: Vec<String> = Theme::PRESETS.iter().map(|name| name.to_string()).collect();
+
+    if let Some(Ok(read_dir)) = get_themes_dir().map(fs::read_dir) {
+        names.extend(
+            read_dir
+                .filter_map(std::result::Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+                .filter(|name| crate::paths::is_valid_name(name)),
+        );
+    }
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme_overrides_base() {
+        let theme = parse_theme(
+            "paper",
+            "base = \"light\"\n[colors]\nprompt_path = \"#005f87\"\ntext_normal = \"black\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(theme.name, "paper");
+        assert_eq!(theme.prompt_path, Color::Rgb(0, 95, 135));
+        assert_eq!(theme.text_normal, Color::Black);
+        assert_eq!(theme.border_active, Theme::light().border_active);
+    }
+
+    #[test]
+    fn test_parse_theme_defaults_to_dark_base() {
+        let theme = parse_theme("empty", "").unwrap();
+        assert_eq!(theme.border_active, Theme::dark().border_active);
+    }
+
+    #[test]
+    fn test_parse_theme_errors() {
+        assert!(matches!(
+            parse_theme("x", "base = \"neon\""),
+            Err(ThemeError::UnknownBase(_))
+        ));
+        assert!(matches!(
+            parse_theme("x", "[colors]\nsparkle = \"red\""),
+            Err(ThemeError::UnknownColor(_))
+        ));
+        assert!(matches!(
+            parse_theme("x", "[colors]\ntext_normal = \"#zzz\""),
+            Err(ThemeError::InvalidColor { .. })
+        ));
+    }
+}
@@ -1,7 +1,11 @@
 //! Theme module for colors and icons
 
 pub mod colors;
+pub mod contrast;
+pub mod gallery;
 pub mod icons;
+pub mod loader;
 
-pub use colors::Palette;
+pub use colors::{blend, Theme};
+pub use gallery::ThemeGallery;
 pub use icons::Icons;
@@ -0,0 +1,297 @@
+use chrono::{Local, TimeZone};
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::centered_rect;
+use super::navigator::{entry_style, render_key_hints};
+use crate::git::overview::{BranchInfo, GitOverview, Upstream};
+use crate::git::BranchSwitcher;
+use crate::messages::TimeMessages;
+use crate::theme::Theme;
+
+/// Branches without a commit for this long are marked stale
+const STALE_DAYS: i64 = 90;
+
+/// Widest a name column gets before names are cut off
+const MAX_COLUMN: usize = 32;
+
+/// Render the `jerm git overview` branch dashboard
+pub fn render_git_overview(
+    f: &mut Frame,
+    area: Rect,
+    overview: &GitOverview,
+    time: &TimeMessages,
+    theme: &Theme,
+) {
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(" Branches ");
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 3 {
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+    let summary_area = Rect {
+        height: 1,
+        ..inner_area
+    };
+    let hints_area = Rect {
+        y: inner_area.y + inner_area.height - 1,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        hints_area,
+        &[&[("\u{2191}\u{2193}", "move"), ("Esc", "close")]],
+        theme,
+    );
+
+    let branches = match overview.branches {
+        None => {
+            f.render_widget(
+                Paragraph::new(Span::styled("Reading branches…", muted)),
+                summary_area,
+            );
+            return;
+        }
+        Some(Err(ref e)) => {
+            f.render_widget(
+                Paragraph::new(Span::styled(
+                    format!("jerm git overview: {e}"),
+                    Style::default().fg(theme.block_failure),
+                )),
+                summary_area,
+            );
+            return;
+        }
+        Some(Ok(ref branches)) => branches,
+    };
+
+    let now = Local::now();
+    let is_stale =
+        |branch: &BranchInfo| (now.timestamp() - branch.last_commit) / 86_400 >= STALE_DAYS;
+    let stale = branches.iter().filter(|b| is_stale(b)).count();
+    let gone = branches
+        .iter()
+        .filter(|b| matches!(b.upstream, Upstream::Gone(_)))
+        .count();
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            format!(
+                "{} branches · {stale} stale (no commits in {STALE_DAYS} days) · {gone} with a deleted upstream",
+                branches.len()
+            ),
+            muted,
+        )),
+        summary_area,
+    );
+
+    let name_width = column_width(branches.iter().map(|b| b.name.as_str()));
+    let upstream_width = column_width(branches.iter().map(|b| match b.upstream {
+        Upstream::None => "-",
+        Upstream::Gone(ref name) | Upstream::Tracking { ref name, .. } => name.as_str(),
+    }));
+
+    // Branch rows, keeping the selection in view
+    let list_height = inner_area.height.saturating_sub(3) as usize;
+    let scroll = (overview.selected + 1).saturating_sub(list_height);
+    let items: Vec<ListItem> = branches
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(list_height)
+        .map(|(idx, branch)| {
+            let selected = idx == overview.selected;
+            let marker = if branch.is_head { "* " } else { "  " };
+            let mut spans = vec![Span::styled(
+                format!(
+                    "{marker}{:<name_width$}  ",
+                    truncate(&branch.name, name_width)
+                ),
+                entry_style(theme, selected),
+            )];
+
+            let (upstream, sync) = match branch.upstream {
+                Upstream::None => ("-".to_string(), vec![Span::styled("local only", muted)]),
+                Upstream::Gone(ref name) => (
+                    name.clone(),
+                    vec![Span::styled(
+                        "gone",
+                        Style::default().fg(theme.block_failure),
+                    )],
+                ),
+                Upstream::Tracking {
+                    ref name,
+                    ahead,
+                    behind,
+                } => {
+                    let counts = match (ahead, behind) {
+                        (0, 0) => vec![Span::styled("up to date", muted)],
+                        _ => vec![Span::styled(
+                            format!("\u{2191}{ahead} \u{2193}{behind}"),
+                            Style::default().fg(theme.git_ahead_behind),
+                        )],
+                    };
+                    (name.clone(), counts)
+                }
+            };
+            spans.push(Span::styled(
+                format!("{:<upstream_width$}  ", truncate(&upstream, upstream_width)),
+                Style::default().fg(theme.git_branch),
+            ));
+            let sync_width: usize = sync.iter().map(|s| s.content.chars().count()).sum();
+            spans.extend(sync);
+            spans.push(Span::raw(" ".repeat(12usize.saturating_sub(sync_width))));
+
+            let age = Local
+                .timestamp_opt(branch.last_commit, 0)
+                .single()
+                .map(|time| time.signed_duration_since(now).abs())
+                .map_or_else(String::new, |age| time.compact(age));
+            let age_style = if is_stale(branch) {
+                Style::default().fg(theme.block_warning)
+            } else {
+                muted
+            };
+            spans.push(Span::styled(age, age_style));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list_area = Rect {
+        y: inner_area.y + 2,
+        height: list_height as u16,
+        ..inner_area
+    };
+    f.render_widget(List::new(items), list_area);
+}
+
+/// Width of a column holding `names`, capped at [`MAX_COLUMN`]
+fn column_width<'a>(names: impl Iterator<Item = &'a str>) -> usize {
+    names
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_COLUMN)
+}
+
+/// Cut `text` to `width` characters, marking the cut with an ellipsis
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Render the `jerm branch` picker on top of the terminal area
+pub fn render_branch_switcher(f: &mut Frame, area: Rect, switcher: &BranchSwitcher, theme: &Theme) {
+    let popup = centered_rect(area, 60, 60);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(" Switch Branch ");
+
+    let inner_area = block.inner(popup);
+    f.render_widget(block, popup);
+
+    if inner_area.height < 3 {
+        return;
+    }
+
+    let muted = Style::default().fg(theme.text_muted);
+
+    // Filter line
+    let filter_area = Rect {
+        height: 1,
+        ..inner_area
+    };
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("/ ", Style::default().fg(theme.nav_key_hint)),
+            Span::styled(
+                switcher.filter.clone(),
+                Style::default().fg(theme.text_normal),
+            ),
+        ])),
+        filter_area,
+    );
+
+    let list_height = inner_area.height.saturating_sub(2) as usize;
+    let list_area = Rect {
+        y: inner_area.y + 1,
+        height: list_height as u16,
+        ..inner_area
+    };
+    let items: Vec<ListItem> = match switcher.branches {
+        None => vec![ListItem::new(Span::styled("Reading branches…", muted))],
+        Some(Err(ref e)) => vec![ListItem::new(Span::styled(
+            format!("jerm branch: {e}"),
+            Style::default().fg(theme.block_failure),
+        ))],
+        Some(Ok(_)) => {
+            // Branch rows, keeping the selection in view
+            let scroll = (switcher.selected + 1).saturating_sub(list_height);
+            switcher
+                .matches()
+                .into_iter()
+                .enumerate()
+                .skip(scroll)
+                .take(list_height)
+                .map(|(idx, branch)| {
+                    let selected = idx == switcher.selected;
+                    let marker = if branch.is_head { "* " } else { "  " };
+                    let mut spans = vec![Span::styled(
+                        format!("{marker}{}", branch.name),
+                        entry_style(theme, selected),
+                    )];
+                    if let Upstream::Tracking { ahead, behind, .. } = branch.upstream {
+                        if ahead > 0 || behind > 0 {
+                            spans.push(Span::styled(
+                                format!("  \u{2191}{ahead} \u{2193}{behind}"),
+                                Style::default().fg(theme.git_ahead_behind),
+                            ));
+                        }
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect()
+        }
+    };
+    f.render_widget(List::new(items), list_area);
+
+    let hints_area = Rect {
+        y: inner_area.y + inner_area.height - 1,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        hints_area,
+        &[&[
+            ("\u{2191}\u{2193}", "move"),
+            ("type", "filter"),
+            ("Enter", "checkout"),
+            ("Esc", "cancel"),
+        ]],
+        theme,
+    );
+}
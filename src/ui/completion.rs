@@ -0,0 +1,81 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::completion::CompletionMenu;
+use crate::theme::Theme;
+
+/// Most candidates shown at once
+const MAX_VISIBLE: u16 = 8;
+
+/// Render the completion popup next to the cursor, above it when there is room
+pub fn render_completion(
+    f: &mut Frame,
+    area: Rect,
+    cursor: (u16, u16),
+    menu: &CompletionMenu,
+    theme: &Theme,
+) {
+    let (cursor_x, cursor_y) = cursor;
+    let longest = menu
+        .candidates
+        .iter()
+        .map(|c| c.display.chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+
+    let height = (menu.candidates.len() as u16).min(MAX_VISIBLE) + 2;
+    let width = (longest + 4).max(16).min(area.width);
+    let x = cursor_x.min(area.x + area.width.saturating_sub(width));
+    let y = if cursor_y.saturating_sub(area.y) >= height {
+        cursor_y - height
+    } else {
+        (cursor_y + 1).min(area.y + area.height.saturating_sub(height))
+    };
+    let popup = Rect {
+        x,
+        y,
+        width,
+        height: height.min(area.height),
+    };
+
+    let items: Vec<ListItem> = menu
+        .candidates
+        .iter()
+        .map(|candidate| {
+            let color = if candidate.is_dir {
+                theme.syntax_path
+            } else {
+                theme.text_normal
+            };
+            ListItem::new(Span::styled(
+                candidate.display.clone(),
+                Style::default().fg(color),
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_active))
+                .title(format!(" {} ", menu.candidates.len())),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(theme.nav_selected_bg)
+                .fg(theme.nav_selected_fg)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let mut state = ListState::default();
+    state.select(menu.selected);
+
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut state);
+}
@@ -0,0 +1,78 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::highlight::Tokenizer;
+use crate::theme::Theme;
+
+/// Render the confirmation popup shown before running a multi-command script
+pub fn render_confirm_script(
+    f: &mut Frame,
+    area: Rect,
+    script: &str,
+    commands: usize,
+    theme: &Theme,
+) {
+    let popup = centered_rect(area, 80, 70);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.nav_header))
+        .title(format!(" Run {commands} commands? "));
+
+    let inner_area = block.inner(popup);
+    f.render_widget(block, popup);
+
+    if inner_area.height < 2 {
+        return;
+    }
+
+    // Number every line so hidden or blank lines stand out
+    let mut lines: Vec<Line> = script
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            let mut spans = vec![Span::styled(
+                format!("{:>3} ", i + 1),
+                Style::default().fg(theme.text_muted),
+            )];
+            spans.extend(Tokenizer::to_spans(&Tokenizer::tokenize(line), theme));
+            Line::from(spans)
+        })
+        .collect();
+
+    let body_height = inner_area.height.saturating_sub(1) as usize;
+    let overflow = lines.len() > body_height;
+    lines.truncate(body_height);
+
+    let body = Rect {
+        height: inner_area.height - 1,
+        ..inner_area
+    };
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), body);
+
+    let footer_text = if overflow {
+        "Enter run all · Esc back to editing · (script truncated)"
+    } else {
+        "Enter run all · Esc back to editing"
+    };
+    let footer = Rect {
+        y: inner_area.y + inner_area.height - 1,
+        height: 1,
+        ..inner_area
+    };
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            footer_text,
+            Style::default().fg(theme.nav_key_hint),
+        )),
+        footer,
+    );
+}
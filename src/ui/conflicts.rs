@@ -0,0 +1,82 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::Span,
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+use super::centered_rect;
+use super::navigator::{entry_style, render_key_hints};
+use crate::git::ConflictList;
+use crate::theme::Theme;
+
+/// Render the list of conflicted files on top of the terminal area
+pub fn render_conflicts(
+    f: &mut Frame,
+    area: Rect,
+    conflicts: &ConflictList,
+    readonly: bool,
+    theme: &Theme,
+) {
+    let popup = centered_rect(area, 70, 60);
+    f.render_widget(Clear, popup);
+
+    let count = conflicts.files.len();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.block_failure))
+        .title(format!(
+            " {count} conflicted {} ",
+            if count == 1 { "file" } else { "files" }
+        ));
+
+    let inner_area = block.inner(popup);
+    f.render_widget(block, popup);
+
+    if inner_area.height < 2 {
+        return;
+    }
+
+    // File list, keeping the selection in view
+    let list_height = inner_area.height.saturating_sub(1) as usize;
+    let scroll = (conflicts.selected + 1).saturating_sub(list_height);
+    let items: Vec<ListItem> = conflicts
+        .files
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(list_height)
+        .map(|(idx, file)| {
+            let selected = idx == conflicts.selected;
+            let prefix = if selected { "> " } else { "  " };
+            ListItem::new(Span::styled(
+                format!("{prefix}{file}"),
+                entry_style(theme, selected),
+            ))
+        })
+        .collect();
+    let list_area = Rect {
+        height: list_height as u16,
+        ..inner_area
+    };
+    f.render_widget(List::new(items), list_area);
+
+    let hints_area = Rect {
+        y: inner_area.y + list_height as u16,
+        height: 1,
+        ..inner_area
+    };
+    let hints: &[(&str, &str)] = if readonly {
+        &[("\u{2191}\u{2193}", "move"), ("Esc", "close")]
+    } else {
+        &[
+            ("\u{2191}\u{2193}", "move"),
+            ("Enter", "edit"),
+            ("a", "mark resolved"),
+            ("Esc", "close"),
+        ]
+    };
+    render_key_hints(f, hints_area, &[hints], theme);
+}
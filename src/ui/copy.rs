@@ -0,0 +1,90 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::navigator::render_key_hints;
+use crate::ansi;
+use crate::blocks::CopyMode;
+use crate::theme::Theme;
+
+/// Render the output buffer with copy mode's cursor and selection
+pub fn render_copy_mode(
+    f: &mut Frame,
+    area: Rect,
+    copy: &mut CopyMode,
+    output: &[String],
+    theme: &Theme,
+) {
+    f.render_widget(Clear, area);
+
+    let selection = copy.selection();
+    let title = match copy.anchor {
+        Some(_) => format!(" Copy ({} lines selected) ", selection.clone().count()),
+        None => " Copy ".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(title);
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 2 {
+        return;
+    }
+
+    let body_height = inner_area.height as usize - 1;
+    copy.adjust_scroll(body_height);
+    let lines: Vec<Line> = output
+        .iter()
+        .enumerate()
+        .skip(copy.scroll)
+        .take(body_height)
+        .map(|(index, line)| {
+            let gutter = if index == copy.cursor {
+                "\u{258c} "
+            } else {
+                "  "
+            };
+            let mut spans = vec![Span::styled(
+                gutter,
+                Style::default().fg(theme.border_active),
+            )];
+            spans.extend(ansi::parse_line(line));
+            let mut line = Line::from(spans);
+            if copy.anchor.is_some() && selection.contains(&index) {
+                line = line.style(Style::default().bg(theme.bg_selected));
+            }
+            line
+        })
+        .collect();
+
+    let body = Rect {
+        height: body_height as u16,
+        ..inner_area
+    };
+    f.render_widget(Paragraph::new(lines), body);
+
+    let hints_area = Rect {
+        y: inner_area.y + body_height as u16,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        hints_area,
+        &[&[
+            ("\u{2191}\u{2193}", "move"),
+            ("v", "select"),
+            ("y", "copy"),
+            ("Esc", "close"),
+        ]],
+        theme,
+    );
+}
@@ -0,0 +1,150 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::navigator::{entry_style, render_key_hints, render_path_header};
+use crate::navigation::diffdir::{DiffColumn, DirDiff};
+use crate::theme::{Icons, Theme};
+
+/// Column titles, in column order
+const TITLES: [&str; 3] = ["Only in A", "Differ", "Only in B"];
+
+/// Render the `jerm diffdir` comparison overlay
+pub fn render_diffdir(f: &mut Frame, area: Rect, diff: &mut DirDiff, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(" Compare Directories ");
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 6 {
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // A and B paths
+            Constraint::Min(3),    // Columns
+            Constraint::Length(2), // Key hints
+        ])
+        .split(inner_area);
+
+    // Both paths, with the subdirectory being compared
+    let header_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1)])
+        .split(chunks[0]);
+    for (row, (label, root)) in [("A", &diff.root_a), ("B", &diff.root_b)]
+        .into_iter()
+        .enumerate()
+    {
+        let path = root.join(&diff.relative);
+        render_path_header(
+            f,
+            header_rows[row],
+            &format!("{label}: {}", path.display()),
+            theme,
+        );
+    }
+
+    if let Some(ref error) = diff.error {
+        let icons = Icons::new();
+        let message = Paragraph::new(Line::from(Span::styled(
+            format!("  {} {}", icons.lock(), error),
+            Style::default().fg(theme.nav_denied),
+        )));
+        f.render_widget(message, chunks[1]);
+    } else if diff.is_identical() {
+        let message = Paragraph::new(Line::from(Span::styled(
+            "  No differences",
+            Style::default().fg(theme.text_muted),
+        )));
+        f.render_widget(message, chunks[1]);
+    } else {
+        render_columns(f, chunks[1], diff, theme);
+    }
+
+    render_key_hints(
+        f,
+        chunks[2],
+        &[
+            &[("\u{2191}\u{2193}", "move"), ("\u{2190}\u{2192}", "column")],
+            &[("Enter", "open dir"), ("Backspace", "up"), ("Esc", "close")],
+        ],
+        theme,
+    );
+}
+
+/// Render the three side-by-side lists
+fn render_columns(f: &mut Frame, area: Rect, diff: &mut DirDiff, theme: &Theme) {
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(area);
+
+    for (column, column_area) in areas.iter().enumerate() {
+        let focused = diff.column as usize == column;
+        let border_color = if focused {
+            theme.border_active
+        } else {
+            theme.border_default
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .title(format!(
+                " {} ({}) ",
+                TITLES[column],
+                diff.columns[column].len()
+            ));
+        let list_area = block.inner(*column_area);
+        f.render_widget(block, *column_area);
+
+        let visible_height = list_area.height as usize;
+        diff.adjust_scroll(column, visible_height);
+
+        let items: Vec<ListItem> = diff.columns[column]
+            .iter()
+            .enumerate()
+            .skip(diff.scroll[column])
+            .take(visible_height)
+            .map(|(index, entry)| {
+                let selected = focused && diff.selected[column] == index;
+                let is_dir = entry.a.or(entry.b).is_some_and(|meta| meta.is_dir);
+                let suffix = if is_dir { "/" } else { "" };
+
+                let mut spans = vec![Span::styled(
+                    format!(
+                        "{}{}{suffix}",
+                        if selected { "> " } else { "  " },
+                        entry.name
+                    ),
+                    entry_style(theme, selected),
+                )];
+                if column == DiffColumn::Differs as usize {
+                    spans.push(Span::styled(
+                        format!("  {}", entry.describe()),
+                        Style::default().fg(theme.text_muted),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        f.render_widget(List::new(items), list_area);
+    }
+}
@@ -0,0 +1,79 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::navigator::render_key_hints;
+use crate::shell::env::EnvPanel;
+use crate::theme::Theme;
+
+/// Render the variables commands run with, marking those set with `export`
+pub fn render_env(f: &mut Frame, area: Rect, panel: &mut EnvPanel, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(format!(" Environment ({} variables) ", panel.vars.len()));
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 2 {
+        return;
+    }
+
+    let name_width = panel
+        .vars
+        .iter()
+        .map(|(name, ..)| name.len())
+        .max()
+        .unwrap_or(0)
+        .min(inner_area.width as usize / 3);
+    let lines: Vec<Line> = panel
+        .vars
+        .iter()
+        .map(|(name, value, changed)| {
+            let name_style = if *changed {
+                Style::default()
+                    .fg(theme.syntax_command)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text_normal)
+            };
+            Line::from(vec![
+                Span::styled(format!("{name:<name_width$}  "), name_style),
+                Span::styled(value.as_str(), Style::default().fg(theme.text_muted)),
+            ])
+        })
+        .collect();
+
+    // Keep the last page in view when scrolled past the end
+    let body_height = inner_area.height as usize - 1;
+    panel.scroll = panel.scroll.min(lines.len().saturating_sub(body_height));
+    let body = Rect {
+        height: body_height as u16,
+        ..inner_area
+    };
+    f.render_widget(Paragraph::new(lines).scroll((panel.scroll as u16, 0)), body);
+
+    let hints_area = Rect {
+        y: inner_area.y + body_height as u16,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        hints_area,
+        &[&[
+            ("\u{2191}\u{2193}", "scroll"),
+            ("PgUp/PgDn", "page"),
+            ("Esc", "close"),
+        ]],
+        theme,
+    );
+}
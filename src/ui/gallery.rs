@@ -0,0 +1,202 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::navigator::render_key_hints;
+use crate::theme::{Theme, ThemeGallery};
+
+/// Rows of a theme card, borders included
+const CARD_HEIGHT: u16 = 8;
+
+/// Width of the sidebar mock, left of the navigator mock
+const SIDEBAR_WIDTH: usize = 24;
+
+/// Render the `jerm theme preview` gallery: one card per installed theme
+pub fn render_theme_gallery(
+    f: &mut Frame,
+    area: Rect,
+    gallery: &mut ThemeGallery,
+    current: &str,
+    theme: &Theme,
+) {
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(format!(" Themes ({}) ", gallery.entries.len()));
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < CARD_HEIGHT + 1 {
+        return;
+    }
+
+    let list_height = inner_area.height - 1;
+    let visible = (list_height / CARD_HEIGHT) as usize;
+    gallery.adjust_scroll(visible);
+
+    for (slot, (index, entry)) in gallery
+        .entries
+        .iter()
+        .enumerate()
+        .skip(gallery.scroll)
+        .take(visible)
+        .enumerate()
+    {
+        let card_area = Rect {
+            y: inner_area.y + slot as u16 * CARD_HEIGHT,
+            height: CARD_HEIGHT,
+            ..inner_area
+        };
+        let selected = index == gallery.selected;
+        let marker = if entry.name == current {
+            " (current)"
+        } else {
+            ""
+        };
+        let title = format!(" {}{marker} ", entry.name);
+
+        match entry.theme {
+            Ok(ref card_theme) => render_card(f, card_area, &title, card_theme, selected),
+            Err(ref error) => {
+                let border = if selected {
+                    theme.border_active
+                } else {
+                    theme.border_default
+                };
+                let card = Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(border))
+                    .title(title);
+                let message = Paragraph::new(Span::styled(
+                    format!("can't load theme: {error}"),
+                    Style::default().fg(theme.block_failure),
+                ))
+                .block(card);
+                f.render_widget(message, card_area);
+            }
+        }
+    }
+
+    let footer_area = Rect {
+        y: inner_area.y + inner_area.height - 1,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        footer_area,
+        &[&[
+            ("\u{2191}\u{2193}", "move"),
+            ("PgUp/PgDn", "page"),
+            ("Enter", "switch"),
+            ("Esc", "close"),
+        ]],
+        theme,
+    );
+}
+
+/// A theme's palette and mocks of the prompt, sidebar, and navigator, all in
+/// that theme's own colors
+fn render_card(f: &mut Frame, area: Rect, title: &str, theme: &Theme, selected: bool) {
+    let border = if selected {
+        theme.border_active
+    } else {
+        theme.border_default
+    };
+    let mut title_style = Style::default().fg(theme.text_normal);
+    if selected {
+        title_style = title_style.add_modifier(Modifier::BOLD);
+    }
+    let card = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border))
+        .title(Span::styled(title.to_string(), title_style));
+
+    let fg = |color| Style::default().fg(color);
+    let swatches: Vec<Span> = theme
+        .palette()
+        .into_iter()
+        .map(|color| Span::styled("\u{2588}\u{2588}", fg(color)))
+        .collect();
+
+    let prompt = Line::from(vec![
+        Span::styled("~/projects/jerm ", fg(theme.prompt_path)),
+        Span::styled("\u{270e} ", fg(theme.prompt_note)),
+        Span::styled("main ", fg(theme.git_branch)),
+        Span::styled("+2 ", fg(theme.git_staged)),
+        Span::styled("~1 ", fg(theme.git_modified)),
+        Span::styled("\u{2191}1 ", fg(theme.git_ahead_behind)),
+        Span::styled("3.2s ", fg(theme.prompt_duration)),
+        Span::styled("$ ", fg(theme.text_normal)),
+        Span::styled("grep", fg(theme.syntax_command)),
+        Span::styled(" -n ", fg(theme.syntax_flag)),
+        Span::styled("\"TODO\"", fg(theme.syntax_string)),
+        Span::styled(" src/", fg(theme.syntax_path)),
+        Span::styled(" | ", fg(theme.syntax_operator)),
+        Span::styled("head", fg(theme.syntax_command)),
+        Span::styled(" 20", fg(theme.syntax_number)),
+    ]);
+
+    let blocks = Line::from(vec![
+        Span::styled("\u{2713} ", fg(theme.block_success)),
+        Span::styled("cargo build  ", fg(theme.text_muted)),
+        Span::styled("\u{2717} ", fg(theme.block_failure)),
+        Span::styled("cargo test  ", fg(theme.text_muted)),
+        Span::styled(
+            "[\u{25b8} 1 error, 2 warnings]",
+            fg(theme.block_warning).add_modifier(Modifier::BOLD),
+        ),
+    ]);
+
+    let sidebar = [("1", "~/projects/jerm", "2h"), ("2", "~/dotfiles", "3d")];
+    let navigator = [
+        Line::from(vec![
+            Span::styled(
+                "> src",
+                Style::default()
+                    .fg(theme.nav_selected_fg)
+                    .bg(theme.nav_selected_bg)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("  ", Style::default()),
+            Span::styled("\u{2191}\u{2193}", fg(theme.nav_key_hint)),
+            Span::styled(" move", fg(theme.text_muted)),
+        ]),
+        Line::from(vec![
+            Span::styled("  locked", fg(theme.text_normal)),
+            Span::styled("  permission denied", fg(theme.nav_denied)),
+        ]),
+    ];
+
+    let mut lines = vec![Line::from(swatches), prompt, blocks];
+    let header = Line::from(vec![
+        Span::styled(
+            format!("{:<SIDEBAR_WIDTH$}", "Shortcuts"),
+            fg(theme.text_muted),
+        ),
+        Span::styled(
+            "~/projects/jerm",
+            fg(theme.nav_header).add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    lines.push(header);
+    for ((number, path, time), nav_line) in sidebar.into_iter().zip(navigator) {
+        let mut spans = vec![
+            Span::styled(format!("{number} "), fg(theme.sidebar_number)),
+            Span::styled(format!("{path:<16} "), fg(theme.sidebar_path)),
+            Span::styled(format!("{time:<4}"), fg(theme.sidebar_time)),
+        ];
+        spans.extend(nav_line.spans);
+        lines.push(Line::from(spans));
+    }
+
+    f.render_widget(Paragraph::new(lines).block(card), area);
+}
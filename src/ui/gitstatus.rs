@@ -0,0 +1,173 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::navigator::render_key_hints;
+use crate::git::details::{GitPanel, StatusDetails};
+use crate::theme::Theme;
+
+/// Render the git status panel
+pub fn render_git_status(f: &mut Frame, area: Rect, panel: &mut GitPanel, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(" Git Status ");
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 2 {
+        return;
+    }
+
+    let lines = match panel.details {
+        None => vec![Line::from(Span::styled(
+            "Reading status…",
+            Style::default().fg(theme.text_muted),
+        ))],
+        Some(Err(ref e)) => vec![Line::from(Span::styled(
+            format!("git status: {e}"),
+            Style::default().fg(theme.block_failure),
+        ))],
+        Some(Ok(ref details)) => status_lines(details, theme),
+    };
+
+    // Keep the last page in view when scrolled past the end
+    let body_height = inner_area.height as usize - 1;
+    panel.scroll = panel.scroll.min(lines.len().saturating_sub(body_height));
+    let body = Rect {
+        height: body_height as u16,
+        ..inner_area
+    };
+    f.render_widget(Paragraph::new(lines).scroll((panel.scroll as u16, 0)), body);
+
+    let hints_area = Rect {
+        y: inner_area.y + body_height as u16,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        hints_area,
+        &[&[
+            ("\u{2191}\u{2193}", "scroll"),
+            ("PgUp/PgDn", "page"),
+            ("Esc", "close"),
+        ]],
+        theme,
+    );
+}
+
+/// Branch summary followed by a section per kind of change
+fn status_lines(details: &StatusDetails, theme: &Theme) -> Vec<Line<'static>> {
+    let muted = Style::default().fg(theme.text_muted);
+    let mut lines = Vec::new();
+
+    let mut branch = vec![
+        Span::styled("On branch ", muted),
+        Span::styled(
+            details.branch.clone(),
+            Style::default()
+                .fg(theme.git_branch)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ];
+    match details.upstream {
+        Some(ref upstream) => {
+            branch.push(Span::styled(" tracking ", muted));
+            branch.push(Span::styled(
+                upstream.clone(),
+                Style::default().fg(theme.git_branch),
+            ));
+            let sync = match (details.ahead, details.behind) {
+                (0, 0) => "  up to date".to_string(),
+                (ahead, behind) => format!("  \u{2191}{ahead} \u{2193}{behind}"),
+            };
+            branch.push(Span::styled(
+                sync,
+                Style::default().fg(theme.git_ahead_behind),
+            ));
+        }
+        None => branch.push(Span::styled("  (no upstream)", muted)),
+    }
+    lines.push(Line::from(branch));
+
+    if details.is_clean() {
+        lines.push(Line::default());
+        lines.push(Line::from(Span::styled(
+            "Nothing to commit, working tree clean",
+            muted,
+        )));
+        return lines;
+    }
+
+    let conflicts: Vec<(&str, &str)> = details
+        .conflicts
+        .iter()
+        .map(|path| ("both modified", path.as_str()))
+        .collect();
+    let staged: Vec<(&str, &str)> = details
+        .staged
+        .iter()
+        .map(|change| (change.label(), change.path.as_str()))
+        .collect();
+    let unstaged: Vec<(&str, &str)> = details
+        .unstaged
+        .iter()
+        .map(|change| (change.label(), change.path.as_str()))
+        .collect();
+    let untracked: Vec<(&str, &str)> = details
+        .untracked
+        .iter()
+        .map(|path| ("", path.as_str()))
+        .collect();
+
+    for (title, files, color) in [
+        ("Unmerged paths", conflicts, theme.block_failure),
+        ("Changes to be committed", staged, theme.git_staged),
+        (
+            "Changes not staged for commit",
+            unstaged,
+            theme.git_modified,
+        ),
+        ("Untracked files", untracked, theme.git_untracked),
+    ] {
+        push_section(&mut lines, title, &files, color, theme);
+    }
+    lines
+}
+
+/// A titled list of files, skipped when empty
+fn push_section(
+    lines: &mut Vec<Line<'static>>,
+    title: &str,
+    files: &[(&str, &str)],
+    color: Color,
+    theme: &Theme,
+) {
+    if files.is_empty() {
+        return;
+    }
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled(
+        format!("{title} ({})", files.len()),
+        Style::default()
+            .fg(theme.text_normal)
+            .add_modifier(Modifier::BOLD),
+    )));
+    for (label, path) in files {
+        let text = if label.is_empty() {
+            format!("    {path}")
+        } else {
+            format!("    {label:<14}{path}")
+        };
+        lines.push(Line::from(Span::styled(text, Style::default().fg(color))));
+    }
+}
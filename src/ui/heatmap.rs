@@ -0,0 +1,192 @@
+use chrono::{Datelike, Duration};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::navigator::render_key_hints;
+use crate::history::heatmap::{Heatmap, WEEKS};
+use crate::theme::{blend, Theme};
+
+/// Width of the weekday label column
+const LABEL_WIDTH: usize = 4;
+
+/// Bars for the hourly breakdown, from fewest to most commands
+const BARS: [char; 8] = [
+    '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}',
+];
+
+/// Render the `jerm heatmap` activity view
+pub fn render_heatmap(f: &mut Frame, area: Rect, heatmap: &mut Heatmap, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(" Activity ");
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 14 || (inner_area.width as usize) < LABEL_WIDTH + 2 {
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Total
+            Constraint::Length(8), // Month labels and weekday rows
+            Constraint::Length(1), // Spacer
+            Constraint::Length(2), // Selected day and its hours
+            Constraint::Min(1),    // Selected day's commands
+            Constraint::Length(2), // Key hints
+        ])
+        .split(inner_area);
+
+    let summary = Paragraph::new(Span::styled(
+        format!("{} commands in the last {WEEKS} weeks", heatmap.total()),
+        Style::default().fg(theme.text_muted),
+    ));
+    f.render_widget(summary, chunks[0]);
+
+    render_grid(f, chunks[1], heatmap, theme);
+    render_selected_day(f, chunks[3], chunks[4], heatmap, theme);
+
+    render_key_hints(
+        f,
+        chunks[5],
+        &[
+            &[("\u{2190}\u{2192}", "week"), ("\u{2191}\u{2193}", "day")],
+            &[("PgUp/PgDn", "scroll commands"), ("Esc", "close")],
+        ],
+        theme,
+    );
+}
+
+/// Render the week columns, scrolled so the selected day is visible
+fn render_grid(f: &mut Frame, area: Rect, heatmap: &Heatmap, theme: &Theme) {
+    let start = heatmap.start();
+    let visible = ((area.width as usize - LABEL_WIDTH) / 2).min(WEEKS as usize);
+    let selected_week = ((heatmap.selected - start).num_days() / 7) as usize;
+    let first_week = (WEEKS as usize - visible).min(selected_week);
+    let muted = Style::default().fg(theme.text_muted);
+
+    // Month names above the first week of each month, where they fit
+    let mut months = vec![' '; visible * 2];
+    let mut last_month = None;
+    for column in 0..visible {
+        let monday = start + Duration::weeks((first_week + column) as i64);
+        if last_month != Some(monday.month()) {
+            last_month = Some(monday.month());
+            let name = monday.format("%b").to_string();
+            let at = column * 2;
+            if at + name.len() <= months.len()
+                && months[at..].iter().take(name.len() + 1).all(|c| *c == ' ')
+            {
+                for (i, c) in name.chars().enumerate() {
+                    months[at + i] = c;
+                }
+            }
+        }
+    }
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:LABEL_WIDTH$}{}",
+            "",
+            months.into_iter().collect::<String>()
+        ),
+        muted,
+    ))];
+
+    for weekday in 0..7 {
+        let label = match weekday {
+            0 => "Mon",
+            2 => "Wed",
+            4 => "Fri",
+            _ => "",
+        };
+        let mut spans = vec![Span::styled(format!("{label:LABEL_WIDTH$}"), muted)];
+        for column in 0..visible {
+            let day = start + Duration::days(((first_week + column) * 7 + weekday) as i64);
+            if day > heatmap.today {
+                spans.push(Span::raw("  "));
+                continue;
+            }
+            let color = blend(
+                theme.bg_selected,
+                theme.block_success,
+                f64::from(heatmap.level(day)) / 4.0,
+            );
+            let mut style = Style::default().fg(color);
+            if day == heatmap.selected {
+                style = style.bg(theme.nav_selected_bg).add_modifier(Modifier::BOLD);
+            }
+            spans.push(Span::styled("\u{25A0}", style));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+/// Render the selected day's header, hourly bars, and command list
+fn render_selected_day(f: &mut Frame, header: Rect, list: Rect, heatmap: &Heatmap, theme: &Theme) {
+    let commands = heatmap.selected_commands();
+    let hours = heatmap.selected_hours();
+    let busiest = hours.iter().copied().max().unwrap_or(0);
+
+    let bars: String = hours
+        .iter()
+        .map(|&count| match count {
+            0 => ' ',
+            _ => BARS[(count * (BARS.len() - 1)).div_ceil(busiest.max(1))],
+        })
+        .collect();
+
+    let header_lines = vec![
+        Line::from(vec![
+            Span::styled(
+                heatmap.selected.format("%a %Y-%m-%d").to_string(),
+                Style::default()
+                    .fg(theme.nav_header)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(
+                    "  {} command{}",
+                    commands.len(),
+                    if commands.len() == 1 { "" } else { "s" }
+                ),
+                Style::default().fg(theme.text_muted),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("0h ", Style::default().fg(theme.text_muted)),
+            Span::styled(bars, Style::default().fg(theme.block_success)),
+            Span::styled(" 23h", Style::default().fg(theme.text_muted)),
+        ]),
+    ];
+    f.render_widget(Paragraph::new(header_lines), header);
+
+    let lines: Vec<Line> = commands
+        .iter()
+        .skip(heatmap.scroll)
+        .take(list.height as usize)
+        .map(|(time, command)| {
+            Line::from(vec![
+                Span::styled(
+                    time.format("%H:%M  ").to_string(),
+                    Style::default().fg(theme.text_muted),
+                ),
+                Span::styled(command.clone(), Style::default().fg(theme.text_normal)),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), list);
+}
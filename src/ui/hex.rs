@@ -0,0 +1,71 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::navigator::render_key_hints;
+use crate::blocks::HexView;
+use crate::theme::Theme;
+
+/// Render the hex dump of a block's output
+pub fn render_hex_view(f: &mut Frame, area: Rect, view: &mut HexView, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(format!(" {} ({} bytes) ", view.command, view.len));
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 2 {
+        return;
+    }
+
+    let lines: Vec<Line> = if view.rows.is_empty() {
+        vec![Line::from(Span::styled(
+            "No output",
+            Style::default().fg(theme.text_muted),
+        ))]
+    } else {
+        view.rows
+            .iter()
+            .map(|row| {
+                Line::from(Span::styled(
+                    row.as_str(),
+                    Style::default().fg(theme.text_normal),
+                ))
+            })
+            .collect()
+    };
+
+    // Keep the last page in view when scrolled past the end
+    let body_height = inner_area.height as usize - 1;
+    view.scroll = view.scroll.min(lines.len().saturating_sub(body_height));
+    let body = Rect {
+        height: body_height as u16,
+        ..inner_area
+    };
+    f.render_widget(Paragraph::new(lines).scroll((view.scroll as u16, 0)), body);
+
+    let hints_area = Rect {
+        y: inner_area.y + body_height as u16,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        hints_area,
+        &[&[
+            ("\u{2191}\u{2193}", "scroll"),
+            ("PgUp/PgDn", "page"),
+            ("Esc", "close"),
+        ]],
+        theme,
+    );
+}
@@ -0,0 +1,39 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::theme::{Icons, Theme};
+
+/// Render the idle lock screen over the whole frame, hiding all output
+pub fn render_lock(f: &mut Frame, area: Rect, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let popup = centered_rect(area, 50, 30);
+    let icons = Icons::new();
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("{} jerm is locked", icons.lock()),
+            Style::default()
+                .fg(theme.nav_header)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Press any key to resume",
+            Style::default().fg(theme.text_muted),
+        )),
+    ];
+
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_default)),
+    );
+    f.render_widget(paragraph, popup);
+}
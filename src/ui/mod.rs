@@ -1,7 +1,69 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+
+pub mod branches;
+pub mod completion;
+pub mod confirm;
+pub mod conflicts;
+pub mod copy;
+pub mod diffdir;
+pub mod env;
+pub mod gallery;
+pub mod gitstatus;
+pub mod heatmap;
+pub mod hex;
+pub mod lock;
 pub mod navigator;
+pub mod note;
 pub mod sidebar;
 pub mod terminal;
+pub mod tests;
+pub mod timeline;
 
+pub use branches::{render_branch_switcher, render_git_overview};
+pub use confirm::render_confirm_script;
+pub use conflicts::render_conflicts;
+pub use copy::render_copy_mode;
+pub use diffdir::render_diffdir;
+pub use env::render_env;
+pub use gallery::render_theme_gallery;
+pub use gitstatus::render_git_status;
+pub use heatmap::render_heatmap;
+pub use hex::render_hex_view;
+pub use lock::render_lock;
 pub use navigator::render_navigator;
+pub use note::render_note;
 pub use sidebar::render_sidebar;
-pub use terminal::render_terminal;
+pub use terminal::{render_pty, render_terminal};
+pub use tests::render_tests;
+pub use timeline::render_timeline;
+
+/// Recolor the border around `area`, keeping what's drawn on it, such as
+/// a title
+pub fn highlight_frame(buf: &mut Buffer, area: Rect, color: Color) {
+    let area = area.intersection(buf.area);
+    if area.is_empty() {
+        return;
+    }
+    for x in area.left()..area.right() {
+        buf.get_mut(x, area.top()).set_fg(color);
+        buf.get_mut(x, area.bottom() - 1).set_fg(color);
+    }
+    for y in area.top()..area.bottom() {
+        buf.get_mut(area.left(), y).set_fg(color);
+        buf.get_mut(area.right() - 1, y).set_fg(color);
+    }
+}
+
+/// Compute a rectangle centered in `area` taking the given percentages of its size
+pub fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -1,23 +1,28 @@
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
     Frame,
 };
 
-use crate::navigation::NavigationState;
-use crate::theme::Palette;
+use crate::config::NavLayout;
+use crate::navigation::directory::DirEntry;
+use crate::navigation::search::{DeepSearch, MAX_RESULTS};
+use crate::navigation::{NavigationState, PromptAction};
+use crate::theme::{Icons, Theme};
 
 /// Render the cd -list navigation overlay
-pub fn render_navigator(f: &mut Frame, area: Rect, nav: &mut NavigationState) {
+pub fn render_navigator(f: &mut Frame, area: Rect, nav: &mut NavigationState, theme: &Theme) {
+    let icons = Icons::new();
+
     // Clear the area first
     f.render_widget(Clear, area);
 
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Plain)
-        .border_style(Style::default().fg(Palette::BORDER_ACTIVE))
+        .border_style(Style::default().fg(theme.border_active))
         .title(" Select Directory ");
 
     let inner_area = block.inner(area);
@@ -27,6 +32,11 @@ pub fn render_navigator(f: &mut Frame, area: Rect, nav: &mut NavigationState) {
         return;
     }
 
+    if let Some(ref mut search) = nav.search {
+        render_search(f, inner_area, search, theme);
+        return;
+    }
+
     // Reserve space for header and footer
     let header_height = 1;
     let footer_height = 2;
@@ -42,49 +52,77 @@ pub fn render_navigator(f: &mut Frame, area: Rect, nav: &mut NavigationState) {
         height: 1,
     };
 
-    let current_path_display = nav.current_path.display().to_string();
-    let path_text = if current_path_display.len() > inner_area.width as usize - 2 {
-        format!(
-            "..{}",
-            &current_path_display[current_path_display.len() - (inner_area.width as usize - 4)..]
-        )
-    } else {
-        current_path_display
+    // Sort order and toggled-on listing options, right-aligned next to the path
+    let flags: Vec<&str> = [(nav.show_hidden, "hidden"), (nav.show_files, "files")]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+    let flags_text = flags
+        .iter()
+        .fold(format!(" [{}", nav.sort.label()), |text, flag| {
+            text + " +" + flag
+        })
+        + "]";
+    let flags_width = (flags_text.len() as u16).min(header_area.width);
+    let path_area = Rect {
+        width: header_area.width - flags_width,
+        ..header_area
     };
-
-    let header = Paragraph::new(Line::from(Span::styled(
-        path_text,
-        Style::default()
-            .fg(Palette::NAV_HEADER)
-            .add_modifier(Modifier::BOLD),
-    )));
-    f.render_widget(header, header_area);
+    render_path_header(f, path_area, &nav.current_path.display().to_string(), theme);
+    if flags_width > 0 {
+        let flags_area = Rect {
+            x: path_area.x + path_area.width,
+            width: flags_width,
+            ..header_area
+        };
+        f.render_widget(
+            Paragraph::new(Span::styled(
+                flags_text,
+                Style::default().fg(theme.text_muted),
+            )),
+            flags_area,
+        );
+    }
 
     // Adjust scroll for visible height
     nav.adjust_scroll(list_height);
 
     // Render directory list
-    let list_area = Rect {
+    let mut list_area = Rect {
         x: inner_area.x,
         y: inner_area.y + header_height as u16,
         width: inner_area.width,
         height: list_height as u16,
     };
 
+    // Columns layout: parent on the left, selected directory's contents on
+    // the right, and the current listing in between
+    if nav.layout == NavLayout::Columns {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Percentage(40),
+                Constraint::Percentage(35),
+            ])
+            .split(list_area);
+        render_parent_column(f, columns[0], nav, theme);
+        render_preview_column(f, columns[2], nav, theme);
+        list_area = columns[1];
+    }
+
     let visible_entries = nav.get_visible_entries(list_height);
 
-    let items: Vec<ListItem> = visible_entries
+    let denied_style = Style::default().fg(theme.nav_denied);
+
+    let mut items: Vec<ListItem> = visible_entries
         .iter()
         .map(|(idx, entry)| {
             let is_selected = nav.is_selected(*idx);
-
-            let style = if is_selected {
-                Style::default()
-                    .fg(Palette::NAV_SELECTED_FG)
-                    .bg(Palette::NAV_SELECTED_BG)
-                    .add_modifier(Modifier::BOLD)
+            let style = if entry.is_dir {
+                entry_style(theme, is_selected)
             } else {
-                Style::default().fg(Palette::TEXT_NORMAL)
+                Style::default().fg(theme.text_muted)
             };
 
             let prefix = if is_selected { "> " } else { "  " };
@@ -97,10 +135,28 @@ pub fn render_navigator(f: &mut Frame, area: Rect, nav: &mut NavigationState) {
             // Fallback to simple text if icons don't work
             let display = format!("{}{}{}", prefix, "", entry.name);
 
-            ListItem::new(Line::from(Span::styled(display, style)))
+            let mut spans = vec![Span::styled(display, style)];
+            if !entry.accessible {
+                spans.push(Span::styled(
+                    format!("  {} permission denied", icons.lock()),
+                    denied_style,
+                ));
+            }
+
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
+    // Show why the current directory has no entries
+    if let Some(ref error) = nav.error {
+        if items.len() < list_height {
+            items.push(ListItem::new(Line::from(Span::styled(
+                format!("  {} {}", icons.lock(), error),
+                denied_style,
+            ))));
+        }
+    }
+
     let list = List::new(items);
     f.render_widget(list, list_area);
 
@@ -112,26 +168,273 @@ pub fn render_navigator(f: &mut Frame, area: Rect, nav: &mut NavigationState) {
         height: footer_height as u16,
     };
 
-    let hint_style = Style::default().fg(Palette::TEXT_MUTED);
-    let key_style = Style::default().fg(Palette::NAV_KEY_HINT);
-
-    let footer_lines = vec![
-        Line::from(vec![
-            Span::styled("\u{2191}\u{2193}", key_style),
-            Span::styled(" move  ", hint_style),
-            Span::styled("\u{2192}", key_style),
-            Span::styled(" enter  ", hint_style),
-            Span::styled("\u{2190}", key_style),
-            Span::styled(" up", hint_style),
-        ]),
-        Line::from(vec![
-            Span::styled("Enter", key_style),
-            Span::styled(" confirm  ", hint_style),
-            Span::styled("Esc", key_style),
-            Span::styled(" cancel", hint_style),
-        ]),
-    ];
-
-    let footer = Paragraph::new(footer_lines);
-    f.render_widget(footer, footer_area);
+    if let Some(ref prompt) = nav.prompt {
+        let (label, hints): (String, &[(&str, &str)]) = match prompt.action {
+            PromptAction::Mkdir => (
+                format!("New directory: {}\u{2588}", prompt.input),
+                &[("Enter", "create"), ("Esc", "cancel")],
+            ),
+            PromptAction::Rename(_) => (
+                format!("Rename to: {}\u{2588}", prompt.input),
+                &[("Enter", "rename"), ("Esc", "cancel")],
+            ),
+            PromptAction::Delete(ref path) => (
+                format!(
+                    "Delete {} and everything in it?",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ),
+                &[("y", "delete"), ("n", "cancel")],
+            ),
+        };
+        let label_area = Rect {
+            height: 1,
+            ..footer_area
+        };
+        f.render_widget(
+            Paragraph::new(Span::styled(label, Style::default().fg(theme.nav_header))),
+            label_area,
+        );
+        let hints_area = Rect {
+            y: footer_area.y + 1,
+            height: 1,
+            ..footer_area
+        };
+        render_key_hints(f, hints_area, &[hints], theme);
+        return;
+    }
+
+    render_key_hints(
+        f,
+        footer_area,
+        &[
+            &[
+                ("\u{2191}\u{2193}", "move"),
+                ("\u{2192}", "enter"),
+                ("\u{2190}", "up"),
+                ("s", "sort"),
+                (
+                    "f",
+                    if nav.show_files {
+                        "hide files"
+                    } else {
+                        "show files"
+                    },
+                ),
+                (
+                    ".",
+                    if nav.show_hidden {
+                        "hide hidden"
+                    } else {
+                        "show hidden"
+                    },
+                ),
+            ],
+            &[
+                ("Enter", "confirm"),
+                ("/", "search"),
+                ("a", "mkdir"),
+                ("r", "rename"),
+                ("d", "delete"),
+                (
+                    "v",
+                    if nav.layout == NavLayout::List {
+                        "columns"
+                    } else {
+                        "list"
+                    },
+                ),
+                ("Esc", "cancel"),
+            ],
+        ],
+        theme,
+    );
+}
+
+/// Render the deep search: query and progress, matches, and key hints
+/// Render the parent directory's subdirectories, marking the current one
+fn render_parent_column(f: &mut Frame, area: Rect, nav: &NavigationState, theme: &Theme) {
+    let height = area.height as usize;
+    // Keep the current directory in view
+    let skip = nav
+        .parent_selected
+        .map_or(0, |idx| (idx + 1).saturating_sub(height));
+    let items: Vec<ListItem> = nav
+        .parent
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(height)
+        .map(|(idx, entry)| {
+            let style = if Some(idx) == nav.parent_selected {
+                Style::default()
+                    .fg(theme.nav_header)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text_muted)
+            };
+            ListItem::new(Span::styled(format!(" {}", entry.name), style))
+        })
+        .collect();
+    f.render_widget(
+        List::new(items).block(
+            Block::default()
+                .borders(Borders::RIGHT)
+                .border_style(Style::default().fg(theme.border_default)),
+        ),
+        area,
+    );
+}
+
+/// Render the contents of the selected directory
+fn render_preview_column(f: &mut Frame, area: Rect, nav: &mut NavigationState, theme: &Theme) {
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(theme.border_default));
+    let height = area.height as usize;
+    let items: Vec<ListItem> = match nav.preview() {
+        Some(Ok(entries)) if entries.is_empty() => vec![ListItem::new(Span::styled(
+            " (empty)",
+            Style::default().fg(theme.text_muted),
+        ))],
+        Some(Ok(entries)) => entries
+            .iter()
+            .take(height)
+            .map(|entry: &DirEntry| {
+                let style = if entry.is_dir {
+                    Style::default().fg(theme.text_normal)
+                } else {
+                    Style::default().fg(theme.text_muted)
+                };
+                ListItem::new(Span::styled(format!(" {}", entry.name), style))
+            })
+            .collect(),
+        Some(Err(error)) => vec![ListItem::new(Span::styled(
+            format!(" {} {}", Icons::new().lock(), error),
+            Style::default().fg(theme.nav_denied),
+        ))],
+        None => Vec::new(),
+    };
+    f.render_widget(List::new(items).block(block), area);
+}
+
+fn render_search(f: &mut Frame, area: Rect, search: &mut DeepSearch, theme: &Theme) {
+    let list_height = area.height.saturating_sub(3) as usize;
+
+    let status = if search.query.is_empty() {
+        "type to search every directory below".to_string()
+    } else if search.results.len() >= MAX_RESULTS {
+        format!("first {MAX_RESULTS} matches")
+    } else if search.done {
+        format!("{} matches", search.results.len())
+    } else {
+        format!("searching\u{2026} {} matches", search.results.len())
+    };
+    let header = Line::from(vec![
+        Span::styled(
+            format!("/{}\u{2588} ", search.query),
+            Style::default()
+                .fg(theme.nav_header)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(status, Style::default().fg(theme.text_muted)),
+    ]);
+    f.render_widget(Paragraph::new(header), Rect { height: 1, ..area });
+
+    search.adjust_scroll(list_height);
+    let items: Vec<ListItem> = search
+        .results
+        .iter()
+        .enumerate()
+        .skip(search.scroll)
+        .take(list_height)
+        .map(|(index, path)| {
+            let selected = index == search.selected;
+            let prefix = if selected { "> " } else { "  " };
+            ListItem::new(Span::styled(
+                format!("{prefix}{}", search.display(path)),
+                entry_style(theme, selected),
+            ))
+        })
+        .collect();
+    let list_area = Rect {
+        y: area.y + 1,
+        height: list_height as u16,
+        ..area
+    };
+    f.render_widget(List::new(items), list_area);
+
+    let footer_area = Rect {
+        y: area.y + 1 + list_height as u16,
+        height: 2,
+        ..area
+    };
+    render_key_hints(
+        f,
+        footer_area,
+        &[
+            &[
+                ("\u{2191}\u{2193}", "move"),
+                ("\u{2192}", "browse"),
+                ("Enter", "cd"),
+            ],
+            &[("type", "filter"), ("Esc", "close search")],
+        ],
+        theme,
+    );
+}
+
+/// Render a path on one line, cutting its start to fit the width
+pub(super) fn render_path_header(f: &mut Frame, area: Rect, path: &str, theme: &Theme) {
+    let width = area.width as usize;
+    let path_text = if path.len() > width.saturating_sub(2) && width > 4 {
+        format!("..{}", &path[path.len() - (width - 4)..])
+    } else {
+        path.to_string()
+    };
+
+    let header = Paragraph::new(Line::from(Span::styled(
+        path_text,
+        Style::default()
+            .fg(theme.nav_header)
+            .add_modifier(Modifier::BOLD),
+    )));
+    f.render_widget(header, area);
+}
+
+/// Style of a list entry, highlighted when selected
+pub(super) fn entry_style(theme: &Theme, selected: bool) -> Style {
+    if selected {
+        Style::default()
+            .fg(theme.nav_selected_fg)
+            .bg(theme.nav_selected_bg)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.text_normal)
+    }
+}
+
+/// Render lines of `key action` hints
+pub(super) fn render_key_hints(
+    f: &mut Frame,
+    area: Rect,
+    lines: &[&[(&str, &str)]],
+    theme: &Theme,
+) {
+    let hint_style = Style::default().fg(theme.text_muted);
+    let key_style = Style::default().fg(theme.nav_key_hint);
+
+    let footer_lines: Vec<Line> = lines
+        .iter()
+        .map(|hints| {
+            let mut spans = Vec::new();
+            for (i, (key, action)) in hints.iter().enumerate() {
+                let separator = if i + 1 < hints.len() { "  " } else { "" };
+                spans.push(Span::styled(key.to_string(), key_style));
+                spans.push(Span::styled(format!(" {action}{separator}"), hint_style));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(footer_lines), area);
 }
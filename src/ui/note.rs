@@ -0,0 +1,70 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::centered_rect;
+use crate::notes::Scratchpad;
+use crate::theme::Theme;
+
+/// Render the `jerm note` scratchpad pane over the terminal
+pub fn render_note(f: &mut Frame, area: Rect, pad: &mut Scratchpad, theme: &Theme) {
+    let popup = centered_rect(area, 70, 60);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(format!(" Note: {} ", pad.dir.display()));
+
+    let inner_area = block.inner(popup);
+    f.render_widget(block, popup);
+
+    if inner_area.height < 2 || inner_area.width == 0 {
+        return;
+    }
+
+    let body_height = (inner_area.height - 1) as usize;
+    pad.adjust_scroll(body_height);
+
+    let lines: Vec<Line> = pad
+        .lines
+        .iter()
+        .skip(pad.scroll)
+        .take(body_height)
+        .map(|line| {
+            Line::from(Span::styled(
+                line.clone(),
+                Style::default().fg(theme.text_normal),
+            ))
+        })
+        .collect();
+    let body = Rect {
+        height: inner_area.height - 1,
+        ..inner_area
+    };
+    f.render_widget(Paragraph::new(lines), body);
+
+    let footer = Rect {
+        y: inner_area.y + inner_area.height - 1,
+        height: 1,
+        ..inner_area
+    };
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            "Esc save and close · an empty note is deleted",
+            Style::default().fg(theme.nav_key_hint),
+        )),
+        footer,
+    );
+
+    let before_cursor: String = pad.lines[pad.row].chars().take(pad.col).collect();
+    let cursor_x = (before_cursor.width() as u16).min(inner_area.width - 1);
+    let cursor_y = (pad.row - pad.scroll) as u16;
+    f.set_cursor(inner_area.x + cursor_x, inner_area.y + cursor_y);
+}
@@ -6,38 +6,112 @@ use ratatui::{
     Frame,
 };
 
-use crate::shortcuts::ShortcutManager;
-use crate::theme::{Icons, Palette};
-
-/// Render the shortcuts sidebar
-pub fn render_sidebar(
-    f: &mut Frame,
-    area: Rect,
-    shortcuts: &ShortcutManager,
-    selected_index: Option<usize>,
-) {
+use chrono::Utc;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::app::{App, AppMode};
+use crate::fuzzy::match_positions;
+use crate::keymap::Action;
+use crate::theme::{blend, Icons};
+
+/// Smallest inner height that still leaves room for the list above the hints
+const MIN_HEIGHT_FOR_HINTS: u16 = 6;
+
+/// Hotkeys that apply to the sidebar right now, one inner vec per footer row
+fn key_hints(app: &App, selected_index: Option<usize>) -> Vec<Vec<(String, &'static str)>> {
+    let Some(index) = selected_index else {
+        let mut line = vec![("^1-9".to_string(), "jump")];
+        if let Some(chord) = app.keymap.chord(Action::Goto) {
+            line.push((chord.to_string(), "goto"));
+        }
+        return vec![line];
+    };
+
+    let mut first = vec![("↵".to_string(), "go")];
+    if !app.readonly {
+        first.push(("Del".to_string(), "delete"));
+    }
+
+    let pinned = app
+        .goto_candidates()
+        .get(index)
+        .map(|shortcut| shortcut.pinned);
+    let second = match pinned {
+        Some(true) => vec![("^P".to_string(), "unpin"), ("⇧↑↓".to_string(), "move")],
+        Some(false) => vec![("^P".to_string(), "pin"), ("type".to_string(), "filter")],
+        None => vec![("type".to_string(), "filter")],
+    };
+    vec![first, second]
+}
+
+/// Render the shortcuts sidebar, highlighting the selection in goto mode
+pub fn render_sidebar(f: &mut Frame, area: Rect, app: &mut App) {
+    app.screen.shortcuts = Rect::default();
+    let theme = &app.theme;
+    let shortcuts = &app.shortcuts;
+    let selected_index =
+        (app.mode == AppMode::ShortcutSelection).then_some(app.selected_shortcut_index);
+    let profile = crate::paths::active_profile();
+    let goto_hint = app.keymap.chord(Action::Goto);
     let icons = Icons::new();
 
+    let filter = selected_index
+        .is_some()
+        .then_some(app.goto_filter.as_str())
+        .filter(|f| !f.is_empty());
+
+    let mut title = match (filter, profile) {
+        (Some(filter), _) => format!(" /{filter} "),
+        (None, Some(name)) => format!(" Shortcuts ({name}) "),
+        (None, None) => " Shortcuts ".to_string(),
+    };
+    // Show the goto chord when it fits between the corners
+    if let (Some(hint), None) = (goto_hint, filter) {
+        let with_hint = format!("{title}{hint} ");
+        if with_hint.width() + 2 <= area.width as usize {
+            title = with_hint;
+        }
+    }
+
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Plain)
-        .border_style(Style::default().fg(Palette::BORDER_DEFAULT))
-        .title(" Shortcuts ");
+        .border_style(Style::default().fg(theme.border_default))
+        .title(title);
 
-    let inner_area = block.inner(area);
+    let mut inner_area = block.inner(area);
     f.render_widget(block, area);
 
+    // Key hints take the bottom rows when there's room left for the list
+    if !shortcuts.is_empty() && inner_area.height >= MIN_HEIGHT_FOR_HINTS {
+        let hints = key_hints(app, selected_index);
+        let rows = hints.len() as u16;
+        inner_area.height -= rows;
+        let footer = Rect::new(
+            inner_area.x,
+            inner_area.y + inner_area.height,
+            inner_area.width,
+            rows,
+        );
+        let lines: Vec<Vec<(&str, &str)>> = hints
+            .iter()
+            .map(|line| line.iter().map(|(k, a)| (k.as_str(), *a)).collect())
+            .collect();
+        let lines: Vec<&[(&str, &str)]> = lines.iter().map(Vec::as_slice).collect();
+        super::navigator::render_key_hints(f, footer, &lines, theme);
+    }
+
     if shortcuts.is_empty() {
         // Show help text when no shortcuts
         let help_items = vec![
             ListItem::new(Line::from(Span::styled(
                 "No shortcuts",
-                Style::default().fg(Palette::TEXT_MUTED),
+                Style::default().fg(theme.text_muted),
             ))),
             ListItem::new(Line::from("")),
             ListItem::new(Line::from(Span::styled(
                 "jerm save to add",
-                Style::default().fg(Palette::TEXT_MUTED),
+                Style::default().fg(theme.text_muted),
             ))),
         ];
 
@@ -46,65 +120,104 @@ pub fn render_sidebar(
         return;
     }
 
-    // Get shortcuts sorted by last accessed
-    let shortcut_list = shortcuts.get_shortcuts();
+    // Get shortcuts: pinned first, then by last accessed (or best matches)
+    let count = app.goto_candidates().len() as u16;
+    app.screen.shortcuts = Rect {
+        height: inner_area.height.min(count),
+        ..inner_area
+    };
+    let shortcut_list = app.goto_candidates();
     let inner_width = inner_area.width as usize;
 
+    if shortcut_list.is_empty() {
+        let no_matches = ListItem::new(Line::from(Span::styled(
+            "No matches",
+            Style::default().fg(theme.text_muted),
+        )));
+        f.render_widget(List::new(vec![no_matches]), inner_area);
+        return;
+    }
+
+    // Frecency relative to the hottest visible shortcut, for tinting
+    let now = Utc::now();
+    let max_frecency = shortcut_list
+        .iter()
+        .map(|s| s.frecency(now))
+        .fold(0.0, f64::max);
+
+    // Numbers are right-aligned to the widest one
+    let digits = shortcut_list.len().to_string().len();
+
     // Create list items with numbers, icons, paths, and times
     let items: Vec<ListItem> = shortcut_list
         .iter()
-        .take(9) // Only show first 9 (for Ctrl+1 through Ctrl+9)
         .enumerate()
         .map(|(i, shortcut)| {
             let is_selected = selected_index == Some(i);
 
+            // Hot shortcuts get a bright number and time, cold ones fade out
+            let (number_color, time_color) = if app.config.ui.shortcut_heat && max_frecency > 0.0 {
+                let color = blend(
+                    theme.text_muted,
+                    theme.sidebar_number,
+                    shortcut.frecency(now) / max_frecency,
+                );
+                (color, color)
+            } else {
+                (theme.sidebar_number, theme.sidebar_time)
+            };
+
             let number_style = if is_selected {
                 Style::default()
-                    .fg(Palette::SIDEBAR_NUMBER)
-                    .bg(Palette::BG_SELECTED)
+                    .fg(number_color)
+                    .bg(theme.bg_selected)
                     .add_modifier(Modifier::BOLD)
             } else {
                 Style::default()
-                    .fg(Palette::SIDEBAR_NUMBER)
+                    .fg(number_color)
                     .add_modifier(Modifier::BOLD)
             };
 
             let path_style = if is_selected {
                 Style::default()
-                    .fg(Palette::SIDEBAR_PATH)
-                    .bg(Palette::BG_SELECTED)
+                    .fg(theme.sidebar_path)
+                    .bg(theme.bg_selected)
             } else {
-                Style::default().fg(Palette::SIDEBAR_PATH)
+                Style::default().fg(theme.sidebar_path)
             };
 
             let time_style = if is_selected {
-                Style::default()
-                    .fg(Palette::SIDEBAR_TIME)
-                    .bg(Palette::BG_SELECTED)
+                Style::default().fg(time_color).bg(theme.bg_selected)
             } else {
-                Style::default().fg(Palette::SIDEBAR_TIME)
+                Style::default().fg(time_color)
             };
 
-            let display_name = shortcut.display_name();
-            let time_ago = shortcut.time_ago();
+            let display_name = shortcut.label();
+            // Pinned shortcuts don't move with use, so a pin replaces the time
+            let time_ago = if shortcut.pinned {
+                Some(icons.pin().to_string())
+            } else {
+                shortcut.time_label(app.config.ui.shortcut_time, &app.messages.time)
+            };
 
             // Layout: [num] [icon] [path...] [time]
-            // num: 2 chars ("1 ")
+            // num: the widest number and a space (" 1 " next to "10 ")
             // icon: 2 chars if nerd fonts (" " or "~ "), else 0
             // time: variable (right-aligned)
 
-            let icon = if display_name.starts_with('~') {
+            let icon = if shortcut.display_name().starts_with('~') {
                 icons.home()
             } else {
                 icons.folder()
             };
 
             let icon_width = if icons.has_nerd_fonts() { 2 } else { 0 };
-            let num_width = 2; // "1 "
-            let time_width = time_ago.len() + 1; // " 2h"
+            let num_width = digits + 1;
+            let time_ago = time_ago.unwrap_or_default();
+            let time_width = time_ago.width() + 1; // " 2h"
 
-            // Only show time if we have enough width (at least 20 chars)
-            let show_time = inner_width >= 20;
+            // Only show time if set and there's enough width (at least 20 chars)
+            let show_time = inner_width >= 20 && !time_ago.is_empty();
 
             let available_for_path = if show_time {
                 inner_width
@@ -117,22 +230,23 @@ pub fn render_sidebar(
                     .saturating_sub(icon_width)
             };
 
-            // Truncate path if needed
-            let truncated_path = if display_name.len() > available_for_path {
+            // Truncate path if needed; `skipped` counts label chars cut from the front
+            let (truncated_path, skipped) = if display_name.width() > available_for_path {
                 if available_for_path > 3 {
-                    format!(
-                        "..{}",
-                        &display_name[display_name.len() - (available_for_path - 2)..]
+                    let start = tail_start(&display_name, available_for_path - 2);
+                    (
+                        format!("..{}", &display_name[start..]),
+                        display_name[..start].chars().count() as isize - 2,
                     )
                 } else {
-                    display_name.chars().take(available_for_path).collect()
+                    (head(&display_name, available_for_path).to_string(), 0)
                 }
             } else {
-                display_name.clone()
+                (display_name.clone(), 0)
             };
 
             // Calculate padding for right-aligned time
-            let path_len = truncated_path.len();
+            let path_len = truncated_path.width();
             let padding_len = if show_time {
                 available_for_path.saturating_sub(path_len)
             } else {
@@ -140,13 +254,32 @@ pub fn render_sidebar(
             };
             let padding = " ".repeat(padding_len);
 
-            let mut spans = vec![Span::styled(format!("{} ", i + 1), number_style)];
+            let mut spans = vec![Span::styled(format!("{:>digits$} ", i + 1), number_style)];
 
             if icons.has_nerd_fonts() {
                 spans.push(Span::styled(format!("{} ", icon), path_style));
             }
 
-            spans.push(Span::styled(truncated_path, path_style));
+            // Characters matching the goto filter stand out
+            let matched = filter
+                .and_then(|filter| match_positions(filter, &display_name))
+                .unwrap_or_default();
+            if matched.is_empty() {
+                spans.push(Span::styled(truncated_path, path_style));
+            } else {
+                let match_style = path_style
+                    .fg(theme.sidebar_number)
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+                for (index, c) in truncated_path.chars().enumerate() {
+                    let label_index = index as isize + skipped;
+                    let style = if label_index >= 0 && matched.contains(&(label_index as usize)) {
+                        match_style
+                    } else {
+                        path_style
+                    };
+                    spans.push(Span::styled(c.to_string(), style));
+                }
+            }
 
             if show_time {
                 spans.push(Span::styled(padding, path_style));
@@ -160,3 +293,28 @@ pub fn render_sidebar(
     let list = List::new(items);
     f.render_widget(list, inner_area);
 }
+
+/// Byte offset where the longest end of `text` fitting in `width` columns
+/// starts
+fn tail_start(text: &str, width: usize) -> usize {
+    let mut used = 0;
+    for (index, c) in text.char_indices().rev() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return index + c.len_utf8();
+        }
+    }
+    0
+}
+
+/// The longest start of `text` fitting in `width` columns
+fn head(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &text[..index];
+        }
+    }
+    text
+}
@@ -1,15 +1,29 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::app::App;
+use super::completion::render_completion;
+use crate::ansi;
+use crate::app::{App, InputArea, NEWLINE_MARKER};
+use crate::blocks::{Change, Diagnostics, OutputDiff, OutputSearch, Severity};
+use crate::config::OutputWrap;
+use crate::highlight::spelling::{self, Misspelling};
+use crate::highlight::tokenizer::TokenType;
 use crate::highlight::Tokenizer;
-use crate::theme::Palette;
+use crate::navigation::diffdir::format_size;
+use crate::shell::pty::PtySession;
+use crate::theme::Theme;
+
+/// Wrapped rows the input takes before `ui.input_box` moves it into a box
+const INPUT_BOX_AFTER: usize = 2;
+
+/// Rows inside the input box
+const INPUT_BOX_ROWS: usize = 6;
 
 /// Wrap a line of text into multiple lines based on width
 fn wrap_line(line: &str, width: usize) -> Vec<String> {
@@ -40,12 +54,251 @@ fn wrap_line(line: &str, width: usize) -> Vec<String> {
     result
 }
 
+/// Wrap styled spans into lines based on width, keeping each span's style
+fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Line<'static>> {
+    if width == 0 {
+        return vec![Line::default()];
+    }
+
+    let mut lines = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut current_width = 0;
+
+    for span in spans {
+        let mut text = String::new();
+        for ch in span.content.chars() {
+            let char_width = ch.width().unwrap_or(0);
+            if current_width + char_width > width {
+                if !text.is_empty() {
+                    current.push(Span::styled(std::mem::take(&mut text), span.style));
+                }
+                lines.push(Line::from(std::mem::take(&mut current)));
+                current_width = 0;
+            }
+            text.push(ch);
+            current_width += char_width;
+        }
+        if !text.is_empty() {
+            current.push(Span::styled(text, span.style));
+        }
+    }
+
+    lines.push(Line::from(current));
+    lines
+}
+
+/// Cut styled spans to the `width` columns starting `offset` columns in,
+/// for truncate mode; a wide character split by either edge is dropped
+fn clip_spans(spans: Vec<Span<'static>>, offset: usize, width: usize) -> Line<'static> {
+    let end = offset + width;
+    let mut clipped = Vec::new();
+    let mut column = 0;
+
+    for span in spans {
+        let mut text = String::new();
+        for ch in span.content.chars() {
+            let char_width = ch.width().unwrap_or(0);
+            if column >= offset && column + char_width <= end {
+                text.push(ch);
+            }
+            column += char_width;
+        }
+        if !text.is_empty() {
+            clipped.push(Span::styled(text, span.style));
+        }
+        if column >= end {
+            break;
+        }
+    }
+
+    Line::from(clipped)
+}
+
+/// Color a stderr line in the theme's error color after `prefix`, keeping
+/// the colors the command chose itself
+fn stderr_spans(spans: Vec<Span<'static>>, prefix: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let error = Style::default().fg(theme.text_error);
+    let prefix = (!prefix.is_empty()).then(|| Span::styled(prefix.to_string(), error));
+    prefix
+        .into_iter()
+        .chain(spans.into_iter().map(|span| match span.style.fg {
+            Some(_) => span,
+            None => span.patch_style(error),
+        }))
+        .collect()
+}
+
+/// Underline the misspelled words (byte ranges) of the input line
+fn underline_misspellings(
+    spans: Vec<Span<'static>>,
+    misspellings: &[Misspelling],
+    theme: &Theme,
+) -> Vec<Span<'static>> {
+    let underline = Style::default()
+        .add_modifier(Modifier::UNDERLINED)
+        .underline_color(theme.text_error);
+    let mut underlined = Vec::new();
+    let mut start = 0;
+    for span in spans {
+        let end = start + span.content.len();
+        let mut at = start;
+        for range in misspellings
+            .iter()
+            .map(|m| m.range.start.max(start)..m.range.end.min(end))
+            .filter(|range| range.start < range.end)
+        {
+            if at < range.start {
+                underlined.push(Span::styled(
+                    span.content[at - start..range.start - start].to_string(),
+                    span.style,
+                ));
+            }
+            underlined.push(Span::styled(
+                span.content[range.start - start..range.end - start].to_string(),
+                span.style.patch(underline),
+            ));
+            at = range.end;
+        }
+        if at < end {
+            underlined.push(Span::styled(
+                span.content[at - start..].to_string(),
+                span.style,
+            ));
+        }
+        start = end;
+    }
+    underlined
+}
+
+/// Mark the search's matches in output line `index`, the current match
+/// standing out from the rest
+fn highlight_matches(
+    spans: Vec<Span<'static>>,
+    search: &OutputSearch,
+    index: usize,
+    theme: &Theme,
+) -> Vec<Span<'static>> {
+    let text: String = spans.iter().map(|span| span.content.as_ref()).collect();
+    let matches = search.find_in(&text);
+    if matches.is_empty() {
+        return spans;
+    }
+    let current = search
+        .current()
+        .filter(|m| m.line == index)
+        .map(|m| m.start);
+    let match_style = Style::default().bg(theme.bg_selected);
+    let current_style = Style::default().fg(Color::Black).bg(theme.block_warning);
+
+    let mut highlighted = Vec::new();
+    let mut column = 0;
+    for span in spans {
+        let mut text = String::new();
+        let mut style = span.style;
+        for ch in span.content.chars() {
+            let hit = matches
+                .iter()
+                .find(|(start, end)| (*start..*end).contains(&column));
+            let ch_style = match hit {
+                Some((start, _)) if Some(*start) == current => span.style.patch(current_style),
+                Some(_) => span.style.patch(match_style),
+                None => span.style,
+            };
+            if ch_style != style && !text.is_empty() {
+                highlighted.push(Span::styled(std::mem::take(&mut text), style));
+            }
+            style = ch_style;
+            text.push(ch);
+            column += 1;
+        }
+        if !text.is_empty() {
+            highlighted.push(Span::styled(text, style));
+        }
+    }
+    highlighted
+}
+
+/// Count badge appended to a block header, e.g. `  [2 errors, 1 warning]`
+fn diagnostics_badge(diagnostics: &Diagnostics, theme: &Theme) -> Span<'static> {
+    let color = if diagnostics.errors() > 0 {
+        theme.block_failure
+    } else {
+        theme.block_warning
+    };
+    let marker = if diagnostics.expanded {
+        "\u{25be}"
+    } else {
+        "\u{25b8}"
+    };
+    Span::styled(
+        format!("  [{marker} {}]", diagnostics.badge()),
+        Style::default().fg(color).add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Badge appended to a re-run block's header, e.g. `  [+2 −1 vs 14:02:11]`
+fn diff_badge(diff: &OutputDiff, theme: &Theme) -> Span<'static> {
+    let text = if diff.lines.is_empty() {
+        format!("  [= {}]", diff.badge())
+    } else {
+        let marker = if diff.expanded {
+            "\u{25be}"
+        } else {
+            "\u{25b8}"
+        };
+        format!("  [{marker} {}]", diff.badge())
+    };
+    Span::styled(text, Style::default().fg(theme.prompt_duration))
+}
+
 /// Render the main terminal area
-pub fn render_terminal(f: &mut Frame, area: Rect, app: &App) {
+pub fn render_terminal(f: &mut Frame, area: Rect, app: &mut App) {
+    app.screen.terminal = area;
+    app.screen.input = None;
+    let auto_cd = app.input_is_auto_cd();
+    let theme = &app.theme;
+    let mut title = String::from(" Terminal ");
+    if app.readonly {
+        title.push_str("[readonly] ");
+    }
+    if let Some(ref recording) = app.recording {
+        title.push_str(&format!("[rec {}] ", recording.name));
+    }
+    if let Some(status) = app.key_macros.status() {
+        title.push_str(&format!("[{status}] "));
+    }
+    if let Some(hint) = app.quit_hint() {
+        title.push_str(&format!("[{hint}] "));
+    }
+    if let Some(ref running) = app.running {
+        title.push_str(&format!("[running: {}] ", running.command));
+    }
+    if let Some(ref retry) = app.retry {
+        title.push_str(&format!("[{}] ", retry.status(std::time::Instant::now())));
+    }
+    if !app.jobs.is_empty() {
+        title.push_str(&format!("[jobs: {}] ", app.jobs.jobs().len()));
+    }
+    if !app.queue.is_empty() {
+        title.push_str(&format!("[queued: {}] ", app.queue.len()));
+    }
+    if let Some(ref search) = app.search {
+        let cursor = if search.editing { "\u{258f}" } else { "" };
+        title.push_str(&format!("[/{}{cursor} {}] ", search.query, search.status()));
+    } else if app.output_scroll.is_some() {
+        title.push_str("[scrollback] ");
+    }
+    if app.output_wrap == OutputWrap::Truncate {
+        match app.output_hscroll {
+            0 => title.push_str("[nowrap] "),
+            columns => title.push_str(&format!("[nowrap +{columns}] ")),
+        }
+    }
+
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Palette::BORDER_DEFAULT))
-        .title(" Terminal ");
+        .border_style(Style::default().fg(theme.border_default))
+        .title(title);
 
     let inner_area = block.inner(area);
     f.render_widget(block, area);
@@ -56,67 +309,293 @@ pub fn render_terminal(f: &mut Frame, area: Rect, app: &App) {
     // Build visual lines as Line objects
     let mut visual_lines: Vec<Line> = Vec::new();
 
-    // Add output lines (with wrapping)
-    for line in &app.output {
-        let wrapped = wrap_line(line, width);
-        for wrapped_line in wrapped {
-            visual_lines.push(Line::from(wrapped_line));
+    // Visual line where the pinned output line starts (when browsing scrollback)
+    let mut pinned_visual_line = None;
+
+    // Blocks with an error summary, keyed by their header line
+    let mut summaries = app
+        .blocks
+        .iter()
+        .filter_map(|block| Some((block.output_start, block.diagnostics.as_ref()?)))
+        .peekable();
+
+    // Re-run blocks with a diff against the earlier run
+    let mut diffs = app
+        .blocks
+        .iter()
+        .filter_map(|block| Some((block.output_start, block.diff.as_ref()?)))
+        .peekable();
+
+    // Output lines wrap, or in truncate mode show one scrolled row each
+    let fit = |spans: Vec<Span<'static>>| match app.output_wrap {
+        OutputWrap::Wrap => wrap_spans(spans, width),
+        OutputWrap::Truncate => vec![clip_spans(spans, app.output_hscroll, width)],
+    };
+
+    // Visual line where the output after the last `clear` starts
+    let mut cleared_visual_line = None;
+
+    // Add output lines
+    for (index, line) in app.output.iter().enumerate() {
+        if app.output_scroll == Some(index) {
+            pinned_visual_line = Some(visual_lines.len());
+        }
+        if index == app.clear_mark {
+            cleared_visual_line = Some(visual_lines.len());
+        }
+        let mut spans = ansi::parse_line(line);
+        if let Some(ref search) = app.search {
+            spans = highlight_matches(spans, search, index, theme);
+        }
+        if app.stderr_lines.contains(&index) {
+            spans = stderr_spans(spans, &app.config.ui.stderr_prefix, theme);
+        }
+        let summary = summaries
+            .next_if(|(start, _)| *start == index)
+            .map(|(_, d)| d);
+        if let Some(diagnostics) = summary {
+            spans.push(diagnostics_badge(diagnostics, theme));
+        }
+        let diff = diffs.next_if(|(start, _)| *start == index).map(|(_, d)| d);
+        if let Some(diff) = diff {
+            spans.push(diff_badge(diff, theme));
+        }
+        visual_lines.extend(fit(spans));
+        if let Some(diff) = diff.filter(|d| d.expanded) {
+            for line in &diff.lines {
+                let (sign, color) = match line.change {
+                    Change::Added => ("+ ", theme.block_success),
+                    Change::Removed => ("- ", theme.block_failure),
+                };
+                let spans = vec![
+                    Span::styled("  \u{2502} ", Style::default().fg(theme.text_muted)),
+                    Span::styled(format!("{sign}{}", line.text), Style::default().fg(color)),
+                ];
+                visual_lines.extend(fit(spans));
+            }
+        }
+        if let Some(diagnostics) = summary.filter(|d| d.expanded) {
+            for item in &diagnostics.items {
+                let color = match item.severity {
+                    Severity::Error => theme.block_failure,
+                    Severity::Warning => theme.block_warning,
+                };
+                let spans = vec![
+                    Span::styled("  \u{2502} ", Style::default().fg(theme.text_muted)),
+                    Span::styled(item.text.clone(), Style::default().fg(color)),
+                ];
+                visual_lines.extend(fit(spans));
+            }
         }
     }
 
-    // Save where the input line starts
-    let input_line_start = visual_lines.len();
+    // Macro playback and template status sit just above the input line
+    if let Some(status) = app.playback_status().or_else(|| app.template_status()) {
+        for wrapped_line in wrap_line(&status, width) {
+            visual_lines.push(Line::from(Span::styled(
+                wrapped_line,
+                Style::default().fg(theme.nav_key_hint),
+            )));
+        }
+    }
+
+    // Cleared output is above the view unless scrolled back to
+    let cleared_visual_line = cleared_visual_line.unwrap_or(visual_lines.len());
+
+    // While a command runs its output takes the place of the prompt
+    if app.running.is_some() {
+        let bottom_scroll = visual_lines
+            .len()
+            .saturating_sub(available_height)
+            .max(cleared_visual_line);
+        let scroll = pinned_visual_line.map_or(bottom_scroll, |line| line.min(bottom_scroll));
+        let visible_lines: Vec<Line> = visual_lines.into_iter().skip(scroll).collect();
+        f.render_widget(Paragraph::new(visible_lines), inner_area);
+        return;
+    }
 
     // Add current prompt and input (with wrapping and syntax highlighting)
     let prompt_spans = app.prompt_spans();
 
+    // Pasted newlines are shown as a visible marker on the single input line
+    let input = app.input.replace('\n', NEWLINE_MARKER);
+
     // Tokenize and highlight the input
-    let tokens = Tokenizer::tokenize(&app.input);
-    let input_spans = Tokenizer::to_spans(&tokens);
+    let mut tokens = Tokenizer::tokenize(&input);
+    // With auto_cd a directory on its own is a cd, not a command
+    if auto_cd {
+        for token in tokens
+            .iter_mut()
+            .filter(|t| t.token_type == TokenType::Command)
+        {
+            token.token_type = TokenType::Path;
+        }
+    }
+    let mut input_spans = Tokenizer::to_spans(&tokens, theme);
+    let misspellings = if app.config.git.spell_check {
+        spelling::check(&input)
+    } else {
+        Vec::new()
+    };
+    if !misspellings.is_empty() {
+        input_spans = underline_misspellings(input_spans, &misspellings, theme);
+    }
 
     let mut full_line_spans = prompt_spans;
     full_line_spans.extend(input_spans);
 
     // For wrapping calculation, use plain string
     let prompt_str = app.prompt_string();
-    let full_input_line = format!("{}{}", prompt_str, app.input);
-    let wrapped_input = wrap_line(&full_input_line, width);
+    let full_input_line = format!("{}{}", prompt_str, input);
+
+    // With `ui.input_box` a long input moves into a box of its own under the
+    // output, so the output stays put while it grows
+    let boxed = app.config.ui.input_box
+        && wrap_line(&full_input_line, width).len() > INPUT_BOX_AFTER
+        && available_height >= (INPUT_BOX_ROWS + 2) * 2;
+    let input_width = if boxed {
+        width.saturating_sub(2).max(1)
+    } else {
+        width
+    };
+    let wrapped_input = wrap_line(&full_input_line, input_width);
+
+    // The input and the lines about it
+    let mut input_lines: Vec<Line> = Vec::new();
 
     // If no wrapping, use colored Line
     if wrapped_input.len() == 1 {
-        visual_lines.push(Line::from(full_line_spans));
+        // Ghost-text autosuggestion, cut to the space left on the line
+        if let Some(ref suggestion) = app.suggestion {
+            let room = input_width.saturating_sub(full_input_line.width());
+            let ghost: String = suggestion
+                .chars()
+                .scan(0, |used, c| {
+                    *used += c.width().unwrap_or(0);
+                    (*used <= room).then_some(c)
+                })
+                .collect();
+            full_line_spans.push(Span::styled(ghost, Style::default().fg(theme.text_muted)));
+        }
+        input_lines.push(Line::from(full_line_spans));
     } else {
         // Wrapping: first line colored, rest plain (acceptable limitation)
-        visual_lines.push(Line::from(full_line_spans));
+        input_lines.push(Line::from(full_line_spans));
         for wrapped_part in wrapped_input.iter().skip(1) {
-            visual_lines.push(Line::from(wrapped_part.clone()));
+            input_lines.push(Line::from(wrapped_part.clone()));
         }
     }
 
-    // Calculate scroll to show the bottom
-    let total_visual_lines = visual_lines.len();
-    let scroll = total_visual_lines.saturating_sub(available_height);
+    // Size of a long input, or a warning once globs may push it past ARG_MAX
+    let stats = app.input_stats();
+    if stats.near_limit(app.arg_space) {
+        input_lines.push(Line::from(Span::styled(
+            format!(
+                "\u{26a0} about {} of arguments after glob expansion; the system allows {} (ARG_MAX)",
+                format_size(stats.expanded_bytes as u64),
+                format_size(app.arg_space as u64)
+            ),
+            Style::default().fg(theme.block_warning),
+        )));
+    } else if wrapped_input.len() > 1 {
+        input_lines.push(Line::from(Span::styled(
+            format!("{} chars \u{b7} {} args", stats.chars, stats.args),
+            Style::default().fg(theme.text_muted),
+        )));
+    }
+
+    // Corrections for the misspelled words in a commit message
+    if !misspellings.is_empty() {
+        let corrections: Vec<String> = misspellings
+            .iter()
+            .map(|m| format!("{} \u{2192} {}", &input[m.range.clone()], m.correction))
+            .collect();
+        input_lines.push(Line::from(Span::styled(
+            format!("\u{270e} {}", corrections.join(" \u{b7} ")),
+            Style::default().fg(theme.text_muted),
+        )));
+    }
+
+    // Synopsis of the command being typed, dimmed under the input
+    if let Some(synopsis) = app.synopsis_line() {
+        input_lines.push(Line::from(Span::styled(
+            synopsis,
+            Style::default()
+                .fg(theme.text_muted)
+                .add_modifier(Modifier::ITALIC),
+        )));
+    }
+
+    let mut input_area = InputArea {
+        x: inner_area.x,
+        y: 0,
+        width: input_width as u16,
+        prompt_width: prompt_str.width(),
+    };
+
+    // Which wrapped line within the input is the cursor on?
+    let (cursor_line_offset, cursor_x_offset) = input_area.cursor_cell(&app.input, app.cursor_pos);
+
+    // Where the input's lines are drawn, and the row of the first
+    let (lines_area, input_line_start, scroll) = if boxed {
+        let box_height = (INPUT_BOX_ROWS + 2) as u16;
+        let output_area = Rect {
+            height: inner_area.height - box_height,
+            ..inner_area
+        };
+        let box_area = Rect {
+            y: output_area.bottom(),
+            height: box_height,
+            ..inner_area
+        };
+
+        // The output fills the rows above the box, as it would without it
+        let bottom_scroll = visual_lines
+            .len()
+            .saturating_sub(output_area.height as usize)
+            .max(cleared_visual_line);
+        let scroll = pinned_visual_line.map_or(bottom_scroll, |line| line.min(bottom_scroll));
+        let visible_lines: Vec<Line> = visual_lines.into_iter().skip(scroll).collect();
+        f.render_widget(Paragraph::new(visible_lines), output_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border_active))
+            .title(" Input ");
+        let box_inner = block.inner(box_area);
+        f.render_widget(block, box_area);
+
+        // Show the lines under the input when they fit, but always the cursor
+        let scroll = input_lines
+            .len()
+            .saturating_sub(INPUT_BOX_ROWS)
+            .min(cursor_line_offset);
+        input_area.x = box_inner.x;
+        visual_lines = input_lines;
+        (box_inner, 0, scroll)
+    } else {
+        // Save where the input line starts
+        let input_line_start = visual_lines.len();
+        visual_lines.extend(input_lines);
+
+        // Calculate scroll to show the bottom, or the pinned line when scrolled back
+        let total_visual_lines = visual_lines.len();
+        let bottom_scroll = total_visual_lines
+            .saturating_sub(available_height)
+            .max(cleared_visual_line);
+        let scroll = pinned_visual_line.map_or(bottom_scroll, |line| line.min(bottom_scroll));
+        (inner_area, input_line_start, scroll)
+    };
 
     // Take visible lines
     let visible_lines: Vec<Line> = visual_lines.into_iter().skip(scroll).collect();
 
     // Render the visible lines
     let paragraph = Paragraph::new(visible_lines);
-    f.render_widget(paragraph, inner_area);
-
-    // Calculate cursor position
-    let prompt_width = prompt_str.width();
-    let input_before_cursor = &app.input[..app
-        .input
-        .char_indices()
-        .nth(app.cursor_pos)
-        .map(|(pos, _)| pos)
-        .unwrap_or(app.input.len())];
-    let cursor_visual_pos = prompt_width + input_before_cursor.width();
+    f.render_widget(paragraph, lines_area);
 
-    // Which wrapped line within the input is the cursor on?
-    let cursor_line_offset = cursor_visual_pos / width;
-    let cursor_x_offset = cursor_visual_pos % width;
+    input_area.y = i32::from(lines_area.y) + input_line_start as i32 - scroll as i32;
+    app.screen.input = Some(input_area);
 
     // Absolute line number where cursor is
     let cursor_line_absolute = input_line_start + cursor_line_offset;
@@ -125,35 +604,145 @@ pub fn render_terminal(f: &mut Frame, area: Rect, app: &App) {
     let cursor_line_visible = cursor_line_absolute.saturating_sub(scroll);
 
     // Set cursor position
-    let cursor_x = inner_area.x + cursor_x_offset as u16;
-    let cursor_y = inner_area.y + cursor_line_visible as u16;
+    let cursor_x = lines_area.x + cursor_x_offset as u16;
+    let cursor_y = lines_area.y + cursor_line_visible as u16;
 
     // Ensure cursor is visible
-    if cursor_line_visible < available_height {
+    if cursor_line_visible < lines_area.height as usize {
         f.set_cursor(cursor_x, cursor_y);
+        if let Some(ref menu) = app.completion {
+            render_completion(f, inner_area, (cursor_x, cursor_y), menu, theme);
+        }
+    }
+}
+
+/// Render an interactive program's emulated screen in the terminal area
+pub fn render_pty(f: &mut Frame, area: Rect, session: &PtySession, theme: &Theme) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(format!(" {} ", session.command));
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    let screen = session.screen();
+    let (rows, cols) = screen.size();
+    let mut lines = Vec::with_capacity(rows as usize);
+
+    for row in 0..rows.min(inner_area.height) {
+        let mut spans = Vec::new();
+        for col in 0..cols.min(inner_area.width) {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+            if cell.is_wide_continuation() {
+                continue;
+            }
+
+            let contents = if cell.has_contents() {
+                cell.contents()
+            } else {
+                " ".to_string()
+            };
+            spans.push(Span::styled(contents, cell_style(cell)));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    f.render_widget(Paragraph::new(lines), inner_area);
+
+    if !screen.hide_cursor() {
+        let (row, col) = screen.cursor_position();
+        if row < inner_area.height && col < inner_area.width {
+            f.set_cursor(inner_area.x + col, inner_area.y + row);
+        }
+    }
+}
+
+/// Convert a vt100 cell's attributes into a ratatui style
+fn cell_style(cell: &vt100::Cell) -> Style {
+    let mut style = Style::default()
+        .fg(vt100_color(cell.fgcolor()))
+        .bg(vt100_color(cell.bgcolor()));
+    if cell.bold() {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    if cell.italic() {
+        style = style.add_modifier(Modifier::ITALIC);
+    }
+    if cell.underline() {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+    if cell.inverse() {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    style
+}
+
+/// Map a vt100 color onto the equivalent ratatui color
+fn vt100_color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(i) => Color::Indexed(i),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
     }
 }
 
 /// Render a status bar at the bottom of the terminal
 #[allow(dead_code)]
 pub fn render_status_bar(f: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.theme;
     let mode_text = match app.mode {
         crate::app::AppMode::Normal => "NORMAL",
         crate::app::AppMode::NavigationList => "NAV",
         crate::app::AppMode::ShortcutSelection => "GOTO",
+        crate::app::AppMode::Pty => "PTY",
+        crate::app::AppMode::Timeline => "TIMELINE",
+        crate::app::AppMode::ConfirmScript => "CONFIRM",
+        crate::app::AppMode::DirDiff => "DIFF",
+        crate::app::AppMode::Note => "NOTE",
+        crate::app::AppMode::Heatmap => "HEATMAP",
+        crate::app::AppMode::ThemeGallery => "THEMES",
+        crate::app::AppMode::Conflicts => "CONFLICTS",
+        crate::app::AppMode::GitOverview => "BRANCHES",
+        crate::app::AppMode::GitStatus => "GIT",
+        crate::app::AppMode::BranchSwitch => "BRANCH",
+        crate::app::AppMode::HexView => "HEX",
+        crate::app::AppMode::Env => "ENV",
+        crate::app::AppMode::Copy => "COPY",
+        crate::app::AppMode::Tests => "TESTS",
+        crate::app::AppMode::Search => "SEARCH",
     };
 
-    let status = Line::from(vec![
+    let mut spans = vec![
         Span::styled(
             format!(" {} ", mode_text),
-            Style::default().fg(Color::Black).bg(Palette::BORDER_ACTIVE),
+            Style::default().fg(Color::Black).bg(theme.border_active),
         ),
         Span::raw(" "),
-        Span::styled(
-            app.current_dir.display().to_string(),
-            Style::default().fg(Palette::TEXT_MUTED),
-        ),
-    ]);
+    ];
+    // Nothing typed into this session will survive it
+    if app.in_memory {
+        spans.push(Span::styled(
+            " IN-MEMORY: not saving ",
+            Style::default().fg(Color::Black).bg(theme.block_warning),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::styled(
+        app.current_dir.display().to_string(),
+        Style::default().fg(theme.text_muted),
+    ));
+    let pieces = app.status_segment.pieces();
+    if app.config.status.status_bar && !pieces.is_empty() {
+        spans.push(Span::styled(
+            "  \u{2502}  ",
+            Style::default().fg(theme.border_default),
+        ));
+        spans.extend(crate::segment::spans(pieces, theme));
+    }
+    let status = Line::from(spans);
 
     let paragraph = Paragraph::new(status);
     f.render_widget(paragraph, area);
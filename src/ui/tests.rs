@@ -0,0 +1,165 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::navigator::{entry_style, render_key_hints};
+use crate::ansi;
+use crate::blocks::format_duration;
+use crate::runner::TestRunner;
+use crate::theme::Theme;
+
+/// Render the test view: the run's status and counts, then the failing
+/// tests (with the output of the expanded ones), or the output itself
+/// while there are none to list
+pub fn render_tests(f: &mut Frame, area: Rect, tests: &mut TestRunner, theme: &Theme) {
+    f.render_widget(Clear, area);
+
+    let watching = if tests.is_watching() {
+        " (watching)"
+    } else {
+        ""
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(format!(" {}{watching} ", tests.project.command));
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height < 3 {
+        return;
+    }
+
+    f.render_widget(
+        Paragraph::new(status_line(tests, theme)),
+        Rect {
+            height: 1,
+            ..inner_area
+        },
+    );
+
+    let body_height = inner_area.height as usize - 2;
+    let mut scroll = tests.scroll;
+    let failures = tests.failures();
+    let lines: Vec<Line> = if failures.is_empty() {
+        // The output's tail, following a run as it goes
+        let start = tests.output.len().saturating_sub(body_height);
+        tests.output[start..]
+            .iter()
+            .map(|line| Line::from(ansi::parse_line(line)))
+            .collect()
+    } else {
+        let mut lines = Vec::new();
+        let mut selected_row = 0;
+        for (index, failure) in failures.iter().enumerate() {
+            let selected = index == tests.selected;
+            let expanded = tests.expanded.contains(&failure.name);
+            if selected {
+                selected_row = lines.len();
+            }
+            let marker = if expanded { "\u{25be}" } else { "\u{25b8}" };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{marker} "),
+                    Style::default().fg(theme.block_failure),
+                ),
+                Span::styled(failure.name.clone(), entry_style(theme, selected)),
+            ]));
+            if expanded {
+                lines.extend(failure.output.iter().map(|line| {
+                    let mut spans = vec![Span::raw("    ")];
+                    spans.extend(ansi::parse_line(line));
+                    Line::from(spans)
+                }));
+            }
+        }
+        // Keep the selected failure's line in view
+        if selected_row < scroll {
+            scroll = selected_row;
+        } else if selected_row >= scroll + body_height {
+            scroll = selected_row + 1 - body_height;
+        }
+        lines.into_iter().skip(scroll).take(body_height).collect()
+    };
+    f.render_widget(
+        Paragraph::new(lines),
+        Rect {
+            y: inner_area.y + 1,
+            height: body_height as u16,
+            ..inner_area
+        },
+    );
+    tests.scroll = scroll;
+
+    let hints_area = Rect {
+        y: inner_area.y + inner_area.height - 1,
+        height: 1,
+        ..inner_area
+    };
+    render_key_hints(
+        f,
+        hints_area,
+        &[&[
+            ("\u{2191}\u{2193}", "select"),
+            ("Enter", "expand"),
+            ("r", "run"),
+            ("w", "watch"),
+            ("Esc", "close"),
+        ]],
+        theme,
+    );
+}
+
+/// `running 3.2s`, or the last run's counts and time
+fn status_line(tests: &TestRunner, theme: &Theme) -> Line<'static> {
+    let muted = Style::default().fg(theme.text_muted);
+    let mut spans = Vec::new();
+    if let Some(last) = &tests.last {
+        let report = &last.report;
+        let (icon, color) = if last.code == 0 {
+            ("\u{2714}", theme.block_success)
+        } else {
+            ("\u{2718}", theme.block_failure)
+        };
+        spans.push(Span::styled(
+            format!("{icon} "),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+        if report.counted || !report.failures.is_empty() {
+            spans.push(Span::styled(
+                format!("{} passed", report.passed),
+                Style::default().fg(theme.block_success),
+            ));
+            spans.push(Span::styled(", ", muted));
+            spans.push(Span::styled(
+                format!("{} failed", report.failed),
+                Style::default().fg(theme.block_failure),
+            ));
+            if report.ignored > 0 {
+                spans.push(Span::styled(format!(", {} ignored", report.ignored), muted));
+            }
+        } else {
+            spans.push(Span::raw(format!("exit {}", last.code)));
+        }
+        spans.push(Span::styled(
+            format!(" in {}", format_duration(last.duration)),
+            muted,
+        ));
+    }
+    if let Some(elapsed) = tests.elapsed() {
+        if !spans.is_empty() {
+            spans.push(Span::styled("  \u{b7}  ", muted));
+        }
+        spans.push(Span::styled(
+            format!("running {}", format_duration(elapsed)),
+            Style::default().fg(theme.block_warning),
+        ));
+    }
+    Line::from(spans)
+}
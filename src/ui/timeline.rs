@@ -0,0 +1,134 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::blocks::{CommandBlock, Timeline};
+use crate::theme::Theme;
+
+/// Render the block timeline overlay on top of the terminal area
+pub fn render_timeline(
+    f: &mut Frame,
+    area: Rect,
+    blocks: &[CommandBlock],
+    timeline: &Timeline,
+    theme: &Theme,
+) {
+    let popup = centered_rect(area, 80, 70);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(Style::default().fg(theme.border_active))
+        .title(" Timeline ");
+
+    let inner_area = block.inner(popup);
+    f.render_widget(block, popup);
+
+    if inner_area.height < 4 {
+        return;
+    }
+
+    let hint_style = Style::default().fg(theme.text_muted);
+    let key_style = Style::default().fg(theme.nav_key_hint);
+
+    // Filter line
+    let filter_area = Rect {
+        height: 1,
+        ..inner_area
+    };
+    let filter = Paragraph::new(Line::from(vec![
+        Span::styled("/ ", key_style),
+        Span::styled(
+            timeline.filter.clone(),
+            Style::default().fg(theme.text_normal),
+        ),
+    ]));
+    f.render_widget(filter, filter_area);
+
+    // Block list, keeping the selection in view
+    let list_height = inner_area.height.saturating_sub(3) as usize;
+    let matches = timeline.matches(blocks);
+    let scroll = (timeline.selected_index + 1).saturating_sub(list_height);
+
+    let items: Vec<ListItem> = matches
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(list_height)
+        .map(|(row, &index)| {
+            let block = &blocks[index];
+            let is_selected = row == timeline.selected_index;
+
+            let (marker, marker_color) = match block.exit_code {
+                Some(0) => ("\u{2713}   ".to_string(), theme.block_success),
+                Some(code) => (format!("\u{2717}{code:<3}"), theme.block_failure),
+                None => ("\u{2026}   ".to_string(), theme.text_muted),
+            };
+
+            let mut command_style = Style::default().fg(theme.text_normal);
+            let mut time_style = Style::default().fg(theme.sidebar_time);
+            let mut marker_style = Style::default().fg(marker_color);
+            if is_selected {
+                command_style = command_style
+                    .fg(theme.nav_selected_fg)
+                    .bg(theme.nav_selected_bg)
+                    .add_modifier(Modifier::BOLD);
+                time_style = time_style.bg(theme.nav_selected_bg);
+                marker_style = marker_style.bg(theme.nav_selected_bg);
+            }
+
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", block.time_label()), time_style),
+                Span::styled(format!("{marker} "), marker_style),
+                Span::styled(block.command.clone(), command_style),
+            ]))
+        })
+        .collect();
+
+    let list_area = Rect {
+        y: inner_area.y + 1,
+        height: list_height as u16,
+        ..inner_area
+    };
+    f.render_widget(List::new(items), list_area);
+
+    // Precise timing of the selected block
+    if let Some(index) = timeline.selected_block(blocks) {
+        let detail_area = Rect {
+            y: inner_area.y + inner_area.height - 2,
+            height: 1,
+            ..inner_area
+        };
+        let detail = Paragraph::new(Span::styled(
+            blocks[index].timing.details(),
+            Style::default().fg(theme.prompt_duration),
+        ));
+        f.render_widget(detail, detail_area);
+    }
+
+    // Footer with key hints
+    let footer_area = Rect {
+        y: inner_area.y + inner_area.height - 1,
+        height: 1,
+        ..inner_area
+    };
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("\u{2191}\u{2193}", key_style),
+        Span::styled(" move  ", hint_style),
+        Span::styled("type", key_style),
+        Span::styled(" filter  ", hint_style),
+        Span::styled("Enter", key_style),
+        Span::styled(" jump  ", hint_style),
+        Span::styled("^R", key_style),
+        Span::styled(" rerun  ", hint_style),
+        Span::styled("Esc", key_style),
+        Span::styled(" close", hint_style),
+    ]));
+    f.render_widget(footer, footer_area);
+}
@@ -0,0 +1,45 @@
+//! Driving `App` without a terminal, the way an embedding would
+
+use std::fs;
+use std::path::PathBuf;
+
+use jerm::app::{App, AppMode};
+use jerm::paths::DATA_DIR_VAR;
+
+/// A fresh data directory, so the test never reads the user's own files
+fn data_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jerm-embed-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    std::env::set_var(DATA_DIR_VAR, &dir);
+    dir
+}
+
+#[test]
+fn test_goto_jumps_to_shortcut() {
+    let dir = data_dir();
+    let project = dir.join("project");
+    fs::create_dir(&project).unwrap();
+
+    let mut app = App::new();
+    app.change_dir(dir.clone());
+    app.shortcuts
+        .add_shortcut(project.clone(), Some("proj".to_string()));
+
+    app.enter_goto_mode();
+    assert_eq!(app.mode, AppMode::ShortcutSelection);
+    for c in "proj".chars() {
+        app.goto_push_filter(c);
+    }
+    app.confirm_goto();
+    assert_eq!(app.mode, AppMode::Normal);
+    assert_eq!(app.current_dir, project);
+    assert!(app.output.last().unwrap().ends_with("project"));
+
+    // Typing only edits the input line; running it is up to the front end
+    app.insert_str("ls -la");
+    app.cursor_home();
+    app.insert_char('#');
+    assert_eq!(app.input, "#ls -la");
+    fs::remove_dir_all(&dir).unwrap();
+}